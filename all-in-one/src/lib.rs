@@ -1,76 +1,112 @@
 #![allow(unused_imports, unused_variables)]
 use anyhow::{bail, Result};
+use async_trait::async_trait;
 use serde_json::Value;
 use tokio::sync::mpsc::Sender;
+use lib::composite::CompositeTranslator;
+use lib::fallback::FallbackTranslator;
+use lib::pool::PoolTranslator;
 pub use lib::*;
 
-pub async fn translate(name: String, config: Value, task: TranslateTask) -> Result<TranslateResult> {
-    match name.as_str() {
+pub async fn create_translator(name: &str, config: Value) -> Result<BoxedTranslator> {
+    match name {
         #[cfg(feature = "plugin-openai")]
         "openai" => {
             use plugin_openai::translator::OpenAITranslator;
-            let trans = OpenAITranslator::new(config).await?;
-            trans.translate(task).await
+            Ok(Box::new(OpenAITranslator::new(config).await?))
         },
         #[cfg(feature = "plugin-hunyuan")]
         "hunyuan" => {
             use plugin_hunyuan::translator::HunyuanTranslator;
-            let trans = HunyuanTranslator::new(config).await?;
-            trans.translate(task).await
+            Ok(Box::new(HunyuanTranslator::new(config).await?))
         },
         #[cfg(feature = "plugin-qwen")]
         "qwen" => {
             use plugin_qwen::translator::QwenMtTranslator;
-            let trans = QwenMtTranslator::new(config).await?;
-            trans.translate(task).await
+            Ok(Box::new(QwenMtTranslator::new(config).await?))
         },
         #[cfg(feature = "plugin-youdao-llm")]
         "youdao_llm" => {
             use plugin_youdao_llm::translator::YoudaoLLMTranslator;
-            let trans = YoudaoLLMTranslator::new(config).await?;
-            trans.translate(task).await
+            Ok(Box::new(YoudaoLLMTranslator::new(config).await?))
         },
         #[cfg(feature = "plugin-baidu-fanyi")]
         "baidu_fanyi" => {
             use plugin_baidu_fanyi::translator::BaiduFanyiTranslator;
-            let trans = BaiduFanyiTranslator::new(config).await?;
-            trans.translate(task).await
+            Ok(Box::new(BaiduFanyiTranslator::new(config).await?))
+        },
+        #[cfg(feature = "plugin-dryrun")]
+        "dryrun" => {
+            use plugin_dryrun::translator::DryRunTranslator;
+            Ok(Box::new(DryRunTranslator::new(config).await?))
+        },
+        "fallback" => {
+            Ok(Box::new(FallbackTranslator::from_config(&config, &BuiltinFactory).await?))
+        },
+        "pool" => {
+            Ok(Box::new(PoolTranslator::from_config(&config, &BuiltinFactory).await?))
+        },
+        "composite" => {
+            Ok(Box::new(CompositeTranslator::from_config(&config, &BuiltinFactory).await?))
         },
         _ => bail!("Translator not found"),
     }
 }
 
-pub async fn translate_stream(name: String, config: Value, task: TranslateTask, sender: Sender<TranslateStreamChunk>) -> Result<()> {
-    match name.as_str() {
+/// 内置翻译器配置的 JSON Schema
+pub fn config_schema(name: &str) -> Option<Value> {
+    match name {
         #[cfg(feature = "plugin-openai")]
-        "openai" => {
-            let trans = plugin_openai::translator::OpenAITranslator::new(config).await?;
-            trans.translate_stream(task, sender).await
-        },
+        "openai" => plugin_openai::translator::OpenAITranslator::config_schema(),
         #[cfg(feature = "plugin-hunyuan")]
-        "hunyuan" => {
-            use plugin_hunyuan::translator::HunyuanTranslator;
-            let trans = HunyuanTranslator::new(config).await?;
-            trans.translate_stream(task, sender).await
-        },
+        "hunyuan" => plugin_hunyuan::translator::HunyuanTranslator::config_schema(),
         #[cfg(feature = "plugin-qwen")]
-        "qwen" => {
-            use plugin_qwen::translator::QwenMtTranslator;
-            let trans = QwenMtTranslator::new(config).await?;
-            trans.translate_stream(task, sender).await
-        },
+        "qwen" => plugin_qwen::translator::QwenMtTranslator::config_schema(),
         #[cfg(feature = "plugin-youdao-llm")]
-        "youdao_llm" => {
-            use plugin_youdao_llm::translator::YoudaoLLMTranslator;
-            let trans = YoudaoLLMTranslator::new(config).await?;
-            trans.translate_stream(task, sender).await
-        },
+        "youdao_llm" => plugin_youdao_llm::translator::YoudaoLLMTranslator::config_schema(),
         #[cfg(feature = "plugin-baidu-fanyi")]
-        "baidu_fanyi" => {
-            use plugin_baidu_fanyi::translator::BaiduFanyiTranslator;
-            let trans = BaiduFanyiTranslator::new(config).await?;
-            trans.translate_stream(task, sender).await
-        },
+        "baidu_fanyi" => plugin_baidu_fanyi::translator::BaiduFanyiTranslator::config_schema(),
+        #[cfg(feature = "plugin-dryrun")]
+        "dryrun" => plugin_dryrun::translator::DryRunTranslator::config_schema(),
+        _ => None,
+    }
+}
+
+/// 检查内置翻译器的配置
+pub fn validate_config(name: &str, config: &Value) -> Result<Vec<validate::ConfigIssue>> {
+    match name {
+        #[cfg(feature = "plugin-openai")]
+        "openai" => plugin_openai::translator::OpenAITranslator::validate_config(config),
+        #[cfg(feature = "plugin-hunyuan")]
+        "hunyuan" => plugin_hunyuan::translator::HunyuanTranslator::validate_config(config),
+        #[cfg(feature = "plugin-qwen")]
+        "qwen" => plugin_qwen::translator::QwenMtTranslator::validate_config(config),
+        #[cfg(feature = "plugin-youdao-llm")]
+        "youdao_llm" => plugin_youdao_llm::translator::YoudaoLLMTranslator::validate_config(config),
+        #[cfg(feature = "plugin-baidu-fanyi")]
+        "baidu_fanyi" => plugin_baidu_fanyi::translator::BaiduFanyiTranslator::validate_config(config),
+        #[cfg(feature = "plugin-dryrun")]
+        "dryrun" => plugin_dryrun::translator::DryRunTranslator::validate_config(config),
         _ => bail!("Translator not found"),
     }
 }
+
+/// 创建内置翻译器，供组合翻译器按名称引用
+pub struct BuiltinFactory;
+
+#[async_trait]
+impl TranslatorFactory for BuiltinFactory {
+    async fn create(&self, name: &str, config: Value) -> Result<BoxedTranslator> {
+        create_translator(name, config).await
+    }
+}
+
+pub async fn translate(name: String, config: Value, task: TranslateTask) -> Result<TranslateResult> {
+    let trans = create_translator(name.as_str(), config).await?;
+    trans.translate(task).await
+}
+
+pub async fn translate_stream(name: String, config: Value, task: TranslateTask, sender: Sender<TranslateStreamChunk>) -> Result<()> {
+    let trans = create_translator(name.as_str(), config).await?;
+    trans.translate_stream(task, sender).await
+}
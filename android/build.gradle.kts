@@ -0,0 +1,25 @@
+// 示例：在 Android 应用中使用 xtranslator-android。
+// 先用 cargo-ndk 构建：cargo ndk -t arm64-v8a -t x86_64 -o android/src/main/jniLibs build -p xtranslator-android --release
+// 插件动态库（如 libplugin_openai.so）放在同一 jniLibs 目录，运行时从 nativeLibraryDir 加载
+plugins {
+    id("com.android.library")
+    kotlin("android")
+}
+
+android {
+    namespace = "io.github.xtranslator"
+    compileSdk = 34
+
+    defaultConfig {
+        minSdk = 24
+    }
+
+    packaging {
+        // 插件由 dlopen 按路径加载，需要解压到 nativeLibraryDir
+        jniLibs.useLegacyPackaging = true
+    }
+}
+
+dependencies {
+    implementation("org.jetbrains.kotlinx:kotlinx-coroutines-android:1.8.1")
+}
@@ -0,0 +1,326 @@
+use anyhow::{bail, Result};
+use jni::objects::{GlobalRef, JClass, JObject, JString, JValue, JValueOwned};
+use jni::sys::{jlong, jstring};
+use jni::{JNIEnv, JavaVM};
+use lib::ffi::catch_panic;
+use lib::ffi_proxy::PluginRegistry;
+use lib::{DynTranslator, TranslateStreamChunk, TranslateTask, TranslatorFactory};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc::channel;
+use tokio::task::AbortHandle;
+
+/// android/src/main/kotlin 中 `XTranslator` 的 native 方法。
+/// 句柄指向装箱的 `Translator`，翻译在共用的运行时中执行，结果通过 Kotlin 回调对象传回
+pub type Translator = Arc<dyn DynTranslator>;
+
+static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| Runtime::new().unwrap());
+
+/// 进行中的请求，供 `nativeCancel` 按编号取消
+static REQUESTS: LazyLock<Mutex<HashMap<jlong, AbortHandle>>> = LazyLock::new(Default::default);
+static NEXT_REQUEST: AtomicI64 = AtomicI64::new(1);
+
+const EXCEPTION: &str = "io/github/xtranslator/XTranslatorException";
+
+fn throw(env: &mut JNIEnv, err: anyhow::Error) {
+    let _ = env.throw_new(EXCEPTION, format!("{:#}", err));
+}
+
+/// 从插件目录按名称创建翻译器，插件的动态库与应用的 native 库放在同一目录
+pub async fn create(plugin_dir: String, name: &str, config: &str) -> Result<Translator> {
+    let config: Value = serde_json::from_str(config)?;
+    let registry = PluginRegistry::scan(plugin_dir)?;
+    Ok(Arc::from(registry.create(name, config).await?))
+}
+
+/// `nativeLanguages` 返回的 JSON
+pub fn languages(translator: &dyn DynTranslator) -> Result<String> {
+    Ok(json!({
+        "input": translator.get_supported_input_languages()?,
+        "output": translator.get_supported_output_languages()?,
+        "pairs": translator.get_supported_pairs()?,
+    })
+    .to_string())
+}
+
+/// 在运行时中执行请求，返回可传给 `cancel` 的编号。请求结束后自动移除
+pub fn spawn_request(future: impl Future<Output = ()> + Send + 'static) -> jlong {
+    // 持有锁直到登记完成，已结束的请求不会留在表中
+    let mut requests = REQUESTS.lock().unwrap();
+    let id = NEXT_REQUEST.fetch_add(1, Ordering::Relaxed);
+    let task = RUNTIME.spawn(async move {
+        future.await;
+        REQUESTS.lock().unwrap().remove(&id);
+    });
+    requests.insert(id, task.abort_handle());
+    id
+}
+
+/// 取消请求，之后不会再调用它的回调。未知或已结束的请求忽略
+pub fn cancel(request: jlong) {
+    if let Some(task) = REQUESTS.lock().unwrap().remove(&request) {
+        task.abort();
+    }
+}
+
+/// Kotlin 回调对象，在运行时的工作线程中调用
+struct Callback {
+    vm: JavaVM,
+    object: GlobalRef,
+}
+
+impl Callback {
+    fn new(env: &JNIEnv, object: &JObject) -> Result<Self> {
+        Ok(Callback {
+            vm: env.get_java_vm()?,
+            object: env.new_global_ref(object)?,
+        })
+    }
+
+    /// 以一个字符串参数调用方法，回调抛出的异常打印后清除，不影响工作线程。
+    /// 工作线程作为守护线程附加，不会阻止虚拟机退出
+    fn call<T>(
+        &self,
+        method: &str,
+        sig: &str,
+        arg: Option<&str>,
+        read: impl FnOnce(JValueOwned) -> jni::errors::Result<T>,
+    ) -> Result<T> {
+        let mut env = self.vm.attach_current_thread_as_daemon()?;
+        let arg = match arg {
+            Some(arg) => Some(env.new_string(arg)?),
+            None => None,
+        };
+        let args: Vec<JValue> = arg.iter().map(|arg| JValue::Object(arg)).collect();
+        let result = env.call_method(&self.object, method, sig, &args);
+        if env.exception_check()? {
+            env.exception_describe()?;
+            env.exception_clear()?;
+        }
+        // 附加的线程不会返回 Java，局部引用需要手动释放
+        if let Some(arg) = arg {
+            env.delete_local_ref(arg)?;
+        }
+        Ok(read(result?)?)
+    }
+
+    fn error(&self, err: anyhow::Error) {
+        let _ = self.call("onError", "(Ljava/lang/String;)V", Some(&format!("{:#}", err)), |v| v.v());
+    }
+}
+
+fn get_string(env: &mut JNIEnv, s: &JString) -> Result<String> {
+    Ok(env.get_string(s)?.into())
+}
+
+/// # Safety
+/// `handle` 必须为 `nativeCreate` 返回且尚未 `nativeDestroy` 的句柄
+unsafe fn translator(handle: jlong) -> Result<Translator> {
+    if handle == 0 {
+        bail!("translator closed");
+    }
+    Ok((*(handle as *const Translator)).clone())
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_github_xtranslator_XTranslator_nativeCreate(
+    mut env: JNIEnv,
+    _class: JClass,
+    plugin_dir: JString,
+    name: JString,
+    config: JString,
+) -> jlong {
+    catch_panic(0, || {
+        let result = (|| {
+            let plugin_dir = get_string(&mut env, &plugin_dir)?;
+            let name = get_string(&mut env, &name)?;
+            let config = get_string(&mut env, &config)?;
+            RUNTIME.block_on(create(plugin_dir, &name, &config))
+        })();
+
+        match result {
+            Ok(translator) => Box::into_raw(Box::new(translator)) as jlong,
+            Err(e) => {
+                throw(&mut env, e);
+                0
+            }
+        }
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_github_xtranslator_XTranslator_nativeDestroy(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) {
+    catch_panic((), || {
+        if handle != 0 {
+            // 进行中的请求各自持有一个引用
+            drop(unsafe { Box::from_raw(handle as *mut Translator) });
+        }
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_github_xtranslator_XTranslator_nativeLanguages(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) -> jstring {
+    catch_panic(std::ptr::null_mut(), || {
+        let result = unsafe { translator(handle) }
+            .and_then(|translator| languages(translator.as_ref()))
+            .and_then(|json| Ok(env.new_string(json)?));
+
+        match result {
+            Ok(json) => json.into_raw(),
+            Err(e) => {
+                throw(&mut env, e);
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// 读取句柄、任务与回调，失败时抛出 `XTranslatorException`
+fn request(env: &mut JNIEnv, handle: jlong, task: &JString, callback: &JObject) -> Result<(Translator, TranslateTask, Callback)> {
+    let translator = unsafe { translator(handle) }?;
+    let task: TranslateTask = serde_json::from_str(&get_string(env, task)?)?;
+    Ok((translator, task, Callback::new(env, callback)?))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_github_xtranslator_XTranslator_nativeTranslate(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    task: JString,
+    callback: JObject,
+) -> jlong {
+    catch_panic(0, || {
+        let (translator, task, callback) = match request(&mut env, handle, &task, &callback) {
+            Ok(request) => request,
+            Err(e) => {
+                throw(&mut env, e);
+                return 0;
+            }
+        };
+
+        spawn_request(async move {
+            let result = translator
+                .translate(task)
+                .await
+                .and_then(|result| Ok(serde_json::to_string(&result)?));
+            match result {
+                Ok(json) => {
+                    let _ = callback.call("onResult", "(Ljava/lang/String;)V", Some(&json), |v| v.v());
+                }
+                Err(e) => callback.error(e),
+            }
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_github_xtranslator_XTranslator_nativeTranslateStream(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    task: JString,
+    callback: JObject,
+) -> jlong {
+    catch_panic(0, || {
+        let (translator, task, callback) = match request(&mut env, handle, &task, &callback) {
+            Ok(request) => request,
+            Err(e) => {
+                throw(&mut env, e);
+                return 0;
+            }
+        };
+
+        spawn_request(async move {
+            let (sender, mut receiver) = channel(32);
+            let stream = tokio::spawn(async move { translator.translate_stream(task, sender).await });
+
+            while let Some(chunk) = receiver.recv().await {
+                let TranslateStreamChunk::Delta(result) = chunk else {
+                    continue;
+                };
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                let more = callback.call("onChunk", "(Ljava/lang/String;)Z", Some(&json), |v| v.z());
+                if !more.unwrap_or(false) {
+                    // 关闭接收端，插件发送失败后尽快结束
+                    drop(receiver);
+                    let _ = stream.await;
+                    return;
+                }
+            }
+
+            match stream.await {
+                Ok(Ok(())) => {
+                    let _ = callback.call("onEnd", "()V", None, |v| v.v());
+                }
+                Ok(Err(e)) => callback.error(e),
+                Err(e) => callback.error(e.into()),
+            }
+        })
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_io_github_xtranslator_XTranslator_nativeCancel(
+    _env: JNIEnv,
+    _class: JClass,
+    request: jlong,
+) {
+    catch_panic((), || cancel(request))
+}
+
+#[test]
+fn test_languages() -> Result<()> {
+    let translator = plugin_dryrun::translator::DryRunTranslator::default();
+    let value: Value = serde_json::from_str(&languages(&translator)?)?;
+    assert_eq!(value["input"], json!(["*"]));
+    assert_eq!(value["pairs"], json!([]));
+
+    Ok(())
+}
+
+#[test]
+fn test_cancel_request() {
+    let (started, mut wait) = tokio::sync::oneshot::channel();
+    let (finished, done) = std::sync::mpsc::channel();
+    let request = spawn_request(async move {
+        let _ = started.send(());
+        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        let _ = finished.send(());
+    });
+    while wait.try_recv().is_err() {
+        std::thread::yield_now();
+    }
+    assert!(REQUESTS.lock().unwrap().contains_key(&request));
+
+    cancel(request);
+    assert!(!REQUESTS.lock().unwrap().contains_key(&request));
+    // 被取消的请求不再运行，`finished` 随任务释放
+    assert!(done.recv_timeout(std::time::Duration::from_secs(1)).is_err());
+
+    // 结束的请求自动移除
+    let (finished, done) = std::sync::mpsc::channel();
+    let request = spawn_request(async move {
+        let _ = finished.send(());
+    });
+    done.recv().unwrap();
+    for _ in 0..100 {
+        if !REQUESTS.lock().unwrap().contains_key(&request) {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    panic!("finished request was not removed");
+}
@@ -0,0 +1,77 @@
+// xtranslator-android 的 Kotlin 接口。native 方法由 libxtranslator_android.so 实现，
+// 任务、结果与配置为 JSON 字符串，字段与 lib 中的 `TranslateTask`、`TranslateResult` 相同
+package io.github.xtranslator
+
+import kotlinx.coroutines.channels.awaitClose
+import kotlinx.coroutines.flow.Flow
+import kotlinx.coroutines.flow.callbackFlow
+import kotlinx.coroutines.suspendCancellableCoroutine
+import kotlin.coroutines.resume
+import kotlin.coroutines.resumeWithException
+
+/** native 层的回调，在 Rust 的工作线程中调用 */
+interface NativeCallback {
+    fun onResult(json: String)
+    fun onError(message: String)
+}
+
+/** 流式翻译的回调，`onChunk` 返回 false 表示不再接收 */
+interface NativeStreamCallback {
+    fun onChunk(json: String): Boolean
+    fun onEnd()
+    fun onError(message: String)
+}
+
+class XTranslatorException(message: String) : Exception(message)
+
+class XTranslator private constructor(private var handle: Long) : AutoCloseable {
+    companion object {
+        init {
+            System.loadLibrary("xtranslator_android")
+        }
+
+        /** 从插件目录（如应用的 `nativeLibraryDir`）按名称创建翻译器 */
+        fun create(pluginDir: String, name: String, configJson: String): XTranslator =
+            XTranslator(nativeCreate(pluginDir, name, configJson))
+
+        @JvmStatic private external fun nativeCreate(pluginDir: String, name: String, configJson: String): Long
+        @JvmStatic private external fun nativeDestroy(handle: Long)
+        @JvmStatic private external fun nativeLanguages(handle: Long): String
+        @JvmStatic private external fun nativeTranslate(handle: Long, taskJson: String, callback: NativeCallback): Long
+        @JvmStatic private external fun nativeTranslateStream(handle: Long, taskJson: String, callback: NativeStreamCallback): Long
+        @JvmStatic private external fun nativeCancel(request: Long)
+    }
+
+    /** JSON 格式的输入、输出语言与语言对 */
+    fun languages(): String = nativeLanguages(handle)
+
+    /** 返回 JSON 格式的 `TranslateResult`，协程取消时取消翻译 */
+    suspend fun translate(taskJson: String): String = suspendCancellableCoroutine { cont ->
+        val request = nativeTranslate(handle, taskJson, object : NativeCallback {
+            override fun onResult(json: String) = cont.resume(json)
+            override fun onError(message: String) = cont.resumeWithException(XTranslatorException(message))
+        })
+        cont.invokeOnCancellation { nativeCancel(request) }
+    }
+
+    /** 逐段发出 JSON 格式的 `TranslateResult` 增量，收集方停止时取消翻译 */
+    fun translateStream(taskJson: String): Flow<String> = callbackFlow {
+        val request = nativeTranslateStream(handle, taskJson, object : NativeStreamCallback {
+            override fun onChunk(json: String): Boolean = trySend(json).isSuccess
+            override fun onEnd() {
+                close()
+            }
+            override fun onError(message: String) {
+                close(XTranslatorException(message))
+            }
+        })
+        awaitClose { nativeCancel(request) }
+    }
+
+    override fun close() {
+        if (handle != 0L) {
+            nativeDestroy(handle)
+            handle = 0L
+        }
+    }
+}
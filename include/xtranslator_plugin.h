@@ -0,0 +1,550 @@
+/* xtranslator 插件接口，由 `cargo xtask header` 生成，不要手动修改 */
+
+#ifndef XTRANSLATOR_PLUGIN_H
+#define XTRANSLATOR_PLUGIN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * 插件与宿主之间的 FFI 版本，导出函数的签名或 `#[repr(C)]` 结构体的布局不兼容地变化时加一。
+ * 没有导出 `get_abi_version` 的旧版插件视为版本 1
+ */
+#define ABI_VERSION 1
+
+/**
+ * `call_translate_batch` 中同时进行的翻译数
+ */
+#define BATCH_PARALLEL 8
+
+typedef enum {
+  TranslateStreamChunkTag_Start,
+  TranslateStreamChunkTag_Delta,
+  TranslateStreamChunkTag_End,
+} TranslateStreamChunkTag;
+
+typedef uint32_t (*GetAbiVersion)(void);
+
+/**
+ * 返回 JSON 格式的 `BTreeMap<String, StructLayout>`，见 `abi_layout`
+ */
+typedef char *(*GetAbiLayout)(void);
+
+/**
+ * 插件返回的字符串都要交回插件释放，宿主与插件可能使用不同的分配器
+ */
+typedef void (*FreeString)(char*);
+
+typedef struct {
+  void *ptr;
+  /**
+   * 错误信息，包含可识别的错误类别时为 JSON 格式的 `FfiError`
+   */
+  char *err;
+} FfiResult_c_void;
+
+/**
+ * 释放结果及其错误信息，`ptr` 的所有权已交给调用方
+ */
+typedef void (*FreeFfiResult)(FfiResult_c_void*);
+
+typedef struct {
+  int8_t *ptr;
+  /**
+   * 错误信息，包含可识别的错误类别时为 JSON 格式的 `FfiError`
+   */
+  char *err;
+} FfiResult_i8;
+
+/**
+ * 释放 `FfiResult<i8>`，包括其中的状态值
+ */
+typedef void (*FreeFfiStatus)(FfiResult_i8*);
+
+typedef struct {
+  char *reasoning;
+  char *content;
+} TranslateResultFFI;
+
+typedef void (*FreeTranslateResult)(TranslateResultFFI*);
+
+typedef union {
+  TranslateResultFFI *delta;
+  uint8_t _dummy;
+} ChunkData;
+
+typedef struct {
+  TranslateStreamChunkTag tag;
+  ChunkData data;
+} TranslateStreamChunkFFI;
+
+typedef void (*FreeTranslateStreamChunk)(TranslateStreamChunkFFI*);
+
+typedef void (*FreeSupportedLanguages)(const char**, size_t);
+
+typedef char *(*GetPluginName)(void);
+
+/**
+ * 返回 JSON 格式的 `PluginMetadata`
+ */
+typedef char *(*GetPluginMetadata)(void);
+
+/**
+ * 返回 JSON 格式的配置 schema，没有时返回空指针
+ */
+typedef char *(*GetConfigSchema)(void);
+
+/**
+ * 插件中的翻译器名称，使用 `free_supported_languages` 释放
+ */
+typedef FfiResult_i8 *(*GetPluginTranslators)(const char***, size_t*);
+
+/**
+ * 与 `GetConfigSchema` 相同，按名称指定插件中的翻译器
+ */
+typedef char *(*GetNamedConfigSchema)(const char*);
+
+typedef struct {
+  char *ptr;
+  /**
+   * 错误信息，包含可识别的错误类别时为 JSON 格式的 `FfiError`
+   */
+  char *err;
+} FfiResult_c_char;
+
+typedef FfiResult_c_char *(*ValidateNamedConfig)(const char*, const char*);
+
+/**
+ * 结果为 JSON 格式的 `Vec<ConfigIssue>`
+ */
+typedef FfiResult_c_char *(*ValidateConfig)(const char*);
+
+typedef struct {
+  uint8_t _private[0];
+} TranslatorHandle;
+
+typedef struct {
+  TranslatorHandle *ptr;
+  /**
+   * 错误信息，包含可识别的错误类别时为 JSON 格式的 `FfiError`
+   */
+  char *err;
+} FfiResult_TranslatorHandle;
+
+/**
+ * 创建插件中的默认翻译器
+ */
+typedef FfiResult_TranslatorHandle *(*CreateTranslator)(const char*);
+
+typedef FfiResult_TranslatorHandle *(*CreateNamedTranslator)(const char*, const char*);
+
+/**
+ * 翻译器只能由创建它的插件释放。`clone_translator` 之后每个引用释放一次，最后一个引用释放时销毁翻译器，
+ * 销毁前等待进行中的调用结束，超过 `DESTROY_TIMEOUT` 后取消它们
+ */
+typedef void (*DestroyTranslator)(TranslatorHandle*);
+
+/**
+ * 增加一个引用并返回同一个句柄，供多个线程各自持有、各自释放
+ */
+typedef TranslatorHandle *(*CloneTranslator)(TranslatorHandle*);
+
+typedef FfiResult_i8 *(*GetSupportedInputLanguages)(TranslatorHandle*, const char***, size_t*);
+
+typedef FfiResult_i8 *(*IsSupportedInputLanguage)(TranslatorHandle*, const char*);
+
+typedef FfiResult_i8 *(*GetSupportedOutputLanguages)(TranslatorHandle*, const char***, size_t*);
+
+typedef FfiResult_i8 *(*IsSupportedOutputLanguage)(TranslatorHandle*, const char*);
+
+typedef FfiResult_i8 *(*IsSupportedPair)(TranslatorHandle*, const char*, const char*);
+
+/**
+ * 语言对按源语言、目标语言交替展开为一个数组，使用 `free_supported_languages` 释放
+ */
+typedef FfiResult_i8 *(*GetSupportedPairs)(TranslatorHandle*, const char***, size_t*);
+
+/**
+ * 结果为 JSON 格式的 `Capabilities`
+ */
+typedef FfiResult_c_char *(*GetCapabilities)(TranslatorHandle*);
+
+typedef struct {
+  TranslateResultFFI *ptr;
+  /**
+   * 错误信息，包含可识别的错误类别时为 JSON 格式的 `FfiError`
+   */
+  char *err;
+} FfiResult_TranslateResultFFI;
+
+typedef FfiResult_TranslateResultFFI *(*CallTranslate)(TranslatorHandle*, const char*);
+
+/**
+ * 异步翻译的结果与 `call_translate` 的返回值相同，可能在插件的任意线程中调用
+ */
+typedef void (*CompletionCallback)(FfiResult_TranslateResultFFI *result, void *user_data);
+
+/**
+ * 立即返回，翻译在插件的运行时中完成后调用 `CompletionCallback`。回调之前不能释放翻译器
+ */
+typedef FfiResult_i8 *(*CallTranslateAsync)(TranslatorHandle*,
+                                            const char*,
+                                            CompletionCallback,
+                                            void*);
+
+/**
+ * `chunk` 的所有权交给回调，读取后交给插件的 `free_translate_stream_chunk` 释放，
+ * 不能用宿主的分配器释放
+ */
+typedef void (*StreamCallback)(TranslateStreamChunkFFI *chunk, void *cb);
+
+typedef FfiResult_i8 *(*CallTranslateStream)(TranslatorHandle*, const char*, StreamCallback, void*);
+
+/**
+ * 插件内 `StreamControl` 的不透明指针
+ */
+typedef struct {
+  uint8_t _private[0];
+} StreamHandle;
+
+/**
+ * 与 `call_translate_stream` 相同，但可以通过 `cancel_stream` 中止。被取消时结果为 1
+ */
+typedef FfiResult_i8 *(*CallTranslateStreamCancellable)(TranslatorHandle*,
+                                                        const char*,
+                                                        StreamCallback,
+                                                        void*,
+                                                        StreamHandle*);
+
+/**
+ * 参数为 JSON 格式的任务数组，结果为对应的 `BatchResult` 数组
+ */
+typedef FfiResult_c_char *(*CallTranslateBatch)(TranslatorHandle*, const char*);
+
+/**
+ * 以下变体的 JSON 参数为 `(ptr, len)` 形式的 UTF-8，不要求以 NUL 结尾，无效的字节替换为 U+FFFD
+ */
+typedef FfiResult_TranslatorHandle *(*CreateTranslatorUtf8)(const uint8_t*, size_t);
+
+typedef FfiResult_TranslateResultFFI *(*CallTranslateUtf8)(TranslatorHandle*, const uint8_t*, size_t);
+
+typedef FfiResult_i8 *(*CallTranslateStreamUtf8)(TranslatorHandle*,
+                                                 const uint8_t*,
+                                                 size_t,
+                                                 StreamCallback,
+                                                 void*,
+                                                 StreamHandle*);
+
+/**
+ * 语言代码为 `(ptr, len)` 形式的 UTF-8，结果与对应的 C 字符串版本相同
+ */
+typedef FfiResult_i8 *(*IsSupportedInputLanguageUtf8)(TranslatorHandle*, const uint8_t*, size_t);
+
+typedef FfiResult_i8 *(*IsSupportedOutputLanguageUtf8)(TranslatorHandle*, const uint8_t*, size_t);
+
+typedef FfiResult_i8 *(*IsSupportedPairUtf8)(TranslatorHandle*,
+                                             const uint8_t*,
+                                             size_t,
+                                             const uint8_t*,
+                                             size_t);
+
+/**
+ * 插件内 `ResultBuffer` 的不透明指针
+ */
+typedef struct {
+  uint8_t _private[0];
+} ResultArena;
+
+/**
+ * 任务为 `(ptr, len)` 形式的 UTF-8，结果为 JSON 格式的 `TranslateResult`，写入 `arena` 后通过最后两个参数返回其位置，
+ * 不以 NUL 结尾，不需要单独释放
+ */
+typedef FfiResult_i8 *(*CallTranslateInto)(TranslatorHandle*,
+                                           const uint8_t*,
+                                           size_t,
+                                           ResultArena*,
+                                           const uint8_t**,
+                                           size_t*);
+
+/**
+ * UTF-16 变体，供 Windows 与 .NET 宿主使用，`len` 为 u16 个数
+ */
+typedef FfiResult_TranslatorHandle *(*CreateTranslatorUtf16)(const uint16_t*, size_t);
+
+typedef FfiResult_TranslateResultFFI *(*CallTranslateUtf16)(TranslatorHandle*,
+                                                            const uint16_t*,
+                                                            size_t);
+
+typedef FfiResult_i8 *(*CallTranslateStreamUtf16)(TranslatorHandle*,
+                                                  const uint16_t*,
+                                                  size_t,
+                                                  StreamCallback,
+                                                  void*,
+                                                  StreamHandle*);
+
+/**
+ * 拒绝新的调用并等待进行中的调用结束，超过 `timeout_ms` 后取消它们。返回后才能安全地 `destroy_translator`，
+ * 结果为 1 表示有调用被取消
+ */
+typedef FfiResult_i8 *(*ShutdownTranslator)(TranslatorHandle*, uint64_t);
+
+typedef StreamHandle *(*CreateStreamHandle)(void);
+
+/**
+ * 可以在其他线程中调用
+ */
+typedef void (*CancelStream)(StreamHandle*);
+
+/**
+ * 流式翻译返回后才能释放
+ */
+typedef void (*FreeStreamHandle)(StreamHandle*);
+
+typedef ResultArena *(*CreateResultArena)(void);
+
+/**
+ * 释放后之前写入的结果都失效
+ */
+typedef void (*FreeResultArena)(ResultArena*);
+
+/**
+ * 进度回调，参数为 `ProgressPhase`（1 到 4）、已用时间（毫秒）、已发送与已接收的字节数。
+ * 在插件的线程中调用，`call_translate_with_progress` 返回后不再调用
+ */
+typedef void (*ProgressCallback)(uint32_t, uint64_t, uint64_t, uint64_t, void*);
+
+/**
+ * 与 `call_translate` 相同，翻译期间通过 `progress` 报告进度，`progress` 可以为空
+ */
+typedef FfiResult_TranslateResultFFI *(*CallTranslateWithProgress)(TranslatorHandle*,
+                                                                   const char*,
+                                                                   ProgressCallback,
+                                                                   void*);
+
+/**
+ * 插件日志回调，参数为级别（1 error 到 5 trace）、target 与消息。字符串只在回调期间有效
+ */
+typedef void (*LogCallback)(uint32_t, const char*, const char*, void*);
+
+/**
+ * 设置插件日志回调，`max_level` 为 0 时关闭
+ */
+typedef void (*SetLogCallback)(LogCallback, void*, uint32_t);
+
+/* `build_ffi!` 指定了 `prefix` 时，包含本文件前把 XTRANSLATOR_PREFIX 定义为同一前缀 */
+#ifndef XTRANSLATOR_PREFIX
+#define XTRANSLATOR_PREFIX
+#endif
+#define XTRANSLATOR_CONCAT_(a, b) a##b
+#define XTRANSLATOR_CONCAT(a, b) XTRANSLATOR_CONCAT_(a, b)
+#define XTRANSLATOR_FN(name) XTRANSLATOR_CONCAT(XTRANSLATOR_PREFIX, name)
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/**
+ * 返回插件中的翻译器名称列表，由 `free_supported_languages` 释放
+ */
+FfiResult_i8 *XTRANSLATOR_FN(get_plugin_translators)(const char ***array, size_t *len);
+
+uint32_t XTRANSLATOR_FN(get_abi_version)(void);
+
+/**
+ * 插件编译时 `#[repr(C)]` 结构体的布局，由 `free_string` 释放
+ */
+char *XTRANSLATOR_FN(get_abi_layout)(void);
+
+char *XTRANSLATOR_FN(get_plugin_name)(void);
+
+char *XTRANSLATOR_FN(get_plugin_metadata)(void);
+
+/**
+ * 返回 JSON 格式的配置 schema，插件没有提供时返回空指针
+ */
+char *XTRANSLATOR_FN(get_config_schema)(void);
+
+/**
+ * 返回指定翻译器的配置 schema，没有提供或没有该翻译器时返回空指针
+ */
+char *XTRANSLATOR_FN(get_named_config_schema)(const char *name);
+
+/**
+ * 返回指定翻译器不依赖配置的语言与能力，没有该翻译器时返回空指针，由 `free_string` 释放
+ */
+char *XTRANSLATOR_FN(get_named_static_info)(const char *name);
+
+/**
+ * 检查配置，返回 JSON 格式的 `Vec<ConfigIssue>`
+ */
+FfiResult_c_char *XTRANSLATOR_FN(validate_config)(const char *json_str);
+
+/**
+ * 按指定翻译器检查配置
+ */
+FfiResult_c_char *XTRANSLATOR_FN(validate_named_config)(const char *name, const char *json_str);
+
+/**
+ * 创建默认翻译器
+ */
+FfiResult_TranslatorHandle *XTRANSLATOR_FN(create_translator)(const char *json_str);
+
+/**
+ * 与 `create_translator` 相同，配置为 `(ptr, len)` 形式的 UTF-8
+ */
+FfiResult_TranslatorHandle *XTRANSLATOR_FN(create_translator_utf8)(const uint8_t *json, size_t len);
+
+/**
+ * 与 `create_translator` 相同，配置为 UTF-16，`len` 为 u16 个数
+ */
+FfiResult_TranslatorHandle *XTRANSLATOR_FN(create_translator_utf16)(const uint16_t *json, size_t len);
+
+/**
+ * 按名称创建插件中的翻译器，名称见 `get_plugin_translators`
+ */
+FfiResult_TranslatorHandle *XTRANSLATOR_FN(create_named_translator)(const char *name, const char *json_str);
+
+/**
+ * 释放 `create_translator` 创建的翻译器的一个引用，最后一个引用释放后不能再使用该句柄。
+ * 销毁前与 `shutdown_translator` 相同地等待进行中的调用结束，不能在回调中释放最后一个引用
+ */
+void XTRANSLATOR_FN(destroy_translator)(TranslatorHandle *translator_ptr);
+
+/**
+ * 增加一个引用并返回同一个句柄，每个引用都要调用一次 `destroy_translator`。
+ * 各引用共享同一个翻译器，`shutdown_translator` 对所有引用生效
+ */
+TranslatorHandle *XTRANSLATOR_FN(clone_translator)(TranslatorHandle *translator_ptr);
+
+/**
+ * 拒绝新的调用并等待进行中的调用结束，超过 `timeout_ms` 后取消它们。
+ * 返回后回调不会再被调用，可以安全地调用 `destroy_translator`
+ */
+FfiResult_i8 *XTRANSLATOR_FN(shutdown_translator)(TranslatorHandle *translator_ptr, uint64_t timeout_ms);
+
+FfiResult_i8 *XTRANSLATOR_FN(get_supported_input_languages)(TranslatorHandle *translator_ptr, const char ***array, size_t *len);
+
+FfiResult_i8 *XTRANSLATOR_FN(get_supported_output_languages)(TranslatorHandle *translator_ptr, const char ***array, size_t *len);
+
+FfiResult_i8 *XTRANSLATOR_FN(is_supported_input_language)(TranslatorHandle *translator_ptr, const char *lang);
+
+FfiResult_i8 *XTRANSLATOR_FN(is_supported_output_language)(TranslatorHandle *translator_ptr, const char *lang);
+
+FfiResult_i8 *XTRANSLATOR_FN(is_supported_pair)(TranslatorHandle *translator_ptr, const char *source, const char *target);
+
+/**
+ * 与 `is_supported_input_language` 相同，语言为 `(ptr, len)` 形式的 UTF-8
+ */
+FfiResult_i8 *XTRANSLATOR_FN(is_supported_input_language_utf8)(TranslatorHandle *translator_ptr, const uint8_t *lang, size_t len);
+
+/**
+ * 与 `is_supported_output_language` 相同，语言为 `(ptr, len)` 形式的 UTF-8
+ */
+FfiResult_i8 *XTRANSLATOR_FN(is_supported_output_language_utf8)(TranslatorHandle *translator_ptr, const uint8_t *lang, size_t len);
+
+/**
+ * 与 `is_supported_pair` 相同，语言为 `(ptr, len)` 形式的 UTF-8
+ */
+FfiResult_i8 *XTRANSLATOR_FN(is_supported_pair_utf8)(TranslatorHandle *translator_ptr, const uint8_t *source, size_t source_len, const uint8_t *target, size_t target_len);
+
+FfiResult_i8 *XTRANSLATOR_FN(get_supported_pairs)(TranslatorHandle *translator_ptr, const char ***array, size_t *len);
+
+/**
+ * 返回 JSON 格式的 `Capabilities`，宿主据此决定是否使用流式翻译、是否传入术语与参考译文
+ */
+FfiResult_c_char *XTRANSLATOR_FN(get_capabilities)(TranslatorHandle *translator_ptr);
+
+FfiResult_TranslateResultFFI *XTRANSLATOR_FN(call_translate)(TranslatorHandle *translator_ptr, const char *json_str);
+
+/**
+ * 与 `call_translate` 相同，任务为 `(ptr, len)` 形式的 UTF-8
+ */
+FfiResult_TranslateResultFFI *XTRANSLATOR_FN(call_translate_utf8)(TranslatorHandle *translator_ptr, const uint8_t *json, size_t len);
+
+/**
+ * 与 `call_translate` 相同，任务为 UTF-16，`len` 为 u16 个数
+ */
+FfiResult_TranslateResultFFI *XTRANSLATOR_FN(call_translate_utf16)(TranslatorHandle *translator_ptr, const uint16_t *json, size_t len);
+
+/**
+ * 与 `call_translate` 相同，翻译期间通过 `progress` 报告进度，返回后不再调用 `progress`
+ */
+FfiResult_TranslateResultFFI *XTRANSLATOR_FN(call_translate_with_progress)(TranslatorHandle *translator_ptr, const char *json_str, ProgressCallback progress, void *user_data);
+
+/**
+ * 一次翻译多个任务，`json_str` 为任务数组。结果为对应的 `BatchResult` 数组，由 `free_string` 释放
+ */
+FfiResult_c_char *XTRANSLATOR_FN(call_translate_batch)(TranslatorHandle *translator_ptr, const char *json_str);
+
+/**
+ * 与 `call_translate_utf8` 相同，结果以 JSON 写入 `arena`，`out`、`out_len` 为其位置，
+ * 在下一次写入同一个 `arena` 或 `free_result_arena` 之前有效
+ */
+FfiResult_i8 *XTRANSLATOR_FN(call_translate_into)(TranslatorHandle *translator_ptr, const uint8_t *json, size_t len, ResultArena *arena, const uint8_t **out, size_t *out_len);
+
+/**
+ * 立即返回，结果通过 `completion` 送达，宿主不需要为每次翻译阻塞一个线程
+ */
+FfiResult_i8 *XTRANSLATOR_FN(call_translate_async)(TranslatorHandle *translator_ptr, const char *json_str, CompletionCallback completion, void *user_data);
+
+FfiResult_i8 *XTRANSLATOR_FN(call_translate_stream)(TranslatorHandle *translator_ptr, const char *json_str, StreamCallback callback_wrapper, void *callback);
+
+FfiResult_i8 *XTRANSLATOR_FN(call_translate_stream_cancellable)(TranslatorHandle *translator_ptr, const char *json_str, StreamCallback callback_wrapper, void *callback, StreamHandle *stream_handle);
+
+/**
+ * 与 `call_translate_stream_cancellable` 相同，任务为 `(ptr, len)` 形式的 UTF-8
+ */
+FfiResult_i8 *XTRANSLATOR_FN(call_translate_stream_utf8)(TranslatorHandle *translator_ptr, const uint8_t *json, size_t len, StreamCallback callback_wrapper, void *callback, StreamHandle *stream_handle);
+
+/**
+ * 与 `call_translate_stream_cancellable` 相同，任务为 UTF-16，`len` 为 u16 个数
+ */
+FfiResult_i8 *XTRANSLATOR_FN(call_translate_stream_utf16)(TranslatorHandle *translator_ptr, const uint16_t *json, size_t len, StreamCallback callback_wrapper, void *callback, StreamHandle *stream_handle);
+
+StreamHandle *XTRANSLATOR_FN(create_stream_handle)(void);
+
+/**
+ * 中止正在进行的流式翻译，回调会收到最后一个 `End`
+ */
+void XTRANSLATOR_FN(cancel_stream)(StreamHandle *stream_handle);
+
+void XTRANSLATOR_FN(free_stream_handle)(StreamHandle *stream_handle);
+
+/**
+ * 创建 `call_translate_into` 使用的缓冲区，由 `free_result_arena` 释放
+ */
+ResultArena *XTRANSLATOR_FN(create_result_arena)(void);
+
+void XTRANSLATOR_FN(free_result_arena)(ResultArena *arena);
+
+/**
+ * 插件中的 `tracing` 日志交给宿主回调，`max_level` 为 0 时关闭
+ */
+void XTRANSLATOR_FN(set_log_callback)(LogCallback callback, void *user_data, uint32_t max_level);
+
+/**
+ * 释放插件返回的字符串，包括错误信息
+ */
+void XTRANSLATOR_FN(free_string)(char *s);
+
+/**
+ * 释放结果及其错误信息，`ptr` 指向的内容已交给调用方
+ */
+void XTRANSLATOR_FN(free_ffi_result)(FfiResult_c_void *result);
+
+void XTRANSLATOR_FN(free_ffi_status)(FfiResult_i8 *result);
+
+void XTRANSLATOR_FN(free_translate_result)(TranslateResultFFI *result);
+
+/**
+ * 释放流式回调收到的增量，包括其中的译文
+ */
+void XTRANSLATOR_FN(free_translate_stream_chunk)(TranslateStreamChunkFFI *chunk);
+
+void XTRANSLATOR_FN(free_supported_languages)(const char **array, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif  /* XTRANSLATOR_PLUGIN_H */
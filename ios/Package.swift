@@ -0,0 +1,16 @@
+// swift-tools-version:5.7
+// 先构建静态库：cargo build -p xtranslator-ios --release --target aarch64-apple-ios，
+// 再用 xcodebuild -create-xcframework 合并各架构，或在 Xcode 的 Library Search Paths 中指向 target 目录
+import PackageDescription
+
+let package = Package(
+    name: "XTranslator",
+    platforms: [.iOS(.v13), .macOS(.v10_15)],
+    products: [
+        .library(name: "XTranslator", targets: ["XTranslator"]),
+    ],
+    targets: [
+        .systemLibrary(name: "XTranslatorFFI", path: "include"),
+        .target(name: "XTranslator", dependencies: ["XTranslatorFFI"], path: "Sources/XTranslator"),
+    ]
+)
@@ -0,0 +1,291 @@
+// 基于 C 接口的 Swift 封装。插件的回调在 Rust 的工作线程中执行，
+// 结果统一派发到调用方指定的 DispatchQueue
+import Foundation
+import XTranslatorFFI
+
+public struct XTranslatorError: Error, CustomStringConvertible {
+    public let message: String
+    /// 插件返回可识别的错误时为 `XTranslateError` 的类别，如 `rate_limited`
+    public var kind: String? = nil
+    public var code: String? = nil
+    public var retryAfterMs: UInt64? = nil
+    public var description: String { message }
+}
+
+/// 可识别的错误以 JSON 格式的 `FfiError` 传递，其余错误只有文本
+private struct FfiError: Decodable {
+    var kind: String
+    var code: String?
+    var retryAfterMs: UInt64?
+    var detail: String
+}
+
+private let decoder: JSONDecoder = {
+    let decoder = JSONDecoder()
+    decoder.keyDecodingStrategy = .convertFromSnakeCase
+    return decoder
+}()
+
+extension XTranslatorError {
+    init(ffi message: String) {
+        guard let error = try? decoder.decode(FfiError.self, from: Data(message.utf8)) else {
+            self.init(message: message)
+            return
+        }
+        self.init(message: error.detail, kind: error.kind, code: error.code, retryAfterMs: error.retryAfterMs)
+    }
+}
+
+public struct TranslatedItem: Codable {
+    public var source: String
+    public var target: String
+
+    public init(source: String, target: String) {
+        self.source = source
+        self.target = target
+    }
+}
+
+/// 字段与 lib 中的 `TranslateTask` 相同，语言为 BCP 47 标签
+public struct TranslateTask: Encodable {
+    public var id: String
+    public var content: String
+    public var sourceLanguage: String?
+    public var targetLanguage: String?
+    public var userPrompt: String?
+    public var systemPrompt: String?
+    public var field: String?
+    public var terms: [TranslatedItem] = []
+    public var references: [TranslatedItem] = []
+    public var requestId: String?
+    public var deadlineMs: UInt64?
+
+    public init(id: String = UUID().uuidString, content: String, sourceLanguage: String? = nil, targetLanguage: String? = nil) {
+        self.id = id
+        self.content = content
+        self.sourceLanguage = sourceLanguage
+        self.targetLanguage = targetLanguage
+    }
+}
+
+public struct TranslateResult {
+    public var reasoning: String?
+    public var content: String?
+}
+
+public enum TranslateStreamChunk {
+    case start
+    case delta(TranslateResult)
+    case end
+}
+
+/// 进行中的流式翻译，`cancel` 后回调仍会收到最后一个 `.end`
+public final class TranslateStreamTask {
+    fileprivate let handle: UnsafeMutablePointer<StreamHandle>
+
+    fileprivate init(handle: UnsafeMutablePointer<StreamHandle>) {
+        self.handle = handle
+    }
+
+    public func cancel() {
+        cancel_stream(handle)
+    }
+
+    deinit {
+        free_stream_handle(handle)
+    }
+}
+
+private final class CompletionBox {
+    /// 翻译结束前保持翻译器存活
+    let translator: XTranslator
+    let queue: DispatchQueue
+    let completion: (Result<TranslateResult, Error>) -> Void
+
+    init(translator: XTranslator, queue: DispatchQueue, completion: @escaping (Result<TranslateResult, Error>) -> Void) {
+        self.translator = translator
+        self.queue = queue
+        self.completion = completion
+    }
+}
+
+private final class StreamBox {
+    let queue: DispatchQueue
+    let onChunk: (TranslateStreamChunk) -> Void
+
+    init(queue: DispatchQueue, onChunk: @escaping (TranslateStreamChunk) -> Void) {
+        self.queue = queue
+        self.onChunk = onChunk
+    }
+}
+
+private func readResult(_ ptr: UnsafeMutablePointer<TranslateResultFFI>) -> TranslateResult {
+    let result = TranslateResult(
+        reasoning: ptr.pointee.reasoning.map { String(cString: $0) },
+        content: ptr.pointee.content.map { String(cString: $0) }
+    )
+    free_translate_result(ptr)
+    return result
+}
+
+/// 释放结果本身并取出错误信息，`ptr` 的所有权留给调用方
+private func takeError<T>(_ result: UnsafeMutablePointer<T>, err: UnsafeMutablePointer<CChar>?) -> XTranslatorError? {
+    let message = err.map { String(cString: $0) }
+    free_ffi_result(UnsafeMutableRawPointer(result).assumingMemoryBound(to: FfiResult_c_void.self))
+    return message.map { XTranslatorError(ffi: $0) }
+}
+
+private func checkStatus(_ status: UnsafeMutablePointer<FfiResult_i8>?) throws -> Int8 {
+    guard let status = status else { throw XTranslatorError(message: "null result") }
+    defer { free_ffi_status(status) }
+    if let err = status.pointee.err {
+        throw XTranslatorError(ffi: String(cString: err))
+    }
+    return status.pointee.ptr?.pointee ?? 0
+}
+
+private func readLanguages(_ call: (UnsafeMutablePointer<UnsafeMutablePointer<UnsafePointer<CChar>?>?>, UnsafeMutablePointer<Int>) -> UnsafeMutablePointer<FfiResult_i8>?) throws -> [String] {
+    var array: UnsafeMutablePointer<UnsafePointer<CChar>?>?
+    var len = 0
+    _ = try checkStatus(call(&array, &len))
+    guard let items = array else { return [] }
+    defer { free_supported_languages(items, len) }
+    return (0..<len).compactMap { items[$0].map { String(cString: $0) } }
+}
+
+private let encoder: JSONEncoder = {
+    let encoder = JSONEncoder()
+    encoder.keyEncodingStrategy = .convertToSnakeCase
+    return encoder
+}()
+
+public final class XTranslator {
+    private let handle: UnsafeMutablePointer<TranslatorHandle>
+
+    /// 静态链接的翻译器名称，如 `openai`、`qwen`
+    public static func names() throws -> [String] {
+        try readLanguages { get_plugin_translators($0, $1) }
+    }
+
+    /// `config` 为该翻译器的 JSON 配置
+    public init(name: String, config: [String: Any] = [:]) throws {
+        let json = String(data: try JSONSerialization.data(withJSONObject: config), encoding: .utf8) ?? "{}"
+        guard let result = create_named_translator(name, json) else {
+            throw XTranslatorError(message: "null result")
+        }
+        let ptr = result.pointee.ptr
+        if let error = takeError(result, err: result.pointee.err) {
+            throw error
+        }
+        guard let ptr = ptr else { throw XTranslatorError(message: "null translator") }
+        handle = ptr
+    }
+
+    deinit {
+        destroy_translator(handle)
+    }
+
+    public func supportedInputLanguages() throws -> [String] {
+        try readLanguages { get_supported_input_languages(handle, $0, $1) }
+    }
+
+    public func supportedOutputLanguages() throws -> [String] {
+        try readLanguages { get_supported_output_languages(handle, $0, $1) }
+    }
+
+    public func isSupportedPair(source: String, target: String) throws -> Bool {
+        try checkStatus(is_supported_pair(handle, source, target)) != 0
+    }
+
+    /// 在插件线程中翻译，完成后在 `queue` 上回调
+    public func translate(_ task: TranslateTask, queue: DispatchQueue = .main, completion: @escaping (Result<TranslateResult, Error>) -> Void) {
+        let json: String
+        do {
+            json = String(data: try encoder.encode(task), encoding: .utf8) ?? "{}"
+        } catch {
+            queue.async { completion(.failure(error)) }
+            return
+        }
+
+        let box = Unmanaged.passRetained(CompletionBox(translator: self, queue: queue, completion: completion)).toOpaque()
+        let callback: CompletionCallback = { result, userData in
+            let box = Unmanaged<CompletionBox>.fromOpaque(userData!).takeRetainedValue()
+            let outcome: Result<TranslateResult, Error>
+            if let result = result {
+                let ptr = result.pointee.ptr
+                if let error = takeError(result, err: result.pointee.err) {
+                    outcome = .failure(error)
+                } else if let ptr = ptr {
+                    outcome = .success(readResult(ptr))
+                } else {
+                    outcome = .failure(XTranslatorError(message: "null result"))
+                }
+            } else {
+                outcome = .failure(XTranslatorError(message: "null result"))
+            }
+            box.queue.async { box.completion(outcome) }
+        }
+
+        do {
+            _ = try checkStatus(call_translate_async(handle, json, callback, box))
+        } catch {
+            Unmanaged<CompletionBox>.fromOpaque(box).release()
+            queue.async { completion(.failure(error)) }
+        }
+    }
+
+    @available(iOS 13.0, macOS 10.15, *)
+    public func translate(_ task: TranslateTask) async throws -> TranslateResult {
+        try await withCheckedThrowingContinuation { cont in
+            translate(task, queue: .global()) { cont.resume(with: $0) }
+        }
+    }
+
+    /// 在后台队列中流式翻译，增量与结束回调都在 `queue` 上执行。
+    /// `completion` 的参数为 true 表示被 `cancel` 中止
+    @discardableResult
+    public func translateStream(
+        _ task: TranslateTask,
+        queue: DispatchQueue = .main,
+        onChunk: @escaping (TranslateStreamChunk) -> Void,
+        completion: @escaping (Result<Bool, Error>) -> Void
+    ) -> TranslateStreamTask? {
+        guard let json = try? String(data: encoder.encode(task), encoding: .utf8),
+              let streamHandle = create_stream_handle() else {
+            queue.async { completion(.failure(XTranslatorError(message: "invalid task"))) }
+            return nil
+        }
+        let stream = TranslateStreamTask(handle: streamHandle)
+        let box = StreamBox(queue: queue, onChunk: onChunk)
+
+        let callback: StreamCallback = { chunk, userData in
+            guard let chunk = chunk, let userData = userData else { return }
+            let box = Unmanaged<StreamBox>.fromOpaque(userData).takeUnretainedValue()
+            let value: TranslateStreamChunk
+            switch chunk.pointee.tag {
+            case TranslateStreamChunkTag_Start:
+                value = .start
+            case TranslateStreamChunkTag_Delta:
+                let delta = chunk.pointee.data.delta.pointee
+                value = .delta(TranslateResult(
+                    reasoning: delta.reasoning.map { String(cString: $0) },
+                    content: delta.content.map { String(cString: $0) }
+                ))
+            default:
+                value = .end
+            }
+            free_translate_stream_chunk(chunk)
+            box.queue.async { box.onChunk(value) }
+        }
+
+        // call_translate_stream_cancellable 会阻塞到翻译结束
+        DispatchQueue.global(qos: .userInitiated).async { [self] in
+            let userData = Unmanaged.passRetained(box).toOpaque()
+            defer { Unmanaged<StreamBox>.fromOpaque(userData).release() }
+            let outcome = Result { try checkStatus(call_translate_stream_cancellable(handle, json, callback, userData, stream.handle)) != 0 }
+            queue.async { completion(outcome) }
+        }
+
+        return stream
+    }
+}
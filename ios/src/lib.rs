@@ -0,0 +1,45 @@
+#[cfg(test)]
+use std::ffi::{CStr, CString};
+
+/// iOS 不允许加载动态库，内置插件静态链接进同一个库，按名称创建翻译器。
+/// 导出的 C 接口与插件相同，见 include/xtranslator_plugin.h
+pub mod ffi {
+    use macros::build_ffi;
+    use plugin_baidu_fanyi::translator::BaiduFanyiTranslator;
+    use plugin_dryrun::translator::DryRunTranslator;
+    use plugin_hunyuan::translator::HunyuanTranslator;
+    use plugin_openai::translator::OpenAITranslator;
+    use plugin_qwen::translator::QwenMtTranslator;
+    use plugin_youdao_llm::translator::YoudaoLLMTranslator;
+
+    build_ffi!(
+        "xtranslator",
+        [
+            ("openai", OpenAITranslator),
+            ("qwen", QwenMtTranslator),
+            ("hunyuan", HunyuanTranslator),
+            ("youdao_llm", YoudaoLLMTranslator),
+            ("baidu_fanyi", BaiduFanyiTranslator),
+            ("dryrun", DryRunTranslator)
+        ]
+    );
+}
+
+#[test]
+fn test_static_translators() -> anyhow::Result<()> {
+    let name = CString::new("dryrun")?;
+    let config = CString::new("{}")?;
+    let result = unsafe { &*ffi::create_named_translator(name.as_ptr(), config.as_ptr()) };
+    assert!(result.err.is_null());
+    ffi::destroy_translator(result.ptr as *mut lib::ffi::TranslatorHandle);
+
+    let schema = ffi::get_named_config_schema(CString::new("qwen")?.as_ptr());
+    assert!(!schema.is_null());
+    let schema: serde_json::Value = serde_json::from_str(unsafe { CStr::from_ptr(schema) }.to_str()?)?;
+    assert!(schema.is_object());
+
+    ffi::free_translate_stream_chunk(lib::TranslateStreamChunk::Start.into_ffi());
+    ffi::free_translate_stream_chunk(std::ptr::null_mut());
+
+    Ok(())
+}
@@ -0,0 +1,11 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // gRPC 接口由 proto/translator.proto 生成，使用随依赖下载的 protoc，不要求系统安装
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=../proto/translator.proto");
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path().unwrap());
+        tonic_prost_build::compile_protos("../proto/translator.proto").unwrap();
+    }
+}
@@ -0,0 +1,352 @@
+use crate::cost::{count_tokens, default_pricing, Pricing};
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{
+    Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator, Usage,
+};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// 一组用量上限，未设置的项不限制
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetLimits {
+    /// 输入与输出 token 数之和
+    pub max_tokens: Option<u64>,
+    /// 计费字符数
+    pub max_characters: Option<u64>,
+    /// 按价格表计算的费用
+    pub max_cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// 翻译器实例的累计上限
+    pub session: BudgetLimits,
+    /// 单个任务的上限
+    pub task: BudgetLimits,
+    /// 计算费用用的价格，未设置时使用 `provider` 的内置参考价格
+    pub pricing: Option<Pricing>,
+    /// 服务名称，见 `cost::default_pricing`
+    pub provider: Option<String>,
+}
+
+/// 超出的范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    Session,
+    Task,
+}
+
+/// 超出的用量类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetKind {
+    Tokens,
+    Characters,
+    Cost,
+}
+
+impl Display for BudgetScope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetScope::Session => f.write_str("session"),
+            BudgetScope::Task => f.write_str("task"),
+        }
+    }
+}
+
+impl Display for BudgetKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetKind::Tokens => f.write_str("tokens"),
+            BudgetKind::Characters => f.write_str("characters"),
+            BudgetKind::Cost => f.write_str("cost"),
+        }
+    }
+}
+
+/// 预计用量超出预算，请求不会发送
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{scope} budget exceeded: {kind} {used} > {limit}")]
+pub struct BudgetExceeded {
+    pub scope: BudgetScope,
+    pub kind: BudgetKind,
+    /// 预计用量，会话范围时包含已用部分
+    pub used: f64,
+    pub limit: f64,
+}
+
+impl BudgetExceeded {
+    /// 从错误链中找出 `BudgetExceeded`
+    pub fn find(err: &anyhow::Error) -> Option<&BudgetExceeded> {
+        err.chain().find_map(|e| e.downcast_ref::<BudgetExceeded>())
+    }
+}
+
+/// 在发送前按 `cost::Pricing::estimate` 估算用量，超出单个任务或会话的预算时直接返回 `BudgetExceeded`。
+/// 会话用量按结果中的实际用量累计，并发请求可能略微超出会话预算
+pub struct BudgetGuard<T> {
+    inner: T,
+    config: BudgetConfig,
+    pricing: Option<Pricing>,
+    spent: Mutex<Usage>,
+}
+
+impl<T> BudgetGuard<T> {
+    pub fn wrap(inner: T, config: BudgetConfig) -> Result<Self> {
+        let pricing = config
+            .pricing
+            .clone()
+            .or_else(|| config.provider.as_deref().and_then(default_pricing));
+
+        if pricing.is_none()
+            && (config.session.max_cost.is_some() || config.task.max_cost.is_some())
+        {
+            bail!("missing argument: pricing");
+        }
+
+        Ok(BudgetGuard {
+            inner,
+            config,
+            pricing,
+            spent: Mutex::new(Usage::default()),
+        })
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// 已累计的用量
+    pub fn spent(&self) -> Usage {
+        self.spent.lock().unwrap().clone()
+    }
+
+    /// 已累计的费用，没有价格时为 0
+    pub fn spent_cost(&self) -> f64 {
+        self.cost(&self.spent())
+    }
+
+    fn cost(&self, usage: &Usage) -> f64 {
+        self.pricing
+            .as_ref()
+            .map(|pricing| pricing.cost(usage))
+            .unwrap_or(0.0)
+    }
+
+    fn estimate(&self, task: &TranslateTask) -> Usage {
+        match &self.pricing {
+            Some(pricing) => pricing.estimate(task).usage,
+            None => {
+                let tokens = count_tokens(&task.content, None) as u64;
+                Usage {
+                    prompt_tokens: tokens,
+                    completion_tokens: tokens,
+                    characters: task.content.chars().count() as u64,
+                    ..Default::default()
+                }
+            }
+        }
+    }
+
+    fn check_limits(&self, scope: BudgetScope, limits: &BudgetLimits, usage: &Usage) -> Result<()> {
+        let checks = [
+            (
+                BudgetKind::Tokens,
+                (usage.prompt_tokens + usage.completion_tokens) as f64,
+                limits.max_tokens.map(|max| max as f64),
+            ),
+            (
+                BudgetKind::Characters,
+                usage.characters as f64,
+                limits.max_characters.map(|max| max as f64),
+            ),
+            (BudgetKind::Cost, self.cost(usage), limits.max_cost),
+        ];
+
+        for (kind, used, limit) in checks {
+            if let Some(limit) = limit.filter(|limit| used > *limit) {
+                bail!(BudgetExceeded {
+                    scope,
+                    kind,
+                    used,
+                    limit,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check(&self, task: &TranslateTask) -> Result<Usage> {
+        let estimate = self.estimate(task);
+        self.check_limits(BudgetScope::Task, &self.config.task, &estimate)?;
+
+        let mut total = self.spent();
+        total.add(&estimate);
+        self.check_limits(BudgetScope::Session, &self.config.session, &total)?;
+
+        Ok(estimate)
+    }
+
+    fn record(&self, usage: &Usage) {
+        self.spent.lock().unwrap().add(usage);
+    }
+}
+
+#[async_trait]
+impl<T> Translator for BudgetGuard<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 预算读取自 `config["budget"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let budget_config: BudgetConfig = match config.get("budget") {
+            Some(budget) => serde_json::from_value(budget.clone())?,
+            None => BudgetConfig::default(),
+        };
+
+        BudgetGuard::wrap(T::new(config).await?, budget_config)
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    /// 结果没有携带用量时按估算值累计
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let estimate = self.check(&task)?;
+
+        let result = self.inner.translate(task).await?;
+        self.record(result.usage.as_ref().unwrap_or(&estimate));
+
+        Ok(result)
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let estimate = self.check(&task)?;
+
+        let (tx, mut rx) = mpsc::channel(64);
+
+        let forward = async {
+            let mut usage: Option<Usage> = None;
+            while let Some(chunk) = rx.recv().await {
+                if let TranslateStreamChunk::Delta(delta) = &chunk {
+                    if let Some(u) = &delta.usage {
+                        usage.get_or_insert_with(Usage::default).add(u);
+                    }
+                }
+                sender.send(chunk).await?;
+            }
+            Ok::<_, anyhow::Error>(usage)
+        };
+
+        let (result, forwarded) = tokio::join!(self.inner.translate_stream(task, tx), forward);
+        result?;
+        let usage = forwarded?;
+
+        self.record(usage.as_ref().unwrap_or(&estimate));
+
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_budget_guard() -> Result<()> {
+    let config = BudgetConfig {
+        session: BudgetLimits {
+            max_characters: Some(12),
+            ..Default::default()
+        },
+        task: BudgetLimits {
+            max_characters: Some(8),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let translator = BudgetGuard::wrap(MockTranslator::new("T:"), config)?;
+
+    let err = translator.translate(task("Hello world")).await.unwrap_err();
+    assert_eq!(
+        BudgetExceeded::find(&err),
+        Some(&BudgetExceeded {
+            scope: BudgetScope::Task,
+            kind: BudgetKind::Characters,
+            used: 11.0,
+            limit: 8.0,
+        })
+    );
+    assert_eq!(translator.inner().calls(), 0);
+
+    translator.translate(task("Hello")).await?;
+    translator.translate(task("Hello")).await?;
+    assert_eq!(translator.spent().characters, 10);
+
+    let err = translator.translate(task("Hello")).await.unwrap_err();
+    assert_eq!(
+        BudgetExceeded::find(&err).map(|e| e.scope),
+        Some(BudgetScope::Session)
+    );
+    assert_eq!(translator.inner().calls(), 2);
+
+    // 限制费用需要价格
+    let config = BudgetConfig {
+        task: BudgetLimits {
+            max_cost: Some(1.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(BudgetGuard::wrap(MockTranslator::new(""), config.clone()).is_err());
+    let translator = BudgetGuard::wrap(
+        MockTranslator::new(""),
+        BudgetConfig {
+            provider: Some("baidu_fanyi".to_string()),
+            ..config
+        },
+    )?;
+    translator.translate(task("Hello")).await?;
+    assert!(translator.spent_cost() > 0.0);
+
+    Ok(())
+}
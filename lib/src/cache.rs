@@ -0,0 +1,722 @@
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// 缓存键
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// 翻译服务标识
+    pub provider: String,
+    /// 原文
+    pub content: String,
+    /// 源语言
+    pub source_language: Option<String>,
+    /// 目标语言
+    pub target_language: Option<String>,
+    /// 提示词、术语表等其余参数的哈希
+    pub prompt_hash: String,
+}
+
+impl CacheKey {
+    pub fn new(provider: &str, task: &TranslateTask) -> Result<Self> {
+        let extra = json!({
+            "user_prompt": task.user_prompt,
+            "system_prompt": task.system_prompt,
+            "field": task.field,
+            "terms": task.terms,
+            "references": task.references,
+            "extra": task.extra,
+        });
+
+        let digest = Sha256::new()
+            .chain_update(serde_json::to_vec(&extra)?)
+            .finalize();
+
+        Ok(CacheKey {
+            provider: provider.to_string(),
+            content: task.content.clone(),
+            source_language: task.source_language.as_ref().map(|tag| tag.to_string()),
+            target_language: task.target_language.as_ref().map(|tag| tag.to_string()),
+            prompt_hash: hex::encode(digest),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// 翻译服务标识，默认为被包装类型的类型名。由 `Translator::new` 创建时再加上
+    /// 其余配置的哈希，配置不同的翻译器不会共用持久化缓存中的条目
+    pub provider: Option<String>,
+    /// 最大缓存条目数
+    #[serde(default = "CacheConfig::default_capacity")]
+    pub capacity: usize,
+    /// 过期时间（毫秒），为空则不过期
+    pub ttl_ms: Option<u64>,
+    /// 存储后端
+    #[serde(default)]
+    pub backend: CacheBackend,
+    /// 持久化缓存路径
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackend {
+    #[default]
+    Memory,
+    /// 需启用 `sled` feature
+    Sled,
+}
+
+impl CacheConfig {
+    fn default_capacity() -> usize {
+        1024
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig {
+            provider: None,
+            capacity: CacheConfig::default_capacity(),
+            ttl_ms: None,
+            backend: CacheBackend::Memory,
+            path: None,
+        }
+    }
+}
+
+/// 缓存记录，用于持久化及导入导出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheRecord {
+    pub key: CacheKey,
+    pub result: TranslateResult,
+    /// 写入时间（Unix 毫秒）
+    pub created_at: u64,
+}
+
+impl CacheRecord {
+    pub fn new(key: CacheKey, result: TranslateResult) -> Self {
+        CacheRecord {
+            key,
+            result,
+            created_at: now_millis(),
+        }
+    }
+
+    pub fn is_expired(&self, ttl: Option<Duration>) -> bool {
+        ttl.map(|ttl| now_millis().saturating_sub(self.created_at) > ttl.as_millis() as u64)
+            .unwrap_or(false)
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 缓存存储后端
+pub trait CacheStore: Send + Sync {
+    fn get(&self, key: &CacheKey) -> Result<Option<CacheRecord>>;
+
+    fn put(&self, record: CacheRecord) -> Result<()>;
+
+    fn remove(&self, key: &CacheKey) -> Result<()>;
+
+    fn clear(&self) -> Result<()>;
+
+    fn len(&self) -> Result<usize>;
+
+    fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// 导出全部记录
+    fn export(&self) -> Result<Vec<CacheRecord>>;
+
+    /// 导入记录，已存在的键会被覆盖
+    fn import(&self, records: Vec<CacheRecord>) -> Result<()> {
+        for record in records {
+            self.put(record)?;
+        }
+        Ok(())
+    }
+
+    /// 以 JSON 格式导出到文件
+    fn export_to_file(&self, path: &Path) -> Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer(BufWriter::new(file), &self.export()?)?;
+        Ok(())
+    }
+
+    /// 从 `export_to_file` 生成的 JSON 文件导入
+    fn import_from_file(&self, path: &Path) -> Result<()> {
+        let file = File::open(path)?;
+        let records: Vec<CacheRecord> = serde_json::from_reader(BufReader::new(file))?;
+        self.import(records)
+    }
+}
+
+/// 内存 LRU 缓存
+pub struct MemoryCache {
+    cache: Mutex<LruCache<CacheKey, CacheRecord>>,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+
+        MemoryCache {
+            cache: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+}
+
+impl CacheStore for MemoryCache {
+    fn get(&self, key: &CacheKey) -> Result<Option<CacheRecord>> {
+        Ok(self.cache.lock().unwrap().get(key).cloned())
+    }
+
+    fn put(&self, record: CacheRecord) -> Result<()> {
+        self.cache.lock().unwrap().put(record.key.clone(), record);
+        Ok(())
+    }
+
+    fn remove(&self, key: &CacheKey) -> Result<()> {
+        self.cache.lock().unwrap().pop(key);
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.cache.lock().unwrap().clear();
+        Ok(())
+    }
+
+    fn len(&self) -> Result<usize> {
+        Ok(self.cache.lock().unwrap().len())
+    }
+
+    fn export(&self) -> Result<Vec<CacheRecord>> {
+        // 按从旧到新的顺序导出，导入时可还原 LRU 顺序
+        Ok(self
+            .cache
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .map(|(_, record)| record.clone())
+            .collect())
+    }
+}
+
+/// 基于 sled 的持久化缓存，不受 `capacity` 限制
+#[cfg(feature = "sled")]
+pub struct SledCache {
+    db: sled::Db,
+}
+
+#[cfg(feature = "sled")]
+impl SledCache {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(SledCache {
+            db: sled::open(path)?,
+        })
+    }
+}
+
+#[cfg(feature = "sled")]
+impl CacheStore for SledCache {
+    fn get(&self, key: &CacheKey) -> Result<Option<CacheRecord>> {
+        match self.db.get(serde_json::to_vec(key)?)? {
+            Some(value) => Ok(Some(serde_json::from_slice(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&self, record: CacheRecord) -> Result<()> {
+        self.db.insert(
+            serde_json::to_vec(&record.key)?,
+            serde_json::to_vec(&record)?,
+        )?;
+        Ok(())
+    }
+
+    fn remove(&self, key: &CacheKey) -> Result<()> {
+        self.db.remove(serde_json::to_vec(key)?)?;
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.db.clear()?;
+        Ok(())
+    }
+
+    fn len(&self) -> Result<usize> {
+        Ok(self.db.len())
+    }
+
+    fn export(&self) -> Result<Vec<CacheRecord>> {
+        self.db
+            .iter()
+            .values()
+            .map(|value| Ok(serde_json::from_slice(&value?)?))
+            .collect()
+    }
+
+    fn import(&self, records: Vec<CacheRecord>) -> Result<()> {
+        let mut batch = sled::Batch::default();
+        for record in records {
+            batch.insert(
+                serde_json::to_vec(&record.key)?,
+                serde_json::to_vec(&record)?,
+            );
+        }
+        self.db.apply_batch(batch)?;
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+/// 带缓存的翻译器包装
+pub struct CachedTranslator<T> {
+    inner: T,
+    provider: String,
+    ttl: Option<Duration>,
+    store: Box<dyn CacheStore>,
+}
+
+impl<T: Translator> CachedTranslator<T> {
+    /// 使用内存 LRU 缓存包装
+    pub fn wrap(inner: T, config: CacheConfig) -> Self {
+        let store = Box::new(MemoryCache::new(config.capacity));
+        CachedTranslator::with_store(inner, config, store)
+    }
+
+    /// 使用指定存储后端包装
+    pub fn with_store(inner: T, config: CacheConfig, store: Box<dyn CacheStore>) -> Self {
+        CachedTranslator {
+            inner,
+            provider: config
+                .provider
+                .unwrap_or(std::any::type_name::<T>().to_string()),
+            ttl: config.ttl_ms.map(Duration::from_millis),
+            store,
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn store(&self) -> &dyn CacheStore {
+        self.store.as_ref()
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) -> Result<()> {
+        self.store.clear()
+    }
+
+    /// 当前缓存条目数
+    pub fn len(&self) -> Result<usize> {
+        self.store.len()
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        self.store.is_empty()
+    }
+
+    fn get(&self, key: &CacheKey) -> Result<Option<TranslateResult>> {
+        match self.store.get(key)? {
+            Some(record) if record.is_expired(self.ttl) => {
+                self.store.remove(key)?;
+                Ok(None)
+            }
+            Some(record) => Ok(Some(record.result)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&self, key: CacheKey, result: TranslateResult) -> Result<()> {
+        self.store.put(CacheRecord::new(key, result))
+    }
+}
+
+#[async_trait]
+impl<T> Translator for CachedTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 缓存参数读取自 `config["cache"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let mut cache_config: CacheConfig = match config.get("cache") {
+            Some(cache) => serde_json::from_value(cache.clone())?,
+            None => CacheConfig::default(),
+        };
+
+        let store: Box<dyn CacheStore> = match cache_config.backend {
+            CacheBackend::Memory => Box::new(MemoryCache::new(cache_config.capacity)),
+            #[cfg(feature = "sled")]
+            CacheBackend::Sled => {
+                let path = cache_config
+                    .path
+                    .as_ref()
+                    .ok_or(anyhow!("missing argument: cache.path"))?;
+                Box::new(SledCache::open(path)?)
+            }
+            #[cfg(not(feature = "sled"))]
+            CacheBackend::Sled => {
+                return Err(anyhow!("cache backend `sled` requires the `sled` feature"))
+            }
+        };
+
+        if cache_config.provider.is_none() {
+            let mut inner_config = config.clone();
+            if let Some(object) = inner_config.as_object_mut() {
+                object.remove("cache");
+            }
+            let digest = Sha256::new()
+                .chain_update(serde_json::to_vec(&inner_config)?)
+                .finalize();
+            cache_config.provider = Some(format!("{}#{}", std::any::type_name::<T>(), hex::encode(digest)));
+        }
+
+        let inner = T::new(config).await?;
+
+        Ok(CachedTranslator::with_store(inner, cache_config, store))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let key = CacheKey::new(&self.provider, &task)?;
+
+        // 命中缓存不产生用量，请求 ID 为本次请求的
+        if let Some(mut result) = self.get(&key)? {
+            result.usage = None;
+            result.request_id = task.request_id;
+            return Ok(result);
+        }
+
+        let result = self.inner.translate(task).await?;
+
+        // 到达截止时间等情况下的部分译文不缓存
+        if !result.partial {
+            self.put(key, result.clone())?;
+        }
+
+        Ok(result)
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let key = CacheKey::new(&self.provider, &task)?;
+
+        // 命中缓存时按 Start/Delta/End 回放
+        if let Some(mut result) = self.get(&key)? {
+            result.usage = None;
+            result.request_id = task.request_id;
+            sender.send(TranslateStreamChunk::Start).await?;
+            sender.send(TranslateStreamChunk::Delta(result)).await?;
+            sender.send(TranslateStreamChunk::End).await?;
+            return Ok(());
+        }
+
+        let (tx, mut rx) = mpsc::channel(64);
+
+        let forward = async {
+            let mut reasoning: Option<String> = None;
+            let mut content: Option<String> = None;
+            let mut provider: Option<String> = None;
+            let mut partial = false;
+            let mut ended = false;
+
+            while let Some(chunk) = rx.recv().await {
+                match &chunk {
+                    TranslateStreamChunk::Delta(delta) => {
+                        if let Some(s) = &delta.reasoning {
+                            reasoning.get_or_insert_with(String::new).push_str(s);
+                        }
+                        if let Some(s) = &delta.content {
+                            content.get_or_insert_with(String::new).push_str(s);
+                        }
+                        if delta.provider.is_some() {
+                            provider = delta.provider.clone();
+                        }
+                        partial |= delta.partial;
+                    }
+                    TranslateStreamChunk::End => ended = true,
+                    TranslateStreamChunk::Start => {}
+                }
+                sender.send(chunk).await?;
+            }
+
+            Ok::<_, anyhow::Error>((ended && !partial).then_some(TranslateResult {
+                reasoning,
+                content,
+                provider,
+                ..Default::default()
+            }))
+        };
+
+        let (result, forwarded) = tokio::join!(self.inner.translate_stream(task, tx), forward);
+
+        result?;
+
+        // 只缓存完整结束且没有部分增量的流
+        if let Some(result) = forwarded? {
+            self.put(key, result)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_cached_translate() -> Result<()> {
+    let translator = CachedTranslator::wrap(MockTranslator::new("T:"), CacheConfig::default());
+
+    let first = translator.translate(task("Hello")).await?;
+    let second = translator.translate(task("Hello")).await?;
+    translator.translate(task("World")).await?;
+
+    assert_eq!(first.content, second.content);
+    assert!(first.usage.is_some());
+    assert!(second.usage.is_none());
+    assert_eq!(translator.inner().calls(), 2);
+    assert_eq!(translator.len()?, 2);
+
+    let mut other = task("Hello");
+    other.field = Some("medical".to_string());
+    translator.translate(other).await?;
+    assert_eq!(translator.inner().calls(), 3);
+
+    // 命中缓存时返回本次请求的 ID
+    let mut first = task("Hello");
+    first.request_id = Some("first".to_string());
+    let mut second = first.clone();
+    second.request_id = Some("second".to_string());
+    assert_eq!(translator.translate(first).await?.request_id.as_deref(), Some("first"));
+    assert_eq!(translator.translate(second).await?.request_id.as_deref(), Some("second"));
+    assert_eq!(translator.inner().calls(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_translate_default_provider() -> Result<()> {
+    let a = CachedTranslator::<MockTranslator>::new(json!({ "prefix": "A:" })).await?;
+    let b = CachedTranslator::<MockTranslator>::new(json!({ "prefix": "B:" })).await?;
+    let a2 = CachedTranslator::<MockTranslator>::new(json!({ "prefix": "A:", "cache": { "capacity": 8 } })).await?;
+    let named = CachedTranslator::<MockTranslator>::new(json!({ "prefix": "A:", "cache": { "provider": "mock" } })).await?;
+
+    assert_ne!(a.provider, b.provider);
+    // 缓存参数不影响服务标识
+    assert_eq!(a.provider, a2.provider);
+    assert_eq!(named.provider, "mock");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_translate_capacity_and_ttl() -> Result<()> {
+    let translator = CachedTranslator::wrap(
+        MockTranslator::new("T:"),
+        CacheConfig {
+            provider: None,
+            capacity: 1,
+            ttl_ms: Some(20),
+            ..Default::default()
+        },
+    );
+
+    translator.translate(task("a")).await?;
+    translator.translate(task("b")).await?;
+    translator.translate(task("a")).await?;
+    assert_eq!(translator.inner().calls(), 3);
+
+    translator.translate(task("a")).await?;
+    assert_eq!(translator.inner().calls(), 3);
+
+    tokio::time::sleep(Duration::from_millis(40)).await;
+    translator.translate(task("a")).await?;
+    assert_eq!(translator.inner().calls(), 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_translate_stream_replay() -> Result<()> {
+    let translator = CachedTranslator::wrap(MockTranslator::new("T:"), CacheConfig::default());
+
+    for request_id in ["first", "second"] {
+        let mut task = task("Hello");
+        task.request_id = Some(request_id.to_string());
+        let (tx, mut rx) = mpsc::channel(64);
+        translator.translate_stream(task, tx).await?;
+
+        let mut chunks = vec![];
+        while let Some(chunk) = rx.recv().await {
+            chunks.push(chunk);
+        }
+
+        assert!(matches!(chunks[0], TranslateStreamChunk::Start));
+        assert!(
+            matches!(&chunks[1], TranslateStreamChunk::Delta(r)
+                if r.content.as_deref() == Some("T:Hello") && r.request_id.as_deref() == Some(request_id))
+        );
+        assert!(matches!(chunks[2], TranslateStreamChunk::End));
+    }
+
+    assert_eq!(translator.inner().calls(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cached_translate_skips_partial() -> Result<()> {
+    use crate::deadline::{DeadlineConfig, DeadlineTranslator};
+
+    let translator = CachedTranslator::wrap(
+        DeadlineTranslator::wrap(
+            MockTranslator::streaming("T:", Duration::from_millis(50)),
+            DeadlineConfig::default(),
+        ),
+        CacheConfig::default(),
+    );
+
+    let mut late = task("one two three four five");
+    late.deadline_ms = Some(125);
+    for _ in 0..2 {
+        assert!(translator.translate(late.clone()).await?.partial);
+    }
+    assert_eq!(translator.inner().inner().calls(), 2);
+
+    for _ in 0..2 {
+        let (tx, mut rx) = mpsc::channel(64);
+        translator.translate_stream(late.clone(), tx).await?;
+        while rx.recv().await.is_some() {}
+    }
+    assert_eq!(translator.inner().inner().calls(), 4);
+    assert!(translator.is_empty()?);
+
+    // 没有截止时间时完整的译文照常缓存
+    translator.translate(task("one two")).await?;
+    translator.translate(task("one two")).await?;
+    assert_eq!(translator.inner().inner().calls(), 5);
+    assert_eq!(translator.len()?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_cache_export_import() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("xtranslator-cache-{}.json", now_millis()));
+
+    let source = MemoryCache::new(16);
+    for content in ["a", "b"] {
+        source.put(CacheRecord::new(
+            CacheKey::new("mock", &task(content))?,
+            TranslateResult {
+                reasoning: None,
+                content: Some(content.to_uppercase()),
+                ..Default::default()
+            },
+        ))?;
+    }
+    source.export_to_file(&path)?;
+
+    let target = MemoryCache::new(16);
+    target.import_from_file(&path)?;
+    std::fs::remove_file(&path)?;
+
+    assert_eq!(target.len()?, 2);
+    let record = target.get(&CacheKey::new("mock", &task("b"))?)?.unwrap();
+    assert_eq!(record.result.content.as_deref(), Some("B"));
+
+    Ok(())
+}
+
+#[cfg(feature = "sled")]
+#[tokio::test]
+async fn test_sled_cache() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("xtranslator-sled-{}", now_millis()));
+
+    {
+        let store = Box::new(SledCache::open(&path)?);
+        let translator =
+            CachedTranslator::with_store(MockTranslator::new("T:"), CacheConfig::default(), store);
+        translator.translate(task("Hello")).await?;
+    }
+
+    let store = Box::new(SledCache::open(&path)?);
+    let translator =
+        CachedTranslator::with_store(MockTranslator::new("T:"), CacheConfig::default(), store);
+    let result = translator.translate(task("Hello")).await?;
+    drop(translator);
+    std::fs::remove_dir_all(&path)?;
+
+    assert_eq!(result.content.as_deref(), Some("T:Hello"));
+
+    // 同一路径下配置不同的翻译器不共用条目
+    let config = |prefix: &str| json!({ "prefix": prefix, "cache": { "backend": "sled", "path": path } });
+    {
+        let translator = CachedTranslator::<MockTranslator>::new(config("A:")).await?;
+        translator.translate(task("Hello")).await?;
+    }
+    let translator = CachedTranslator::<MockTranslator>::new(config("B:")).await?;
+    let result = translator.translate(task("Hello")).await?;
+    drop(translator);
+    std::fs::remove_dir_all(&path)?;
+
+    assert_eq!(result.content.as_deref(), Some("B:Hello"));
+
+    Ok(())
+}
@@ -0,0 +1,444 @@
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::utils::split_sentences;
+use crate::{
+    Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, TranslatedItem, Translator,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkConfig {
+    /// 每段最多字符数，未设置时使用翻译器 `capabilities().max_input_chars`
+    pub max_chars: Option<usize>,
+    /// 将上一段的原文与译文作为参考译文带入下一段，仅在顺序翻译时生效
+    #[serde(default = "default_context")]
+    pub context: bool,
+    /// 并行翻译的段数
+    #[serde(default = "default_parallel")]
+    pub parallel: usize,
+}
+
+fn default_context() -> bool {
+    true
+}
+
+fn default_parallel() -> usize {
+    1
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        ChunkConfig {
+            max_chars: None,
+            context: default_context(),
+            parallel: default_parallel(),
+        }
+    }
+}
+
+/// 一段原文，`sentences` 拼接后即为该段内容
+#[derive(Debug, Clone, Default)]
+pub struct Chunk {
+    pub sentences: Vec<String>,
+}
+
+impl Chunk {
+    pub fn content(&self) -> String {
+        self.sentences.concat()
+    }
+}
+
+/// 按句子将文本切分为不超过 `max_chars` 个字符的段，过长的句子在空白处或直接按字符截断
+pub fn chunk(text: &str, max_chars: usize) -> Vec<Chunk> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = vec![];
+    let mut current = Chunk::default();
+    let mut length = 0;
+
+    let sentences = split_sentences(text)
+        .into_iter()
+        .flat_map(|sentence| split_long(&sentence, max_chars));
+
+    for sentence in sentences {
+        let count = sentence.chars().count();
+        if length + count > max_chars && !current.sentences.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            length = 0;
+        }
+        length += count;
+        current.sentences.push(sentence);
+    }
+
+    if !current.sentences.is_empty() {
+        chunks.push(current);
+    }
+
+    chunks
+}
+
+fn split_long(sentence: &str, max_chars: usize) -> Vec<String> {
+    let mut parts = vec![];
+    let mut rest = sentence;
+
+    while rest.chars().count() > max_chars {
+        let limit = rest
+            .char_indices()
+            .nth(max_chars)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let end = rest[..limit]
+            .rfind(char::is_whitespace)
+            .map(|i| i + rest[i..].chars().next().map(char::len_utf8).unwrap_or(1))
+            .unwrap_or(limit);
+
+        parts.push(rest[..end].to_string());
+        rest = &rest[end..];
+    }
+
+    if !rest.is_empty() {
+        parts.push(rest.to_string());
+    }
+
+    parts
+}
+
+/// 将原文拆成首尾空白与正文，空白不发送给翻译服务
+fn split_padding(text: &str) -> (&str, &str, &str) {
+    let start = text.len() - text.trim_start().len();
+    let end = text.trim_end().len().max(start);
+    (&text[..start], &text[start..end], &text[end..])
+}
+
+/// 超出长度限制时自动分段翻译并拼接结果
+pub struct ChunkedTranslator<T> {
+    inner: T,
+    config: ChunkConfig,
+}
+
+impl<T: Translator> ChunkedTranslator<T> {
+    pub fn wrap(inner: T, config: ChunkConfig) -> Self {
+        ChunkedTranslator { inner, config }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn max_chars(&self) -> Option<usize> {
+        self.config
+            .max_chars
+            .or(self.inner.capabilities().max_input_chars)
+    }
+
+    /// 需要分段时返回各段，否则返回 `None`
+    fn chunks(&self, task: &TranslateTask) -> Option<Vec<Chunk>> {
+        let max_chars = self.max_chars()?;
+        if task.content.chars().count() <= max_chars {
+            return None;
+        }
+        Some(chunk(&task.content, max_chars))
+    }
+
+    fn chunk_task(
+        &self,
+        task: &TranslateTask,
+        content: &str,
+        context: Option<TranslatedItem>,
+    ) -> TranslateTask {
+        let mut task = task.clone();
+        task.content = content.to_string();
+        if let Some(context) = context {
+            task.references.push(context);
+        }
+        task
+    }
+
+    fn context(&self, chunk: &Chunk, translation: &str) -> Option<TranslatedItem> {
+        let source = chunk.content().trim().to_string();
+        let target = translation.trim().to_string();
+
+        (self.config.context && !source.is_empty() && !target.is_empty())
+            .then_some(TranslatedItem { source, target })
+    }
+
+    async fn translate_chunk(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let (leading, content, trailing) = split_padding(&task.content);
+        if content.is_empty() {
+            return Ok(TranslateResult {
+                content: Some(task.content.clone()),
+                ..Default::default()
+            });
+        }
+
+        let (leading, trailing) = (leading.to_string(), trailing.to_string());
+        let mut task = task.clone();
+        task.content = content.to_string();
+
+        let mut result = self.inner.translate(task).await?;
+        result.content = result
+            .content
+            .map(|content| format!("{}{}{}", leading, content, trailing));
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl<T> Translator for ChunkedTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 分段参数读取自 `config["chunk"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let chunk_config = match config.get("chunk") {
+            Some(chunk) => serde_json::from_value(chunk.clone())?,
+            None => ChunkConfig::default(),
+        };
+
+        Ok(ChunkedTranslator::wrap(T::new(config).await?, chunk_config))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    /// 分段后不再有长度限制
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = self.inner.capabilities();
+        capabilities.max_input_chars = None;
+        capabilities
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let Some(chunks) = self.chunks(&task) else {
+            return self.inner.translate(task).await;
+        };
+
+        let results: Vec<TranslateResult> = if self.config.parallel > 1 {
+            let tasks: Vec<TranslateTask> = chunks
+                .iter()
+                .map(|chunk| self.chunk_task(&task, &chunk.content(), None))
+                .collect();
+
+            stream::iter(tasks.into_iter().map(|task| self.translate_chunk(task)))
+                .buffered(self.config.parallel)
+                .try_collect()
+                .await?
+        } else {
+            let mut results: Vec<TranslateResult> = vec![];
+            for (i, chunk) in chunks.iter().enumerate() {
+                let context = i.checked_sub(1).and_then(|prev| {
+                    self.context(
+                        &chunks[prev],
+                        results[prev].content.as_deref().unwrap_or(""),
+                    )
+                });
+                let result = self
+                    .translate_chunk(self.chunk_task(&task, &chunk.content(), context))
+                    .await?;
+                results.push(result);
+            }
+            results
+        };
+
+        let mut merged = TranslateResult::default();
+        for result in results {
+            if let Some(s) = result.reasoning {
+                merged
+                    .reasoning
+                    .get_or_insert_with(String::new)
+                    .push_str(&s);
+            }
+            merged
+                .content
+                .get_or_insert_with(String::new)
+                .push_str(result.content.as_deref().unwrap_or(""));
+            if result.provider.is_some() {
+                merged.provider = result.provider;
+            }
+            // 各段是独立的请求，保留第一段的请求 ID
+            if merged.request_id.is_none() {
+                merged.request_id = result.request_id;
+            }
+            if let Some(usage) = &result.usage {
+                merged.add_usage(usage);
+            }
+            merged.add_confidence(result.confidence);
+        }
+
+        Ok(merged)
+    }
+
+    /// 逐段流式翻译，只输出一次 Start 与 End
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let Some(chunks) = self.chunks(&task) else {
+            return self.inner.translate_stream(task, sender).await;
+        };
+
+        sender.send(TranslateStreamChunk::Start).await?;
+
+        let mut previous: Option<String> = None;
+        for (i, chunk) in chunks.iter().enumerate() {
+            let context = i
+                .checked_sub(1)
+                .and_then(|prev| self.context(&chunks[prev], previous.as_deref().unwrap_or("")));
+            let chunk_task = self.chunk_task(&task, &chunk.content(), context);
+            let (leading, content, trailing) = split_padding(&chunk_task.content);
+
+            let delta = |content: &str| {
+                TranslateStreamChunk::Delta(TranslateResult {
+                    content: Some(content.to_string()),
+                    ..Default::default()
+                })
+            };
+
+            if content.is_empty() {
+                sender.send(delta(&chunk_task.content)).await?;
+                continue;
+            }
+
+            if !leading.is_empty() {
+                sender.send(delta(leading)).await?;
+            }
+
+            let mut inner_task = chunk_task.clone();
+            inner_task.content = content.to_string();
+
+            let (tx, mut rx) = mpsc::channel(64);
+            let forward = async {
+                let mut translation = String::new();
+                while let Some(chunk) = rx.recv().await {
+                    if let TranslateStreamChunk::Delta(delta) = chunk {
+                        if let Some(s) = &delta.content {
+                            translation.push_str(s);
+                        }
+                        sender.send(TranslateStreamChunk::Delta(delta)).await?;
+                    }
+                }
+                Ok::<_, anyhow::Error>(translation)
+            };
+
+            let (result, forwarded) =
+                tokio::join!(self.inner.translate_stream(inner_task, tx), forward);
+            result?;
+            previous = Some(forwarded?);
+
+            if !trailing.is_empty() {
+                sender.send(delta(trailing)).await?;
+            }
+        }
+
+        sender.send(TranslateStreamChunk::End).await?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_chunk() {
+    let text = "First sentence. Second sentence. Third one here.";
+    let chunks = chunk(text, 20);
+    assert_eq!(
+        chunks.iter().map(|c| c.content()).collect::<Vec<_>>(),
+        vec!["First sentence. ", "Second sentence. ", "Third one here."]
+    );
+
+    let chunks = chunk(text, 40);
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks.iter().map(|c| c.content()).collect::<String>(), text);
+
+    let long = chunk("abcdefghij klmnop", 8);
+    assert_eq!(
+        long.iter().map(|c| c.content()).collect::<Vec<_>>(),
+        vec!["abcdefgh", "ij ", "klmnop"]
+    );
+}
+
+#[tokio::test]
+async fn test_chunked_translator() -> Result<()> {
+    let content = "First sentence. Second sentence. Third one here.";
+
+    for parallel in [1, 3] {
+        let translator = ChunkedTranslator::wrap(
+            MockTranslator::new("T:"),
+            ChunkConfig {
+                max_chars: Some(20),
+                parallel,
+                ..Default::default()
+            },
+        );
+
+        let result = translator.translate(task(content)).await?;
+        assert_eq!(
+            result.content.as_deref(),
+            Some("T:First sentence. T:Second sentence. T:Third one here.")
+        );
+        assert_eq!(translator.inner().calls(), 3);
+        assert_eq!(result.usage.map(|u| u.characters), Some(46));
+    }
+
+    let translator = ChunkedTranslator::wrap(
+        MockTranslator::new("T:"),
+        ChunkConfig {
+            max_chars: Some(20),
+            ..Default::default()
+        },
+    );
+    let (tx, mut rx) = mpsc::channel(64);
+    translator.translate_stream(task(content), tx).await?;
+
+    let mut chunks = vec![];
+    while let Some(chunk) = rx.recv().await {
+        chunks.push(chunk);
+    }
+    assert!(matches!(chunks.first(), Some(TranslateStreamChunk::Start)));
+    assert!(matches!(chunks.last(), Some(TranslateStreamChunk::End)));
+    let text: String = chunks
+        .iter()
+        .filter_map(|c| match c {
+            TranslateStreamChunk::Delta(d) => d.content.clone(),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        text,
+        "T:First sentence. T:Second sentence. T:Third one here."
+    );
+
+    // 未超出长度时直接透传
+    let result = translator.translate(task("Short.")).await?;
+    assert_eq!(result.content.as_deref(), Some("T:Short."));
+
+    Ok(())
+}
@@ -0,0 +1,379 @@
+#[cfg(test)]
+use crate::error::XTranslateError;
+use crate::ffi_proxy::ProxyTranslatorFactory;
+use crate::retry::ErrorClass;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{
+    BoxedTranslator, Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask,
+    Translator, TranslatorFactory, TranslatorSpec,
+};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::mpsc::Sender;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitConfig {
+    /// 连续失败多少次后断开
+    pub failure_threshold: u32,
+    /// 断开后等待多久放行一个试探请求（毫秒）
+    pub cooldown_ms: u64,
+    /// 断开期间改用的翻译器
+    pub fallback: Option<TranslatorSpec>,
+}
+
+impl Default for CircuitConfig {
+    fn default() -> Self {
+        CircuitConfig {
+            failure_threshold: 5,
+            cooldown_ms: 30_000,
+            fallback: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// 正常放行
+    Closed,
+    /// 直接拒绝请求
+    Open,
+    /// 冷却结束，试探请求进行中
+    HalfOpen,
+}
+
+/// 熔断期间的请求被拒绝，未发送到服务
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("circuit open, retry in {} ms", retry_in.as_millis())]
+pub struct CircuitOpen {
+    pub retry_in: Duration,
+}
+
+impl CircuitOpen {
+    /// 从错误链中找出 `CircuitOpen`
+    pub fn find(err: &anyhow::Error) -> Option<&CircuitOpen> {
+        err.chain().find_map(|e| e.downcast_ref::<CircuitOpen>())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Breaker {
+    failures: u32,
+    opened_at: Option<Instant>,
+    probing: bool,
+}
+
+/// 请求被取消时结束试探，避免一直停留在半开状态
+struct ProbeGuard<'a>(&'a Mutex<Breaker>);
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut breaker) = self.0.lock() {
+            breaker.probing = false;
+        }
+    }
+}
+
+/// 熔断器：连续出现限流、5xx、网络错误或超时后断开，冷却期内直接返回 `CircuitOpen`
+/// 或转给 `fallback`，冷却结束后放行一个试探请求，成功则恢复。
+/// 鉴权失败等与服务可用性无关的错误不计入
+pub struct CircuitBreaker<T> {
+    inner: T,
+    config: CircuitConfig,
+    fallback: Option<BoxedTranslator>,
+    breaker: Mutex<Breaker>,
+}
+
+impl<T: Translator> CircuitBreaker<T> {
+    pub fn wrap(inner: T, config: CircuitConfig) -> Self {
+        CircuitBreaker {
+            inner,
+            config,
+            fallback: None,
+            breaker: Mutex::new(Breaker::default()),
+        }
+    }
+
+    pub fn with_fallback(mut self, fallback: BoxedTranslator) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let breaker = self.breaker.lock().unwrap();
+        match breaker.opened_at {
+            None => CircuitState::Closed,
+            Some(_) if breaker.probing => CircuitState::HalfOpen,
+            Some(opened_at) if opened_at.elapsed() >= self.cooldown() => CircuitState::HalfOpen,
+            Some(_) => CircuitState::Open,
+        }
+    }
+
+    fn cooldown(&self) -> Duration {
+        Duration::from_millis(self.config.cooldown_ms)
+    }
+
+    /// 断开时返回 `CircuitOpen`，放行试探请求时返回 `true`
+    fn admit(&self) -> Result<bool, CircuitOpen> {
+        let mut breaker = self.breaker.lock().unwrap();
+        let Some(opened_at) = breaker.opened_at else {
+            return Ok(false);
+        };
+
+        let elapsed = opened_at.elapsed();
+        if elapsed < self.cooldown() {
+            return Err(CircuitOpen {
+                retry_in: self.cooldown() - elapsed,
+            });
+        }
+
+        // 同一时间只放行一个试探请求
+        if breaker.probing {
+            return Err(CircuitOpen {
+                retry_in: Duration::ZERO,
+            });
+        }
+        breaker.probing = true;
+
+        Ok(true)
+    }
+
+    fn record<R>(&self, result: &Result<R>, probing: bool) {
+        let mut breaker = self.breaker.lock().unwrap();
+        if probing {
+            breaker.probing = false;
+        }
+
+        match result {
+            Ok(_) => {
+                breaker.failures = 0;
+                breaker.opened_at = None;
+            }
+            // 与服务可用性无关的错误既不计入也不能说明服务已恢复，状态不变
+            Err(e) if ErrorClass::of(e) == ErrorClass::Permanent => {}
+            Err(_) => {
+                breaker.failures += 1;
+                if probing || breaker.failures >= self.config.failure_threshold {
+                    breaker.opened_at = Some(Instant::now());
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T> Translator for CircuitBreaker<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 参数读取自 `config["circuit_breaker"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let circuit_config: CircuitConfig = match config.get("circuit_breaker") {
+            Some(circuit) => serde_json::from_value(circuit.clone())?,
+            None => CircuitConfig::default(),
+        };
+
+        let fallback = match &circuit_config.fallback {
+            Some(spec) => {
+                let factory = ProxyTranslatorFactory::from_config(&config)?;
+                Some(factory.create(&spec.name, spec.config.clone()).await?)
+            }
+            None => None,
+        };
+
+        let mut translator = CircuitBreaker::wrap(T::new(config).await?, circuit_config);
+        translator.fallback = fallback;
+
+        Ok(translator)
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let probing = match self.admit() {
+            Ok(probing) => probing,
+            Err(open) => {
+                return match &self.fallback {
+                    Some(fallback) => fallback.translate(task).await,
+                    None => bail!(open),
+                }
+            }
+        };
+
+        let _probe = probing.then(|| ProbeGuard(&self.breaker));
+        let result = self.inner.translate(task).await;
+        self.record(&result, probing);
+
+        result
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let probing = match self.admit() {
+            Ok(probing) => probing,
+            Err(open) => {
+                return match &self.fallback {
+                    Some(fallback) => fallback.translate_stream(task, sender).await,
+                    None => bail!(open),
+                }
+            }
+        };
+
+        let _probe = probing.then(|| ProbeGuard(&self.breaker));
+        let result = self.inner.translate_stream(task, sender).await;
+        self.record(&result, probing);
+
+        result
+    }
+}
+
+#[tokio::test]
+async fn test_circuit_breaker() -> Result<()> {
+    let network = || XTranslateError::Network("connection refused".to_string());
+    let translator = CircuitBreaker::wrap(
+        MockTranslator::flaky("T:", vec![network(), network(), network()]),
+        CircuitConfig {
+            failure_threshold: 2,
+            cooldown_ms: 50,
+            fallback: None,
+        },
+    );
+
+    assert!(translator.translate(task("Hello")).await.is_err());
+    assert_eq!(translator.state(), CircuitState::Closed);
+    assert!(translator.translate(task("Hello")).await.is_err());
+    assert_eq!(translator.state(), CircuitState::Open);
+
+    // 断开期间不调用服务
+    let err = translator.translate(task("Hello")).await.unwrap_err();
+    assert!(CircuitOpen::find(&err).is_some());
+    assert_eq!(translator.inner().calls(), 2);
+
+    // 试探失败后重新断开
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(translator.state(), CircuitState::HalfOpen);
+    assert!(translator.translate(task("Hello")).await.is_err());
+    assert_eq!(translator.state(), CircuitState::Open);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("T:Hello"));
+    assert_eq!(translator.state(), CircuitState::Closed);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_fallback() -> Result<()> {
+    let translator = CircuitBreaker::wrap(
+        MockTranslator::flaky("", vec![XTranslateError::from_status(503, "unavailable")]),
+        CircuitConfig {
+            failure_threshold: 1,
+            ..Default::default()
+        },
+    )
+    .with_fallback(Box::new(MockTranslator::new("B:")));
+
+    assert!(translator.translate(task("Hello")).await.is_err());
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("B:Hello"));
+    assert_eq!(translator.inner().calls(), 1);
+
+    // 鉴权失败不计入
+    let translator = CircuitBreaker::wrap(
+        MockTranslator::flaky("", vec![XTranslateError::AuthFailed("bad key".to_string())]),
+        CircuitConfig {
+            failure_threshold: 1,
+            ..Default::default()
+        },
+    );
+    assert!(translator.translate(task("Hello")).await.is_err());
+    assert_eq!(translator.state(), CircuitState::Closed);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_permanent_error() -> Result<()> {
+    let network = || XTranslateError::Network("connection refused".to_string());
+    let auth = || XTranslateError::AuthFailed("bad key".to_string());
+
+    // 鉴权失败不清零连续失败次数
+    let translator = CircuitBreaker::wrap(
+        MockTranslator::flaky("", vec![network(), auth(), network()]),
+        CircuitConfig {
+            failure_threshold: 2,
+            cooldown_ms: 50,
+            fallback: None,
+        },
+    );
+    for _ in 0..3 {
+        assert!(translator.translate(task("Hello")).await.is_err());
+    }
+    assert_eq!(translator.state(), CircuitState::Open);
+
+    // 试探请求鉴权失败时不恢复，下一个试探成功后才闭合
+    let translator = CircuitBreaker::wrap(
+        MockTranslator::flaky("T:", vec![network(), auth()]),
+        CircuitConfig {
+            failure_threshold: 1,
+            cooldown_ms: 50,
+            fallback: None,
+        },
+    );
+    assert!(translator.translate(task("Hello")).await.is_err());
+    assert_eq!(translator.state(), CircuitState::Open);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(translator.translate(task("Hello")).await.is_err());
+    assert_eq!(translator.state(), CircuitState::HalfOpen);
+    assert_eq!(translator.inner().calls(), 2);
+
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("T:Hello"));
+    assert_eq!(translator.state(), CircuitState::Closed);
+
+    Ok(())
+}
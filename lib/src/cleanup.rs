@@ -0,0 +1,296 @@
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::Result;
+use async_trait::async_trait;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use serde_json::json;
+use serde_json::Value;
+use std::sync::LazyLock;
+use tokio::sync::mpsc::Sender;
+
+/// 模型常在译文前加的标签
+const LABELS: &[&str] = &[
+    "translation",
+    "translated text",
+    "here is the translation",
+    "here's the translation",
+    "译文",
+    "翻译",
+    "翻译结果",
+    "以下是译文",
+    "以下是翻译",
+];
+
+/// 包裹整段译文的引号
+const QUOTES: &[(char, char)] = &[
+    ('"', '"'),
+    ('“', '”'),
+    ('「', '」'),
+    ('『', '』'),
+    ('《', '》'),
+];
+
+static CODE_FENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)^```[^\n`]*\n(.*?)\n?```$").unwrap());
+
+static EXPLANATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^\s*[(（]?\s*(note|notes|explanation|注|注释|注意|说明|解释|备注)\s*[:：]")
+        .unwrap()
+});
+
+/// 译文后处理配置，各项默认开启
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct CleanupConfig {
+    /// 去掉开头的 `Translation:`、`译文：` 等标签
+    pub strip_labels: bool,
+    /// 去掉包裹整段译文的引号，原文本身带引号时保留
+    pub strip_quotes: bool,
+    /// 去掉包裹整段译文的 Markdown 代码块，原文本身是代码块时保留
+    pub strip_code_fences: bool,
+    /// 去掉译文后另起一段的解释，如 `Note: ...`、`（注：...）`
+    pub strip_explanations: bool,
+    /// 额外需要去掉的标签，不区分大小写，不含冒号
+    pub labels: Vec<String>,
+}
+
+impl Default for CleanupConfig {
+    fn default() -> Self {
+        CleanupConfig {
+            strip_labels: true,
+            strip_quotes: true,
+            strip_code_fences: true,
+            strip_explanations: true,
+            labels: vec![],
+        }
+    }
+}
+
+impl CleanupConfig {
+    fn strip_label<'a>(&self, text: &'a str) -> Option<&'a str> {
+        let lower = text.to_lowercase();
+        // 小写后长度不变时才能按字节截取
+        if lower.len() != text.len() {
+            return None;
+        }
+
+        LABELS
+            .iter()
+            .copied()
+            .chain(self.labels.iter().map(|s| s.as_str()))
+            .find_map(|label| {
+                let rest = lower.strip_prefix(&label.to_lowercase())?;
+                let rest = rest.trim_start_matches([' ', '\t']);
+                let rest = rest.strip_prefix(':').or_else(|| rest.strip_prefix('：'))?;
+                Some(text[text.len() - rest.len()..].trim_start())
+            })
+    }
+}
+
+fn strip_code_fence(text: &str) -> Option<&str> {
+    CODE_FENCE
+        .captures(text)
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str().trim())
+}
+
+fn strip_quotes(text: &str) -> Option<&str> {
+    QUOTES.iter().find_map(|(open, close)| {
+        let inner = text.strip_prefix(*open)?.strip_suffix(*close)?;
+        // `"a" 和 "b"` 这类并非整段包裹
+        if inner.contains(*open) || inner.contains(*close) {
+            return None;
+        }
+        Some(inner.trim())
+    })
+}
+
+fn strip_explanation(text: &str) -> Option<&str> {
+    let mut offset = 0;
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        if i > 0 && EXPLANATION.is_match(line) {
+            return Some(text[..offset].trim_end());
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// 去掉大模型译文中常见的多余内容，`source` 为原文，用于保留原文本身就有的引号、代码块与注释
+pub fn clean_output(text: &str, source: &str, config: &CleanupConfig) -> String {
+    let source = source.trim();
+    let keep_fence = strip_code_fence(source).is_some();
+    let keep_quotes = strip_quotes(source).is_some();
+    let keep_explanation = source.lines().any(|line| EXPLANATION.is_match(line));
+
+    let mut text = text.trim();
+
+    if config.strip_explanations && !keep_explanation {
+        if let Some(stripped) = strip_explanation(text) {
+            text = stripped;
+        }
+    }
+
+    // 标签、代码块与引号可能相互嵌套，如 `译文：```...```
+    loop {
+        let stripped = if config.strip_labels {
+            config.strip_label(text)
+        } else {
+            None
+        }
+        .or_else(|| (config.strip_code_fences && !keep_fence).then(|| strip_code_fence(text))?)
+        .or_else(|| (config.strip_quotes && !keep_quotes).then(|| strip_quotes(text))?);
+
+        match stripped {
+            Some(stripped) if !stripped.is_empty() => text = stripped,
+            _ => break,
+        }
+    }
+
+    text.to_string()
+}
+
+/// 对译文做后处理，去掉标签、引号、代码块与解释。
+/// 流式翻译无法预知后续内容，原样转发
+pub struct CleanupTranslator<T> {
+    inner: T,
+    config: CleanupConfig,
+}
+
+impl<T> CleanupTranslator<T> {
+    pub fn wrap(inner: T, config: CleanupConfig) -> Self {
+        CleanupTranslator { inner, config }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<T> Translator for CleanupTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 后处理参数读取自 `config["cleanup"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let cleanup_config = match config.get("cleanup") {
+            Some(cleanup) => serde_json::from_value(cleanup.clone())?,
+            None => CleanupConfig::default(),
+        };
+
+        let inner = T::new(config).await?;
+
+        Ok(CleanupTranslator::wrap(inner, cleanup_config))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let source = task.content.clone();
+        let mut result = self.inner.translate(task).await?;
+        result.content = result
+            .content
+            .map(|content| clean_output(&content, &source, &self.config));
+        Ok(result)
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        self.inner.translate_stream(task, sender).await
+    }
+}
+
+#[test]
+fn test_clean_output() {
+    let config = CleanupConfig::default();
+    let clean = |text: &str, source: &str| clean_output(text, source, &config);
+
+    assert_eq!(clean("Translation: 你好", "Hello"), "你好");
+    assert_eq!(clean("译文：你好", "Hello"), "你好");
+    assert_eq!(clean("\"你好\"", "Hello"), "你好");
+    assert_eq!(clean("“你好”", "Hello"), "你好");
+    assert_eq!(clean("```\n你好\n```", "Hello"), "你好");
+    assert_eq!(clean("译文：\n```text\n“你好”\n```", "Hello"), "你好");
+    assert_eq!(clean("你好\n\n注：这是一句问候语。", "Hello"), "你好");
+    assert_eq!(
+        clean("Bonjour\n\n(Note: formal greeting)", "你好"),
+        "Bonjour"
+    );
+
+    // 原文本身带有的内容保留
+    assert_eq!(clean("“你好”", "\"Hello\""), "“你好”");
+    assert_eq!(
+        clean("```\nprint(1)\n```", "```\nprint(1)\n```"),
+        "```\nprint(1)\n```"
+    );
+    assert_eq!(
+        clean("你好\n注：示例", "Hello\nNote: sample"),
+        "你好\n注：示例"
+    );
+    assert_eq!(clean("“甲”和“乙”", "A and B"), "“甲”和“乙”");
+    assert_eq!(clean("翻译很难", "Translating is hard"), "翻译很难");
+
+    let config = CleanupConfig {
+        strip_quotes: false,
+        labels: vec!["Output".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(
+        clean_output("output: \"你好\"", "Hello", &config),
+        "\"你好\""
+    );
+}
+
+#[tokio::test]
+async fn test_cleanup_translator() -> Result<()> {
+    let translator: CleanupTranslator<MockTranslator> =
+        CleanupTranslator::new(json!({ "prefix": "Translation: ", "cleanup": {} })).await?;
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("Hello"));
+
+    let translator: CleanupTranslator<MockTranslator> = CleanupTranslator::new(json!({
+        "prefix": "Translation: ",
+        "cleanup": { "strip_labels": false }
+    }))
+    .await?;
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("Translation: Hello"));
+
+    Ok(())
+}
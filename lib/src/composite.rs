@@ -0,0 +1,304 @@
+use crate::ffi_proxy::ProxyTranslatorFactory;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{
+    BoxedTranslator, Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask,
+    Translator, TranslatorFactory, TranslatorSpec,
+};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use language_tags::LanguageTag;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+#[cfg(test)]
+use serde_json::json;
+use tokio::sync::mpsc::Sender;
+
+/// 路由规则，所有条件都满足时使用 `translator`，未设置的条件视为匹配
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteRule {
+    /// 目标翻译器名称，对应 `CompositeConfig::translators` 中的键
+    pub translator: String,
+    /// 源语言，如 `zh`、`zh-TW`，`*` 匹配任意语言
+    #[serde(default)]
+    pub source: Vec<String>,
+    /// 目标语言
+    #[serde(default)]
+    pub target: Vec<String>,
+    /// 领域，对应 `TranslateTask::field`
+    #[serde(default)]
+    pub field: Vec<String>,
+    /// 最小原文长度（字符数）
+    pub min_length: Option<usize>,
+    /// 最大原文长度（字符数）
+    pub max_length: Option<usize>,
+}
+
+impl RouteRule {
+    pub fn matches(&self, task: &TranslateTask) -> bool {
+        let length = task.content.chars().count();
+
+        match_language(&self.source, task.source_language.as_ref())
+            && match_language(&self.target, task.target_language.as_ref())
+            && (self.field.is_empty()
+                || task
+                    .field
+                    .as_ref()
+                    .map(|field| self.field.iter().any(|f| f == field))
+                    .unwrap_or(false))
+            && self.min_length.map(|min| length >= min).unwrap_or(true)
+            && self.max_length.map(|max| length <= max).unwrap_or(true)
+    }
+}
+
+/// 语言模式按子标签前缀匹配，`zh` 可匹配 `zh-CN`
+fn match_language(patterns: &[String], tag: Option<&LanguageTag>) -> bool {
+    if patterns.is_empty() || patterns.iter().any(|p| p == "*") {
+        return true;
+    }
+
+    let Some(tag) = tag else {
+        return false;
+    };
+    let tag = tag.as_str().to_lowercase();
+
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.to_lowercase();
+        tag == pattern || tag.starts_with(&format!("{}-", pattern))
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompositeConfig {
+    /// 可供路由的翻译器，键为规则中引用的名称
+    pub translators: HashMap<String, TranslatorSpec>,
+    /// 按顺序匹配的路由规则
+    #[serde(default)]
+    pub rules: Vec<RouteRule>,
+    /// 没有规则匹配时使用的翻译器
+    pub default: Option<String>,
+}
+
+/// 按语言对、领域和原文长度将任务路由到不同的翻译器
+pub struct CompositeTranslator {
+    translators: HashMap<String, BoxedTranslator>,
+    rules: Vec<RouteRule>,
+    default: Option<String>,
+}
+
+impl CompositeTranslator {
+    pub fn with_translators(
+        translators: HashMap<String, BoxedTranslator>,
+        rules: Vec<RouteRule>,
+        default: Option<String>,
+    ) -> Result<Self> {
+        for name in rules
+            .iter()
+            .map(|rule| &rule.translator)
+            .chain(default.iter())
+        {
+            if !translators.contains_key(name) {
+                bail!("unknown translator in route: {}", name);
+            }
+        }
+
+        Ok(CompositeTranslator {
+            translators,
+            rules,
+            default,
+        })
+    }
+
+    pub async fn from_config(config: &Value, factory: &dyn TranslatorFactory) -> Result<Self> {
+        let config: CompositeConfig = serde_json::from_value(config.clone())?;
+
+        let mut translators = HashMap::new();
+        for (key, spec) in config.translators {
+            let translator = factory.create(&spec.name, spec.config).await?;
+            translators.insert(key, translator);
+        }
+
+        CompositeTranslator::with_translators(translators, config.rules, config.default)
+    }
+
+    /// 返回任务命中的翻译器名称
+    pub fn route(&self, task: &TranslateTask) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(task))
+            .map(|rule| rule.translator.as_str())
+            .or(self.default.as_deref())
+    }
+
+    fn select(&self, task: &TranslateTask) -> Result<(&str, &BoxedTranslator)> {
+        let name = self
+            .route(task)
+            .ok_or(anyhow!("no route matches task {}", task.id))?;
+        let translator = self
+            .translators
+            .get(name)
+            .ok_or(anyhow!("unknown translator in route: {}", name))?;
+
+        Ok((name, translator))
+    }
+
+    fn merge_languages(
+        &self,
+        f: impl Fn(&BoxedTranslator) -> Result<Vec<String>>,
+    ) -> Result<Vec<String>> {
+        let mut list: Vec<String> = vec![];
+        for translator in self.translators.values() {
+            for lang in f(translator)? {
+                if !list.contains(&lang) {
+                    list.push(lang);
+                }
+            }
+        }
+        Ok(list)
+    }
+}
+
+#[async_trait]
+impl Translator for CompositeTranslator {
+    type This = Self;
+
+    async fn new(config: Value) -> Result<Self> {
+        let factory = ProxyTranslatorFactory::from_config(&config)?;
+        CompositeTranslator::from_config(&config, &factory).await
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.merge_languages(|t| t.get_supported_input_languages())
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.merge_languages(|t| t.get_supported_output_languages())
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        Ok(self
+            .translators
+            .values()
+            .any(|t| t.is_supported_input_language(lang.clone()).unwrap_or(false)))
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        Ok(self
+            .translators
+            .values()
+            .any(|t| t.is_supported_output_language(lang.clone()).unwrap_or(false)))
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        Ok(self.translators.values().any(|t| {
+            t.is_supported_pair(source.clone(), target.clone())
+                .unwrap_or(false)
+        }))
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        let mut pairs = vec![];
+        for translator in self.translators.values() {
+            for pair in translator.get_supported_pairs()? {
+                if !pairs.contains(&pair) {
+                    pairs.push(pair);
+                }
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// 各路由的能力可能不同，以默认翻译器为准
+    fn capabilities(&self) -> Capabilities {
+        self.default
+            .as_ref()
+            .and_then(|name| self.translators.get(name))
+            .map(|translator| translator.capabilities())
+            .unwrap_or_default()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let (name, translator) = self.select(&task)?;
+
+        let mut result = translator.translate(task).await?;
+        if result.provider.is_none() {
+            result.provider = Some(name.to_string());
+        }
+
+        Ok(result)
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let (_, translator) = self.select(&task)?;
+        translator.translate_stream(task, sender).await
+    }
+}
+
+#[test]
+fn test_route_rule() -> Result<()> {
+    let rule: RouteRule = serde_json::from_value(json!({
+        "translator": "qwen",
+        "source": ["zh", "en"],
+        "target": ["zh", "en"],
+        "max_length": 10,
+    }))?;
+
+    assert!(rule.matches(&task("Hello")));
+    assert!(!rule.matches(&task("Hello, world!")));
+
+    let mut ja = task("こんにちは");
+    ja.source_language = Some(LanguageTag::parse("ja")?);
+    assert!(!rule.matches(&ja));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_composite_translate() -> Result<()> {
+    let translator = CompositeTranslator::with_translators(
+        HashMap::from([
+            (
+                "qwen".to_string(),
+                Box::new(MockTranslator::new("Q:")) as BoxedTranslator,
+            ),
+            (
+                "deepl".to_string(),
+                Box::new(MockTranslator::new("D:")) as BoxedTranslator,
+            ),
+            (
+                "openai".to_string(),
+                Box::new(MockTranslator::new("O:")) as BoxedTranslator,
+            ),
+        ]),
+        serde_json::from_value(json!([
+            { "translator": "qwen", "source": ["zh", "en"], "target": ["zh", "en"] },
+            { "translator": "deepl", "source": ["ja"], "target": ["zh"] },
+        ]))?,
+        Some("openai".to_string()),
+    )?;
+
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("Q:Hello"));
+    assert_eq!(result.provider.as_deref(), Some("qwen"));
+
+    let mut ja = task("こんにちは");
+    ja.source_language = Some(LanguageTag::parse("ja-JP")?);
+    assert_eq!(translator.route(&ja), Some("deepl"));
+
+    ja.target_language = Some(LanguageTag::parse("ko")?);
+    assert_eq!(translator.route(&ja), Some("openai"));
+
+    assert!(CompositeTranslator::with_translators(
+        HashMap::new(),
+        vec![],
+        Some("missing".to_string())
+    )
+    .is_err());
+
+    Ok(())
+}
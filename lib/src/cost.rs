@@ -0,0 +1,270 @@
+#[cfg(test)]
+use crate::testing::task;
+use crate::{TranslateResult, TranslateTask, Usage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 计费单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BillingUnit {
+    /// 按 token 计费
+    #[default]
+    Tokens,
+    /// 按字符计费
+    Characters,
+}
+
+/// 价格表，单价均为每百万计费单位
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pricing {
+    #[serde(default)]
+    pub unit: BillingUnit,
+    /// 输入单价
+    pub input: f64,
+    /// 输出单价，按字符计费时通常为 0
+    #[serde(default)]
+    pub output: f64,
+    /// 币种，如 `CNY`、`USD`
+    pub currency: String,
+    /// 用于 token 计数的模型名称
+    pub model: Option<String>,
+}
+
+impl Pricing {
+    /// 根据用量计算费用
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        match self.unit {
+            BillingUnit::Tokens => {
+                (usage.prompt_tokens as f64 * self.input
+                    + usage.completion_tokens as f64 * self.output)
+                    / 1_000_000.0
+            }
+            BillingUnit::Characters => usage.characters as f64 * self.input / 1_000_000.0,
+        }
+    }
+
+    /// 在发送请求前估算费用，输出 token 数按与输入相同估计
+    pub fn estimate(&self, task: &TranslateTask) -> CostEstimate {
+        let model = self.model.as_deref();
+
+        let mut prompt_tokens = count_tokens(&task.content, model);
+        for prompt in [&task.system_prompt, &task.user_prompt].into_iter().flatten() {
+            prompt_tokens += count_tokens(prompt, model);
+        }
+        for item in task.terms.iter().chain(task.references.iter()) {
+            prompt_tokens += count_tokens(&item.source, model) + count_tokens(&item.target, model);
+        }
+
+        let usage = Usage {
+            prompt_tokens: prompt_tokens as u64,
+            completion_tokens: count_tokens(&task.content, model) as u64,
+            characters: task.content.chars().count() as u64,
+            ..Default::default()
+        };
+
+        CostEstimate {
+            cost: self.cost(&usage),
+            currency: self.currency.clone(),
+            usage,
+        }
+    }
+}
+
+/// 费用估算结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub usage: Usage,
+    pub cost: f64,
+    pub currency: String,
+}
+
+/// 内置的参考价格，价格可能随时调整，以各服务官方公布为准
+pub fn default_pricing(provider: &str) -> Option<Pricing> {
+    let pricing = match provider {
+        "baidu_fanyi" => Pricing {
+            unit: BillingUnit::Characters,
+            input: 49.0,
+            output: 0.0,
+            currency: "CNY".to_string(),
+            model: None,
+        },
+        "qwen" => Pricing {
+            unit: BillingUnit::Tokens,
+            input: 0.7,
+            output: 1.95,
+            currency: "CNY".to_string(),
+            model: Some("qwen-mt-turbo".to_string()),
+        },
+        "openai" => Pricing {
+            unit: BillingUnit::Tokens,
+            input: 0.15,
+            output: 0.6,
+            currency: "USD".to_string(),
+            model: Some("gpt-4o-mini".to_string()),
+        },
+        _ => return None,
+    };
+
+    Some(pricing)
+}
+
+/// 使用内置参考价格估算费用
+pub fn estimate(provider: &str, task: &TranslateTask) -> Option<CostEstimate> {
+    default_pricing(provider).map(|pricing| pricing.estimate(task))
+}
+
+/// 统计文本的 token 数。启用 `tiktoken` 特性时使用对应模型的分词器，
+/// 否则按每个 CJK 字符 1 个 token、其他字符每 4 个 1 个 token 估算
+pub fn count_tokens(text: &str, model: Option<&str>) -> usize {
+    #[cfg(feature = "tiktoken")]
+    {
+        let bpe = model
+            .and_then(|model| tiktoken_rs::bpe_for_model(model).ok())
+            .unwrap_or_else(tiktoken_rs::o200k_base_singleton);
+        bpe.encode_with_special_tokens(text).len()
+    }
+
+    #[cfg(not(feature = "tiktoken"))]
+    {
+        let _ = model;
+        count_tokens_by_chars(text)
+    }
+}
+
+/// 按字符估算 token 数
+pub fn count_tokens_by_chars(text: &str) -> usize {
+    let (cjk, other) = text.chars().fold((0usize, 0usize), |(cjk, other), c| {
+        if is_wide(c) {
+            (cjk + 1, other)
+        } else {
+            (cjk, other + 1)
+        }
+    });
+
+    cjk + other.div_ceil(4)
+}
+
+fn is_wide(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{11FF}'
+        | '\u{2E80}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FFEF}')
+}
+
+/// 按服务累计实际用量
+#[derive(Debug, Default)]
+pub struct CostTracker {
+    usage: Mutex<HashMap<String, Usage>>,
+    pricing: HashMap<String, Pricing>,
+}
+
+impl CostTracker {
+    pub fn new() -> Self {
+        CostTracker::default()
+    }
+
+    /// 设置服务的价格，未设置时使用 `default_pricing`
+    pub fn with_pricing(mut self, provider: impl Into<String>, pricing: Pricing) -> Self {
+        self.pricing.insert(provider.into(), pricing);
+        self
+    }
+
+    pub fn record(&self, provider: &str, usage: &Usage) {
+        self.usage
+            .lock()
+            .unwrap()
+            .entry(provider.to_string())
+            .or_default()
+            .add(usage);
+    }
+
+    /// 记录翻译结果中携带的用量，没有用量时忽略
+    pub fn record_result(&self, provider: &str, result: &TranslateResult) {
+        if let Some(usage) = &result.usage {
+            self.record(provider, usage);
+        }
+    }
+
+    /// 某个服务的累计用量
+    pub fn usage(&self, provider: &str) -> Usage {
+        self.usage
+            .lock()
+            .unwrap()
+            .get(provider)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// 某个服务的累计费用，没有价格时返回 `None`
+    pub fn cost(&self, provider: &str) -> Option<(f64, String)> {
+        let pricing = self
+            .pricing
+            .get(provider)
+            .cloned()
+            .or_else(|| default_pricing(provider))?;
+        Some((pricing.cost(&self.usage(provider)), pricing.currency))
+    }
+
+    /// 按币种汇总所有服务的费用
+    pub fn total(&self) -> HashMap<String, f64> {
+        let providers: Vec<String> = self.usage.lock().unwrap().keys().cloned().collect();
+
+        let mut total = HashMap::new();
+        for provider in providers {
+            if let Some((cost, currency)) = self.cost(&provider) {
+                *total.entry(currency).or_insert(0.0) += cost;
+            }
+        }
+        total
+    }
+}
+
+#[test]
+fn test_count_tokens() {
+    assert_eq!(count_tokens_by_chars("你好世界"), 4);
+    assert_eq!(count_tokens_by_chars("Hello World!"), 3);
+    assert!(count_tokens("Hello World!", Some("gpt-4o")) > 0);
+}
+
+#[test]
+fn test_cost() {
+    let baidu = estimate("baidu_fanyi", &task("Hello")).unwrap();
+    assert_eq!(baidu.usage.characters, 5);
+    assert_eq!(baidu.currency, "CNY");
+    assert!((baidu.cost - 5.0 * 49.0 / 1_000_000.0).abs() < 1e-12);
+    assert!(estimate("unknown", &task("Hello")).is_none());
+
+    let tracker = CostTracker::new().with_pricing(
+        "custom",
+        Pricing {
+            unit: BillingUnit::Tokens,
+            input: 1.0,
+            output: 2.0,
+            currency: "USD".to_string(),
+            model: None,
+        },
+    );
+    let usage = Usage {
+        prompt_tokens: 1_000_000,
+        completion_tokens: 500_000,
+        characters: 0,
+        ..Default::default()
+    };
+    tracker.record("custom", &usage);
+    tracker.record_result(
+        "custom",
+        &TranslateResult {
+            usage: Some(usage.clone()),
+            ..Default::default()
+        },
+    );
+    tracker.record_result("custom", &TranslateResult::default());
+
+    assert_eq!(tracker.usage("custom").prompt_tokens, 2_000_000);
+    assert_eq!(tracker.cost("custom"), Some((4.0, "USD".to_string())));
+    assert_eq!(tracker.total()["USD"], 4.0);
+}
@@ -0,0 +1,167 @@
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+/// 一组访问凭证，如腾讯云 STS 临时密钥、AWS 会话凭证或 OAuth 访问令牌
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Credential {
+    /// 如 SecretId、AccessKeyId
+    #[serde(default)]
+    pub id: String,
+    /// 如 SecretKey、SecretAccessKey
+    #[serde(default)]
+    pub secret: String,
+    /// 临时凭证的 token 或 OAuth 访问令牌
+    #[serde(default)]
+    pub token: Option<String>,
+    /// 过期时间，Unix 时间戳（秒），为空表示长期有效
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+}
+
+impl Credential {
+    /// 在 `margin` 之内过期
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        let Some(expires_at) = self.expires_at else {
+            return false;
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        now + margin >= Duration::from_secs(expires_at)
+    }
+}
+
+/// 凭证来源，每次调用都应返回新的凭证
+#[async_trait]
+pub trait CredentialProvider: Send + Sync {
+    async fn fetch(&self) -> Result<Credential>;
+}
+
+/// 执行命令并将标准输出解析为 JSON 格式的 `Credential`，与 AWS 的 `credential_process` 类似
+#[derive(Debug, Clone)]
+pub struct CommandCredentials {
+    pub command: Vec<String>,
+}
+
+#[async_trait]
+impl CredentialProvider for CommandCredentials {
+    async fn fetch(&self) -> Result<Credential> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or(anyhow!("missing argument: command"))?;
+
+        let output = Command::new(program).args(args).output().await?;
+        if !output.status.success() {
+            bail!(
+                "credential process exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(serde_json::from_slice(&output.stdout)?)
+    }
+}
+
+/// 缓存凭证，在过期前或被标记失效后重新获取。并发请求只会触发一次刷新
+pub struct CredentialCache {
+    provider: Box<dyn CredentialProvider>,
+    current: Mutex<Option<Credential>>,
+    refresh_before: Duration,
+}
+
+impl Debug for CredentialCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CredentialCache")
+            .field("refresh_before", &self.refresh_before)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CredentialCache {
+    /// 默认在过期前 60 秒刷新
+    pub fn new(provider: impl CredentialProvider + 'static) -> Self {
+        CredentialCache {
+            provider: Box::new(provider),
+            current: Mutex::new(None),
+            refresh_before: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_refresh_before(mut self, refresh_before: Duration) -> Self {
+        self.refresh_before = refresh_before;
+        self
+    }
+
+    pub async fn get(&self) -> Result<Credential> {
+        let mut current = self.current.lock().await;
+
+        if let Some(credential) = current.as_ref() {
+            if !credential.expires_within(self.refresh_before) {
+                return Ok(credential.clone());
+            }
+        }
+
+        let credential = self.provider.fetch().await?;
+        *current = Some(credential.clone());
+        Ok(credential)
+    }
+
+    /// 服务端返回凭证失效时调用，下次 `get` 会重新获取
+    pub async fn invalidate(&self) {
+        *self.current.lock().await = None;
+    }
+}
+
+#[cfg(test)]
+struct CountingProvider {
+    fetches: AtomicUsize,
+    lifetime: u64,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl CredentialProvider for CountingProvider {
+    async fn fetch(&self) -> Result<Credential> {
+        let n = self.fetches.fetch_add(1, Ordering::SeqCst) + 1;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        Ok(Credential {
+            id: "id".to_string(),
+            secret: "secret".to_string(),
+            token: Some(format!("token-{}", n)),
+            expires_at: Some(now + self.lifetime),
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_credential_cache() -> Result<()> {
+    let cache = CredentialCache::new(CountingProvider {
+        fetches: AtomicUsize::new(0),
+        lifetime: 3600,
+    });
+    assert_eq!(cache.get().await?.token.as_deref(), Some("token-1"));
+    assert_eq!(cache.get().await?.token.as_deref(), Some("token-1"));
+    cache.invalidate().await;
+    assert_eq!(cache.get().await?.token.as_deref(), Some("token-2"));
+
+    // 即将过期的凭证每次都会刷新
+    let cache = CredentialCache::new(CountingProvider {
+        fetches: AtomicUsize::new(0),
+        lifetime: 30,
+    });
+    cache.get().await?;
+    assert_eq!(cache.get().await?.token.as_deref(), Some("token-2"));
+
+    assert!(!Credential::default().expires_within(Duration::from_secs(60)));
+
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use crate::error::XTranslateError;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::utils::{stream2normal, with_deadline};
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeadlineConfig {
+    /// 任务未设置 `deadline_ms` 时使用的截止时间（毫秒）
+    pub deadline_ms: Option<u64>,
+    /// 到期时返回已产出的部分译文，否则返回 `XTranslateError::Timeout`
+    pub partial: bool,
+}
+
+impl Default for DeadlineConfig {
+    fn default() -> Self {
+        DeadlineConfig {
+            deadline_ms: None,
+            partial: true,
+        }
+    }
+}
+
+/// 按 `TranslateTask::deadline_ms` 限制翻译时间，适合实时字幕等迟到的译文没有意义的场景。
+/// 到期后取消请求，流式翻译补发一个 `partial` 为 `true` 的增量并结束；
+/// 普通翻译在服务支持流式输出时同样返回已产出的部分，否则返回超时错误
+pub struct DeadlineTranslator<T> {
+    inner: T,
+    config: DeadlineConfig,
+}
+
+impl<T: Translator> DeadlineTranslator<T> {
+    pub fn wrap(inner: T, config: DeadlineConfig) -> Self {
+        DeadlineTranslator { inner, config }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn deadline(&self, task: &TranslateTask) -> Option<Duration> {
+        task.deadline_ms
+            .or(self.config.deadline_ms)
+            .map(Duration::from_millis)
+    }
+
+    fn timeout(deadline: Duration) -> XTranslateError {
+        XTranslateError::Timeout(format!("deadline exceeded: {} ms", deadline.as_millis()))
+    }
+}
+
+#[async_trait]
+impl<T> Translator for DeadlineTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 截止时间参数读取自 `config["deadline"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let deadline_config = match config.get("deadline") {
+            Some(deadline) => serde_json::from_value(deadline.clone())?,
+            None => DeadlineConfig::default(),
+        };
+
+        Ok(DeadlineTranslator::wrap(
+            T::new(config).await?,
+            deadline_config,
+        ))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    /// 没有产出任何译文时返回超时错误
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let Some(deadline) = self.deadline(&task) else {
+            return self.inner.translate(task).await;
+        };
+
+        if !self.config.partial || !self.inner.capabilities().supports_streaming {
+            return with_deadline(Some(deadline), self.inner.translate(task)).await;
+        }
+
+        let result = stream2normal(self, task).await?;
+        if result.partial && result.content.as_deref().is_none_or(str::is_empty) {
+            bail!(Self::timeout(deadline));
+        }
+
+        Ok(result)
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let Some(deadline) = self.deadline(&task) else {
+            return self.inner.translate_stream(task, sender).await;
+        };
+
+        if !self.config.partial {
+            return with_deadline(Some(deadline), self.inner.translate_stream(task, sender)).await;
+        }
+
+        let (tx, mut rx) = mpsc::channel(64);
+        let mut started = false;
+        let mut ended = false;
+
+        let forward = async {
+            while let Some(chunk) = rx.recv().await {
+                match &chunk {
+                    TranslateStreamChunk::Start => started = true,
+                    TranslateStreamChunk::End => ended = true,
+                    TranslateStreamChunk::Delta(_) => {}
+                }
+                sender.send(chunk).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+
+        let request_id = task.request_id.clone();
+        let translate = async { tokio::join!(self.inner.translate_stream(task, tx), forward) };
+
+        match tokio::time::timeout(deadline, translate).await {
+            Ok((result, forwarded)) => result.and(forwarded),
+            // 到期时请求随 future 一同取消
+            Err(_) => {
+                if ended {
+                    return Ok(());
+                }
+                if !started {
+                    sender.send(TranslateStreamChunk::Start).await?;
+                }
+                sender
+                    .send(TranslateStreamChunk::Delta(TranslateResult {
+                        request_id,
+                        partial: true,
+                        ..Default::default()
+                    }))
+                    .await?;
+                sender.send(TranslateStreamChunk::End).await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_deadline_translator() -> Result<()> {
+    let translator = DeadlineTranslator::wrap(
+        MockTranslator::streaming("T:", Duration::from_millis(50)),
+        DeadlineConfig::default(),
+    );
+
+    // 未设置截止时间时完整输出
+    let result = translator.translate(task("one two three")).await?;
+    assert_eq!(result.content.as_deref(), Some("T:one two three"));
+    assert!(!result.partial);
+
+    let mut late = task("one two three four five");
+    late.deadline_ms = Some(125);
+    let result = translator.translate(late.clone()).await?;
+    assert!(result.partial);
+    assert_eq!(result.content.as_deref(), Some("T:one two "));
+
+    let (tx, mut rx) = mpsc::channel(64);
+    translator.translate_stream(late.clone(), tx).await?;
+    let mut chunks = vec![];
+    while let Some(chunk) = rx.recv().await {
+        chunks.push(chunk);
+    }
+    assert!(matches!(chunks.first(), Some(TranslateStreamChunk::Start)));
+    assert!(matches!(chunks.last(), Some(TranslateStreamChunk::End)));
+    assert!(matches!(
+        &chunks[chunks.len() - 2],
+        TranslateStreamChunk::Delta(delta) if delta.partial
+    ));
+
+    // 不返回部分译文时按超时处理
+    let translator = DeadlineTranslator::wrap(
+        MockTranslator::streaming("T:", Duration::from_millis(50)),
+        DeadlineConfig {
+            partial: false,
+            ..Default::default()
+        },
+    );
+    let (tx, _rx) = mpsc::channel(64);
+    let err = translator.translate_stream(late, tx).await.unwrap_err();
+    assert!(matches!(
+        XTranslateError::find(&err),
+        Some(XTranslateError::Timeout(_))
+    ));
+
+    Ok(())
+}
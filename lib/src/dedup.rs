@@ -0,0 +1,244 @@
+use crate::cache::CacheKey;
+use crate::error::XTranslateError;
+#[cfg(test)]
+use crate::deadline::{DeadlineConfig, DeadlineTranslator};
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+#[cfg(test)]
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::Sender;
+
+/// 分发给等待方的结果，错误保留 `XTranslateError` 分类
+type Shared = Result<TranslateResult, (Option<XTranslateError>, String)>;
+
+/// 截止时间不同的请求可能得到部分译文或超时错误，不能共享结果
+type PendingKey = (CacheKey, Option<u64>);
+
+/// 合并并发的相同请求（如字幕中重复出现的台词），只调用一次翻译服务并把结果分发给所有等待方。
+/// 判断是否相同的规则与 `cache::CacheKey` 一致，另外要求截止时间相同，流式翻译不做合并
+pub struct DedupTranslator<T> {
+    inner: T,
+    pending: Mutex<HashMap<PendingKey, broadcast::Sender<Shared>>>,
+}
+
+/// 发起请求的一方被取消时移除登记，等待方会重新发起请求
+struct PendingGuard<'a> {
+    pending: &'a Mutex<HashMap<PendingKey, broadcast::Sender<Shared>>>,
+    key: &'a PendingKey,
+    done: bool,
+}
+
+impl PendingGuard<'_> {
+    /// 请求完成，取出用于分发结果的发送端
+    fn finish(mut self) -> Option<broadcast::Sender<Shared>> {
+        self.done = true;
+        self.pending.lock().unwrap().remove(self.key)
+    }
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.pending.lock().unwrap().remove(self.key);
+        }
+    }
+}
+
+impl<T> DedupTranslator<T> {
+    pub fn wrap(inner: T) -> Self {
+        DedupTranslator {
+            inner,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// 正在进行中的不同请求数
+    pub fn in_flight(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl<T> Translator for DedupTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    async fn new(config: Value) -> Result<Self> {
+        Ok(DedupTranslator::wrap(T::new(config).await?))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    /// 等待方拿到的结果不携带用量，避免重复计费，请求 ID 为等待方自己的
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        // 只包装一个翻译器，服务标识留空即可
+        let key = (CacheKey::new("", &task)?, task.deadline_ms);
+
+        loop {
+            let receiver = {
+                let mut pending = self.pending.lock().unwrap();
+                match pending.get(&key) {
+                    Some(sender) => Some(sender.subscribe()),
+                    None => {
+                        pending.insert(key.clone(), broadcast::channel(1).0);
+                        None
+                    }
+                }
+            };
+
+            if let Some(mut receiver) = receiver {
+                match receiver.recv().await {
+                    Ok(Ok(mut result)) => {
+                        result.usage = None;
+                        result.request_id = task.request_id;
+                        return Ok(result);
+                    }
+                    Ok(Err((Some(e), _))) => return Err(anyhow!(e)),
+                    Ok(Err((None, message))) => return Err(anyhow!(message)),
+                    Err(_) => continue,
+                }
+            }
+
+            let guard = PendingGuard {
+                pending: &self.pending,
+                key: &key,
+                done: false,
+            };
+
+            let result = self.inner.translate(task).await;
+
+            if let Some(sender) = guard.finish() {
+                let shared = match &result {
+                    Ok(result) => Ok(result.clone()),
+                    Err(e) => Err((XTranslateError::find(e).cloned(), e.to_string())),
+                };
+                // 没有等待方时发送失败，忽略即可
+                let _ = sender.send(shared);
+            }
+
+            return result;
+        }
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        self.inner.translate_stream(task, sender).await
+    }
+}
+
+#[tokio::test]
+async fn test_dedup_translator() -> Result<()> {
+    let translator = DedupTranslator::wrap(MockTranslator::slow("T:", Duration::from_millis(20)));
+
+    let with_id = |content: &str, request_id: &str| {
+        let mut task = task(content);
+        task.request_id = Some(request_id.to_string());
+        task
+    };
+    let (a, b, c) = tokio::join!(
+        translator.translate(with_id("Hello", "a")),
+        translator.translate(with_id("Hello", "b")),
+        translator.translate(with_id("World", "c")),
+    );
+    let (a, b, c) = (a?, b?, c?);
+
+    // 等待方拿到自己的请求 ID
+    assert_eq!(a.request_id.as_deref(), Some("a"));
+    assert_eq!(b.request_id.as_deref(), Some("b"));
+
+    assert_eq!(a.content.as_deref(), Some("T:Hello"));
+    assert_eq!(b.content, a.content);
+    assert_eq!(c.content.as_deref(), Some("T:World"));
+    assert!(a.usage.is_some());
+    assert!(b.usage.is_none());
+    assert_eq!(translator.inner().calls(), 2);
+    assert_eq!(translator.in_flight(), 0);
+
+    // 完成后的相同请求会重新调用
+    translator.translate(task("Hello")).await?;
+    assert_eq!(translator.inner().calls(), 3);
+
+    let translator = DedupTranslator::wrap(MockTranslator {
+        fail: true,
+        ..MockTranslator::slow("", Duration::from_millis(20))
+    });
+    let (a, b) = tokio::join!(
+        translator.translate(task("Hello")),
+        translator.translate(task("Hello")),
+    );
+    assert!(a.is_err());
+    assert_eq!(b.unwrap_err().to_string(), "mock failure");
+    assert_eq!(translator.inner().calls(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dedup_deadline() -> Result<()> {
+    let translator = DedupTranslator::wrap(DeadlineTranslator::wrap(
+        MockTranslator::streaming("T:", Duration::from_millis(50)),
+        DeadlineConfig::default(),
+    ));
+
+    let with_deadline = |deadline_ms: u64| {
+        let mut task = task("one two three four five");
+        task.deadline_ms = Some(deadline_ms);
+        task
+    };
+    let (late, full) = tokio::join!(
+        translator.translate(with_deadline(125)),
+        translator.translate(with_deadline(5000)),
+    );
+    let (late, full) = (late?, full?);
+
+    // 截止时间较短的请求得到部分译文，不会分发给另一方
+    assert!(late.partial);
+    assert_eq!(late.content.as_deref(), Some("T:one two "));
+    assert!(!full.partial);
+    assert_eq!(full.content.as_deref(), Some("T:one two three four five"));
+    assert_eq!(translator.inner().inner().calls(), 2);
+
+    Ok(())
+}
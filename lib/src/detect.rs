@@ -0,0 +1,354 @@
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::Result;
+use async_trait::async_trait;
+use language_tags::LanguageTag;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::Sender;
+use whatlang::{Detector, Lang};
+
+/// 语种识别
+#[async_trait]
+pub trait LanguageDetector: Send + Sync {
+    /// 识别文本语种，按置信度从高到低返回候选语言
+    async fn detect(&self, text: &str) -> Result<Vec<(LanguageTag, f32)>>;
+}
+
+/// 基于 whatlang 的离线语种识别
+#[derive(Default)]
+pub struct WhatlangDetector {
+    detector: Detector,
+}
+
+impl WhatlangDetector {
+    pub fn new() -> Self {
+        WhatlangDetector::default()
+    }
+
+    /// 仅在给定语言中识别，无法映射到 whatlang 的语言会被忽略
+    pub fn with_allowlist(languages: &[LanguageTag]) -> Self {
+        let list = Lang::all()
+            .iter()
+            .filter(|lang| {
+                languages
+                    .iter()
+                    .any(|tag| tag.primary_language() == lang_to_bcp47(**lang))
+            })
+            .cloned()
+            .collect();
+
+        WhatlangDetector {
+            detector: Detector::with_allowlist(list),
+        }
+    }
+
+    pub fn detect_sync(&self, text: &str) -> Vec<(LanguageTag, f32)> {
+        self.detector
+            .detect(text)
+            .and_then(|info| {
+                LanguageTag::parse(lang_to_bcp47(info.lang()))
+                    .ok()
+                    .map(|tag| (tag, info.confidence() as f32))
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl LanguageDetector for WhatlangDetector {
+    async fn detect(&self, text: &str) -> Result<Vec<(LanguageTag, f32)>> {
+        Ok(self.detect_sync(text))
+    }
+}
+
+/// 使用内置离线识别器识别文本语种
+pub fn detect(text: &str) -> Vec<(LanguageTag, f32)> {
+    WhatlangDetector::new().detect_sync(text)
+}
+
+/// 何时识别并填充源语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectMode {
+    /// 仅在服务不支持自动识别源语言时
+    #[default]
+    Auto,
+    /// 未指定源语言时总是识别，便于缓存与统计使用确定的语言
+    Always,
+    /// 不识别
+    Never,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DetectConfig {
+    #[serde(default)]
+    pub mode: DetectMode,
+    /// 候选语言的最低置信度，默认采用置信度最高且服务支持的候选
+    #[serde(default)]
+    pub min_confidence: f32,
+}
+
+/// 任务未指定源语言时识别原文语种并填入 `source_language`，识别不出时保持为空
+pub struct DetectingTranslator<T> {
+    inner: T,
+    detector: Box<dyn LanguageDetector>,
+    config: DetectConfig,
+}
+
+impl<T> DetectingTranslator<T> {
+    /// 使用内置离线识别器
+    pub fn wrap(inner: T, config: DetectConfig) -> Self {
+        DetectingTranslator::with_detector(inner, config, Box::new(WhatlangDetector::new()))
+    }
+
+    pub fn with_detector(
+        inner: T,
+        config: DetectConfig,
+        detector: Box<dyn LanguageDetector>,
+    ) -> Self {
+        DetectingTranslator {
+            inner,
+            detector,
+            config,
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> DetectingTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    fn should_detect(&self) -> bool {
+        match self.config.mode {
+            DetectMode::Auto => !self.inner.capabilities().supports_auto_detect,
+            DetectMode::Always => true,
+            DetectMode::Never => false,
+        }
+    }
+
+    /// 按配置识别并填充源语言
+    pub async fn fill_source_language(&self, task: &mut TranslateTask) -> Result<()> {
+        if task.source_language.is_some() || !self.should_detect() {
+            return Ok(());
+        }
+
+        for (tag, confidence) in self.detector.detect(&task.content).await? {
+            if confidence < self.config.min_confidence {
+                break;
+            }
+            if self
+                .inner
+                .is_supported_input_language(tag.to_string())
+                .unwrap_or(false)
+            {
+                task.source_language = Some(tag);
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T> Translator for DetectingTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 识别参数读取自 `config["detect"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let detect_config = match config.get("detect") {
+            Some(detect) => serde_json::from_value(detect.clone())?,
+            None => DetectConfig::default(),
+        };
+
+        let inner = T::new(config).await?;
+
+        Ok(DetectingTranslator::wrap(inner, detect_config))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = self.inner.capabilities();
+        capabilities.supports_auto_detect |= self.config.mode != DetectMode::Never;
+        capabilities
+    }
+
+    async fn translate(&self, mut task: TranslateTask) -> Result<TranslateResult> {
+        self.fill_source_language(&mut task).await?;
+        self.inner.translate(task).await
+    }
+
+    async fn translate_stream(
+        &self,
+        mut task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        self.fill_source_language(&mut task).await?;
+        self.inner.translate_stream(task, sender).await
+    }
+}
+
+/// whatlang 使用 ISO 639-3，转换为 BCP47 推荐的最短形式
+fn lang_to_bcp47(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Epo => "eo",
+        Lang::Eng => "en",
+        Lang::Rus => "ru",
+        Lang::Cmn => "zh",
+        Lang::Spa => "es",
+        Lang::Por => "pt",
+        Lang::Ita => "it",
+        Lang::Ben => "bn",
+        Lang::Fra => "fr",
+        Lang::Deu => "de",
+        Lang::Ukr => "uk",
+        Lang::Kat => "ka",
+        Lang::Ara => "ar",
+        Lang::Hin => "hi",
+        Lang::Jpn => "ja",
+        Lang::Heb => "he",
+        Lang::Yid => "yi",
+        Lang::Pol => "pl",
+        Lang::Amh => "am",
+        Lang::Jav => "jv",
+        Lang::Kor => "ko",
+        Lang::Nob => "nb",
+        Lang::Dan => "da",
+        Lang::Swe => "sv",
+        Lang::Fin => "fi",
+        Lang::Tur => "tr",
+        Lang::Nld => "nl",
+        Lang::Hun => "hu",
+        Lang::Ces => "cs",
+        Lang::Ell => "el",
+        Lang::Bul => "bg",
+        Lang::Bel => "be",
+        Lang::Mar => "mr",
+        Lang::Kan => "kn",
+        Lang::Ron => "ro",
+        Lang::Slv => "sl",
+        Lang::Hrv => "hr",
+        Lang::Srp => "sr",
+        Lang::Mkd => "mk",
+        Lang::Lit => "lt",
+        Lang::Lav => "lv",
+        Lang::Est => "et",
+        Lang::Tam => "ta",
+        Lang::Vie => "vi",
+        Lang::Urd => "ur",
+        Lang::Tha => "th",
+        Lang::Guj => "gu",
+        Lang::Uzb => "uz",
+        Lang::Pan => "pa",
+        Lang::Aze => "az",
+        Lang::Ind => "id",
+        Lang::Tel => "te",
+        Lang::Pes => "fa",
+        Lang::Mal => "ml",
+        Lang::Ori => "or",
+        Lang::Mya => "my",
+        Lang::Nep => "ne",
+        Lang::Sin => "si",
+        Lang::Khm => "km",
+        Lang::Tuk => "tk",
+        Lang::Aka => "ak",
+        Lang::Zul => "zu",
+        Lang::Sna => "sn",
+        Lang::Afr => "af",
+        Lang::Lat => "la",
+        Lang::Slk => "sk",
+        Lang::Cat => "ca",
+        Lang::Tgl => "tl",
+        Lang::Hye => "hy",
+    }
+}
+
+#[test]
+fn test_detect() {
+    let result = detect("落霞与孤鹜齐飞，秋水共长天一色。");
+    assert_eq!(result[0].0.primary_language(), "zh");
+
+    let result = detect("The quick brown fox jumps over the lazy dog.");
+    assert_eq!(result[0].0.primary_language(), "en");
+
+    assert!(detect("").is_empty());
+}
+
+#[tokio::test]
+async fn test_detect_allowlist() -> Result<()> {
+    let detector =
+        WhatlangDetector::with_allowlist(&[LanguageTag::parse("de")?, LanguageTag::parse("nl")?]);
+
+    let result = detector
+        .detect("Das ist ein kleiner Test für die Erkennung.")
+        .await?;
+    assert_eq!(result[0].0.primary_language(), "de");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_detecting_translator() -> Result<()> {
+    let translator = DetectingTranslator::wrap(MockTranslator::new(""), DetectConfig::default());
+
+    let mut task = task("Das ist ein kleiner Test für die Erkennung.");
+    task.source_language = None;
+    translator.fill_source_language(&mut task).await?;
+    assert_eq!(
+        task.source_language.as_ref().map(|t| t.as_str()),
+        Some("de")
+    );
+    assert!(translator.capabilities().supports_auto_detect);
+
+    // 服务不支持识别出的语言时保持为空
+    let translator =
+        DetectingTranslator::wrap(MockTranslator::only("", &["en"]), DetectConfig::default());
+    task.source_language = None;
+    translator.fill_source_language(&mut task).await?;
+    assert!(task.source_language.is_none());
+
+    let translator = DetectingTranslator::wrap(
+        MockTranslator::new(""),
+        DetectConfig {
+            mode: DetectMode::Never,
+            ..Default::default()
+        },
+    );
+    translator.fill_source_language(&mut task).await?;
+    assert!(task.source_language.is_none());
+
+    Ok(())
+}
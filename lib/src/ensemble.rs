@@ -0,0 +1,375 @@
+use crate::ffi_proxy::ProxyTranslatorFactory;
+use crate::qe::estimate;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::trace::record_failure;
+use crate::utils::{normal2stream, supports_task};
+use crate::{
+    BoxedTranslator, Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask,
+    Translator, TranslatorFactory, TranslatorSpec,
+};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::Sender;
+
+/// 评审时默认使用的系统提示词
+const JUDGE_PROMPT: &str = r##"你是专业的翻译评审。请比较下面{{ source_language }}原文的几个{{ target_language }}候选译文，从准确、流畅、术语一致三个方面选出最好的一个。
+只输出最佳译文的序号，不要输出其它内容。"##;
+
+/// 各服务给出的一个候选译文
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Candidate {
+    /// 翻译服务
+    pub provider: String,
+    /// 译文
+    pub content: String,
+    /// 质量评估得分
+    pub score: f32,
+}
+
+/// 选择最佳译文的方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Selection {
+    /// 按 `qe::estimate` 的得分
+    #[default]
+    Quality,
+    /// 由大模型评审，评审失败时按得分
+    Judge,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnsembleConfig {
+    /// 同时调用的翻译器
+    pub translators: Vec<TranslatorSpec>,
+    #[serde(default)]
+    pub selection: Selection,
+    /// 评审用的翻译器，需要支持提示词
+    #[serde(default)]
+    pub judge: Option<TranslatorSpec>,
+    /// 评审用的系统提示词
+    #[serde(default)]
+    pub judge_prompt: Option<String>,
+}
+
+/// 将任务同时发给多个翻译器，从结果中选出最佳译文，所有候选写入 `TranslateResult::alternatives`。
+/// 用量为所有请求之和。流式翻译在选出结果后一次性输出
+pub struct EnsembleTranslator {
+    translators: Vec<(String, BoxedTranslator)>,
+    selection: Selection,
+    judge: Option<BoxedTranslator>,
+    judge_prompt: Option<String>,
+}
+
+impl EnsembleTranslator {
+    /// 按质量评估得分选择
+    pub fn with_translators(translators: Vec<(String, BoxedTranslator)>) -> Self {
+        EnsembleTranslator {
+            translators,
+            selection: Selection::Quality,
+            judge: None,
+            judge_prompt: None,
+        }
+    }
+
+    /// 由评审翻译器选择
+    pub fn with_judge(mut self, judge: BoxedTranslator, prompt: Option<String>) -> Self {
+        self.selection = Selection::Judge;
+        self.judge = Some(judge);
+        self.judge_prompt = prompt;
+        self
+    }
+
+    pub async fn from_config(config: &Value, factory: &dyn TranslatorFactory) -> Result<Self> {
+        let config: EnsembleConfig = serde_json::from_value(config.clone())?;
+
+        let mut translators = vec![];
+        for spec in config.translators {
+            let translator = factory.create(&spec.name, spec.config).await?;
+            translators.push((spec.name, translator));
+        }
+
+        let mut ensemble = EnsembleTranslator::with_translators(translators);
+
+        if config.selection == Selection::Judge {
+            let spec = config.judge.ok_or(anyhow!("missing argument: judge"))?;
+            let judge = factory.create(&spec.name, spec.config).await?;
+            ensemble = ensemble.with_judge(judge, config.judge_prompt);
+        }
+
+        Ok(ensemble)
+    }
+
+    /// 请评审选出最佳候选，返回下标
+    async fn judge(
+        &self,
+        task: &TranslateTask,
+        candidates: &[Candidate],
+        result: &mut TranslateResult,
+    ) -> Result<usize> {
+        let judge = self.judge.as_ref().ok_or(anyhow!("missing judge"))?;
+
+        let mut content = format!("原文：\n{}\n\n候选译文：\n", task.content);
+        for (i, candidate) in candidates.iter().enumerate() {
+            content.push_str(&format!("{}. {}\n", i + 1, candidate.content));
+        }
+
+        let judged = judge
+            .translate(TranslateTask {
+                id: task.id.clone(),
+                content,
+                source_language: task.source_language.clone(),
+                target_language: task.target_language.clone(),
+                user_prompt: Some("{{{ content }}}".to_string()),
+                system_prompt: Some(
+                    self.judge_prompt
+                        .clone()
+                        .unwrap_or_else(|| JUDGE_PROMPT.to_string()),
+                ),
+                field: task.field.clone(),
+                terms: vec![],
+                references: vec![],
+                extra: None,
+                request_id: None,
+                deadline_ms: None,
+            })
+            .await?;
+
+        if let Some(usage) = &judged.usage {
+            result.add_usage(usage);
+        }
+
+        let answer = judged.content.unwrap_or_default();
+        let index: usize = answer
+            .split(|c: char| !c.is_ascii_digit())
+            .find(|s| !s.is_empty())
+            .ok_or(anyhow!("invalid judge answer: {}", answer))?
+            .parse()?;
+
+        if index == 0 || index > candidates.len() {
+            bail!("invalid judge answer: {}", answer);
+        }
+
+        Ok(index - 1)
+    }
+}
+
+#[async_trait]
+impl Translator for EnsembleTranslator {
+    type This = Self;
+
+    async fn new(config: Value) -> Result<Self> {
+        let factory = ProxyTranslatorFactory::from_config(&config)?;
+        EnsembleTranslator::from_config(&config, &factory).await
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        let mut list: Vec<String> = vec![];
+        for (_, translator) in &self.translators {
+            for lang in translator.get_supported_input_languages()? {
+                if !list.contains(&lang) {
+                    list.push(lang);
+                }
+            }
+        }
+        Ok(list)
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        let mut list: Vec<String> = vec![];
+        for (_, translator) in &self.translators {
+            for lang in translator.get_supported_output_languages()? {
+                if !list.contains(&lang) {
+                    list.push(lang);
+                }
+            }
+        }
+        Ok(list)
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        Ok(self
+            .translators
+            .iter()
+            .any(|(_, t)| t.is_supported_input_language(lang.clone()).unwrap_or(false)))
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        Ok(self.translators.iter().any(|(_, t)| {
+            t.is_supported_output_language(lang.clone())
+                .unwrap_or(false)
+        }))
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        Ok(self.translators.iter().any(|(_, t)| {
+            t.is_supported_pair(source.clone(), target.clone())
+                .unwrap_or(false)
+        }))
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        let mut pairs = vec![];
+        for (_, translator) in &self.translators {
+            for pair in translator.get_supported_pairs()? {
+                if !pairs.contains(&pair) {
+                    pairs.push(pair);
+                }
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// 以第一个翻译器为准，不支持流式输出
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = self
+            .translators
+            .first()
+            .map(|(_, translator)| translator.capabilities())
+            .unwrap_or_default();
+        capabilities.supports_streaming = false;
+        capabilities
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let translators: Vec<&(String, BoxedTranslator)> = self
+            .translators
+            .iter()
+            .filter(|(_, translator)| supports_task(translator.as_ref(), &task))
+            .collect();
+
+        let results = join_all(
+            translators
+                .iter()
+                .map(|(_, translator)| translator.translate(task.clone())),
+        )
+        .await;
+
+        let mut errors = vec![];
+        let mut usage = TranslateResult::default();
+        let mut succeeded: Vec<TranslateResult> = vec![];
+        let mut candidates: Vec<Candidate> = vec![];
+
+        for ((name, _), result) in translators.iter().zip(results) {
+            match result {
+                Ok(mut result) => {
+                    if let Some(u) = &result.usage {
+                        usage.add_usage(u);
+                    }
+                    let content = result.content.clone().unwrap_or_default();
+                    let quality = estimate(&task, &content);
+                    candidates.push(Candidate {
+                        provider: name.clone(),
+                        content,
+                        score: quality.score,
+                    });
+                    result.provider = Some(name.clone());
+                    result.quality = Some(quality);
+                    succeeded.push(result);
+                }
+                Err(e) => {
+                    record_failure(name, &e);
+                    errors.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+
+        if succeeded.is_empty() {
+            bail!("all translators failed: [{}]", errors.join("; "));
+        }
+
+        // 得分相同时取靠前的翻译器
+        let by_score = candidates.iter().enumerate().fold(0, |best, (i, c)| {
+            if c.score > candidates[best].score {
+                i
+            } else {
+                best
+            }
+        });
+
+        let index = match self.selection {
+            Selection::Quality => by_score,
+            Selection::Judge if candidates.len() > 1 => {
+                match self.judge(&task, &candidates, &mut usage).await {
+                    Ok(index) => index,
+                    Err(e) => {
+                        record_failure("judge", &e);
+                        by_score
+                    }
+                }
+            }
+            Selection::Judge => by_score,
+        };
+
+        let mut result = succeeded.swap_remove(index);
+        result.usage = usage.usage;
+        result.alternatives = candidates;
+
+        Ok(result)
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        normal2stream(self, task, sender).await
+    }
+}
+
+#[tokio::test]
+async fn test_ensemble_translator() -> Result<()> {
+    let mut task = task("Hello");
+    task.references.push(crate::TranslatedItem {
+        source: "Hello".to_string(),
+        target: "B:Hello".to_string(),
+    });
+
+    let translators = || -> Vec<(String, BoxedTranslator)> {
+        vec![
+            ("a".to_string(), Box::new(MockTranslator::new("A:"))),
+            ("broken".to_string(), Box::new(MockTranslator::failing())),
+            ("b".to_string(), Box::new(MockTranslator::new("B:"))),
+        ]
+    };
+
+    let translator = EnsembleTranslator::with_translators(translators());
+    let result = translator.translate(task.clone()).await?;
+    assert_eq!(result.content.as_deref(), Some("B:Hello"));
+    assert_eq!(result.provider.as_deref(), Some("b"));
+    assert_eq!(
+        result
+            .alternatives
+            .iter()
+            .map(|c| c.provider.as_str())
+            .collect::<Vec<_>>(),
+        vec!["a", "b"]
+    );
+    assert_eq!(result.usage.unwrap().characters, 10);
+
+    // 评审选择第一个候选
+    let translator = EnsembleTranslator::with_translators(translators())
+        .with_judge(Box::new(MockTranslator::new("1 ")), None);
+    let result = translator.translate(task.clone()).await?;
+    assert_eq!(result.provider.as_deref(), Some("a"));
+
+    // 评审出错时按得分选择
+    let translator = EnsembleTranslator::with_translators(translators())
+        .with_judge(Box::new(MockTranslator::failing()), None);
+    let result = translator.translate(task).await?;
+    assert_eq!(result.provider.as_deref(), Some("b"));
+
+    let translator = EnsembleTranslator::with_translators(vec![(
+        "broken".to_string(),
+        Box::new(MockTranslator::failing()),
+    )]);
+    assert!(translator
+        .translate(crate::testing::task("Hello"))
+        .await
+        .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use thiserror::Error;
+
+/// 翻译错误分类。接口仍返回 `anyhow::Result`，各插件将可识别的错误包装为
+/// `XTranslateError`，调用方通过 `XTranslateError::find` 取出并判断是否可以重试。
+/// 以 `ErrorRecord` 的形式序列化，插件返回的错误据此跨越 FFI 边界
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize, Deserialize)]
+#[serde(into = "ErrorRecord", try_from = "ErrorRecord")]
+pub enum XTranslateError {
+    /// 配置无效
+    #[error("invalid config: {0}")]
+    InvalidConfig(String),
+    /// 不支持的语言
+    #[error("unsupported language: {0}")]
+    UnsupportedLanguage(String),
+    /// 鉴权失败
+    #[error("authentication failed: {0}")]
+    AuthFailed(String),
+    /// 触发限流
+    #[error("rate limited")]
+    RateLimited {
+        /// 服务端建议的重试间隔
+        retry_after: Option<Duration>,
+    },
+    /// 服务端返回的其他错误
+    #[error("provider error {code}: {message}")]
+    ProviderError { code: String, message: String },
+    /// 网络错误
+    #[error("network error: {0}")]
+    Network(String),
+    /// 超时
+    #[error("timeout: {0}")]
+    Timeout(String),
+    /// 调用方取消
+    #[error("cancelled")]
+    Cancelled,
+    /// 原文超出服务的长度限制
+    #[error("input too long: {length} chars, max {max}")]
+    InputTooLong { length: usize, max: usize },
+}
+
+impl XTranslateError {
+    /// 从错误链中找出 `XTranslateError`
+    pub fn find(err: &anyhow::Error) -> Option<&XTranslateError> {
+        err.chain()
+            .find_map(|e| e.downcast_ref::<XTranslateError>())
+    }
+
+    /// 限流、网络错误与超时可以重试
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            XTranslateError::RateLimited { .. }
+                | XTranslateError::Network(_)
+                | XTranslateError::Timeout(_)
+        )
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            XTranslateError::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// 按 HTTP 状态码分类，用于服务端没有返回业务错误码的情况
+    pub fn from_status(status: u16, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match status {
+            401 | 403 => XTranslateError::AuthFailed(message),
+            429 => XTranslateError::RateLimited { retry_after: None },
+            408 | 504 => XTranslateError::Timeout(message),
+            _ => XTranslateError::ProviderError {
+                code: status.to_string(),
+                message,
+            },
+        }
+    }
+
+    /// 同 `from_status`，限流时读取 `Retry-After` 头
+    pub fn from_response(status: u16, headers: &HeaderMap, message: impl Into<String>) -> Self {
+        match XTranslateError::from_status(status, message) {
+            XTranslateError::RateLimited { .. } => XTranslateError::RateLimited {
+                retry_after: parse_retry_after(headers),
+            },
+            err => err,
+        }
+    }
+
+    /// 服务端 5xx 错误
+    pub fn is_server_error(&self) -> bool {
+        match self {
+            XTranslateError::ProviderError { code, .. } => code.starts_with('5') && code.len() == 3,
+            _ => false,
+        }
+    }
+}
+
+/// `XTranslateError` 的 JSON 形式，`kind` 为变体名称，其余字段按变体取值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ErrorRecord {
+    kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    #[serde(default)]
+    message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry_after_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max: Option<usize>,
+}
+
+impl From<XTranslateError> for ErrorRecord {
+    fn from(err: XTranslateError) -> Self {
+        let record = |kind: &str, message: String| ErrorRecord {
+            kind: kind.to_string(),
+            message,
+            ..Default::default()
+        };
+
+        match err {
+            XTranslateError::InvalidConfig(message) => record("invalid_config", message),
+            XTranslateError::UnsupportedLanguage(message) => record("unsupported_language", message),
+            XTranslateError::AuthFailed(message) => record("auth_failed", message),
+            XTranslateError::RateLimited { retry_after } => ErrorRecord {
+                retry_after_ms: retry_after.map(|d| d.as_millis().min(u64::MAX as u128) as u64),
+                ..record("rate_limited", String::new())
+            },
+            XTranslateError::ProviderError { code, message } => ErrorRecord {
+                code: Some(code),
+                ..record("provider_error", message)
+            },
+            XTranslateError::Network(message) => record("network", message),
+            XTranslateError::Timeout(message) => record("timeout", message),
+            XTranslateError::Cancelled => record("cancelled", String::new()),
+            XTranslateError::InputTooLong { length, max } => ErrorRecord {
+                length: Some(length),
+                max: Some(max),
+                ..record("input_too_long", String::new())
+            },
+        }
+    }
+}
+
+impl TryFrom<ErrorRecord> for XTranslateError {
+    type Error = String;
+
+    fn try_from(record: ErrorRecord) -> Result<Self, Self::Error> {
+        Ok(match record.kind.as_str() {
+            "invalid_config" => XTranslateError::InvalidConfig(record.message),
+            "unsupported_language" => XTranslateError::UnsupportedLanguage(record.message),
+            "auth_failed" => XTranslateError::AuthFailed(record.message),
+            "rate_limited" => XTranslateError::RateLimited {
+                retry_after: record.retry_after_ms.map(Duration::from_millis),
+            },
+            "provider_error" => XTranslateError::ProviderError {
+                code: record.code.unwrap_or_default(),
+                message: record.message,
+            },
+            "network" => XTranslateError::Network(record.message),
+            "timeout" => XTranslateError::Timeout(record.message),
+            "cancelled" => XTranslateError::Cancelled,
+            "input_too_long" => XTranslateError::InputTooLong {
+                length: record.length.unwrap_or_default(),
+                max: record.max.unwrap_or_default(),
+            },
+            kind => return Err(format!("unknown error kind: {}", kind)),
+        })
+    }
+}
+
+/// 只支持秒数形式的 `Retry-After`
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// 未分类的错误按不可重试处理
+pub fn is_retryable(err: &anyhow::Error) -> bool {
+    XTranslateError::find(err)
+        .map(|e| e.is_retryable())
+        .unwrap_or(false)
+}
+
+impl From<reqwest::Error> for XTranslateError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            XTranslateError::Timeout(err.to_string())
+        } else if let Some(status) = err.status() {
+            XTranslateError::from_status(status.as_u16(), err.to_string())
+        } else {
+            XTranslateError::Network(err.to_string())
+        }
+    }
+}
+
+#[test]
+fn test_xtranslate_error() {
+    let err = anyhow::Error::new(XTranslateError::from_status(429, "too many requests"))
+        .context("translate failed");
+    let found = XTranslateError::find(&err).unwrap();
+    assert_eq!(found, &XTranslateError::RateLimited { retry_after: None });
+    assert!(is_retryable(&err));
+
+    assert!(!XTranslateError::from_status(401, "bad key").is_retryable());
+    assert!(XTranslateError::from_status(502, "bad gateway").is_server_error());
+
+    let mut headers = HeaderMap::new();
+    headers.insert(RETRY_AFTER, "3".parse().unwrap());
+    assert_eq!(
+        XTranslateError::from_response(429, &headers, "too many requests").retry_after(),
+        Some(Duration::from_secs(3))
+    );
+    assert!(!is_retryable(&anyhow::anyhow!("unknown")));
+}
+
+#[test]
+fn test_xtranslate_error_json() {
+    let errors = [
+        XTranslateError::RateLimited { retry_after: Some(Duration::from_millis(1500)) },
+        XTranslateError::ProviderError { code: "502".to_string(), message: "bad gateway".to_string() },
+        XTranslateError::InputTooLong { length: 10, max: 5 },
+        XTranslateError::Cancelled,
+    ];
+    for err in errors {
+        let json = serde_json::to_string(&err).unwrap();
+        assert_eq!(serde_json::from_str::<XTranslateError>(&json).unwrap(), err);
+    }
+
+    let json = serde_json::to_value(XTranslateError::RateLimited { retry_after: Some(Duration::from_secs(3)) }).unwrap();
+    assert_eq!(json, serde_json::json!({ "kind": "rate_limited", "message": "", "retry_after_ms": 3000 }));
+    assert!(serde_json::from_str::<XTranslateError>(r#"{"kind":"unknown"}"#).is_err());
+}
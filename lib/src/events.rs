@@ -0,0 +1,296 @@
+use crate::request::ensure_request_id;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{
+    Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator, Usage,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(test)]
+use std::sync::Mutex;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// 翻译任务的生命周期事件，供界面展示进度与统计
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TranslateEvent {
+    Started {
+        task_id: String,
+        request_id: Option<String>,
+        /// 原文字符数
+        characters: usize,
+    },
+    /// 收到第一段非空译文，只在流式翻译时发送
+    FirstByte {
+        task_id: String,
+        request_id: Option<String>,
+        elapsed_ms: u64,
+    },
+    Completed {
+        task_id: String,
+        request_id: Option<String>,
+        elapsed_ms: u64,
+        /// 收到的增量个数，普通翻译为 1
+        chunks: usize,
+        /// 译文字节数
+        bytes: usize,
+        usage: Option<Usage>,
+    },
+    Failed {
+        task_id: String,
+        request_id: Option<String>,
+        elapsed_ms: u64,
+        chunks: usize,
+        bytes: usize,
+        error: String,
+    },
+}
+
+/// 事件接收方，在翻译所在的任务中同步调用，不应阻塞
+pub trait EventSink: Send + Sync {
+    fn emit(&self, event: &TranslateEvent);
+}
+
+impl<F: Fn(&TranslateEvent) + Send + Sync> EventSink for F {
+    fn emit(&self, event: &TranslateEvent) {
+        self(event)
+    }
+}
+
+/// 丢弃所有事件
+#[derive(Debug, Clone, Default)]
+pub struct NoopSink;
+
+impl EventSink for NoopSink {
+    fn emit(&self, _event: &TranslateEvent) {}
+}
+
+static SINK: LazyLock<RwLock<Arc<dyn EventSink>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(NoopSink)));
+
+/// 注册进程内的事件接收方，由 `EventTranslator::new` 创建的翻译器使用
+pub fn set_sink(sink: impl EventSink + 'static) {
+    *SINK.write().unwrap() = Arc::new(sink);
+}
+
+pub fn sink() -> Arc<dyn EventSink> {
+    SINK.read().unwrap().clone()
+}
+
+fn millis(elapsed: Duration) -> u64 {
+    elapsed.as_millis() as u64
+}
+
+/// 在翻译开始、收到首个译文、完成与失败时向 `EventSink` 发送事件。
+/// 任务未指定 `request_id` 时会生成一个，便于关联同一任务的事件
+pub struct EventTranslator<T> {
+    inner: T,
+    sink: Arc<dyn EventSink>,
+}
+
+impl<T: Translator> EventTranslator<T> {
+    pub fn wrap(inner: T, sink: Arc<dyn EventSink>) -> Self {
+        EventTranslator { inner, sink }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn started(&self, task: &TranslateTask) -> Instant {
+        self.sink.emit(&TranslateEvent::Started {
+            task_id: task.id.clone(),
+            request_id: task.request_id.clone(),
+            characters: task.content.chars().count(),
+        });
+        Instant::now()
+    }
+
+    fn finished(
+        &self,
+        task: &TranslateTask,
+        start: Instant,
+        chunks: usize,
+        bytes: usize,
+        result: Result<Option<Usage>, &anyhow::Error>,
+    ) {
+        let task_id = task.id.clone();
+        let request_id = task.request_id.clone();
+        let elapsed_ms = millis(start.elapsed());
+
+        self.sink.emit(&match result {
+            Ok(usage) => TranslateEvent::Completed {
+                task_id,
+                request_id,
+                elapsed_ms,
+                chunks,
+                bytes,
+                usage,
+            },
+            Err(e) => TranslateEvent::Failed {
+                task_id,
+                request_id,
+                elapsed_ms,
+                chunks,
+                bytes,
+                error: e.to_string(),
+            },
+        });
+    }
+}
+
+#[async_trait]
+impl<T> Translator for EventTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 事件发送到 `set_sink` 注册的接收方，配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        Ok(EventTranslator::wrap(T::new(config).await?, sink()))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn translate(&self, mut task: TranslateTask) -> Result<TranslateResult> {
+        ensure_request_id(&mut task);
+        let start = self.started(&task);
+
+        let result = self.inner.translate(task.clone()).await;
+        match &result {
+            Ok(r) => {
+                let bytes = r.content.as_deref().map(str::len).unwrap_or(0);
+                self.finished(&task, start, 1, bytes, Ok(r.usage.clone()));
+            }
+            Err(e) => self.finished(&task, start, 0, 0, Err(e)),
+        }
+
+        result
+    }
+
+    async fn translate_stream(
+        &self,
+        mut task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        ensure_request_id(&mut task);
+        let start = self.started(&task);
+
+        let (tx, mut rx) = mpsc::channel(64);
+        let mut chunks = 0;
+        let mut bytes = 0;
+        let mut usage: Option<Usage> = None;
+
+        let forward = async {
+            while let Some(chunk) = rx.recv().await {
+                if let TranslateStreamChunk::Delta(delta) = &chunk {
+                    let len = delta.content.as_deref().map(str::len).unwrap_or(0);
+                    if bytes == 0 && len > 0 {
+                        self.sink.emit(&TranslateEvent::FirstByte {
+                            task_id: task.id.clone(),
+                            request_id: task.request_id.clone(),
+                            elapsed_ms: millis(start.elapsed()),
+                        });
+                    }
+                    chunks += 1;
+                    bytes += len;
+                    if let Some(u) = &delta.usage {
+                        usage.get_or_insert_with(Usage::default).add(u);
+                    }
+                }
+                sender.send(chunk).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+
+        let (result, forwarded) =
+            tokio::join!(self.inner.translate_stream(task.clone(), tx), forward);
+        let result = result.and(forwarded);
+
+        match &result {
+            Ok(()) => self.finished(&task, start, chunks, bytes, Ok(usage)),
+            Err(e) => self.finished(&task, start, chunks, bytes, Err(e)),
+        }
+
+        result
+    }
+}
+
+#[tokio::test]
+async fn test_event_translator() -> Result<()> {
+    let events = Arc::new(Mutex::new(vec![]));
+    let collected = events.clone();
+    let translator = EventTranslator::wrap(
+        MockTranslator::streaming("T:", Duration::from_millis(1)),
+        Arc::new(move |event: &TranslateEvent| collected.lock().unwrap().push(event.clone())),
+    );
+
+    let (tx, mut rx) = mpsc::channel(64);
+    translator.translate_stream(task("one two"), tx).await?;
+    while rx.recv().await.is_some() {}
+
+    let received = std::mem::take(&mut *events.lock().unwrap());
+    assert_eq!(received.len(), 3);
+    assert!(matches!(
+        &received[0],
+        TranslateEvent::Started {
+            characters: 7,
+            request_id: Some(_),
+            ..
+        }
+    ));
+    assert!(matches!(&received[1], TranslateEvent::FirstByte { .. }));
+    assert!(matches!(
+        &received[2],
+        TranslateEvent::Completed {
+            chunks: 3,
+            bytes: 9,
+            ..
+        }
+    ));
+
+    let collected = events.clone();
+    let failing = EventTranslator::wrap(
+        MockTranslator::failing(),
+        Arc::new(move |event: &TranslateEvent| collected.lock().unwrap().push(event.clone())),
+    );
+    assert!(failing.translate(task("Hello")).await.is_err());
+    assert!(matches!(
+        events.lock().unwrap().last(),
+        Some(TranslateEvent::Failed { error, .. }) if error == "mock failure"
+    ));
+
+    Ok(())
+}
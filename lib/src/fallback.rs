@@ -0,0 +1,250 @@
+use crate::ffi_proxy::ProxyTranslatorFactory;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::trace::record_failure;
+use crate::utils::supports_task;
+use crate::{
+    BoxedTranslator, Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask,
+    Translator, TranslatorFactory, TranslatorSpec,
+};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// 按顺序尝试多个翻译器，出错或不支持该语言对时自动切换到下一个
+pub struct FallbackTranslator {
+    translators: Vec<(String, BoxedTranslator)>,
+}
+
+impl FallbackTranslator {
+    pub fn with_translators(translators: Vec<(String, BoxedTranslator)>) -> Self {
+        FallbackTranslator { translators }
+    }
+
+    pub fn push(&mut self, name: impl Into<String>, translator: BoxedTranslator) {
+        self.translators.push((name.into(), translator));
+    }
+
+    /// 由 `config["translators"]` 中的 `TranslatorSpec` 列表创建
+    pub async fn from_config(config: &Value, factory: &dyn TranslatorFactory) -> Result<Self> {
+        let specs: Vec<TranslatorSpec> = serde_json::from_value(
+            config
+                .get("translators")
+                .cloned()
+                .ok_or(anyhow!("missing argument: translators"))?,
+        )?;
+
+        let mut translators = vec![];
+        for spec in specs {
+            let translator = factory.create(&spec.name, spec.config).await?;
+            translators.push((spec.name, translator));
+        }
+
+        Ok(FallbackTranslator::with_translators(translators))
+    }
+
+    fn merge_languages(
+        &self,
+        f: impl Fn(&BoxedTranslator) -> Result<Vec<String>>,
+    ) -> Result<Vec<String>> {
+        let mut list: Vec<String> = vec![];
+        for (_, translator) in &self.translators {
+            for lang in f(translator)? {
+                if !list.contains(&lang) {
+                    list.push(lang);
+                }
+            }
+        }
+        Ok(list)
+    }
+}
+
+#[async_trait]
+impl Translator for FallbackTranslator {
+    type This = Self;
+
+    async fn new(config: Value) -> Result<Self> {
+        let factory = ProxyTranslatorFactory::from_config(&config)?;
+        FallbackTranslator::from_config(&config, &factory).await
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.merge_languages(|t| t.get_supported_input_languages())
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.merge_languages(|t| t.get_supported_output_languages())
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        Ok(self
+            .translators
+            .iter()
+            .any(|(_, t)| t.is_supported_input_language(lang.clone()).unwrap_or(false)))
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        Ok(self.translators.iter().any(|(_, t)| {
+            t.is_supported_output_language(lang.clone())
+                .unwrap_or(false)
+        }))
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        Ok(self.translators.iter().any(|(_, t)| {
+            t.is_supported_pair(source.clone(), target.clone())
+                .unwrap_or(false)
+        }))
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        let mut pairs = vec![];
+        for (_, translator) in &self.translators {
+            for pair in translator.get_supported_pairs()? {
+                if !pairs.contains(&pair) {
+                    pairs.push(pair);
+                }
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// 以首选翻译器为准
+    fn capabilities(&self) -> Capabilities {
+        self.translators
+            .first()
+            .map(|(_, translator)| translator.capabilities())
+            .unwrap_or_default()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let mut errors = vec![];
+
+        for (name, translator) in &self.translators {
+            if !supports_task(translator.as_ref(), &task) {
+                errors.push(format!("{}: unsupported language pair", name));
+                continue;
+            }
+
+            match translator.translate(task.clone()).await {
+                Ok(mut result) => {
+                    result.provider = Some(name.clone());
+                    return Ok(result);
+                }
+                Err(e) => {
+                    record_failure(name, &e);
+                    errors.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+
+        bail!("all translators failed: [{}]", errors.join("; "))
+    }
+
+    /// 尚未输出译文时出错才会切换，已输出部分内容后出错直接返回错误
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let mut errors = vec![];
+        let mut started = false;
+
+        for (name, translator) in &self.translators {
+            if !supports_task(translator.as_ref(), &task) {
+                errors.push(format!("{}: unsupported language pair", name));
+                continue;
+            }
+
+            let (tx, mut rx) = mpsc::channel(64);
+            let mut produced = false;
+
+            let forward = async {
+                while let Some(chunk) = rx.recv().await {
+                    let chunk = match chunk {
+                        // Start 延迟到第一个输出时发送，避免切换后重复
+                        TranslateStreamChunk::Start => continue,
+                        TranslateStreamChunk::Delta(mut delta) => {
+                            delta.provider = Some(name.clone());
+                            TranslateStreamChunk::Delta(delta)
+                        }
+                        TranslateStreamChunk::End => TranslateStreamChunk::End,
+                    };
+
+                    if !started {
+                        sender.send(TranslateStreamChunk::Start).await?;
+                        started = true;
+                    }
+                    produced = true;
+                    sender.send(chunk).await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            };
+
+            let (result, forwarded) =
+                tokio::join!(translator.translate_stream(task.clone(), tx), forward);
+            forwarded?;
+
+            match result {
+                Ok(_) => return Ok(()),
+                Err(e) if produced => return Err(e),
+                Err(e) => {
+                    record_failure(name, &e);
+                    errors.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+
+        bail!("all translators failed: [{}]", errors.join("; "))
+    }
+}
+
+#[tokio::test]
+async fn test_fallback_translate() -> Result<()> {
+    let translator = FallbackTranslator::with_translators(vec![
+        ("broken".to_string(), Box::new(MockTranslator::failing())),
+        (
+            "ja-only".to_string(),
+            Box::new(MockTranslator::only("JA:", &["ja"])),
+        ),
+        ("backup".to_string(), Box::new(MockTranslator::new("B:"))),
+    ]);
+
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("B:Hello"));
+    assert_eq!(result.provider.as_deref(), Some("backup"));
+
+    let (tx, mut rx) = mpsc::channel(64);
+    translator.translate_stream(task("Hello"), tx).await?;
+
+    let mut chunks = vec![];
+    while let Some(chunk) = rx.recv().await {
+        chunks.push(chunk);
+    }
+    assert_eq!(chunks.len(), 3);
+    assert!(matches!(chunks[0], TranslateStreamChunk::Start));
+    assert!(
+        matches!(&chunks[1], TranslateStreamChunk::Delta(r) if r.provider.as_deref() == Some("backup"))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fallback_all_failed() -> Result<()> {
+    let translator = FallbackTranslator::with_translators(vec![
+        ("a".to_string(), Box::new(MockTranslator::failing())),
+        (
+            "b".to_string(),
+            Box::new(MockTranslator::only("JA:", &["ja"])),
+        ),
+    ]);
+
+    let err = translator.translate(task("Hello")).await.unwrap_err();
+    assert!(err.to_string().contains("a: mock failure"));
+    assert!(err.to_string().contains("b: unsupported language pair"));
+
+    Ok(())
+}
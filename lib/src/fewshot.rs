@@ -0,0 +1,144 @@
+use crate::{TranslateTask, TranslatedItem};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 示例的选取与排列方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FewShotOrder {
+    /// 取前几条，保持 `references` 中的顺序
+    #[default]
+    Original,
+    /// 取最后几条，适合按时间顺序排列的剧集、章节内容
+    Recent,
+    /// 取与原文最相近的几条，越相近越靠近待翻译文本
+    Relevance,
+}
+
+/// 将 `task.references` 作为多轮对话示例发送的配置
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct FewShotConfig {
+    /// 最多使用的示例数
+    pub max_examples: usize,
+    pub order: FewShotOrder,
+}
+
+impl Default for FewShotConfig {
+    fn default() -> Self {
+        FewShotConfig {
+            max_examples: 5,
+            order: FewShotOrder::default(),
+        }
+    }
+}
+
+/// 拉丁文字按单词、其余文字按单个字符切分
+fn tokens(text: &str) -> HashSet<String> {
+    let mut tokens = HashSet::new();
+    let mut word = String::new();
+
+    for c in text.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_ascii_alphanumeric() {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            tokens.insert(std::mem::take(&mut word));
+        }
+        if c.is_alphanumeric() {
+            tokens.insert(c.to_string());
+        }
+    }
+    if !word.is_empty() {
+        tokens.insert(word);
+    }
+
+    tokens
+}
+
+/// 按配置选出用作示例的参考译文，返回顺序即发送顺序
+pub fn select_examples<'a>(
+    task: &'a TranslateTask,
+    config: &FewShotConfig,
+) -> Vec<&'a TranslatedItem> {
+    let references: Vec<&TranslatedItem> = task
+        .references
+        .iter()
+        .filter(|item| !item.source.trim().is_empty() && !item.target.trim().is_empty())
+        .collect();
+    let max = config.max_examples.min(references.len());
+
+    match config.order {
+        FewShotOrder::Original => references[..max].to_vec(),
+        FewShotOrder::Recent => references[references.len() - max..].to_vec(),
+        FewShotOrder::Relevance => {
+            let content = tokens(&task.content);
+            let mut scored: Vec<(usize, usize, &TranslatedItem)> = references
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| (tokens(&item.source).intersection(&content).count(), i, item))
+                .collect();
+
+            // 相同得分时靠后的条目更新
+            scored.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+            scored.truncate(max);
+            scored.reverse();
+            scored.into_iter().map(|(_, _, item)| item).collect()
+        }
+    }
+}
+
+#[test]
+fn test_select_examples() {
+    let item = |source: &str, target: &str| TranslatedItem {
+        source: source.to_string(),
+        target: target.to_string(),
+    };
+    let mut task = crate::testing::task("The dragon attacks the castle");
+    task.references = vec![
+        item("Good morning", "早上好"),
+        item("The dragon sleeps", "巨龙在沉睡"),
+        item("Empty", ""),
+        item("Close the door", "关门"),
+        item("The castle is quiet", "城堡很安静"),
+    ];
+
+    let sources = |config: FewShotConfig| -> Vec<String> {
+        select_examples(&task, &config)
+            .iter()
+            .map(|item| item.source.clone())
+            .collect()
+    };
+
+    assert_eq!(
+        sources(FewShotConfig {
+            max_examples: 2,
+            order: FewShotOrder::Original,
+        }),
+        vec!["Good morning", "The dragon sleeps"]
+    );
+    assert_eq!(
+        sources(FewShotConfig {
+            max_examples: 2,
+            order: FewShotOrder::Recent,
+        }),
+        vec!["Close the door", "The castle is quiet"]
+    );
+    assert_eq!(
+        sources(FewShotConfig {
+            max_examples: 3,
+            order: FewShotOrder::Relevance,
+        }),
+        vec!["Close the door", "The dragon sleeps", "The castle is quiet"]
+    );
+    assert_eq!(
+        sources(FewShotConfig {
+            max_examples: 10,
+            ..Default::default()
+        })
+        .len(),
+        4
+    );
+}
@@ -1,22 +1,458 @@
-use crate::{TranslateResult, TranslateStreamChunk};
+use crate::error::XTranslateError;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateStreamChunk, TranslateTask};
 use anyhow::{anyhow, bail, Result};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::{c_char, c_void, CStr, CString};
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 use std::ptr;
+use std::slice;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, LazyLock, Mutex};
+use std::time::Duration;
+use tokio::runtime::{Builder, Handle, Runtime};
+use tokio::sync::Notify;
 
-pub type GetPluginName = unsafe extern fn() -> *mut c_char;
-pub type CreateTranslator = unsafe extern fn(*const c_char) -> *mut FfiResult<TranslatorHandle>;
-pub type GetSupportedInputLanguages = unsafe extern fn(*mut TranslatorHandle, *mut *mut *const c_char, *mut usize) -> *mut FfiResult<i8>;
-pub type IsSupportedInputLanguage = unsafe extern fn(*mut TranslatorHandle, *const c_char) -> *mut FfiResult<i8>;
-pub type GetSupportedOutputLanguages = unsafe extern fn(*mut TranslatorHandle, *mut *mut *const c_char, *mut usize) -> *mut FfiResult<i8>;
-pub type IsSupportedOutputLanguage = unsafe extern fn(*mut TranslatorHandle, *const c_char) -> *mut FfiResult<i8>;
-pub type CallTranslate = unsafe extern fn(*mut TranslatorHandle, *const c_char) -> *mut FfiResult<TranslateResultFFI>;
-pub type CallTranslateStream = unsafe extern fn(*mut TranslatorHandle, *const c_char, StreamCallback, *mut c_void) -> *mut FfiResult<i8>;
+/// 插件与宿主之间的 FFI 版本，导出函数的签名或 `#[repr(C)]` 结构体的布局不兼容地变化时加一。
+/// 没有导出 `get_abi_version` 的旧版插件视为版本 1
+pub const ABI_VERSION: u32 = 1;
+
+/// 编译插件或宿主时使用的 lib 版本
+pub const LIB_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 引入当前 `ABI_VERSION` 的 lib 版本，插件要求宿主不低于该版本
+pub const MIN_HOST_VERSION: &str = "0.1.0";
+
+/// `get_plugin_metadata` 返回的插件信息
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginMetadata {
+    pub name: String,
+    /// 插件 crate 的版本
+    pub version: String,
+    /// 编译插件时使用的 lib 版本
+    pub lib_version: String,
+    pub abi_version: u32,
+    /// 要求的最低宿主 lib 版本
+    pub min_host_version: String,
+    /// 编译时启用的 Cargo 特性，如 `tracing`
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl PluginMetadata {
+    /// 宿主的 lib 版本满足插件要求
+    pub fn is_compatible(&self) -> bool {
+        self.abi_version == ABI_VERSION
+            && parse_version(LIB_VERSION) >= parse_version(&self.min_host_version)
+    }
+}
+
+/// 按数字比较 `major.minor.patch`，忽略预发布后缀
+pub fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or("")
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// 插件中 `CARGO_PKG_*` 环境变量为空时视为未设置
+pub fn non_empty(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// 插件内共用的运行时，首次调用时创建。每个插件动态库各有一个
+static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| {
+    Builder::new_multi_thread()
+        .enable_all()
+        .thread_name("xtranslator-plugin")
+        .build()
+        .expect("failed to build plugin runtime")
+});
+
+pub fn runtime() -> &'static Runtime {
+    &RUNTIME
+}
+
+/// 在当前线程已进入的运行时中执行，否则使用共享运行时，不再为每次调用创建运行时
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    match Handle::try_current() {
+        Ok(handle) => handle.block_on(future),
+        Err(_) => RUNTIME.block_on(future),
+    }
+}
+
+/// `call_translate_batch` 中同时进行的翻译数
+pub const BATCH_PARALLEL: usize = 8;
+
+/// `call_translate_batch` 中一个任务的结果，单个任务失败不影响其他任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum BatchResult {
+    Ok(TranslateResult),
+    Err(String),
+}
+
+impl BatchResult {
+    pub fn into_result(self) -> Result<TranslateResult> {
+        match self {
+            BatchResult::Ok(result) => Ok(result),
+            BatchResult::Err(e) => Err(restore_error(&e, str::to_string)),
+        }
+    }
+}
+
+impl From<Result<TranslateResult>> for BatchResult {
+    fn from(result: Result<TranslateResult>) -> Self {
+        match result {
+            Ok(result) => BatchResult::Ok(result),
+            Err(e) => BatchResult::Err(encode_error(&e, format!("{}", e))),
+        }
+    }
+}
+
+/// 插件返回的错误中包含 `XTranslateError` 时的 JSON 形式，宿主据此还原错误类别。
+/// 其余错误与旧版插件的错误只有文本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FfiError {
+    #[serde(flatten)]
+    pub error: XTranslateError,
+    /// 包含上下文的完整错误信息
+    pub detail: String,
+}
+
+/// 可识别的错误编码为 JSON 格式的 `FfiError`，其余错误只保留 `detail`
+pub fn encode_error(err: &anyhow::Error, detail: String) -> String {
+    let Some(error) = XTranslateError::find(err) else {
+        return detail;
+    };
+
+    let error = FfiError {
+        error: error.clone(),
+        detail,
+    };
+    serde_json::to_string(&error).unwrap_or(error.detail)
+}
+
+/// 还原 `encode_error` 编码的错误，`describe` 由完整错误信息生成外层的错误信息
+pub fn restore_error(message: &str, describe: impl FnOnce(&str) -> String) -> anyhow::Error {
+    match serde_json::from_str::<FfiError>(message) {
+        Ok(FfiError { error, detail }) => anyhow::Error::new(error).context(describe(&detail)),
+        Err(_) => anyhow!(describe(message)),
+    }
+}
+
+/// 按顺序返回每个任务的结果，最多同时翻译 `BATCH_PARALLEL` 个
+pub async fn translate_batch(translator: &dyn DynTranslator, tasks: Vec<TranslateTask>) -> Vec<BatchResult> {
+    stream::iter(tasks.into_iter().map(|task| translator.translate(task)))
+        .buffered(BATCH_PARALLEL)
+        .map(BatchResult::from)
+        .collect()
+        .await
+}
+
+pub type GetAbiVersion = unsafe extern "C" fn() -> u32;
+/// 返回 JSON 格式的 `BTreeMap<String, StructLayout>`，见 `abi_layout`
+pub type GetAbiLayout = unsafe extern "C" fn() -> *mut c_char;
+/// 插件返回的字符串都要交回插件释放，宿主与插件可能使用不同的分配器
+pub type FreeString = unsafe extern "C" fn(*mut c_char);
+/// 释放结果及其错误信息，`ptr` 的所有权已交给调用方
+pub type FreeFfiResult = unsafe extern "C" fn(*mut FfiResult<c_void>);
+/// 释放 `FfiResult<i8>`，包括其中的状态值
+pub type FreeFfiStatus = unsafe extern "C" fn(*mut FfiResult<i8>);
+pub type FreeTranslateResult = unsafe extern "C" fn(*mut TranslateResultFFI);
+pub type FreeTranslateStreamChunk = unsafe extern "C" fn(*mut TranslateStreamChunkFFI);
+pub type FreeSupportedLanguages = unsafe extern "C" fn(*mut *const c_char, usize);
+pub type GetPluginName = unsafe extern "C" fn() -> *mut c_char;
+/// 返回 JSON 格式的 `PluginMetadata`
+pub type GetPluginMetadata = unsafe extern "C" fn() -> *mut c_char;
+/// 返回 JSON 格式的配置 schema，没有时返回空指针
+pub type GetConfigSchema = unsafe extern "C" fn() -> *mut c_char;
+/// 结果为 JSON 格式的 `Vec<ConfigIssue>`
+pub type ValidateConfig = unsafe extern "C" fn(*const c_char) -> *mut FfiResult<c_char>;
+/// 插件中的翻译器名称，使用 `free_supported_languages` 释放
+pub type GetPluginTranslators = unsafe extern "C" fn(*mut *mut *const c_char, *mut usize) -> *mut FfiResult<i8>;
+/// 与 `GetConfigSchema` 相同，按名称指定插件中的翻译器
+pub type GetNamedConfigSchema = unsafe extern "C" fn(*const c_char) -> *mut c_char;
+/// 返回 JSON 格式的 `StaticInfo`，没有该翻译器时返回空指针，使用 `free_string` 释放
+pub type GetNamedStaticInfo = unsafe extern "C" fn(*const c_char) -> *mut c_char;
+pub type ValidateNamedConfig = unsafe extern "C" fn(*const c_char, *const c_char) -> *mut FfiResult<c_char>;
+/// 创建插件中的默认翻译器
+pub type CreateTranslator = unsafe extern "C" fn(*const c_char) -> *mut FfiResult<TranslatorHandle>;
+pub type CreateNamedTranslator = unsafe extern "C" fn(*const c_char, *const c_char) -> *mut FfiResult<TranslatorHandle>;
+/// 翻译器只能由创建它的插件释放。`clone_translator` 之后每个引用释放一次，最后一个引用释放时销毁翻译器，
+/// 销毁前等待进行中的调用结束，超过 `DESTROY_TIMEOUT` 后取消它们
+pub type DestroyTranslator = unsafe extern "C" fn(*mut TranslatorHandle);
+/// 增加一个引用并返回同一个句柄，供多个线程各自持有、各自释放
+pub type CloneTranslator = unsafe extern "C" fn(*mut TranslatorHandle) -> *mut TranslatorHandle;
+pub type GetSupportedInputLanguages = unsafe extern "C" fn(*mut TranslatorHandle, *mut *mut *const c_char, *mut usize) -> *mut FfiResult<i8>;
+pub type IsSupportedInputLanguage = unsafe extern "C" fn(*mut TranslatorHandle, *const c_char) -> *mut FfiResult<i8>;
+pub type GetSupportedOutputLanguages = unsafe extern "C" fn(*mut TranslatorHandle, *mut *mut *const c_char, *mut usize) -> *mut FfiResult<i8>;
+pub type IsSupportedOutputLanguage = unsafe extern "C" fn(*mut TranslatorHandle, *const c_char) -> *mut FfiResult<i8>;
+pub type IsSupportedPair = unsafe extern "C" fn(*mut TranslatorHandle, *const c_char, *const c_char) -> *mut FfiResult<i8>;
+/// 语言对按源语言、目标语言交替展开为一个数组，使用 `free_supported_languages` 释放
+pub type GetSupportedPairs = unsafe extern "C" fn(*mut TranslatorHandle, *mut *mut *const c_char, *mut usize) -> *mut FfiResult<i8>;
+/// 结果为 JSON 格式的 `Capabilities`
+pub type GetCapabilities = unsafe extern "C" fn(*mut TranslatorHandle) -> *mut FfiResult<c_char>;
+pub type CallTranslate = unsafe extern "C" fn(*mut TranslatorHandle, *const c_char) -> *mut FfiResult<TranslateResultFFI>;
+/// 立即返回，翻译在插件的运行时中完成后调用 `CompletionCallback`。回调之前不能释放翻译器
+pub type CallTranslateAsync = unsafe extern "C" fn(*mut TranslatorHandle, *const c_char, CompletionCallback, *mut c_void) -> *mut FfiResult<i8>;
+pub type CallTranslateStream = unsafe extern "C" fn(*mut TranslatorHandle, *const c_char, StreamCallback, *mut c_void) -> *mut FfiResult<i8>;
+/// 与 `call_translate_stream` 相同，但可以通过 `cancel_stream` 中止。被取消时结果为 1
+pub type CallTranslateStreamCancellable = unsafe extern "C" fn(*mut TranslatorHandle, *const c_char, StreamCallback, *mut c_void, *mut StreamHandle) -> *mut FfiResult<i8>;
+/// 参数为 JSON 格式的任务数组，结果为对应的 `BatchResult` 数组
+pub type CallTranslateBatch = unsafe extern "C" fn(*mut TranslatorHandle, *const c_char) -> *mut FfiResult<c_char>;
+/// 以下变体的 JSON 参数为 `(ptr, len)` 形式的 UTF-8，不要求以 NUL 结尾，无效的字节替换为 U+FFFD
+pub type CreateTranslatorUtf8 = unsafe extern "C" fn(*const u8, usize) -> *mut FfiResult<TranslatorHandle>;
+pub type CallTranslateUtf8 = unsafe extern "C" fn(*mut TranslatorHandle, *const u8, usize) -> *mut FfiResult<TranslateResultFFI>;
+pub type CallTranslateStreamUtf8 = unsafe extern "C" fn(*mut TranslatorHandle, *const u8, usize, StreamCallback, *mut c_void, *mut StreamHandle) -> *mut FfiResult<i8>;
+/// 语言代码为 `(ptr, len)` 形式的 UTF-8，结果与对应的 C 字符串版本相同
+pub type IsSupportedInputLanguageUtf8 = unsafe extern "C" fn(*mut TranslatorHandle, *const u8, usize) -> *mut FfiResult<i8>;
+pub type IsSupportedOutputLanguageUtf8 = unsafe extern "C" fn(*mut TranslatorHandle, *const u8, usize) -> *mut FfiResult<i8>;
+pub type IsSupportedPairUtf8 = unsafe extern "C" fn(*mut TranslatorHandle, *const u8, usize, *const u8, usize) -> *mut FfiResult<i8>;
+/// 任务为 `(ptr, len)` 形式的 UTF-8，结果为 JSON 格式的 `TranslateResult`，写入 `arena` 后通过最后两个参数返回其位置，
+/// 不以 NUL 结尾，不需要单独释放
+pub type CallTranslateInto = unsafe extern "C" fn(*mut TranslatorHandle, *const u8, usize, *mut ResultArena, *mut *const u8, *mut usize) -> *mut FfiResult<i8>;
+/// UTF-16 变体，供 Windows 与 .NET 宿主使用，`len` 为 u16 个数
+pub type CreateTranslatorUtf16 = unsafe extern "C" fn(*const u16, usize) -> *mut FfiResult<TranslatorHandle>;
+pub type CallTranslateUtf16 = unsafe extern "C" fn(*mut TranslatorHandle, *const u16, usize) -> *mut FfiResult<TranslateResultFFI>;
+pub type CallTranslateStreamUtf16 = unsafe extern "C" fn(*mut TranslatorHandle, *const u16, usize, StreamCallback, *mut c_void, *mut StreamHandle) -> *mut FfiResult<i8>;
+/// 拒绝新的调用并等待进行中的调用结束，超过 `timeout_ms` 后取消它们。返回后才能安全地 `destroy_translator`，
+/// 结果为 1 表示有调用被取消
+pub type ShutdownTranslator = unsafe extern "C" fn(*mut TranslatorHandle, u64) -> *mut FfiResult<i8>;
+pub type CreateStreamHandle = unsafe extern "C" fn() -> *mut StreamHandle;
+/// 可以在其他线程中调用
+pub type CancelStream = unsafe extern "C" fn(*mut StreamHandle);
+/// 流式翻译返回后才能释放
+pub type FreeStreamHandle = unsafe extern "C" fn(*mut StreamHandle);
+pub type CreateResultArena = unsafe extern "C" fn() -> *mut ResultArena;
+/// 释放后之前写入的结果都失效
+pub type FreeResultArena = unsafe extern "C" fn(*mut ResultArena);
+/// 进度回调，参数为 `ProgressPhase`（1 到 4）、已用时间（毫秒）、已发送与已接收的字节数。
+/// 在插件的线程中调用，`call_translate_with_progress` 返回后不再调用
+pub type ProgressCallback = extern "C" fn(u32, u64, u64, u64, *mut c_void);
+/// 与 `call_translate` 相同，翻译期间通过 `progress` 报告进度，`progress` 可以为空
+pub type CallTranslateWithProgress = unsafe extern "C" fn(*mut TranslatorHandle, *const c_char, Option<ProgressCallback>, *mut c_void) -> *mut FfiResult<TranslateResultFFI>;
+/// 插件日志回调，参数为级别（1 error 到 5 trace）、target 与消息。字符串只在回调期间有效
+pub type LogCallback = extern "C" fn(u32, *const c_char, *const c_char, *mut c_void);
+/// 设置插件日志回调，`max_level` 为 0 时关闭
+pub type SetLogCallback = unsafe extern "C" fn(Option<LogCallback>, *mut c_void, u32);
 
 #[repr(C)]
 pub struct TranslatorHandle {
     _private: [u8; 0],
 }
 
+/// 插件内 `StreamControl` 的不透明指针
+#[repr(C)]
+pub struct StreamHandle {
+    _private: [u8; 0],
+}
+
+/// 插件内 `ResultBuffer` 的不透明指针
+#[repr(C)]
+pub struct ResultArena {
+    _private: [u8; 0],
+}
+
+/// `call_translate_into` 写入结果的缓冲区，由插件创建和释放，可以在多次翻译之间复用。
+/// 每次写入覆盖上一次的结果，同一时间只能用于一个调用
+#[derive(Debug, Default)]
+pub struct ResultBuffer {
+    data: Vec<u8>,
+}
+
+impl ResultBuffer {
+    pub fn into_ffi(self) -> *mut ResultArena {
+        Box::into_raw(Box::new(self)) as *mut ResultArena
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` 为空指针，或是 `into_ffi` 返回且尚未释放的缓冲区，`'a` 内没有其他引用
+    pub unsafe fn from_ptr<'a>(ptr: *mut ResultArena) -> Option<&'a mut ResultBuffer> {
+        unsafe { (ptr as *mut ResultBuffer).as_mut() }
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` 为空指针，或是 `into_ffi` 返回且尚未释放的缓冲区，之后不再使用
+    pub unsafe fn free(ptr: *mut ResultArena) {
+        if !ptr.is_null() {
+            drop(unsafe { Box::from_raw(ptr as *mut ResultBuffer) });
+        }
+    }
+
+    /// 以 JSON 写入 `value`，返回的切片在下一次写入之前有效
+    pub fn write_json<T: Serialize>(&mut self, value: &T) -> Result<&[u8]> {
+        self.data.clear();
+        serde_json::to_writer(&mut self.data, value)?;
+
+        Ok(&self.data)
+    }
+}
+
+/// 流式翻译的控制状态，由插件创建和释放
+#[derive(Debug, Default)]
+pub struct StreamControl {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl StreamControl {
+    pub fn into_ffi(self) -> *mut StreamHandle {
+        Box::into_raw(Box::new(self)) as *mut StreamHandle
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` 为空指针，或是 `into_ffi` 返回的句柄，且在 `'a` 内不会被释放
+    pub unsafe fn from_ptr<'a>(ptr: *mut StreamHandle) -> Option<&'a StreamControl> {
+        unsafe { (ptr as *const StreamControl).as_ref() }
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` 为空指针，或是 `into_ffi` 返回且尚未释放的句柄，没有仍在使用它的流式翻译
+    pub unsafe fn free(ptr: *mut StreamHandle) {
+        if !ptr.is_null() {
+            drop(unsafe { Box::from_raw(ptr as *mut StreamControl) });
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// 被取消时完成
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// 翻译器句柄上正在进行的调用
+#[derive(Debug, Default)]
+struct InFlight {
+    calls: Mutex<usize>,
+    idle: Condvar,
+    closing: AtomicBool,
+    cancel: StreamControl,
+}
+
+static IN_FLIGHT: LazyLock<Mutex<HashMap<usize, Arc<InFlight>>>> = LazyLock::new(Default::default);
+
+fn in_flight(handle: *mut TranslatorHandle) -> Arc<InFlight> {
+    IN_FLIGHT.lock().unwrap().entry(handle as usize).or_default().clone()
+}
+
+/// 一次进行中的调用，释放时结束。回调应在释放之前完成，`shutdown_translator` 返回后不会再有回调
+pub struct CallGuard(Arc<InFlight>);
+
+impl CallGuard {
+    /// 翻译器正在关闭时返回错误
+    pub fn begin(handle: *mut TranslatorHandle) -> Result<Self> {
+        let state = in_flight(handle);
+        let mut calls = state.calls.lock().unwrap();
+        if state.closing.load(Ordering::SeqCst) {
+            bail!("translator is shutting down");
+        }
+        *calls += 1;
+        drop(calls);
+
+        Ok(CallGuard(state))
+    }
+
+    /// `shutdown_translator` 等待超时后完成，调用应尽快结束
+    pub async fn cancelled(&self) {
+        self.0.cancel.cancelled().await
+    }
+}
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        let mut calls = self.0.calls.lock().unwrap();
+        *calls -= 1;
+        if *calls == 0 {
+            self.0.idle.notify_all();
+        }
+    }
+}
+
+/// 拒绝新的调用并等待进行中的调用结束，超过 `timeout` 后取消它们并等待其返回。
+/// 返回是否有调用被取消
+pub fn shutdown_calls(handle: *mut TranslatorHandle, timeout: Duration) -> bool {
+    let state = in_flight(handle);
+    state.closing.store(true, Ordering::SeqCst);
+
+    let calls = state.calls.lock().unwrap();
+    let (calls, wait) = state.idle.wait_timeout_while(calls, timeout, |calls| *calls > 0).unwrap();
+    if !wait.timed_out() {
+        return false;
+    }
+
+    state.cancel.cancel();
+    drop(state.idle.wait_while(calls, |calls| *calls > 0).unwrap());
+    true
+}
+
+/// `destroy_translator` 等待进行中的调用结束的时间
+pub const DESTROY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 翻译器释放后移除记录，句柄的地址可能被复用
+pub fn forget_calls(handle: *mut TranslatorHandle) {
+    IN_FLIGHT.lock().unwrap().remove(&(handle as usize));
+}
+
+/// `clone_translator` 增加的引用数，没有记录的句柄只有创建时的一个引用
+static EXTRA_REFS: LazyLock<Mutex<HashMap<usize, usize>>> = LazyLock::new(Default::default);
+
+/// 增加一个引用，每个引用都要调用一次 `destroy_translator`
+pub fn retain_handle(handle: *mut TranslatorHandle) {
+    *EXTRA_REFS.lock().unwrap().entry(handle as usize).or_insert(0) += 1;
+}
+
+/// 释放一个引用，返回是否为最后一个引用，此时由调用方释放翻译器
+pub fn release_handle(handle: *mut TranslatorHandle) -> bool {
+    let mut refs = EXTRA_REFS.lock().unwrap();
+    match refs.get_mut(&(handle as usize)) {
+        Some(extra) if *extra > 1 => {
+            *extra -= 1;
+            false
+        }
+        Some(_) => {
+            refs.remove(&(handle as usize));
+            false
+        }
+        None => true,
+    }
+}
+
 #[repr(C)]
 pub struct FfiObj {
     _private: [u8; 0],
@@ -25,6 +461,7 @@ pub struct FfiObj {
 #[repr(C)]
 pub struct FfiResult<T> {
     pub ptr: *mut T,
+    /// 错误信息，包含可识别的错误类别时为 JSON 格式的 `FfiError`
     pub err: *mut c_char,
 }
 
@@ -48,15 +485,120 @@ impl <T> Into<FfiResult<T>> for Result<T> {
                 }
             }
             Err(err) => {
+                let message = encode_error(&err, format!("{:?}", err));
                 FfiResult {
                     ptr: ptr::null_mut(),
-                    err: CString::new(format!("{:?}", err)).unwrap().into_raw(),
+                    err: CString::new(message.replace('\0', "")).unwrap_or_default().into_raw(),
                 }
             }
         }
     }
 }
 
+/// # Safety
+///
+/// `s` 为空指针，或是本插件以 `CString::into_raw` 返回且尚未释放的字符串
+pub unsafe fn free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+/// # Safety
+///
+/// `result` 为空指针，或是本插件以 `to_ptr` 返回且尚未释放的结果，`ptr` 的所有权已交给调用方
+pub unsafe fn free_ffi_result<T>(result: *mut FfiResult<T>) {
+    if result.is_null() {
+        return;
+    }
+    let result = unsafe { Box::from_raw(result) };
+    unsafe { free_string(result.err) };
+}
+
+/// # Safety
+///
+/// 与 `free_ffi_result` 相同，`ptr` 也由本插件分配且尚未释放
+pub unsafe fn free_ffi_status(result: *mut FfiResult<i8>) {
+    if result.is_null() {
+        return;
+    }
+    let result = unsafe { Box::from_raw(result) };
+    if !result.ptr.is_null() {
+        drop(unsafe { Box::from_raw(result.ptr) });
+    }
+    unsafe { free_string(result.err) };
+}
+
+/// `(ptr, len)` 形式的 UTF-8 参数，`len` 为 0 时可以传空指针
+///
+/// # Safety
+///
+/// `len` 不为 0 时 `ptr` 为空指针，或指向 `len` 个可读的字节，且在 `'a` 内有效、不被修改
+pub unsafe fn utf8_arg<'a>(ptr: *const u8, len: usize) -> Result<Cow<'a, str>> {
+    if len == 0 {
+        return Ok(Cow::Borrowed(""));
+    }
+    if ptr.is_null() {
+        bail!("Null pointer received");
+    }
+
+    Ok(String::from_utf8_lossy(unsafe { slice::from_raw_parts(ptr, len) }))
+}
+
+/// `(ptr, len)` 形式的 UTF-16 参数，`len` 为 u16 个数
+///
+/// # Safety
+///
+/// 与 `utf8_arg` 相同，`ptr` 指向 `len` 个可读的 u16
+pub unsafe fn utf16_arg<'a>(ptr: *const u16, len: usize) -> Result<Cow<'a, str>> {
+    if len == 0 {
+        return Ok(Cow::Borrowed(""));
+    }
+    if ptr.is_null() {
+        bail!("Null pointer received");
+    }
+
+    Ok(Cow::Owned(String::from_utf16_lossy(unsafe { slice::from_raw_parts(ptr, len) })))
+}
+
+/// 字符串直接作为 `FfiResult::ptr` 返回，由宿主交给 `free_string` 释放
+pub fn string_result(s: String) -> *mut FfiResult<c_char> {
+    let result = match CString::new(s) {
+        Ok(s) => FfiResult {
+            ptr: s.into_raw(),
+            err: ptr::null_mut(),
+        },
+        Err(e) => FfiResult {
+            ptr: ptr::null_mut(),
+            err: CString::new(format!("{:?}", e).replace('\0', "")).unwrap_or_default().into_raw(),
+        },
+    };
+    Box::into_raw(Box::new(result))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "unknown panic"
+    }
+}
+
+/// panic 不能跨越 C 边界展开，发生时返回 `default`
+pub fn catch_panic<R>(default: R, f: impl FnOnce() -> R) -> R {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+/// panic 转为 `FfiResult` 中的错误
+pub fn catch_ffi<T>(f: impl FnOnce() -> *mut FfiResult<T>) -> *mut FfiResult<T> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(payload) => Err(anyhow!("plugin panicked: {}", panic_message(payload.as_ref()))).to_ptr(),
+    }
+}
+
 pub fn unwrap_handle_result<T>(result: *mut FfiResult<T>) -> Result<*mut T> {
     if result.is_null() {
         return Err(anyhow!("result is null"));
@@ -65,7 +607,8 @@ pub fn unwrap_handle_result<T>(result: *mut FfiResult<T>) -> Result<*mut T> {
     let result = unsafe { Box::from_raw(result) };
 
     if !result.err.is_null() {
-        return Err(anyhow!("result's error: {:?}", unsafe { CString::from_raw(result.err) }.to_string_lossy().to_owned()));
+        let message = unsafe { CString::from_raw(result.err) }.to_string_lossy().into_owned();
+        return Err(restore_error(&message, |detail| format!("result's error: {:?}", detail)));
     }
 
     if result.ptr.is_null() {
@@ -106,6 +649,30 @@ impl TranslateResult {
         Box::into_raw(Box::new(TranslateResultFFI { reasoning, content }))
     }
 
+    /// 复制结果而不释放，之后由分配它的一方释放
+    ///
+    /// # Safety
+    ///
+    /// `result` 为空指针，或指向有效的 `TranslateResultFFI`，其中的字符串为空指针或以 NUL 结尾
+    pub unsafe fn copy_from_ffi(result: *const TranslateResultFFI) -> Result<TranslateResult> {
+        let Some(result) = (unsafe { result.as_ref() }) else {
+            bail!("null pointer received from ffi");
+        };
+
+        let copy = |s: *mut c_char| -> Result<Option<String>> {
+            if s.is_null() {
+                return Ok(None);
+            }
+            Ok(Some(unsafe { CStr::from_ptr(s) }.to_str()?.to_owned()))
+        };
+
+        Ok(TranslateResult {
+            reasoning: copy(result.reasoning)?,
+            content: copy(result.content)?,
+            ..Default::default()
+        })
+    }
+
     pub fn from_ffi(result: *mut TranslateResultFFI) -> Result<TranslateResult> {
         if result.is_null() {
             bail!("null pointer received from ffi");
@@ -124,11 +691,32 @@ impl TranslateResult {
             } else {
                 Some(unsafe { CString::from_raw(result.content).into_string()? })
             },
+            ..Default::default()
         })
     }
 }
 
-pub fn free_translate_result(result: *mut TranslateResultFFI) {
+/// 释放增量及其中的译文
+///
+/// # Safety
+///
+/// `chunk` 为空指针，或是本插件以 `into_ffi` 返回且尚未释放的增量
+pub unsafe fn free_translate_stream_chunk(chunk: *mut TranslateStreamChunkFFI) {
+    if chunk.is_null() {
+        return;
+    }
+    let chunk = unsafe { Box::from_raw(chunk) };
+    if let TranslateStreamChunkTag::Delta = chunk.tag {
+        unsafe { free_translate_result(chunk.data.delta) };
+    }
+}
+
+/// 释放结果及其中的字符串
+///
+/// # Safety
+///
+/// `result` 为空指针，或是本插件以 `into_ffi` 返回且尚未释放的结果
+pub unsafe fn free_translate_result(result: *mut TranslateResultFFI) {
     if result.is_null() {
         return;
     }
@@ -143,13 +731,31 @@ pub fn free_translate_result(result: *mut TranslateResultFFI) {
     }
 }
 
+/// 异步翻译的结果与 `call_translate` 的返回值相同，可能在插件的任意线程中调用
+pub type CompletionCallback = extern "C" fn(result: *mut FfiResult<TranslateResultFFI>, user_data: *mut c_void);
+
+pub type CompletionHandler = Box<dyn FnOnce(*mut FfiResult<TranslateResultFFI>) + Send>;
+
+/// `user_data` 为 `Box<CompletionHandler>`，只会被调用一次
+pub extern "C" fn completion_callback(result: *mut FfiResult<TranslateResultFFI>, user_data: *mut c_void) {
+    catch_panic((), || {
+        let handler = unsafe { Box::from_raw(user_data as *mut CompletionHandler) };
+        handler(result);
+    })
+}
+
+/// `chunk` 的所有权交给回调，读取后交给插件的 `free_translate_stream_chunk` 释放，
+/// 不能用宿主的分配器释放
 pub type StreamCallback = extern "C" fn(chunk: *mut TranslateStreamChunkFFI, cb: *mut c_void);
 
+/// `stream_callback` 的 `cb` 指向的闭包，插件可能在任意线程中调用
+pub type StreamHandler<'a> = Box<dyn Fn(*mut TranslateStreamChunkFFI) + Send + Sync + 'a>;
+
 pub extern "C" fn stream_callback(chunk: *mut TranslateStreamChunkFFI, cb: *mut c_void) {
-    unsafe {
-        let closure = &*(cb as *const Box<dyn Fn(*mut TranslateStreamChunkFFI)>);
+    catch_panic((), || unsafe {
+        let closure = &*(cb as *const StreamHandler);
         closure(chunk);
-    }
+    })
 }
 
 #[repr(C)]
@@ -193,6 +799,24 @@ impl TranslateStreamChunk {
         Box::into_raw(b)
     }
 
+    /// 复制增量而不取得所有权，之后由插件的 `free_translate_stream_chunk` 释放
+    ///
+    /// # Safety
+    ///
+    /// `chunk` 为空指针，或指向有效的 `TranslateStreamChunkFFI`，`Delta` 中的译文满足 `TranslateResult::copy_from_ffi` 的要求
+    pub unsafe fn copy_from_ffi(chunk: *const TranslateStreamChunkFFI) -> Result<TranslateStreamChunk> {
+        let Some(chunk) = (unsafe { chunk.as_ref() }) else {
+            bail!("null pointer received from ffi");
+        };
+        match chunk.tag {
+            TranslateStreamChunkTag::Start => Ok(TranslateStreamChunk::Start),
+            TranslateStreamChunkTag::Delta => {
+                Ok(TranslateStreamChunk::Delta(unsafe { TranslateResult::copy_from_ffi(chunk.data.delta) }?))
+            }
+            TranslateStreamChunkTag::End => Ok(TranslateStreamChunk::End),
+        }
+    }
+
     pub fn from_ffi(result: *mut TranslateStreamChunkFFI) -> Result<TranslateStreamChunk> {
         if result.is_null() {
             bail!("null pointer received from ffi");
@@ -220,7 +844,7 @@ pub fn wrap_err(error_ptr: *mut c_char) -> Result<()> {
         unsafe {
             let _ = Box::from_raw(error_ptr);
         }
-        return Err(anyhow!(msg));
+        return Err(restore_error(&msg, str::to_string));
     }
 
     Ok(())
@@ -254,7 +878,12 @@ pub fn convert_string_vec_to_c_array(
     Ok(0).to_ptr()
 }
 
-pub fn free_supported_languages(array: *mut *const c_char, len: usize) {
+/// 释放 `convert_string_vec_to_c_array` 返回的数组及其中的字符串
+///
+/// # Safety
+///
+/// `array` 为空指针，或是本插件以 `convert_string_vec_to_c_array` 返回且尚未释放的数组，`len` 为当时的长度
+pub unsafe fn free_supported_languages(array: *mut *const c_char, len: usize) {
     if array.is_null() || len == 0 {
         return;
     }
@@ -269,3 +898,232 @@ pub fn free_supported_languages(array: *mut *const c_char, len: usize) {
         }
     }
 }
+
+/// `#[repr(C)]` 结构体的大小、对齐与字段偏移，宿主加载插件时与自身比较，见 `get_abi_layout`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructLayout {
+    pub size: usize,
+    pub align: usize,
+    pub fields: BTreeMap<String, usize>,
+}
+
+macro_rules! struct_layout {
+    ($ty:ty, [$($field:ident),*]) => {
+        StructLayout {
+            size: std::mem::size_of::<$ty>(),
+            align: std::mem::align_of::<$ty>(),
+            fields: BTreeMap::from([$((stringify!($field).to_string(), std::mem::offset_of!($ty, $field))),*]),
+        }
+    };
+}
+
+/// 跨越 FFI 边界的结构体布局，`FfiResult<T>` 的布局与 `T` 无关
+pub fn abi_layout() -> BTreeMap<String, StructLayout> {
+    BTreeMap::from([
+        ("FfiResult".to_string(), struct_layout!(FfiResult<c_void>, [ptr, err])),
+        ("TranslateResultFFI".to_string(), struct_layout!(TranslateResultFFI, [reasoning, content])),
+        ("TranslateStreamChunkFFI".to_string(), struct_layout!(TranslateStreamChunkFFI, [tag, data])),
+    ])
+}
+
+/// 与 `abi_layout()` 不一致的结构体名称
+pub fn abi_layout_mismatches(plugin: &BTreeMap<String, StructLayout>) -> Vec<String> {
+    abi_layout()
+        .into_iter()
+        .filter(|(name, layout)| plugin.get(name) != Some(layout))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+#[test]
+fn test_abi_layout() {
+    let layout = abi_layout();
+    let pointer = std::mem::size_of::<*mut c_void>();
+    assert_eq!(layout["FfiResult"].size, 2 * pointer);
+    assert_eq!(layout["FfiResult"].fields["err"], pointer);
+    assert_eq!(layout["TranslateResultFFI"].fields["content"], pointer);
+    assert!(abi_layout_mismatches(&layout).is_empty());
+
+    let mut plugin = layout.clone();
+    plugin.get_mut("TranslateStreamChunkFFI").unwrap().size += 8;
+    plugin.remove("FfiResult");
+    assert_eq!(abi_layout_mismatches(&plugin), vec!["FfiResult", "TranslateStreamChunkFFI"]);
+}
+
+#[test]
+fn test_plugin_metadata() {
+    let mut metadata = PluginMetadata {
+        abi_version: ABI_VERSION,
+        min_host_version: MIN_HOST_VERSION.to_string(),
+        ..Default::default()
+    };
+    assert!(metadata.is_compatible());
+
+    metadata.min_host_version = "999.0.0".to_string();
+    assert!(!metadata.is_compatible());
+
+    assert!(parse_version("0.10.0") > parse_version("0.9.1"));
+    assert_eq!(parse_version("1.2.3-beta"), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_catch_ffi() {
+    let result = catch_ffi::<i8>(|| panic!("boom"));
+    let err = unwrap_handle_result(result).unwrap_err();
+    assert!(err.to_string().contains("plugin panicked: boom"));
+
+    let result = catch_ffi(|| Ok(1i8).to_ptr());
+    assert_eq!(unsafe { *Box::from_raw(unwrap_handle_result(result).unwrap()) }, 1);
+
+    assert_eq!(catch_panic(0, || -> u32 { panic!("boom") }), 0);
+}
+
+#[test]
+fn test_ffi_error() {
+    let err = anyhow::Error::new(XTranslateError::Network("reset".to_string())).context("translate failed");
+    let err = unwrap_handle_result(Err::<i8, _>(err).to_ptr()).unwrap_err();
+    assert_eq!(XTranslateError::find(&err), Some(&XTranslateError::Network("reset".to_string())));
+    assert!(err.to_string().contains("translate failed"));
+
+    // 未分类的错误与旧版插件的错误只有文本
+    let err = unwrap_handle_result(Err::<i8, _>(anyhow!("boom")).to_ptr()).unwrap_err();
+    assert!(err.to_string().starts_with("result's error: \"boom"));
+    assert!(XTranslateError::find(&err).is_none());
+    assert!(XTranslateError::find(&restore_error("{\"kind\":\"unknown\"}", str::to_string)).is_none());
+}
+
+#[test]
+fn test_utf8_utf16_arg() -> Result<()> {
+    let text = "{\"content\":\"a\u{0}b 你好\"}";
+    unsafe {
+        assert_eq!(utf8_arg(text.as_ptr(), text.len())?, text);
+        assert_eq!(utf8_arg(b"ab\xffc".as_ptr(), 4)?, "ab\u{fffd}c");
+        assert_eq!(utf8_arg(ptr::null(), 0)?, "");
+        assert!(utf8_arg(ptr::null(), 1).is_err());
+
+        let wide: Vec<u16> = text.encode_utf16().collect();
+        assert_eq!(utf16_arg(wide.as_ptr(), wide.len())?, text);
+        assert!(utf16_arg(ptr::null(), 1).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_handle_refs() {
+    let handle = 0x20 as *mut TranslatorHandle;
+    assert!(release_handle(handle));
+
+    retain_handle(handle);
+    retain_handle(handle);
+    assert!(!release_handle(handle));
+    assert!(!release_handle(handle));
+    assert!(release_handle(handle));
+}
+
+#[test]
+fn test_shutdown_calls() -> Result<()> {
+    let handle = 0x10 as *mut TranslatorHandle;
+    assert!(!shutdown_calls(handle, Duration::ZERO));
+    assert!(CallGuard::begin(handle).is_err());
+    forget_calls(handle);
+
+    let guard = CallGuard::begin(handle)?;
+    let finished = std::thread::spawn(move || {
+        block_on(guard.cancelled());
+        drop(guard);
+    });
+    assert!(shutdown_calls(handle, Duration::from_millis(10)));
+    finished.join().unwrap();
+    forget_calls(handle);
+
+    Ok(())
+}
+
+#[test]
+fn test_stream_chunk_copy() -> Result<()> {
+    let chunk = TranslateStreamChunk::Delta(TranslateResult {
+        content: Some("你好".to_string()),
+        ..Default::default()
+    })
+    .into_ffi();
+    let copied = unsafe { TranslateStreamChunk::copy_from_ffi(chunk) }?;
+    unsafe {
+        free_translate_stream_chunk(chunk);
+        free_translate_stream_chunk(std::ptr::null_mut());
+    }
+
+    let TranslateStreamChunk::Delta(delta) = copied else {
+        bail!("expected delta");
+    };
+    assert_eq!(delta.content.as_deref(), Some("你好"));
+    assert!(unsafe { TranslateStreamChunk::copy_from_ffi(std::ptr::null()) }.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_result_buffer() -> Result<()> {
+    let arena = ResultBuffer::default().into_ffi();
+    let buffer = unsafe { ResultBuffer::from_ptr(arena) }.unwrap();
+    assert_eq!(buffer.write_json(&vec!["a", "b"])?, br#"["a","b"]"#);
+    // 覆盖上一次的结果
+    assert_eq!(buffer.write_json(&1)?, b"1");
+    unsafe {
+        ResultBuffer::free(arena);
+
+        assert!(ResultBuffer::from_ptr(ptr::null_mut()).is_none());
+        ResultBuffer::free(ptr::null_mut());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_batch() -> Result<()> {
+    let translator = MockTranslator::flaky("译: ", vec![XTranslateError::Timeout("slow".to_string())]);
+    let results = translate_batch(&translator, vec![task("a"), task("b"), task("c")]).await;
+    let json = serde_json::to_string(&results)?;
+
+    let results: Vec<BatchResult> = serde_json::from_str(&json)?;
+    assert_eq!(results.len(), 3);
+    assert!(matches!(&results[0], BatchResult::Err(e) if e.contains("slow")));
+    let err = results[0].clone().into_result().unwrap_err();
+    assert_eq!(XTranslateError::find(&err), Some(&XTranslateError::Timeout("slow".to_string())));
+    let contents: Vec<Option<String>> = results[1..]
+        .iter()
+        .map(|r| r.clone().into_result().ok().and_then(|r| r.content))
+        .collect();
+    assert_eq!(contents, vec![Some("译: b".to_string()), Some("译: c".to_string())]);
+
+    Ok(())
+}
+
+#[test]
+fn test_block_on() {
+    let name = block_on(async {
+        tokio::spawn(async { std::thread::current().name().map(str::to_string) }).await
+    });
+    assert_eq!(name.unwrap().as_deref(), Some("xtranslator-plugin"));
+}
+
+#[tokio::test]
+async fn test_stream_control() {
+    let ptr = StreamControl::default().into_ffi();
+    let control = unsafe { StreamControl::from_ptr(ptr) }.unwrap();
+
+    let addr = ptr as usize;
+    let waiting = tokio::spawn(async move {
+        let control = unsafe { StreamControl::from_ptr(addr as *mut StreamHandle) }.unwrap();
+        control.cancelled().await
+    });
+    tokio::task::yield_now().await;
+    assert!(!control.is_cancelled());
+
+    control.cancel();
+    waiting.await.unwrap();
+    // 取消之后再等待立即完成
+    control.cancelled().await;
+
+    unsafe { StreamControl::free(ptr) };
+}
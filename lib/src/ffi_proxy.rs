@@ -1,35 +1,171 @@
-use crate::ffi::{free_supported_languages, stream_callback, unwrap_handle_result, CallTranslate, CallTranslateStream, CreateTranslator, GetPluginName, GetSupportedInputLanguages, GetSupportedOutputLanguages, IsSupportedInputLanguage, IsSupportedOutputLanguage, TranslateStreamChunkFFI, TranslatorHandle};
-use crate::{TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
-use anyhow::{anyhow, Result};
+use crate::ffi::{abi_layout_mismatches, translate_batch, BatchResult, CallTranslateBatch, free_string, free_supported_languages, PluginMetadata, StructLayout, ABI_VERSION, DESTROY_TIMEOUT, restore_error, completion_callback, stream_callback, unwrap_handle_result, CallTranslate, CallTranslateAsync, CallTranslateStream, CallTranslateInto, CallTranslateStreamCancellable, CallTranslateWithProgress, CancelStream, GetCapabilities, CompletionHandler, CreateNamedTranslator, CreateResultArena, CreateStreamHandle, CreateTranslator, DestroyTranslator, FfiResult, FreeFfiResult, FreeFfiStatus, FreeResultArena, FreeStreamHandle, FreeString, FreeSupportedLanguages, FreeTranslateResult, FreeTranslateStreamChunk, GetAbiLayout, GetAbiVersion, GetConfigSchema, GetNamedConfigSchema, GetNamedStaticInfo, GetPluginMetadata, GetPluginName, GetPluginTranslators, GetSupportedInputLanguages, GetSupportedOutputLanguages, GetSupportedPairs, IsSupportedInputLanguage, IsSupportedInputLanguageUtf8, IsSupportedOutputLanguage, IsSupportedOutputLanguageUtf8, IsSupportedPair, IsSupportedPairUtf8, ResultArena, SetLogCallback, ShutdownTranslator, StreamHandle, StreamHandler, TranslateResultFFI, TranslateStreamChunkFFI, TranslatorHandle, ValidateConfig, ValidateNamedConfig};
+use crate::host_env::host_env;
+use crate::manifest::PluginManifest;
+use crate::plugin_log;
+use crate::progress::{self, host_progress_callback, ProgressSink};
+#[cfg(feature = "tracing")]
+use crate::trace::language_pair;
+use crate::utils::language_pairs;
+use crate::validate::ConfigIssue;
+use crate::{BoxedTranslator, Capabilities, StaticInfo, TranslateResult, TranslateStreamChunk, TranslateTask, Translator, TranslatorFactory};
+use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use libloading::{Library, Symbol};
+use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::ffi::{c_char, c_void, CStr, CString};
 use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
 use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot;
 use walkdir::WalkDir;
 
 pub struct ProxyTranslator {
-    lib: Library,
+    plugin: Arc<PluginSlot>,
+}
+
+/// 插件动态库与其中创建的翻译器句柄
+struct LoadedPlugin {
+    lib: Arc<PluginLibrary>,
+    alloc: PluginAllocator,
+    symbols: PluginSymbols,
     handle: *mut TranslatorHandle,
+    /// 上一次 `call_translate_into` 用过的缓冲区，供下一次调用复用
+    arena: AtomicPtr<ResultArena>,
 }
 
-unsafe impl Sync for ProxyTranslator {
+// 插件中的翻译器实现了 `Send + Sync`（由 `build_ffi!` 检查），导出函数只以共享引用访问句柄，
+// 句柄在 `Drop` 之前不会改变，因此可以在多个线程中同时调用
+unsafe impl Sync for LoadedPlugin {
 }
 
-unsafe impl Send for ProxyTranslator {
+unsafe impl Send for LoadedPlugin {
 }
 
-impl ProxyTranslator {
-    pub async fn load(path: String, config: Value) -> Result<Self> {
-        let mut cfg = config.clone();
-        cfg["_dll_path"] = Value::String(path);
+/// 卸载插件时取出其中的 `LoadedPlugin`，进行中的调用持有的引用释放后关闭动态库
+type PluginSlot = RwLock<Option<Arc<LoadedPlugin>>>;
 
-        Self::new(cfg).await
+/// 注册表记录的翻译器槽位及其创建时的插件句柄
+type LiveSlot = (Weak<PluginSlot>, Weak<LoadedPlugin>);
+
+/// 插件导出的释放函数。插件分配的内存交回插件释放，旧版插件没有导出时由宿主释放
+#[derive(Clone, Copy)]
+struct PluginAllocator {
+    free_string: Option<FreeString>,
+    free_ffi_result: Option<FreeFfiResult>,
+    free_ffi_status: Option<FreeFfiStatus>,
+    free_translate_result: Option<FreeTranslateResult>,
+    free_translate_stream_chunk: Option<FreeTranslateStreamChunk>,
+    free_supported_languages: Option<FreeSupportedLanguages>,
+}
+
+/// 查找 `build_ffi!` 中以 `prefix` 为前缀导出的函数
+unsafe fn symbol<T: Copy>(lib: &Library, prefix: &str, name: &str) -> Result<T, libloading::Error> {
+    lib.get::<T>(format!("{}{}", prefix, name).as_bytes()).map(|f| *f)
+}
+
+impl PluginAllocator {
+    fn load(lib: &Library, prefix: &str) -> Self {
+        unsafe {
+            PluginAllocator {
+                free_string: symbol::<FreeString>(lib, prefix, "free_string").ok(),
+                free_ffi_result: symbol::<FreeFfiResult>(lib, prefix, "free_ffi_result").ok(),
+                free_ffi_status: symbol::<FreeFfiStatus>(lib, prefix, "free_ffi_status").ok(),
+                free_translate_result: symbol::<FreeTranslateResult>(lib, prefix, "free_translate_result").ok(),
+                free_translate_stream_chunk: symbol::<FreeTranslateStreamChunk>(lib, prefix, "free_translate_stream_chunk").ok(),
+                free_supported_languages: symbol::<FreeSupportedLanguages>(lib, prefix, "free_supported_languages").ok(),
+            }
+        }
+    }
+
+    fn free_string(&self, s: *mut c_char) {
+        match self.free_string {
+            Some(free) => unsafe { free(s) },
+            None => unsafe { free_string(s) },
+        }
+    }
+
+    fn take_string(&self, s: *mut c_char) -> Result<String> {
+        if s.is_null() {
+            bail!("null pointer received from ffi");
+        }
+        let copy = unsafe { CStr::from_ptr(s) }.to_str().map(str::to_owned);
+        self.free_string(s);
+
+        Ok(copy?)
+    }
+
+    /// 复制结果中的指针与错误信息
+    fn read_result<T>(result: *mut FfiResult<T>) -> Result<(*mut T, Option<String>)> {
+        let Some(r) = (unsafe { result.as_ref() }) else {
+            bail!("result is null");
+        };
+        let err = (!r.err.is_null()).then(|| unsafe { CStr::from_ptr(r.err) }.to_string_lossy().into_owned());
+
+        Ok((r.ptr, err))
+    }
+
+    fn check_result<T>(ptr: *mut T, err: Option<String>) -> Result<*mut T> {
+        if let Some(err) = err {
+            return Err(restore_error(&err, |detail| format!("result's error: {:?}", detail)));
+        }
+        if ptr.is_null() {
+            bail!("result obj is null");
+        }
+
+        Ok(ptr)
+    }
+
+    /// 取出结果中的指针，所有权交给调用方
+    fn take_result<T>(&self, result: *mut FfiResult<T>) -> Result<*mut T> {
+        let Some(free) = self.free_ffi_result else {
+            return unwrap_handle_result(result);
+        };
+
+        let (ptr, err) = Self::read_result(result)?;
+        unsafe { free(result as *mut FfiResult<c_void>) };
+
+        Self::check_result(ptr, err)
+    }
+
+    fn take_status(&self, result: *mut FfiResult<i8>) -> Result<i8> {
+        let Some(free) = self.free_ffi_status else {
+            return Ok(*unsafe { Box::from_raw(unwrap_handle_result(result)?) });
+        };
+
+        let (ptr, err) = Self::read_result(result)?;
+        let status = Self::check_result(ptr, err).map(|ptr| unsafe { *ptr });
+        unsafe { free(result) };
+
+        status
+    }
+
+    fn take_translate_result(&self, result: *mut TranslateResultFFI) -> Result<TranslateResult> {
+        let Some(free) = self.free_translate_result else {
+            return TranslateResult::from_ffi(result);
+        };
+
+        let copy = unsafe { TranslateResult::copy_from_ffi(result) };
+        unsafe { free(result) };
+
+        copy
+    }
+
+    fn take_chunk(&self, chunk: *mut TranslateStreamChunkFFI) -> Result<TranslateStreamChunk> {
+        let Some(free) = self.free_translate_stream_chunk else {
+            return TranslateStreamChunk::from_ffi(chunk);
+        };
+
+        let copy = unsafe { TranslateStreamChunk::copy_from_ffi(chunk) };
+        unsafe { free(chunk) };
+
+        copy
     }
 
-    fn unwrap_ffi_list(array: *mut *const c_char, len: usize) -> Result<Vec<String>> {
+    fn take_list(&self, array: *mut *const c_char, len: usize) -> Result<Vec<String>> {
         let list = unsafe {
             let slice = if array.is_null() {
                 &[]
@@ -45,142 +181,785 @@ impl ProxyTranslator {
                         .map_err(|e| anyhow!("UTF-8 conversion failed: {}", e))
                 })
                 .collect::<Result<Vec<_>, _>>()
-        }?;
+        };
 
-        free_supported_languages(array, len);
+        match self.free_supported_languages {
+            Some(free) => unsafe { free(array, len) },
+            None => unsafe { free_supported_languages(array, len) },
+        }
 
-        Ok(list)
+        list
     }
 }
 
-#[async_trait]
-impl Translator for ProxyTranslator {
-    type This = Self;
+/// 可取消的流式翻译，插件导出 `call_translate_stream_cancellable` 时需要同时导出其余三个函数
+#[derive(Clone, Copy)]
+struct CancellableStream {
+    call: CallTranslateStreamCancellable,
+    create_handle: CreateStreamHandle,
+    cancel: CancelStream,
+    free_handle: FreeStreamHandle,
+}
 
-    async fn new(config: Value) -> Result<Self> {
-        let path = config["_dll_path"].as_str().ok_or(anyhow!("missing argument: path"))?;
+/// 结果写入缓冲区的翻译，插件导出 `call_translate_into` 时需要同时导出另外两个函数
+#[derive(Clone, Copy)]
+struct TranslateInto {
+    call: CallTranslateInto,
+    create_arena: CreateResultArena,
+    free_arena: FreeResultArena,
+}
 
-        unsafe {
-            let lib = Library::new(path)?;
-            let create_translator: Symbol<CreateTranslator> = lib.get(b"create_translator")?;
+/// 打开插件时解析的翻译器函数，缺少必需的函数时不能加载。
+/// `Option` 中的函数旧版插件没有导出
+#[derive(Clone, Copy)]
+struct PluginSymbols {
+    get_plugin_name: GetPluginName,
+    create_translator: CreateTranslator,
+    get_supported_input_languages: GetSupportedInputLanguages,
+    get_supported_output_languages: GetSupportedOutputLanguages,
+    is_supported_input_language: IsSupportedInputLanguage,
+    is_supported_output_language: IsSupportedOutputLanguage,
+    call_translate: CallTranslate,
+    call_translate_stream: CallTranslateStream,
+    destroy_translator: Option<DestroyTranslator>,
+    is_supported_pair: Option<IsSupportedPair>,
+    get_supported_pairs: Option<GetSupportedPairs>,
+    get_capabilities: Option<GetCapabilities>,
+    call_translate_async: Option<CallTranslateAsync>,
+    call_translate_batch: Option<CallTranslateBatch>,
+    call_translate_with_progress: Option<CallTranslateWithProgress>,
+    cancellable_stream: Option<CancellableStream>,
+    shutdown_translator: Option<ShutdownTranslator>,
+    is_supported_input_language_utf8: Option<IsSupportedInputLanguageUtf8>,
+    is_supported_output_language_utf8: Option<IsSupportedOutputLanguageUtf8>,
+    is_supported_pair_utf8: Option<IsSupportedPairUtf8>,
+    translate_into: Option<TranslateInto>,
+}
 
-            let config_str = serde_json::to_string(&config)?;
-            let config_cstr = CString::new(config_str)?.into_raw();
+impl PluginSymbols {
+    fn load(lib: &Library, prefix: &str) -> Result<Self> {
+        unsafe fn required<T: Copy>(lib: &Library, prefix: &str, name: &str) -> Result<T> {
+            symbol(lib, prefix, name).map_err(|e| anyhow!("missing plugin symbol {}{}: {}", prefix, name, e))
+        }
 
-            let handle_result = create_translator(config_cstr);
+        unsafe fn optional<T: Copy>(lib: &Library, prefix: &str, name: &str) -> Option<T> {
+            symbol(lib, prefix, name).ok()
+        }
 
-            let handle = unwrap_handle_result(handle_result)?;
+        unsafe {
+            let cancellable_stream = match optional::<CallTranslateStreamCancellable>(lib, prefix, "call_translate_stream_cancellable") {
+                Some(call) => Some(CancellableStream {
+                    call,
+                    create_handle: required(lib, prefix, "create_stream_handle")?,
+                    cancel: required(lib, prefix, "cancel_stream")?,
+                    free_handle: required(lib, prefix, "free_stream_handle")?,
+                }),
+                None => None,
+            };
+            let translate_into = match optional::<CallTranslateInto>(lib, prefix, "call_translate_into") {
+                Some(call) => Some(TranslateInto {
+                    call,
+                    create_arena: required(lib, prefix, "create_result_arena")?,
+                    free_arena: required(lib, prefix, "free_result_arena")?,
+                }),
+                None => None,
+            };
 
-            Ok(ProxyTranslator {
-                lib,
-                handle,
+            Ok(PluginSymbols {
+                get_plugin_name: required(lib, prefix, "get_plugin_name")?,
+                create_translator: required(lib, prefix, "create_translator")?,
+                get_supported_input_languages: required(lib, prefix, "get_supported_input_languages")?,
+                get_supported_output_languages: required(lib, prefix, "get_supported_output_languages")?,
+                is_supported_input_language: required(lib, prefix, "is_supported_input_language")?,
+                is_supported_output_language: required(lib, prefix, "is_supported_output_language")?,
+                call_translate: required(lib, prefix, "call_translate")?,
+                call_translate_stream: required(lib, prefix, "call_translate_stream")?,
+                destroy_translator: optional(lib, prefix, "destroy_translator"),
+                is_supported_pair: optional(lib, prefix, "is_supported_pair"),
+                get_supported_pairs: optional(lib, prefix, "get_supported_pairs"),
+                get_capabilities: optional(lib, prefix, "get_capabilities"),
+                call_translate_async: optional(lib, prefix, "call_translate_async"),
+                call_translate_batch: optional(lib, prefix, "call_translate_batch"),
+                call_translate_with_progress: optional(lib, prefix, "call_translate_with_progress"),
+                cancellable_stream,
+                shutdown_translator: optional(lib, prefix, "shutdown_translator"),
+                is_supported_input_language_utf8: optional(lib, prefix, "is_supported_input_language_utf8"),
+                is_supported_output_language_utf8: optional(lib, prefix, "is_supported_output_language_utf8"),
+                is_supported_pair_utf8: optional(lib, prefix, "is_supported_pair_utf8"),
+                translate_into,
             })
         }
     }
+}
+
+/// 读取插件导出的元数据，旧版插件没有导出时返回 `None`
+pub fn plugin_metadata(lib: &Library) -> Result<Option<PluginMetadata>> {
+    read_metadata(lib, "")
+}
+
+fn read_metadata(lib: &Library, prefix: &str) -> Result<Option<PluginMetadata>> {
+    let Ok(get_plugin_metadata) = (unsafe { symbol::<GetPluginMetadata>(lib, prefix, "get_plugin_metadata") }) else {
+        return Ok(None);
+    };
+
+    let ptr = unsafe { get_plugin_metadata() };
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    let json = PluginAllocator::load(lib, prefix).take_string(ptr)?;
+
+    Ok(Some(serde_json::from_str(&json)?))
+}
+
+/// 读取插件的配置 schema，插件没有提供时返回 `None`
+pub fn plugin_config_schema(lib: &Library) -> Result<Option<Value>> {
+    read_config_schema(lib, "")
+}
+
+fn read_config_schema(lib: &Library, prefix: &str) -> Result<Option<Value>> {
+    let Ok(get_config_schema) = (unsafe { symbol::<GetConfigSchema>(lib, prefix, "get_config_schema") }) else {
+        return Ok(None);
+    };
+
+    let ptr = unsafe { get_config_schema() };
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    let json = PluginAllocator::load(lib, prefix).take_string(ptr)?;
+
+    Ok(Some(serde_json::from_str(&json)?))
+}
+
+/// 由插件检查配置，旧版插件没有导出时返回 `None`
+pub fn plugin_validate_config(lib: &Library, config: &Value) -> Result<Option<Vec<ConfigIssue>>> {
+    read_validate_config(lib, "", config)
+}
+
+fn read_validate_config(lib: &Library, prefix: &str, config: &Value) -> Result<Option<Vec<ConfigIssue>>> {
+    let Ok(validate_config) = (unsafe { symbol::<ValidateConfig>(lib, prefix, "validate_config") }) else {
+        return Ok(None);
+    };
+
+    let input = CString::new(config.to_string())?;
+    let alloc = PluginAllocator::load(lib, prefix);
+    let ptr = alloc.take_result(unsafe { validate_config(input.as_ptr()) })?;
+    let json = alloc.take_string(ptr)?;
+
+    Ok(Some(serde_json::from_str(&json)?))
+}
+
+/// 读取指定路径插件的元数据
+pub fn read_plugin_metadata(path: &str) -> Result<Option<PluginMetadata>> {
+    let lib = unsafe { Library::new(path)? };
+    plugin_metadata(&lib)
+}
+
+/// 检查插件的 FFI 版本，不一致时结构体布局可能不同，不能加载
+pub fn check_abi_version(lib: &Library) -> Result<()> {
+    check_prefixed_abi_version(lib, "")
+}
+
+fn check_prefixed_abi_version(lib: &Library, prefix: &str) -> Result<()> {
+    let version = match unsafe { symbol::<GetAbiVersion>(lib, prefix, "get_abi_version") } {
+        Ok(get_abi_version) => unsafe { get_abi_version() },
+        Err(_) => 1,
+    };
+
+    if version != ABI_VERSION {
+        bail!("incompatible plugin ABI version: {}, expected {}", version, ABI_VERSION);
+    }
+
+    Ok(())
+}
+
+/// 比较插件与宿主的结构体布局，不一致时传递结构体会破坏内存。旧版插件没有导出 `get_abi_layout`
+fn check_abi_layout(lib: &Library, prefix: &str, alloc: &PluginAllocator) -> Result<()> {
+    let Ok(get_abi_layout) = (unsafe { symbol::<GetAbiLayout>(lib, prefix, "get_abi_layout") }) else {
+        return Ok(());
+    };
+
+    let layout: BTreeMap<String, StructLayout> = serde_json::from_str(&alloc.take_string(unsafe { get_abi_layout() })?)?;
+    let mismatches = abi_layout_mismatches(&layout);
+    if !mismatches.is_empty() {
+        bail!("incompatible plugin ABI layout: {}", mismatches.join(", "));
+    }
+
+    Ok(())
+}
+
+/// 已打开的插件动态库，由同一插件的翻译器共享
+pub struct PluginLibrary {
+    lib: Library,
+    alloc: PluginAllocator,
+    symbols: PluginSymbols,
+    path: String,
+    prefix: String,
+}
+
+impl PluginLibrary {
+    pub fn open(path: &str) -> Result<Self> {
+        Self::open_with_prefix(path, "")
+    }
+
+    /// 打开以 `build_ffi!(..., prefix = "...")` 导出的插件，同一个动态库中可以有多个前缀不同的插件
+    pub fn open_with_prefix(path: &str, prefix: &str) -> Result<Self> {
+        let lib = unsafe { Library::new(path)? };
+        check_prefixed_abi_version(&lib, prefix)?;
+        let alloc = PluginAllocator::load(&lib, prefix);
+        check_abi_layout(&lib, prefix, &alloc)?;
+        let symbols = PluginSymbols::load(&lib, prefix)?;
+
+        // 插件日志转发给宿主，旧版插件没有导出该函数
+        if let Ok(set_log_callback) = unsafe { symbol::<SetLogCallback>(&lib, prefix, "set_log_callback") } {
+            unsafe { set_log_callback(Some(plugin_log::host_log_callback), ptr::null_mut(), plugin_log::level()) };
+        }
+
+        Ok(PluginLibrary {
+            lib,
+            alloc,
+            symbols,
+            path: path.to_string(),
+            prefix: prefix.to_string(),
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// 导出函数名的前缀，见 `open_with_prefix`
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// 查找导出符号，`symbol` 不含前缀
+    ///
+    /// # Safety
+    /// 与 `Library::get` 相同，`T` 必须与导出符号的类型一致
+    pub unsafe fn get<T>(&self, symbol: &[u8]) -> Result<Symbol<'_, T>, libloading::Error> {
+        self.lib.get(&[self.prefix.as_bytes(), symbol].concat())
+    }
+
+    pub fn name(&self) -> Result<String> {
+        self.alloc.take_string(unsafe { (self.symbols.get_plugin_name)() })
+    }
+
+    /// 插件中的翻译器名称，第一个为默认翻译器。旧版插件只有一个与插件同名的翻译器
+    pub fn translators(&self) -> Result<Vec<String>> {
+        let Ok(get_plugin_translators) = (unsafe { self.get::<GetPluginTranslators>(b"get_plugin_translators") }) else {
+            return Ok(vec![self.name()?]);
+        };
+
+        let mut list_ptr: *mut *const c_char = ptr::null_mut();
+        let mut len: usize = 0;
+        let ret = unsafe { get_plugin_translators(&mut list_ptr as *mut _, &mut len as *mut _) };
+        self.alloc.take_status(ret)?;
+
+        self.alloc.take_list(list_ptr, len)
+    }
+
+    /// 插件中有该名称的翻译器且导出了按名称调用的函数
+    fn has_named(&self, translator: &str, symbol: &[u8]) -> bool {
+        unsafe { self.get::<*const c_void>(symbol) }.is_ok()
+            && self.translators().is_ok_and(|names| names.iter().any(|name| name == translator))
+    }
+
+    pub fn metadata(&self) -> Result<Option<PluginMetadata>> {
+        read_metadata(&self.lib, &self.prefix)
+    }
+
+    pub fn config_schema(&self) -> Result<Option<Value>> {
+        read_config_schema(&self.lib, &self.prefix)
+    }
+
+    pub fn validate_config(&self, config: &Value) -> Result<Option<Vec<ConfigIssue>>> {
+        read_validate_config(&self.lib, &self.prefix, config)
+    }
+
+    /// 插件中没有该名称的翻译器时读取默认翻译器的配置 schema
+    pub fn named_config_schema(&self, translator: &str) -> Result<Option<Value>> {
+        if !self.has_named(translator, b"get_named_config_schema") {
+            return self.config_schema();
+        }
+
+        let get_named_config_schema = unsafe { self.get::<GetNamedConfigSchema>(b"get_named_config_schema")? };
+        let name = CString::new(translator)?;
+        let ptr = unsafe { get_named_config_schema(name.as_ptr()) };
+        if ptr.is_null() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_str(&self.alloc.take_string(ptr)?)?))
+    }
+
+    /// 不依赖配置的语言与能力，旧版插件或没有该翻译器时返回 `None`
+    pub fn named_static_info(&self, translator: &str) -> Result<Option<StaticInfo>> {
+        if !self.has_named(translator, b"get_named_static_info") {
+            return Ok(None);
+        }
+
+        let get_named_static_info = unsafe { self.get::<GetNamedStaticInfo>(b"get_named_static_info")? };
+        let name = CString::new(translator)?;
+        let ptr = unsafe { get_named_static_info(name.as_ptr()) };
+        if ptr.is_null() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_str(&self.alloc.take_string(ptr)?)?))
+    }
+
+    pub fn validate_named_config(&self, translator: &str, config: &Value) -> Result<Option<Vec<ConfigIssue>>> {
+        if !self.has_named(translator, b"validate_named_config") {
+            return self.validate_config(config);
+        }
+
+        let validate_named_config = unsafe { self.get::<ValidateNamedConfig>(b"validate_named_config")? };
+        let name = CString::new(translator)?;
+        let input = CString::new(config.to_string())?;
+        let ptr = self.alloc.take_result(unsafe { validate_named_config(name.as_ptr(), input.as_ptr()) })?;
+
+        Ok(Some(serde_json::from_str(&self.alloc.take_string(ptr)?)?))
+    }
+}
+
+impl ProxyTranslator {
+    fn plugin(&self) -> Result<Arc<LoadedPlugin>> {
+        self.plugin.read().unwrap().clone().ok_or(anyhow!("plugin unloaded"))
+    }
+
+    pub fn metadata(&self) -> Result<Option<PluginMetadata>> {
+        self.plugin()?.lib.metadata()
+    }
+
+    pub fn config_schema(&self) -> Result<Option<Value>> {
+        self.plugin()?.lib.config_schema()
+    }
+
+    /// 翻译期间向 `sink` 报告进度，用于不支持流式输出的服务。
+    /// 旧版插件没有导出 `call_translate_with_progress` 时只报告开始、结束与已用时间
+    pub async fn translate_with_progress(&self, task: TranslateTask, sink: Arc<dyn ProgressSink>) -> Result<TranslateResult> {
+        let plugin = self.plugin()?;
+        let Some(call_translate_with_progress) = plugin.symbols.call_translate_with_progress else {
+            return progress::track(Some(sink), self.translate(task)).await;
+        };
+
+        let input = CString::new(serde_json::to_string(&task)?)?;
+        // 插件在翻译期间阻塞当前线程，`sink` 在调用返回前保持有效
+        tokio::task::spawn_blocking(move || {
+            let user_data = &sink as *const Arc<dyn ProgressSink> as *mut c_void;
+            let result = unsafe {
+                call_translate_with_progress(plugin.handle, input.as_ptr(), Some(host_progress_callback), user_data)
+            };
+            let result = plugin.alloc.take_result(result)?;
+            plugin.alloc.take_translate_result(result)
+        })
+        .await?
+    }
+
+    /// 一次调用翻译多个任务，按顺序返回每个任务的结果。
+    /// 旧版插件没有导出 `call_translate_batch` 时逐个调用 `translate`
+    pub async fn translate_batch(&self, tasks: Vec<TranslateTask>) -> Result<Vec<Result<TranslateResult>>> {
+        let plugin = self.plugin()?;
+        let Some(call_translate_batch) = plugin.symbols.call_translate_batch else {
+            return Ok(translate_batch(self, tasks).await.into_iter().map(BatchResult::into_result).collect());
+        };
+
+        let input = CString::new(serde_json::to_string(&tasks)?)?;
+        // 插件在翻译期间阻塞当前线程
+        let json = tokio::task::spawn_blocking(move || {
+            let result = unsafe { call_translate_batch(plugin.handle, input.as_ptr()) };
+            let ptr = plugin.alloc.take_result(result)?;
+            plugin.alloc.take_string(ptr)
+        })
+        .await??;
+
+        let results: Vec<BatchResult> = serde_json::from_str(&json)?;
+        if results.len() != tasks.len() {
+            bail!("plugin returned {} results for {} tasks", results.len(), tasks.len());
+        }
+
+        Ok(results.into_iter().map(BatchResult::into_result).collect())
+    }
+
+    /// 等待进行中的翻译结束，超过 `timeout` 后取消它们，之后的调用都会失败。
+    /// 返回是否有翻译被取消，旧版插件没有导出 `shutdown_translator` 时直接返回 false
+    pub async fn shutdown(&self, timeout: Duration) -> Result<bool> {
+        let plugin = self.plugin()?;
+        if plugin.symbols.shutdown_translator.is_none() {
+            return Ok(false);
+        }
+
+        // 插件在等待期间阻塞当前线程
+        tokio::task::spawn_blocking(move || plugin.shutdown(timeout)).await?
+    }
+
+    /// 注入 `set_host_env` 设置的 `_proxy` 等保留键后序列化配置
+    fn config_cstring(config: &Value) -> Result<CString> {
+        let mut config = config.clone();
+        host_env().inject(&mut config);
+
+        Ok(CString::new(serde_json::to_string(&config)?)?)
+    }
+
+    /// 用已打开的动态库创建默认翻译器
+    pub fn create(library: Arc<PluginLibrary>, config: &Value) -> Result<Self> {
+        let config_cstr = Self::config_cstring(config)?;
+
+        let handle_result = unsafe { (library.symbols.create_translator)(config_cstr.as_ptr()) };
+        Self::from_handle(library, handle_result)
+    }
+
+    /// 按名称创建插件中的翻译器，插件中没有该名称时创建默认翻译器
+    pub fn create_named(library: Arc<PluginLibrary>, translator: &str, config: &Value) -> Result<Self> {
+        if !library.has_named(translator, b"create_named_translator") {
+            return Self::create(library, config);
+        }
+
+        let create_named_translator = *unsafe { library.get::<CreateNamedTranslator>(b"create_named_translator") }?;
+        let name = CString::new(translator)?;
+        let config_cstr = Self::config_cstring(config)?;
+
+        let handle_result = unsafe { create_named_translator(name.as_ptr(), config_cstr.as_ptr()) };
+        Self::from_handle(library, handle_result)
+    }
+
+    fn from_handle(library: Arc<PluginLibrary>, handle_result: *mut FfiResult<TranslatorHandle>) -> Result<Self> {
+        let handle = library.alloc.take_result(handle_result)?;
+
+        Ok(ProxyTranslator {
+            plugin: Arc::new(RwLock::new(Some(Arc::new(LoadedPlugin {
+                alloc: library.alloc,
+                symbols: library.symbols,
+                lib: library,
+                handle,
+                arena: AtomicPtr::new(ptr::null_mut()),
+            })))),
+        })
+    }
+
+    pub async fn load(path: String, config: Value) -> Result<Self> {
+        let mut cfg = config.clone();
+        cfg["_dll_path"] = Value::String(path);
+
+        Self::new(cfg).await
+    }
+}
+
+#[async_trait]
+impl Translator for ProxyTranslator {
+    type This = Self;
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "ffi_create_translator", skip_all, err, fields(path = config["_dll_path"].as_str())))]
+    async fn new(config: Value) -> Result<Self> {
+        let path = config["_dll_path"].as_str().ok_or(anyhow!("missing argument: path"))?;
+        let library = Arc::new(PluginLibrary::open(path)?);
+
+        Self::create(library, &config)
+    }
 
     fn get_supported_input_languages(&self) -> Result<Vec<String>> {
-        let get_supported_input_languages: Symbol<GetSupportedInputLanguages> = unsafe { self.lib.get(b"get_supported_input_languages") }?;
+        let plugin = self.plugin()?;
+        let get_supported_input_languages = plugin.symbols.get_supported_input_languages;
 
         let mut languages_ptr: *mut *const c_char = ptr::null_mut();
         let mut len: usize = 0;
 
         let ret = unsafe {
             get_supported_input_languages(
-                self.handle,
+                plugin.handle,
                 &mut languages_ptr as *mut _,
                 &mut len as *mut _,
             )
         };
 
-        unwrap_handle_result(ret)?;
+        plugin.alloc.take_status(ret)?;
 
-        ProxyTranslator::unwrap_ffi_list(languages_ptr, len)
+        plugin.alloc.take_list(languages_ptr, len)
     }
 
     fn get_supported_output_languages(&self) -> Result<Vec<String>> {
-        let get_supported_output_languages: Symbol<GetSupportedOutputLanguages> = unsafe { self.lib.get(b"get_supported_output_languages") }?;
+        let plugin = self.plugin()?;
+        let get_supported_output_languages = plugin.symbols.get_supported_output_languages;
 
         let mut languages_ptr: *mut *const c_char = ptr::null_mut();
         let mut len: usize = 0;
 
         let ret = unsafe {
             get_supported_output_languages(
-                self.handle,
+                plugin.handle,
                 &mut languages_ptr as *mut _,
                 &mut len as *mut _,
             )
         };
 
-        unwrap_handle_result(ret)?;
+        plugin.alloc.take_status(ret)?;
 
-        ProxyTranslator::unwrap_ffi_list(languages_ptr, len)
+        plugin.alloc.take_list(languages_ptr, len)
     }
 
     fn is_supported_input_language(&self, lang: String) -> Result<bool> {
-        let is_supported_input_language: Symbol<IsSupportedInputLanguage> = unsafe { self.lib.get(b"is_supported_input_language") }?;
+        let plugin = self.plugin()?;
+        let ret = match plugin.symbols.is_supported_input_language_utf8 {
+            Some(is_supported_input_language) => unsafe { is_supported_input_language(plugin.handle, lang.as_ptr(), lang.len()) },
+            None => {
+                let lang = CString::new(lang)?;
+                unsafe { (plugin.symbols.is_supported_input_language)(plugin.handle, lang.as_ptr()) }
+            }
+        };
+
+        Ok(plugin.alloc.take_status(ret)? == 0i8)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        let plugin = self.plugin()?;
+        let ret = match plugin.symbols.is_supported_output_language_utf8 {
+            Some(is_supported_output_language) => unsafe { is_supported_output_language(plugin.handle, lang.as_ptr(), lang.len()) },
+            None => {
+                let lang = CString::new(lang)?;
+                unsafe { (plugin.symbols.is_supported_output_language)(plugin.handle, lang.as_ptr()) }
+            }
+        };
 
-        let lang = CString::new(lang)?.into_raw();
+        Ok(plugin.alloc.take_status(ret)? == 0i8)
+    }
+
+    /// 旧版插件没有导出该函数时，按源语言与目标语言分别判断
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        let plugin = self.plugin()?;
+        if let Some(is_supported_pair) = plugin.symbols.is_supported_pair_utf8 {
+            let ret = unsafe { is_supported_pair(plugin.handle, source.as_ptr(), source.len(), target.as_ptr(), target.len()) };
+            return Ok(plugin.alloc.take_status(ret)? == 0i8);
+        }
+        let Some(is_supported_pair) = plugin.symbols.is_supported_pair else {
+            return Ok(self.is_supported_input_language(source)? && self.is_supported_output_language(target)?);
+        };
 
-        let ret = unsafe { is_supported_input_language(self.handle, lang) };
+        let source = CString::new(source)?;
+        let target = CString::new(target)?;
 
-        let b = unsafe { Box::from_raw(unwrap_handle_result(ret)?) };
+        let ret = unsafe { is_supported_pair(plugin.handle, source.as_ptr(), target.as_ptr()) };
 
-        Ok(*b == 0i8)
+        Ok(plugin.alloc.take_status(ret)? == 0i8)
     }
 
-    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
-        let is_supported_output_language: Symbol<IsSupportedOutputLanguage> = unsafe { self.lib.get(b"is_supported_output_language") }?;
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        let plugin = self.plugin()?;
+        let Some(get_supported_pairs) = plugin.symbols.get_supported_pairs else {
+            return Ok(language_pairs(&self.get_supported_input_languages()?, &self.get_supported_output_languages()?));
+        };
+
+        let mut list_ptr: *mut *const c_char = ptr::null_mut();
+        let mut len: usize = 0;
+
+        let ret = unsafe {
+            get_supported_pairs(
+                plugin.handle,
+                &mut list_ptr as *mut _,
+                &mut len as *mut _,
+            )
+        };
 
-        let lang = CString::new(lang)?.into_raw();
+        plugin.alloc.take_status(ret)?;
 
-        let ret = unsafe { is_supported_output_language(self.handle, lang) };
+        let list = plugin.alloc.take_list(list_ptr, len)?;
 
-        let b = unsafe { Box::from_raw(unwrap_handle_result(ret)?) };
+        Ok(list.chunks_exact(2).map(|pair| (pair[0].clone(), pair[1].clone())).collect())
+    }
 
-        Ok(*b == 0i8)
+    /// 旧版插件没有导出 `get_capabilities`，或读取失败时按全部不支持处理
+    fn capabilities(&self) -> Capabilities {
+        let read = || -> Result<Capabilities> {
+            let plugin = self.plugin()?;
+            let Some(get_capabilities) = plugin.symbols.get_capabilities else {
+                return Ok(Capabilities::default());
+            };
+
+            let ptr = plugin.alloc.take_result(unsafe { get_capabilities(plugin.handle) })?;
+            Ok(serde_json::from_str(&plugin.alloc.take_string(ptr)?)?)
+        };
+
+        read().unwrap_or_default()
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "ffi_translate", skip_all, err, fields(task_id = %task.id, languages = %language_pair(&task))))]
     async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
-        let call_translate: Symbol<CallTranslate> = unsafe { self.lib.get(b"call_translate") }?;
-        let result = unsafe { call_translate(self.handle, CString::new(serde_json::to_string(&task).unwrap())?.into_raw()) };
+        let plugin = self.plugin()?;
+        // 优先使用异步接口，不阻塞当前线程
+        if let Some(call_translate_async) = plugin.symbols.call_translate_async {
+            // 先序列化任务，失败时不会留下未释放的 `user_data`
+            let input = CString::new(serde_json::to_string(&task)?)?;
+            let (tx, rx) = oneshot::channel::<Result<TranslateResult>>();
+            // 宿主的 future 可能先被丢弃，回调持有插件直到翻译结束，翻译器与动态库不会提前释放
+            let pending = plugin.clone();
+            let handler: CompletionHandler = Box::new(move |result| {
+                let result = pending.alloc.take_result(result).and_then(|r| pending.alloc.take_translate_result(r));
+                let _ = tx.send(result);
+                release_pending(pending);
+            });
+            let user_data = Box::into_raw(Box::new(handler));
+
+            let result = unsafe { call_translate_async(plugin.handle, input.as_ptr(), completion_callback, user_data as *mut c_void) };
+            if let Err(e) = plugin.alloc.take_status(result) {
+                // 没有开始翻译，回调不会被调用
+                drop(unsafe { Box::from_raw(user_data) });
+                return Err(e);
+            }
 
-        let result = unwrap_handle_result(result)?;
+            return rx.await?;
+        }
+
+        // 任务以 `(ptr, len)` 传入，结果写入复用的缓冲区，不需要 C 字符串
+        if let Some(translate_into) = plugin.symbols.translate_into {
+            let input = serde_json::to_vec(&task)?;
+            // 插件在翻译期间阻塞当前线程
+            return tokio::task::spawn_blocking(move || plugin.translate_into(translate_into, &input)).await?;
+        }
 
-        Ok(TranslateResult::from_ffi(result)?)
+        let call_translate = plugin.symbols.call_translate;
+        let input = CString::new(serde_json::to_string(&task)?)?;
+        tokio::task::spawn_blocking(move || {
+            let result = unsafe { call_translate(plugin.handle, input.as_ptr()) };
+            let result = plugin.alloc.take_result(result)?;
+            plugin.alloc.take_translate_result(result)
+        })
+        .await?
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(name = "ffi_translate_stream", skip_all, err, fields(task_id = %task.id, languages = %language_pair(&task))))]
     async fn translate_stream(&self, task: TranslateTask, sender: Sender<TranslateStreamChunk>) -> Result<()> {
-        let call_translate_stream: Symbol<CallTranslateStream> = unsafe { self.lib.get(b"call_translate_stream") }?;
+        let plugin = self.plugin()?;
+        // 接收方被丢弃时中止插件中的请求
+        if let Some(cancellable) = plugin.symbols.cancellable_stream {
+            let input = CString::new(serde_json::to_string(&task)?)?;
+            // 插件在整个流式翻译期间阻塞当前线程，不占用运行时的工作线程
+            return tokio::task::spawn_blocking(move || {
+                let stream_handle = unsafe { (cancellable.create_handle)() };
+                if stream_handle.is_null() {
+                    bail!("failed to create stream handle");
+                }
 
-        let closure: Box<dyn Fn(*mut TranslateStreamChunkFFI)> = Box::new(|x| {
-            if let Ok(chunk) = TranslateStreamChunk::from_ffi(x) {
-                sender.blocking_send(chunk).unwrap();
-            }
-        });
+                let handle_addr = stream_handle as usize;
+                let closure: StreamHandler = Box::new(|x| {
+                    if let Ok(chunk) = plugin.alloc.take_chunk(x) {
+                        if sender.blocking_send(chunk).is_err() {
+                            unsafe { (cancellable.cancel)(handle_addr as *mut StreamHandle) };
+                        }
+                    }
+                });
+                let callback = Box::into_raw(Box::new(closure));
 
-        let callback = Box::into_raw(Box::new(closure)) as *mut c_void;
+                let result = unsafe {
+                    (cancellable.call)(plugin.handle, input.as_ptr(), stream_callback, callback as *mut c_void, stream_handle)
+                };
+
+                unsafe {
+                    (cancellable.free_handle)(stream_handle);
+                    drop(Box::from_raw(callback));
+                }
+                plugin.alloc.take_status(result)?;
+
+                Ok(())
+            })
+            .await?;
+        }
+
+        let call_translate_stream = plugin.symbols.call_translate_stream;
+        let input = CString::new(serde_json::to_string(&task)?)?;
+
+        // 旧版接口无法取消，接收方被丢弃后仍释放其余片段，直到插件返回
+        tokio::task::spawn_blocking(move || {
+            let closure: StreamHandler = Box::new(|x| {
+                if let Ok(chunk) = plugin.alloc.take_chunk(x) {
+                    let _ = sender.blocking_send(chunk);
+                }
+            });
 
-        let result = unsafe {
-            call_translate_stream(self.handle, CString::new(serde_json::to_string(&task).unwrap())?.into_raw(), stream_callback, callback)
+            let callback = Box::into_raw(Box::new(closure));
+
+            let result = unsafe {
+                call_translate_stream(plugin.handle, input.as_ptr(), stream_callback, callback as *mut c_void)
+            };
+
+            unsafe { drop(Box::from_raw(callback)) };
+            plugin.alloc.take_status(result)?;
+
+            Ok(())
+        })
+        .await?
+    }
+}
+
+/// 在插件的线程中释放回调持有的引用。是最后一个引用时，翻译器需要等待本次调用结束后才能销毁，
+/// 动态库也不能在其自身的线程中关闭，因此交给新的线程释放
+fn release_pending(plugin: Arc<LoadedPlugin>) {
+    if let Some(plugin) = Arc::into_inner(plugin) {
+        std::thread::spawn(move || drop(plugin));
+    }
+}
+
+impl LoadedPlugin {
+    /// 阻塞当前线程直到进行中的调用结束，见 `ProxyTranslator::shutdown`
+    fn shutdown(&self, timeout: Duration) -> Result<bool> {
+        let Some(shutdown_translator) = self.symbols.shutdown_translator else {
+            return Ok(false);
         };
 
-        unwrap_handle_result(result)?;
+        let timeout_ms = timeout.as_millis().min(u64::MAX as u128) as u64;
+        let status = unsafe { shutdown_translator(self.handle, timeout_ms) };
 
-        Ok(())
+        Ok(self.alloc.take_status(status)? != 0)
+    }
+
+    /// 同时进行的调用各自创建缓冲区，结束后只保留一个
+    fn translate_into(&self, symbols: TranslateInto, input: &[u8]) -> Result<TranslateResult> {
+        let mut arena = self.arena.swap(ptr::null_mut(), Ordering::AcqRel);
+        if arena.is_null() {
+            arena = unsafe { (symbols.create_arena)() };
+            if arena.is_null() {
+                bail!("failed to create result arena");
+            }
+        }
+
+        let mut out: *const u8 = ptr::null();
+        let mut out_len: usize = 0;
+        let status = unsafe { (symbols.call)(self.handle, input.as_ptr(), input.len(), arena, &mut out, &mut out_len) };
+        let result = self.alloc.take_status(status).and_then(|_| {
+            if out.is_null() {
+                bail!("result obj is null");
+            }
+            Ok(serde_json::from_slice(unsafe { std::slice::from_raw_parts(out, out_len) })?)
+        });
+
+        if self.arena.compare_exchange(ptr::null_mut(), arena, Ordering::AcqRel, Ordering::Acquire).is_err() {
+            unsafe { (symbols.free_arena)(arena) };
+        }
+
+        result
     }
 }
 
-impl Drop for ProxyTranslator {
+impl Drop for LoadedPlugin {
+    /// 句柄指向插件内的具体类型，由插件导出的 `destroy_translator` 释放，释放前等待进行中的调用结束。
+    /// 旧版插件没有导出该函数，只能泄漏
     fn drop(&mut self) {
-        if !self.handle.is_null() {
-            unsafe {
-                let _ = Box::from_raw(self.handle);
-            }
+        if self.handle.is_null() {
+            return;
+        }
+
+        let _ = self.shutdown(DESTROY_TIMEOUT);
+
+        let arena = *self.arena.get_mut();
+        if let (Some(translate_into), false) = (self.symbols.translate_into, arena.is_null()) {
+            unsafe { (translate_into.free_arena)(arena) };
+        }
+        if let Some(destroy_translator) = self.symbols.destroy_translator {
+            unsafe { destroy_translator(self.handle) };
         }
+        self.handle = ptr::null_mut();
     }
 }
 
-pub fn load_translators(root: String) -> Result<HashMap<String, String>> {
+fn plugin_files(root: String) -> Vec<String> {
     let extensions = {
         #[cfg(windows)]
         {
@@ -209,8 +988,6 @@ pub fn load_translators(root: String) -> Result<HashMap<String, String>> {
     };
     let mut libraries = Vec::new();
 
-    let mut map = HashMap::new();
-
     for entry in WalkDir::new(root)
         .into_iter()
         .filter_map(|e| e.ok())
@@ -240,29 +1017,439 @@ pub fn load_translators(root: String) -> Result<HashMap<String, String>> {
         }
     }
 
+    libraries
+}
 
-    for lib_path in libraries {
-        let library_result = unsafe { Library::new(&lib_path) };
-        if library_result.is_err() {
-            continue;
+/// 发现插件时读取的信息，动态库保持打开供之后创建翻译器
+#[derive(Clone, Serialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub path: String,
+    pub version: Option<String>,
+    pub metadata: Option<PluginMetadata>,
+    /// 插件中的翻译器名称，第一个为默认翻译器
+    pub translators: Vec<String>,
+    /// 默认翻译器的语言与能力，无法确定时为 `None`
+    pub input_languages: Option<Vec<String>>,
+    pub output_languages: Option<Vec<String>>,
+    pub capabilities: Option<Capabilities>,
+    /// 每个翻译器的语言与能力，顺序与 `translators` 相同
+    pub translator_info: Vec<(String, StaticInfo)>,
+    #[serde(skip)]
+    pub library: Arc<PluginLibrary>,
+}
+
+/// 优先读取插件导出的 `StaticInfo`。其中缺少的字段与配置有关，或来自旧版插件，
+/// 尝试以空配置创建翻译器读取，需要必填配置时保持 `None`
+fn probe_translator(library: &Arc<PluginLibrary>, translator: &str, default: bool) -> StaticInfo {
+    let mut info = library.named_static_info(translator).ok().flatten().unwrap_or_default();
+    if info.input_languages.is_some() && info.output_languages.is_some() && info.capabilities.is_some() {
+        return info;
+    }
+
+    let config = Value::Object(Default::default());
+    let created = if default {
+        ProxyTranslator::create(library.clone(), &config)
+    } else {
+        ProxyTranslator::create_named(library.clone(), translator, &config)
+    };
+    if let Ok(created) = created {
+        info.input_languages = info.input_languages.or_else(|| created.get_supported_input_languages().ok());
+        info.output_languages = info.output_languages.or_else(|| created.get_supported_output_languages().ok());
+        info.capabilities = info.capabilities.or_else(|| Some(created.capabilities()));
+    }
+    info
+}
+
+impl PluginInfo {
+    /// 读取插件信息，要求更高版本宿主的插件返回错误
+    pub fn probe(library: Arc<PluginLibrary>) -> Result<Self> {
+        let metadata = library.metadata().ok().flatten();
+        if let Some(metadata) = &metadata {
+            if !metadata.is_compatible() {
+                bail!("plugin requires host version {}", metadata.min_host_version);
+            }
+        }
+        let name = library.name()?;
+        let translators = library.translators()?;
+        let translator_info: Vec<(String, StaticInfo)> = translators
+            .iter()
+            .enumerate()
+            .map(|(i, translator)| (translator.clone(), probe_translator(&library, translator, i == 0)))
+            .collect();
+        let default = translator_info.first().map(|(_, info)| info.clone()).unwrap_or_default();
+
+        Ok(PluginInfo {
+            name,
+            path: library.path().to_string(),
+            version: metadata.as_ref().map(|m| m.version.clone()),
+            input_languages: default.input_languages,
+            output_languages: default.output_languages,
+            capabilities: default.capabilities,
+            metadata,
+            translators,
+            translator_info,
+            library,
+        })
+    }
+}
+
+/// 打开插件并读取信息，跳过无法打开、FFI 版本不一致或要求更高版本宿主的插件
+fn probe_plugin(path: &str) -> Option<PluginInfo> {
+    let library = PluginLibrary::open(path).ok()?;
+    PluginInfo::probe(Arc::new(library)).ok()
+}
+
+/// 动态库旁的清单，无法读取的清单按没有清单处理
+fn plugin_manifest(path: &str) -> Option<PluginManifest> {
+    PluginManifest::find(std::path::Path::new(path)).ok().flatten()
+}
+
+/// 扫描目录中的插件，每个动态库只打开一次。清单表明不兼容的插件不会被打开
+pub fn load_translators(root: String) -> Result<Vec<PluginInfo>> {
+    Ok(plugin_files(root)
+        .iter()
+        .filter(|path| plugin_manifest(path).is_none_or(|manifest| manifest.is_compatible()))
+        .filter_map(|path| probe_plugin(path))
+        .collect())
+}
+
+fn shadow_path(name: &str) -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+
+    let file = format!(
+        "xtranslator-{}-{}-{}{}",
+        name,
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed),
+        std::env::consts::DLL_SUFFIX
+    );
+    std::env::temp_dir().join(file).to_string_lossy().into_owned()
+}
+
+struct PluginEntry {
+    path: String,
+    /// 首次使用时打开，之后由该插件的翻译器共享
+    library: Option<Arc<PluginLibrary>>,
+    info: Option<PluginInfo>,
+    manifest: Option<PluginManifest>,
+}
+
+impl PluginEntry {
+    fn new(path: String) -> Self {
+        PluginEntry {
+            path,
+            library: None,
+            info: None,
+            manifest: None,
         }
-        let library = library_result?;
+    }
+}
+
+/// `PluginRegistry::unload` 等待进行中的调用结束的时间
+pub const UNLOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 等待调用持有的引用在 `timeout` 内全部释放
+async fn released_within(loaded: &[Weak<LoadedPlugin>], timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    while loaded.iter().any(|plugin| plugin.strong_count() > 0) {
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    true
+}
+
+/// 插件注册表：按名称查找插件动态库，打开后缓存，并据此创建翻译器。
+/// 配置中的 `_dll_path` 优先于按名称查找
+pub struct PluginRegistry {
+    root: Option<String>,
+    plugins: RwLock<HashMap<String, PluginEntry>>,
+    /// 由注册表创建的翻译器及其插件句柄，卸载插件时撤销。翻译器释放后进行中的调用仍持有插件句柄
+    live: Mutex<HashMap<String, Vec<LiveSlot>>>,
+    /// 重新加载时复制出的动态库
+    shadows: Mutex<HashMap<String, String>>,
+}
+
+/// 原先的名称，保留以兼容
+pub type ProxyTranslatorFactory = PluginRegistry;
 
-        let get_name_result = unsafe { library.get::<GetPluginName>(b"get_plugin_name") };
-        if get_name_result.is_err() {
+/// 有清单的插件按清单登记，首次使用时才打开动态库；没有清单的插件打开后读取信息
+fn scan_plugins(root: String) -> HashMap<String, PluginEntry> {
+    let mut plugins = HashMap::new();
+
+    for path in plugin_files(root) {
+        if let Some(manifest) = plugin_manifest(&path) {
+            if manifest.is_compatible() {
+                for name in manifest.translators() {
+                    let entry = PluginEntry {
+                        manifest: Some(manifest.clone()),
+                        ..PluginEntry::new(path.clone())
+                    };
+                    plugins.insert(name, entry);
+                }
+            }
             continue;
         }
-        let get_name = get_name_result?;
 
-        let name_ptr = unsafe { get_name() };
-        if name_ptr.is_null() {
+        let Some(info) = probe_plugin(&path) else {
             continue;
+        };
+        // 插件中的每个翻译器都可以按名称创建
+        for name in info.translators.clone() {
+            let entry = PluginEntry {
+                path: info.path.clone(),
+                library: Some(info.library.clone()),
+                info: Some(info.clone()),
+                manifest: None,
+            };
+            plugins.insert(name, entry);
+        }
+    }
+
+    plugins
+}
+
+/// 在目录中查找包含 `name` 的插件，先查清单，没有清单的插件需要打开读取
+fn find_plugin(root: String, name: &str) -> Option<String> {
+    let files = plugin_files(root);
+    let listed = files.iter().find(|path| {
+        plugin_manifest(path).is_some_and(|manifest| manifest.is_compatible() && manifest.translators().iter().any(|n| n == name))
+    });
+    if let Some(path) = listed {
+        return Some(path.clone());
+    }
+
+    files
+        .into_iter()
+        .filter(|path| plugin_manifest(path).is_none())
+        .find(|path| probe_plugin(path).is_some_and(|info| info.translators.iter().any(|n| n == name)))
+}
+
+impl PluginRegistry {
+    /// 插件名称到路径的映射，动态库在首次使用时打开
+    pub fn new(plugins: HashMap<String, String>) -> Self {
+        let plugins = plugins
+            .into_iter()
+            .map(|(name, path)| (name, PluginEntry::new(path)))
+            .collect();
+
+        PluginRegistry {
+            root: None,
+            plugins: RwLock::new(plugins),
+            live: Mutex::new(HashMap::new()),
+            shadows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 扫描目录中的插件，扫描时打开的动态库保留供之后使用
+    pub fn scan(root: String) -> Result<Self> {
+        let mut registry = PluginRegistry::new(HashMap::new());
+        registry.plugins = RwLock::new(scan_plugins(root.clone()));
+        registry.root = Some(root);
+
+        Ok(registry)
+    }
+
+    /// 读取组合翻译器配置中的 `plugin_dir`
+    pub fn from_config(config: &Value) -> Result<Self> {
+        match config["plugin_dir"].as_str() {
+            Some(root) => PluginRegistry::scan(root.to_string()),
+            None => Ok(PluginRegistry::new(HashMap::new())),
+        }
+    }
+
+    fn path(&self, name: &str) -> Result<String> {
+        if let Some(shadow) = self.shadows.lock().unwrap().get(name) {
+            return Ok(shadow.clone());
+        }
+
+        self.plugins
+            .read()
+            .unwrap()
+            .get(name)
+            .map(|entry| entry.path.clone())
+            .ok_or(anyhow!("plugin not found: {}", name))
+    }
+
+    /// 打开插件的动态库，已打开时返回缓存
+    pub fn library(&self, name: &str) -> Result<Arc<PluginLibrary>> {
+        if let Some(library) = self.plugins.read().unwrap().get(name).and_then(|entry| entry.library.clone()) {
+            return Ok(library);
         }
-        let name = unsafe { CString::from_raw(name_ptr).to_string_lossy().into_owned() };
 
-        map.insert(name, lib_path);
+        let library = Arc::new(PluginLibrary::open(&self.path(name)?)?);
+        let mut plugins = self.plugins.write().unwrap();
+        let entry = plugins.get_mut(name).ok_or(anyhow!("plugin not found: {}", name))?;
+
+        Ok(entry.library.get_or_insert(library).clone())
     }
 
+    fn track(&self, name: &str, translator: &ProxyTranslator) {
+        let Ok(plugin) = translator.plugin() else {
+            return;
+        };
+        let mut live = self.live.lock().unwrap();
+        let slots = live.entry(name.to_string()).or_default();
+        slots.retain(|(_, plugin)| plugin.strong_count() > 0);
+        slots.push((Arc::downgrade(&translator.plugin), Arc::downgrade(&plugin)));
+    }
 
-    Ok(map)
-}
\ No newline at end of file
+    /// 可以创建的翻译器名称，一个插件可能包含多个翻译器
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.plugins.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn metadata(&self, name: &str) -> Result<Option<PluginMetadata>> {
+        self.library(name)?.metadata()
+    }
+
+    /// 读取插件信息，扫描时已读取的直接返回
+    pub fn info(&self, name: &str) -> Result<PluginInfo> {
+        if let Some(info) = self.plugins.read().unwrap().get(name).and_then(|entry| entry.info.clone()) {
+            return Ok(info);
+        }
+
+        let info = PluginInfo::probe(self.library(name)?)?;
+        if let Some(entry) = self.plugins.write().unwrap().get_mut(name) {
+            entry.info = Some(info.clone());
+        }
+
+        Ok(info)
+    }
+
+    /// 列出插件信息，跳过无法打开的插件。包含多个翻译器的插件只列出一次
+    pub fn list(&self) -> Vec<PluginInfo> {
+        let mut list: Vec<PluginInfo> = vec![];
+        for info in self.names().iter().filter_map(|name| self.info(name).ok()) {
+            if !list.iter().any(|i| i.path == info.path) {
+                list.push(info);
+            }
+        }
+        list
+    }
+
+    /// 卸载插件：撤销由注册表创建的翻译器，等待进行中的调用结束后关闭动态库。
+    /// 之后这些翻译器的调用返回错误，需要重新创建。最多等待 `UNLOAD_TIMEOUT`，见 `unload_timeout`
+    pub async fn unload(&self, name: &str) -> Result<()> {
+        self.unload_timeout(name, UNLOAD_TIMEOUT).await
+    }
+
+    /// 由插件的 `shutdown_translator` 等待进行中的调用结束，`timeout` 内没有结束时取消，
+    /// 取消后 `timeout` 内仍未结束时返回错误，动态库在最后一个调用结束后关闭。
+    /// 旧版插件没有导出该函数时只能等待宿主一侧的调用返回
+    pub async fn unload_timeout(&self, name: &str, timeout: Duration) -> Result<()> {
+        let removed = self.plugins.write().unwrap().remove(name);
+        let slots = self.live.lock().unwrap().remove(name).unwrap_or_default();
+        if removed.is_none() && slots.is_empty() {
+            bail!("plugin not found: {}", name);
+        }
+
+        for slot in slots.iter().filter_map(|(slot, _)| slot.upgrade()) {
+            slot.write().unwrap().take();
+        }
+        let weak: Vec<Weak<LoadedPlugin>> = slots.into_iter().map(|(_, plugin)| plugin).collect();
+
+        // 以插件统计的进行中调用为准，宿主一侧的引用释放不代表插件中的调用已经结束。
+        // 最后一个引用在等待结束后释放时，返回前翻译器已经销毁
+        let drain = weak
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|plugin| tokio::task::spawn_blocking(move || plugin.shutdown(timeout)));
+        // 插件中的调用不响应取消时 `shutdown_translator` 不会返回
+        let drained = tokio::time::timeout(timeout * 2, futures_util::future::join_all(drain)).await.is_ok();
+        let released = drained && released_within(&weak, timeout).await;
+
+        if let Some(shadow) = self.shadows.lock().unwrap().remove(name) {
+            let _ = std::fs::remove_file(shadow);
+        }
+
+        if !released {
+            bail!("plugin {} is still in use after {} ms", name, timeout.as_millis());
+        }
+
+        Ok(())
+    }
+
+    /// 卸载后从原路径重新加载，用于在不重启宿主的情况下更新动态库。
+    /// 由 `scan` 创建且注册表中没有该名称时在插件目录中查找，其余插件不受影响
+    pub async fn reload(&self, name: &str) -> Result<()> {
+        let path = self.plugins.read().unwrap().get(name).map(|entry| entry.path.clone());
+        if self.live.lock().unwrap().contains_key(name) || path.is_some() {
+            self.unload(name).await?;
+        }
+
+        let path = match (path, &self.root) {
+            (Some(path), _) => path,
+            (None, Some(root)) => {
+                let (root, lookup) = (root.clone(), name.to_string());
+                tokio::task::spawn_blocking(move || find_plugin(root, &lookup))
+                    .await?
+                    .ok_or(anyhow!("plugin not found: {}", name))?
+            }
+            (None, None) => bail!("plugin not found: {}", name),
+        };
+
+        // 动态库中注册了线程局部变量析构函数等情况下系统不会真正关闭动态库，
+        // 同一路径再次加载得到的仍是旧版本，因此复制一份后从副本加载
+        let shadow = shadow_path(name);
+        let (from, to) = (path.clone(), shadow.clone());
+        tokio::task::spawn_blocking(move || std::fs::copy(from, to)).await??;
+
+        let entry = PluginEntry {
+            manifest: plugin_manifest(&path).filter(|manifest| manifest.is_compatible()),
+            ..PluginEntry::new(path)
+        };
+        self.plugins.write().unwrap().insert(name.to_string(), entry);
+        self.shadows.lock().unwrap().insert(name.to_string(), shadow);
+
+        Ok(())
+    }
+
+    /// 扫描时读取的插件清单，插件没有清单时为 `None`
+    pub fn manifest(&self, name: &str) -> Option<PluginManifest> {
+        self.plugins.read().unwrap().get(name).and_then(|entry| entry.manifest.clone())
+    }
+
+    /// 读取插件的配置 schema，供界面生成配置表单。清单中指定了 schema 时不打开动态库
+    pub fn config_schema(&self, name: &str) -> Result<Option<Value>> {
+        if let Some(schema) = self.manifest(name).map(|manifest| manifest.config_schema()).transpose()?.flatten() {
+            return Ok(Some(schema));
+        }
+
+        self.library(name)?.named_config_schema(name)
+    }
+
+    /// 不创建翻译器，直接由插件检查配置
+    pub fn validate_config(&self, name: &str, config: &Value) -> Result<Option<Vec<ConfigIssue>>> {
+        self.library(name)?.validate_named_config(name, config)
+    }
+}
+
+impl Drop for PluginRegistry {
+    /// 删除重新加载时复制出的动态库，仍在使用的副本在部分系统上无法删除
+    fn drop(&mut self) {
+        for shadow in self.shadows.get_mut().unwrap().values() {
+            let _ = std::fs::remove_file(shadow);
+        }
+    }
+}
+
+#[async_trait]
+impl TranslatorFactory for PluginRegistry {
+    async fn create(&self, name: &str, mut config: Value) -> Result<BoxedTranslator> {
+        let translator = if config["_dll_path"].is_string() {
+            ProxyTranslator::new(config).await?
+        } else {
+            let library = self.library(name)?;
+            config["_dll_path"] = Value::String(library.path().to_string());
+            ProxyTranslator::create_named(library, name, &config)?
+        };
+        self.track(name, &translator);
+
+        Ok(Box::new(translator))
+    }
+}
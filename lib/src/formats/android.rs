@@ -0,0 +1,365 @@
+use super::{escape_xml, mask_markup, restore_markup, translate_document, Document, FormatConfig};
+use crate::html::decode_entities;
+use crate::placeholder::Masked;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask};
+use anyhow::{anyhow, bail, Result};
+use regex::Regex;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+
+static STRING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<string\s([^>]*[^/])>(.*?)</string>").unwrap());
+
+static ARRAY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<(string-array|plurals)\s([^>]*[^/])>(.*?)</(?:string-array|plurals)>")
+        .unwrap()
+});
+
+static ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<item\b([^>]*)>(.*?)</item>").unwrap());
+
+static ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([\w:\-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// `<xliff:g>` 整体保留，其余标签、CDATA 边界与 `%1$s` 等占位符单独保留
+static MARKUP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?s)<xliff:g\b[^>]*>.*?</xliff:g>|<!\[CDATA\[|\]\]>|<[^<>]+>",
+        r"|%(?:\d+\$)?[-#+ 0,(]*\d*(?:\.\d+)?[a-zA-Z%]"
+    ))
+    .unwrap()
+});
+
+/// 一个待翻译的文本，`<string-array>` 与 `<plurals>` 的每个 `<item>` 各为一个
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AndroidString {
+    pub name: String,
+    /// `<plurals>` 中的 `quantity`
+    pub quantity: Option<String>,
+    /// 原始内容，保留转义、实体与标签
+    pub raw: String,
+    range: Range<usize>,
+    cdata: bool,
+    /// 整个值以双引号包裹
+    quoted: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AndroidStrings {
+    content: String,
+    pub strings: Vec<AndroidString>,
+    masked: Vec<Masked>,
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    ATTRIBUTE
+        .captures_iter(attributes)
+        .find(|caps| &caps[1] == name)
+        .and_then(|caps| caps.get(2).or(caps.get(3)))
+        .map(|m| m.as_str().to_string())
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(c) => result.push(c),
+                    None => result.push_str(&hex),
+                }
+            }
+            Some(c) => result.push(c),
+            None => {}
+        }
+    }
+    result
+}
+
+/// 双引号包裹的值中单引号不需要转义
+fn escape(text: &str, quoted: bool) -> String {
+    let mut result = String::with_capacity(text.len());
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\'' if !quoted => result.push_str("\\'"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '@' | '?' if i == 0 && !quoted => {
+                result.push('\\');
+                result.push(c);
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Android 资源目录名：`zh-CN` 为 `values-zh-rCN`，带文字或数字地区的语言使用 `values-b+zh+Hans`
+pub fn values_dir(language: &str) -> String {
+    let parts: Vec<&str> = language
+        .split(['-', '_'])
+        .filter(|p| !p.is_empty())
+        .collect();
+    match parts.as_slice() {
+        [language] => format!("values-{}", language.to_lowercase()),
+        [language, region]
+            if region.len() == 2 && region.chars().all(|c| c.is_ascii_alphabetic()) =>
+        {
+            format!(
+                "values-{}-r{}",
+                language.to_lowercase(),
+                region.to_uppercase()
+            )
+        }
+        _ => format!("values-b+{}", parts.join("+")),
+    }
+}
+
+impl AndroidStrings {
+    /// `translatable="false"` 的资源与 `@string/name` 等引用不翻译
+    pub fn parse(content: &str) -> Result<Self> {
+        if !content.contains("<resources") {
+            bail!("missing resources root element");
+        }
+
+        let comments: Vec<Range<usize>> = COMMENT.find_iter(content).map(|m| m.range()).collect();
+        let in_comment = |position: usize| comments.iter().any(|r| r.contains(&position));
+        let translatable =
+            |attributes: &str| attribute(attributes, "translatable").as_deref() != Some("false");
+
+        let mut file = AndroidStrings {
+            content: content.to_string(),
+            ..Default::default()
+        };
+        for caps in STRING.captures_iter(content) {
+            if in_comment(caps.get(0).unwrap().start()) || !translatable(&caps[1]) {
+                continue;
+            }
+            file.push(
+                attribute(&caps[1], "name"),
+                None,
+                caps.get(2).unwrap().range(),
+            );
+        }
+        for caps in ARRAY.captures_iter(content) {
+            if in_comment(caps.get(0).unwrap().start()) || !translatable(&caps[2]) {
+                continue;
+            }
+            let offset = caps.get(3).unwrap().start();
+            for item in ITEM.captures_iter(&caps[3]) {
+                let range = item.get(2).unwrap().range();
+                file.push(
+                    attribute(&caps[2], "name"),
+                    attribute(&item[1], "quantity"),
+                    offset + range.start..offset + range.end,
+                );
+            }
+        }
+        file.strings.sort_by_key(|s| s.range.start);
+
+        file.masked = file
+            .strings
+            .iter()
+            .map(|s| {
+                let text = if s.quoted {
+                    &s.raw.trim()[1..s.raw.trim().len() - 1]
+                } else {
+                    s.raw.as_str()
+                };
+                let mut masked = mask_markup(text, &MARKUP);
+                if !s.cdata {
+                    masked.text = decode_entities(&masked.text);
+                }
+                masked.text = unescape(&masked.text);
+                masked
+            })
+            .collect();
+
+        Ok(file)
+    }
+
+    fn push(&mut self, name: Option<String>, quantity: Option<String>, range: Range<usize>) {
+        let raw = self.content[range.clone()].to_string();
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('@') || trimmed.starts_with('?') {
+            return;
+        }
+
+        self.strings.push(AndroidString {
+            name: name.unwrap_or_default(),
+            quantity,
+            cdata: raw.contains("<![CDATA["),
+            quoted: trimmed.len() >= 2 && trimmed.starts_with('"') && trimmed.ends_with('"'),
+            raw,
+            range,
+        });
+    }
+}
+
+impl Document for AndroidStrings {
+    fn segments(&self) -> Vec<String> {
+        self.masked.iter().map(|m| m.text.clone()).collect()
+    }
+
+    fn render(&self, translations: &[String], _config: &FormatConfig) -> String {
+        let mut xml = String::new();
+        let mut position = 0;
+        for (i, string) in self.strings.iter().enumerate() {
+            let Some(translation) = translations.get(i).filter(|t| !t.trim().is_empty()) else {
+                continue;
+            };
+
+            let mut text = escape(translation, string.quoted);
+            if !string.cdata {
+                text = escape_xml(&text);
+            }
+            let mut text = restore_markup(&text, &self.masked[i].placeholders);
+            if string.quoted {
+                text = format!("\"{}\"", text);
+            }
+
+            xml.push_str(&self.content[position..string.range.start]);
+            xml.push_str(&text);
+            position = string.range.end;
+        }
+        xml.push_str(&self.content[position..]);
+
+        xml
+    }
+}
+
+/// 翻译 `task.content` 中的 `strings.xml`
+pub async fn translate(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = AndroidStrings::parse(&task.content)?;
+    translate_document(translator, task, &file, config).await
+}
+
+/// 翻译后写入 `res_dir` 下目标语言的 `values-<lang>/strings.xml`，返回写入的路径
+pub async fn translate_to(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    res_dir: &Path,
+    config: &FormatConfig,
+) -> Result<PathBuf> {
+    let language = task
+        .target_language
+        .as_ref()
+        .ok_or_else(|| anyhow!("target language is required"))?;
+    let result = translate(translator, task, config).await?;
+
+    let dir = res_dir.join(values_dir(language.as_str()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("strings.xml");
+    std::fs::write(&path, result.content.unwrap_or_default())?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+const SAMPLE: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<resources xmlns:xliff="urn:oasis:names:tc:xliff:document:1.2">
+    <!-- <string name="commented">Hidden</string> -->
+    <string name="app_name" translatable="false">Demo</string>
+    <string name="welcome">Welcome, <xliff:g id="name">%1$s</xliff:g>!</string>
+    <string name="dont">Don\'t &amp; stop</string>
+    <string name="html"><![CDATA[<b>Bold</b> %d]]></string>
+    <string name="spaced">"  It's spaced  "</string>
+    <string name="link">@string/welcome</string>
+    <string-array name="planets">
+        <item>Mercury</item>
+        <item>Venus</item>
+    </string-array>
+    <plurals name="songs">
+        <item quantity="one">%d song</item>
+        <item quantity="other">%d songs</item>
+    </plurals>
+</resources>
+"#;
+
+#[test]
+fn test_parse_android() -> Result<()> {
+    let file = AndroidStrings::parse(SAMPLE)?;
+    let names: Vec<&str> = file.strings.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["welcome", "dont", "html", "spaced", "planets", "planets", "songs", "songs"]
+    );
+    assert_eq!(file.strings[7].quantity.as_deref(), Some("other"));
+    assert_eq!(
+        file.segments(),
+        vec![
+            "Welcome, ⟦0⟧!",
+            "Don't & stop",
+            "⟦0⟧⟦1⟧Bold⟦2⟧ ⟦3⟧⟦4⟧",
+            "  It's spaced  ",
+            "Mercury",
+            "Venus",
+            "⟦0⟧ song",
+            "⟦0⟧ songs"
+        ]
+    );
+
+    // 原样写回
+    assert_eq!(file.render(&[], &FormatConfig::default()), SAMPLE);
+    assert_eq!(
+        file.render(&file.segments(), &FormatConfig::default()),
+        SAMPLE
+    );
+
+    assert_eq!(values_dir("fr"), "values-fr");
+    assert_eq!(values_dir("zh-CN"), "values-zh-rCN");
+    assert_eq!(values_dir("zh-Hans"), "values-b+zh+Hans");
+    assert_eq!(values_dir("es-419"), "values-b+es+419");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_android() -> Result<()> {
+    let translator = MockTranslator::new("译 ");
+    let config = FormatConfig {
+        batch_size: 1,
+        ..Default::default()
+    };
+    let mut task = task(SAMPLE);
+    task.target_language = Some("zh-CN".parse()?);
+
+    let dir = std::env::temp_dir().join(format!("xtranslator-android-{}", std::process::id()));
+    let path = translate_to(&translator, &task, &dir, &config).await?;
+    assert_eq!(path, dir.join("values-zh-rCN").join("strings.xml"));
+
+    let content = std::fs::read_to_string(&path)?;
+    assert!(content.contains(r#"<string name="app_name" translatable="false">Demo</string>"#));
+    assert!(content.contains(
+        r#"<string name="welcome">译 Welcome, <xliff:g id="name">%1$s</xliff:g>!</string>"#
+    ));
+    assert!(content.contains(r#"<string name="dont">译 Don\'t &amp; stop</string>"#));
+    assert!(content.contains(r#"<string name="html">译 <![CDATA[<b>Bold</b> %d]]></string>"#));
+    assert!(content.contains(r#"<string name="spaced">"译   It's spaced  "</string>"#));
+    assert!(content.contains(r#"<string name="link">@string/welcome</string>"#));
+    assert!(content.contains(r#"<item quantity="other">译 %d songs</item>"#));
+    assert!(content.contains("<!-- <string name=\"commented\">Hidden</string> -->"));
+
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
@@ -0,0 +1,406 @@
+use super::{escape_xml, mask_markup, restore_markup, translate_document, Document, FormatConfig};
+use crate::html::decode_entities;
+use crate::placeholder::Masked;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask};
+use anyhow::{anyhow, bail, Result};
+use regex::Regex;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// `%@`、`%1$d`、`%lld`、`%.2f` 等格式说明符与 `.stringsdict` 中的 `%#@files@` 变量
+static FORMAT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"%#@\w+@",
+        r"|%(?:\d+\$)?[-#+ 0']*\d*(?:\.\d+)?(?:hh|h|ll|l|q|z|t|j|L)?[@dDuUxXoOfeEgGcCsSpaAF%]"
+    ))
+    .unwrap()
+});
+
+static PLIST_STRING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<key>([^<]*)</key>\s*<string>(.*?)</string>").unwrap());
+
+/// 需要翻译的 `.stringsdict` 键：复数类别与含有变量的格式串
+const PLURAL_KEYS: &[&str] = &[
+    "NSStringLocalizedFormatKey",
+    "zero",
+    "one",
+    "two",
+    "few",
+    "many",
+    "other",
+];
+
+/// 一个待翻译的值，`range` 为其在文件中的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppleString {
+    pub key: String,
+    pub value: String,
+    range: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Strings,
+    StringsDict,
+}
+
+/// `Localizable.strings` 或 `.stringsdict` 文件，注释与键原样保留
+#[derive(Debug, Clone)]
+pub struct AppleStrings {
+    kind: Kind,
+    content: String,
+    pub strings: Vec<AppleString>,
+    masked: Vec<Masked>,
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('u' | 'U') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(c) => result.push(c),
+                    None => result.push_str(&hex),
+                }
+            }
+            Some(c) => result.push(c),
+            None => {}
+        }
+    }
+    result
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// 引号内的范围
+    Quoted(Range<usize>),
+    Word(Range<usize>),
+    Equals,
+    Semicolon,
+}
+
+/// 切分 `.strings` 文件，跳过 `/* */` 与 `//` 注释
+fn tokenize(content: &str) -> Result<Vec<Token>> {
+    let bytes = content.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = content[i + 2..]
+                    .find("*/")
+                    .map(|end| i + 2 + end + 2)
+                    .ok_or_else(|| anyhow!("unterminated comment"))?;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = content[i..]
+                    .find('\n')
+                    .map(|end| i + end)
+                    .unwrap_or(bytes.len());
+            }
+            b'"' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < bytes.len() && bytes[end] != b'"' {
+                    end += if bytes[end] == b'\\' { 2 } else { 1 };
+                }
+                if end >= bytes.len() {
+                    bail!("unterminated string at byte {}", i);
+                }
+                tokens.push(Token::Quoted(start..end));
+                i = end + 1;
+            }
+            b'=' => {
+                tokens.push(Token::Equals);
+                i += 1;
+            }
+            b';' => {
+                tokens.push(Token::Semicolon);
+                i += 1;
+            }
+            c if c.is_ascii_whitespace() => i += 1,
+            _ => {
+                let start = i;
+                while i < bytes.len()
+                    && !bytes[i].is_ascii_whitespace()
+                    && !matches!(bytes[i], b'=' | b';' | b'"')
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Word(start..i));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Apple 的语言目录名，`zh-CN` 为 `zh-Hans.lproj`，`zh-TW` 为 `zh-Hant.lproj`
+pub fn lproj_dir(language: &str) -> String {
+    let language = match language.replace('_', "-").as_str() {
+        "zh-CN" | "zh-SG" => "zh-Hans".to_string(),
+        "zh-TW" | "zh-HK" | "zh-MO" => "zh-Hant".to_string(),
+        language => language.to_string(),
+    };
+    format!("{}.lproj", language)
+}
+
+impl AppleStrings {
+    pub fn parse_strings(content: &str) -> Result<Self> {
+        let content = content.trim_start_matches('\u{feff}');
+        let tokens = tokenize(content)?;
+
+        let mut strings = vec![];
+        for window in tokens.windows(4) {
+            let [Token::Quoted(key) | Token::Word(key), Token::Equals, Token::Quoted(value), Token::Semicolon] =
+                window
+            else {
+                continue;
+            };
+            strings.push(AppleString {
+                key: unescape(&content[key.clone()]),
+                value: unescape(&content[value.clone()]),
+                range: value.clone(),
+            });
+        }
+
+        Ok(Self::new(Kind::Strings, content, strings))
+    }
+
+    /// 只翻译复数类别与 `NSStringLocalizedFormatKey` 的值
+    pub fn parse_stringsdict(content: &str) -> Result<Self> {
+        if !content.contains("<plist") {
+            bail!("missing plist root element");
+        }
+
+        let strings = PLIST_STRING
+            .captures_iter(content)
+            .filter(|caps| PLURAL_KEYS.contains(&caps[1].trim()))
+            .map(|caps| AppleString {
+                key: caps[1].trim().to_string(),
+                value: decode_entities(&caps[2]),
+                range: caps.get(2).unwrap().range(),
+            })
+            .collect();
+
+        Ok(Self::new(Kind::StringsDict, content, strings))
+    }
+
+    fn new(kind: Kind, content: &str, strings: Vec<AppleString>) -> Self {
+        let masked = strings
+            .iter()
+            .map(|s| mask_markup(&s.value, &FORMAT))
+            .collect();
+
+        AppleStrings {
+            kind,
+            content: content.to_string(),
+            strings,
+            masked,
+        }
+    }
+}
+
+impl Document for AppleStrings {
+    /// 只有格式说明符的值（如 `%#@files@`）不翻译
+    fn segments(&self) -> Vec<String> {
+        self.masked
+            .iter()
+            .map(|m| {
+                let rest = m.text.replace(|c: char| "⟦⟧0123456789".contains(c), "");
+                if rest.trim().is_empty() {
+                    String::new()
+                } else {
+                    m.text.clone()
+                }
+            })
+            .collect()
+    }
+
+    fn render(&self, translations: &[String], _config: &FormatConfig) -> String {
+        let mut output = String::new();
+        let mut position = 0;
+        for (i, string) in self.strings.iter().enumerate() {
+            let Some(translation) = translations.get(i).filter(|t| !t.trim().is_empty()) else {
+                continue;
+            };
+            let text = match self.kind {
+                Kind::Strings => escape(translation),
+                Kind::StringsDict => escape_xml(translation),
+            };
+
+            output.push_str(&self.content[position..string.range.start]);
+            output.push_str(&restore_markup(&text, &self.masked[i].placeholders));
+            position = string.range.end;
+        }
+        output.push_str(&self.content[position..]);
+
+        output
+    }
+}
+
+/// 翻译 `task.content` 中的 `.strings` 文件
+pub async fn translate_strings(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = AppleStrings::parse_strings(&task.content)?;
+    translate_document(translator, task, &file, config).await
+}
+
+/// 翻译 `task.content` 中的 `.stringsdict` 文件
+pub async fn translate_stringsdict(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = AppleStrings::parse_stringsdict(&task.content)?;
+    translate_document(translator, task, &file, config).await
+}
+
+/// 按 `file_name` 的扩展名翻译后写入 `dir` 下目标语言的 `<lang>.lproj/<file_name>`，返回写入的路径
+pub async fn translate_to(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    dir: &Path,
+    file_name: &str,
+    config: &FormatConfig,
+) -> Result<PathBuf> {
+    let language = task
+        .target_language
+        .as_ref()
+        .ok_or_else(|| anyhow!("target language is required"))?;
+    let result = if file_name.ends_with(".stringsdict") {
+        translate_stringsdict(translator, task, config).await?
+    } else {
+        translate_strings(translator, task, config).await?
+    };
+
+    let dir = dir.join(lproj_dir(language.as_str()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    std::fs::write(&path, result.content.unwrap_or_default())?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+const STRINGS: &str = r#"/* Greeting shown on launch */
+"greeting" = "Hello, %@!";
+// "commented" = "Hidden";
+"url" = "Visit http://example.com";
+login_title = "Sign \"in\"";
+"count" = "%1$d of %2$lld";
+"#;
+
+#[cfg(test)]
+const STRINGSDICT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+    <key>%d files</key>
+    <dict>
+        <key>NSStringLocalizedFormatKey</key>
+        <string>%#@files@</string>
+        <key>files</key>
+        <dict>
+            <key>NSStringFormatSpecTypeKey</key>
+            <string>NSStringPluralRuleType</string>
+            <key>NSStringFormatValueTypeKey</key>
+            <string>d</string>
+            <key>one</key>
+            <string>%d file &amp; folder</string>
+            <key>other</key>
+            <string>%d files</string>
+        </dict>
+    </dict>
+</dict>
+</plist>
+"#;
+
+#[test]
+fn test_parse_apple_strings() -> Result<()> {
+    let file = AppleStrings::parse_strings(STRINGS)?;
+    let pairs: Vec<(&str, &str)> = file
+        .strings
+        .iter()
+        .map(|s| (s.key.as_str(), s.value.as_str()))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("greeting", "Hello, %@!"),
+            ("url", "Visit http://example.com"),
+            ("login_title", "Sign \"in\""),
+            ("count", "%1$d of %2$lld"),
+        ]
+    );
+    assert_eq!(file.segments()[3], "⟦0⟧ of ⟦1⟧");
+    assert_eq!(
+        file.render(&file.segments(), &FormatConfig::default()),
+        STRINGS
+    );
+
+    let file = AppleStrings::parse_stringsdict(STRINGSDICT)?;
+    assert_eq!(file.segments(), vec!["", "⟦0⟧ file & folder", "⟦0⟧ files"]);
+    assert_eq!(
+        file.render(&file.segments(), &FormatConfig::default()),
+        STRINGSDICT
+    );
+
+    assert!(AppleStrings::parse_strings("\"a\" = \"b").is_err());
+    assert_eq!(lproj_dir("zh-CN"), "zh-Hans.lproj");
+    assert_eq!(lproj_dir("pt-BR"), "pt-BR.lproj");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_apple_strings() -> Result<()> {
+    let translator = MockTranslator::new("\"译\" ");
+    let config = FormatConfig {
+        batch_size: 1,
+        ..Default::default()
+    };
+    let mut task = task(STRINGS);
+    task.target_language = Some("zh-CN".parse()?);
+    let dir = std::env::temp_dir().join(format!("xtranslator-lproj-{}", std::process::id()));
+
+    let path = translate_to(&translator, &task, &dir, "Localizable.strings", &config).await?;
+    assert_eq!(path, dir.join("zh-Hans.lproj").join("Localizable.strings"));
+    let content = std::fs::read_to_string(&path)?;
+    assert!(content.starts_with("/* Greeting shown on launch */\n\"greeting\" = \"\\\"译\\\" Hello, %@!\";\n// \"commented\""));
+    assert!(content.contains("login_title = \"\\\"译\\\" Sign \\\"in\\\"\";"));
+    assert!(content.contains("\"count\" = \"\\\"译\\\" %1$d of %2$lld\";"));
+
+    task.content = STRINGSDICT.to_string();
+    let path = translate_to(&translator, &task, &dir, "Localizable.stringsdict", &config).await?;
+    let content = std::fs::read_to_string(&path)?;
+    assert!(content.contains("<string>%#@files@</string>"));
+    assert!(content.contains("<string>\"译\" %d file &amp; folder</string>"));
+    assert!(content.contains("<string>NSStringPluralRuleType</string>"));
+
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
@@ -0,0 +1,309 @@
+use super::{mask_markup, restore_markup, translate_document, Document, FormatConfig};
+use crate::placeholder::Masked;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask};
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// 样式覆盖标签（含卡拉 OK 标签 `{\k20}`）与 `\N`、`\n`、`\h` 换行与空格
+static MARKUP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{[^}]*\}|\\[Nnh]").unwrap());
+
+/// 未声明 `Format:` 时 ASS 与 SSA 对白的字段数，文本为最后一个字段
+const DEFAULT_FIELDS: usize = 10;
+
+/// 未声明 `Format:` 时 `Style` 字段的位置
+const DEFAULT_STYLE_FIELD: usize = 3;
+
+/// 一条对白，`fields` 为文本之前的字段，保留原有空白
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssDialogue {
+    pub fields: Vec<String>,
+    pub text: String,
+    style_field: usize,
+}
+
+impl AssDialogue {
+    pub fn style(&self) -> &str {
+        self.fields
+            .get(self.style_field)
+            .map(|s| s.trim())
+            .unwrap_or("")
+    }
+
+    fn line(&self, style: Option<&str>, text: &str) -> String {
+        let mut fields = self.fields.clone();
+        if let (Some(style), Some(field)) = (style, fields.get_mut(self.style_field)) {
+            *field = replace_trimmed(field, style);
+        }
+        fields.push(text.to_string());
+
+        format!("Dialogue:{}", fields.join(","))
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Line {
+    Raw(String),
+    Dialogue(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AssFile {
+    lines: Vec<Line>,
+    pub dialogues: Vec<AssDialogue>,
+    /// `Style:` 行的位置、样式名称与内容
+    styles: Vec<(usize, String, String)>,
+    style_name_field: usize,
+    crlf: bool,
+    masked: Vec<Masked>,
+}
+
+/// 替换 `field` 中去掉首尾空白后的部分
+fn replace_trimmed(field: &str, value: &str) -> String {
+    let start = field.len() - field.trim_start().len();
+    let end = field.trim_end().len();
+    format!("{}{}{}", &field[..start], value, &field[end.max(start)..])
+}
+
+fn format_fields(value: &str) -> Vec<String> {
+    value.split(',').map(|s| s.trim().to_lowercase()).collect()
+}
+
+impl AssFile {
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut file = AssFile {
+            crlf: content.contains("\r\n"),
+            ..Default::default()
+        };
+        let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+
+        let mut section = String::new();
+        let mut event_fields = DEFAULT_FIELDS;
+        let mut style_field = DEFAULT_STYLE_FIELD;
+
+        for line in content.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') && trimmed.ends_with(']') {
+                section = trimmed.to_lowercase();
+            }
+
+            let Some((key, value)) = line.split_once(':') else {
+                file.lines.push(Line::Raw(line.to_string()));
+                continue;
+            };
+
+            match (section.as_str(), key.trim()) {
+                ("[v4+ styles]" | "[v4 styles]", "Format") => {
+                    file.style_name_field = format_fields(value)
+                        .iter()
+                        .position(|f| f == "name")
+                        .unwrap_or(0);
+                }
+                ("[v4+ styles]" | "[v4 styles]", "Style") => {
+                    let name = value
+                        .split(',')
+                        .nth(file.style_name_field)
+                        .unwrap_or("")
+                        .trim();
+                    file.styles
+                        .push((file.lines.len(), name.to_string(), line.to_string()));
+                }
+                ("[events]", "Format") => {
+                    let fields = format_fields(value);
+                    if fields.last().map(String::as_str) != Some("text") {
+                        bail!("the last event field must be Text");
+                    }
+                    event_fields = fields.len();
+                    style_field = fields
+                        .iter()
+                        .position(|f| f == "style")
+                        .unwrap_or(DEFAULT_STYLE_FIELD);
+                }
+                ("[events]", "Dialogue") => {
+                    let mut fields: Vec<String> = value
+                        .splitn(event_fields, ',')
+                        .map(str::to_string)
+                        .collect();
+                    if fields.len() < event_fields {
+                        bail!("invalid dialogue line: {}", line);
+                    }
+                    let text = fields.pop().unwrap_or_default();
+                    file.lines.push(Line::Dialogue(file.dialogues.len()));
+                    file.dialogues.push(AssDialogue {
+                        fields,
+                        text,
+                        style_field,
+                    });
+                    continue;
+                }
+                _ => {}
+            }
+            file.lines.push(Line::Raw(line.to_string()));
+        }
+
+        file.masked = file
+            .dialogues
+            .iter()
+            .map(|d| mask_markup(&d.text, &MARKUP))
+            .collect();
+
+        Ok(file)
+    }
+
+    /// 以第一条对白的样式为模板声明 `name`，已存在时返回 `None`
+    fn secondary_style_line(&self, name: &str) -> Option<String> {
+        if self.styles.iter().any(|(_, style, _)| style == name) {
+            return None;
+        }
+        let source = self.dialogues.first().map(AssDialogue::style).unwrap_or("");
+        let (_, _, template) = self
+            .styles
+            .iter()
+            .find(|(_, style, _)| style == source)
+            .or(self.styles.last())?;
+
+        let (key, value) = template.split_once(':')?;
+        let fields: Vec<String> = value
+            .split(',')
+            .enumerate()
+            .map(|(i, f)| {
+                if i == self.style_name_field {
+                    replace_trimmed(f, name)
+                } else {
+                    f.to_string()
+                }
+            })
+            .collect();
+
+        Some(format!("{}:{}", key, fields.join(",")))
+    }
+}
+
+impl Document for AssFile {
+    fn segments(&self) -> Vec<String> {
+        self.masked.iter().map(|m| m.text.clone()).collect()
+    }
+
+    /// 双语字幕中原文与译文以 `\N` 分隔，设置了 `secondary_style` 时改为另起一行
+    fn render(&self, translations: &[String], config: &FormatConfig) -> String {
+        let secondary = config.secondary_style.as_deref();
+        let style_line = secondary.and_then(|name| self.secondary_style_line(name));
+        let mut ass = String::new();
+
+        for (i, line) in self.lines.iter().enumerate() {
+            match line {
+                Line::Raw(raw) => ass.push_str(raw),
+                Line::Dialogue(index) => {
+                    let dialogue = &self.dialogues[*index];
+                    let text = match translations.get(*index) {
+                        Some(translation) => {
+                            restore_markup(translation, &self.masked[*index].placeholders)
+                        }
+                        None => dialogue.text.clone(),
+                    };
+
+                    match secondary {
+                        Some(style) => {
+                            ass.push_str(&dialogue.line(None, &dialogue.text));
+                            if text != dialogue.text {
+                                ass.push('\n');
+                                ass.push_str(&dialogue.line(Some(style), &text));
+                            }
+                        }
+                        None if config.bilingual && text != dialogue.text => ass.push_str(
+                            &dialogue.line(None, &format!("{}\\N{}", dialogue.text, text)),
+                        ),
+                        None => ass.push_str(&dialogue.line(None, &text)),
+                    }
+                }
+            }
+            ass.push('\n');
+
+            if let (Some(style_line), Some((last, _, _))) = (&style_line, self.styles.last()) {
+                if i == *last {
+                    ass.push_str(style_line);
+                    ass.push('\n');
+                }
+            }
+        }
+
+        if self.crlf {
+            ass = ass.replace('\n', "\r\n");
+        }
+        ass
+    }
+}
+
+/// 翻译 `task.content` 中的 ASS/SSA 字幕，只翻译 `Dialogue` 的文本
+pub async fn translate(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = AssFile::parse(&task.content)?;
+    translate_document(translator, task, &file, config).await
+}
+
+#[cfg(test)]
+const SAMPLE: &str = "[Script Info]
+Title: Sample
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize
+Style: Default,Arial,20
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,{\\an8}Hello,\\Nworld
+Comment: 0,0:00:02.00,0:00:03.00,Default,,0,0,0,,note
+Dialogue: 0,0:00:03.00,0:00:04.00,Default,,0,0,0,,{\\k20}Ka{\\k30}ra
+";
+
+#[test]
+fn test_parse_ass() -> Result<()> {
+    let file = AssFile::parse(SAMPLE)?;
+    assert_eq!(file.dialogues.len(), 2);
+    assert_eq!(file.dialogues[0].style(), "Default");
+    assert_eq!(file.dialogues[0].text, "{\\an8}Hello,\\Nworld");
+    assert_eq!(file.segments(), vec!["⟦0⟧Hello,⟦1⟧world", "⟦0⟧Ka⟦1⟧ra"]);
+
+    // 原样写回
+    assert_eq!(file.render(&[], &FormatConfig::default()), SAMPLE);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_ass() -> Result<()> {
+    let translator = MockTranslator::new("> ");
+    let config = FormatConfig {
+        batch_size: 1,
+        secondary_style: Some("Translated".to_string()),
+        ..Default::default()
+    };
+    let content = translate(&translator, &task(SAMPLE), &config)
+        .await?
+        .content
+        .unwrap();
+
+    assert!(content.contains("Style: Default,Arial,20\nStyle: Translated,Arial,20\n"));
+    assert!(content.contains(
+        "Dialogue: 0,0:00:01.00,0:00:02.00,Default,,0,0,0,,{\\an8}Hello,\\Nworld\nDialogue: 0,0:00:01.00,0:00:02.00,Translated,,0,0,0,,> {\\an8}Hello,\\Nworld\n"
+    ));
+    assert!(content.contains("Comment: 0,0:00:02.00,0:00:03.00,Default,,0,0,0,,note\n"));
+
+    let config = FormatConfig {
+        batch_size: 1,
+        bilingual: true,
+        ..Default::default()
+    };
+    let content = translate(&translator, &task(SAMPLE), &config)
+        .await?
+        .content
+        .unwrap();
+    assert!(content.contains("Default,,0,0,0,,{\\k20}Ka{\\k30}ra\\N> {\\k20}Ka{\\k30}ra\n"));
+
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use super::{mask_markup, restore_markup, translate_document, Document, FormatConfig};
+use crate::placeholder::Masked;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask};
+use anyhow::Result;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::{Map, Value};
+use std::sync::LazyLock;
+
+/// `{{count}}`、`{name}` 等插值，i18next 的 `$t(key)` 与 vue-i18n 的 `@:key` 链接
+static MARKUP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{[^{}]*\}\}|\{[^{}]*\}|\$t\([^)]*\)|@(?:\.[A-Za-z]+)?:[\w.\-]+").unwrap()
+});
+
+/// 嵌套键的 JSON 语言文件，写回时保持键的顺序与缩进
+#[derive(Debug, Clone)]
+pub struct JsonFile {
+    value: Value,
+    indent: String,
+    trailing_newline: bool,
+    /// 待翻译字符串的 JSON Pointer
+    pointers: Vec<String>,
+    masked: Vec<Masked>,
+}
+
+fn pointer_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn collect(value: &Value, pointer: &str, pointers: &mut Vec<String>) {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => pointers.push(pointer.to_string()),
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect(item, &format!("{}/{}", pointer, i), pointers);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                collect(
+                    item,
+                    &format!("{}/{}", pointer, pointer_token(key)),
+                    pointers,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 按 `source` 的键顺序合并，`target` 中缺少或为空字符串的值取自 `source` 并记为待翻译
+fn merge(
+    source: &Value,
+    target: Option<&Value>,
+    pointer: &str,
+    pointers: &mut Vec<String>,
+) -> Value {
+    match (source, target) {
+        (Value::Object(source), Some(Value::Object(target))) => {
+            let mut map = Map::new();
+            for (key, value) in source {
+                let pointer = format!("{}/{}", pointer, pointer_token(key));
+                map.insert(
+                    key.clone(),
+                    merge(value, target.get(key), &pointer, pointers),
+                );
+            }
+            for (key, value) in target {
+                if !map.contains_key(key) {
+                    map.insert(key.clone(), value.clone());
+                }
+            }
+            Value::Object(map)
+        }
+        (Value::String(_), Some(Value::String(t))) if t.is_empty() => {
+            collect(source, pointer, pointers);
+            source.clone()
+        }
+        (_, Some(target)) => target.clone(),
+        (source, None) => {
+            collect(source, pointer, pointers);
+            source.clone()
+        }
+    }
+}
+
+fn detect_indent(content: &str) -> String {
+    content
+        .lines()
+        .skip(1)
+        .find(|line| !line.trim().is_empty())
+        .map(|line| line[..line.len() - line.trim_start().len()].to_string())
+        .filter(|indent| !indent.is_empty())
+        .unwrap_or_else(|| "  ".to_string())
+}
+
+impl JsonFile {
+    pub fn parse(content: &str) -> Result<Self> {
+        let content = content.trim_start_matches('\u{feff}');
+        let value: Value = serde_json::from_str(content)?;
+        let mut pointers = vec![];
+        collect(&value, "", &mut pointers);
+
+        Ok(Self::new(value, content, pointers))
+    }
+
+    /// 只翻译 `target` 中缺少的键，已有的译文与 `source` 中没有的键原样保留
+    pub fn missing_from(source: &str, target: &str) -> Result<Self> {
+        let source = source.trim_start_matches('\u{feff}');
+        let target = target.trim_start_matches('\u{feff}');
+        let source_value: Value = serde_json::from_str(source)?;
+        let target_value: Value = if target.trim().is_empty() {
+            Value::Object(Map::new())
+        } else {
+            serde_json::from_str(target)?
+        };
+
+        let mut pointers = vec![];
+        let value = merge(&source_value, Some(&target_value), "", &mut pointers);
+
+        Ok(Self::new(value, source, pointers))
+    }
+
+    fn new(value: Value, content: &str, pointers: Vec<String>) -> Self {
+        let masked = pointers
+            .iter()
+            .map(|pointer| {
+                let text = value.pointer(pointer).and_then(Value::as_str).unwrap_or("");
+                mask_markup(text, &MARKUP)
+            })
+            .collect();
+
+        JsonFile {
+            value,
+            indent: detect_indent(content),
+            trailing_newline: content.ends_with('\n'),
+            pointers,
+            masked,
+        }
+    }
+
+    pub fn pointers(&self) -> &[String] {
+        &self.pointers
+    }
+}
+
+impl Document for JsonFile {
+    fn segments(&self) -> Vec<String> {
+        self.masked.iter().map(|m| m.text.clone()).collect()
+    }
+
+    fn render(&self, translations: &[String], _config: &FormatConfig) -> String {
+        let mut value = self.value.clone();
+        for (i, pointer) in self.pointers.iter().enumerate() {
+            let Some(translation) = translations.get(i) else {
+                continue;
+            };
+            if let Some(slot) = value.pointer_mut(pointer) {
+                *slot = Value::String(restore_markup(translation, &self.masked[i].placeholders));
+            }
+        }
+
+        let mut buffer = vec![];
+        let mut serializer = serde_json::Serializer::with_formatter(
+            &mut buffer,
+            PrettyFormatter::with_indent(self.indent.as_bytes()),
+        );
+        if value.serialize(&mut serializer).is_err() {
+            return self.value.to_string();
+        }
+
+        let mut json = String::from_utf8(buffer).unwrap_or_default();
+        if self.trailing_newline {
+            json.push('\n');
+        }
+        json
+    }
+}
+
+/// 翻译 `task.content` 中 JSON 语言文件的所有字符串
+pub async fn translate(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = JsonFile::parse(&task.content)?;
+    translate_document(translator, task, &file, config).await
+}
+
+/// 以 `task.content` 为源语言文件，只补全 `target` 中缺少的键
+pub async fn translate_missing(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    target: &str,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = JsonFile::missing_from(&task.content, target)?;
+    translate_document(translator, task, &file, config).await
+}
+
+#[cfg(test)]
+const SAMPLE: &str = r#"{
+    "title": "Inbox",
+    "messages": {
+        "count": "You have {{count}} messages",
+        "link": "@:title and $t(common.more)",
+        "empty": ""
+    },
+    "tabs": [
+        "All",
+        "Unread"
+    ],
+    "limit": 10,
+    "a/b": "Slash"
+}
+"#;
+
+#[test]
+fn test_parse_json() -> Result<()> {
+    let file = JsonFile::parse(SAMPLE)?;
+    assert_eq!(
+        file.pointers(),
+        [
+            "/title",
+            "/messages/count",
+            "/messages/link",
+            "/tabs/0",
+            "/tabs/1",
+            "/a~1b"
+        ]
+    );
+    assert_eq!(file.segments()[1], "You have ⟦0⟧ messages");
+    assert_eq!(file.segments()[2], "⟦0⟧ and ⟦1⟧");
+
+    // 原样写回，键的顺序不变
+    assert_eq!(file.render(&[], &FormatConfig::default()), SAMPLE);
+
+    assert!(JsonFile::parse("{").is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_json() -> Result<()> {
+    let translator = MockTranslator::new("> ");
+    let config = FormatConfig {
+        batch_size: 1,
+        ..Default::default()
+    };
+    let content = translate(&translator, &task(SAMPLE), &config)
+        .await?
+        .content
+        .unwrap();
+    let value: Value = serde_json::from_str(&content)?;
+    assert_eq!(value["messages"]["count"], "> You have {{count}} messages");
+    assert_eq!(value["tabs"][1], "> Unread");
+    assert_eq!(value["limit"], 10);
+
+    let target = r#"{"extra": "保留", "messages": {"count": "你有 {{count}} 条消息", "link": ""}}"#;
+    let translator = MockTranslator::new("> ");
+    let content = translate_missing(&translator, &task(SAMPLE), target, &config)
+        .await?
+        .content
+        .unwrap();
+    let value: Value = serde_json::from_str(&content)?;
+    assert_eq!(value["messages"]["count"], "你有 {{count}} 条消息");
+    assert_eq!(value["messages"]["link"], "> @:title and $t(common.more)");
+    assert_eq!(value["title"], "> Inbox");
+    assert_eq!(value["extra"], "保留");
+    // 按源文件的键顺序输出，目标文件多出的键在后
+    let keys: Vec<&String> = value.as_object().unwrap().keys().collect();
+    assert_eq!(keys, ["title", "messages", "tabs", "limit", "a/b", "extra"]);
+    assert_eq!(translator.calls(), 5);
+
+    Ok(())
+}
@@ -0,0 +1,249 @@
+use crate::placeholder::{unmask, Masked};
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask, TranslatedItem, Usage};
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+pub mod android;
+pub mod apple;
+pub mod ass;
+pub mod json;
+pub mod po;
+pub mod properties;
+pub mod srt;
+pub mod tmx;
+pub mod vtt;
+pub mod xliff;
+pub mod yaml;
+
+/// 合并请求时的片段分隔符
+const BATCH_SEPARATOR: &str = "\n⟦§⟧\n";
+
+static SENTINEL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"⟦\s*(\d+)\s*⟧").unwrap());
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormatConfig {
+    /// 每次请求合并的片段数，译文的片段数对不上时退回逐段翻译
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// 作为参考译文附带的前文片段数
+    #[serde(default = "default_context")]
+    pub context: usize,
+    /// 同时保留原文与译文，格式不支持时忽略
+    #[serde(default)]
+    pub bilingual: bool,
+    /// ASS 字幕中译文以该样式另起一行，原文行保留。样式不存在时以第一条对白的样式为模板添加
+    #[serde(default)]
+    pub secondary_style: Option<String>,
+    /// PO 文件中填写的译文标记为 `fuzzy`，留待人工校对
+    #[serde(default)]
+    pub mark_fuzzy: bool,
+}
+
+fn default_batch_size() -> usize {
+    10
+}
+
+fn default_context() -> usize {
+    2
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        FormatConfig {
+            batch_size: default_batch_size(),
+            context: default_context(),
+            bilingual: false,
+            secondary_style: None,
+            mark_fuzzy: false,
+        }
+    }
+}
+
+/// 可翻译的文件：拆出待翻译的文本，再用译文写回
+pub trait Document {
+    /// 待翻译的文本，格式中的标记已替换为 `⟦0⟧` 形式的哨兵
+    fn segments(&self) -> Vec<String>;
+
+    /// `translations` 与 `segments()` 一一对应，缺失的片段保留原文
+    fn render(&self, translations: &[String], config: &FormatConfig) -> String;
+}
+
+/// 把匹配 `markup` 的标记替换为哨兵
+pub fn mask_markup(text: &str, markup: &Regex) -> Masked {
+    let mut placeholders = vec![];
+    let text = markup
+        .replace_all(text, |caps: &regex::Captures| {
+            placeholders.push(caps[0].to_string());
+            format!("⟦{}⟧", placeholders.len() - 1)
+        })
+        .to_string();
+
+    Masked { text, placeholders }
+}
+
+/// 还原哨兵，译文中丢失的标记追加在末尾
+pub fn restore_markup(text: &str, placeholders: &[String]) -> String {
+    let present: HashSet<usize> = SENTINEL
+        .captures_iter(text)
+        .filter_map(|caps| caps[1].parse().ok())
+        .collect();
+    let (mut restored, _) = unmask(text, placeholders);
+    for (index, placeholder) in placeholders.iter().enumerate() {
+        if !present.contains(&index) {
+            restored.push_str(placeholder);
+        }
+    }
+
+    restored
+}
+
+/// 转义 XML 文本内容
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+async fn translate_text(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    text: &str,
+    usage: &mut Option<Usage>,
+) -> Result<String> {
+    let mut task = task.clone();
+    task.content = text.to_string();
+
+    let result = translator.translate(task).await?;
+    if let Some(u) = &result.usage {
+        usage.get_or_insert_with(Usage::default).add(u);
+    }
+    Ok(result.content.unwrap_or_default())
+}
+
+async fn translate_batch(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    texts: &[&str],
+    usage: &mut Option<Usage>,
+) -> Result<Vec<String>> {
+    if texts.len() > 1 {
+        let joined = translate_text(translator, task, &texts.join(BATCH_SEPARATOR), usage).await?;
+        let parts: Vec<String> = joined.split("⟦§⟧").map(|s| s.trim().to_string()).collect();
+        if parts.len() == texts.len() {
+            return Ok(parts);
+        }
+    }
+
+    let mut translations = vec![];
+    for text in texts {
+        translations.push(translate_text(translator, task, text, usage).await?);
+    }
+    Ok(translations)
+}
+
+/// 按 `config.batch_size` 分批翻译，每批附带前文的原文与译文作为参考。空白片段不翻译
+pub async fn translate_segments(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    segments: &[String],
+    config: &FormatConfig,
+    usage: &mut Option<Usage>,
+) -> Result<Vec<String>> {
+    let mut translations: Vec<String> = segments.to_vec();
+    let pending: Vec<usize> = (0..segments.len())
+        .filter(|&i| !segments[i].trim().is_empty())
+        .collect();
+
+    for (batch_index, batch) in pending.chunks(config.batch_size.max(1)).enumerate() {
+        let done = batch_index * config.batch_size.max(1);
+        let mut batch_task = task.clone();
+        batch_task.references.extend(
+            pending[done.saturating_sub(config.context)..done]
+                .iter()
+                .map(|&i| TranslatedItem {
+                    source: segments[i].clone(),
+                    target: translations[i].clone(),
+                }),
+        );
+
+        let texts: Vec<&str> = batch.iter().map(|&i| segments[i].as_str()).collect();
+        let translated = translate_batch(translator, &batch_task, &texts, usage).await?;
+        for (&i, text) in batch.iter().zip(translated) {
+            translations[i] = text;
+        }
+    }
+
+    Ok(translations)
+}
+
+/// 翻译整个文件，`task.content` 以外的字段用于每次请求
+pub async fn translate_document(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    document: &dyn Document,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let mut usage = None;
+    let translations =
+        translate_segments(translator, task, &document.segments(), config, &mut usage).await?;
+
+    Ok(TranslateResult {
+        content: Some(document.render(&translations, config)),
+        usage,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_mask_markup() {
+    let markup = Regex::new(r"</?[a-z]+>").unwrap();
+    let masked = mask_markup("<i>Hello</i> world", &markup);
+    assert_eq!(masked.text, "⟦0⟧Hello⟦1⟧ world");
+
+    assert_eq!(
+        restore_markup("⟦0⟧你好⟦1⟧ 世界", &masked.placeholders),
+        "<i>你好</i> 世界"
+    );
+    // 丢失的标记追加在末尾
+    assert_eq!(
+        restore_markup("⟦0⟧你好世界", &masked.placeholders),
+        "<i>你好世界</i>"
+    );
+}
+
+#[tokio::test]
+async fn test_translate_segments() -> Result<()> {
+    let translator = MockTranslator::new("> ");
+    let segments: Vec<String> = ["a", "", "b", "c"].iter().map(|s| s.to_string()).collect();
+    let config = FormatConfig {
+        batch_size: 1,
+        ..Default::default()
+    };
+
+    let mut usage = None;
+    let translations =
+        translate_segments(&translator, &task(""), &segments, &config, &mut usage).await?;
+    assert_eq!(translations, vec!["> a", "", "> b", "> c"]);
+    assert_eq!(translator.calls(), 3);
+    assert_eq!(usage.map(|u| u.characters), Some(3));
+
+    // 合并为一次请求，分隔符两侧的空白被去除
+    let translator = MockTranslator::new("");
+    let translations = translate_segments(
+        &translator,
+        &task(""),
+        &segments,
+        &FormatConfig::default(),
+        &mut None,
+    )
+    .await?;
+    assert_eq!(translations, vec!["a", "", "b", "c"]);
+    assert_eq!(translator.calls(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,440 @@
+use super::{restore_markup, translate_document, Document, FormatConfig};
+use crate::placeholder::{mask, Masked};
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask};
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static KEYWORD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^(msgctxt|msgid_plural|msgid|msgstr(?:\[(\d+)\])?)\s+(".*")\s*$"#).unwrap()
+});
+
+static NPLURALS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"nplurals\s*=\s*(\d+)").unwrap());
+
+/// 一个条目，`msgstr` 按复数形式排列
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoEntry {
+    pub context: Option<String>,
+    pub msgid: String,
+    pub msgid_plural: Option<String>,
+    pub msgstr: Vec<String>,
+    /// `#,` 中的标记，如 `fuzzy`、`c-format`
+    pub flags: Vec<String>,
+    /// 第一行 `msgstr` 之前的内容，原样保留
+    head: Vec<String>,
+    lines: Vec<String>,
+}
+
+impl PoEntry {
+    pub fn is_fuzzy(&self) -> bool {
+        self.flags.iter().any(|f| f == "fuzzy")
+    }
+
+    /// 已有不是 fuzzy 的译文
+    pub fn is_translated(&self) -> bool {
+        !self.is_fuzzy() && self.msgstr.iter().any(|s| !s.is_empty())
+    }
+
+    pub fn is_header(&self) -> bool {
+        self.msgid.is_empty() && self.context.is_none()
+    }
+
+    /// `fuzzy` 为 `false` 时一并去掉 `#|` 开头的旧原文
+    fn head_with_fuzzy(&self, fuzzy: bool) -> Vec<String> {
+        let mut flags: Vec<&str> = self
+            .flags
+            .iter()
+            .map(String::as_str)
+            .filter(|f| *f != "fuzzy")
+            .collect();
+        if fuzzy {
+            flags.insert(0, "fuzzy");
+        }
+        let flags_line = (!flags.is_empty()).then(|| format!("#, {}", flags.join(", ")));
+
+        let mut head = vec![];
+        let mut flags_written = false;
+        for line in &self.head {
+            if line.starts_with("#,") {
+                if let Some(flags_line) = flags_line.as_ref().filter(|_| !flags_written) {
+                    head.push(flags_line.clone());
+                }
+                flags_written = true;
+            } else if !line.starts_with('#') && !flags_written {
+                if let Some(flags_line) = &flags_line {
+                    head.push(flags_line.clone());
+                }
+                flags_written = true;
+                head.push(line.clone());
+            } else if fuzzy || !line.starts_with("#|") {
+                head.push(line.clone());
+            }
+        }
+        head
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Block {
+    /// 废弃条目等无法解析的块，原样保留
+    Raw(String),
+    Entry(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PoFile {
+    blocks: Vec<Block>,
+    pub entries: Vec<PoEntry>,
+    /// 文件头 `Plural-Forms` 中的复数形式数量
+    pub nplurals: Option<usize>,
+    crlf: bool,
+    /// 每个片段对应的条目，以及是否为 `msgid_plural`
+    slots: Vec<(usize, bool)>,
+    masked: Vec<Masked>,
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value);
+
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some(c) => result.push(c),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+fn quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// 多行文本按 gettext 的习惯以空字符串开头、每行一个字符串
+fn keyword_lines(keyword: &str, value: &str) -> Vec<String> {
+    let lines: Vec<&str> = value.split_inclusive('\n').collect();
+    if lines.len() <= 1 {
+        return vec![format!("{} {}", keyword, quote(value))];
+    }
+
+    let mut result = vec![format!("{} \"\"", keyword)];
+    result.extend(lines.iter().map(|line| quote(line)));
+    result
+}
+
+fn parse_entry(block: &[&str]) -> Option<PoEntry> {
+    let mut entry = PoEntry {
+        lines: block.iter().map(|s| s.to_string()).collect(),
+        ..Default::default()
+    };
+    let mut current: Option<(String, Option<usize>)> = None;
+    let mut has_msgid = false;
+
+    for line in block {
+        let line = line.trim_end();
+        if line.starts_with("#~") {
+            return None;
+        }
+        if let Some(flags) = line.strip_prefix("#,") {
+            entry.flags.extend(
+                flags
+                    .split(',')
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty()),
+            );
+        }
+        if line.starts_with('#') {
+            if current.is_none() {
+                entry.head.push(line.to_string());
+            }
+            continue;
+        }
+
+        let value = if let Some(caps) = KEYWORD.captures(line) {
+            let keyword = caps[1].split('[').next().unwrap_or("").to_string();
+            let index = caps.get(2).and_then(|m| m.as_str().parse().ok());
+            current = Some((keyword, index));
+            unquote(&caps[3])
+        } else if line.trim_start().starts_with('"') {
+            unquote(line)
+        } else {
+            return None;
+        };
+
+        let (keyword, index) = current.as_ref()?;
+        match keyword.as_str() {
+            "msgctxt" => entry
+                .context
+                .get_or_insert_with(String::new)
+                .push_str(&value),
+            "msgid" => {
+                has_msgid = true;
+                entry.msgid.push_str(&value)
+            }
+            "msgid_plural" => entry
+                .msgid_plural
+                .get_or_insert_with(String::new)
+                .push_str(&value),
+            _ => {
+                let index = index.unwrap_or(0);
+                if entry.msgstr.len() <= index {
+                    entry.msgstr.resize(index + 1, String::new());
+                }
+                entry.msgstr[index].push_str(&value);
+            }
+        }
+        if !keyword.starts_with("msgstr") {
+            entry.head.push(line.to_string());
+        }
+    }
+
+    has_msgid.then_some(entry)
+}
+
+impl PoFile {
+    /// 文件头与没有 `msgid` 的块不翻译，`#~` 废弃条目原样保留
+    pub fn parse(content: &str) -> Result<Self> {
+        let crlf = content.contains("\r\n");
+        let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+
+        let mut file = PoFile {
+            crlf,
+            ..Default::default()
+        };
+        let mut block: Vec<&str> = vec![];
+        for (number, line) in content.lines().chain([""]).enumerate() {
+            if !line.trim().is_empty() {
+                block.push(line);
+                continue;
+            }
+            if block.is_empty() {
+                continue;
+            }
+
+            match parse_entry(&block) {
+                Some(entry) => {
+                    if entry.is_header() {
+                        file.nplurals = entry
+                            .msgstr
+                            .first()
+                            .and_then(|header| NPLURALS.captures(header))
+                            .and_then(|caps| caps[1].parse().ok());
+                    }
+                    file.blocks.push(Block::Entry(file.entries.len()));
+                    file.entries.push(entry);
+                }
+                None if block.iter().all(|line| line.starts_with('#')) => {
+                    file.blocks.push(Block::Raw(block.join("\n")))
+                }
+                None => bail!("invalid po entry before line {}", number + 1),
+            }
+            block.clear();
+        }
+
+        for (i, entry) in file.entries.iter().enumerate() {
+            if entry.is_header() || entry.is_translated() {
+                continue;
+            }
+            file.slots.push((i, false));
+            file.masked.push(mask(&entry.msgid));
+            if let Some(plural) = &entry.msgid_plural {
+                file.slots.push((i, true));
+                file.masked.push(mask(plural));
+            }
+        }
+
+        Ok(file)
+    }
+
+    fn msgstr(&self, entry: &PoEntry, singular: &str, plural: Option<&str>) -> Vec<String> {
+        let Some(plural) = plural else {
+            return keyword_lines("msgstr", singular);
+        };
+
+        let count = self.nplurals.unwrap_or(entry.msgstr.len().max(2)).max(1);
+        (0..count)
+            .flat_map(|n| {
+                let text = if n == 0 && count > 1 {
+                    singular
+                } else {
+                    plural
+                };
+                keyword_lines(&format!("msgstr[{}]", n), text)
+            })
+            .collect()
+    }
+}
+
+impl Document for PoFile {
+    fn segments(&self) -> Vec<String> {
+        self.masked.iter().map(|m| m.text.clone()).collect()
+    }
+
+    /// 填写的条目按 `config.mark_fuzzy` 设置 fuzzy 标记
+    fn render(&self, translations: &[String], config: &FormatConfig) -> String {
+        let mut filled: Vec<(Option<String>, Option<String>)> =
+            vec![(None, None); self.entries.len()];
+        for (slot, (&(entry, plural), masked)) in self.slots.iter().zip(&self.masked).enumerate() {
+            let Some(translation) = translations.get(slot).filter(|t| !t.trim().is_empty()) else {
+                continue;
+            };
+            let text = restore_markup(translation, &masked.placeholders);
+            if plural {
+                filled[entry].1 = Some(text);
+            } else {
+                filled[entry].0 = Some(text);
+            }
+        }
+
+        let blocks: Vec<String> = self
+            .blocks
+            .iter()
+            .map(|block| {
+                let index = match block {
+                    Block::Raw(raw) => return raw.clone(),
+                    Block::Entry(index) => *index,
+                };
+                let entry = &self.entries[index];
+                let (Some(singular), plural) = &filled[index] else {
+                    return entry.lines.join("\n");
+                };
+                if entry.msgid_plural.is_some() && plural.is_none() {
+                    return entry.lines.join("\n");
+                }
+
+                let mut lines = entry.head_with_fuzzy(config.mark_fuzzy);
+                lines.extend(self.msgstr(entry, singular, plural.as_deref()));
+                lines.join("\n")
+            })
+            .collect();
+
+        let mut po = blocks.join("\n\n") + "\n";
+        if self.crlf {
+            po = po.replace('\n', "\r\n");
+        }
+        po
+    }
+}
+
+/// 填写 `task.content` 中 PO/POT 文件的空译文，fuzzy 条目视为未翻译
+pub async fn translate(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = PoFile::parse(&task.content)?;
+    translate_document(translator, task, &file, config).await
+}
+
+#[cfg(test)]
+const SAMPLE: &str = r#"msgid ""
+msgstr ""
+"Language: zh_CN\n"
+"Plural-Forms: nplurals=1; plural=0;\n"
+
+#: src/main.c:10
+#, c-format
+msgid "Hello, %s!"
+msgstr ""
+
+msgid "One file"
+msgid_plural "%d files"
+msgstr[0] ""
+msgstr[1] ""
+
+#, fuzzy
+#| msgid "Open"
+msgid "Open file"
+msgstr "打开"
+
+msgctxt "menu"
+msgid "Quit"
+msgstr "退出"
+
+#~ msgid "Old"
+#~ msgstr "旧"
+"#;
+
+#[test]
+fn test_parse_po() -> Result<()> {
+    let file = PoFile::parse(SAMPLE)?;
+    assert_eq!(file.nplurals, Some(1));
+    assert_eq!(file.entries.len(), 5);
+    assert_eq!(file.entries[1].flags, vec!["c-format"]);
+    assert_eq!(file.entries[2].msgid_plural.as_deref(), Some("%d files"));
+    assert!(file.entries[3].is_fuzzy());
+    assert_eq!(file.entries[4].context.as_deref(), Some("menu"));
+    assert!(file.entries[4].is_translated());
+    assert_eq!(
+        file.segments(),
+        vec!["Hello, ⟦0⟧!", "One file", "⟦0⟧ files", "Open file"]
+    );
+
+    // 原样写回
+    assert_eq!(file.render(&[], &FormatConfig::default()), SAMPLE);
+
+    assert!(PoFile::parse("msgid \"a\"\nnot po\n").is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_po() -> Result<()> {
+    let translator = MockTranslator::new("> ");
+    let config = FormatConfig {
+        batch_size: 1,
+        ..Default::default()
+    };
+    let content = translate(&translator, &task(SAMPLE), &config)
+        .await?
+        .content
+        .unwrap();
+
+    assert!(content.contains("#, c-format\nmsgid \"Hello, %s!\"\nmsgstr \"> Hello, %s!\"\n"));
+    // 目标语言只有一种复数形式时使用 `msgid_plural` 的译文
+    assert!(content.contains("msgid_plural \"%d files\"\nmsgstr[0] \"> %d files\"\n\n"));
+    assert!(content.contains("\nmsgid \"Open file\"\nmsgstr \"> Open file\"\n"));
+    assert!(!content.contains("#| msgid"));
+    assert!(content.contains("msgstr \"退出\"\n\n#~ msgid \"Old\"\n"));
+    assert_eq!(translator.calls(), 4);
+
+    let config = FormatConfig {
+        batch_size: 1,
+        mark_fuzzy: true,
+        ..Default::default()
+    };
+    let content = translate(
+        &translator,
+        &task("msgid \"Line\\nBreak\"\nmsgstr \"\"\n"),
+        &config,
+    )
+    .await?
+    .content
+    .unwrap();
+    assert_eq!(
+        content,
+        "#, fuzzy\nmsgid \"Line\\nBreak\"\nmsgstr \"\"\n\"> Line\\n\"\n\"Break\"\n"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use super::{mask_markup, restore_markup, translate_document, Document, FormatConfig};
+use crate::placeholder::Masked;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask};
+use anyhow::Result;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// MessageFormat 的 `{0}`、`{0,number,#}` 与 `String.format` 的 `%s`、`%1$d`
+static MARKUP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{[^{}]*\}|%(?:\d+\$)?[-#+0,(]*\d*(?:\.\d+)?[sdfxXeEgGcb%]").unwrap()
+});
+
+/// 一个键值对，可能由多行续行组成
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    pub key: String,
+    pub value: String,
+    start: usize,
+    end: usize,
+    /// 值之前的内容，包括键与分隔符
+    head: String,
+}
+
+/// Java `.properties` 文件，只翻译值，注释与空行原样保留
+#[derive(Debug, Clone, Default)]
+pub struct PropertiesFile {
+    lines: Vec<String>,
+    pub properties: Vec<Property>,
+    /// 原文件以 `\uXXXX` 表示非 ASCII 字符，写回时同样转义
+    ascii: bool,
+    crlf: bool,
+    trailing_newline: bool,
+    masked: Vec<Masked>,
+}
+
+/// 行尾有奇数个反斜杠时下一行为续行
+fn continues(line: &str) -> bool {
+    (line.len() - line.trim_end_matches('\\').len()) % 2 == 1
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('f') => result.push('\u{c}'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let Ok(unit) = u16::from_str_radix(&hex, 16) else {
+                    result.push_str(&hex);
+                    continue;
+                };
+                // 代理对
+                let mut units = vec![unit];
+                if (0xD800..0xDC00).contains(&unit) && chars.peek() == Some(&'\\') {
+                    let rest: String = chars.clone().take(6).collect();
+                    if let Some(low) = rest
+                        .strip_prefix("\\u")
+                        .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+                    {
+                        units.push(low);
+                        chars.by_ref().take(6).for_each(drop);
+                    }
+                }
+                result.extend(char::decode_utf16(units).map(|c| c.unwrap_or('\u{fffd}')));
+            }
+            Some(c) => result.push(c),
+            None => {}
+        }
+    }
+    result
+}
+
+fn escape(value: &str, ascii: bool) -> String {
+    let mut result = String::with_capacity(value.len());
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\u{c}' => result.push_str("\\f"),
+            ' ' if i == 0 => result.push_str("\\ "),
+            c if ascii && !c.is_ascii() => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    result.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// 键的结束位置与值的开始位置
+fn split_key(line: &str) -> (usize, usize) {
+    let bytes = line.as_bytes();
+    let start = line.len() - line.trim_start().len();
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'=' | b':' | b' ' | b'\t' | b'\x0c' => break,
+            _ => i += 1,
+        }
+    }
+    let key_end = i.min(line.len());
+
+    let skip_whitespace = |mut i: usize| {
+        while i < bytes.len() && matches!(bytes[i], b' ' | b'\t' | b'\x0c') {
+            i += 1;
+        }
+        i
+    };
+    let mut value_start = skip_whitespace(key_end);
+    if value_start < bytes.len() && matches!(bytes[value_start], b'=' | b':') {
+        value_start = skip_whitespace(value_start + 1);
+    }
+
+    (key_end, value_start)
+}
+
+impl PropertiesFile {
+    pub fn parse(content: &str) -> Result<Self> {
+        let crlf = content.contains("\r\n");
+        let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+        let mut file = PropertiesFile {
+            lines: content.lines().map(str::to_string).collect(),
+            ascii: content.is_ascii() && content.contains("\\u"),
+            crlf,
+            trailing_newline: content.ends_with('\n'),
+            ..Default::default()
+        };
+
+        let mut number = 0;
+        while number < file.lines.len() {
+            let start = number;
+            let trimmed = file.lines[number].trim_start();
+            number += 1;
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+                continue;
+            }
+
+            let mut logical = file.lines[start].trim_start().to_string();
+            while continues(&logical) && number < file.lines.len() {
+                logical.pop();
+                logical.push_str(file.lines[number].trim_start());
+                number += 1;
+            }
+            if continues(&logical) {
+                logical.pop();
+            }
+
+            let (key_end, value_start) = split_key(&logical);
+            let indent = &file.lines[start][..file.lines[start].len() - trimmed.len()];
+            file.properties.push(Property {
+                key: unescape(&logical[..key_end]),
+                value: unescape(&logical[value_start..]),
+                start,
+                end: number,
+                head: format!("{}{}", indent, &logical[..value_start]),
+            });
+        }
+
+        file.masked = file
+            .properties
+            .iter()
+            .map(|p| mask_markup(&p.value, &MARKUP))
+            .collect();
+
+        Ok(file)
+    }
+}
+
+impl Document for PropertiesFile {
+    fn segments(&self) -> Vec<String> {
+        self.masked.iter().map(|m| m.text.clone()).collect()
+    }
+
+    /// 翻译过的值写为一行
+    fn render(&self, translations: &[String], _config: &FormatConfig) -> String {
+        let mut lines: Vec<String> = vec![];
+        let mut number = 0;
+        for (i, property) in self.properties.iter().enumerate() {
+            let Some(translation) = translations.get(i) else {
+                continue;
+            };
+            if property.value.trim().is_empty() {
+                continue;
+            }
+            let value = restore_markup(translation, &self.masked[i].placeholders);
+            lines.extend_from_slice(&self.lines[number..property.start]);
+            lines.push(format!("{}{}", property.head, escape(&value, self.ascii)));
+            number = property.end;
+        }
+        lines.extend_from_slice(&self.lines[number..]);
+
+        let mut properties = lines.join("\n");
+        if self.trailing_newline {
+            properties.push('\n');
+        }
+        if self.crlf {
+            properties = properties.replace('\n', "\r\n");
+        }
+        properties
+    }
+}
+
+/// 翻译 `task.content` 中 `.properties` 文件的值
+pub async fn translate(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = PropertiesFile::parse(&task.content)?;
+    translate_document(translator, task, &file, config).await
+}
+
+#[cfg(test)]
+const SAMPLE: &str = "# Messages
+! legacy comment
+
+greeting = Hello, {0}!
+farewell:Goodbye
+title Caf\\u00e9 menu
+long.text = First part \\
+    second part
+path\\=key = a\\\\b
+empty =
+";
+
+#[test]
+fn test_parse_properties() -> Result<()> {
+    let file = PropertiesFile::parse(SAMPLE)?;
+    let pairs: Vec<(&str, &str)> = file
+        .properties
+        .iter()
+        .map(|p| (p.key.as_str(), p.value.as_str()))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("greeting", "Hello, {0}!"),
+            ("farewell", "Goodbye"),
+            ("title", "Café menu"),
+            ("long.text", "First part second part"),
+            ("path=key", "a\\b"),
+            ("empty", ""),
+        ]
+    );
+    assert_eq!(file.segments()[0], "Hello, ⟦0⟧!");
+
+    // 原样写回
+    assert_eq!(file.render(&[], &FormatConfig::default()), SAMPLE);
+
+    assert_eq!(unescape("\\ud83d\\ude00"), "😀");
+    assert_eq!(escape("😀", true), "\\ud83d\\ude00");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_properties() -> Result<()> {
+    let translator = MockTranslator::new("你好 ");
+    let config = FormatConfig {
+        batch_size: 1,
+        ..Default::default()
+    };
+    let content = translate(&translator, &task(SAMPLE), &config)
+        .await?
+        .content
+        .unwrap();
+
+    assert!(content.starts_with("# Messages\n! legacy comment\n\n"));
+    // 原文件只含 ASCII，非 ASCII 字符写为 `\uXXXX`
+    assert!(content.contains("greeting = \\u4f60\\u597d Hello, {0}!\n"));
+    assert!(content.contains("farewell:\\u4f60\\u597d Goodbye\n"));
+    assert!(content.contains("long.text = \\u4f60\\u597d First part second part\npath\\=key"));
+    assert!(content.ends_with("empty =\n"));
+    assert_eq!(translator.calls(), 5);
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use super::{mask_markup, restore_markup, translate_document, Document, FormatConfig};
+use crate::placeholder::Masked;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask};
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static TIMING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*\d{1,2}:\d{2}:\d{2}[,.]\d{1,3}\s*-->\s*\d{1,2}:\d{2}:\d{2}[,.]\d{1,3}")
+        .unwrap()
+});
+
+/// 字幕中的 HTML 标签与 `{\an8}` 等样式标记
+static MARKUP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"</?[A-Za-z][^>]*>|\{\\[^}]*\}").unwrap());
+
+/// 一条字幕
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrtCue {
+    /// 序号，原样保留
+    pub index: String,
+    /// 时间轴与其后的位置信息
+    pub timing: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SrtFile {
+    pub cues: Vec<SrtCue>,
+    crlf: bool,
+    masked: Vec<Masked>,
+}
+
+impl SrtFile {
+    /// 没有时间轴的块视为上一条字幕的续行
+    pub fn parse(content: &str) -> Result<Self> {
+        let crlf = content.contains("\r\n");
+        let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+
+        let mut cues: Vec<SrtCue> = vec![];
+        let mut block: Vec<&str> = vec![];
+        for (number, line) in content.lines().chain([""]).enumerate() {
+            if !line.trim().is_empty() {
+                block.push(line);
+                continue;
+            }
+            if block.is_empty() {
+                continue;
+            }
+
+            let timing_line = block.iter().position(|line| TIMING.is_match(line));
+            match timing_line {
+                Some(position) if position <= 1 => cues.push(SrtCue {
+                    index: if position == 1 {
+                        block[0].trim().to_string()
+                    } else {
+                        (cues.len() + 1).to_string()
+                    },
+                    timing: block[position].trim().to_string(),
+                    text: block[position + 1..].join("\n"),
+                }),
+                _ => match cues.last_mut() {
+                    Some(cue) => {
+                        if !cue.text.is_empty() {
+                            cue.text.push('\n');
+                        }
+                        cue.text.push_str(&block.join("\n"));
+                    }
+                    None => bail!("invalid srt cue before line {}", number + 1),
+                },
+            }
+            block.clear();
+        }
+
+        let masked = cues
+            .iter()
+            .map(|cue| mask_markup(&cue.text, &MARKUP))
+            .collect();
+
+        Ok(SrtFile { cues, crlf, masked })
+    }
+}
+
+impl Document for SrtFile {
+    fn segments(&self) -> Vec<String> {
+        self.masked.iter().map(|m| m.text.clone()).collect()
+    }
+
+    /// 双语字幕中原文在上，译文在下
+    fn render(&self, translations: &[String], config: &FormatConfig) -> String {
+        let mut srt = String::new();
+
+        for (i, cue) in self.cues.iter().enumerate() {
+            let text = match translations.get(i) {
+                Some(translation) => restore_markup(translation, &self.masked[i].placeholders),
+                None => cue.text.clone(),
+            };
+
+            srt.push_str(&cue.index);
+            srt.push('\n');
+            srt.push_str(&cue.timing);
+            srt.push('\n');
+            if config.bilingual && text != cue.text {
+                srt.push_str(&cue.text);
+                srt.push('\n');
+            }
+            srt.push_str(&text);
+            srt.push_str("\n\n");
+        }
+
+        if self.crlf {
+            srt = srt.replace('\n', "\r\n");
+        }
+        srt
+    }
+}
+
+/// 翻译 `task.content` 中的 SRT 字幕，序号与时间轴保持不变
+pub async fn translate(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = SrtFile::parse(&task.content)?;
+    translate_document(translator, task, &file, config).await
+}
+
+#[test]
+fn test_parse_srt() -> Result<()> {
+    let srt = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,500\r\n<i>Hello</i>\r\nworld\r\n\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000 X1:10\r\n{\\an8}Top\r\n\r\nstray line\r\n";
+    let file = SrtFile::parse(srt)?;
+    assert_eq!(
+        file.cues,
+        vec![
+            SrtCue {
+                index: "1".to_string(),
+                timing: "00:00:01,000 --> 00:00:02,500".to_string(),
+                text: "<i>Hello</i>\nworld".to_string(),
+            },
+            SrtCue {
+                index: "2".to_string(),
+                timing: "00:00:03,000 --> 00:00:04,000 X1:10".to_string(),
+                text: "{\\an8}Top\nstray line".to_string(),
+            },
+        ]
+    );
+    assert_eq!(
+        file.segments(),
+        vec!["⟦0⟧Hello⟦1⟧\nworld", "⟦0⟧Top\nstray line"]
+    );
+
+    // 原样写回
+    assert_eq!(
+        file.render(&[], &FormatConfig::default()),
+        "1\r\n00:00:01,000 --> 00:00:02,500\r\n<i>Hello</i>\r\nworld\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000 X1:10\r\n{\\an8}Top\r\nstray line\r\n\r\n"
+    );
+
+    assert!(SrtFile::parse("not a subtitle").is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_srt() -> Result<()> {
+    let translator = MockTranslator::new("> ");
+    let config = FormatConfig {
+        batch_size: 1,
+        bilingual: true,
+        ..Default::default()
+    };
+    let result = translate(
+        &translator,
+        &task("1\n00:00:01,000 --> 00:00:02,000\n<b>Hi</b>\n"),
+        &config,
+    )
+    .await?;
+    assert_eq!(
+        result.content.as_deref(),
+        Some("1\n00:00:01,000 --> 00:00:02,000\n<b>Hi</b>\n> <b>Hi</b>\n\n")
+    );
+
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use super::escape_xml;
+use crate::ffi::LIB_VERSION;
+use crate::html::decode_entities;
+use crate::TranslatedItem;
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+static HEADER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<header\b([^>]*)>").unwrap());
+
+static TU: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<tu\b[^>]*>(.*?)</tu>").unwrap());
+
+static TUV: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<tuv\b([^>]*)>.*?<seg\b[^>]*>(.*?)</seg>").unwrap());
+
+static LANG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\b(?:xml:lang|lang|srclang)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap()
+});
+
+/// `<bpt>`、`<ept>`、`<ph>`、`<it>` 中的原生代码与其余行内标签，读取时去除
+static INLINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<bpt\b.*?</bpt>|<ept\b.*?</ept>|<ph\b.*?</ph>|<it\b.*?</it>|<[^<>]+>").unwrap()
+});
+
+/// 一个翻译单元中各语言的文本
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TmxUnit {
+    pub variants: Vec<(String, String)>,
+}
+
+impl TmxUnit {
+    pub fn text(&self, language: &str) -> Option<&str> {
+        self.variants
+            .iter()
+            .find(|(lang, _)| lang.eq_ignore_ascii_case(language))
+            .or_else(|| {
+                self.variants
+                    .iter()
+                    .find(|(lang, _)| primary(lang).eq_ignore_ascii_case(primary(language)))
+            })
+            .map(|(_, text)| text.as_str())
+    }
+}
+
+/// TMX 1.4 翻译记忆
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Tmx {
+    /// `header` 中的 `srclang`，可能为 `*all*`
+    pub source_language: Option<String>,
+    pub units: Vec<TmxUnit>,
+}
+
+fn primary(language: &str) -> &str {
+    language.split(['-', '_']).next().unwrap_or(language)
+}
+
+fn language(attributes: &str) -> Option<String> {
+    LANG.captures(attributes)
+        .and_then(|caps| caps.get(1).or(caps.get(2)))
+        .map(|m| m.as_str().to_string())
+}
+
+impl Tmx {
+    pub fn parse(content: &str) -> Result<Self> {
+        if !content.contains("<tmx") {
+            bail!("missing tmx root element");
+        }
+
+        let units = TU
+            .captures_iter(content)
+            .map(|tu| TmxUnit {
+                variants: TUV
+                    .captures_iter(&tu[1])
+                    .filter_map(|tuv| {
+                        let text = decode_entities(&INLINE.replace_all(&tuv[2], ""));
+                        Some((language(&tuv[1])?, text))
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Tmx {
+            source_language: HEADER.captures(content).and_then(|caps| language(&caps[1])),
+            units,
+        })
+    }
+
+    pub fn from_items(source: &str, target: &str, items: &[TranslatedItem]) -> Self {
+        Tmx {
+            source_language: Some(source.to_string()),
+            units: items
+                .iter()
+                .map(|item| TmxUnit {
+                    variants: vec![
+                        (source.to_string(), item.source.clone()),
+                        (target.to_string(), item.target.clone()),
+                    ],
+                })
+                .collect(),
+        }
+    }
+
+    /// 取出 `source` 到 `target` 的译文对，语言先精确匹配，再按主语言（如 `en`）匹配
+    pub fn items(&self, source: &str, target: &str) -> Vec<TranslatedItem> {
+        self.units
+            .iter()
+            .filter_map(|unit| {
+                let source = unit.text(source)?;
+                let target = unit.text(target)?;
+                (!source.trim().is_empty() && !target.trim().is_empty()).then(|| TranslatedItem {
+                    source: source.to_string(),
+                    target: target.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut tmx =
+            String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tmx version=\"1.4\">\n");
+        tmx.push_str(&format!(
+            "  <header creationtool=\"xtranslator\" creationtoolversion=\"{}\" segtype=\"sentence\" o-tmf=\"xtranslator\" adminlang=\"en\" srclang=\"{}\" datatype=\"plaintext\"/>\n",
+            LIB_VERSION,
+            escape_xml(self.source_language.as_deref().unwrap_or("*all*"))
+        ));
+        tmx.push_str("  <body>\n");
+        for unit in &self.units {
+            tmx.push_str("    <tu>\n");
+            for (language, text) in &unit.variants {
+                tmx.push_str(&format!(
+                    "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n",
+                    escape_xml(language),
+                    escape_xml(text)
+                ));
+            }
+            tmx.push_str("    </tu>\n");
+        }
+        tmx.push_str("  </body>\n</tmx>\n");
+
+        tmx
+    }
+}
+
+/// 从 TMX 文件读取译文对，可直接作为 `task.references` 使用
+pub fn load(path: impl AsRef<Path>, source: &str, target: &str) -> Result<Vec<TranslatedItem>> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(Tmx::parse(content.trim_start_matches('\u{feff}'))?.items(source, target))
+}
+
+pub fn save(
+    path: impl AsRef<Path>,
+    source: &str,
+    target: &str,
+    items: &[TranslatedItem],
+) -> Result<()> {
+    std::fs::write(path, Tmx::from_items(source, target, items).to_xml())?;
+    Ok(())
+}
+
+#[test]
+fn test_tmx() -> Result<()> {
+    let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tmx version="1.4">
+  <header creationtool="OmegaT" segtype="sentence" o-tmf="OmegaT TMX" adminlang="EN-US" srclang="EN-US" datatype="plaintext"/>
+  <body>
+    <tu>
+      <tuv lang="EN-US"><seg>Save <bpt i="1">&lt;b&gt;</bpt>all<ept i="1">&lt;/b&gt;</ept> &amp; exit</seg></tuv>
+      <tuv xml:lang="ZH-CN">
+        <prop type="x-note">ignored</prop>
+        <seg>全部保存并退出</seg>
+      </tuv>
+    </tu>
+    <tu>
+      <tuv xml:lang="en-US"><seg>Only source</seg></tuv>
+    </tu>
+  </body>
+</tmx>"#;
+    let tmx = Tmx::parse(content)?;
+    assert_eq!(tmx.source_language.as_deref(), Some("EN-US"));
+    assert_eq!(tmx.units.len(), 2);
+    let pairs = |items: Vec<TranslatedItem>| -> Vec<(String, String)> {
+        items.into_iter().map(|i| (i.source, i.target)).collect()
+    };
+    assert_eq!(
+        pairs(tmx.items("en", "zh-CN")),
+        vec![("Save all & exit".to_string(), "全部保存并退出".to_string())]
+    );
+
+    // 写出后能原样读回
+    let items = tmx.items("en", "zh");
+    let exported = Tmx::from_items("en", "zh", &items).to_xml();
+    assert!(exported.contains("<seg>Save all &amp; exit</seg>"));
+    assert_eq!(
+        pairs(Tmx::parse(&exported)?.items("en", "zh")),
+        pairs(items)
+    );
+
+    assert!(Tmx::parse("<xliff/>").is_err());
+
+    Ok(())
+}
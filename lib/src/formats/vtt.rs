@@ -0,0 +1,200 @@
+use super::{mask_markup, restore_markup, translate_document, Document, FormatConfig};
+use crate::placeholder::Masked;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask};
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static TIMING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:\d+:)?\d{2}:\d{2}\.\d{3}\s+-->\s+(?:\d+:)?\d{2}:\d{2}\.\d{3}").unwrap()
+});
+
+/// `<v Name>`、`<c.class>`、`<i>` 等标签与 `<00:01.000>` 时间戳
+static MARKUP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"</?[A-Za-z0-9][^<>]*>").unwrap());
+
+/// 一条字幕
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VttCue {
+    pub id: Option<String>,
+    /// 时间轴与其后的 `align:start` 等设置
+    pub timing: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+enum Block {
+    /// 文件头、`NOTE`、`STYLE` 与 `REGION` 块，原样保留
+    Raw(String),
+    Cue(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct VttFile {
+    blocks: Vec<Block>,
+    pub cues: Vec<VttCue>,
+    crlf: bool,
+    masked: Vec<Masked>,
+}
+
+impl VttFile {
+    pub fn parse(content: &str) -> Result<Self> {
+        let crlf = content.contains("\r\n");
+        let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+        if !content.starts_with("WEBVTT") {
+            bail!("missing WEBVTT header");
+        }
+
+        let mut file = VttFile {
+            crlf,
+            ..Default::default()
+        };
+        let mut block: Vec<&str> = vec![];
+        for line in content.lines().chain([""]) {
+            if !line.trim().is_empty() {
+                block.push(line);
+                continue;
+            }
+            if block.is_empty() {
+                continue;
+            }
+
+            let timing_line = block.iter().position(|line| TIMING.is_match(line));
+            match timing_line {
+                Some(position) if position <= 1 && !file.blocks.is_empty() => {
+                    file.blocks.push(Block::Cue(file.cues.len()));
+                    file.cues.push(VttCue {
+                        id: (position == 1).then(|| block[0].to_string()),
+                        timing: block[position].to_string(),
+                        text: block[position + 1..].join("\n"),
+                    });
+                }
+                _ => file.blocks.push(Block::Raw(block.join("\n"))),
+            }
+            block.clear();
+        }
+
+        file.masked = file
+            .cues
+            .iter()
+            .map(|cue| mask_markup(&cue.text, &MARKUP))
+            .collect();
+
+        Ok(file)
+    }
+}
+
+impl Document for VttFile {
+    fn segments(&self) -> Vec<String> {
+        self.masked.iter().map(|m| m.text.clone()).collect()
+    }
+
+    /// 双语字幕中原文在上，译文在下
+    fn render(&self, translations: &[String], config: &FormatConfig) -> String {
+        let blocks: Vec<String> = self
+            .blocks
+            .iter()
+            .map(|block| {
+                let index = match block {
+                    Block::Raw(raw) => return raw.clone(),
+                    Block::Cue(index) => *index,
+                };
+                let cue = &self.cues[index];
+                let text = match translations.get(index) {
+                    Some(translation) => {
+                        restore_markup(translation, &self.masked[index].placeholders)
+                    }
+                    None => cue.text.clone(),
+                };
+
+                let mut lines: Vec<&str> = cue.id.iter().map(String::as_str).collect();
+                lines.push(&cue.timing);
+                if config.bilingual && text != cue.text {
+                    lines.push(&cue.text);
+                }
+                lines.push(&text);
+                lines.join("\n")
+            })
+            .collect();
+
+        let mut vtt = blocks.join("\n\n") + "\n";
+        if self.crlf {
+            vtt = vtt.replace('\n', "\r\n");
+        }
+        vtt
+    }
+}
+
+/// 翻译 `task.content` 中的 WebVTT 字幕，注释、样式与字幕设置保持不变
+pub async fn translate(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = VttFile::parse(&task.content)?;
+    translate_document(translator, task, &file, config).await
+}
+
+#[cfg(test)]
+const SAMPLE: &str = "WEBVTT - sample
+Kind: captions
+
+NOTE the translator
+keeps this
+
+STYLE
+::cue { color: yellow }
+
+intro
+00:01.000 --> 00:02.500 align:start position:10%
+<v Roger>Hello <i>there</i>
+
+01:00:03.000 --> 01:00:04.000
+Karaoke <00:03.500>words
+";
+
+#[test]
+fn test_parse_vtt() -> Result<()> {
+    let file = VttFile::parse(SAMPLE)?;
+    assert_eq!(
+        file.cues[0],
+        VttCue {
+            id: Some("intro".to_string()),
+            timing: "00:01.000 --> 00:02.500 align:start position:10%".to_string(),
+            text: "<v Roger>Hello <i>there</i>".to_string(),
+        }
+    );
+    assert_eq!(
+        file.segments(),
+        vec!["⟦0⟧Hello ⟦1⟧there⟦2⟧", "Karaoke ⟦0⟧words"]
+    );
+
+    // 原样写回
+    assert_eq!(file.render(&[], &FormatConfig::default()), SAMPLE);
+
+    assert!(VttFile::parse("1\n00:01.000 --> 00:02.000\nHi\n").is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_vtt() -> Result<()> {
+    let translator = MockTranslator::new("> ");
+    let config = FormatConfig {
+        batch_size: 1,
+        bilingual: true,
+        ..Default::default()
+    };
+    let content = translate(&translator, &task(SAMPLE), &config)
+        .await?
+        .content
+        .unwrap();
+
+    assert!(content.contains("NOTE the translator\nkeeps this\n\n"));
+    assert!(content.contains(
+        "intro\n00:01.000 --> 00:02.500 align:start position:10%\n<v Roger>Hello <i>there</i>\n> <v Roger>Hello <i>there</i>\n"
+    ));
+
+    Ok(())
+}
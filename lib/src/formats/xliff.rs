@@ -0,0 +1,448 @@
+use super::{escape_xml, mask_markup, restore_markup, translate_document, Document, FormatConfig};
+use crate::html::decode_entities;
+use crate::placeholder::Masked;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask};
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+static VERSION_2: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"urn:oasis:names:tc:xliff:document:2\.|<xliff\b[^>]*\bversion\s*=\s*["']2\."#)
+        .unwrap()
+});
+
+static TRANS_UNIT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<trans-unit\b([^>]*)>(.*?)</trans-unit>").unwrap());
+
+static UNIT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<unit\b([^>]*[^/])>(.*?)</unit>").unwrap());
+
+static SEGMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<segment\b([^>]*)>(.*?)</segment>").unwrap());
+
+static SOURCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<source\b[^>]*>(.*?)</source>").unwrap());
+
+static TARGET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<target\b([^>]*[^/])>(.*?)</target>|<target\b([^>]*)/>|<target>(.*?)</target>")
+        .unwrap()
+});
+
+static ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([A-Za-z_][\w:.\-]*)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// 行内标签：`<ph>`、`<bpt>`、`<ept>`、`<it>` 连同其中的原生代码整体保留，其余标签单独保留
+static MARKUP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?s)<ph\b[^>]*[^/]>.*?</ph>|<bpt\b[^>]*[^/]>.*?</bpt>|<ept\b[^>]*[^/]>.*?</ept>|<it\b[^>]*[^/]>.*?</it>|<[^<>]+>",
+    )
+    .unwrap()
+});
+
+/// 视为已批准的 XLIFF 1.2 译文状态
+const APPROVED_STATES: &[&str] = &["final", "signed-off"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XliffVersion {
+    V1_2,
+    V2_0,
+}
+
+/// 一个待翻译的单元，XLIFF 2.0 中对应一个 `<segment>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XliffUnit {
+    pub id: Option<String>,
+    /// 原文，保留行内标签与实体
+    pub source: String,
+    pub target: Option<String>,
+    /// 译文已批准或单元不需要翻译，写回时保持不变
+    pub locked: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Span {
+    /// `</source>` 之后的位置
+    source_end: usize,
+    /// `<source>` 所在行的缩进
+    indent: String,
+    /// 已有的 `<target>` 元素与其属性
+    target: Option<(Range<usize>, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct XliffFile {
+    content: String,
+    pub version: XliffVersion,
+    pub units: Vec<XliffUnit>,
+    /// 写回时补充到 `<file>`（1.2）或 `<xliff>`（2.0）上的目标语言，已声明时不覆盖
+    pub target_language: Option<String>,
+    spans: Vec<Span>,
+    masked: Vec<Masked>,
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    ATTRIBUTE
+        .captures_iter(attributes)
+        .find(|caps| &caps[1] == name)
+        .map(|caps| {
+            caps.get(2)
+                .or(caps.get(3))
+                .map(|m| m.as_str())
+                .unwrap_or("")
+                .to_string()
+        })
+}
+
+/// 设置属性值，不存在时追加
+fn set_attribute(attributes: &str, name: &str, value: &str) -> String {
+    match ATTRIBUTE
+        .captures_iter(attributes)
+        .find(|caps| &caps[1] == name)
+    {
+        Some(caps) => {
+            let range = caps.get(0).unwrap().range();
+            format!(
+                "{}{}=\"{}\"{}",
+                &attributes[..range.start],
+                name,
+                value,
+                &attributes[range.end..]
+            )
+        }
+        None => format!("{} {}=\"{}\"", attributes.trim_end(), name, value),
+    }
+}
+
+impl XliffFile {
+    pub fn parse(content: &str) -> Result<Self> {
+        if !content.contains("<xliff") {
+            bail!("missing xliff root element");
+        }
+
+        let mut file = XliffFile {
+            content: content.to_string(),
+            version: if VERSION_2.is_match(content) {
+                XliffVersion::V2_0
+            } else {
+                XliffVersion::V1_2
+            },
+            units: vec![],
+            target_language: None,
+            spans: vec![],
+            masked: vec![],
+        };
+
+        match file.version {
+            XliffVersion::V1_2 => {
+                for caps in TRANS_UNIT.captures_iter(content) {
+                    let attributes = &caps[1];
+                    let locked = attribute(attributes, "approved").as_deref() == Some("yes")
+                        || attribute(attributes, "translate").as_deref() == Some("no");
+                    file.push_segment(
+                        caps.get(2).unwrap().start(),
+                        &caps[2],
+                        attribute(attributes, "id"),
+                        locked,
+                    );
+                }
+            }
+            XliffVersion::V2_0 => {
+                for unit in UNIT.captures_iter(content) {
+                    let unit_id = attribute(&unit[1], "id");
+                    let unit_locked = attribute(&unit[1], "translate").as_deref() == Some("no");
+                    let offset = unit.get(2).unwrap().start();
+                    for segment in SEGMENT.captures_iter(&unit[2]) {
+                        let locked = unit_locked
+                            || attribute(&segment[1], "state").as_deref() == Some("final");
+                        let id = attribute(&segment[1], "id").or(unit_id.clone());
+                        file.push_segment(
+                            offset + segment.get(2).unwrap().start(),
+                            &segment[2],
+                            id,
+                            locked,
+                        );
+                    }
+                }
+            }
+        }
+
+        file.masked = file
+            .units
+            .iter()
+            .map(|unit| {
+                let mut masked = mask_markup(&unit.source, &MARKUP);
+                masked.text = decode_entities(&masked.text);
+                masked
+            })
+            .collect();
+
+        Ok(file)
+    }
+
+    /// `body` 为 `<trans-unit>` 或 `<segment>` 的内容，`offset` 为其在文件中的位置
+    fn push_segment(&mut self, offset: usize, body: &str, id: Option<String>, mut locked: bool) {
+        let Some(source) = SOURCE.captures(body) else {
+            return;
+        };
+        let source_start = offset + source.get(0).unwrap().start();
+        let line_start = self.content[..source_start]
+            .rfind('\n')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let indent = &self.content[line_start..source_start];
+
+        let target = TARGET.captures(body).map(|caps| {
+            let attributes = caps
+                .get(1)
+                .or(caps.get(3))
+                .map(|m| m.as_str())
+                .unwrap_or("");
+            let text = caps
+                .get(2)
+                .or(caps.get(4))
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_default();
+            let range = caps.get(0).unwrap().range();
+            (
+                offset + range.start..offset + range.end,
+                attributes.to_string(),
+                text,
+            )
+        });
+        if self.version == XliffVersion::V1_2 {
+            locked |= target
+                .as_ref()
+                .and_then(|(_, attributes, _)| attribute(attributes, "state"))
+                .is_some_and(|state| APPROVED_STATES.contains(&state.as_str()));
+        }
+
+        self.units.push(XliffUnit {
+            id,
+            source: source[1].to_string(),
+            target: target.as_ref().map(|(_, _, text)| text.clone()),
+            locked,
+        });
+        self.spans.push(Span {
+            source_end: offset + source.get(0).unwrap().end(),
+            indent: if indent.trim().is_empty() {
+                indent.to_string()
+            } else {
+                String::new()
+            },
+            target: target.map(|(range, attributes, _)| (range, attributes)),
+        });
+    }
+
+    fn target_element(&self, attributes: &str, text: &str) -> String {
+        let attributes = match self.version {
+            XliffVersion::V1_2 => set_attribute(attributes, "state", "translated"),
+            XliffVersion::V2_0 => attributes.to_string(),
+        };
+        format!("<target{}>{}</target>", attributes, text)
+    }
+
+    /// 在根元素上补充目标语言
+    fn with_target_language(&self, xliff: String) -> String {
+        let Some(language) = &self.target_language else {
+            return xliff;
+        };
+        let (tag, name) = match self.version {
+            XliffVersion::V1_2 => ("<file", "target-language"),
+            XliffVersion::V2_0 => ("<xliff", "trgLang"),
+        };
+        let Some(start) = xliff.find(tag) else {
+            return xliff;
+        };
+        let Some(end) = xliff[start..].find('>').map(|i| start + i) else {
+            return xliff;
+        };
+        let attributes = xliff[start + tag.len()..end].trim_end_matches('/');
+        if attribute(attributes, name).is_some() {
+            return xliff;
+        }
+
+        format!(
+            "{}{} {}=\"{}\"{}",
+            &xliff[..start + tag.len()],
+            attributes,
+            name,
+            escape_xml(language),
+            &xliff[start + tag.len() + attributes.len()..]
+        )
+    }
+}
+
+impl Document for XliffFile {
+    /// 已批准与不需要翻译的单元为空白片段，不会被翻译
+    fn segments(&self) -> Vec<String> {
+        self.units
+            .iter()
+            .zip(&self.masked)
+            .map(|(unit, masked)| {
+                if unit.locked {
+                    String::new()
+                } else {
+                    masked.text.clone()
+                }
+            })
+            .collect()
+    }
+
+    fn render(&self, translations: &[String], _config: &FormatConfig) -> String {
+        let mut xliff = String::new();
+        let mut position = 0;
+
+        for (i, (unit, span)) in self.units.iter().zip(&self.spans).enumerate() {
+            let Some(translation) = translations.get(i) else {
+                continue;
+            };
+            if unit.locked || translation.trim().is_empty() {
+                continue;
+            }
+            let text = restore_markup(&escape_xml(translation), &self.masked[i].placeholders);
+
+            match &span.target {
+                Some((range, attributes)) => {
+                    xliff.push_str(&self.content[position..range.start]);
+                    xliff.push_str(&self.target_element(attributes, &text));
+                    position = range.end;
+                }
+                None => {
+                    let attributes = match self.version {
+                        XliffVersion::V1_2 => " state=\"translated\"",
+                        XliffVersion::V2_0 => "",
+                    };
+                    xliff.push_str(&self.content[position..span.source_end]);
+                    if !span.indent.is_empty() {
+                        xliff.push('\n');
+                        xliff.push_str(&span.indent);
+                    }
+                    xliff.push_str(&self.target_element(attributes, &text));
+                    position = span.source_end;
+                }
+            }
+        }
+        xliff.push_str(&self.content[position..]);
+
+        self.with_target_language(xliff)
+    }
+}
+
+/// 翻译 `task.content` 中的 XLIFF 1.2 或 2.0 文件，已批准的译文保持不变
+pub async fn translate(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let mut file = XliffFile::parse(&task.content)?;
+    file.target_language = task.target_language.as_ref().map(|l| l.to_string());
+    translate_document(translator, task, &file, config).await
+}
+
+#[cfg(test)]
+const SAMPLE_1_2: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">
+  <file source-language="en" datatype="plaintext" original="app">
+    <body>
+      <trans-unit id="hello">
+        <source>Hello <g id="1">world</g> &amp; <ph id="2">&lt;br/&gt;</ph></source>
+      </trans-unit>
+      <trans-unit id="saved">
+        <source>Saved</source>
+        <target state="needs-translation">Old</target>
+      </trans-unit>
+      <trans-unit id="approved" approved="yes">
+        <source>Done</source>
+        <target>完成</target>
+      </trans-unit>
+    </body>
+  </file>
+</xliff>
+"#;
+
+#[cfg(test)]
+const SAMPLE_2_0: &str = r#"<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.0" srcLang="en">
+  <file id="f1">
+    <unit id="u1">
+      <segment>
+        <source>Click <pc id="1">here</pc><ph id="2"/></source>
+      </segment>
+      <segment state="final">
+        <source>Bye</source>
+        <target>再见</target>
+      </segment>
+    </unit>
+    <unit id="u2" translate="no">
+      <segment>
+        <source>SKU-1</source>
+      </segment>
+    </unit>
+  </file>
+</xliff>
+"#;
+
+#[test]
+fn test_parse_xliff() -> Result<()> {
+    let file = XliffFile::parse(SAMPLE_1_2)?;
+    assert_eq!(file.version, XliffVersion::V1_2);
+    assert_eq!(file.units.len(), 3);
+    assert_eq!(file.units[1].target.as_deref(), Some("Old"));
+    assert!(file.units[2].locked);
+    assert_eq!(
+        file.segments(),
+        vec!["Hello ⟦0⟧world⟦1⟧ & ⟦2⟧", "Saved", ""]
+    );
+    // 原样写回
+    assert_eq!(file.render(&[], &FormatConfig::default()), SAMPLE_1_2);
+
+    let file = XliffFile::parse(SAMPLE_2_0)?;
+    assert_eq!(file.version, XliffVersion::V2_0);
+    assert_eq!(file.units[1].id.as_deref(), Some("u1"));
+    assert_eq!(file.segments(), vec!["Click ⟦0⟧here⟦1⟧⟦2⟧", "", ""]);
+    assert_eq!(file.render(&[], &FormatConfig::default()), SAMPLE_2_0);
+
+    assert!(XliffFile::parse("<html></html>").is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_xliff() -> Result<()> {
+    let translator = MockTranslator::new("> ");
+    let config = FormatConfig {
+        batch_size: 1,
+        ..Default::default()
+    };
+    let mut task = task(SAMPLE_1_2);
+    task.target_language = Some("zh".parse()?);
+
+    let content = translate(&translator, &task, &config)
+        .await?
+        .content
+        .unwrap();
+    assert!(content.contains(
+        r#"<file source-language="en" datatype="plaintext" original="app" target-language="zh">"#
+    ));
+    assert!(content.contains(concat!(
+        r#"<source>Hello <g id="1">world</g> &amp; <ph id="2">&lt;br/&gt;</ph></source>"#,
+        "\n        ",
+        r#"<target state="translated">&gt; Hello <g id="1">world</g> &amp; <ph id="2">&lt;br/&gt;</ph></target>"#
+    )));
+    assert!(content.contains(r#"<target state="translated">&gt; Saved</target>"#));
+    assert!(content.contains("<target>完成</target>"));
+    assert_eq!(translator.calls(), 2);
+
+    task.content = SAMPLE_2_0.to_string();
+    let content = translate(&translator, &task, &config)
+        .await?
+        .content
+        .unwrap();
+    assert!(content.contains(r#"version="2.0" srcLang="en" trgLang="zh">"#));
+    assert!(content.contains(r#"<target>&gt; Click <pc id="1">here</pc><ph id="2"/></target>"#));
+    assert!(!content.contains("SKU-1</source>\n        <target>"));
+
+    Ok(())
+}
@@ -0,0 +1,460 @@
+use super::{mask_markup, restore_markup, translate_document, Document, FormatConfig};
+use crate::placeholder::Masked;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{DynTranslator, TranslateResult, TranslateTask};
+use anyhow::Result;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Rails 的 `%{name}`、`%<name>d`，`%Y` 等时间格式与 `{{name}}`、`{name}` 插值
+static MARKUP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"%\{[^}]*\}|%<\w+>[A-Za-z]?|%[-_0^#:]*[A-Za-z]|\{\{[^{}]*\}\}|\{[^{}]*\}").unwrap()
+});
+
+/// 会被解析为空值、布尔值或数字的纯量
+static NON_STRING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"^(?:~|null|Null|NULL|true|True|TRUE|false|False|FALSE|yes|Yes|YES|no|No|NO|on|On|ON|off|Off|OFF",
+        r"|[-+]?\d[\d_]*(?:\.\d*)?(?:[eE][-+]?\d+)?|[-+]?\.\d+|0x[0-9a-fA-F]+|0o[0-7]+|[-+]?\.(?:inf|Inf|INF)|\.(?:nan|NaN|NAN))$"
+    ))
+    .unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Plain,
+    Single,
+    Double,
+    /// `|` 块
+    Literal,
+    /// `>` 块
+    Folded,
+}
+
+/// 一个字符串值，占据 `start..end` 行
+#[derive(Debug, Clone)]
+struct Scalar {
+    start: usize,
+    end: usize,
+    /// 值之前的内容，包括缩进、键与锚点；块的整行标记
+    head: String,
+    text: String,
+    style: Style,
+    /// 值之后的注释
+    tail: String,
+    /// 块内容的缩进
+    indent: String,
+}
+
+/// YAML 语言文件，只改写字符串值所在的行，注释、锚点与别名原样保留
+#[derive(Debug, Clone, Default)]
+pub struct YamlFile {
+    lines: Vec<String>,
+    scalars: Vec<Scalar>,
+    crlf: bool,
+    trailing_newline: bool,
+    masked: Vec<Masked>,
+}
+
+fn leading_spaces(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// 键之后的位置，没有键时返回 `None`
+fn key_end(rest: &str) -> Option<usize> {
+    let start = match rest.chars().next()? {
+        quote @ ('"' | '\'') => rest[1..].find(quote)? + 2,
+        _ => 0,
+    };
+    let bytes = rest.as_bytes();
+    (start..rest.len())
+        .find(|&i| bytes[i] == b':' && (i + 1 == rest.len() || bytes[i + 1] == b' '))
+        .filter(|&i| !rest[..i].contains(" #"))
+        .map(|i| i + 1)
+}
+
+fn unescape_double(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(c) => result.push(c),
+                    None => result.push_str(&format!("\\u{}", hex)),
+                }
+            }
+            Some(c) => result.push(c),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+fn quote_double(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+fn needs_quotes(value: &str) -> bool {
+    value.is_empty()
+        || value != value.trim()
+        || value.starts_with(|c| "-?:,[]{}#&*!|>'\"%@`".contains(c))
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.ends_with(':')
+        || value.contains('\n')
+        || NON_STRING.is_match(value)
+}
+
+/// 解析一行中的单行纯量，返回值之前的内容、值、样式与值之后的内容
+fn parse_inline(head: &str, value: &str) -> Option<(String, Style, String)> {
+    match value.chars().next()? {
+        '"' => {
+            let mut escaped = false;
+            let close = value[1..].char_indices().find_map(|(i, c)| {
+                let found = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                found.then_some(i + 1)
+            })?;
+            let tail = &value[close + 1..];
+            Some((
+                unescape_double(&value[1..close]),
+                Style::Double,
+                tail.to_string(),
+            ))
+        }
+        '\'' => {
+            let bytes = value.as_bytes();
+            let mut i = 1;
+            while i < bytes.len() {
+                if bytes[i] == b'\'' {
+                    if bytes.get(i + 1) == Some(&b'\'') {
+                        i += 2;
+                        continue;
+                    }
+                    let text = value[1..i].replace("''", "'");
+                    return Some((text, Style::Single, value[i + 1..].to_string()));
+                }
+                i += 1;
+            }
+            None
+        }
+        '*' | '[' | '{' | '|' | '>' => None,
+        _ => {
+            let end = value.find(" #").unwrap_or(value.len());
+            let text = value[..end].trim_end();
+            if NON_STRING.is_match(text) || head.trim_start().starts_with("<<") {
+                return None;
+            }
+            Some((
+                text.to_string(),
+                Style::Plain,
+                value[text.len()..].to_string(),
+            ))
+        }
+    }
+}
+
+impl YamlFile {
+    pub fn parse(content: &str) -> Result<Self> {
+        let crlf = content.contains("\r\n");
+        let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+        let mut file = YamlFile {
+            lines: content.lines().map(str::to_string).collect(),
+            crlf,
+            trailing_newline: content.ends_with('\n'),
+            ..Default::default()
+        };
+
+        let mut number = 0;
+        while number < file.lines.len() {
+            number = match file.parse_line(number) {
+                Some(scalar) => {
+                    let end = scalar.end;
+                    if !scalar.text.trim().is_empty() {
+                        file.scalars.push(scalar);
+                    }
+                    end
+                }
+                None => number + 1,
+            };
+        }
+
+        file.masked = file
+            .scalars
+            .iter()
+            .map(|s| mask_markup(&s.text, &MARKUP))
+            .collect();
+
+        Ok(file)
+    }
+
+    fn parse_line(&self, number: usize) -> Option<Scalar> {
+        let line = &self.lines[number];
+        let trimmed = line.trim_start();
+        if trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with('%')
+            || trimmed == "---"
+            || trimmed == "..."
+        {
+            return None;
+        }
+
+        let mut head_len = line.len() - trimmed.len();
+        let mut item = false;
+        while let Some(rest) = line[head_len..].strip_prefix('-') {
+            if !rest.starts_with(' ') {
+                break;
+            }
+            item = true;
+            head_len += 1 + rest.len() - rest.trim_start().len();
+        }
+        match key_end(&line[head_len..]) {
+            Some(end) => {
+                let rest = &line[head_len + end..];
+                head_len += end + rest.len() - rest.trim_start().len();
+            }
+            None if item => {}
+            None => return None,
+        }
+        // 锚点与标签
+        while line[head_len..].starts_with(['&', '!']) {
+            let rest = &line[head_len..];
+            let token = rest.find(' ').unwrap_or(rest.len());
+            let after = &rest[token..];
+            head_len += token + after.len() - after.trim_start().len();
+        }
+
+        let (head, value) = line.split_at(head_len);
+        if value.starts_with(['|', '>']) {
+            return self.parse_block(number, value);
+        }
+        let (text, style, tail) = parse_inline(head, value)?;
+
+        Some(Scalar {
+            start: number,
+            end: number + 1,
+            head: head.to_string(),
+            text,
+            style,
+            tail,
+            indent: String::new(),
+        })
+    }
+
+    fn parse_block(&self, number: usize, indicator: &str) -> Option<Scalar> {
+        let parent_indent = leading_spaces(&self.lines[number]);
+        let mut end = number + 1;
+        let mut last = number;
+        while end < self.lines.len() {
+            let line = &self.lines[end];
+            if !line.trim().is_empty() {
+                if leading_spaces(line) <= parent_indent {
+                    break;
+                }
+                last = end;
+            }
+            end += 1;
+        }
+        if last == number {
+            return None;
+        }
+
+        let content = &self.lines[number + 1..last + 1];
+        let indent = content
+            .iter()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| leading_spaces(l))
+            .min()
+            .unwrap_or(0);
+        let lines: Vec<&str> = content
+            .iter()
+            .map(|l| l.get(indent..).unwrap_or(""))
+            .collect();
+        let style = if indicator.starts_with('|') {
+            Style::Literal
+        } else {
+            Style::Folded
+        };
+        let text = match style {
+            Style::Literal => lines.join("\n"),
+            _ => lines
+                .split(|l| l.trim().is_empty())
+                .map(|paragraph| paragraph.join(" "))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+
+        Some(Scalar {
+            start: number,
+            end: last + 1,
+            head: self.lines[number].clone(),
+            text,
+            style,
+            tail: String::new(),
+            indent: " ".repeat(indent),
+        })
+    }
+}
+
+impl Scalar {
+    fn render(&self, text: &str) -> Vec<String> {
+        match self.style {
+            Style::Literal | Style::Folded => {
+                let mut lines = vec![self.head.clone()];
+                for (i, line) in text.split('\n').enumerate() {
+                    // 折叠块中段落之间以空行分隔
+                    if self.style == Style::Folded && i > 0 {
+                        lines.push(String::new());
+                    }
+                    if line.is_empty() {
+                        lines.push(String::new());
+                    } else {
+                        lines.push(format!("{}{}", self.indent, line));
+                    }
+                }
+                lines
+            }
+            style => {
+                let value = match style {
+                    Style::Plain if !needs_quotes(text) => text.to_string(),
+                    Style::Single if !text.contains('\n') => {
+                        format!("'{}'", text.replace('\'', "''"))
+                    }
+                    _ => quote_double(text),
+                };
+                vec![format!("{}{}{}", self.head, value, self.tail)]
+            }
+        }
+    }
+}
+
+impl Document for YamlFile {
+    fn segments(&self) -> Vec<String> {
+        self.masked.iter().map(|m| m.text.clone()).collect()
+    }
+
+    fn render(&self, translations: &[String], _config: &FormatConfig) -> String {
+        let mut lines: Vec<String> = vec![];
+        let mut number = 0;
+        for (i, scalar) in self.scalars.iter().enumerate() {
+            let Some(translation) = translations.get(i) else {
+                continue;
+            };
+            lines.extend_from_slice(&self.lines[number..scalar.start]);
+            lines.extend(scalar.render(&restore_markup(translation, &self.masked[i].placeholders)));
+            number = scalar.end;
+        }
+        lines.extend_from_slice(&self.lines[number..]);
+
+        let mut yaml = lines.join("\n");
+        if self.trailing_newline {
+            yaml.push('\n');
+        }
+        if self.crlf {
+            yaml = yaml.replace('\n', "\r\n");
+        }
+        yaml
+    }
+}
+
+/// 翻译 `task.content` 中 YAML 语言文件的字符串值
+pub async fn translate(
+    translator: &dyn DynTranslator,
+    task: &TranslateTask,
+    config: &FormatConfig,
+) -> Result<TranslateResult> {
+    let file = YamlFile::parse(&task.content)?;
+    translate_document(translator, task, &file, config).await
+}
+
+#[cfg(test)]
+const SAMPLE: &str = r#"# Rails locale
+en:
+  defaults: &defaults
+    greeting: "Hello, %{name}!"  # shown on login
+    farewell: 'It''s time'
+  admin:
+    <<: *defaults
+    title: Dashboard
+  count:
+    one: 1 item
+    other: "%{count} items"
+  enabled: true
+  limit: 10
+  list:
+    - First
+    - Second
+  body: |
+    Line one
+    Line two
+  empty: ""
+  shared: &name Shared name
+  alias: *name
+"#;
+
+#[test]
+fn test_parse_yaml() -> Result<()> {
+    let file = YamlFile::parse(SAMPLE)?;
+    assert_eq!(
+        file.segments(),
+        vec![
+            "Hello, ⟦0⟧!",
+            "It's time",
+            "Dashboard",
+            "1 item",
+            "⟦0⟧ items",
+            "First",
+            "Second",
+            "Line one\nLine two",
+            "Shared name"
+        ]
+    );
+
+    // 原样写回，以原文作为译文时引号与块的写法不变
+    assert_eq!(file.render(&[], &FormatConfig::default()), SAMPLE);
+    assert_eq!(
+        file.render(&file.segments(), &FormatConfig::default()),
+        SAMPLE
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_translate_yaml() -> Result<()> {
+    let translator = MockTranslator::new("> ");
+    let config = FormatConfig {
+        batch_size: 1,
+        ..Default::default()
+    };
+    let content = translate(&translator, &task(SAMPLE), &config)
+        .await?
+        .content
+        .unwrap();
+
+    assert!(content.contains("    greeting: \"> Hello, %{name}!\"  # shown on login\n"));
+    assert!(content.contains("    farewell: '> It''s time'\n"));
+    // 以 `>` 开头的纯量需要加引号
+    assert!(content.contains("    title: \"> Dashboard\"\n"));
+    assert!(content.contains("    <<: *defaults\n"));
+    assert!(content.contains("  body: |\n    > Line one\n    Line two\n"));
+    assert!(content.contains("  shared: &name \"> Shared name\"\n  alias: *name\n"));
+    assert!(content.contains("  enabled: true\n  limit: 10\n"));
+
+    Ok(())
+}
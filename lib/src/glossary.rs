@@ -0,0 +1,404 @@
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::terms::contains_term;
+use crate::utils::normal2stream;
+use crate::{
+    Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, TranslatedItem, Translator,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use serde_json::json;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// 术语不符合时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GlossaryMode {
+    /// 仅在结果中报告
+    #[default]
+    Report,
+    /// 将译文中残留的原文术语替换为指定译法
+    Replace,
+    /// 把违规术语置于术语表最前重新翻译，仍不符合时再做替换
+    Reprompt,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GlossaryConfig {
+    /// 术语表，会与 `task.terms` 合并
+    #[serde(default)]
+    pub terms: Vec<TranslatedItem>,
+    #[serde(default)]
+    pub mode: GlossaryMode,
+    /// 匹配原文术语时区分大小写，默认不区分并容忍复数等简单词形变化
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// `reprompt` 模式下最多重新翻译的次数
+    #[serde(default = "default_max_reprompts")]
+    pub max_reprompts: usize,
+}
+
+fn default_max_reprompts() -> usize {
+    1
+}
+
+/// 术语遵循情况
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TermCompliance {
+    /// 原文中出现的术语数量
+    pub total: usize,
+    /// 译文使用了指定译法的数量
+    pub matched: usize,
+    /// 未使用指定译法的术语
+    pub violations: Vec<TranslatedItem>,
+}
+
+impl TermCompliance {
+    pub fn is_compliant(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// 术语表
+#[derive(Debug, Clone, Default)]
+pub struct Glossary {
+    terms: Vec<TranslatedItem>,
+    case_sensitive: bool,
+}
+
+impl Glossary {
+    pub fn new(terms: Vec<TranslatedItem>) -> Self {
+        Glossary {
+            terms,
+            case_sensitive: false,
+        }
+    }
+
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    fn contains(&self, text: &str, term: &str) -> bool {
+        if self.case_sensitive {
+            text.contains(term)
+        } else {
+            contains_term(text, term)
+        }
+    }
+
+    /// 原文中出现的术语，包含任务自带的术语
+    pub fn relevant_terms(&self, task: &TranslateTask) -> Vec<TranslatedItem> {
+        let mut list: Vec<TranslatedItem> = vec![];
+        for term in task.terms.iter().chain(self.terms.iter()) {
+            if !term.source.is_empty()
+                && self.contains(&task.content, &term.source)
+                && !list.iter().any(|t| t.source == term.source)
+            {
+                list.push(term.clone());
+            }
+        }
+        list
+    }
+
+    /// 将原文中出现的术语补充到任务中，供各插件传给服务端
+    pub fn apply(&self, task: &mut TranslateTask) {
+        for term in self.relevant_terms(task) {
+            if !task.terms.iter().any(|t| t.source == term.source) {
+                task.terms.push(term);
+            }
+        }
+    }
+
+    /// 检查译文是否使用了指定的术语译法
+    pub fn check(&self, task: &TranslateTask, output: &str) -> TermCompliance {
+        let terms = self.relevant_terms(task);
+        let violations: Vec<TranslatedItem> = terms
+            .iter()
+            .filter(|term| !output.contains(&term.target))
+            .cloned()
+            .collect();
+
+        TermCompliance {
+            total: terms.len(),
+            matched: terms.len() - violations.len(),
+            violations,
+        }
+    }
+
+    /// 将译文中原样保留的原文术语替换为指定译法
+    pub fn replace(&self, output: &str, violations: &[TranslatedItem]) -> String {
+        let mut output = output.to_string();
+        for term in violations {
+            output = if self.case_sensitive {
+                output.replace(&term.source, &term.target)
+            } else {
+                replace_ignore_case(&output, &term.source, &term.target)
+            };
+        }
+        output
+    }
+}
+
+fn replace_ignore_case(text: &str, from: &str, to: &str) -> String {
+    let lower = text.to_lowercase();
+    let from = from.to_lowercase();
+
+    // 大小写转换可能改变字节长度，此时退回到区分大小写的替换
+    if lower.len() != text.len() || from.is_empty() {
+        return text.replace(from.as_str(), to);
+    }
+
+    let mut result = String::new();
+    let mut last = 0;
+    for (index, _) in lower.match_indices(&from) {
+        result.push_str(&text[last..index]);
+        result.push_str(to);
+        last = index + from.len();
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+/// 在翻译前注入术语表，翻译后检查并按 `GlossaryMode` 处理违规术语
+pub struct GlossaryTranslator<T> {
+    inner: T,
+    glossary: Glossary,
+    mode: GlossaryMode,
+    max_reprompts: usize,
+}
+
+impl<T: Translator> GlossaryTranslator<T> {
+    pub fn wrap(inner: T, config: GlossaryConfig) -> Self {
+        GlossaryTranslator {
+            inner,
+            glossary: Glossary::new(config.terms).case_sensitive(config.case_sensitive),
+            mode: config.mode,
+            max_reprompts: config.max_reprompts,
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn glossary(&self) -> &Glossary {
+        &self.glossary
+    }
+
+    fn enforce(&self, task: &TranslateTask, result: &mut TranslateResult) -> TermCompliance {
+        let content = result.content.clone().unwrap_or_default();
+        let compliance = self.glossary.check(task, &content);
+
+        if compliance.is_compliant() || self.mode == GlossaryMode::Report {
+            return compliance;
+        }
+
+        let replaced = self.glossary.replace(&content, &compliance.violations);
+        let compliance = self.glossary.check(task, &replaced);
+        result.content = Some(replaced);
+        compliance
+    }
+}
+
+#[async_trait]
+impl<T> Translator for GlossaryTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 术语表参数读取自 `config["glossary"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let glossary_config = match config.get("glossary") {
+            Some(glossary) => serde_json::from_value(glossary.clone())?,
+            None => GlossaryConfig::default(),
+        };
+
+        let inner = T::new(config).await?;
+
+        Ok(GlossaryTranslator::wrap(inner, glossary_config))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn translate(&self, mut task: TranslateTask) -> Result<TranslateResult> {
+        self.glossary.apply(&mut task);
+
+        let mut result = self.inner.translate(task.clone()).await?;
+        let mut compliance = self
+            .glossary
+            .check(&task, result.content.as_deref().unwrap_or(""));
+
+        if self.mode == GlossaryMode::Reprompt {
+            for _ in 0..self.max_reprompts {
+                if compliance.is_compliant() {
+                    break;
+                }
+
+                // 违规术语放在最前，部分服务只采用前若干条术语
+                let mut retry = task.clone();
+                retry
+                    .terms
+                    .retain(|t| !compliance.violations.iter().any(|v| v.source == t.source));
+                retry.terms.splice(0..0, compliance.violations.clone());
+
+                let previous = result.usage.take();
+                result = self.inner.translate(retry).await?;
+                if let Some(usage) = &previous {
+                    result.add_usage(usage);
+                }
+                compliance = self
+                    .glossary
+                    .check(&task, result.content.as_deref().unwrap_or(""));
+            }
+        }
+
+        result.glossary = Some(self.enforce(&task, &mut result));
+
+        Ok(result)
+    }
+
+    /// `report` 模式下直接转发流，并在结束前追加一个携带术语检查结果的空增量；
+    /// 其他模式需要改写译文，会先完整翻译再输出
+    async fn translate_stream(
+        &self,
+        mut task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        if self.mode != GlossaryMode::Report {
+            return normal2stream(self, task, sender).await;
+        }
+
+        self.glossary.apply(&mut task);
+
+        let (tx, mut rx) = mpsc::channel(64);
+
+        let forward = async {
+            let mut content = String::new();
+
+            while let Some(chunk) = rx.recv().await {
+                match &chunk {
+                    TranslateStreamChunk::Delta(delta) => {
+                        if let Some(s) = &delta.content {
+                            content.push_str(s);
+                        }
+                    }
+                    TranslateStreamChunk::End => {
+                        let compliance = self.glossary.check(&task, &content);
+                        sender
+                            .send(TranslateStreamChunk::Delta(TranslateResult {
+                                glossary: Some(compliance),
+                                ..Default::default()
+                            }))
+                            .await?;
+                    }
+                    TranslateStreamChunk::Start => {}
+                }
+                sender.send(chunk).await?;
+            }
+
+            Ok::<_, anyhow::Error>(())
+        };
+
+        let (result, forwarded) =
+            tokio::join!(self.inner.translate_stream(task.clone(), tx), forward);
+
+        result?;
+        forwarded
+    }
+}
+
+#[test]
+fn test_glossary_check() {
+    let glossary = Glossary::new(vec![TranslatedItem {
+        source: "Rust".to_string(),
+        target: "锈".to_string(),
+    }]);
+
+    let mut task = task("I love rust and Go");
+    task.terms.push(TranslatedItem {
+        source: "Go".to_string(),
+        target: "围棋".to_string(),
+    });
+    glossary.apply(&mut task);
+    assert_eq!(task.terms.len(), 2);
+
+    let compliance = glossary.check(&task, "我爱锈和 Go");
+    assert_eq!(compliance.total, 2);
+    assert_eq!(compliance.matched, 1);
+    assert_eq!(compliance.violations[0].source, "Go");
+
+    assert_eq!(
+        glossary.replace("我爱 RUST", &glossary.terms),
+        "我爱 锈".to_string()
+    );
+}
+
+#[tokio::test]
+async fn test_glossary_translator() -> Result<()> {
+    // MockTranslator 的译文包含原文，可用于验证替换
+    let config: GlossaryConfig = serde_json::from_value(json!({
+        "terms": [{ "source": "Hello", "target": "你好" }],
+        "mode": "replace",
+    }))?;
+
+    let translator = GlossaryTranslator::wrap(MockTranslator::new("T:"), config.clone());
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("T:你好"));
+    assert!(result.glossary.unwrap().is_compliant());
+
+    let translator = GlossaryTranslator::wrap(
+        MockTranslator::new("T:"),
+        GlossaryConfig {
+            mode: GlossaryMode::Report,
+            ..config
+        },
+    );
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("T:Hello"));
+    assert_eq!(result.glossary.unwrap().violations.len(), 1);
+
+    let (tx, mut rx) = mpsc::channel(64);
+    translator.translate_stream(task("Hello"), tx).await?;
+    let mut chunks = vec![];
+    while let Some(chunk) = rx.recv().await {
+        chunks.push(chunk);
+    }
+    assert_eq!(chunks.len(), 4);
+    assert!(
+        matches!(&chunks[2], TranslateStreamChunk::Delta(r) if r.glossary.as_ref().map(|g| g.total) == Some(1))
+    );
+
+    Ok(())
+}
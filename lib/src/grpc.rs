@@ -0,0 +1,431 @@
+use crate::error::XTranslateError;
+use crate::ffi::{encode_error, FfiError};
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures_util::stream::{self, Stream, StreamExt};
+use proto::stream_chunk::Chunk;
+use proto::translator_service_client::TranslatorServiceClient;
+use proto::translator_service_server::{TranslatorService, TranslatorServiceServer};
+use proto::{
+    Empty, GetLanguagesRequest, GetLanguagesResponse, LanguagePair, ListTranslatorsRequest,
+    ListTranslatorsResponse, StreamChunk, TranslateRequest, TranslateResponse, TranslatorInfo,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::pin::Pin;
+use std::sync::Arc;
+#[cfg(test)]
+use std::time::Duration;
+use tokio::sync::mpsc::{self, Sender};
+use tonic::metadata::MetadataValue;
+use tonic::transport::Channel;
+use tonic::{Code, Request, Response, Status};
+
+/// 由 `proto/translator.proto` 生成
+pub mod proto {
+    tonic::include_proto!("xtranslator.v1");
+}
+
+/// 网关的错误以 gRPC 状态返回，消息与 FFI 的错误信息格式相同
+fn status(err: anyhow::Error) -> Status {
+    let code = match XTranslateError::find(&err) {
+        Some(XTranslateError::InvalidConfig(_))
+        | Some(XTranslateError::UnsupportedLanguage(_))
+        | Some(XTranslateError::InputTooLong { .. }) => Code::InvalidArgument,
+        Some(XTranslateError::AuthFailed(_)) => Code::Unauthenticated,
+        Some(XTranslateError::RateLimited { .. }) => Code::ResourceExhausted,
+        Some(XTranslateError::ProviderError { .. }) => Code::Internal,
+        Some(XTranslateError::Network(_)) => Code::Unavailable,
+        Some(XTranslateError::Timeout(_)) => Code::DeadlineExceeded,
+        Some(XTranslateError::Cancelled) => Code::Cancelled,
+        None => Code::Unknown,
+    };
+
+    Status::new(code, encode_error(&err, format!("{:#}", err)))
+}
+
+/// 还原网关返回的错误。连接失败等不是由网关翻译器产生的错误按状态码分类
+fn restore_status(status: Status) -> anyhow::Error {
+    if let Ok(FfiError { error, detail }) = serde_json::from_str::<FfiError>(status.message()) {
+        return anyhow::Error::new(error).context(detail);
+    }
+
+    let message = status.message().to_string();
+    anyhow!(match status.code() {
+        Code::Unauthenticated | Code::PermissionDenied => XTranslateError::AuthFailed(message),
+        Code::ResourceExhausted => XTranslateError::RateLimited { retry_after: None },
+        Code::Unavailable => XTranslateError::Network(message),
+        Code::DeadlineExceeded => XTranslateError::Timeout(message),
+        Code::Cancelled => XTranslateError::Cancelled,
+        code => XTranslateError::ProviderError {
+            code: (code as i32).to_string(),
+            message,
+        },
+    })
+}
+
+fn parse_task(json: &str) -> Result<TranslateTask, Status> {
+    serde_json::from_str(json).map_err(|e| Status::invalid_argument(format!("invalid task: {}", e)))
+}
+
+fn chunk_message(chunk: TranslateStreamChunk) -> Result<StreamChunk, Status> {
+    let chunk = match chunk {
+        TranslateStreamChunk::Start => Chunk::Start(Empty {}),
+        TranslateStreamChunk::Delta(delta) => {
+            Chunk::Delta(serde_json::to_string(&delta).map_err(|e| Status::internal(e.to_string()))?)
+        }
+        TranslateStreamChunk::End => Chunk::End(Empty {}),
+    };
+
+    Ok(StreamChunk { chunk: Some(chunk) })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcConfig {
+    /// 网关地址，如 `http://127.0.0.1:50051`
+    pub endpoint: String,
+    /// 网关上的翻译器名称，为空时使用网关的默认翻译器
+    #[serde(default)]
+    pub translator: String,
+    /// 以 `authorization: Bearer <token>` 随每个请求发送
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// 通过 gRPC 调用远程翻译网关，多个客户端共用网关上配置好凭据的翻译器，接口见 `proto/translator.proto`。
+/// 语言与能力在连接时读取，流式翻译使用服务端流，接收方被丢弃时取消请求
+pub struct GrpcTranslator {
+    config: GrpcConfig,
+    client: TranslatorServiceClient<Channel>,
+    languages: GetLanguagesResponse,
+    capabilities: Capabilities,
+}
+
+impl GrpcTranslator {
+    pub async fn connect(config: GrpcConfig) -> Result<Self> {
+        let channel = Channel::from_shared(config.endpoint.clone())
+            .map_err(|e| anyhow!(XTranslateError::InvalidConfig(e.to_string())))?
+            .connect()
+            .await
+            .map_err(|e| anyhow!(XTranslateError::Network(e.to_string())))?;
+
+        let mut translator = GrpcTranslator {
+            config,
+            client: TranslatorServiceClient::new(channel),
+            languages: GetLanguagesResponse::default(),
+            capabilities: Capabilities::default(),
+        };
+
+        let request = translator.request(ListTranslatorsRequest {})?;
+        let list = translator.client.list_translators(request).await.map_err(restore_status)?.into_inner();
+        let info = match translator.config.translator.as_str() {
+            "" => list.translators.first(),
+            name => list.translators.iter().find(|info| info.name == name),
+        }
+        .ok_or(anyhow!(XTranslateError::InvalidConfig(format!(
+            "translator not found on gateway: {}",
+            translator.config.translator
+        ))))?;
+        translator.capabilities = serde_json::from_str(&info.capabilities)?;
+
+        let request = translator.request(GetLanguagesRequest {
+            translator: translator.config.translator.clone(),
+        })?;
+        translator.languages = translator.client.get_languages(request).await.map_err(restore_status)?.into_inner();
+
+        Ok(translator)
+    }
+
+    fn request<T>(&self, message: T) -> Result<Request<T>> {
+        let mut request = Request::new(message);
+        if let Some(token) = &self.config.token {
+            let value: MetadataValue<_> = format!("Bearer {}", token)
+                .parse()
+                .map_err(|_| anyhow!(XTranslateError::InvalidConfig("invalid token".to_string())))?;
+            request.metadata_mut().insert("authorization", value);
+        }
+
+        Ok(request)
+    }
+
+    fn translate_request(&self, task: &TranslateTask) -> Result<Request<TranslateRequest>> {
+        self.request(TranslateRequest {
+            translator: self.config.translator.clone(),
+            task: serde_json::to_string(task)?,
+        })
+    }
+
+    fn supports(languages: &[String], lang: &str) -> bool {
+        languages.iter().any(|l| l == "*" || l == lang)
+    }
+}
+
+#[async_trait]
+impl Translator for GrpcTranslator {
+    type This = Self;
+
+    async fn new(config: Value) -> Result<Self> {
+        let config: GrpcConfig = serde_json::from_value(config)
+            .map_err(|e| anyhow!(XTranslateError::InvalidConfig(e.to_string())))?;
+        GrpcTranslator::connect(config).await
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        Ok(self.languages.input_languages.clone())
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        Ok(self.languages.output_languages.clone())
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        Ok(Self::supports(&self.languages.input_languages, &lang))
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        Ok(Self::supports(&self.languages.output_languages, &lang))
+    }
+
+    /// 网关没有列出语言对时（如支持任意语言）按源语言与目标语言分别判断
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        if self.languages.pairs.is_empty() {
+            return Ok(self.is_supported_input_language(source)? && self.is_supported_output_language(target)?);
+        }
+
+        Ok(self.languages.pairs.iter().any(|pair| pair.source == source && pair.target == target))
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        Ok(self.languages.pairs.iter().map(|pair| (pair.source.clone(), pair.target.clone())).collect())
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities.clone()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let request = self.translate_request(&task)?;
+        let response = self.client.clone().translate(request).await.map_err(restore_status)?;
+
+        Ok(serde_json::from_str(&response.into_inner().result)?)
+    }
+
+    async fn translate_stream(&self, task: TranslateTask, sender: Sender<TranslateStreamChunk>) -> Result<()> {
+        let request = self.translate_request(&task)?;
+        let mut stream = self.client.clone().translate_stream(request).await.map_err(restore_status)?.into_inner();
+
+        while let Some(message) = stream.message().await.map_err(restore_status)? {
+            let chunk = match message.chunk {
+                Some(Chunk::Start(_)) => TranslateStreamChunk::Start,
+                Some(Chunk::Delta(json)) => TranslateStreamChunk::Delta(serde_json::from_str(&json)?),
+                Some(Chunk::End(_)) => TranslateStreamChunk::End,
+                None => continue,
+            };
+            // 丢弃响应流即取消网关上的请求
+            if sender.send(chunk).await.is_err() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// 把翻译器作为 gRPC 服务提供给 `GrpcTranslator`，第一个添加的翻译器为默认翻译器。
+/// 设置 `token` 后拒绝 `authorization` 不匹配的请求
+#[derive(Default)]
+pub struct GrpcGateway {
+    translators: Vec<(String, Arc<dyn crate::DynTranslator>)>,
+    token: Option<String>,
+}
+
+impl GrpcGateway {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(mut self, name: &str, translator: impl crate::DynTranslator + 'static) -> Self {
+        self.translators.push((name.to_string(), Arc::new(translator)));
+        self
+    }
+
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    pub fn into_service(self) -> TranslatorServiceServer<Self> {
+        TranslatorServiceServer::new(self)
+    }
+
+    fn authorize<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let Some(token) = &self.token else {
+            return Ok(());
+        };
+
+        let expected = format!("Bearer {}", token);
+        match request.metadata().get("authorization") {
+            Some(value) if value.as_bytes() == expected.as_bytes() => Ok(()),
+            _ => Err(Status::unauthenticated("invalid token")),
+        }
+    }
+
+    fn translator(&self, name: &str) -> Result<Arc<dyn crate::DynTranslator>, Status> {
+        let found = match name {
+            "" => self.translators.first(),
+            name => self.translators.iter().find(|(n, _)| n == name),
+        };
+
+        found
+            .map(|(_, translator)| translator.clone())
+            .ok_or_else(|| Status::not_found(format!("translator not found: {}", name)))
+    }
+}
+
+type ChunkStream = Pin<Box<dyn Stream<Item = Result<StreamChunk, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl TranslatorService for GrpcGateway {
+    async fn list_translators(&self, request: Request<ListTranslatorsRequest>) -> Result<Response<ListTranslatorsResponse>, Status> {
+        self.authorize(&request)?;
+
+        let translators = self
+            .translators
+            .iter()
+            .map(|(name, translator)| {
+                Ok(TranslatorInfo {
+                    name: name.clone(),
+                    capabilities: serde_json::to_string(&translator.capabilities()).map_err(|e| Status::internal(e.to_string()))?,
+                })
+            })
+            .collect::<Result<_, Status>>()?;
+
+        Ok(Response::new(ListTranslatorsResponse { translators }))
+    }
+
+    async fn get_languages(&self, request: Request<GetLanguagesRequest>) -> Result<Response<GetLanguagesResponse>, Status> {
+        self.authorize(&request)?;
+        let translator = self.translator(&request.get_ref().translator)?;
+
+        let pairs = translator
+            .get_supported_pairs()
+            .map_err(status)?
+            .into_iter()
+            .map(|(source, target)| LanguagePair { source, target })
+            .collect();
+
+        Ok(Response::new(GetLanguagesResponse {
+            input_languages: translator.get_supported_input_languages().map_err(status)?,
+            output_languages: translator.get_supported_output_languages().map_err(status)?,
+            pairs,
+        }))
+    }
+
+    async fn translate(&self, request: Request<TranslateRequest>) -> Result<Response<TranslateResponse>, Status> {
+        self.authorize(&request)?;
+        let translator = self.translator(&request.get_ref().translator)?;
+        let task = parse_task(&request.get_ref().task)?;
+
+        let result = translator.translate(task).await.map_err(status)?;
+        let result = serde_json::to_string(&result).map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(TranslateResponse { result }))
+    }
+
+    type TranslateStreamStream = ChunkStream;
+
+    /// 客户端断开后响应流被丢弃，翻译器的接收方随之关闭
+    async fn translate_stream(&self, request: Request<TranslateRequest>) -> Result<Response<ChunkStream>, Status> {
+        self.authorize(&request)?;
+        let translator = self.translator(&request.get_ref().translator)?;
+        let task = parse_task(&request.get_ref().task)?;
+
+        let (tx, rx) = mpsc::channel(16);
+        let handle = tokio::spawn(async move { translator.translate_stream(task, tx).await });
+
+        let chunks = stream::unfold(rx, |mut rx| async move {
+            let chunk = rx.recv().await?;
+            Some((chunk_message(chunk), rx))
+        });
+        // 增量发送完后报告翻译的错误
+        let error = stream::once(async move {
+            match handle.await {
+                Ok(Ok(())) => None,
+                Ok(Err(e)) => Some(Err(status(e))),
+                Err(e) => Some(Err(Status::internal(e.to_string()))),
+            }
+        })
+        .filter_map(|error| async move { error });
+
+        Ok(Response::new(Box::pin(chunks.chain(error))))
+    }
+}
+
+#[tokio::test]
+async fn test_grpc_translator() -> Result<()> {
+    let gateway = GrpcGateway::new()
+        .add("streaming", MockTranslator::streaming("T:", Duration::from_millis(1)))
+        .add("flaky", MockTranslator::flaky("F:", vec![XTranslateError::RateLimited { retry_after: Some(Duration::from_millis(1500)) }]))
+        .with_token("secret");
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let endpoint = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(gateway.into_service())
+            .serve_with_incoming(tonic::transport::server::TcpIncoming::from(listener)),
+    );
+
+    let config = |translator: &str, token: &str| GrpcConfig {
+        endpoint: endpoint.clone(),
+        translator: translator.to_string(),
+        token: Some(token.to_string()),
+    };
+
+    // 默认翻译器
+    let translator = GrpcTranslator::connect(config("", "secret")).await?;
+    assert!(translator.capabilities().supports_streaming);
+    assert!(translator.is_supported_input_language("ja".to_string())?);
+    assert!(translator.is_supported_pair("en".to_string(), "ja".to_string())?);
+
+    let result = translator.translate(task("one two three")).await?;
+    assert_eq!(result.content.as_deref(), Some("T:one two three"));
+
+    let (tx, mut rx) = mpsc::channel(64);
+    translator.translate_stream(task("one two three"), tx).await?;
+    let mut chunks = vec![];
+    while let Some(chunk) = rx.recv().await {
+        chunks.push(chunk);
+    }
+    assert!(matches!(chunks.first(), Some(TranslateStreamChunk::Start)));
+    assert!(matches!(chunks.last(), Some(TranslateStreamChunk::End)));
+    assert_eq!(chunks.len(), 6);
+
+    // 接收方被丢弃时结束
+    let (tx, rx) = mpsc::channel(1);
+    drop(rx);
+    translator.translate_stream(task("one two three"), tx).await?;
+
+    // 网关翻译器的错误类别在客户端还原
+    let translator = GrpcTranslator::connect(config("flaky", "secret")).await?;
+    let err = translator.translate(task("Hello")).await.unwrap_err();
+    assert_eq!(
+        XTranslateError::find(&err),
+        Some(&XTranslateError::RateLimited {
+            retry_after: Some(Duration::from_millis(1500))
+        })
+    );
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("F:Hello"));
+
+    let err = GrpcTranslator::connect(config("flaky", "wrong")).await.err().unwrap();
+    assert!(matches!(XTranslateError::find(&err), Some(XTranslateError::AuthFailed(_))));
+
+    let err = GrpcTranslator::connect(config("missing", "secret")).await.err().unwrap();
+    assert!(matches!(XTranslateError::find(&err), Some(XTranslateError::InvalidConfig(_))));
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use reqwest::Certificate;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use serde_json::json;
+use serde_json::Value;
+use std::sync::{LazyLock, RwLock};
+
+/// 保留的配置键，由宿主注入，所有内置插件通过 `HttpConfig` 读取
+pub const PROXY: &str = "_proxy";
+pub const TIMEOUT_MS: &str = "_timeout_ms";
+pub const CA_BUNDLE: &str = "_ca_bundle";
+pub const LOCALE: &str = "_locale";
+
+/// 宿主环境设置，插件自己的同类配置优先
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct HostEnv {
+    /// 默认代理地址，插件配置了 `proxy` 时不使用
+    #[serde(rename = "_proxy", skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// 默认请求超时（毫秒），插件配置了 `timeout_ms` 时不使用
+    #[serde(rename = "_timeout_ms", skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// 额外信任的 CA 证书文件（PEM，可包含多个证书）
+    #[serde(rename = "_ca_bundle", skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<String>,
+    /// 宿主界面语言，如 `zh-CN`，作为 `Accept-Language` 发送
+    #[serde(rename = "_locale", skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+
+impl HostEnv {
+    /// 把设置写入插件配置，配置中已有的键不覆盖
+    pub fn inject(&self, config: &mut Value) {
+        let Some(config) = config.as_object_mut() else {
+            return;
+        };
+
+        if let Value::Object(env) = serde_json::to_value(self).unwrap_or_default() {
+            for (key, value) in env {
+                config.entry(key).or_insert(value);
+            }
+        }
+    }
+
+    /// 读取 `ca_bundle` 中的证书
+    pub fn certificates(&self) -> Result<Vec<Certificate>> {
+        match &self.ca_bundle {
+            Some(path) => Ok(Certificate::from_pem_bundle(&std::fs::read(path)?)?),
+            None => Ok(vec![]),
+        }
+    }
+}
+
+static HOST_ENV: LazyLock<RwLock<HostEnv>> = LazyLock::new(|| RwLock::new(HostEnv::default()));
+
+/// 设置注入到之后创建的插件翻译器中的宿主环境，见 `ProxyTranslator`
+pub fn set_host_env(env: HostEnv) {
+    *HOST_ENV.write().unwrap() = env;
+}
+
+pub fn host_env() -> HostEnv {
+    HOST_ENV.read().unwrap().clone()
+}
+
+#[test]
+fn test_host_env_inject() -> Result<()> {
+    let env = HostEnv {
+        proxy: Some("http://127.0.0.1:8080".to_string()),
+        timeout_ms: Some(3000),
+        ca_bundle: None,
+        locale: Some("zh-CN".to_string()),
+    };
+
+    let mut config = json!({ "api_key": "key", "_timeout_ms": 1000 });
+    env.inject(&mut config);
+    assert_eq!(
+        config,
+        json!({
+            "api_key": "key",
+            "_proxy": "http://127.0.0.1:8080",
+            "_timeout_ms": 1000,
+            "_locale": "zh-CN",
+        })
+    );
+
+    let parsed: HostEnv = serde_json::from_value(config)?;
+    assert_eq!(parsed.timeout_ms, Some(1000));
+    assert!(parsed.certificates()?.is_empty());
+
+    let missing = HostEnv {
+        ca_bundle: Some("/nonexistent/ca.pem".to_string()),
+        ..HostEnv::default()
+    };
+    assert!(missing.certificates().is_err());
+
+    Ok(())
+}
@@ -0,0 +1,666 @@
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::utils::normal2stream;
+use crate::{
+    Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator, Usage,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use serde_json::json;
+use serde_json::Value;
+use std::sync::LazyLock;
+use tokio::sync::mpsc::Sender;
+
+/// 不拆分句子的行内元素，在片段中以 `<0>`、`</0>`、`<0/>` 形式保留，其属性不会被翻译
+const INLINE_TAGS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "br", "cite", "del", "dfn", "em", "font", "i", "ins", "label",
+    "mark", "q", "s", "small", "span", "strong", "sub", "sup", "time", "u", "var", "wbr",
+];
+
+/// 内容不需要翻译的元素
+const SKIP_TAGS: &[&str] = &[
+    "code", "kbd", "math", "noscript", "pre", "samp", "script", "style", "svg", "template",
+];
+
+/// 需要翻译的属性
+const ATTRIBUTES: &[&str] = &["alt", "title", "placeholder", "aria-label"];
+
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+static TAG_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^</?\s*([A-Za-z][A-Za-z0-9\-]*)").unwrap());
+
+static ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\s([a-z\-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+static NO_TRANSLATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\stranslate\s*=\s*["']?no\b|\sclass\s*=\s*["'][^"']*\bnotranslate\b"#)
+        .unwrap()
+});
+
+static INLINE_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(/?)\s*(\d+)\s*(/?)>").unwrap());
+
+/// 一段待翻译的文本
+#[derive(Debug, Clone, Default)]
+pub struct HtmlSegment {
+    /// 发送给翻译服务的文本，行内标签已替换为占位符
+    pub text: String,
+    leading: String,
+    trailing: String,
+    /// 占位符对应的原始标签，`(开始标签, 结束标签)`
+    tags: Vec<(String, Option<String>)>,
+    /// 是否为属性值
+    attribute: Option<char>,
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Raw(String),
+    Segment(usize),
+}
+
+/// 拆分后的 HTML 文档
+#[derive(Debug, Clone, Default)]
+pub struct HtmlDocument {
+    parts: Vec<Part>,
+    segments: Vec<HtmlSegment>,
+}
+
+#[derive(Debug)]
+enum Token<'a> {
+    Text(&'a str),
+    Tag {
+        raw: &'a str,
+        name: String,
+        closing: bool,
+    },
+    Raw(&'a str),
+}
+
+fn find_tag_end(html: &str, start: usize) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in html[start..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(start + i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 从 `start` 开始查找与 `name` 匹配的结束标签，返回结束标签之后的位置
+fn find_element_end(html: &str, start: usize, name: &str) -> usize {
+    let mut depth = 1;
+    let mut pos = start;
+
+    while let Some(offset) = html[pos..].find('<') {
+        let tag_start = pos + offset;
+        let Some(caps) = TAG_NAME.captures(&html[tag_start..]) else {
+            pos = tag_start + 1;
+            continue;
+        };
+        let Some(tag_end) = find_tag_end(html, tag_start) else {
+            break;
+        };
+        pos = tag_end;
+
+        if !caps[1].eq_ignore_ascii_case(name) {
+            continue;
+        }
+
+        if html[tag_start..].starts_with("</") {
+            depth -= 1;
+            if depth == 0 {
+                return pos;
+            }
+        } else if !html[tag_start..tag_end].ends_with("/>") {
+            depth += 1;
+        }
+    }
+
+    html.len()
+}
+
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut pos = 0;
+    let mut text_start = 0;
+
+    while let Some(offset) = html[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &html[start..];
+
+        let end = if rest.starts_with("<!--") {
+            rest.find("-->")
+                .map(|i| start + i + 3)
+                .unwrap_or(html.len())
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            find_tag_end(html, start).unwrap_or(html.len())
+        } else if TAG_NAME.is_match(rest) {
+            match find_tag_end(html, start) {
+                Some(end) => end,
+                None => break,
+            }
+        } else {
+            pos = start + 1;
+            continue;
+        };
+
+        if text_start < start {
+            tokens.push(Token::Text(&html[text_start..start]));
+        }
+
+        let raw = &html[start..end];
+        match TAG_NAME.captures(raw) {
+            Some(caps) if !rest.starts_with("<!") => {
+                let name = caps[1].to_lowercase();
+                let closing = raw.starts_with("</");
+                let self_closing = raw.ends_with("/>") || VOID_TAGS.contains(&name.as_str());
+
+                if !closing
+                    && !self_closing
+                    && (SKIP_TAGS.contains(&name.as_str()) || NO_TRANSLATE.is_match(raw))
+                {
+                    let element_end = find_element_end(html, end, &name);
+                    tokens.push(Token::Raw(&html[start..element_end]));
+                    pos = element_end;
+                    text_start = element_end;
+                    continue;
+                }
+
+                tokens.push(Token::Tag { raw, name, closing });
+            }
+            _ => tokens.push(Token::Raw(raw)),
+        }
+
+        pos = end;
+        text_start = end;
+    }
+
+    if text_start < html.len() {
+        tokens.push(Token::Text(&html[text_start..]));
+    }
+
+    tokens
+}
+
+/// 解码常见实体
+pub fn decode_entities(text: &str) -> String {
+    static ENTITY: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[A-Za-z]+);").unwrap());
+
+    ENTITY
+        .replace_all(text, |caps: &regex::Captures| {
+            let name = &caps[1];
+            let c = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{A0}'),
+                _ if name.starts_with("#x") || name.starts_with("#X") => {
+                    u32::from_str_radix(&name[2..], 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                }
+                _ if name.starts_with('#') => name[1..].parse().ok().and_then(char::from_u32),
+                _ => None,
+            };
+            c.map(|c| c.to_string()).unwrap_or(caps[0].to_string())
+        })
+        .to_string()
+}
+
+/// 转义文本，`quote` 为属性值所用的引号；已经是实体引用的 `&` 保持不变
+pub fn encode_entities(text: &str, quote: Option<char>) -> String {
+    static ENTITY_PREFIX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^&(#[0-9]+|#[xX][0-9a-fA-F]+|[A-Za-z]+);").unwrap());
+
+    let mut result = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        match c {
+            '&' if ENTITY_PREFIX.is_match(&text[i..]) => result.push('&'),
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '\u{A0}' => result.push_str("&nbsp;"),
+            '"' if quote == Some('"') => result.push_str("&quot;"),
+            '\'' if quote == Some('\'') => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+fn split_whitespace(text: &str) -> (String, &str, String) {
+    let trimmed_start = text.trim_start();
+    let leading = &text[..text.len() - trimmed_start.len()];
+    let trimmed = trimmed_start.trim_end();
+    let trailing = &trimmed_start[trimmed.len()..];
+    (leading.to_string(), trimmed, trailing.to_string())
+}
+
+#[derive(Default)]
+struct Builder {
+    document: HtmlDocument,
+    raw: Vec<String>,
+    text: String,
+    tags: Vec<(String, Option<String>)>,
+    open: Vec<(String, usize)>,
+    has_text: bool,
+}
+
+impl Builder {
+    fn push_raw(&mut self, raw: &str) {
+        match self.document.parts.last_mut() {
+            Some(Part::Raw(s)) => s.push_str(raw),
+            _ => self.document.parts.push(Part::Raw(raw.to_string())),
+        }
+    }
+
+    fn push_segment(&mut self, segment: HtmlSegment) {
+        self.document.segments.push(segment);
+        self.document
+            .parts
+            .push(Part::Segment(self.document.segments.len() - 1));
+    }
+
+    fn text(&mut self, raw: &str) {
+        self.raw.push(raw.to_string());
+        let decoded = decode_entities(raw);
+        if !decoded.trim().is_empty() {
+            self.has_text = true;
+        }
+        self.text.push_str(&decoded);
+    }
+
+    fn inline(&mut self, raw: &str, name: &str, closing: bool) {
+        self.raw.push(raw.to_string());
+
+        if closing {
+            if let Some(pos) = self.open.iter().rposition(|(n, _)| n == name) {
+                let (_, index) = self.open.remove(pos);
+                self.tags[index].1 = Some(raw.to_string());
+                self.text.push_str(&format!("</{}>", index));
+                return;
+            }
+        }
+
+        let index = self.tags.len();
+        self.tags.push((raw.to_string(), None));
+
+        if closing || raw.ends_with("/>") || VOID_TAGS.contains(&name) {
+            self.text.push_str(&format!("<{}/>", index));
+        } else {
+            self.open.push((name.to_string(), index));
+            self.text.push_str(&format!("<{}>", index));
+        }
+    }
+
+    fn flush(&mut self) {
+        let raw = std::mem::take(&mut self.raw).concat();
+        let text = std::mem::take(&mut self.text);
+        let mut tags = std::mem::take(&mut self.tags);
+        self.open.clear();
+
+        if !std::mem::take(&mut self.has_text) {
+            self.push_raw(&raw);
+            return;
+        }
+
+        // 未闭合的行内标签按自闭合处理
+        for (_, close) in tags.iter_mut() {
+            close.get_or_insert_with(String::new);
+        }
+
+        let (leading, text, trailing) = split_whitespace(&text);
+        self.push_segment(HtmlSegment {
+            text: text.to_string(),
+            leading: encode_entities(&leading, None),
+            trailing: encode_entities(&trailing, None),
+            tags,
+            attribute: None,
+        });
+    }
+
+    fn tag(&mut self, raw: &str) {
+        let mut last = 0;
+        for caps in ATTRIBUTE.captures_iter(raw) {
+            let name = caps[1].to_lowercase();
+            let Some(value) = caps.get(2).or(caps.get(3)) else {
+                continue;
+            };
+
+            if !ATTRIBUTES.contains(&name.as_str()) || value.as_str().trim().is_empty() {
+                continue;
+            }
+
+            let quote = raw[..value.start()].chars().last();
+            self.push_raw(&raw[last..value.start()]);
+            let decoded = decode_entities(value.as_str());
+            let (leading, text, trailing) = split_whitespace(&decoded);
+            self.push_segment(HtmlSegment {
+                text: text.to_string(),
+                leading,
+                trailing,
+                tags: vec![],
+                attribute: quote,
+            });
+            last = value.end();
+        }
+        self.push_raw(&raw[last..]);
+    }
+}
+
+impl HtmlDocument {
+    pub fn parse(html: &str) -> Self {
+        let mut builder = Builder::default();
+
+        for token in tokenize(html) {
+            match token {
+                Token::Text(text) => builder.text(text),
+                Token::Tag { raw, name, closing } if INLINE_TAGS.contains(&name.as_str()) => {
+                    builder.inline(raw, &name, closing)
+                }
+                Token::Tag { raw, .. } => {
+                    builder.flush();
+                    builder.tag(raw);
+                }
+                Token::Raw(raw) => {
+                    builder.flush();
+                    builder.push_raw(raw);
+                }
+            }
+        }
+        builder.flush();
+
+        builder.document
+    }
+
+    pub fn segments(&self) -> &[HtmlSegment] {
+        &self.segments
+    }
+
+    /// 使用译文重新组装 HTML，`translations` 与 `segments()` 一一对应，
+    /// 缺失的行内标签追加在片段末尾
+    pub fn render(&self, translations: &[String]) -> String {
+        let mut html = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Raw(raw) => html.push_str(raw),
+                Part::Segment(index) => {
+                    let segment = &self.segments[*index];
+                    let text = translations.get(*index).unwrap_or(&segment.text);
+                    html.push_str(&segment.leading);
+                    html.push_str(&segment.render(text));
+                    html.push_str(&segment.trailing);
+                }
+            }
+        }
+
+        html
+    }
+}
+
+impl HtmlSegment {
+    fn render(&self, text: &str) -> String {
+        if self.attribute.is_some() {
+            return encode_entities(text, self.attribute);
+        }
+
+        let mut html = String::new();
+        let mut used = vec![(false, false); self.tags.len()];
+        let mut last = 0;
+
+        for caps in INLINE_PLACEHOLDER.captures_iter(text) {
+            let index: usize = caps[2].parse().unwrap_or(usize::MAX);
+            let Some((open, close)) = self.tags.get(index) else {
+                continue;
+            };
+
+            let whole = caps.get(0).unwrap();
+            html.push_str(&encode_entities(&text[last..whole.start()], None));
+            last = whole.end();
+
+            if &caps[1] == "/" {
+                html.push_str(close.as_deref().unwrap_or(""));
+                used[index].1 = true;
+            } else {
+                html.push_str(open);
+                used[index].0 = true;
+                if &caps[3] == "/" {
+                    used[index].1 = true;
+                }
+            }
+        }
+        html.push_str(&encode_entities(&text[last..], None));
+
+        for (index, (open, close)) in self.tags.iter().enumerate() {
+            if !used[index].0 {
+                html.push_str(open);
+            }
+            if !used[index].1 {
+                html.push_str(close.as_deref().unwrap_or(""));
+            }
+        }
+
+        html
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HtmlConfig {
+    /// 翻译服务原生支持 HTML，直接发送原始内容
+    #[serde(default)]
+    pub native: bool,
+    /// 将所有片段合并为一次请求，片段数对不上时退回逐段翻译
+    #[serde(default)]
+    pub batch: bool,
+}
+
+/// 合并请求时的片段分隔符
+const BATCH_SEPARATOR: &str = "\n⟦§⟧\n";
+
+/// 按 HTML 结构拆分文本，逐段翻译后重新组装
+pub struct HtmlTranslator<T> {
+    inner: T,
+    config: HtmlConfig,
+}
+
+impl<T: Translator> HtmlTranslator<T> {
+    pub fn wrap(inner: T, config: HtmlConfig) -> Self {
+        HtmlTranslator { inner, config }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// 翻译单个片段，用量累加到 `usage`
+    async fn translate_segment(
+        &self,
+        task: &TranslateTask,
+        text: &str,
+        usage: &mut Option<Usage>,
+    ) -> Result<String> {
+        let mut task = task.clone();
+        task.content = text.to_string();
+
+        let result = self.inner.translate(task).await?;
+        if let Some(u) = &result.usage {
+            usage.get_or_insert_with(Usage::default).add(u);
+        }
+        Ok(result.content.unwrap_or_default())
+    }
+
+    async fn translate_segments(
+        &self,
+        task: &TranslateTask,
+        texts: &[&str],
+        usage: &mut Option<Usage>,
+    ) -> Result<Vec<String>> {
+        if self.config.batch && texts.len() > 1 {
+            let joined = self
+                .translate_segment(task, &texts.join(BATCH_SEPARATOR), usage)
+                .await?;
+            let parts: Vec<String> = joined.split("⟦§⟧").map(|s| s.trim().to_string()).collect();
+            if parts.len() == texts.len() {
+                return Ok(parts);
+            }
+        }
+
+        let mut translations = vec![];
+        for text in texts {
+            translations.push(self.translate_segment(task, text, usage).await?);
+        }
+        Ok(translations)
+    }
+}
+
+#[async_trait]
+impl<T> Translator for HtmlTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// HTML 参数读取自 `config["html"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let html_config = match config.get("html") {
+            Some(html) => serde_json::from_value(html.clone())?,
+            None => HtmlConfig::default(),
+        };
+
+        Ok(HtmlTranslator::wrap(T::new(config).await?, html_config))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        if self.config.native {
+            return self.inner.translate(task).await;
+        }
+
+        let document = HtmlDocument::parse(&task.content);
+        let texts: Vec<&str> = document
+            .segments()
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect();
+        let mut usage = None;
+        let translations = self.translate_segments(&task, &texts, &mut usage).await?;
+
+        Ok(TranslateResult {
+            content: Some(document.render(&translations)),
+            usage,
+            ..Default::default()
+        })
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        if self.config.native {
+            return self.inner.translate_stream(task, sender).await;
+        }
+
+        normal2stream(self, task, sender).await
+    }
+}
+
+#[test]
+fn test_html_document() {
+    let html = r#"<div class="a"><p>Click <a href="/x">here</a> to&nbsp;continue &amp; save.</p>
+<img src="a.png" alt="A &quot;cat&quot;"><pre>let x = 1;</pre><p translate="no">Keep</p>
+<script>var s = "<p>x</p>";</script><!-- note --> <b>Bold</b></div>"#;
+
+    let document = HtmlDocument::parse(html);
+    let texts: Vec<&str> = document
+        .segments()
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            "Click <0>here</0> to\u{A0}continue & save.",
+            "A \"cat\"",
+            "<0>Bold</0>",
+        ]
+    );
+
+    // 原样组装应得到等价的 HTML
+    assert_eq!(document.render(&[]), html);
+
+    let translated = document.render(&[
+        "点击<0>这里</0>继续 & 保存。".to_string(),
+        "一只\"猫\"".to_string(),
+        "粗体".to_string(),
+    ]);
+    assert!(translated.contains(r#"<p>点击<a href="/x">这里</a>继续 &amp; 保存。</p>"#));
+    assert!(translated.contains(r#"alt="一只&quot;猫&quot;""#));
+    assert!(translated.contains("<pre>let x = 1;</pre><p translate=\"no\">Keep</p>"));
+    assert!(translated.ends_with("<!-- note --> 粗体<b></b></div>"));
+}
+
+#[tokio::test]
+async fn test_html_translator() -> Result<()> {
+    let content = "<p>Hello <b>world</b></p><p>Bye</p>";
+
+    let translator = HtmlTranslator::wrap(MockTranslator::new("T:"), HtmlConfig::default());
+    let result = translator.translate(task(content)).await?;
+    assert_eq!(
+        result.content.as_deref(),
+        Some("<p>T:Hello <b>world</b></p><p>T:Bye</p>")
+    );
+
+    let config: HtmlConfig = serde_json::from_value(json!({ "native": true }))?;
+    let translator = HtmlTranslator::wrap(MockTranslator::new("T:"), config);
+    let result = translator.translate(task(content)).await?;
+    assert_eq!(
+        result.content.as_deref(),
+        Some("T:<p>Hello <b>world</b></p><p>Bye</p>")
+    );
+
+    Ok(())
+}
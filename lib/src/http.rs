@@ -0,0 +1,295 @@
+use crate::error::XTranslateError;
+use crate::host_env::HostEnv;
+use crate::progress::{self, ProgressPhase};
+use crate::utils::to_header_map;
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy, Request, Response};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(test)]
+use std::sync::Mutex;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::Duration;
+
+/// 插件的 HTTP 层，宿主可替换为自己的实现，如自定义 TLS、企业代理或测试用的模拟服务。
+/// 基于 async-openai 的插件只通过 `build_client` 获取客户端，请求由 async-openai 发送
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    /// `builder` 已应用 `HttpConfig` 中的设置
+    fn build_client(&self, builder: ClientBuilder) -> Result<Client> {
+        Ok(builder.build()?)
+    }
+
+    async fn execute(&self, client: &Client, request: Request) -> Result<Response> {
+        Ok(client
+            .execute(request)
+            .await
+            .map_err(XTranslateError::from)?)
+    }
+}
+
+/// 默认实现，直接使用 reqwest
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport;
+
+impl HttpTransport for ReqwestTransport {}
+
+#[async_trait]
+impl<T: HttpTransport + ?Sized> HttpTransport for Arc<T> {
+    fn build_client(&self, builder: ClientBuilder) -> Result<Client> {
+        self.as_ref().build_client(builder)
+    }
+
+    async fn execute(&self, client: &Client, request: Request) -> Result<Response> {
+        self.as_ref().execute(client, request).await
+    }
+}
+
+static TRANSPORT: LazyLock<RwLock<Arc<dyn HttpTransport>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(ReqwestTransport)));
+
+/// 替换进程内所有插件使用的 HTTP 层。以动态库加载的插件各自持有一份，不受影响
+pub fn set_transport(transport: impl HttpTransport + 'static) {
+    *TRANSPORT.write().unwrap() = Arc::new(transport);
+}
+
+pub fn transport() -> Arc<dyn HttpTransport> {
+    TRANSPORT.read().unwrap().clone()
+}
+
+/// 通过当前的 HTTP 层发送请求，并向 `progress::track` 报告请求与响应的大小
+pub async fn execute(client: &Client, request: Request) -> Result<Response> {
+    let sent = request.body().and_then(|b| b.as_bytes()).map_or(0, |b| b.len() as u64);
+    progress::report(ProgressPhase::Sending, sent, 0);
+
+    let response = transport().execute(client, request).await?;
+    progress::report(ProgressPhase::Receiving, 0, response.content_length().unwrap_or(0));
+
+    Ok(response)
+}
+
+/// 各插件通用的 HTTP 配置，以 `#[serde(flatten)]` 方式嵌入插件配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct HttpConfig {
+    /// 单次请求超时（毫秒）
+    pub timeout_ms: Option<u64>,
+    /// 建立连接超时（毫秒）
+    pub connect_timeout_ms: Option<u64>,
+    /// 整个翻译任务的截止时间（毫秒），包含流式输出
+    pub deadline_ms: Option<u64>,
+    /// 代理设置
+    pub proxy: Option<ProxyConfig>,
+    /// 忽略 `HTTP_PROXY` 等系统代理环境变量
+    #[serde(default)]
+    pub disable_system_proxy: bool,
+    /// 附加到每个请求的 HTTP 头，如自建网关要求的 `X-Org-Id`
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// 自定义 User-Agent
+    pub user_agent: Option<String>,
+    /// 同一服务的最大并发请求数，进程内所有实例共享，见 `limit::with_limit`
+    pub max_concurrency: Option<usize>,
+    /// 宿主注入的 `_proxy`、`_timeout_ms` 等保留键
+    #[serde(flatten)]
+    pub host: HostEnv,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ProxyConfig {
+    /// 代理地址，支持 `http://`、`https://`、`socks5://` 与 `socks5h://`
+    pub url: String,
+    /// 不经过代理的主机，格式同 `NO_PROXY` 环境变量
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    /// 代理认证用户名
+    pub username: Option<String>,
+    /// 代理认证密码
+    pub password: Option<String>,
+}
+
+impl ProxyConfig {
+    pub fn build(&self) -> Result<Proxy> {
+        let mut proxy = Proxy::all(self.url.as_str())?;
+
+        if let Some(username) = &self.username {
+            proxy = proxy.basic_auth(username, self.password.as_deref().unwrap_or(""));
+        }
+
+        if !self.no_proxy.is_empty() {
+            proxy = proxy.no_proxy(NoProxy::from_string(self.no_proxy.join(",").as_str()));
+        }
+
+        Ok(proxy)
+    }
+}
+
+impl HttpConfig {
+    pub fn client_builder(&self) -> Result<ClientBuilder> {
+        let mut builder = Client::builder();
+
+        if self.disable_system_proxy {
+            builder = builder.no_proxy();
+        }
+
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.build()?);
+        } else if let Some(url) = &self.host.proxy {
+            builder = builder.proxy(Proxy::all(url.as_str())?);
+        }
+
+        if let Some(timeout) = self.timeout_ms.or(self.host.timeout_ms) {
+            builder = builder.timeout(Duration::from_millis(timeout));
+        }
+
+        if let Some(timeout) = self.connect_timeout_ms {
+            builder = builder.connect_timeout(Duration::from_millis(timeout));
+        }
+
+        for cert in self.host.certificates()? {
+            builder = builder.add_root_certificate(cert);
+        }
+
+        let mut headers = self.headers.clone();
+        if let Some(locale) = &self.host.locale {
+            if !headers.keys().any(|k| k.eq_ignore_ascii_case("accept-language")) {
+                headers.insert("Accept-Language".to_string(), locale.clone());
+            }
+        }
+
+        if !headers.is_empty() {
+            builder = builder.default_headers(to_header_map(&headers)?);
+        }
+
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+
+        Ok(builder)
+    }
+
+    /// 通过当前的 HTTP 层创建客户端，见 `set_transport`
+    pub fn build_client(&self) -> Result<Client> {
+        transport().build_client(self.client_builder()?)
+    }
+
+    /// 在配置的 HTTP 头之外附加每次请求不同的头，如请求 ID
+    pub fn build_client_with(&self, headers: &[(&str, &str)]) -> Result<Client> {
+        let mut config = self.clone();
+        for (name, value) in headers {
+            config.headers.insert(name.to_string(), value.to_string());
+        }
+        config.build_client()
+    }
+
+    pub fn deadline(&self) -> Option<Duration> {
+        self.deadline_ms.map(Duration::from_millis)
+    }
+}
+
+#[test]
+fn test_http_config_flatten() -> Result<()> {
+    #[derive(Deserialize)]
+    struct PluginConfig {
+        api_key: String,
+        #[serde(flatten, default)]
+        http: HttpConfig,
+    }
+
+    let config: PluginConfig = serde_json::from_value(serde_json::json!({
+        "api_key": "key",
+        "timeout_ms": 1000,
+        "deadline_ms": 5000,
+    }))?;
+
+    assert_eq!(config.api_key, "key");
+    assert_eq!(config.http.timeout_ms, Some(1000));
+    assert_eq!(config.http.connect_timeout_ms, None);
+    assert_eq!(config.http.deadline(), Some(Duration::from_millis(5000)));
+    config.http.build_client()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_proxy_config() -> Result<()> {
+    let config: HttpConfig = serde_json::from_value(serde_json::json!({
+        "proxy": {
+            "url": "socks5h://127.0.0.1:1080",
+            "no_proxy": ["fanyi-api.baidu.com", "*.tencentcloudapi.com"],
+            "username": "user",
+            "password": "pass",
+        },
+    }))?;
+
+    config.build_client()?;
+
+    let invalid: HttpConfig = serde_json::from_value(serde_json::json!({
+        "proxy": { "url": "not a url" },
+    }))?;
+    assert!(invalid.build_client().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_host_env_config() -> Result<()> {
+    let config: HttpConfig = serde_json::from_value(serde_json::json!({
+        "timeout_ms": 1000,
+        "_timeout_ms": 3000,
+        "_proxy": "socks5h://127.0.0.1:1080",
+        "_locale": "zh-CN",
+    }))?;
+
+    assert_eq!(config.timeout_ms, Some(1000));
+    assert_eq!(config.host.timeout_ms, Some(3000));
+    assert_eq!(config.host.locale.as_deref(), Some("zh-CN"));
+    config.build_client()?;
+
+    let invalid: HttpConfig = serde_json::from_value(serde_json::json!({
+        "_ca_bundle": "/nonexistent/ca.pem",
+    }))?;
+    assert!(invalid.build_client().is_err());
+
+    Ok(())
+}
+
+#[cfg(test)]
+struct MockTransport {
+    urls: Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl HttpTransport for MockTransport {
+    async fn execute(&self, _client: &Client, request: Request) -> Result<Response> {
+        self.urls.lock().unwrap().push(request.url().to_string());
+        Ok(http::Response::builder()
+            .status(200)
+            .body(r#"{"ok":true}"#)?
+            .into())
+    }
+}
+
+#[tokio::test]
+async fn test_transport() -> Result<()> {
+    let mock = Arc::new(MockTransport {
+        urls: Mutex::new(vec![]),
+    });
+
+    set_transport(mock.clone());
+    let client = HttpConfig::default().build_client()?;
+    let request = client.post("https://example.com/translate").build()?;
+    let resp = execute(&client, request).await;
+    set_transport(ReqwestTransport);
+
+    let json: serde_json::Value = serde_json::from_str(&resp?.text().await?)?;
+    assert_eq!(json["ok"], true);
+    assert_eq!(
+        *mock.urls.lock().unwrap(),
+        vec!["https://example.com/translate".to_string()]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,228 @@
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// 拦截器，可挂载到任意翻译器上用于日志、审计或改写请求与结果
+pub trait Interceptor: Send + Sync {
+    /// 发送请求前调用，可修改任务，返回错误时不再发送请求
+    fn on_request(&self, _task: &mut TranslateTask) -> Result<()> {
+        Ok(())
+    }
+
+    /// 收到结果后调用，可修改结果。流式翻译时对每个增量调用一次
+    fn on_response(&self, _task: &TranslateTask, _result: &mut TranslateResult) -> Result<()> {
+        Ok(())
+    }
+
+    /// 翻译失败时调用
+    fn on_error(&self, _task: &TranslateTask, _error: &anyhow::Error) {}
+}
+
+/// 按添加顺序依次调用拦截器
+pub struct InterceptedTranslator<T> {
+    inner: T,
+    interceptors: Vec<Box<dyn Interceptor>>,
+}
+
+impl<T> InterceptedTranslator<T> {
+    pub fn wrap(inner: T) -> Self {
+        InterceptedTranslator {
+            inner,
+            interceptors: vec![],
+        }
+    }
+
+    pub fn with(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.push(interceptor);
+        self
+    }
+
+    pub fn push(&mut self, interceptor: impl Interceptor + 'static) {
+        self.interceptors.push(Box::new(interceptor));
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    fn request(&self, task: &mut TranslateTask) -> Result<()> {
+        for interceptor in &self.interceptors {
+            interceptor.on_request(task)?;
+        }
+        Ok(())
+    }
+
+    fn response(&self, task: &TranslateTask, result: &mut TranslateResult) -> Result<()> {
+        for interceptor in &self.interceptors {
+            interceptor.on_response(task, result)?;
+        }
+        Ok(())
+    }
+
+    fn error(&self, task: &TranslateTask, error: &anyhow::Error) {
+        for interceptor in &self.interceptors {
+            interceptor.on_error(task, error);
+        }
+    }
+}
+
+#[async_trait]
+impl<T> Translator for InterceptedTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 拦截器只能在代码中添加，配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        Ok(InterceptedTranslator::wrap(T::new(config).await?))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn translate(&self, mut task: TranslateTask) -> Result<TranslateResult> {
+        self.request(&mut task)?;
+
+        let result = self
+            .inner
+            .translate(task.clone())
+            .await
+            .and_then(|mut result| {
+                self.response(&task, &mut result)?;
+                Ok(result)
+            });
+
+        if let Err(e) = &result {
+            self.error(&task, e);
+        }
+
+        result
+    }
+
+    async fn translate_stream(
+        &self,
+        mut task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        self.request(&mut task)?;
+
+        let (tx, mut rx) = mpsc::channel(64);
+
+        let forward = async {
+            while let Some(mut chunk) = rx.recv().await {
+                if let TranslateStreamChunk::Delta(delta) = &mut chunk {
+                    self.response(&task, delta)?;
+                }
+                sender.send(chunk).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+
+        let (result, forwarded) =
+            tokio::join!(self.inner.translate_stream(task.clone(), tx), forward);
+        let result = result.and(forwarded);
+
+        if let Err(e) = &result {
+            self.error(&task, e);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+#[cfg(test)]
+impl Interceptor for Recorder {
+    fn on_request(&self, task: &mut TranslateTask) -> Result<()> {
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("request:{}", task.content));
+        task.content = task.content.trim().to_string();
+        Ok(())
+    }
+
+    fn on_response(&self, _task: &TranslateTask, result: &mut TranslateResult) -> Result<()> {
+        self.events.lock().unwrap().push("response".to_string());
+        result.content = result.content.as_ref().map(|s| s.to_uppercase());
+        Ok(())
+    }
+
+    fn on_error(&self, _task: &TranslateTask, error: &anyhow::Error) {
+        self.events.lock().unwrap().push(format!("error:{}", error));
+    }
+}
+
+#[tokio::test]
+async fn test_intercepted_translator() -> Result<()> {
+    let recorder = Recorder::default();
+    let events = recorder.events.clone();
+    let translator = InterceptedTranslator::wrap(MockTranslator::new("t:")).with(recorder);
+
+    let result = translator.translate(task(" hello ")).await?;
+    assert_eq!(result.content.as_deref(), Some("T:HELLO"));
+
+    let (tx, mut rx) = mpsc::channel(64);
+    translator.translate_stream(task("world"), tx).await?;
+    let mut content = String::new();
+    while let Some(chunk) = rx.recv().await {
+        if let TranslateStreamChunk::Delta(delta) = chunk {
+            content.push_str(delta.content.as_deref().unwrap_or(""));
+        }
+    }
+    assert_eq!(content, "T:WORLD");
+
+    let recorder = Recorder::default();
+    let failed = recorder.events.clone();
+    let translator = InterceptedTranslator::wrap(MockTranslator::failing()).with(recorder);
+    assert!(translator.translate(task("hello")).await.is_err());
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["request: hello ", "response", "request:world", "response"]
+    );
+    assert_eq!(
+        *failed.lock().unwrap(),
+        vec!["request:hello", "error:mock failure"]
+    );
+
+    Ok(())
+}
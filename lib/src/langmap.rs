@@ -0,0 +1,196 @@
+use crate::error::XTranslateError;
+use anyhow::{bail, Result};
+use language_tags::LanguageTag;
+
+/// `LanguageTag::canonicalize` 未覆盖的常见别名
+const ALIASES: &[(&str, &str)] = &[("cmn", "zh"), ("tl", "fil")];
+
+/// 默认使用繁体中文的地区
+const HANT_REGIONS: &[&str] = &["TW", "HK", "MO"];
+
+/// 规范化语言标签，替换已废弃的子标签（如 `iw` → `he`）与常见别名，无法规范化时原样返回
+pub fn normalize(tag: &LanguageTag) -> LanguageTag {
+    let tag = tag.canonicalize().unwrap_or_else(|_| tag.clone());
+
+    let primary = tag.primary_language();
+    match ALIASES.iter().find(|(from, _)| *from == primary) {
+        Some((_, to)) => {
+            LanguageTag::parse(&format!("{}{}", to, &tag.as_str()[primary.len()..])).unwrap_or(tag)
+        }
+        None => tag,
+    }
+}
+
+/// 标签使用的文字，未写明时按地区推断，如 `zh-TW` 为 `Hant`、`zh-CN` 为 `Hans`
+pub fn script_of(tag: &LanguageTag) -> Option<&str> {
+    if let Some(script) = tag.script() {
+        return Some(script);
+    }
+
+    match (tag.primary_language(), tag.region()) {
+        ("zh", Some(region)) if HANT_REGIONS.contains(&region) => Some("Hant"),
+        ("zh", Some(_)) => Some("Hans"),
+        _ => None,
+    }
+}
+
+struct Rule<T> {
+    primary: String,
+    script: Option<String>,
+    regions: Vec<String>,
+    code: T,
+}
+
+impl<T> Rule<T> {
+    fn matches(&self, tag: &LanguageTag) -> bool {
+        self.primary == tag.primary_language()
+            && self
+                .script
+                .as_deref()
+                .is_none_or(|script| script_of(tag) == Some(script))
+            && (self.regions.is_empty()
+                || tag
+                    .region()
+                    .is_some_and(|region| self.regions.iter().any(|r| r == region)))
+    }
+
+    /// 同时命中多条规则时，限定条件越多越优先
+    fn specificity(&self) -> usize {
+        self.script.is_some() as usize + !self.regions.is_empty() as usize
+    }
+
+    fn tag(&self) -> String {
+        let mut tag = self.primary.clone();
+        if let Some(script) = &self.script {
+            tag.push('-');
+            tag.push_str(script);
+        }
+        if let Some(region) = self.regions.first() {
+            tag.push('-');
+            tag.push_str(region);
+        }
+        tag
+    }
+}
+
+/// BCP-47 标签与服务语言代码的映射表，插件声明一次后用于 `TryFrom<LanguageTag>` 与反向查询
+pub struct LangMap<T> {
+    rules: Vec<Rule<T>>,
+}
+
+impl<T> Default for LangMap<T> {
+    fn default() -> Self {
+        LangMap { rules: vec![] }
+    }
+}
+
+impl<T: Clone> LangMap<T> {
+    pub fn new() -> Self {
+        LangMap::default()
+    }
+
+    fn rule(mut self, primary: &str, script: Option<&str>, regions: &[&str], code: T) -> Self {
+        let primary = normalize(&LanguageTag::parse(primary).expect("invalid language"));
+        self.rules.push(Rule {
+            primary: primary.primary_language().to_string(),
+            script: script.map(|s| s.to_string()),
+            regions: regions.iter().map(|r| r.to_ascii_uppercase()).collect(),
+            code,
+        });
+        self
+    }
+
+    /// 按主语言匹配
+    pub fn lang(self, primary: &str, code: T) -> Self {
+        self.rule(primary, None, &[], code)
+    }
+
+    /// 限定文字，如 `zh-Hant`；未写明文字的标签按地区推断
+    pub fn script(self, primary: &str, script: &str, code: T) -> Self {
+        self.rule(primary, Some(script), &[], code)
+    }
+
+    /// 限定地区，如只支持巴西葡萄牙语时为 `pt` + `["BR"]`
+    pub fn region(self, primary: &str, regions: &[&str], code: T) -> Self {
+        self.rule(primary, None, regions, code)
+    }
+
+    pub fn get(&self, tag: &LanguageTag) -> Option<T> {
+        let tag = normalize(tag);
+
+        let mut best: Option<&Rule<T>> = None;
+        for rule in self.rules.iter().filter(|rule| rule.matches(&tag)) {
+            if best.is_none_or(|b| rule.specificity() > b.specificity()) {
+                best = Some(rule);
+            }
+        }
+
+        best.map(|rule| rule.code.clone())
+    }
+
+    /// 找不到时返回 `XTranslateError::UnsupportedLanguage`
+    pub fn map(&self, tag: &LanguageTag) -> Result<T> {
+        match self.get(tag) {
+            Some(code) => Ok(code),
+            None => bail!(XTranslateError::UnsupportedLanguage(tag.to_string())),
+        }
+    }
+
+    pub fn contains(&self, tag: &LanguageTag) -> bool {
+        self.get(tag).is_some()
+    }
+
+    /// 映射表中声明的所有标签，按声明顺序去重
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = vec![];
+        for tag in self.rules.iter().map(|rule| rule.tag()) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+}
+
+impl<T: Clone + PartialEq> LangMap<T> {
+    /// 反向查询，返回服务语言代码第一次声明时对应的标签
+    pub fn tag(&self, code: &T) -> Option<LanguageTag> {
+        self.rules
+            .iter()
+            .find(|rule| &rule.code == code)
+            .and_then(|rule| LanguageTag::parse(&rule.tag()).ok())
+    }
+}
+
+#[test]
+fn test_lang_map() {
+    let map = LangMap::new()
+        .lang("zh", "zh")
+        .script("zh", "Hant", "cht")
+        .region("pt", &["BR"], "pt")
+        .lang("he", "he")
+        .lang("fil", "fil");
+
+    let get = |tag: &str| map.get(&LanguageTag::parse(tag).unwrap());
+
+    assert_eq!(get("zh"), Some("zh"));
+    assert_eq!(get("zh-CN"), Some("zh"));
+    assert_eq!(get("zh-TW"), Some("cht"));
+    assert_eq!(get("zh-Hant"), Some("cht"));
+    assert_eq!(get("zh-Hans-HK"), Some("zh"));
+    assert_eq!(get("cmn-Hant"), Some("cht"));
+    assert_eq!(get("pt-BR"), Some("pt"));
+    assert_eq!(get("pt-PT"), None);
+    assert_eq!(get("iw"), Some("he"));
+    assert_eq!(get("tl"), Some("fil"));
+
+    let err = map.map(&LanguageTag::parse("ja").unwrap()).unwrap_err();
+    assert_eq!(
+        XTranslateError::find(&err),
+        Some(&XTranslateError::UnsupportedLanguage("ja".to_string()))
+    );
+
+    assert_eq!(map.tag(&"cht").unwrap().as_str(), "zh-Hant");
+    assert_eq!(map.tag(&"pt").unwrap().as_str(), "pt-BR");
+    assert_eq!(map.tags(), vec!["zh", "zh-Hant", "pt-BR", "he", "fil"]);
+}
@@ -0,0 +1,221 @@
+use crate::chunk::{chunk, ChunkConfig, ChunkedTranslator};
+use crate::error::XTranslateError;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::Sender;
+
+/// 原文超出长度限制时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LengthStrategy {
+    /// 返回 `XTranslateError::InputTooLong`
+    #[default]
+    Error,
+    /// 在句子或空白处截断，只翻译不超出限制的部分
+    Truncate,
+    /// 由 `chunk::ChunkedTranslator` 分段翻译
+    Split,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LengthConfig {
+    /// 最多字符数，未设置时使用翻译器 `capabilities().max_input_chars`
+    pub max_chars: Option<usize>,
+    pub strategy: LengthStrategy,
+    /// 分段参数，仅在 `split` 时生效，其中的 `max_chars` 会被上面的设置覆盖
+    pub chunk: ChunkConfig,
+}
+
+/// 在发送前统一检查原文长度，避免服务端静默截断或超出模型上下文
+pub struct LengthLimitedTranslator<T> {
+    inner: ChunkedTranslator<T>,
+    config: LengthConfig,
+}
+
+impl<T: Translator> LengthLimitedTranslator<T> {
+    pub fn wrap(inner: T, config: LengthConfig) -> Self {
+        let chunk_config = ChunkConfig {
+            max_chars: config.max_chars.or(config.chunk.max_chars),
+            ..config.chunk.clone()
+        };
+
+        LengthLimitedTranslator {
+            inner: ChunkedTranslator::wrap(inner, chunk_config),
+            config,
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        self.inner.inner()
+    }
+
+    fn max_chars(&self) -> Option<usize> {
+        self.config
+            .max_chars
+            .or(self.inner().capabilities().max_input_chars)
+    }
+
+    /// 未超出限制时返回 `None`，按 `error` 处理时返回错误，否则返回截断后的任务
+    fn check(&self, task: &TranslateTask) -> Result<Option<TranslateTask>> {
+        let Some(max) = self.max_chars() else {
+            return Ok(None);
+        };
+        let length = task.content.chars().count();
+        if length <= max {
+            return Ok(None);
+        }
+
+        match self.config.strategy {
+            LengthStrategy::Error => bail!(XTranslateError::InputTooLong { length, max }),
+            LengthStrategy::Truncate => {
+                let mut task = task.clone();
+                task.content = chunk(&task.content, max)
+                    .first()
+                    .map(|c| c.content())
+                    .unwrap_or_default();
+                Ok(Some(task))
+            }
+            LengthStrategy::Split => Ok(Some(task.clone())),
+        }
+    }
+}
+
+#[async_trait]
+impl<T> Translator for LengthLimitedTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 长度参数读取自 `config["length"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let length_config = match config.get("length") {
+            Some(length) => serde_json::from_value(length.clone())?,
+            None => LengthConfig::default(),
+        };
+
+        Ok(LengthLimitedTranslator::wrap(
+            T::new(config).await?,
+            length_config,
+        ))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner().get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner().get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner().is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner().is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner().is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner().get_supported_pairs()
+    }
+
+    /// 截断或分段后不再有长度限制
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = self.inner().capabilities();
+        capabilities.max_input_chars = match self.config.strategy {
+            LengthStrategy::Error => self.max_chars(),
+            LengthStrategy::Truncate | LengthStrategy::Split => None,
+        };
+        capabilities
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        match self.check(&task)? {
+            None => self.inner().translate(task).await,
+            Some(task) if self.config.strategy == LengthStrategy::Split => {
+                self.inner.translate(task).await
+            }
+            Some(task) => self.inner().translate(task).await,
+        }
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        match self.check(&task)? {
+            None => self.inner().translate_stream(task, sender).await,
+            Some(task) if self.config.strategy == LengthStrategy::Split => {
+                self.inner.translate_stream(task, sender).await
+            }
+            Some(task) => self.inner().translate_stream(task, sender).await,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_length_limited_translator() -> Result<()> {
+    let content = "First sentence. Second sentence. Third one here.";
+    let translator = |strategy| {
+        LengthLimitedTranslator::wrap(
+            MockTranslator::new("T:"),
+            LengthConfig {
+                max_chars: Some(20),
+                strategy,
+                ..Default::default()
+            },
+        )
+    };
+
+    let err = translator(LengthStrategy::Error)
+        .translate(task(content))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        XTranslateError::find(&err),
+        Some(&XTranslateError::InputTooLong {
+            length: 48,
+            max: 20
+        })
+    );
+    assert_eq!(
+        translator(LengthStrategy::Error)
+            .capabilities()
+            .max_input_chars,
+        Some(20)
+    );
+
+    let result = translator(LengthStrategy::Truncate)
+        .translate(task(content))
+        .await?;
+    assert_eq!(result.content.as_deref(), Some("T:First sentence. "));
+
+    let split = translator(LengthStrategy::Split);
+    let result = split.translate(task(content)).await?;
+    assert_eq!(
+        result.content.as_deref(),
+        Some("T:First sentence. T:Second sentence. T:Third one here.")
+    );
+    assert_eq!(split.inner().calls(), 3);
+    assert_eq!(split.capabilities().max_input_chars, None);
+
+    // 未超出长度时直接透传
+    let result = translator(LengthStrategy::Error)
+        .translate(task("Short."))
+        .await?;
+    assert_eq!(result.content.as_deref(), Some("T:Short."));
+
+    Ok(())
+}
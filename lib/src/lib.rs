@@ -1,6 +1,57 @@
 pub mod utils;
 pub mod ffi;
 pub mod ffi_proxy;
+pub mod budget;
+pub mod cache;
+pub mod chunk;
+pub mod circuit;
+pub mod cleanup;
+pub mod composite;
+pub mod cost;
+pub mod credential;
+pub mod deadline;
+pub mod dedup;
+pub mod detect;
+pub mod ensemble;
+pub mod error;
+pub mod events;
+pub mod fallback;
+pub mod fewshot;
+pub mod formats;
+pub mod glossary;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod host_env;
+pub mod html;
+pub mod http;
+pub mod intercept;
+pub mod langmap;
+pub mod length;
+pub mod limit;
+pub mod manifest;
+pub mod markdown;
+pub mod normalize;
+pub mod options;
+pub mod pii;
+pub mod pipeline;
+pub mod placeholder;
+pub mod plugin_log;
+pub mod pool;
+pub mod preset;
+pub mod progress;
+pub mod qe;
+pub mod request;
+pub mod retry;
+pub mod secrets;
+pub mod subprocess;
+pub mod terms;
+pub mod trace;
+pub mod validate;
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(test)]
+mod testing;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -39,16 +90,135 @@ pub struct TranslateTask {
     pub terms: Vec<TranslatedItem>,
     /// 参考译文
     pub references: Vec<TranslatedItem>,
-    /// 扩展数据
+    /// 扩展数据，插件专属参数放在以插件名为键的对象中，见 `options::ProviderOptions`
     pub extra: Option<Value>,
+    /// 请求 ID，用于幂等与日志关联，未设置时由插件生成，见 `request::ensure_request_id`
+    #[serde(default)]
+    #[builder(default)]
+    pub request_id: Option<String>,
+    /// 截止时间（毫秒），到期后流式翻译返回已产出的部分译文，见 `deadline::DeadlineTranslator`
+    #[serde(default)]
+    #[builder(default)]
+    pub deadline_ms: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TranslateResult {
     pub reasoning: Option<String>,
     pub content: Option<String>,
+    /// 实际产出译文的翻译服务
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// 本次请求的 ID，与 `TranslateTask::request_id` 相同
+    #[serde(default)]
+    pub request_id: Option<String>,
+    /// 译文不完整，如到达截止时间时已产出的部分
+    #[serde(default)]
+    pub partial: bool,
+    /// 置信度，取值 0~1，越高越好。服务未提供时由 `qe::annotate` 按质量评估填写
+    #[serde(default)]
+    pub confidence: Option<f32>,
+    /// 术语遵循情况
+    #[serde(default)]
+    pub glossary: Option<glossary::TermCompliance>,
+    /// 质量评估
+    #[serde(default)]
+    pub quality: Option<qe::QualityEstimate>,
+    /// 各服务的候选译文，见 `ensemble::EnsembleTranslator`
+    #[serde(default)]
+    pub alternatives: Vec<ensemble::Candidate>,
+    /// 本次翻译的资源用量
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+impl TranslateResult {
+    /// 累加用量，用于合并多次请求的结果
+    pub fn add_usage(&mut self, usage: &Usage) {
+        self.usage.get_or_insert_with(Usage::default).add(usage);
+    }
+
+    /// 合并多次请求的置信度，取最低值
+    pub fn add_confidence(&mut self, confidence: Option<f32>) {
+        if let Some(confidence) = confidence {
+            self.confidence = Some(self.confidence.map_or(confidence, |c| c.min(confidence)));
+        }
+    }
+}
+
+/// 资源用量
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    /// 输入 token 数
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    /// 输出 token 数
+    #[serde(default)]
+    pub completion_tokens: u64,
+    /// 计费字符数
+    #[serde(default)]
+    pub characters: u64,
+    /// 请求耗时（毫秒）
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    /// 服务端返回的请求 ID
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+impl Usage {
+    /// 累加另一份用量，耗时相加
+    pub fn add(&mut self, other: &Usage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.characters += other.characters;
+        if let Some(latency_ms) = other.latency_ms {
+            *self.latency_ms.get_or_insert(0) += latency_ms;
+        }
+        // 多次请求合并时保留第一个请求 ID
+        if self.request_id.is_none() {
+            self.request_id = other.request_id.clone();
+        }
+    }
 }
 
+/// 翻译服务的能力描述，宿主据此决定传入哪些参数，未声明的能力按不支持处理
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Capabilities {
+    /// 服务端原生支持流式输出，否则 `translate_stream` 只会一次性返回整段译文
+    pub supports_streaming: bool,
+    /// 支持 `TranslateTask::terms`
+    pub supports_terms: bool,
+    /// 单次请求最多使用的术语数，超出部分会被忽略
+    pub max_terms: Option<usize>,
+    /// 支持 `TranslateTask::references`
+    pub supports_references: bool,
+    /// 单次请求最多使用的参考译文数
+    pub max_references: Option<usize>,
+    /// 支持 `TranslateTask::field`
+    pub supports_field: bool,
+    /// 支持 `TranslateTask::user_prompt` 与 `TranslateTask::system_prompt`
+    pub supports_prompt: bool,
+    /// 可以不指定源语言，由服务自动识别
+    pub supports_auto_detect: bool,
+    /// 必须指定目标语言
+    pub needs_target_language: bool,
+    /// 单次请求最多支持的字符数，超出时可由 `length::LengthLimitedTranslator` 报错、截断或分段翻译
+    pub max_input_chars: Option<usize>,
+}
+
+/// 不依赖配置的语言与能力，见 `Translator::static_info`。与配置有关的字段为 `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StaticInfo {
+    pub input_languages: Option<Vec<String>>,
+    pub output_languages: Option<Vec<String>>,
+    pub capabilities: Option<Capabilities>,
+}
+
+// 增量是最常见的消息，装箱只会增加每个增量的分配
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize)]
 pub enum TranslateStreamChunk {
     Start,
@@ -56,6 +226,7 @@ pub enum TranslateStreamChunk {
     End,
 }
 
+/// 翻译器接口。可识别的错误应包装为 `error::XTranslateError`，便于调用方区分可重试与不可重试的失败
 #[async_trait]
 pub trait Translator {
     type This;
@@ -63,6 +234,21 @@ pub trait Translator {
     /// 创建翻译实例
     async fn new(config: Value) -> Result<Self::This>;
 
+    /// 配置的 JSON Schema，供界面自动生成配置表单
+    fn config_schema() -> Option<Value> {
+        None
+    }
+
+    /// 在不创建实例、不发起网络请求的情况下检查配置，返回发现的问题
+    fn validate_config(_config: &Value) -> Result<Vec<validate::ConfigIssue>> {
+        Ok(vec![])
+    }
+
+    /// 不创建实例即可读取的语言与能力，供发现插件时展示。必填配置的翻译器无法以空配置创建，应尽量提供
+    fn static_info() -> StaticInfo {
+        StaticInfo::default()
+    }
+
     /// 获取支持的源语言列表
     fn get_supported_input_languages(&self) -> Result<Vec<String>>;
 
@@ -75,6 +261,24 @@ pub trait Translator {
     /// 是否支持该语言作为目标语言
     fn is_supported_output_language(&self, lang: String) -> Result<bool>;
 
+    /// 是否支持从 `source` 翻译到 `target`，只支持部分语言组合的服务需要重写
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        Ok(self.is_supported_input_language(source)? && self.is_supported_output_language(target)?)
+    }
+
+    /// 获取支持的语言对，默认为源语言与目标语言两两组合，不含相同语言
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        Ok(utils::language_pairs(
+            &self.get_supported_input_languages()?,
+            &self.get_supported_output_languages()?,
+        ))
+    }
+
+    /// 能力描述
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
     /// 翻译
     async fn translate(&self, task: TranslateTask) -> Result<TranslateResult>;
 
@@ -85,3 +289,100 @@ pub trait Translator {
         sender: Sender<TranslateStreamChunk>,
     ) -> Result<()>;
 }
+
+/// 对象安全的翻译器接口，所有 `Translator` 均自动实现，用于组合不同类型的翻译器
+#[async_trait]
+pub trait DynTranslator: Send + Sync {
+    fn get_supported_input_languages(&self) -> Result<Vec<String>>;
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>>;
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool>;
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool>;
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool>;
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>>;
+
+    fn capabilities(&self) -> Capabilities;
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult>;
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()>;
+}
+
+#[async_trait]
+impl<T: Translator + Send + Sync> DynTranslator for T {
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        Translator::get_supported_input_languages(self)
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        Translator::get_supported_output_languages(self)
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        Translator::is_supported_input_language(self, lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        Translator::is_supported_output_language(self, lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        Translator::is_supported_pair(self, source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        Translator::get_supported_pairs(self)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Translator::capabilities(self)
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        Translator::translate(self, task).await
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        Translator::translate_stream(self, task, sender).await
+    }
+}
+
+pub type BoxedTranslator = Box<dyn DynTranslator>;
+
+/// 组合翻译器配置中的子翻译器描述
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslatorSpec {
+    /// 插件名称
+    pub name: String,
+    /// 传给插件的配置
+    #[serde(default)]
+    pub config: Value,
+}
+
+/// 按名称和配置创建翻译器
+#[async_trait]
+pub trait TranslatorFactory: Send + Sync {
+    async fn create(&self, name: &str, config: Value) -> Result<BoxedTranslator>;
+}
+
+#[test]
+fn test_capabilities() {
+    let capabilities: Capabilities =
+        serde_json::from_str(r#"{"supports_terms": true, "max_terms": 10}"#).unwrap();
+    assert!(capabilities.supports_terms);
+    assert_eq!(capabilities.max_terms, Some(10));
+    assert!(!capabilities.supports_streaming);
+    assert_eq!(capabilities.max_input_chars, None);
+}
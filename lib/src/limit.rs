@@ -0,0 +1,68 @@
+use anyhow::Result;
+#[cfg(test)]
+use futures_util::future::try_join_all;
+use std::collections::HashMap;
+use std::future::Future;
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+#[cfg(test)]
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+static LIMITERS: LazyLock<Mutex<HashMap<String, Arc<Semaphore>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 取得服务共享的信号量。同一服务的上限以第一次设置的值为准
+pub fn limiter(provider: &str, max_concurrency: usize) -> Arc<Semaphore> {
+    LIMITERS
+        .lock()
+        .unwrap()
+        .entry(provider.to_string())
+        .or_insert_with(|| Arc::new(Semaphore::new(max_concurrency.max(1))))
+        .clone()
+}
+
+/// 在服务的并发上限内执行 `fut`，未设置上限时直接执行。
+/// 流式翻译会在整个流结束后才释放名额
+pub async fn with_limit<T>(
+    provider: &str,
+    max_concurrency: Option<usize>,
+    fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    match max_concurrency {
+        Some(max) => {
+            let _permit = limiter(provider, max).acquire_owned().await?;
+            fut.await
+        }
+        None => fut.await,
+    }
+}
+
+#[tokio::test]
+async fn test_with_limit() -> Result<()> {
+    let running = AtomicUsize::new(0);
+    let peak = AtomicUsize::new(0);
+
+    let task = || async {
+        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+        peak.fetch_max(now, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        running.fetch_sub(1, Ordering::SeqCst);
+        Ok(())
+    };
+
+    let tasks: Vec<_> = (0..6)
+        .map(|_| with_limit("test_with_limit", Some(2), task()))
+        .collect();
+    try_join_all(tasks).await?;
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+
+    // 其他服务不受影响
+    peak.store(0, Ordering::SeqCst);
+    let tasks: Vec<_> = (0..3).map(|_| with_limit("other", None, task())).collect();
+    try_join_all(tasks).await?;
+    assert_eq!(peak.load(Ordering::SeqCst), 3);
+
+    Ok(())
+}
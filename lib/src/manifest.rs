@@ -0,0 +1,137 @@
+use crate::ffi::{parse_version, ABI_VERSION, LIB_VERSION};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// 插件目录中依次查找的清单文件
+pub const MANIFEST_FILES: [&str; 2] = ["plugin.toml", "plugin.json"];
+
+/// 动态库旁的插件清单，发现插件时据此过滤，不需要打开动态库
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// 描述的动态库文件名，省略时描述同一目录中的所有动态库
+    #[serde(default)]
+    pub library: Option<String>,
+    /// 插件中的翻译器名称，第一个为默认翻译器，省略时只有 `name`
+    #[serde(default)]
+    pub translators: Vec<String>,
+    /// 配置 schema 的 JSON 文件，相对于清单所在目录
+    #[serde(default)]
+    pub config_schema: Option<PathBuf>,
+    /// 插件要求的最低 FFI 版本
+    #[serde(default)]
+    pub min_abi_version: Option<u32>,
+    /// 插件要求的最低宿主 lib 版本
+    #[serde(default)]
+    pub min_host_version: Option<String>,
+    /// 清单所在目录
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+impl PluginManifest {
+    /// 读取清单文件，按扩展名解析 TOML 或 JSON
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let mut manifest: PluginManifest = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&content)?,
+            _ => serde_json::from_str(&content)?,
+        };
+        manifest.dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        Ok(manifest)
+    }
+
+    /// 读取动态库所在目录中的清单，没有清单时返回 `None`
+    pub fn find(library: &Path) -> Result<Option<Self>> {
+        let Some(dir) = library.parent() else {
+            return Ok(None);
+        };
+
+        for file in MANIFEST_FILES {
+            let path = dir.join(file);
+            if path.is_file() {
+                let manifest = Self::from_file(&path)?;
+                return Ok(manifest.describes(library).then_some(manifest));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn describes(&self, library: &Path) -> bool {
+        match &self.library {
+            Some(file) => library.file_name().and_then(|f| f.to_str()) == Some(file.as_str()),
+            None => true,
+        }
+    }
+
+    pub fn translators(&self) -> Vec<String> {
+        if self.translators.is_empty() {
+            vec![self.name.clone()]
+        } else {
+            self.translators.clone()
+        }
+    }
+
+    /// 宿主的 FFI 与 lib 版本满足插件要求
+    pub fn is_compatible(&self) -> bool {
+        self.min_abi_version.is_none_or(|version| version <= ABI_VERSION)
+            && self
+                .min_host_version
+                .as_ref()
+                .is_none_or(|version| parse_version(LIB_VERSION) >= parse_version(version))
+    }
+
+    /// 读取清单中指定的配置 schema
+    pub fn config_schema(&self) -> Result<Option<Value>> {
+        let Some(path) = &self.config_schema else {
+            return Ok(None);
+        };
+
+        let content = std::fs::read_to_string(self.dir.join(path))
+            .map_err(|e| anyhow!("failed to read config schema {}: {}", path.display(), e))?;
+
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+}
+
+#[test]
+fn test_plugin_manifest() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("xtranslator-manifest-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(
+        dir.join("plugin.toml"),
+        r#"
+name = "demo"
+version = "1.2.0"
+library = "libdemo.so"
+translators = ["demo", "demo_fast"]
+config_schema = "schema.json"
+min_abi_version = 1
+"#,
+    )?;
+    std::fs::write(dir.join("schema.json"), r#"{"type":"object"}"#)?;
+
+    let manifest = PluginManifest::find(&dir.join("libdemo.so"))?.unwrap();
+    assert_eq!(manifest.version.as_deref(), Some("1.2.0"));
+    assert_eq!(manifest.translators(), vec!["demo", "demo_fast"]);
+    assert!(manifest.is_compatible());
+    assert_eq!(manifest.config_schema()?, Some(serde_json::json!({ "type": "object" })));
+    // 清单指定了动态库时不描述其他文件
+    assert!(PluginManifest::find(&dir.join("libother.so"))?.is_none());
+
+    std::fs::remove_file(dir.join("plugin.toml"))?;
+    std::fs::write(dir.join("plugin.json"), r#"{"name":"demo","min_abi_version":99}"#)?;
+    let manifest = PluginManifest::find(&dir.join("libother.so"))?.unwrap();
+    assert_eq!(manifest.translators(), vec!["demo"]);
+    assert!(!manifest.is_compatible());
+
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
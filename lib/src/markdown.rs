@@ -0,0 +1,275 @@
+use crate::pipeline::Processor;
+#[cfg(test)]
+use crate::testing::task;
+use crate::{TranslateResult, TranslateTask};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::LazyLock;
+
+/// 行内需要保护的内容：行内代码、链接地址、引用式链接定义、自动链接、裸 URL、HTML 注释与标签。
+/// 链接只保护括号中的地址部分，链接文字仍然翻译
+static INLINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(`+)[^`]+?(?:`+)",
+        r#"|\]\(\s*[^)\s]*(?:\s+"[^"]*")?\s*\)"#,
+        r"|(?m)^[ ]{0,3}\[[^\]]+\]:\s*\S+.*$",
+        r"|<[A-Za-z][A-Za-z0-9+.\-]*://[^>\s]+>",
+        r"|https?://[^\s<>()\[\]]+",
+        r"|<!--[\s\S]*?-->",
+        r"|</?[A-Za-z][^>\n]*>",
+    ))
+    .unwrap()
+});
+
+/// 译文中的哨兵，与占位符处理使用不同的形式以便同时使用
+static SENTINEL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"⟦\s*M\s*(\d+)\s*⟧").unwrap());
+
+fn sentinel(index: usize) -> String {
+    format!("⟦M{}⟧", index)
+}
+
+/// 保护后的文本与被保护的原始内容，第 `i` 项对应哨兵 `⟦Mi⟧`
+#[derive(Debug, Clone, Default)]
+pub struct Protected {
+    pub text: String,
+    pub items: Vec<String>,
+}
+
+impl Protected {
+    fn protect(&mut self, raw: &str) {
+        self.items.push(raw.to_string());
+        self.text.push_str(&sentinel(self.items.len() - 1));
+    }
+
+    fn protect_inline(&mut self, text: &str) {
+        let mut last = 0;
+        for m in INLINE.find_iter(text) {
+            self.text.push_str(&text[last..m.start()]);
+
+            // 正则不支持后行断言，链接地址的匹配带有前面的 `]`
+            match m.as_str().strip_prefix(']') {
+                Some(destination) => {
+                    self.text.push(']');
+                    self.protect(destination);
+                }
+                None => self.protect(m.as_str()),
+            }
+            last = m.end();
+        }
+        self.text.push_str(&text[last..]);
+    }
+}
+
+/// 返回代码围栏的标记字符与长度
+fn fence(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+
+    let c = trimmed.chars().next()?;
+    if c != '`' && c != '~' {
+        return None;
+    }
+
+    let len = trimmed.chars().take_while(|&x| x == c).count();
+    (len >= 3).then_some((c, len))
+}
+
+/// 保护 front matter、代码块以及行内的代码、链接和 HTML
+pub fn protect(markdown: &str) -> Protected {
+    let mut protected = Protected::default();
+    let mut rest = markdown;
+
+    // front matter 只出现在文档开头
+    for delimiter in ["---", "+++"] {
+        if rest.starts_with(&format!("{}\n", delimiter)) {
+            let closing = format!("\n{}\n", delimiter);
+            let end = rest[delimiter.len()..]
+                .find(&closing)
+                .map(|i| delimiter.len() + i + closing.len());
+            if let Some(end) = end {
+                protected.protect(&rest[..end]);
+                rest = &rest[end..];
+            }
+        }
+    }
+
+    let mut prose = String::new();
+    let mut code = String::new();
+    let mut open: Option<(char, usize)> = None;
+
+    for line in rest.split_inclusive('\n') {
+        match open {
+            None => match fence(line) {
+                Some(marker) => {
+                    protected.protect_inline(&std::mem::take(&mut prose));
+                    code.push_str(line);
+                    open = Some(marker);
+                }
+                None => prose.push_str(line),
+            },
+            Some((c, len)) => {
+                let closing = fence(line)
+                    .map(|(x, l)| x == c && l >= len && line.trim().chars().all(|y| y == c))
+                    .unwrap_or(false);
+
+                if closing {
+                    // 结束围栏之后的换行留在正文中
+                    let content = line.trim_end_matches(['\r', '\n']);
+                    code.push_str(content);
+                    protected.protect(&std::mem::take(&mut code));
+                    protected.text.push_str(&line[content.len()..]);
+                    open = None;
+                } else {
+                    code.push_str(line);
+                }
+            }
+        }
+    }
+
+    // 未闭合的代码块一直延续到文末
+    if !code.is_empty() {
+        protected.protect(&code);
+    }
+    protected.protect_inline(&prose);
+
+    protected
+}
+
+/// 还原被保护的内容，返回还原后的文本与丢失的内容
+pub fn restore(text: &str, items: &[String]) -> (String, Vec<String>) {
+    let mut used = vec![false; items.len()];
+
+    let restored = SENTINEL
+        .replace_all(text, |caps: &regex::Captures| {
+            let index: usize = caps[1].parse().unwrap_or(usize::MAX);
+            match items.get(index) {
+                Some(item) => {
+                    used[index] = true;
+                    item.clone()
+                }
+                None => caps[0].to_string(),
+            }
+        })
+        .to_string();
+
+    let missing = items
+        .iter()
+        .zip(used)
+        .filter(|(_, used)| !used)
+        .map(|(item, _)| item.clone())
+        .collect();
+
+    (restored, missing)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkdownConfig {
+    /// 译文丢失代码块、链接等内容时返回错误
+    #[serde(default = "default_strict")]
+    pub strict: bool,
+}
+
+fn default_strict() -> bool {
+    true
+}
+
+impl Default for MarkdownConfig {
+    fn default() -> Self {
+        MarkdownConfig {
+            strict: default_strict(),
+        }
+    }
+}
+
+/// 翻译前保护 Markdown 中不应翻译的内容，翻译后还原
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownProcessor {
+    config: MarkdownConfig,
+}
+
+impl MarkdownProcessor {
+    pub fn new(config: Value) -> Result<Self> {
+        let config = if config.is_null() {
+            MarkdownConfig::default()
+        } else {
+            serde_json::from_value(config)?
+        };
+
+        Ok(MarkdownProcessor { config })
+    }
+}
+
+#[async_trait]
+impl Processor for MarkdownProcessor {
+    async fn pre_process(&self, task: &mut TranslateTask) -> Result<()> {
+        task.content = protect(&task.content).text;
+        Ok(())
+    }
+
+    async fn post_process(&self, task: &TranslateTask, result: &mut TranslateResult) -> Result<()> {
+        let protected = protect(&task.content);
+
+        if let Some(content) = &result.content {
+            let (restored, missing) = restore(content, &protected.items);
+            if self.config.strict && !missing.is_empty() {
+                bail!("markdown check failed, missing: {:?}", missing);
+            }
+            result.content = Some(restored);
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_protect() {
+    let markdown = "---\ntitle: Hello\n---\n# Install\n\nRun `cargo build` or see [the docs](https://docs.rs \"Docs\").\n\n```rust\nfn main() {}\n```\n\nVisit https://example.com <br> now.\n\n[docs]: https://docs.rs\n";
+
+    let protected = protect(markdown);
+    assert_eq!(
+        protected.text,
+        "⟦M0⟧# Install\n\nRun ⟦M1⟧ or see [the docs]⟦M2⟧.\n\n⟦M3⟧\n\nVisit ⟦M4⟧ ⟦M5⟧ now.\n\n⟦M6⟧\n"
+    );
+    assert_eq!(protected.items[0], "---\ntitle: Hello\n---\n");
+    assert_eq!(protected.items[3], "```rust\nfn main() {}\n```");
+
+    let (restored, missing) = restore(&protected.text, &protected.items);
+    assert_eq!(restored, markdown);
+    assert!(missing.is_empty());
+}
+
+#[tokio::test]
+async fn test_markdown_processor() -> Result<()> {
+    let processor = MarkdownProcessor::new(Value::Null)?;
+    let original = task("Use `x` here\n\n~~~\ncode\n~~~");
+
+    let mut protected = original.clone();
+    processor.pre_process(&mut protected).await?;
+    assert_eq!(protected.content, "Use ⟦M0⟧ here\n\n⟦M1⟧");
+
+    let mut result = TranslateResult {
+        content: Some("在这里使用 ⟦ M0 ⟧\n\n⟦M1⟧".to_string()),
+        ..Default::default()
+    };
+    processor.post_process(&original, &mut result).await?;
+    assert_eq!(
+        result.content.as_deref(),
+        Some("在这里使用 `x`\n\n~~~\ncode\n~~~")
+    );
+
+    let mut dropped = TranslateResult {
+        content: Some("在这里使用".to_string()),
+        ..Default::default()
+    };
+    assert!(processor
+        .post_process(&original, &mut dropped)
+        .await
+        .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use crate::pipeline::Processor;
+#[cfg(test)]
+use crate::testing::task;
+use crate::TranslateTask;
+use anyhow::Result;
+use async_trait::async_trait;
+use icu_normalizer::ComposingNormalizer;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 全角与半角转换
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidthMode {
+    /// 保持不变
+    #[default]
+    Keep,
+    /// 全角英文字母、数字、标点与空格转为半角
+    Halfwidth,
+    /// 半角英文字母、数字、标点与空格转为全角
+    Fullwidth,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NormalizeConfig {
+    /// 转换为 Unicode NFC
+    pub nfc: bool,
+    /// 去掉除换行、回车、制表符外的控制字符
+    pub strip_controls: bool,
+    /// 去掉开头的 BOM
+    pub strip_bom: bool,
+    pub width: WidthMode,
+}
+
+impl Default for NormalizeConfig {
+    fn default() -> Self {
+        NormalizeConfig {
+            nfc: true,
+            strip_controls: true,
+            strip_bom: true,
+            width: WidthMode::default(),
+        }
+    }
+}
+
+fn to_halfwidth(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        _ => c,
+    }
+}
+
+fn to_fullwidth(c: char) -> char {
+    match c {
+        ' ' => '\u{3000}',
+        '!'..='~' => char::from_u32(c as u32 + 0xFEE0).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// 按配置规范化文本
+pub fn normalize_text(text: &str, config: &NormalizeConfig) -> String {
+    let text = if config.strip_bom {
+        text.trim_start_matches('\u{FEFF}')
+    } else {
+        text
+    };
+
+    let text = if config.nfc {
+        ComposingNormalizer::new_nfc().normalize(text)
+    } else {
+        text.to_string()
+    };
+
+    text.chars()
+        .filter(|&c| !config.strip_controls || !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+        .map(|c| match config.width {
+            WidthMode::Keep => c,
+            WidthMode::Halfwidth => to_halfwidth(c),
+            WidthMode::Fullwidth => to_fullwidth(c),
+        })
+        .collect()
+}
+
+/// 翻译前规范化原文，部分服务无法正确处理未规范化的输入
+#[derive(Debug, Clone, Default)]
+pub struct NormalizeProcessor {
+    config: NormalizeConfig,
+}
+
+impl NormalizeProcessor {
+    pub fn new(config: Value) -> Result<Self> {
+        let config = if config.is_null() {
+            NormalizeConfig::default()
+        } else {
+            serde_json::from_value(config)?
+        };
+
+        Ok(NormalizeProcessor { config })
+    }
+}
+
+#[async_trait]
+impl Processor for NormalizeProcessor {
+    async fn pre_process(&self, task: &mut TranslateTask) -> Result<()> {
+        task.content = normalize_text(&task.content, &self.config);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_normalize_text() {
+    let config = NormalizeConfig::default();
+    assert_eq!(
+        normalize_text("\u{FEFF}Cafe\u{301}\u{0}\u{7}\tok\r\n", &config),
+        "Café\tok\r\n"
+    );
+    assert_eq!(
+        normalize_text("ＡＢＣ　１２３！", &config),
+        "ＡＢＣ　１２３！"
+    );
+
+    let config = NormalizeConfig {
+        width: WidthMode::Halfwidth,
+        ..Default::default()
+    };
+    assert_eq!(
+        normalize_text("ＡＢＣ　１２３！你好", &config),
+        "ABC 123!你好"
+    );
+
+    let config = NormalizeConfig {
+        width: WidthMode::Fullwidth,
+        ..Default::default()
+    };
+    assert_eq!(normalize_text("A1 !", &config), "Ａ１　！");
+
+    let config = NormalizeConfig {
+        nfc: false,
+        strip_controls: false,
+        strip_bom: false,
+        width: WidthMode::Keep,
+    };
+    let raw = "\u{FEFF}e\u{301}\u{7}";
+    assert_eq!(normalize_text(raw, &config), raw);
+}
+
+#[tokio::test]
+async fn test_normalize_processor() -> Result<()> {
+    let processor = NormalizeProcessor::new(serde_json::json!({ "width": "halfwidth" }))?;
+    let mut task = task("\u{FEFF}Ｈｅｌｌｏ\u{1B}");
+    processor.pre_process(&mut task).await?;
+    assert_eq!(task.content, "Hello");
+    Ok(())
+}
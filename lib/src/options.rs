@@ -0,0 +1,64 @@
+use crate::error::XTranslateError;
+use crate::TranslateTask;
+use anyhow::{bail, Result};
+use serde::de::DeserializeOwned;
+
+/// 插件专属的任务参数，读取自 `task.extra["<NAMESPACE>"]`。
+/// 实现时应加上 `#[serde(deny_unknown_fields)]`，拼错的参数名会返回错误而不是被忽略
+pub trait ProviderOptions: DeserializeOwned + Default {
+    /// `task.extra` 中的键名，与插件名相同
+    const NAMESPACE: &'static str;
+
+    /// 未设置时返回默认值，格式错误时返回 `XTranslateError::InvalidConfig`
+    fn from_task(task: &TranslateTask) -> Result<Self> {
+        let Some(value) = task.extra.as_ref().and_then(|e| e.get(Self::NAMESPACE)) else {
+            return Ok(Self::default());
+        };
+        if value.is_null() {
+            return Ok(Self::default());
+        }
+
+        match serde_json::from_value(value.clone()) {
+            Ok(options) => Ok(options),
+            Err(e) => bail!(XTranslateError::InvalidConfig(format!(
+                "extra.{}: {}",
+                Self::NAMESPACE,
+                e
+            ))),
+        }
+    }
+}
+
+#[test]
+fn test_provider_options() -> Result<()> {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Default, PartialEq, Deserialize)]
+    #[serde(rename_all = "camelCase", deny_unknown_fields)]
+    struct Options {
+        polish_option: Option<String>,
+    }
+
+    impl ProviderOptions for Options {
+        const NAMESPACE: &'static str = "mock";
+    }
+
+    let mut task = crate::testing::task("Hello");
+    assert_eq!(Options::from_task(&task)?, Options::default());
+
+    task.extra = Some(json!({ "other": 1, "mock": { "polishOption": "1" } }));
+    assert_eq!(
+        Options::from_task(&task)?.polish_option.as_deref(),
+        Some("1")
+    );
+
+    task.extra = Some(json!({ "mock": { "polishOptoin": "1" } }));
+    let err = Options::from_task(&task).unwrap_err();
+    assert!(matches!(
+        XTranslateError::find(&err),
+        Some(XTranslateError::InvalidConfig(message)) if message.contains("polishOptoin")
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,270 @@
+use crate::pipeline::Processor;
+#[cfg(test)]
+use crate::testing::task;
+use crate::{TranslateResult, TranslateTask};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// 与 `placeholder` 的哨兵区分，两者可以同时使用
+static SENTINEL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"⟪\s*(\d+)\s*⟫").unwrap());
+
+fn sentinel(index: usize) -> String {
+    format!("⟪{}⟫", index)
+}
+
+/// 可识别的敏感信息类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    /// 电子邮箱
+    Email,
+    /// 中国居民身份证号
+    IdCard,
+    /// 银行卡号
+    CardNumber,
+    /// 电话号码
+    Phone,
+    /// IPv4 地址
+    IpAddress,
+}
+
+impl PiiKind {
+    pub const ALL: &'static [PiiKind] = &[
+        PiiKind::Email,
+        PiiKind::IdCard,
+        PiiKind::CardNumber,
+        PiiKind::Phone,
+        PiiKind::IpAddress,
+    ];
+
+    /// 数字两侧使用 ASCII 单词边界，以便识别紧挨中文的号码
+    fn pattern(&self) -> &'static str {
+        match self {
+            PiiKind::Email => {
+                r"[A-Za-z0-9._%+\-]+@[A-Za-z0-9\-]+(?:\.[A-Za-z0-9\-]+)*\.[A-Za-z]{2,}"
+            }
+            PiiKind::IdCard => r"(?-u:\b)\d{17}[\dXx](?-u:\b)",
+            PiiKind::CardNumber => r"(?-u:\b)\d{4}(?:[ \-]?\d{4}){2,3}(?:[ \-]?\d{1,3})?(?-u:\b)",
+            PiiKind::Phone => concat!(
+                r"(?:\+\d{1,3}[ \-]?)?(?-u:\b)1[3-9]\d{9}(?-u:\b)",
+                r"|(?:\+\d{1,3}[ \-]?)?(?:\(\d{1,4}\)[ \-]?)?(?-u:\b)\d{2,4}[ \-]\d{3,4}[ \-]?\d{3,4}(?-u:\b)",
+            ),
+            PiiKind::IpAddress => {
+                r"(?-u:\b)(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)(?-u:\b)"
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PiiConfig {
+    /// 要识别的类型，默认全部
+    pub kinds: Vec<PiiKind>,
+    /// 额外的敏感词，如人名、项目代号或不雅词汇，不区分大小写
+    pub terms: Vec<String>,
+    /// 译文中有敏感信息丢失时返回错误
+    pub strict: bool,
+}
+
+impl Default for PiiConfig {
+    fn default() -> Self {
+        PiiConfig {
+            kinds: PiiKind::ALL.to_vec(),
+            terms: vec![],
+            strict: true,
+        }
+    }
+}
+
+/// 翻译前将敏感信息替换为 `⟪0⟫` 形式的哨兵，翻译后还原，原文中的敏感信息不会发送给翻译服务。
+/// 只处理 `task.content`，术语与参考译文原样发送
+#[derive(Debug, Clone)]
+pub struct PiiProcessor {
+    config: PiiConfig,
+    pattern: Option<Regex>,
+}
+
+impl PiiProcessor {
+    pub fn new(config: Value) -> Result<Self> {
+        let config = if config.is_null() {
+            PiiConfig::default()
+        } else {
+            serde_json::from_value(config)?
+        };
+
+        PiiProcessor::with_config(config)
+    }
+
+    pub fn with_config(config: PiiConfig) -> Result<Self> {
+        // 敏感词优先，较长的在前
+        let mut terms: Vec<&String> = config
+            .terms
+            .iter()
+            .filter(|t| !t.trim().is_empty())
+            .collect();
+        terms.sort_by_key(|t| std::cmp::Reverse(t.chars().count()));
+
+        let patterns: Vec<String> = terms
+            .iter()
+            .map(|t| format!("(?i:{})", regex::escape(t)))
+            .chain(config.kinds.iter().map(|k| format!("(?:{})", k.pattern())))
+            .collect();
+
+        let pattern = if patterns.is_empty() {
+            None
+        } else {
+            Some(Regex::new(&patterns.join("|"))?)
+        };
+
+        Ok(PiiProcessor { config, pattern })
+    }
+
+    /// 替换敏感信息，返回替换后的文本与按哨兵序号排列的原文
+    pub fn mask(&self, text: &str) -> (String, Vec<String>) {
+        let Some(pattern) = &self.pattern else {
+            return (text.to_string(), vec![]);
+        };
+
+        let mut values: Vec<String> = vec![];
+        let masked = pattern
+            .replace_all(text, |caps: &Captures| {
+                // 相同的内容使用同一个哨兵
+                let value = caps[0].to_string();
+                let index = match values.iter().position(|v| *v == value) {
+                    Some(index) => index,
+                    None => {
+                        values.push(value);
+                        values.len() - 1
+                    }
+                };
+                sentinel(index)
+            })
+            .to_string();
+
+        (masked, values)
+    }
+
+    /// 还原哨兵，返回还原后的文本与缺失的敏感信息数量
+    pub fn unmask(&self, text: &str, values: &[String]) -> (String, usize) {
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+
+        let restored = SENTINEL
+            .replace_all(text, |caps: &Captures| {
+                let index: usize = caps[1].parse().unwrap_or(usize::MAX);
+                match values.get(index) {
+                    Some(value) => {
+                        *counts.entry(index).or_insert(0) += 1;
+                        value.clone()
+                    }
+                    None => caps[0].to_string(),
+                }
+            })
+            .to_string();
+
+        let missing = (0..values.len())
+            .filter(|i| !counts.contains_key(i))
+            .count();
+        (restored, missing)
+    }
+}
+
+#[async_trait]
+impl Processor for PiiProcessor {
+    async fn pre_process(&self, task: &mut TranslateTask) -> Result<()> {
+        task.content = self.mask(&task.content).0;
+        Ok(())
+    }
+
+    async fn post_process(&self, task: &TranslateTask, result: &mut TranslateResult) -> Result<()> {
+        // 替换是确定性的，重新识别原文即可还原
+        let (_, values) = self.mask(&task.content);
+
+        if let Some(content) = &result.content {
+            let (restored, missing) = self.unmask(content, &values);
+            if self.config.strict && missing > 0 {
+                // 错误信息中不包含敏感信息本身
+                bail!("pii check failed: {} masked values missing", missing);
+            }
+            result.content = Some(restored);
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pii_mask() -> Result<()> {
+    let processor = PiiProcessor::with_config(PiiConfig {
+        terms: vec!["Project Falcon".to_string()],
+        ..Default::default()
+    })?;
+
+    let (masked, values) = processor.mask(
+        "Mail alice.w@example.co.uk or call +86 13812345678, 010-8765-4321. \
+         身份证号11010519491231002X，卡号 6222 0212 3456 7890，IP 192.168.1.20。\
+         project falcon ships in 2025, ask alice.w@example.co.uk.",
+    );
+    assert_eq!(
+        values,
+        vec![
+            "alice.w@example.co.uk",
+            "+86 13812345678",
+            "010-8765-4321",
+            "11010519491231002X",
+            "6222 0212 3456 7890",
+            "192.168.1.20",
+            "project falcon",
+        ]
+    );
+    assert_eq!(
+        masked,
+        "Mail ⟪0⟫ or call ⟪1⟫, ⟪2⟫. 身份证号⟪3⟫，卡号 ⟪4⟫，IP ⟪5⟫。⟪6⟫ ships in 2025, ask ⟪0⟫."
+    );
+
+    let (restored, missing) = processor.unmask("⟪ 1 ⟫ ⟪0⟫ ⟪9⟫", &values[..3]);
+    assert_eq!(restored, "+86 13812345678 alice.w@example.co.uk ⟪9⟫");
+    assert_eq!(missing, 1);
+
+    let processor = PiiProcessor::with_config(PiiConfig {
+        kinds: vec![PiiKind::Email],
+        ..Default::default()
+    })?;
+    assert_eq!(processor.mask("call 13812345678").1.len(), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pii_processor() -> Result<()> {
+    let processor = PiiProcessor::new(Value::Null)?;
+    let original = task("Contact bob@example.com");
+
+    let mut masked = original.clone();
+    processor.pre_process(&mut masked).await?;
+    assert_eq!(masked.content, "Contact ⟪0⟫");
+
+    let mut result = TranslateResult {
+        content: Some("联系 ⟪0⟫".to_string()),
+        ..Default::default()
+    };
+    processor.post_process(&original, &mut result).await?;
+    assert_eq!(result.content.as_deref(), Some("联系 bob@example.com"));
+
+    let mut dropped = TranslateResult {
+        content: Some("联系我们".to_string()),
+        ..Default::default()
+    };
+    let err = processor
+        .post_process(&original, &mut dropped)
+        .await
+        .unwrap_err();
+    assert!(!err.to_string().contains("bob@"));
+
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use crate::markdown::MarkdownProcessor;
+use crate::normalize::NormalizeProcessor;
+use crate::pii::PiiProcessor;
+use crate::placeholder::PlaceholderProcessor;
+use crate::qe::QualityProcessor;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::utils::normal2stream;
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use serde_json::json;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// 翻译前后的处理步骤
+#[async_trait]
+pub trait Processor: Send + Sync {
+    /// 翻译前处理任务
+    async fn pre_process(&self, _task: &mut TranslateTask) -> Result<()> {
+        Ok(())
+    }
+
+    /// 翻译后处理结果，`task` 为本步骤 `pre_process` 之前的任务
+    async fn post_process(
+        &self,
+        _task: &TranslateTask,
+        _result: &mut TranslateResult,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// `post_process` 能否逐个作用于流式增量，否则流式翻译会先完整翻译再输出
+    fn process_deltas(&self) -> bool {
+        false
+    }
+}
+
+/// 配置中的处理步骤描述
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessorSpec {
+    /// 处理步骤名称
+    pub name: String,
+    /// 传给处理步骤的配置
+    #[serde(default)]
+    pub config: Value,
+}
+
+/// 按名称创建内置处理步骤
+pub fn create_processor(name: &str, config: Value) -> Result<Box<dyn Processor>> {
+    match name {
+        "markdown" => Ok(Box::new(MarkdownProcessor::new(config)?)),
+        "normalize" => Ok(Box::new(NormalizeProcessor::new(config)?)),
+        "pii" => Ok(Box::new(PiiProcessor::new(config)?)),
+        "placeholder" => Ok(Box::new(PlaceholderProcessor::new(config)?)),
+        "quality" => Ok(Box::new(QualityProcessor::new(config)?)),
+        _ => bail!("Processor not found: {}", name),
+    }
+}
+
+/// 在任意翻译器前后串联处理步骤；`pre_process` 按顺序执行，`post_process` 按相反顺序执行
+pub struct PipelineTranslator<T> {
+    inner: T,
+    processors: Vec<Box<dyn Processor>>,
+}
+
+impl<T: Translator> PipelineTranslator<T> {
+    pub fn wrap(inner: T) -> Self {
+        PipelineTranslator {
+            inner,
+            processors: vec![],
+        }
+    }
+
+    /// 追加处理步骤
+    pub fn with(mut self, processor: impl Processor + 'static) -> Self {
+        self.processors.push(Box::new(processor));
+        self
+    }
+
+    pub fn push(&mut self, processor: Box<dyn Processor>) {
+        self.processors.push(processor);
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// 依次执行 `pre_process`，返回每个步骤执行前的任务快照
+    async fn pre_process(
+        &self,
+        mut task: TranslateTask,
+    ) -> Result<(TranslateTask, Vec<TranslateTask>)> {
+        let mut snapshots = vec![];
+        for processor in &self.processors {
+            snapshots.push(task.clone());
+            processor.pre_process(&mut task).await?;
+        }
+        Ok((task, snapshots))
+    }
+
+    async fn post_process(
+        &self,
+        snapshots: &[TranslateTask],
+        result: &mut TranslateResult,
+    ) -> Result<()> {
+        for (processor, task) in self.processors.iter().zip(snapshots).rev() {
+            processor.post_process(task, result).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T> Translator for PipelineTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 处理步骤读取自 `config["processors"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let specs: Vec<ProcessorSpec> = match config.get("processors") {
+            Some(processors) => serde_json::from_value(processors.clone())?,
+            None => vec![],
+        };
+
+        let mut translator = PipelineTranslator::wrap(T::new(config).await?);
+        for spec in specs {
+            translator.push(create_processor(&spec.name, spec.config)?);
+        }
+
+        Ok(translator)
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let (task, snapshots) = self.pre_process(task).await?;
+
+        let mut result = self.inner.translate(task).await?;
+        self.post_process(&snapshots, &mut result).await?;
+
+        Ok(result)
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        if !self.processors.iter().all(|p| p.process_deltas()) {
+            return normal2stream(self, task, sender).await;
+        }
+
+        let (task, snapshots) = self.pre_process(task).await?;
+
+        let (tx, mut rx) = mpsc::channel(64);
+
+        let forward = async {
+            while let Some(mut chunk) = rx.recv().await {
+                if let TranslateStreamChunk::Delta(delta) = &mut chunk {
+                    self.post_process(&snapshots, delta).await?;
+                }
+                sender.send(chunk).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+
+        let (result, forwarded) = tokio::join!(self.inner.translate_stream(task, tx), forward);
+
+        result?;
+        forwarded
+    }
+}
+
+#[cfg(test)]
+struct UppercaseProcessor;
+
+#[cfg(test)]
+#[async_trait]
+impl Processor for UppercaseProcessor {
+    async fn pre_process(&self, task: &mut TranslateTask) -> Result<()> {
+        task.content = task.content.to_uppercase();
+        Ok(())
+    }
+
+    async fn post_process(&self, task: &TranslateTask, result: &mut TranslateResult) -> Result<()> {
+        result.content = result
+            .content
+            .as_ref()
+            .map(|s| format!("{} <{}>", s, task.content));
+        Ok(())
+    }
+
+    fn process_deltas(&self) -> bool {
+        true
+    }
+}
+
+#[tokio::test]
+async fn test_pipeline_translate() -> Result<()> {
+    let translator = PipelineTranslator::wrap(MockTranslator::new("T:"))
+        .with(UppercaseProcessor)
+        .with(QualityProcessor::new(json!({}))?);
+
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("T:HELLO <Hello>"));
+    // post_process 逆序执行，质量评估先于 UppercaseProcessor
+    assert!(result.quality.is_some());
+
+    let (tx, mut rx) = mpsc::channel(64);
+    translator.translate_stream(task("Hello"), tx).await?;
+    assert!(matches!(rx.recv().await, Some(TranslateStreamChunk::Start)));
+    assert!(
+        matches!(rx.recv().await, Some(TranslateStreamChunk::Delta(r)) if r.content.as_deref() == Some("T:HELLO <Hello>"))
+    );
+
+    assert!(create_processor("unknown", Value::Null).is_err());
+
+    Ok(())
+}
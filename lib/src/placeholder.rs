@@ -0,0 +1,186 @@
+use crate::pipeline::Processor;
+#[cfg(test)]
+use crate::testing::task;
+use crate::{TranslateResult, TranslateTask};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// 常见占位符：`{{name}}`、`{0}`、`{name}`、`%s`、`%1$d`、`%(name)s`、`<0>`、`</0>`、`${VAR}`、`$VAR`
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"\{\{\s*[\w.\-]+\s*\}\}",
+        r"|\{\d+\}|\{[A-Za-z_]\w*\}",
+        r"|%(?:\d+\$)?[-+0#]*\d*(?:\.\d+)?[sdifuxXeEgGcp@]",
+        r"|%\([\w.]+\)[sdif]",
+        r"|</?\d+/?>",
+        r"|\$\{[A-Za-z_]\w*\}|\$[A-Za-z_]\w*",
+    ))
+    .unwrap()
+});
+
+/// 译文中的哨兵，允许服务端在数字两侧插入空白
+static SENTINEL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"⟦\s*(\d+)\s*⟧").unwrap());
+
+fn sentinel(index: usize) -> String {
+    format!("⟦{}⟧", index)
+}
+
+/// 替换后的文本与占位符列表，第 `i` 个占位符对应哨兵 `⟦i⟧`
+#[derive(Debug, Clone, Default)]
+pub struct Masked {
+    pub text: String,
+    pub placeholders: Vec<String>,
+}
+
+/// 将占位符替换为稳定的哨兵
+pub fn mask(text: &str) -> Masked {
+    let mut placeholders = vec![];
+    let text = PLACEHOLDER
+        .replace_all(text, |caps: &regex::Captures| {
+            placeholders.push(caps[0].to_string());
+            sentinel(placeholders.len() - 1)
+        })
+        .to_string();
+
+    Masked { text, placeholders }
+}
+
+/// 将哨兵还原为占位符，并检查是否有占位符丢失或重复。
+/// 无法识别的哨兵保持原样
+pub fn unmask(text: &str, placeholders: &[String]) -> (String, Vec<String>) {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+
+    let restored = SENTINEL
+        .replace_all(text, |caps: &regex::Captures| {
+            let index: usize = caps[1].parse().unwrap_or(usize::MAX);
+            match placeholders.get(index) {
+                Some(placeholder) => {
+                    *counts.entry(index).or_insert(0) += 1;
+                    placeholder.clone()
+                }
+                None => caps[0].to_string(),
+            }
+        })
+        .to_string();
+
+    let mut problems = vec![];
+    for (index, placeholder) in placeholders.iter().enumerate() {
+        match counts.get(&index).copied().unwrap_or(0) {
+            0 => problems.push(format!("missing placeholder {}", placeholder)),
+            1 => {}
+            n => problems.push(format!("placeholder {} duplicated {} times", placeholder, n)),
+        }
+    }
+
+    (restored, problems)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaceholderConfig {
+    /// 译文中占位符丢失或重复时返回错误
+    #[serde(default = "default_strict")]
+    pub strict: bool,
+}
+
+fn default_strict() -> bool {
+    true
+}
+
+impl Default for PlaceholderConfig {
+    fn default() -> Self {
+        PlaceholderConfig {
+            strict: default_strict(),
+        }
+    }
+}
+
+/// 翻译前将占位符替换为哨兵，翻译后还原并校验
+#[derive(Debug, Clone, Default)]
+pub struct PlaceholderProcessor {
+    config: PlaceholderConfig,
+}
+
+impl PlaceholderProcessor {
+    pub fn new(config: Value) -> Result<Self> {
+        let config = if config.is_null() {
+            PlaceholderConfig::default()
+        } else {
+            serde_json::from_value(config)?
+        };
+
+        Ok(PlaceholderProcessor { config })
+    }
+}
+
+#[async_trait]
+impl Processor for PlaceholderProcessor {
+    async fn pre_process(&self, task: &mut TranslateTask) -> Result<()> {
+        task.content = mask(&task.content).text;
+        Ok(())
+    }
+
+    async fn post_process(&self, task: &TranslateTask, result: &mut TranslateResult) -> Result<()> {
+        // 替换是确定性的，重新识别原文即可得到哨兵与占位符的对应关系
+        let masked = mask(&task.content);
+
+        if let Some(content) = &result.content {
+            let (restored, problems) = unmask(content, &masked.placeholders);
+            if self.config.strict && !problems.is_empty() {
+                bail!("placeholder check failed: [{}]", problems.join("; "));
+            }
+            result.content = Some(restored);
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_mask() {
+    let masked = mask("Hello {{name}}, you have {0} new %s from $USER <0>here</0> (%1$d%%)");
+    assert_eq!(
+        masked.placeholders,
+        vec!["{{name}}", "{0}", "%s", "$USER", "<0>", "</0>", "%1$d"]
+    );
+    assert_eq!(
+        masked.text,
+        "Hello ⟦0⟧, you have ⟦1⟧ new ⟦2⟧ from ⟦3⟧ ⟦4⟧here⟦5⟧ (⟦6⟧%%)"
+    );
+
+    let (restored, problems) = unmask("你好 ⟦ 0 ⟧，⟦1⟧⟦1⟧", &masked.placeholders[..3]);
+    assert_eq!(restored, "你好 {{name}}，{0}{0}");
+    assert_eq!(
+        problems,
+        vec!["placeholder {0} duplicated 2 times", "missing placeholder %s"]
+    );
+}
+
+#[tokio::test]
+async fn test_placeholder_processor() -> Result<()> {
+    let processor = PlaceholderProcessor::new(Value::Null)?;
+    let original = task("Hello {name}");
+
+    let mut masked = original.clone();
+    processor.pre_process(&mut masked).await?;
+    assert_eq!(masked.content, "Hello ⟦0⟧");
+
+    let mut result = TranslateResult {
+        content: Some("你好 ⟦0⟧".to_string()),
+        ..Default::default()
+    };
+    processor.post_process(&original, &mut result).await?;
+    assert_eq!(result.content.as_deref(), Some("你好 {name}"));
+
+    let mut dropped = TranslateResult {
+        content: Some("你好".to_string()),
+        ..Default::default()
+    };
+    assert!(processor.post_process(&original, &mut dropped).await.is_err());
+
+    Ok(())
+}
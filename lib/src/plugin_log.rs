@@ -0,0 +1,305 @@
+#[cfg(not(feature = "tracing"))]
+use crate::ffi::LogCallback;
+use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use std::ffi::CString;
+use std::ffi::{c_char, c_void, CStr};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// 插件日志级别，FFI 中依次为 1 到 5，0 表示关闭
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u32)]
+pub enum LogLevel {
+    Error = 1,
+    Warn = 2,
+    Info = 3,
+    Debug = 4,
+    Trace = 5,
+}
+
+impl LogLevel {
+    pub fn from_u32(level: u32) -> Option<Self> {
+        match level {
+            1 => Some(LogLevel::Error),
+            2 => Some(LogLevel::Warn),
+            3 => Some(LogLevel::Info),
+            4 => Some(LogLevel::Debug),
+            5 => Some(LogLevel::Trace),
+            _ => None,
+        }
+    }
+}
+
+/// 插件发出的一条日志
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginLog {
+    pub level: LogLevel,
+    pub target: String,
+    pub message: String,
+}
+
+/// 宿主接收插件日志，在插件的线程中同步调用，不应阻塞
+pub trait LogHandler: Send + Sync {
+    fn log(&self, record: &PluginLog);
+}
+
+impl<F: Fn(&PluginLog) + Send + Sync> LogHandler for F {
+    fn log(&self, record: &PluginLog) {
+        self(record)
+    }
+}
+
+/// 写入宿主的 `tracing`，未启用 `tracing` 特性时丢弃
+#[derive(Debug, Clone, Default)]
+pub struct TracingHandler;
+
+impl LogHandler for TracingHandler {
+    #[cfg(feature = "tracing")]
+    fn log(&self, record: &PluginLog) {
+        let (target, message) = (&record.target, &record.message);
+        match record.level {
+            LogLevel::Error => {
+                tracing::error!(target: "plugin", plugin_target = %target, "{}", message)
+            }
+            LogLevel::Warn => {
+                tracing::warn!(target: "plugin", plugin_target = %target, "{}", message)
+            }
+            LogLevel::Info => {
+                tracing::info!(target: "plugin", plugin_target = %target, "{}", message)
+            }
+            LogLevel::Debug => {
+                tracing::debug!(target: "plugin", plugin_target = %target, "{}", message)
+            }
+            LogLevel::Trace => {
+                tracing::trace!(target: "plugin", plugin_target = %target, "{}", message)
+            }
+        }
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    fn log(&self, _record: &PluginLog) {}
+}
+
+static HANDLER: LazyLock<RwLock<Arc<dyn LogHandler>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(TracingHandler)));
+
+static LEVEL: AtomicU32 = AtomicU32::new(LogLevel::Info as u32);
+
+/// 注册宿主的插件日志接收方
+pub fn set_handler(handler: impl LogHandler + 'static) {
+    *HANDLER.write().unwrap() = Arc::new(handler);
+}
+
+pub fn handler() -> Arc<dyn LogHandler> {
+    HANDLER.read().unwrap().clone()
+}
+
+/// 插件发送日志的最低级别，`None` 表示关闭。对之后打开的插件生效
+pub fn set_level(level: Option<LogLevel>) {
+    LEVEL.store(level.map(|l| l as u32).unwrap_or(0), Ordering::Relaxed);
+}
+
+pub fn level() -> u32 {
+    LEVEL.load(Ordering::Relaxed)
+}
+
+/// 宿主传给插件 `set_log_callback` 的回调，转发给 `set_handler` 注册的接收方
+pub extern "C" fn host_log_callback(
+    level: u32,
+    target: *const c_char,
+    message: *const c_char,
+    _user_data: *mut c_void,
+) {
+    let Some(level) = LogLevel::from_u32(level) else {
+        return;
+    };
+    let text = |s: *const c_char| {
+        if s.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned()
+        }
+    };
+
+    handler().log(&PluginLog {
+        level,
+        target: text(target),
+        message: text(message),
+    });
+}
+
+#[cfg(feature = "tracing")]
+pub use subscriber::{install, HostSubscriber};
+
+/// 插件一侧：把插件中的 `tracing` 事件通过回调交给宿主
+#[cfg(feature = "tracing")]
+mod subscriber {
+    use super::LogLevel;
+    use crate::ffi::LogCallback;
+    use std::ffi::{c_void, CString};
+    use std::fmt::Write;
+    use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+    use std::sync::{Once, RwLock};
+    use tracing::field::{Field, Visit};
+    use tracing::metadata::LevelFilter;
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::subscriber::Interest;
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    static CALLBACK: RwLock<Option<(LogCallback, usize)>> = RwLock::new(None);
+    static MAX_LEVEL: AtomicU32 = AtomicU32::new(0);
+    static INSTALL: Once = Once::new();
+
+    fn level_of(level: &Level) -> u32 {
+        let level = match *level {
+            Level::ERROR => LogLevel::Error,
+            Level::WARN => LogLevel::Warn,
+            Level::INFO => LogLevel::Info,
+            Level::DEBUG => LogLevel::Debug,
+            Level::TRACE => LogLevel::Trace,
+        };
+        level as u32
+    }
+
+    /// 设置回调，`callback` 为 `None` 或 `max_level` 为 0 时不再发送。
+    /// 首次调用时注册为插件的全局 `tracing` 订阅者，插件已注册其他订阅者时不生效
+    pub fn install(callback: Option<LogCallback>, user_data: *mut c_void, max_level: u32) {
+        *CALLBACK.write().unwrap() = callback.map(|cb| (cb, user_data as usize));
+        MAX_LEVEL.store(max_level, Ordering::Relaxed);
+
+        INSTALL.call_once(|| {
+            let _ = tracing::subscriber::set_global_default(HostSubscriber::default());
+        });
+    }
+
+    #[derive(Default)]
+    struct Message {
+        message: String,
+        fields: String,
+    }
+
+    impl Visit for Message {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                let _ = write!(self.message, "{:?}", value);
+            } else {
+                let _ = write!(self.fields, " {}={:?}", field.name(), value);
+            }
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "message" {
+                self.message.push_str(value);
+            } else {
+                let _ = write!(self.fields, " {}={}", field.name(), value);
+            }
+        }
+    }
+
+    /// 只转发事件，不记录 span
+    #[derive(Default)]
+    pub struct HostSubscriber {
+        next_id: AtomicU64,
+    }
+
+    impl Subscriber for HostSubscriber {
+        // 回调与级别可能随时改变，不缓存判断结果
+        fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+            Interest::sometimes()
+        }
+
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            level_of(metadata.level()) <= MAX_LEVEL.load(Ordering::Relaxed)
+        }
+
+        fn max_level_hint(&self) -> Option<LevelFilter> {
+            Some(LevelFilter::TRACE)
+        }
+
+        fn new_span(&self, _span: &Attributes<'_>) -> Id {
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let Some((callback, user_data)) = *CALLBACK.read().unwrap() else {
+                return;
+            };
+
+            let mut message = Message::default();
+            event.record(&mut message);
+            let text = format!("{}{}", message.message, message.fields).replace('\0', "");
+
+            let metadata = event.metadata();
+            let target = CString::new(metadata.target().replace('\0', "")).unwrap_or_default();
+            let text = CString::new(text).unwrap_or_default();
+
+            callback(
+                level_of(metadata.level()),
+                target.as_ptr(),
+                text.as_ptr(),
+                user_data as *mut c_void,
+            );
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+}
+
+/// 插件未启用 `tracing` 特性时没有需要转发的日志
+#[cfg(not(feature = "tracing"))]
+pub fn install(_callback: Option<LogCallback>, _user_data: *mut c_void, _max_level: u32) {}
+
+#[test]
+fn test_host_log_callback() {
+    let records = Arc::new(std::sync::Mutex::new(vec![]));
+    let collected = records.clone();
+    set_handler(move |record: &PluginLog| collected.lock().unwrap().push(record.clone()));
+
+    let target = CString::new("plugin_openai::translator").unwrap();
+    let message = CString::new("request failed status=429").unwrap();
+    host_log_callback(2, target.as_ptr(), message.as_ptr(), std::ptr::null_mut());
+    host_log_callback(9, target.as_ptr(), message.as_ptr(), std::ptr::null_mut());
+    set_handler(TracingHandler);
+
+    assert_eq!(
+        *records.lock().unwrap(),
+        vec![PluginLog {
+            level: LogLevel::Warn,
+            target: "plugin_openai::translator".to_string(),
+            message: "request failed status=429".to_string(),
+        }]
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_host_subscriber() {
+    static RECEIVED: std::sync::Mutex<Vec<(u32, String)>> = std::sync::Mutex::new(vec![]);
+
+    extern "C" fn collect(level: u32, _: *const c_char, message: *const c_char, _: *mut c_void) {
+        let message = unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned();
+        RECEIVED.lock().unwrap().push((level, message));
+    }
+
+    install(Some(collect), std::ptr::null_mut(), LogLevel::Info as u32);
+    tracing::subscriber::with_default(HostSubscriber::default(), || {
+        tracing::warn!(status = 429, "rate limited");
+        tracing::debug!("dropped");
+    });
+    install(None, std::ptr::null_mut(), 0);
+
+    assert_eq!(
+        *RECEIVED.lock().unwrap(),
+        vec![(LogLevel::Warn as u32, "rate limited status=429".to_string())]
+    );
+}
@@ -0,0 +1,376 @@
+use crate::ffi_proxy::ProxyTranslatorFactory;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::trace::record_failure;
+use crate::utils::supports_task;
+use crate::{
+    BoxedTranslator, Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask,
+    Translator, TranslatorFactory, TranslatorSpec,
+};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+#[cfg(test)]
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// 负载均衡策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolStrategy {
+    /// 轮询
+    #[default]
+    RoundRobin,
+    /// 选择进行中任务最少的实例
+    LeastInFlight,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolConfig {
+    /// 池中的翻译器实例
+    pub translators: Vec<TranslatorSpec>,
+    #[serde(default)]
+    pub strategy: PoolStrategy,
+    /// 连续失败多少次后暂时摘除该实例
+    #[serde(default = "default_max_failures")]
+    pub max_failures: usize,
+    /// 摘除后多久重新尝试（毫秒）
+    #[serde(default = "default_cooldown_ms")]
+    pub cooldown_ms: u64,
+}
+
+fn default_max_failures() -> usize {
+    3
+}
+
+fn default_cooldown_ms() -> u64 {
+    30_000
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            translators: vec![],
+            strategy: PoolStrategy::default(),
+            max_failures: default_max_failures(),
+            cooldown_ms: default_cooldown_ms(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Health {
+    failures: usize,
+    unhealthy_until: Option<Instant>,
+}
+
+struct Backend {
+    name: String,
+    translator: BoxedTranslator,
+    in_flight: AtomicUsize,
+    health: Mutex<Health>,
+}
+
+impl Backend {
+    fn is_healthy(&self) -> bool {
+        let health = self.health.lock().unwrap();
+        health
+            .unhealthy_until
+            .map(|until| Instant::now() >= until)
+            .unwrap_or(true)
+    }
+
+    fn mark_success(&self) {
+        let mut health = self.health.lock().unwrap();
+        health.failures = 0;
+        health.unhealthy_until = None;
+    }
+
+    fn mark_failure(&self, max_failures: usize, cooldown: Duration) {
+        let mut health = self.health.lock().unwrap();
+        health.failures += 1;
+        if health.failures >= max_failures {
+            health.unhealthy_until = Some(Instant::now() + cooldown);
+        }
+    }
+}
+
+/// 进行中任务计数，离开作用域时自动减一
+struct InFlightGuard<'a>(&'a AtomicUsize);
+
+impl<'a> InFlightGuard<'a> {
+    fn new(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard(counter)
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 在多个同类实例（如多个 API Key 或多个地域）之间分发任务，并跟踪各实例的健康状态
+pub struct PoolTranslator {
+    backends: Vec<Backend>,
+    strategy: PoolStrategy,
+    max_failures: usize,
+    cooldown: Duration,
+    next: AtomicUsize,
+}
+
+impl PoolTranslator {
+    pub fn with_translators(
+        translators: Vec<(String, BoxedTranslator)>,
+        strategy: PoolStrategy,
+    ) -> Self {
+        let config = PoolConfig::default();
+
+        PoolTranslator {
+            backends: translators
+                .into_iter()
+                .map(|(name, translator)| Backend {
+                    name,
+                    translator,
+                    in_flight: AtomicUsize::new(0),
+                    health: Mutex::new(Health::default()),
+                })
+                .collect(),
+            strategy,
+            max_failures: config.max_failures.max(1),
+            cooldown: Duration::from_millis(config.cooldown_ms),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// 设置摘除阈值与冷却时间
+    pub fn with_health(mut self, max_failures: usize, cooldown: Duration) -> Self {
+        self.max_failures = max_failures.max(1);
+        self.cooldown = cooldown;
+        self
+    }
+
+    pub async fn from_config(config: &Value, factory: &dyn TranslatorFactory) -> Result<Self> {
+        let config: PoolConfig = serde_json::from_value(config.clone())?;
+
+        let mut translators = vec![];
+        for spec in config.translators {
+            let translator = factory.create(&spec.name, spec.config).await?;
+            translators.push((spec.name, translator));
+        }
+
+        Ok(
+            PoolTranslator::with_translators(translators, config.strategy).with_health(
+                config.max_failures,
+                Duration::from_millis(config.cooldown_ms),
+            ),
+        )
+    }
+
+    /// 当前处于健康状态的实例数量
+    pub fn healthy_count(&self) -> usize {
+        self.backends.iter().filter(|b| b.is_healthy()).count()
+    }
+
+    /// 按策略排序候选实例，健康实例在前，全部不健康时仍会尝试
+    fn candidates(&self, task: &TranslateTask) -> Vec<&Backend> {
+        let supported: Vec<&Backend> = self
+            .backends
+            .iter()
+            .filter(|b| supports_task(b.translator.as_ref(), task))
+            .collect();
+
+        if supported.is_empty() {
+            return supported;
+        }
+
+        let mut ordered: Vec<&Backend> = match self.strategy {
+            PoolStrategy::RoundRobin => {
+                let start = self.next.fetch_add(1, Ordering::SeqCst) % supported.len();
+                supported[start..]
+                    .iter()
+                    .chain(supported[..start].iter())
+                    .cloned()
+                    .collect()
+            }
+            PoolStrategy::LeastInFlight => {
+                let mut list = supported;
+                list.sort_by_key(|b| b.in_flight.load(Ordering::SeqCst));
+                list
+            }
+        };
+
+        // 稳定排序，保持策略给出的相对顺序
+        ordered.sort_by_key(|b| !b.is_healthy());
+        ordered
+    }
+}
+
+#[async_trait]
+impl Translator for PoolTranslator {
+    type This = Self;
+
+    async fn new(config: Value) -> Result<Self> {
+        let factory = ProxyTranslatorFactory::from_config(&config)?;
+        PoolTranslator::from_config(&config, &factory).await
+    }
+
+    /// 池中实例应为同类翻译器，以第一个实例为准
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        match self.backends.first() {
+            Some(backend) => backend.translator.get_supported_input_languages(),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        match self.backends.first() {
+            Some(backend) => backend.translator.get_supported_output_languages(),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        Ok(self.backends.iter().any(|b| {
+            b.translator
+                .is_supported_input_language(lang.clone())
+                .unwrap_or(false)
+        }))
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        Ok(self.backends.iter().any(|b| {
+            b.translator
+                .is_supported_output_language(lang.clone())
+                .unwrap_or(false)
+        }))
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        Ok(self.backends.iter().any(|b| {
+            b.translator
+                .is_supported_pair(source.clone(), target.clone())
+                .unwrap_or(false)
+        }))
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        match self.backends.first() {
+            Some(backend) => backend.translator.get_supported_pairs(),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.backends
+            .first()
+            .map(|backend| backend.translator.capabilities())
+            .unwrap_or_default()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let mut errors = vec![];
+
+        for backend in self.candidates(&task) {
+            let _guard = InFlightGuard::new(&backend.in_flight);
+
+            match backend.translator.translate(task.clone()).await {
+                Ok(mut result) => {
+                    backend.mark_success();
+                    result.provider = Some(backend.name.clone());
+                    return Ok(result);
+                }
+                Err(e) => {
+                    backend.mark_failure(self.max_failures, self.cooldown);
+                    record_failure(&backend.name, &e);
+                    errors.push(format!("{}: {}", backend.name, e));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            bail!("no translator in pool supports this language pair");
+        }
+
+        bail!("all translators failed: [{}]", errors.join("; "))
+    }
+
+    /// 流式翻译只使用选中的一个实例，不做重试，避免重复输出
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let Some(backend) = self.candidates(&task).into_iter().next() else {
+            bail!("no translator in pool supports this language pair");
+        };
+
+        let _guard = InFlightGuard::new(&backend.in_flight);
+
+        match backend.translator.translate_stream(task, sender).await {
+            Ok(_) => {
+                backend.mark_success();
+                Ok(())
+            }
+            Err(e) => {
+                backend.mark_failure(self.max_failures, self.cooldown);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_pool_round_robin() -> Result<()> {
+    let translator = PoolTranslator::with_translators(
+        vec![
+            ("a".to_string(), Box::new(MockTranslator::new("A:"))),
+            ("b".to_string(), Box::new(MockTranslator::new("B:"))),
+        ],
+        PoolStrategy::RoundRobin,
+    );
+
+    let mut providers = vec![];
+    for _ in 0..4 {
+        providers.push(translator.translate(task("Hello")).await?.provider.unwrap());
+    }
+    assert_eq!(providers, vec!["a", "b", "a", "b"]);
+
+    let (tx, mut rx) = mpsc::channel(64);
+    translator.translate_stream(task("Hello"), tx).await?;
+    assert!(matches!(rx.recv().await, Some(TranslateStreamChunk::Start)));
+    assert!(
+        matches!(rx.recv().await, Some(TranslateStreamChunk::Delta(r)) if r.content.as_deref() == Some("A:Hello"))
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pool_health() -> Result<()> {
+    let translator = PoolTranslator::with_translators(
+        vec![
+            ("broken".to_string(), Box::new(MockTranslator::failing())),
+            ("ok".to_string(), Box::new(MockTranslator::new("OK:"))),
+        ],
+        PoolStrategy::RoundRobin,
+    )
+    .with_health(1, Duration::from_secs(60));
+
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.provider.as_deref(), Some("ok"));
+    assert_eq!(translator.healthy_count(), 1);
+
+    // 被摘除的实例不再优先使用
+    for _ in 0..3 {
+        let result = translator.translate(task("Hello")).await?;
+        assert_eq!(result.provider.as_deref(), Some("ok"));
+    }
+
+    Ok(())
+}
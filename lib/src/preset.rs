@@ -0,0 +1,107 @@
+/// 内置的提示词预设，模板语法与 `utils::format_messages` 相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preset {
+    /// 名称，可在配置或 `task.field` 中使用
+    pub name: &'static str,
+    /// 适用场景
+    pub description: &'static str,
+    /// 系统提示词模板
+    pub system_prompt: &'static str,
+    /// 用户提示词模板
+    pub user_prompt: &'static str,
+}
+
+/// 未指定预设时使用
+pub const DEFAULT_PRESET: &str = "default";
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: DEFAULT_PRESET,
+        description: "通用翻译",
+        system_prompt: r##"请将以下{{ source_language }}内容精准翻译为{{ target_language }}，确保符合以下要求：
+1. 保持专业语气与原文风格
+2. 要做到信达雅
+3. 保留专业术语及关键数据
+4. 只输出译文，不要输出其它内容"##,
+        user_prompt: "{{ content }}",
+    },
+    Preset {
+        name: "subtitle",
+        description: "影视字幕",
+        system_prompt: r##"你是专业的字幕译者，请将以下{{ source_language }}字幕翻译为{{ target_language }}，确保符合以下要求：
+1. 口语化、简洁，适合在屏幕上快速阅读
+2. 保持原有的换行，不要合并或拆分行
+3. 人名、称谓在前后文中保持一致
+4. 只输出译文，不要输出其它内容"##,
+        user_prompt: "{{ content }}",
+    },
+    Preset {
+        name: "technical-doc",
+        description: "技术文档",
+        system_prompt: r##"你是资深技术文档译者，请将以下{{ source_language }}技术文档翻译为{{ target_language }}，确保符合以下要求：
+1. 术语准确，使用业内通用译法，没有通用译法的术语保留原文
+2. 代码、命令、路径、URL 与占位符保持原样，不要翻译
+3. 保留 Markdown 等标记格式
+4. 只输出译文，不要输出其它内容"##,
+        user_prompt: "{{ content }}",
+    },
+    Preset {
+        name: "marketing",
+        description: "营销文案",
+        system_prompt: r##"你是营销文案本地化专家，请将以下{{ source_language }}文案改写为地道的{{ target_language }}，确保符合以下要求：
+1. 传达原文的卖点与情绪，可以调整句式，不必逐字对应
+2. 符合目标语言读者的表达习惯与文化
+3. 品牌名、产品名保持原样
+4. 只输出译文，不要输出其它内容"##,
+        user_prompt: "{{ content }}",
+    },
+    Preset {
+        name: "game-dialogue",
+        description: "游戏对白",
+        system_prompt: r##"你是游戏本地化译者，请将以下{{ source_language }}游戏文本翻译为{{ target_language }}，确保符合以下要求：
+1. 贴合角色性格与说话语气
+2. 变量、占位符与控制符（如 `{0}`、`%s`、`<color>`）保持原样
+3. 专有名词在前后文中保持一致
+4. 只输出译文，不要输出其它内容"##,
+        user_prompt: "{{ content }}",
+    },
+    Preset {
+        name: "literary",
+        description: "文学作品",
+        system_prompt: r##"你是文学译者，请将以下{{ source_language }}文学作品翻译为{{ target_language }}，确保符合以下要求：
+1. 保留原文的文风、修辞与意境
+2. 译文流畅自然，读起来像目标语言的原创作品
+3. 不增删情节与细节
+4. 只输出译文，不要输出其它内容"##,
+        user_prompt: "{{ content }}",
+    },
+];
+
+/// 按名称查找预设，不区分大小写，`_` 与 `-` 等价
+pub fn preset(name: &str) -> Option<&'static Preset> {
+    let name = name.trim().to_lowercase().replace('_', "-");
+    PRESETS.iter().find(|preset| preset.name == name)
+}
+
+pub fn default_preset() -> &'static Preset {
+    preset(DEFAULT_PRESET).unwrap()
+}
+
+/// 所有预设的名称
+pub fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|preset| preset.name).collect()
+}
+
+#[test]
+fn test_preset() {
+    assert_eq!(default_preset().name, "default");
+    assert_eq!(preset("Technical_Doc").unwrap().name, "technical-doc");
+    assert!(preset("unknown").is_none());
+    assert_eq!(preset_names().len(), PRESETS.len());
+
+    for preset in PRESETS {
+        assert!(handlebars::Handlebars::new()
+            .register_template_string(preset.name, preset.system_prompt)
+            .is_ok());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::ffi::ProgressCallback;
+use serde::{Deserialize, Serialize};
+use std::ffi::c_void;
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+#[cfg(test)]
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::{interval_at, Instant};
+
+/// 没有新进展时重复报告的间隔，宿主据此更新已用时间
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 翻译所处的阶段，FFI 中依次为 1 到 4
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u32)]
+pub enum ProgressPhase {
+    Started = 1,
+    /// 请求已发出，等待服务响应
+    Sending = 2,
+    /// 已收到响应头，正在读取响应
+    Receiving = 3,
+    Finished = 4,
+}
+
+impl ProgressPhase {
+    pub fn from_u32(phase: u32) -> Option<Self> {
+        match phase {
+            1 => Some(ProgressPhase::Started),
+            2 => Some(ProgressPhase::Sending),
+            3 => Some(ProgressPhase::Receiving),
+            4 => Some(ProgressPhase::Finished),
+            _ => None,
+        }
+    }
+}
+
+/// 一次非流式翻译的进度，字节数为本次翻译中所有请求的合计
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Progress {
+    pub phase: ProgressPhase,
+    pub elapsed_ms: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+/// 进度接收方，在翻译所在的线程中同步调用，不应阻塞
+pub trait ProgressSink: Send + Sync {
+    fn progress(&self, progress: &Progress);
+}
+
+impl<F: Fn(&Progress) + Send + Sync> ProgressSink for F {
+    fn progress(&self, progress: &Progress) {
+        self(progress)
+    }
+}
+
+/// 转发给 `call_translate_with_progress` 的回调
+struct CallbackSink {
+    callback: ProgressCallback,
+    user_data: usize,
+}
+
+impl ProgressSink for CallbackSink {
+    fn progress(&self, progress: &Progress) {
+        (self.callback)(
+            progress.phase as u32,
+            progress.elapsed_ms,
+            progress.bytes_sent,
+            progress.bytes_received,
+            self.user_data as *mut c_void,
+        );
+    }
+}
+
+/// 插件一侧：把宿主的回调包装为 `ProgressSink`
+pub fn callback_sink(callback: Option<ProgressCallback>, user_data: *mut c_void) -> Option<Arc<dyn ProgressSink>> {
+    let callback = callback?;
+    Some(Arc::new(CallbackSink {
+        callback,
+        user_data: user_data as usize,
+    }))
+}
+
+/// 宿主一侧传给插件的回调，`user_data` 指向调用期间有效的 `Arc<dyn ProgressSink>`
+pub extern "C" fn host_progress_callback(
+    phase: u32,
+    elapsed_ms: u64,
+    bytes_sent: u64,
+    bytes_received: u64,
+    user_data: *mut c_void,
+) {
+    let (Some(phase), Some(sink)) = (
+        ProgressPhase::from_u32(phase),
+        unsafe { (user_data as *const Arc<dyn ProgressSink>).as_ref() },
+    ) else {
+        return;
+    };
+
+    sink.progress(&Progress {
+        phase,
+        elapsed_ms,
+        bytes_sent,
+        bytes_received,
+    });
+}
+
+struct Tracker {
+    sink: Arc<dyn ProgressSink>,
+    start: Instant,
+    phase: AtomicU32,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+}
+
+impl Tracker {
+    fn emit(&self) {
+        let phase = ProgressPhase::from_u32(self.phase.load(Ordering::Relaxed)).unwrap_or(ProgressPhase::Started);
+        self.sink.progress(&Progress {
+            phase,
+            elapsed_ms: self.start.elapsed().as_millis() as u64,
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+        });
+    }
+}
+
+tokio::task_local! {
+    static TRACKER: Arc<Tracker>;
+}
+
+/// 执行 `fut` 并向 `sink` 报告进度：开始与结束时各一次，进入新阶段时一次，
+/// 其余时间每隔 `PROGRESS_INTERVAL` 一次。`sink` 为 `None` 时直接执行
+pub async fn track<F: Future>(sink: Option<Arc<dyn ProgressSink>>, fut: F) -> F::Output {
+    let Some(sink) = sink else {
+        return fut.await;
+    };
+
+    let tracker = Arc::new(Tracker {
+        sink,
+        start: Instant::now(),
+        phase: AtomicU32::new(ProgressPhase::Started as u32),
+        bytes_sent: AtomicU64::new(0),
+        bytes_received: AtomicU64::new(0),
+    });
+    tracker.emit();
+
+    let fut = TRACKER.scope(tracker.clone(), fut);
+    tokio::pin!(fut);
+    let mut ticker = interval_at(Instant::now() + PROGRESS_INTERVAL, PROGRESS_INTERVAL);
+    let output = loop {
+        tokio::select! {
+            output = &mut fut => break output,
+            _ = ticker.tick() => tracker.emit(),
+        }
+    };
+
+    tracker.phase.store(ProgressPhase::Finished as u32, Ordering::Relaxed);
+    tracker.emit();
+
+    output
+}
+
+/// 由 HTTP 层在发送请求与收到响应时调用，不在 `track` 中时忽略
+pub fn report(phase: ProgressPhase, bytes_sent: u64, bytes_received: u64) {
+    let _ = TRACKER.try_with(|tracker| {
+        tracker.phase.store(phase as u32, Ordering::Relaxed);
+        tracker.bytes_sent.fetch_add(bytes_sent, Ordering::Relaxed);
+        tracker.bytes_received.fetch_add(bytes_received, Ordering::Relaxed);
+        tracker.emit();
+    });
+}
+
+#[tokio::test]
+async fn test_track_progress() {
+    let records = Arc::new(Mutex::new(vec![]));
+    let collected = records.clone();
+    let sink: Arc<dyn ProgressSink> = Arc::new(move |p: &Progress| collected.lock().unwrap().push(p.clone()));
+
+    let output = track(Some(sink), async {
+        report(ProgressPhase::Sending, 120, 0);
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        report(ProgressPhase::Receiving, 0, 300);
+        "done"
+    })
+    .await;
+    assert_eq!(output, "done");
+    report(ProgressPhase::Sending, 1, 1);
+    assert_eq!(track(None, async { 1 }).await, 1);
+
+    let records = records.lock().unwrap();
+    let phases: Vec<ProgressPhase> = records.iter().map(|p| p.phase).collect();
+    assert_eq!(
+        phases,
+        vec![
+            ProgressPhase::Started,
+            ProgressPhase::Sending,
+            ProgressPhase::Sending,
+            ProgressPhase::Sending,
+            ProgressPhase::Receiving,
+            ProgressPhase::Finished,
+        ]
+    );
+    let last = records.last().unwrap();
+    assert_eq!((last.bytes_sent, last.bytes_received), (120, 300));
+    assert!(last.elapsed_ms >= 1200);
+}
@@ -0,0 +1,393 @@
+use crate::detect::detect;
+use crate::pipeline::Processor;
+#[cfg(test)]
+use crate::testing::task;
+use crate::{TranslateResult, TranslateTask};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 译文质量评估结果，`score` 取值 0~1，越高越好
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QualityEstimate {
+    /// 综合得分
+    pub score: f32,
+    /// 译文与原文的字符数之比
+    pub length_ratio: f32,
+    /// 译文疑似未翻译
+    pub untranslated: bool,
+    /// 重复程度，0 表示没有重复
+    pub repetition: f32,
+    /// 乱码字符占比
+    pub garbage: f32,
+    /// 与参考译文的 chrF 分数
+    pub chrf: Option<f32>,
+    /// 与参考译文的 BLEU 分数
+    pub bleu: Option<f32>,
+    /// 回译与原文的 chrF 分数，见 `verify::VerifyingTranslator`
+    #[serde(default)]
+    pub back_translation: Option<f32>,
+    /// 发现的问题
+    pub issues: Vec<String>,
+}
+
+/// 字符数之比，原文为空时返回 1
+pub fn length_ratio(source: &str, target: &str) -> f32 {
+    let source = source.chars().filter(|c| !c.is_whitespace()).count();
+    let target = target.chars().filter(|c| !c.is_whitespace()).count();
+
+    if source == 0 {
+        return 1.0;
+    }
+
+    target as f32 / source as f32
+}
+
+/// 重复程度：字符 4-gram 中重复出现部分的占比
+pub fn repetition(text: &str) -> f32 {
+    let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if chars.len() < 16 {
+        return 0.0;
+    }
+
+    let grams = ngrams(&chars, 4);
+    let total: usize = grams.values().sum();
+    1.0 - grams.len() as f32 / total as f32
+}
+
+/// 乱码字符占比，包括替换字符 U+FFFD 与除换行、制表符外的控制字符
+pub fn garbage(text: &str) -> f32 {
+    let total = text.chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+
+    let bad = text
+        .chars()
+        .filter(|&c| c == '\u{FFFD}' || (c.is_control() && !matches!(c, '\n' | '\r' | '\t')))
+        .count();
+    bad as f32 / total as f32
+}
+
+/// 译文是否基本保留了原文：内容相同，或识别出的语种仍为源语言
+pub fn is_untranslated(task: &TranslateTask, target: &str) -> bool {
+    let (Some(source_language), Some(target_language)) =
+        (&task.source_language, &task.target_language)
+    else {
+        return normalize(&task.content) == normalize(target);
+    };
+
+    if source_language.primary_language() == target_language.primary_language() {
+        return false;
+    }
+
+    if normalize(&task.content) == normalize(target) {
+        return true;
+    }
+
+    detect(target)
+        .first()
+        .map(|(lang, confidence)| {
+            *confidence > 0.5 && lang.primary_language() == source_language.primary_language()
+        })
+        .unwrap_or(false)
+}
+
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn ngrams<T: Clone + Eq + std::hash::Hash>(items: &[T], n: usize) -> HashMap<Vec<T>, usize> {
+    let mut map = HashMap::new();
+    if items.len() >= n {
+        for window in items.windows(n) {
+            *map.entry(window.to_vec()).or_insert(0) += 1;
+        }
+    }
+    map
+}
+
+fn overlap<T: Clone + Eq + std::hash::Hash>(
+    hypothesis: &HashMap<Vec<T>, usize>,
+    reference: &HashMap<Vec<T>, usize>,
+) -> usize {
+    hypothesis
+        .iter()
+        .map(|(gram, count)| (*count).min(*reference.get(gram).unwrap_or(&0)))
+        .sum()
+}
+
+/// chrF（字符 1~6-gram，β=2），取值 0~1
+pub fn chrf(hypothesis: &str, reference: &str) -> f32 {
+    let hypothesis: Vec<char> = hypothesis.chars().filter(|c| !c.is_whitespace()).collect();
+    let reference: Vec<char> = reference.chars().filter(|c| !c.is_whitespace()).collect();
+
+    let mut precision = 0.0;
+    let mut recall = 0.0;
+    let mut orders = 0;
+
+    for n in 1..=6 {
+        let h = ngrams(&hypothesis, n);
+        let r = ngrams(&reference, n);
+        let h_total: usize = h.values().sum();
+        let r_total: usize = r.values().sum();
+        if h_total == 0 || r_total == 0 {
+            continue;
+        }
+
+        let matched = overlap(&h, &r) as f32;
+        precision += matched / h_total as f32;
+        recall += matched / r_total as f32;
+        orders += 1;
+    }
+
+    if orders == 0 {
+        return 0.0;
+    }
+
+    let precision = precision / orders as f32;
+    let recall = recall / orders as f32;
+    let beta2 = 4.0;
+    if precision + recall == 0.0 {
+        return 0.0;
+    }
+
+    (1.0 + beta2) * precision * recall / (beta2 * precision + recall)
+}
+
+/// 按空白分词，CJK 字符单独成词
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    for word in text.split_whitespace() {
+        let mut current = String::new();
+        for c in word.chars() {
+            if is_cjk(c) {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                tokens.push(c.to_string());
+            } else {
+                current.push(c);
+            }
+        }
+        if !current.is_empty() {
+            tokens.push(current);
+        }
+    }
+    tokens
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{3000}'..='\u{303F}'
+        | '\u{FF00}'..='\u{FFEF}')
+}
+
+/// 句子级 BLEU（1~4-gram，加一平滑），取值 0~1
+pub fn bleu(hypothesis: &str, reference: &str) -> f32 {
+    let hypothesis = tokenize(hypothesis);
+    let reference = tokenize(reference);
+
+    if hypothesis.is_empty() || reference.is_empty() {
+        return 0.0;
+    }
+
+    let mut log_sum = 0.0;
+    for n in 1..=4 {
+        let h = ngrams(&hypothesis, n);
+        let r = ngrams(&reference, n);
+        let total: usize = h.values().sum();
+        let matched = overlap(&h, &r);
+
+        let precision = if n == 1 {
+            if matched == 0 {
+                return 0.0;
+            }
+            matched as f32 / total as f32
+        } else {
+            (matched as f32 + 1.0) / (total as f32 + 1.0)
+        };
+        log_sum += precision.ln() / 4.0;
+    }
+
+    let brevity = if hypothesis.len() >= reference.len() {
+        1.0
+    } else {
+        (1.0 - reference.len() as f32 / hypothesis.len() as f32).exp()
+    };
+
+    brevity * log_sum.exp()
+}
+
+/// 评估译文质量，`task.references` 中原文与任务原文一致的条目视为参考译文
+pub fn estimate(task: &TranslateTask, target: &str) -> QualityEstimate {
+    let mut estimate = QualityEstimate {
+        length_ratio: length_ratio(&task.content, target),
+        untranslated: is_untranslated(task, target),
+        repetition: repetition(target),
+        garbage: garbage(target),
+        ..Default::default()
+    };
+
+    let mut score: f32 = 1.0;
+
+    if target.trim().is_empty() {
+        if !task.content.trim().is_empty() {
+            estimate.issues.push("empty output".to_string());
+            score = 0.0;
+        }
+    } else {
+        if !(0.2..=4.0).contains(&estimate.length_ratio) {
+            estimate.issues.push(format!(
+                "suspicious length ratio: {:.2}",
+                estimate.length_ratio
+            ));
+            score -= 0.3;
+        }
+        if estimate.untranslated {
+            estimate
+                .issues
+                .push("output looks untranslated".to_string());
+            score -= 0.5;
+        }
+        if estimate.repetition > 0.5 {
+            estimate
+                .issues
+                .push(format!("repetitive output: {:.2}", estimate.repetition));
+            score -= 0.3;
+        }
+        if estimate.garbage > 0.05 {
+            estimate
+                .issues
+                .push(format!("garbage characters: {:.2}", estimate.garbage));
+            score -= 0.4;
+        }
+    }
+
+    let reference = task
+        .references
+        .iter()
+        .find(|item| item.source.trim() == task.content.trim());
+
+    if let Some(reference) = reference {
+        let chrf = chrf(target, &reference.target);
+        estimate.chrf = Some(chrf);
+        estimate.bleu = Some(bleu(target, &reference.target));
+        score = (score.max(0.0) + chrf) / 2.0;
+    }
+
+    estimate.score = score.clamp(0.0, 1.0);
+    estimate
+}
+
+/// 评估结果并写入 `TranslateResult::quality`
+pub fn annotate(task: &TranslateTask, result: &mut TranslateResult) {
+    let estimate = estimate(task, result.content.as_deref().unwrap_or(""));
+    result.confidence.get_or_insert(estimate.score);
+    result.quality = Some(estimate);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QualityConfig {
+    /// 低于该分数时返回错误，便于与 `FallbackTranslator` 配合切换服务
+    pub min_score: Option<f32>,
+}
+
+/// 将质量评估写入结果的处理步骤
+pub struct QualityProcessor {
+    config: QualityConfig,
+}
+
+impl QualityProcessor {
+    pub fn new(config: Value) -> Result<Self> {
+        let config = if config.is_null() {
+            QualityConfig::default()
+        } else {
+            serde_json::from_value(config)?
+        };
+
+        Ok(QualityProcessor { config })
+    }
+}
+
+#[async_trait]
+impl Processor for QualityProcessor {
+    async fn post_process(&self, task: &TranslateTask, result: &mut TranslateResult) -> Result<()> {
+        annotate(task, result);
+
+        if let (Some(min), Some(quality)) = (self.config.min_score, &result.quality) {
+            if quality.score < min {
+                bail!(
+                    "quality score {:.2} below {:.2}: [{}]",
+                    quality.score,
+                    min,
+                    quality.issues.join("; ")
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_metrics() {
+    assert_eq!(chrf("你好世界", "你好世界"), 1.0);
+    assert_eq!(chrf("abc", "xyz"), 0.0);
+    assert!((bleu("the cat sat on the mat", "the cat sat on the mat") - 1.0).abs() < 1e-6);
+    assert!(bleu("the cat", "the cat sat on the mat") < 0.5);
+
+    assert_eq!(repetition("你好"), 0.0);
+    assert!(repetition(&"哈哈哈哈".repeat(10)) > 0.9);
+    assert!(garbage("ab\u{FFFD}\u{FFFD}") > 0.4);
+    assert_eq!(length_ratio("", "abc"), 1.0);
+}
+
+#[test]
+fn test_estimate() {
+    let mut task = task("The quick brown fox jumps over the lazy dog.");
+
+    let good = estimate(&task, "敏捷的棕色狐狸跳过了那只懒狗。");
+    assert!(good.issues.is_empty());
+    assert_eq!(good.score, 1.0);
+
+    let untranslated = estimate(&task, "The quick brown fox jumps over the lazy dog.");
+    assert!(untranslated.untranslated);
+    assert!(untranslated.score < 0.6);
+
+    assert_eq!(estimate(&task, "").score, 0.0);
+
+    task.references.push(crate::TranslatedItem {
+        source: task.content.clone(),
+        target: "敏捷的棕色狐狸跳过了那只懒狗。".to_string(),
+    });
+    let mut result = TranslateResult {
+        content: Some("敏捷的棕色狐狸跳过了懒狗。".into()),
+        ..Default::default()
+    };
+    annotate(&task, &mut result);
+    let quality = result.quality.unwrap();
+    let chrf = quality.chrf.unwrap();
+    assert!(chrf > 0.5 && chrf < 1.0);
+    assert!(quality.bleu.is_some());
+    assert_eq!(result.confidence, Some(quality.score));
+
+    // 保留服务返回的置信度
+    let mut result = TranslateResult {
+        content: Some("敏捷的棕色狐狸跳过了懒狗。".into()),
+        confidence: Some(0.42),
+        ..Default::default()
+    };
+    annotate(&task, &mut result);
+    assert_eq!(result.confidence, Some(0.42));
+}
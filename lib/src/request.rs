@@ -0,0 +1,86 @@
+use crate::TranslateTask;
+use anyhow::Result;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+
+/// 附带请求 ID 的错误，便于与服务端日志对照。
+/// `XTranslateError::find` 仍可从错误链中取出原始错误
+#[derive(Debug)]
+pub struct RequestError {
+    pub request_id: String,
+    pub error: anyhow::Error,
+}
+
+impl Display for RequestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (request_id: {})", self.error, self.request_id)
+    }
+}
+
+impl std::error::Error for RequestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
+impl RequestError {
+    /// 从错误链中找出请求 ID
+    pub fn find(err: &anyhow::Error) -> Option<&str> {
+        err.chain()
+            .find_map(|e| e.downcast_ref::<RequestError>())
+            .map(|e| e.request_id.as_str())
+    }
+}
+
+/// 调用方未指定 `task.request_id` 时生成一个，写回任务并返回
+pub fn ensure_request_id(task: &mut TranslateTask) -> String {
+    task.request_id
+        .get_or_insert_with(|| uuid::Uuid::new_v4().to_string())
+        .clone()
+}
+
+/// 执行 `fut`，失败时将错误包装为 `RequestError`
+pub async fn with_request_id<T>(
+    request_id: &str,
+    fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    fut.await.map_err(|error| {
+        // 组合翻译器中可能已经包装过
+        if RequestError::find(&error).is_some() {
+            return error;
+        }
+        anyhow::Error::new(RequestError {
+            request_id: request_id.to_string(),
+            error,
+        })
+    })
+}
+
+#[tokio::test]
+async fn test_with_request_id() -> Result<()> {
+    use crate::error::XTranslateError;
+
+    let mut task = crate::testing::task("Hello");
+    let request_id = ensure_request_id(&mut task);
+    assert_eq!(task.request_id.as_deref(), Some(request_id.as_str()));
+    assert_eq!(ensure_request_id(&mut task), request_id);
+
+    let err = with_request_id("req-1", async {
+        Err::<(), _>(anyhow::Error::new(XTranslateError::Cancelled))
+    })
+    .await
+    .unwrap_err();
+    assert_eq!(err.to_string(), "cancelled (request_id: req-1)");
+    assert_eq!(RequestError::find(&err), Some("req-1"));
+    assert_eq!(
+        XTranslateError::find(&err),
+        Some(&XTranslateError::Cancelled)
+    );
+
+    let err = with_request_id("req-2", async { Err::<(), _>(err) })
+        .await
+        .unwrap_err();
+    assert_eq!(RequestError::find(&err), Some("req-1"));
+
+    Ok(())
+}
@@ -0,0 +1,317 @@
+use crate::error::XTranslateError;
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Sender;
+
+/// 按重试策略划分的错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    RateLimited,
+    /// 服务端 5xx 错误
+    Server,
+    Network,
+    Timeout,
+    /// 鉴权、配置、语言不支持等重试也不会成功的错误，以及未分类的错误
+    Permanent,
+}
+
+impl ErrorClass {
+    pub fn of(err: &anyhow::Error) -> Self {
+        match XTranslateError::find(err) {
+            Some(XTranslateError::RateLimited { .. }) => ErrorClass::RateLimited,
+            Some(XTranslateError::Network(_)) => ErrorClass::Network,
+            Some(XTranslateError::Timeout(_)) => ErrorClass::Timeout,
+            Some(e) if e.is_server_error() => ErrorClass::Server,
+            _ => ErrorClass::Permanent,
+        }
+    }
+}
+
+/// 一类错误的重试策略，等待时间按指数退避
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// 最多重试次数，0 表示不重试
+    pub max_retries: u32,
+    /// 首次重试前等待的时间（毫秒），之后每次翻倍
+    pub backoff_ms: u64,
+    /// 等待时间上限（毫秒），同样限制服务端要求的 `Retry-After`
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 2,
+            backoff_ms: 500,
+            max_backoff_ms: 10_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn never() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// 第 `retry` 次重试（从 0 开始）前等待的时间
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .backoff_ms
+            .saturating_mul(1u64.checked_shl(retry).unwrap_or(u64::MAX));
+        Duration::from_millis(delay.min(self.max_backoff_ms))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    pub rate_limited: RetryPolicy,
+    pub server: RetryPolicy,
+    pub network: RetryPolicy,
+    pub timeout: RetryPolicy,
+    /// 限流时优先使用服务端 `Retry-After` 指定的等待时间
+    pub respect_retry_after: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            rate_limited: RetryPolicy {
+                max_retries: 3,
+                backoff_ms: 1000,
+                max_backoff_ms: 30_000,
+            },
+            server: RetryPolicy::default(),
+            network: RetryPolicy::default(),
+            timeout: RetryPolicy {
+                max_retries: 1,
+                ..Default::default()
+            },
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// `Permanent` 类错误始终不重试
+    pub fn policy(&self, class: ErrorClass) -> Option<&RetryPolicy> {
+        match class {
+            ErrorClass::RateLimited => Some(&self.rate_limited),
+            ErrorClass::Server => Some(&self.server),
+            ErrorClass::Network => Some(&self.network),
+            ErrorClass::Timeout => Some(&self.timeout),
+            ErrorClass::Permanent => None,
+        }
+    }
+}
+
+/// 按错误类别重试：限流按 `Retry-After` 等待，5xx 与网络错误指数退避，鉴权失败、语言不支持等直接返回。
+/// 流式翻译只在尚未输出增量时重试
+pub struct RetryTranslator<T> {
+    inner: T,
+    config: RetryConfig,
+}
+
+impl<T: Translator> RetryTranslator<T> {
+    pub fn wrap(inner: T, config: RetryConfig) -> Self {
+        RetryTranslator { inner, config }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// 返回下次重试前等待的时间，不再重试时返回 `None`
+    fn next_delay(
+        &self,
+        err: &anyhow::Error,
+        retries: &mut HashMap<ErrorClass, u32>,
+    ) -> Option<Duration> {
+        let class = ErrorClass::of(err);
+        let policy = self.config.policy(class)?;
+
+        let count = retries.entry(class).or_default();
+        if *count >= policy.max_retries {
+            return None;
+        }
+
+        let retry_after = XTranslateError::find(err)
+            .and_then(|e| e.retry_after())
+            .filter(|_| self.config.respect_retry_after)
+            .map(|d| d.min(Duration::from_millis(policy.max_backoff_ms)));
+
+        let delay = retry_after.unwrap_or_else(|| policy.backoff(*count));
+        *count += 1;
+
+        Some(delay)
+    }
+}
+
+#[async_trait]
+impl<T> Translator for RetryTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 重试策略读取自 `config["retry"]`，其余配置原样传给被包装的翻译器
+    async fn new(config: Value) -> Result<Self> {
+        let retry_config = match config.get("retry") {
+            Some(retry) => serde_json::from_value(retry.clone())?,
+            None => RetryConfig::default(),
+        };
+
+        Ok(RetryTranslator::wrap(T::new(config).await?, retry_config))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let mut retries = HashMap::new();
+
+        loop {
+            match self.inner.translate(task.clone()).await {
+                Ok(result) => return Ok(result),
+                Err(e) => match self.next_delay(&e, &mut retries) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(e),
+                },
+            }
+        }
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let mut retries = HashMap::new();
+        let mut started = false;
+
+        loop {
+            let (tx, mut rx) = mpsc::channel(64);
+            let mut emitted = false;
+
+            let forward = async {
+                while let Some(chunk) = rx.recv().await {
+                    match &chunk {
+                        // 重试时不重复发送开始标记
+                        TranslateStreamChunk::Start if started => continue,
+                        TranslateStreamChunk::Start => started = true,
+                        TranslateStreamChunk::Delta(_) => emitted = true,
+                        TranslateStreamChunk::End => {}
+                    }
+                    sender.send(chunk).await?;
+                }
+                Ok::<_, anyhow::Error>(())
+            };
+
+            let (result, forwarded) =
+                tokio::join!(self.inner.translate_stream(task.clone(), tx), forward);
+            forwarded?;
+
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) if emitted => return Err(e),
+                Err(e) => match self.next_delay(&e, &mut retries) {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(e),
+                },
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_retry_translator() -> Result<()> {
+    let fast = RetryPolicy {
+        max_retries: 2,
+        backoff_ms: 1,
+        max_backoff_ms: 5,
+    };
+    let config = RetryConfig {
+        rate_limited: fast.clone(),
+        server: fast.clone(),
+        network: fast.clone(),
+        timeout: RetryPolicy::never(),
+        respect_retry_after: true,
+    };
+
+    let translator = RetryTranslator::wrap(
+        MockTranslator::flaky(
+            "T:",
+            vec![
+                XTranslateError::RateLimited {
+                    retry_after: Some(Duration::from_secs(60)),
+                },
+                XTranslateError::from_status(503, "unavailable"),
+                XTranslateError::Network("reset".to_string()),
+            ],
+        ),
+        config.clone(),
+    );
+    let result = translator.translate(task("Hello")).await?;
+    assert_eq!(result.content.as_deref(), Some("T:Hello"));
+    assert_eq!(translator.inner().calls(), 4);
+
+    // 鉴权失败不重试
+    let translator = RetryTranslator::wrap(
+        MockTranslator::flaky("", vec![XTranslateError::AuthFailed("bad key".to_string())]),
+        config.clone(),
+    );
+    assert!(translator.translate(task("Hello")).await.is_err());
+    assert_eq!(translator.inner().calls(), 1);
+
+    // 每类错误分别计数
+    let translator = RetryTranslator::wrap(
+        MockTranslator::flaky("", vec![XTranslateError::Network("reset".to_string()); 3]),
+        config,
+    );
+    let err = translator.translate(task("Hello")).await.unwrap_err();
+    assert_eq!(ErrorClass::of(&err), ErrorClass::Network);
+    assert_eq!(translator.inner().calls(), 3);
+
+    assert_eq!(fast.backoff(0), Duration::from_millis(1));
+    assert_eq!(fast.backoff(10), Duration::from_millis(5));
+
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// 配置中引用密钥的键名，如 `"api_key": {"$secret": "OPENAI_KEY"}`
+pub const SECRET_KEY: &str = "$secret";
+
+/// 默认的钥匙串服务名
+pub const DEFAULT_SERVICE: &str = "xtranslator";
+
+/// 密钥来源
+pub trait SecretProvider: Send + Sync {
+    /// 找不到时返回 `None`
+    fn get(&self, name: &str) -> Result<Option<String>>;
+}
+
+/// 从环境变量读取
+#[derive(Debug, Clone, Default)]
+pub struct EnvSecrets;
+
+impl SecretProvider for EnvSecrets {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        Ok(std::env::var(name).ok())
+    }
+}
+
+/// 从目录下与密钥同名的文件读取，如 Docker 与 Kubernetes 挂载的 `/run/secrets`。
+/// 去掉末尾的换行
+#[derive(Debug, Clone)]
+pub struct FileSecrets {
+    pub dir: PathBuf,
+}
+
+impl SecretProvider for FileSecrets {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        // 不允许跳出目录
+        if name.is_empty() || name.contains(['/', '\\']) || name == ".." {
+            bail!("invalid secret name: {}", name);
+        }
+
+        match std::fs::read_to_string(self.dir.join(name)) {
+            Ok(s) => Ok(Some(s.trim_end_matches(['\r', '\n']).to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// 执行命令并读取标准输出，参数中的 `{name}` 替换为密钥名，命令失败时视为找不到
+#[derive(Debug, Clone)]
+pub struct CommandSecrets {
+    pub command: Vec<String>,
+}
+
+impl CommandSecrets {
+    /// 系统钥匙串：macOS 使用 `security`，Linux 使用 libsecret 的 `secret-tool`
+    pub fn keyring(service: &str) -> Result<Self> {
+        let command: Vec<&str> = if cfg!(target_os = "macos") {
+            vec![
+                "security",
+                "find-generic-password",
+                "-s",
+                service,
+                "-a",
+                "{name}",
+                "-w",
+            ]
+        } else if cfg!(target_os = "linux") {
+            vec![
+                "secret-tool",
+                "lookup",
+                "service",
+                service,
+                "account",
+                "{name}",
+            ]
+        } else {
+            bail!("keyring is not supported on this platform");
+        };
+
+        Ok(CommandSecrets {
+            command: command.into_iter().map(|s| s.to_string()).collect(),
+        })
+    }
+}
+
+impl SecretProvider for CommandSecrets {
+    fn get(&self, name: &str) -> Result<Option<String>> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or(anyhow!("missing argument: command"))?;
+
+        let output = Command::new(program)
+            .args(args.iter().map(|arg| arg.replace("{name}", name)))
+            .output()?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            String::from_utf8(output.stdout)?
+                .trim_end_matches(['\r', '\n'])
+                .to_string(),
+        ))
+    }
+}
+
+/// 配置中 `secrets` 字段，选择密钥来源
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum SecretsConfig {
+    #[default]
+    Env,
+    File {
+        dir: PathBuf,
+    },
+    Keyring {
+        #[serde(default = "default_service")]
+        service: String,
+    },
+    Command {
+        command: Vec<String>,
+    },
+}
+
+fn default_service() -> String {
+    DEFAULT_SERVICE.to_string()
+}
+
+impl SecretsConfig {
+    pub fn build(&self) -> Result<Box<dyn SecretProvider>> {
+        Ok(match self {
+            SecretsConfig::Env => Box::new(EnvSecrets),
+            SecretsConfig::File { dir } => Box::new(FileSecrets { dir: dir.clone() }),
+            SecretsConfig::Keyring { service } => Box::new(CommandSecrets::keyring(service)?),
+            SecretsConfig::Command { command } => Box::new(CommandSecrets {
+                command: command.clone(),
+            }),
+        })
+    }
+}
+
+/// 将配置中所有 `{"$secret": "NAME"}` 替换为密钥，找不到时返回错误。
+/// 密钥来源读取自 `config["secrets"]`，未设置时使用环境变量
+pub fn resolve_secrets(config: &mut Value) -> Result<()> {
+    let secrets: SecretsConfig = match config.get("secrets") {
+        Some(secrets) => serde_json::from_value(secrets.clone())?,
+        None => SecretsConfig::default(),
+    };
+
+    resolve_with(config, secrets.build()?.as_ref())
+}
+
+pub fn resolve_with(value: &mut Value, provider: &dyn SecretProvider) -> Result<()> {
+    match value {
+        Value::Object(map) => {
+            if map.len() == 1 {
+                if let Some(Value::String(name)) = map.get(SECRET_KEY) {
+                    // 错误信息中只包含密钥名
+                    let secret = provider
+                        .get(name)?
+                        .ok_or(anyhow!("secret not found: {}", name))?;
+                    *value = Value::String(secret);
+                    return Ok(());
+                }
+            }
+
+            for item in map.values_mut() {
+                resolve_with(item, provider)?;
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                resolve_with(item, provider)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_resolve_secrets() -> Result<()> {
+    use serde_json::json;
+
+    let dir = std::env::temp_dir().join(format!("xtranslator-secrets-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("OPENAI_KEY"), "sk-test\n")?;
+
+    let mut config = json!({
+        "model": "gpt-4o-mini",
+        "api_key": { "$secret": "OPENAI_KEY" },
+        "http": { "headers": [{ "$secret": "OPENAI_KEY" }] },
+        "secrets": { "backend": "file", "dir": dir },
+    });
+    resolve_secrets(&mut config)?;
+    assert_eq!(config["api_key"], "sk-test");
+    assert_eq!(config["http"]["headers"][0], "sk-test");
+    assert_eq!(config["model"], "gpt-4o-mini");
+
+    let mut missing = json!({ "api_key": { "$secret": "MISSING_KEY" } });
+    let err = resolve_with(&mut missing, &FileSecrets { dir: dir.clone() }).unwrap_err();
+    assert_eq!(err.to_string(), "secret not found: MISSING_KEY");
+    assert!(FileSecrets { dir: dir.clone() }.get("../etc").is_err());
+
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
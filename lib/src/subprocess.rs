@@ -0,0 +1,371 @@
+use crate::error::XTranslateError;
+use crate::utils::language_pairs;
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::mpsc::Sender;
+use tokio::sync::oneshot;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubprocessConfig {
+    /// 插件程序及其参数
+    pub command: Vec<String>,
+    /// 额外的环境变量
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// 传给插件 `initialize` 的配置
+    #[serde(default)]
+    pub config: Value,
+}
+
+/// 插件对 `initialize` 的响应
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InitializeResult {
+    pub name: Option<String>,
+    /// 支持的源语言，`*` 表示任意语言
+    pub input_languages: Vec<String>,
+    /// 支持的目标语言，`*` 表示任意语言
+    pub output_languages: Vec<String>,
+    /// 只支持部分语言组合时列出，否则为源语言与目标语言两两组合
+    pub pairs: Option<Vec<(String, String)>>,
+    pub capabilities: Capabilities,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Incoming {
+    id: Option<u64>,
+    method: Option<String>,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    result: Value,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChunkParams {
+    id: u64,
+    chunk: TranslateStreamChunk,
+}
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value>>>>>;
+type Streams = Arc<Mutex<HashMap<u64, Sender<TranslateStreamChunk>>>>;
+
+fn exited() -> anyhow::Error {
+    anyhow!("plugin process exited")
+}
+
+async fn write_message(stdin: &tokio::sync::Mutex<ChildStdin>, message: &Value) -> Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+
+    let mut stdin = stdin.lock().await;
+    stdin.write_all(&line).await?;
+    stdin.flush().await?;
+
+    Ok(())
+}
+
+/// 在独立进程中运行的插件，通过标准输入输出交换 JSON-RPC 2.0 消息，每行一条。
+/// 插件崩溃只会使进行中的请求失败，不影响宿主，插件可以用任意语言编写。
+///
+/// 宿主发送的请求：
+/// - `initialize`，参数 `{"config": ...}`，返回 `InitializeResult`
+/// - `translate`，参数 `{"task": TranslateTask}`，返回 `TranslateResult`
+/// - `translate_stream`，参数同上，结束时返回 `null`。期间插件发送
+///   `stream_chunk` 通知，参数 `{"id": 请求 ID, "chunk": TranslateStreamChunk}`
+///
+/// 宿主发送的通知：`cancel`，参数 `{"id": 请求 ID}`，接收方不再需要该请求的增量。
+/// 插件返回的错误转为 `XTranslateError::ProviderError`，`code` 为 JSON-RPC 错误码
+pub struct SubprocessTranslator {
+    info: InitializeResult,
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    next_id: AtomicU64,
+    pending: Pending,
+    streams: Streams,
+    _child: Child,
+}
+
+impl SubprocessTranslator {
+    pub async fn spawn(config: SubprocessConfig) -> Result<Self> {
+        let (program, args) = config
+            .command
+            .split_first()
+            .ok_or(anyhow!("missing argument: command"))?;
+
+        let mut child = Command::new(program)
+            .args(args)
+            .envs(&config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let stdin = Arc::new(tokio::sync::Mutex::new(
+            child.stdin.take().ok_or(anyhow!("missing stdin"))?,
+        ));
+        let stdout = child.stdout.take().ok_or(anyhow!("missing stdout"))?;
+
+        let pending: Pending = Default::default();
+        let streams: Streams = Default::default();
+        tokio::spawn(Self::read_loop(
+            stdout,
+            stdin.clone(),
+            pending.clone(),
+            streams.clone(),
+        ));
+
+        let mut translator = SubprocessTranslator {
+            info: InitializeResult::default(),
+            stdin,
+            next_id: AtomicU64::new(1),
+            pending,
+            streams,
+            _child: child,
+        };
+
+        let info = translator
+            .call("initialize", json!({ "config": config.config }))
+            .await?;
+        translator.info = serde_json::from_value(info)?;
+
+        Ok(translator)
+    }
+
+    pub fn info(&self) -> &InitializeResult {
+        &self.info
+    }
+
+    async fn read_loop(
+        stdout: ChildStdout,
+        stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+        pending: Pending,
+        streams: Streams,
+    ) {
+        let mut lines = BufReader::new(stdout).lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            let Ok(message) = serde_json::from_str::<Incoming>(&line) else {
+                continue;
+            };
+
+            match (message.method.as_deref(), message.id) {
+                (Some("stream_chunk"), _) => {
+                    let Ok(params) = serde_json::from_value::<StreamChunkParams>(message.params)
+                    else {
+                        continue;
+                    };
+                    let sender = streams.lock().unwrap().get(&params.id).cloned();
+                    let Some(sender) = sender else {
+                        continue;
+                    };
+
+                    if sender.send(params.chunk).await.is_err() {
+                        streams.lock().unwrap().remove(&params.id);
+                        let cancel = json!({
+                            "jsonrpc": "2.0",
+                            "method": "cancel",
+                            "params": { "id": params.id },
+                        });
+                        let _ = write_message(&stdin, &cancel).await;
+                    }
+                }
+                (None, Some(id)) => {
+                    let Some(reply) = pending.lock().unwrap().remove(&id) else {
+                        continue;
+                    };
+                    let result = match message.error {
+                        Some(e) => Err(anyhow!(XTranslateError::ProviderError {
+                            code: e.code.to_string(),
+                            message: e.message,
+                        })),
+                        None => Ok(message.result),
+                    };
+                    let _ = reply.send(result);
+                }
+                _ => {}
+            }
+        }
+
+        // 进程已退出，进行中的请求全部失败
+        for (_, reply) in pending.lock().unwrap().drain() {
+            let _ = reply.send(Err(exited()));
+        }
+        streams.lock().unwrap().clear();
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.call_with_id(id, method, params).await
+    }
+
+    async fn call_with_id(&self, id: u64, method: &str, params: Value) -> Result<Value> {
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        if let Err(e) = write_message(&self.stdin, &request).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e.context(exited()));
+        }
+
+        rx.await.map_err(|_| exited())?
+    }
+
+    fn supports(languages: &[String], lang: &str) -> bool {
+        languages.iter().any(|l| l == "*" || l == lang)
+    }
+}
+
+#[async_trait]
+impl Translator for SubprocessTranslator {
+    type This = Self;
+
+    async fn new(config: Value) -> Result<Self> {
+        let config: SubprocessConfig = serde_json::from_value(config)
+            .map_err(|e| anyhow!(XTranslateError::InvalidConfig(e.to_string())))?;
+        SubprocessTranslator::spawn(config).await
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        Ok(self.info.input_languages.clone())
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        Ok(self.info.output_languages.clone())
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        Ok(Self::supports(&self.info.input_languages, &lang))
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        Ok(Self::supports(&self.info.output_languages, &lang))
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        match &self.info.pairs {
+            Some(pairs) => Ok(pairs.iter().any(|(s, t)| *s == source && *t == target)),
+            None => Ok(self.is_supported_input_language(source)?
+                && self.is_supported_output_language(target)?),
+        }
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        match &self.info.pairs {
+            Some(pairs) => Ok(pairs.clone()),
+            None => Ok(language_pairs(
+                &self.info.input_languages,
+                &self.info.output_languages,
+            )),
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.info.capabilities.clone()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let result = self.call("translate", json!({ "task": task })).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.streams.lock().unwrap().insert(id, sender);
+
+        let result = self
+            .call_with_id(id, "translate_stream", json!({ "task": task }))
+            .await;
+        self.streams.lock().unwrap().remove(&id);
+
+        result.map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_subprocess_translator() -> Result<()> {
+    use tokio::sync::mpsc;
+
+    // 按请求顺序回复的插件，第 5 个请求时退出
+    let script = r#"
+read line; echo '{"jsonrpc":"2.0","id":1,"result":{"name":"echo","input_languages":["*"],"output_languages":["zh-CN"]}}'
+read line; echo '{"jsonrpc":"2.0","id":2,"result":{"content":"你好"}}'
+read line
+echo '{"jsonrpc":"2.0","method":"stream_chunk","params":{"id":3,"chunk":"Start"}}'
+echo '{"jsonrpc":"2.0","method":"stream_chunk","params":{"id":3,"chunk":{"Delta":{"content":"你"}}}}'
+echo '{"jsonrpc":"2.0","method":"stream_chunk","params":{"id":3,"chunk":{"Delta":{"content":"好"}}}}'
+echo '{"jsonrpc":"2.0","method":"stream_chunk","params":{"id":3,"chunk":"End"}}'
+echo '{"jsonrpc":"2.0","id":3,"result":null}'
+read line; echo '{"jsonrpc":"2.0","id":4,"error":{"code":-32000,"message":"boom"}}'
+read line; exit 1
+"#;
+    let translator = SubprocessTranslator::new(json!({
+        "command": ["sh", "-c", script],
+    }))
+    .await?;
+    assert_eq!(translator.info().name.as_deref(), Some("echo"));
+    assert!(translator.is_supported_pair("en".to_string(), "zh-CN".to_string())?);
+    assert!(!translator.is_supported_output_language("en".to_string())?);
+
+    let task: TranslateTask = serde_json::from_value(json!({
+        "id": "1",
+        "content": "Hello",
+        "target_language": "zh-CN",
+        "terms": [],
+        "references": [],
+    }))?;
+
+    let result = translator.translate(task.clone()).await?;
+    assert_eq!(result.content.as_deref(), Some("你好"));
+
+    let (tx, mut rx) = mpsc::channel(64);
+    translator.translate_stream(task.clone(), tx).await?;
+    let mut content = String::new();
+    let mut chunks = 0;
+    while let Some(chunk) = rx.recv().await {
+        chunks += 1;
+        if let TranslateStreamChunk::Delta(delta) = chunk {
+            content.push_str(delta.content.as_deref().unwrap_or(""));
+        }
+    }
+    assert_eq!((chunks, content.as_str()), (4, "你好"));
+
+    let err = translator.translate(task.clone()).await.unwrap_err();
+    assert!(matches!(
+        XTranslateError::find(&err),
+        Some(XTranslateError::ProviderError { code, .. }) if code == "-32000"
+    ));
+
+    // 插件退出后请求失败而不是一直等待
+    let err = translator.translate(task).await.unwrap_err();
+    assert!(err.to_string().contains("plugin process exited"));
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use crate::TranslatedItem;
+
+/// 按单词匹配的术语只包含这些字符，其余（如中文、`C++`）按子串匹配
+fn is_word_term(term: &str) -> bool {
+    term.chars().any(|c| c.is_ascii_alphabetic())
+        && term
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c.is_whitespace() || c == '-' || c == '\'')
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// 去掉常见的复数、所有格与动词词尾后可能的词形
+fn variants(word: &str) -> Vec<String> {
+    let mut list = vec![word.to_string()];
+    let mut push = |stem: &str, tail: &str| {
+        if stem.chars().count() >= 2 {
+            list.push(format!("{}{}", stem, tail));
+        }
+    };
+
+    if let Some(stem) = word.strip_suffix("'s") {
+        push(stem, "");
+    }
+    if let Some(stem) = word.strip_suffix("ies") {
+        push(stem, "y");
+    }
+    if let Some(stem) = word.strip_suffix("es") {
+        push(stem, "");
+    }
+    if let Some(stem) = word.strip_suffix('s') {
+        push(stem, "");
+    }
+    if let Some(stem) = word.strip_suffix("ied") {
+        push(stem, "y");
+    }
+    for suffix in ["ing", "ed"] {
+        if let Some(stem) = word.strip_suffix(suffix) {
+            push(stem, "");
+            push(stem, "e");
+            // running -> run
+            let chars: Vec<char> = stem.chars().collect();
+            if chars.len() >= 3 && chars[chars.len() - 1] == chars[chars.len() - 2] {
+                push(&stem[..stem.len() - chars[chars.len() - 1].len_utf8()], "");
+            }
+        }
+    }
+
+    list
+}
+
+fn same_word(a: &str, b: &str) -> bool {
+    let b = variants(b);
+    variants(a).iter().any(|v| b.contains(v))
+}
+
+/// 原文中是否出现术语，不区分大小写，并容忍简单的词形变化（如复数、`-ed`、`-ing`）
+pub fn contains_term(text: &str, term: &str) -> bool {
+    let term = term.trim();
+    if term.is_empty() {
+        return false;
+    }
+
+    if !is_word_term(term) {
+        return text.to_lowercase().contains(&term.to_lowercase());
+    }
+
+    let text = words(text);
+    let term = words(term);
+    if term.is_empty() || term.len() > text.len() {
+        return false;
+    }
+
+    text.windows(term.len())
+        .any(|window| window.iter().zip(&term).all(|(a, b)| same_word(a, b)))
+}
+
+/// 选出要发送给服务端的术语：原文中出现的术语排在前面，其余保持原顺序，
+/// 设置了上限时只保留前 `max` 条
+pub fn select_terms(
+    text: &str,
+    terms: &[TranslatedItem],
+    max: Option<usize>,
+) -> Vec<TranslatedItem> {
+    let (mut present, absent): (Vec<TranslatedItem>, Vec<TranslatedItem>) = terms
+        .iter()
+        .cloned()
+        .partition(|term| contains_term(text, &term.source));
+
+    present.extend(absent);
+    if let Some(max) = max {
+        present.truncate(max);
+    }
+    present
+}
+
+#[test]
+fn test_contains_term() {
+    assert!(contains_term("I love Rust", "rust"));
+    assert!(contains_term("Two APIs were removed", "API"));
+    assert!(contains_term("The boxes are heavy", "box"));
+    assert!(contains_term("It uses a cache", "use"));
+    assert!(contains_term("The server is running", "run"));
+    assert!(contains_term("Libraries are loaded", "library"));
+    assert!(contains_term("The user's session expired", "user session"));
+    assert!(contains_term("欢迎使用机器翻译", "机器翻译"));
+    assert!(contains_term("Written in C++", "c++"));
+
+    assert!(!contains_term("This is good", "go"));
+    assert!(!contains_term("session of the user", "user session"));
+    assert!(!contains_term("anything", ""));
+}
+
+#[test]
+fn test_select_terms() {
+    let term = |source: &str| TranslatedItem {
+        source: source.to_string(),
+        target: String::new(),
+    };
+    let terms: Vec<TranslatedItem> = ["cat", "dog", "bird", "fish"].map(term).to_vec();
+
+    let selected = select_terms("Dogs and fish", &terms, Some(3));
+    assert_eq!(
+        selected
+            .iter()
+            .map(|t| t.source.as_str())
+            .collect::<Vec<_>>(),
+        vec!["dog", "fish", "cat"]
+    );
+    assert_eq!(select_terms("Dogs and fish", &terms, None).len(), 4);
+}
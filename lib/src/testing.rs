@@ -0,0 +1,192 @@
+use crate::error::XTranslateError;
+use crate::utils::normal2stream;
+use crate::{
+    Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator, Usage,
+};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+
+/// 测试用翻译器，译文为 `prefix + 原文`
+pub struct MockTranslator {
+    pub prefix: String,
+    pub fail: bool,
+    /// 支持的语言，为空表示全部支持
+    pub languages: Vec<String>,
+    /// 每次翻译前等待的时间
+    pub delay: Option<Duration>,
+    /// 流式翻译时逐词输出，每个词之前等待的时间
+    pub word_delay: Option<Duration>,
+    /// 依次返回的错误，用完后正常翻译
+    pub errors: Mutex<Vec<XTranslateError>>,
+    pub calls: AtomicUsize,
+}
+
+impl MockTranslator {
+    pub fn new(prefix: &str) -> Self {
+        MockTranslator {
+            prefix: prefix.to_string(),
+            fail: false,
+            languages: vec![],
+            delay: None,
+            word_delay: None,
+            errors: Mutex::new(vec![]),
+            calls: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn failing() -> Self {
+        MockTranslator {
+            fail: true,
+            ..MockTranslator::new("")
+        }
+    }
+
+    pub fn only(prefix: &str, languages: &[&str]) -> Self {
+        MockTranslator {
+            languages: languages.iter().map(|s| s.to_string()).collect(),
+            ..MockTranslator::new(prefix)
+        }
+    }
+
+    pub fn slow(prefix: &str, delay: Duration) -> Self {
+        MockTranslator {
+            delay: Some(delay),
+            ..MockTranslator::new(prefix)
+        }
+    }
+
+    pub fn streaming(prefix: &str, word_delay: Duration) -> Self {
+        MockTranslator {
+            word_delay: Some(word_delay),
+            ..MockTranslator::new(prefix)
+        }
+    }
+
+    pub fn flaky(prefix: &str, errors: Vec<XTranslateError>) -> Self {
+        MockTranslator {
+            errors: Mutex::new(errors.into_iter().rev().collect()),
+            ..MockTranslator::new(prefix)
+        }
+    }
+
+    fn supports(&self, lang: &str) -> bool {
+        self.languages.is_empty()
+            || self
+                .languages
+                .iter()
+                .any(|l| lang.split('-').next() == Some(l.as_str()))
+    }
+
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl Translator for MockTranslator {
+    type This = Self;
+
+    async fn new(config: Value) -> Result<Self> {
+        Ok(MockTranslator::new(config["prefix"].as_str().unwrap_or("")))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        Ok(vec!["*".to_string()])
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        Ok(vec!["*".to_string()])
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        Ok(self.supports(&lang))
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        Ok(self.supports(&lang))
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_streaming: self.word_delay.is_some(),
+            ..Default::default()
+        }
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+
+        if self.fail {
+            bail!("mock failure");
+        }
+
+        if let Some(err) = self.errors.lock().unwrap().pop() {
+            bail!(err);
+        }
+
+        Ok(TranslateResult {
+            reasoning: None,
+            content: Some(format!("{}{}", self.prefix, task.content)),
+            request_id: task.request_id.clone(),
+            usage: Some(Usage {
+                characters: task.content.chars().count() as u64,
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let Some(word_delay) = self.word_delay else {
+            return normal2stream(self, task, sender).await;
+        };
+
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let delta = |content: &str| {
+            TranslateStreamChunk::Delta(TranslateResult {
+                content: Some(content.to_string()),
+                ..Default::default()
+            })
+        };
+
+        sender.send(TranslateStreamChunk::Start).await?;
+        sender.send(delta(&self.prefix)).await?;
+        for word in task.content.split_inclusive(' ') {
+            tokio::time::sleep(word_delay).await;
+            sender.send(delta(word)).await?;
+        }
+        sender.send(TranslateStreamChunk::End).await?;
+
+        Ok(())
+    }
+}
+
+pub fn task(content: &str) -> TranslateTask {
+    TranslateTask {
+        id: "123456".to_string(),
+        content: content.to_string(),
+        source_language: Some("en-US".parse().unwrap()),
+        target_language: Some("zh-CN".parse().unwrap()),
+        user_prompt: None,
+        system_prompt: None,
+        field: None,
+        terms: vec![],
+        references: vec![],
+        extra: None,
+        request_id: None,
+        deadline_ms: None,
+    }
+}
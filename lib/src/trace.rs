@@ -0,0 +1,54 @@
+#[cfg(test)]
+use crate::testing::task;
+use crate::{TranslateTask, Usage};
+
+// `record_*` 在未启用 `tracing` 特性时为空操作，插件与组合翻译器可直接调用
+
+/// 任务的语言对，形如 `en-US->zh-CN`，未指定源语言时为 `auto`
+pub fn language_pair(task: &TranslateTask) -> String {
+    format!(
+        "{}->{}",
+        task.source_language
+            .as_ref()
+            .map(|tag| tag.as_str())
+            .unwrap_or("auto"),
+        task.target_language
+            .as_ref()
+            .map(|tag| tag.as_str())
+            .unwrap_or("?"),
+    )
+}
+
+/// 在当前 span 下记录一次请求的用量与耗时
+pub fn record_usage(usage: &Usage) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        prompt_tokens = usage.prompt_tokens,
+        completion_tokens = usage.completion_tokens,
+        characters = usage.characters,
+        latency_ms = usage.latency_ms,
+        request_id = usage.request_id.as_deref(),
+        "translate finished"
+    );
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = usage;
+}
+
+/// 记录可重试的失败，如组合翻译器切换到下一个翻译器
+pub fn record_failure(provider: &str, error: &anyhow::Error) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(provider, error = %error, "translator failed");
+
+    #[cfg(not(feature = "tracing"))]
+    let _ = (provider, error);
+}
+
+#[test]
+fn test_language_pair() {
+    let mut task = task("Hello");
+    assert_eq!(language_pair(&task), "en-US->zh-CN");
+
+    task.source_language = None;
+    assert_eq!(language_pair(&task), "auto->zh-CN");
+}
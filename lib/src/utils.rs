@@ -1,8 +1,16 @@
-use crate::{TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use crate::error::XTranslateError;
+use crate::{TranslateResult, TranslateStreamChunk, TranslateTask, Translator, Usage};
 use anyhow::{anyhow, Result};
 use handlebars::{
     Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderErrorReason,
 };
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{LazyLock, RwLock};
+use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
 #[cfg(test)]
@@ -10,7 +18,11 @@ use crate::TranslatedItem;
 #[cfg(test)]
 use serde_json::json;
 
-pub fn format_messages(template: &String, task: &TranslateTask) -> Result<String> {
+/// 缓存的模板数量上限，超过后清空重新编译
+const MAX_TEMPLATES: usize = 256;
+
+/// 已编译模板的注册表，以模板内容的哈希为名称，所有翻译器共享
+static TEMPLATES: LazyLock<RwLock<Handlebars<'static>>> = LazyLock::new(|| {
     let mut reg = Handlebars::new();
     reg.register_helper(
         "json",
@@ -30,8 +42,29 @@ pub fn format_messages(template: &String, task: &TranslateTask) -> Result<String
             },
         ),
     );
-    reg.render_template(template.as_str(), &task)
-        .map_err(|e| anyhow!(e))
+    RwLock::new(reg)
+});
+
+/// 渲染提示词模板，同一模板只编译一次
+pub fn format_messages(template: &String, task: &TranslateTask) -> Result<String> {
+    let name = hex::encode(Sha256::digest(template.as_bytes()));
+
+    {
+        let reg = TEMPLATES.read().unwrap();
+        if reg.has_template(&name) {
+            return reg.render(&name, &task).map_err(|e| anyhow!(e));
+        }
+    }
+
+    let mut reg = TEMPLATES.write().unwrap();
+    if !reg.has_template(&name) {
+        if reg.get_templates().len() >= MAX_TEMPLATES {
+            reg.clear_templates();
+        }
+        reg.register_template_string(&name, template)
+            .map_err(|e| anyhow!(e))?;
+    }
+    reg.render(&name, &task).map_err(|e| anyhow!(e))
 }
 
 pub async fn stream2normal(
@@ -43,18 +76,37 @@ pub async fn stream2normal(
     translator.translate_stream(task, tx).await?;
 
     let mut result = vec![];
+    let mut usage: Option<Usage> = None;
+    let mut request_id: Option<String> = None;
+    let mut partial = false;
+    let mut confidence: Option<f32> = None;
 
     while let Some(chunk) = rx.recv().await {
         if let TranslateStreamChunk::Delta(res) = chunk {
             if let Some(s) = res.content {
                 result.push(s);
             }
+            if let Some(u) = &res.usage {
+                usage.get_or_insert_with(Usage::default).add(u);
+            }
+            if request_id.is_none() {
+                request_id = res.request_id;
+            }
+            partial |= res.partial;
+            if let Some(c) = res.confidence {
+                confidence = Some(confidence.map_or(c, |v| v.min(c)));
+            }
         }
     }
 
     Ok(TranslateResult {
         reasoning: None,
         content: Some(result.join("")),
+        usage,
+        request_id,
+        partial,
+        confidence,
+        ..Default::default()
     })
 }
 
@@ -74,6 +126,322 @@ pub async fn normal2stream(
     Ok(())
 }
 
+/// 在截止时间内执行，超时返回错误
+pub async fn with_deadline<T>(
+    deadline: Option<Duration>,
+    fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout(deadline, fut)
+            .await
+            .map_err(|_| {
+                XTranslateError::Timeout(format!("deadline exceeded: {} ms", deadline.as_millis()))
+            })?,
+        None => fut.await,
+    }
+}
+
+/// 将配置中的 HTTP 头转换为 `HeaderMap`
+pub fn to_header_map(headers: &HashMap<String, String>) -> Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+
+    for (name, value) in headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| anyhow!("invalid header name {:?}: {}", name, e))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|e| anyhow!("invalid header value for {}: {}", name, e))?;
+        map.insert(name, value);
+    }
+
+    Ok(map)
+}
+
+/// 解析 OpenAI 兼容接口返回的 `usage` 与 `id` 字段，没有 `usage` 时返回 `None`
+pub fn openai_usage(response: &Value) -> Option<Usage> {
+    let usage = response.get("usage").filter(|u| u.is_object())?;
+
+    Some(Usage {
+        prompt_tokens: usage["prompt_tokens"].as_u64().unwrap_or(0),
+        completion_tokens: usage["completion_tokens"].as_u64().unwrap_or(0),
+        request_id: response["id"].as_str().map(|s| s.to_string()),
+        ..Default::default()
+    })
+}
+
+/// 由 OpenAI 兼容接口返回的 `logprobs` 计算置信度：各 token 对数概率的均值取指数
+pub fn openai_confidence(response: &Value) -> Option<f32> {
+    let tokens = response["choices"][0]["logprobs"]["content"].as_array()?;
+    let logprobs: Vec<f64> = tokens.iter().filter_map(|t| t["logprob"].as_f64()).collect();
+    if logprobs.is_empty() {
+        return None;
+    }
+
+    let mean = logprobs.iter().sum::<f64>() / logprobs.len() as f64;
+    Some(mean.exp().clamp(0.0, 1.0) as f32)
+}
+
+/// 生成类型的 JSON Schema
+pub fn schema_of<T: schemars::JsonSchema>() -> Value {
+    serde_json::to_value(schemars::schema_for!(T)).unwrap_or(Value::Null)
+}
+
+/// 源语言与目标语言两两组合，不含相同语言
+pub fn language_pairs(inputs: &[String], outputs: &[String]) -> Vec<(String, String)> {
+    let mut pairs = vec![];
+    for source in inputs {
+        for target in outputs {
+            if source != target {
+                pairs.push((source.clone(), target.clone()));
+            }
+        }
+    }
+    pairs
+}
+
+/// 检查翻译器是否支持任务的语言对，查询出错视为不支持
+pub fn supports_task(translator: &dyn crate::DynTranslator, task: &TranslateTask) -> bool {
+    if let (Some(source), Some(target)) = (&task.source_language, &task.target_language) {
+        return translator
+            .is_supported_pair(source.to_string(), target.to_string())
+            .unwrap_or(false);
+    }
+
+    let input = task
+        .source_language
+        .as_ref()
+        .map(|tag| {
+            translator
+                .is_supported_input_language(tag.to_string())
+                .unwrap_or(false)
+        })
+        .unwrap_or(true);
+
+    let output = task
+        .target_language
+        .as_ref()
+        .map(|tag| {
+            translator
+                .is_supported_output_language(tag.to_string())
+                .unwrap_or(false)
+        })
+        .unwrap_or(true);
+
+    input && output
+}
+
+/// 不作为句末的常见缩写
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "inc", "ltd",
+    "co", "no", "fig", "vol", "approx", "dept", "est", "u.s", "u.k", "jan", "feb", "mar", "apr",
+    "jun", "jul", "aug", "sep", "sept", "oct", "nov", "dec",
+];
+
+fn is_cjk_terminator(c: char) -> bool {
+    matches!(c, '。' | '！' | '？' | '；' | '…' | '｡')
+}
+
+fn is_closing(c: char) -> bool {
+    matches!(
+        c,
+        '"' | '\'' | ')' | ']' | '”' | '’' | '」' | '』' | '）' | '】' | '》' | '〉'
+    )
+}
+
+/// 按句子切分文本，每句包含其后的空白，所有句子拼接后与原文一致。
+/// 支持中日文标点，英文句点会跳过常见缩写、单字母缩写与小写开头的后文
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut sentences = vec![];
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (_, c) = chars[i];
+
+        let boundary = if is_cjk_terminator(c) {
+            while i + 1 < chars.len()
+                && (is_cjk_terminator(chars[i + 1].1) || is_closing(chars[i + 1].1))
+            {
+                i += 1;
+            }
+            true
+        } else if matches!(c, '.' | '!' | '?') {
+            let mark = i;
+            while i + 1 < chars.len()
+                && (matches!(chars[i + 1].1, '.' | '!' | '?') || is_closing(chars[i + 1].1))
+            {
+                i += 1;
+            }
+
+            let next = chars.get(i + 1).map(|&(_, c)| c);
+            let after = chars[i + 1..]
+                .iter()
+                .map(|&(_, c)| c)
+                .find(|c| !c.is_whitespace());
+
+            let word: String = chars[..mark]
+                .iter()
+                .rev()
+                .map(|&(_, c)| c)
+                .take_while(|c| c.is_alphanumeric() || *c == '.')
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .collect();
+
+            let abbreviation = c == '.'
+                && mark == i
+                && (ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+                    || (word.chars().count() == 1 && word.chars().all(|c| c.is_uppercase())));
+
+            next.map(|c| c.is_whitespace()).unwrap_or(true)
+                && !abbreviation
+                && !after.map(|c| c.is_lowercase()).unwrap_or(false)
+        } else if c == '\n' {
+            // 空行分段
+            chars.get(i + 1).map(|&(_, c)| c == '\n').unwrap_or(false)
+        } else {
+            false
+        };
+
+        i += 1;
+
+        if boundary {
+            while i < chars.len() && chars[i].1.is_whitespace() {
+                i += 1;
+            }
+            let end = chars.get(i).map(|&(pos, _)| pos).unwrap_or(text.len());
+            sentences.push(text[start..end].to_string());
+            start = end;
+        }
+    }
+
+    if start < text.len() {
+        sentences.push(text[start..].to_string());
+    }
+
+    sentences
+}
+
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ThinkState {
+    /// 尚未出现非空白内容
+    #[default]
+    Start,
+    Think,
+    /// 推理结束，跳过紧随其后的空白
+    AfterThink,
+    Content,
+}
+
+/// 解析部分兼容接口混在正文开头的 `<think>...</think>` 推理内容，支持流式输入。
+/// 标签被拆分到多个增量中时会暂存，直到能够判断
+#[derive(Debug, Default)]
+pub struct ThinkParser {
+    state: ThinkState,
+    buffer: String,
+}
+
+impl ThinkParser {
+    pub fn new() -> Self {
+        ThinkParser::default()
+    }
+
+    /// 输入一段增量内容，返回此时可以输出的 `(推理, 正文)`
+    pub fn push(&mut self, delta: &str) -> (Option<String>, Option<String>) {
+        self.buffer.push_str(delta);
+
+        let mut reasoning = String::new();
+        let mut content = String::new();
+
+        loop {
+            match self.state {
+                ThinkState::Start => {
+                    let trimmed = self.buffer.trim_start();
+                    if let Some(rest) = trimmed.strip_prefix(THINK_OPEN) {
+                        self.buffer = rest.to_string();
+                        self.state = ThinkState::Think;
+                    } else if THINK_OPEN.starts_with(trimmed) {
+                        break;
+                    } else {
+                        self.state = ThinkState::Content;
+                    }
+                }
+                ThinkState::Think => match self.buffer.find(THINK_CLOSE) {
+                    Some(end) => {
+                        reasoning.push_str(&self.buffer[..end]);
+                        self.buffer = self.buffer[end + THINK_CLOSE.len()..].to_string();
+                        self.state = ThinkState::AfterThink;
+                    }
+                    None => {
+                        // 末尾可能是被拆开的结束标签
+                        let keep = (1..THINK_CLOSE.len())
+                            .rev()
+                            .find(|&n| self.buffer.ends_with(&THINK_CLOSE[..n]))
+                            .unwrap_or(0);
+                        let emit = self.buffer.len() - keep;
+                        reasoning.push_str(&self.buffer[..emit]);
+                        self.buffer.drain(..emit);
+                        break;
+                    }
+                },
+                ThinkState::AfterThink => {
+                    let trimmed = self.buffer.trim_start();
+                    if trimmed.is_empty() {
+                        self.buffer.clear();
+                        break;
+                    }
+                    self.buffer = trimmed.to_string();
+                    self.state = ThinkState::Content;
+                }
+                ThinkState::Content => {
+                    content.push_str(&self.buffer);
+                    self.buffer.clear();
+                    break;
+                }
+            }
+        }
+
+        (non_empty(reasoning), non_empty(content))
+    }
+
+    /// 输入结束时取出暂存的内容，未闭合的推理按推理输出
+    pub fn finish(&mut self) -> (Option<String>, Option<String>) {
+        let rest = std::mem::take(&mut self.buffer);
+        match std::mem::replace(&mut self.state, ThinkState::Content) {
+            ThinkState::Think => (non_empty(rest), None),
+            ThinkState::AfterThink => (None, None),
+            ThinkState::Start | ThinkState::Content => (None, non_empty(rest)),
+        }
+    }
+}
+
+fn non_empty(s: String) -> Option<String> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+/// 拆分正文开头的 `<think>...</think>`，返回 `(推理, 正文)`，没有推理时正文原样返回
+pub fn split_think(content: &str) -> (Option<String>, String) {
+    let mut parser = ThinkParser::new();
+    let (reasoning, text) = parser.push(content);
+    let (rest_reasoning, rest_text) = parser.finish();
+
+    let reasoning = match (reasoning, rest_reasoning) {
+        (Some(a), Some(b)) => Some(a + &b),
+        (a, b) => a.or(b),
+    };
+    let text = text.unwrap_or_default() + &rest_text.unwrap_or_default();
+
+    (reasoning.map(|r| r.trim().to_string()), text)
+}
+
 #[test]
 fn test_format_messages() -> Result<()> {
     let task = TranslateTask {
@@ -87,6 +455,8 @@ fn test_format_messages() -> Result<()> {
         terms: vec![],
         references: vec![],
         extra: None,
+        request_id: None,
+        deadline_ms: None,
     };
 
     let template =
@@ -140,6 +510,8 @@ fn test_format_messages2() -> Result<()> {
                 },
             ]
         })),
+        request_id: None,
+        deadline_ms: None,
     };
 
     let template = r###"## 领域描述
@@ -169,6 +541,55 @@ fn test_format_messages2() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_format_messages_cache() -> Result<()> {
+    let mut task = crate::testing::task("Hello");
+    let template = "{{ content }} -> {{ target_language }}".to_string();
+
+    assert_eq!(format_messages(&template, &task)?, "Hello -> zh-CN");
+    task.content = "World".to_string();
+    assert_eq!(format_messages(&template, &task)?, "World -> zh-CN");
+
+    let name = hex::encode(Sha256::digest(template.as_bytes()));
+    assert!(TEMPLATES.read().unwrap().has_template(&name));
+
+    assert!(format_messages(&"{{#if}}".to_string(), &task).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_with_deadline() -> Result<()> {
+    let ok = with_deadline(Some(Duration::from_millis(100)), async { Ok(1) }).await?;
+    assert_eq!(ok, 1);
+
+    let err = with_deadline(Some(Duration::from_millis(10)), async {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        Ok(2)
+    })
+    .await;
+    assert!(err.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_to_header_map() -> Result<()> {
+    let headers = HashMap::from([
+        ("X-Org-Id".to_string(), "org-123".to_string()),
+        ("x-trace".to_string(), "abc".to_string()),
+    ]);
+
+    let map = to_header_map(&headers)?;
+    assert_eq!(map["x-org-id"], "org-123");
+    assert_eq!(map["x-trace"], "abc");
+
+    let invalid = HashMap::from([("bad header".to_string(), "v".to_string())]);
+    assert!(to_header_map(&invalid).is_err());
+
+    Ok(())
+}
+
 pub async fn test_translate<T: Translator>(translator: T) -> Result<()> {
     let task = TranslateTask {
         id: "123456".to_string(),
@@ -181,6 +602,8 @@ pub async fn test_translate<T: Translator>(translator: T) -> Result<()> {
         terms: vec![],
         references: vec![],
         extra: None,
+        request_id: None,
+        deadline_ms: None,
     };
 
     let result = translator.translate(task).await?;
@@ -202,6 +625,8 @@ pub async fn test_translate_stream<T: Translator>(translator: T) -> Result<()> {
         terms: vec![],
         references: vec![],
         extra: None,
+        request_id: None,
+        deadline_ms: None,
     };
 
     let (tx, mut rx) = tokio::sync::mpsc::channel(64);
@@ -218,3 +643,83 @@ pub async fn test_translate_stream<T: Translator>(translator: T) -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_split_sentences() {
+    let text = "Dr. Smith arrived at 3.14 p.m. today. He said \"Hi!\" Then left... e.g. this stays. 你好。今天天气很好！「真的吗？」好的\n\nNew paragraph";
+    let sentences = split_sentences(text);
+
+    assert_eq!(
+        sentences,
+        vec![
+            "Dr. Smith arrived at 3.14 p.m. today. ",
+            "He said \"Hi!\" ",
+            "Then left... e.g. this stays. ",
+            "你好。",
+            "今天天气很好！",
+            "「真的吗？」",
+            "好的\n\n",
+            "New paragraph",
+        ]
+    );
+    assert_eq!(sentences.concat(), text);
+    assert!(split_sentences("").is_empty());
+}
+
+#[test]
+fn test_language_pairs() {
+    let langs = vec!["en".to_string(), "zh".to_string()];
+    assert_eq!(
+        language_pairs(&langs, &langs),
+        vec![
+            ("en".to_string(), "zh".to_string()),
+            ("zh".to_string(), "en".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_split_think() {
+    assert_eq!(
+        split_think("<think>\nuser wants zh\n</think>\n\n你好"),
+        (Some("user wants zh".to_string()), "你好".to_string())
+    );
+    assert_eq!(split_think("你好"), (None, "你好".to_string()));
+    assert_eq!(
+        split_think("说 <think> 也是正文"),
+        (None, "说 <think> 也是正文".to_string())
+    );
+    assert_eq!(
+        split_think("<think>未闭合"),
+        (Some("未闭合".to_string()), String::new())
+    );
+
+    // 按字符逐个输入，标签被拆开时结果不变
+    let text = "  <think>推理</thi></think>\n正文</think>";
+    let mut parser = ThinkParser::new();
+    let (mut reasoning, mut content) = (String::new(), String::new());
+    for c in text.chars() {
+        let (r, t) = parser.push(&c.to_string());
+        reasoning.push_str(&r.unwrap_or_default());
+        content.push_str(&t.unwrap_or_default());
+    }
+    let (r, t) = parser.finish();
+    reasoning.push_str(&r.unwrap_or_default());
+    content.push_str(&t.unwrap_or_default());
+
+    assert_eq!(reasoning, "推理</thi>");
+    assert_eq!(content, "正文</think>");
+}
+
+#[test]
+fn test_openai_confidence() {
+    let response = json!({
+        "choices": [{
+            "logprobs": { "content": [{ "logprob": 0.0 }, { "logprob": -0.2 }] }
+        }]
+    });
+    let confidence = openai_confidence(&response).unwrap();
+    assert!((confidence - (-0.1f32).exp()).abs() < 1e-6);
+
+    assert_eq!(openai_confidence(&json!({ "choices": [{}] })), None);
+}
@@ -0,0 +1,147 @@
+use crate::http::HttpConfig;
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+#[cfg(test)]
+use serde_json::json;
+use serde_json::Value;
+
+/// 配置中的一个问题
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigIssue {
+    /// 字段名
+    pub field: String,
+    /// 问题描述
+    pub message: String,
+}
+
+/// 逐项检查配置字段并收集问题，不会发起任何网络请求
+pub struct ConfigValidator<'a> {
+    config: &'a Value,
+    issues: Vec<ConfigIssue>,
+}
+
+impl<'a> ConfigValidator<'a> {
+    pub fn new(config: &'a Value) -> Self {
+        let mut validator = ConfigValidator {
+            config,
+            issues: vec![],
+        };
+
+        if !config.is_object() {
+            validator.issue("", "config must be an object");
+        }
+
+        validator
+    }
+
+    pub fn issue(&mut self, field: &str, message: impl Into<String>) {
+        self.issues.push(ConfigIssue {
+            field: field.to_string(),
+            message: message.into(),
+        });
+    }
+
+    /// 可选的字符串字段，存在但不是字符串时记录问题
+    pub fn optional_str(&mut self, field: &str) -> Option<&'a str> {
+        match self.config.get(field) {
+            None | Some(Value::Null) => None,
+            Some(Value::String(s)) => Some(s.as_str()),
+            Some(_) => {
+                self.issue(field, "must be a string");
+                None
+            }
+        }
+    }
+
+    /// 必填的非空字符串字段
+    pub fn require_str(&mut self, field: &str) -> Option<&'a str> {
+        let present = matches!(self.config.get(field), Some(v) if !v.is_null());
+        match self.optional_str(field) {
+            Some(s) if s.trim().is_empty() => {
+                self.issue(field, "must not be empty");
+                None
+            }
+            Some(s) => Some(s),
+            None => {
+                if !present {
+                    self.issue(field, "is required");
+                }
+                None
+            }
+        }
+    }
+
+    /// 取值必须在给定列表中
+    pub fn one_of(&mut self, field: &str, allowed: &[&str]) {
+        if let Some(value) = self.require_str(field) {
+            if !allowed.contains(&value) {
+                self.issue(field, format!("must be one of {:?}", allowed));
+            }
+        }
+    }
+
+    /// `http://` 或 `https://` 地址
+    pub fn url(&mut self, field: &str) {
+        if let Some(value) = self.require_str(field) {
+            if !(value.starts_with("http://") || value.starts_with("https://")) {
+                self.issue(field, "must start with http:// or https://");
+            }
+        }
+    }
+
+    /// 可选的提示词模板，检查能否编译
+    pub fn template(&mut self, field: &str) {
+        if let Some(template) = self.optional_str(field) {
+            if let Err(e) = Handlebars::new().register_template_string(field, template) {
+                self.issue(field, format!("invalid template: {}", e.reason()));
+            }
+        }
+    }
+
+    /// 检查以 `#[serde(flatten)]` 嵌入的 `HttpConfig`
+    pub fn http(&mut self) {
+        match serde_json::from_value::<HttpConfig>(self.config.clone()) {
+            Ok(http) => {
+                if let Err(e) = http.client_builder() {
+                    self.issue("http", e.to_string());
+                }
+            }
+            Err(e) => self.issue("http", e.to_string()),
+        }
+    }
+
+    pub fn finish(self) -> Vec<ConfigIssue> {
+        self.issues
+    }
+}
+
+#[test]
+fn test_config_validator() {
+    let config = json!({
+        "model": "unknown",
+        "api_base": "example.com",
+        "api_key": " ",
+        "prompt": "{{#if}}",
+        "proxy": { "url": "not a url" },
+    });
+
+    let mut validator = ConfigValidator::new(&config);
+    validator.one_of("model", &["a", "b"]);
+    validator.url("api_base");
+    validator.require_str("api_key");
+    validator.require_str("secret");
+    validator.template("prompt");
+    validator.http();
+
+    let fields: Vec<String> = validator.finish().into_iter().map(|i| i.field).collect();
+    assert_eq!(
+        fields,
+        vec!["model", "api_base", "api_key", "secret", "prompt", "http"]
+    );
+
+    let config = json!({ "api_key": "key", "timeout_ms": 1000 });
+    let mut validator = ConfigValidator::new(&config);
+    validator.require_str("api_key");
+    validator.http();
+    assert!(validator.finish().is_empty());
+}
@@ -0,0 +1,315 @@
+use crate::detect::detect;
+use crate::ffi_proxy::ProxyTranslatorFactory;
+use crate::qe::{annotate, chrf};
+#[cfg(test)]
+use crate::testing::{task, MockTranslator};
+use crate::{
+    BoxedTranslator, Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask,
+    TranslatedItem, Translator, TranslatorFactory, TranslatorSpec,
+};
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::Sender;
+
+/// 重试时默认使用的系统提示词
+const STRICT_PROMPT: &str = r##"请将以下{{ source_language }}内容忠实地翻译为{{ target_language }}，确保符合以下要求：
+1. 逐句翻译，不要遗漏、增加或改写任何信息
+2. 保留数字、专有名词与原有格式
+3. 只输出译文，不要输出其它内容"##;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VerifyConfig {
+    /// 回译与原文的 chrF 低于该值视为未通过
+    pub min_similarity: f32,
+    /// 未通过时重新翻译的次数
+    pub max_retries: usize,
+    /// 重新翻译时使用的系统提示词，仅对支持提示词的服务生效
+    pub strict_prompt: Option<String>,
+    /// 重试后仍未通过时返回错误，默认只在质量评估中标记
+    pub fail_on_mismatch: bool,
+    /// 用于回译的翻译器，默认使用被包装的翻译器
+    pub verifier: Option<TranslatorSpec>,
+}
+
+impl Default for VerifyConfig {
+    fn default() -> Self {
+        VerifyConfig {
+            min_similarity: 0.4,
+            max_retries: 1,
+            strict_prompt: None,
+            fail_on_mismatch: false,
+            verifier: None,
+        }
+    }
+}
+
+/// 回译校验：将译文翻译回源语言并与原文比较，未通过时换用更严格的提示词重试，
+/// 仍未通过则在 `quality.issues` 中标记，适合无人值守的批量任务。
+/// 回译相似度写入 `quality.back_translation`，所有请求的用量累加到结果中。
+/// 流式翻译不做校验
+pub struct VerifyingTranslator<T> {
+    inner: T,
+    verifier: Option<BoxedTranslator>,
+    config: VerifyConfig,
+}
+
+impl<T> VerifyingTranslator<T> {
+    /// 使用被包装的翻译器回译
+    pub fn wrap(inner: T, config: VerifyConfig) -> Self {
+        VerifyingTranslator {
+            inner,
+            verifier: None,
+            config,
+        }
+    }
+
+    pub fn with_verifier(inner: T, verifier: BoxedTranslator, config: VerifyConfig) -> Self {
+        VerifyingTranslator {
+            inner,
+            verifier: Some(verifier),
+            config,
+        }
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> VerifyingTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    fn verifier(&self) -> &dyn crate::DynTranslator {
+        match &self.verifier {
+            Some(verifier) => verifier.as_ref(),
+            None => &self.inner,
+        }
+    }
+
+    /// 回译并返回与原文的相似度，无法确定源语言时返回 `None`
+    async fn back_translate(
+        &self,
+        task: &TranslateTask,
+        result: &mut TranslateResult,
+    ) -> Result<Option<f32>> {
+        let source_language = match &task.source_language {
+            Some(tag) => tag.clone(),
+            None => match detect(&task.content).into_iter().next() {
+                Some((tag, _)) => tag,
+                None => return Ok(None),
+            },
+        };
+
+        let back_task = TranslateTask {
+            id: task.id.clone(),
+            content: result.content.clone().unwrap_or_default(),
+            source_language: task.target_language.clone(),
+            target_language: Some(source_language),
+            user_prompt: None,
+            system_prompt: None,
+            field: task.field.clone(),
+            terms: task
+                .terms
+                .iter()
+                .map(|term| TranslatedItem {
+                    source: term.target.clone(),
+                    target: term.source.clone(),
+                })
+                .collect(),
+            references: vec![],
+            extra: None,
+            request_id: None,
+            deadline_ms: None,
+        };
+
+        let back = self.verifier().translate(back_task).await?;
+        if let Some(usage) = &back.usage {
+            result.add_usage(usage);
+        }
+
+        Ok(Some(chrf(
+            back.content.as_deref().unwrap_or(""),
+            &task.content,
+        )))
+    }
+}
+
+#[async_trait]
+impl<T> Translator for VerifyingTranslator<T>
+where
+    T: Translator<This = T> + Send + Sync,
+{
+    type This = Self;
+
+    /// 校验参数读取自 `config["verify"]`，其余配置原样传给被包装的翻译器。
+    /// 设置了 `verifier` 时按 `plugin_dir` 加载回译插件
+    async fn new(config: Value) -> Result<Self> {
+        let verify_config: VerifyConfig = match config.get("verify") {
+            Some(verify) => serde_json::from_value(verify.clone())?,
+            None => VerifyConfig::default(),
+        };
+
+        let verifier = match &verify_config.verifier {
+            Some(spec) => Some(
+                ProxyTranslatorFactory::from_config(&config)?
+                    .create(&spec.name, spec.config.clone())
+                    .await?,
+            ),
+            None => None,
+        };
+
+        let inner = T::new(config).await?;
+
+        Ok(VerifyingTranslator {
+            inner,
+            verifier,
+            config: verify_config,
+        })
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.inner.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.inner.is_supported_output_language(lang)
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        self.inner.is_supported_pair(source, target)
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        self.inner.get_supported_pairs()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let mut attempt = task.clone();
+        let mut usage = None;
+        let mut best: Option<(f32, TranslateResult)> = None;
+
+        for _ in 0..=self.config.max_retries {
+            let mut result = self.inner.translate(attempt.clone()).await?;
+            let similarity = self.back_translate(&task, &mut result).await?;
+
+            annotate(&task, &mut result);
+            if let Some(quality) = &mut result.quality {
+                quality.back_translation = similarity;
+            }
+
+            // 之前各次尝试的用量
+            if let Some(usage) = &usage {
+                result.add_usage(usage);
+            }
+            usage = result.usage.clone();
+
+            let Some(similarity) = similarity else {
+                return Ok(result);
+            };
+            if similarity >= self.config.min_similarity {
+                return Ok(result);
+            }
+
+            if best.as_ref().is_none_or(|(best, _)| similarity > *best) {
+                best = Some((similarity, result));
+            }
+
+            attempt.system_prompt = Some(
+                self.config
+                    .strict_prompt
+                    .clone()
+                    .unwrap_or_else(|| STRICT_PROMPT.to_string()),
+            );
+        }
+
+        let (similarity, mut result) = best.unwrap();
+        result.usage = usage;
+
+        if self.config.fail_on_mismatch {
+            bail!(
+                "back-translation similarity {:.2} below {:.2}",
+                similarity,
+                self.config.min_similarity
+            );
+        }
+
+        if let Some(quality) = &mut result.quality {
+            quality
+                .issues
+                .push(format!("back-translation mismatch: {:.2}", similarity));
+        }
+
+        Ok(result)
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        self.inner.translate_stream(task, sender).await
+    }
+}
+
+#[tokio::test]
+async fn test_verifying_translator() -> Result<()> {
+    // 回译与原文一致
+    let translator = VerifyingTranslator::wrap(MockTranslator::new(""), VerifyConfig::default());
+    let result = translator.translate(task("Hello world")).await?;
+    let quality = result.quality.unwrap();
+    assert_eq!(quality.back_translation, Some(1.0));
+    assert!(!quality
+        .issues
+        .iter()
+        .any(|i| i.contains("back-translation")));
+    // 翻译与回译各一次
+    assert_eq!(translator.inner().calls(), 2);
+    assert_eq!(result.usage.unwrap().characters, 22);
+
+    let config = VerifyConfig {
+        min_similarity: 0.9,
+        max_retries: 2,
+        ..Default::default()
+    };
+    let translator = VerifyingTranslator::with_verifier(
+        MockTranslator::new(""),
+        Box::new(MockTranslator::new("Something else entirely: ")),
+        config.clone(),
+    );
+    let result = translator.translate(task("Hello world")).await?;
+    let quality = result.quality.unwrap();
+    assert!(quality.back_translation.unwrap() < 0.9);
+    assert!(quality
+        .issues
+        .iter()
+        .any(|i| i.contains("back-translation")));
+    assert_eq!(translator.inner().calls(), 3);
+
+    let translator = VerifyingTranslator::with_verifier(
+        MockTranslator::new(""),
+        Box::new(MockTranslator::new("Something else entirely: ")),
+        VerifyConfig {
+            fail_on_mismatch: true,
+            ..config
+        },
+    );
+    assert!(translator.translate(task("Hello world")).await.is_err());
+
+    Ok(())
+}
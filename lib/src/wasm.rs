@@ -0,0 +1,398 @@
+use crate::error::XTranslateError;
+use crate::ffi::{restore_error, PluginMetadata};
+use crate::validate::ConfigIssue;
+use crate::{Capabilities, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::mpsc::Sender;
+use wasmtime::component::{Component, HasSelf, Linker, ResourceAny, ResourceTable};
+use wasmtime::{Engine, Store};
+use wasmtime_wasi::{WasiCtx, WasiCtxView, WasiView};
+
+wasmtime::component::bindgen!({
+    path: "../wit/translator.wit",
+    world: "plugin",
+});
+
+use exports::xtranslator::plugin::translator::{GuestTranslator, PluginError};
+use xtranslator::plugin::types::StreamChunk;
+
+/// 所有组件共用的引擎
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(Engine::default)
+}
+
+/// 插件返回的错误，`message` 格式与 FFI 的错误信息相同
+fn plugin_error(err: PluginError) -> anyhow::Error {
+    restore_error(&err.message, str::to_string)
+}
+
+/// 组件实例的宿主状态
+struct State {
+    wasi: WasiCtx,
+    table: ResourceTable,
+    /// 进行中的流式翻译，`push-chunk` 按 ID 转发增量
+    streams: HashMap<u64, Sender<TranslateStreamChunk>>,
+}
+
+impl WasiView for State {
+    fn ctx(&mut self) -> WasiCtxView<'_> {
+        WasiCtxView {
+            ctx: &mut self.wasi,
+            table: &mut self.table,
+        }
+    }
+}
+
+impl xtranslator::plugin::types::Host for State {}
+
+impl xtranslator::plugin::host::Host for State {
+    /// 插件在宿主的阻塞线程中调用，直接等待接收方
+    fn push_chunk(&mut self, stream_id: u64, chunk: StreamChunk) -> bool {
+        let Some(sender) = self.streams.get(&stream_id) else {
+            return false;
+        };
+        let chunk = match chunk {
+            StreamChunk::Start => TranslateStreamChunk::Start,
+            StreamChunk::Delta(json) => match serde_json::from_str(&json) {
+                Ok(result) => TranslateStreamChunk::Delta(result),
+                Err(_) => return true,
+            },
+            StreamChunk::End => TranslateStreamChunk::End,
+        };
+
+        sender.blocking_send(chunk).is_ok()
+    }
+}
+
+/// 已编译的 WebAssembly 组件插件，接口见 `wit/translator.wit`。
+/// 每个翻译器在独立的实例中运行，宿主只提供不含文件、网络与环境变量的 WASI
+pub struct WasmPlugin {
+    path: String,
+    component: Component,
+    linker: Linker<State>,
+}
+
+impl WasmPlugin {
+    pub fn open(path: &str) -> Result<Self> {
+        let component = Component::from_file(engine(), path)?;
+
+        let mut linker = Linker::new(engine());
+        wasmtime_wasi::p2::add_to_linker_sync(&mut linker)?;
+        Plugin::add_to_linker::<_, HasSelf<State>>(&mut linker, |state| state)?;
+
+        Ok(WasmPlugin {
+            path: path.to_string(),
+            component,
+            linker,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    fn instantiate(&self) -> Result<(Store<State>, Plugin)> {
+        let state = State {
+            wasi: WasiCtx::builder().build(),
+            table: ResourceTable::new(),
+            streams: HashMap::new(),
+        };
+        let mut store = Store::new(engine(), state);
+        let plugin = Plugin::instantiate(&mut store, &self.component, &self.linker)?;
+
+        Ok((store, plugin))
+    }
+
+    pub fn name(&self) -> Result<String> {
+        let (mut store, plugin) = self.instantiate()?;
+        Ok(plugin.xtranslator_plugin_translator().call_get_plugin_name(&mut store)?)
+    }
+
+    pub fn metadata(&self) -> Result<PluginMetadata> {
+        let (mut store, plugin) = self.instantiate()?;
+        let json = plugin.xtranslator_plugin_translator().call_get_plugin_metadata(&mut store)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn config_schema(&self) -> Result<Option<Value>> {
+        let (mut store, plugin) = self.instantiate()?;
+        let json = plugin.xtranslator_plugin_translator().call_get_config_schema(&mut store)?;
+        Ok(json.map(|json| serde_json::from_str(&json)).transpose()?)
+    }
+
+    pub fn validate_config(&self, config: &Value) -> Result<Vec<ConfigIssue>> {
+        let (mut store, plugin) = self.instantiate()?;
+        let json = plugin
+            .xtranslator_plugin_translator()
+            .call_validate_config(&mut store, &serde_json::to_string(config)?)?
+            .map_err(plugin_error)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// 翻译器所在的实例
+struct Instance {
+    store: Store<State>,
+    plugin: Plugin,
+    translator: ResourceAny,
+}
+
+impl Instance {
+    /// 调用翻译器资源的方法，`f` 的参数为导出的接口、store 与资源句柄
+    fn call<R>(
+        &mut self,
+        f: impl FnOnce(GuestTranslator<'_>, &mut Store<State>, ResourceAny) -> wasmtime::Result<Result<R, PluginError>>,
+    ) -> Result<R> {
+        let guest = self.plugin.xtranslator_plugin_translator().translator();
+        f(guest, &mut self.store, self.translator)?.map_err(plugin_error)
+    }
+}
+
+/// 创建翻译器时读取的语言与能力。翻译期间实例被占用，查询不应等待翻译结束
+struct Info {
+    input_languages: Vec<String>,
+    output_languages: Vec<String>,
+    pairs: Vec<(String, String)>,
+    capabilities: Capabilities,
+}
+
+/// `*` 表示任意语言
+fn supports(languages: &[String], lang: &str) -> bool {
+    languages.iter().any(|l| l == "*" || l == lang)
+}
+
+/// 加载 WebAssembly 组件插件的翻译器，与 `ProxyTranslator` 对应，不需要为每个平台构建动态库。
+/// 组件在沙箱中运行，插件崩溃只会使当前调用失败。同一翻译器的调用依次执行，
+/// 翻译在阻塞线程中进行，不占用运行时的工作线程
+pub struct WasmTranslator {
+    plugin: Arc<WasmPlugin>,
+    instance: Arc<Mutex<Instance>>,
+    info: Info,
+    next_stream: AtomicU64,
+}
+
+impl WasmTranslator {
+    /// 用已编译的组件创建翻译器，配置以 JSON 格式传给插件
+    pub fn create(plugin: Arc<WasmPlugin>, config: &Value) -> Result<Self> {
+        let (mut store, instance) = plugin.instantiate()?;
+        let translator = instance
+            .xtranslator_plugin_translator()
+            .translator()
+            .call_create(&mut store, &serde_json::to_string(config)?)?
+            .map_err(plugin_error)?;
+        let mut instance = Instance {
+            store,
+            plugin: instance,
+            translator,
+        };
+
+        let info = Info {
+            input_languages: instance.call(|guest, store, translator| guest.call_get_supported_input_languages(store, translator))?,
+            output_languages: instance.call(|guest, store, translator| guest.call_get_supported_output_languages(store, translator))?,
+            pairs: instance.call(|guest, store, translator| guest.call_get_supported_pairs(store, translator))?,
+            capabilities: serde_json::from_str(&instance.call(|guest, store, translator| guest.call_capabilities(store, translator).map(Ok))?)?,
+        };
+
+        Ok(WasmTranslator {
+            plugin,
+            instance: Arc::new(Mutex::new(instance)),
+            info,
+            next_stream: AtomicU64::new(1),
+        })
+    }
+
+    pub async fn load(path: String, config: Value) -> Result<Self> {
+        let mut cfg = config.clone();
+        cfg["_wasm_path"] = Value::String(path);
+
+        Self::new(cfg).await
+    }
+
+    pub fn plugin(&self) -> &Arc<WasmPlugin> {
+        &self.plugin
+    }
+}
+
+#[async_trait]
+impl Translator for WasmTranslator {
+    type This = Self;
+
+    /// 组件路径读取自 `config["_wasm_path"]`，编译组件较慢，在阻塞线程中进行
+    async fn new(config: Value) -> Result<Self> {
+        let path = config["_wasm_path"]
+            .as_str()
+            .ok_or(anyhow!(XTranslateError::InvalidConfig("missing argument: _wasm_path".to_string())))?
+            .to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let plugin = Arc::new(WasmPlugin::open(&path)?);
+            WasmTranslator::create(plugin, &config)
+        })
+        .await?
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        Ok(self.info.input_languages.clone())
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        Ok(self.info.output_languages.clone())
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        Ok(supports(&self.info.input_languages, &lang))
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        Ok(supports(&self.info.output_languages, &lang))
+    }
+
+    /// 插件没有列出语言对时（如支持任意语言）按源语言与目标语言分别判断
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool> {
+        if self.info.pairs.is_empty() {
+            return Ok(self.is_supported_input_language(source)? && self.is_supported_output_language(target)?);
+        }
+
+        Ok(self.info.pairs.iter().any(|(s, t)| *s == source && *t == target))
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>> {
+        Ok(self.info.pairs.clone())
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.info.capabilities.clone()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let input = serde_json::to_string(&task)?;
+        let instance = self.instance.clone();
+
+        let json = tokio::task::spawn_blocking(move || {
+            instance
+                .lock()
+                .unwrap()
+                .call(|guest, store, translator| guest.call_translate(store, translator, &input))
+        })
+        .await??;
+
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// 接收方被丢弃后 `push-chunk` 返回 false，插件应尽快结束
+    async fn translate_stream(&self, task: TranslateTask, sender: Sender<TranslateStreamChunk>) -> Result<()> {
+        let input = serde_json::to_string(&task)?;
+        let instance = self.instance.clone();
+        let id = self.next_stream.fetch_add(1, Ordering::SeqCst);
+
+        tokio::task::spawn_blocking(move || {
+            let mut instance = instance.lock().unwrap();
+            instance.store.data_mut().streams.insert(id, sender);
+            let result = instance.call(|guest, store, translator| guest.call_translate_stream(store, translator, id, &input));
+            instance.store.data_mut().streams.remove(&id);
+
+            result
+        })
+        .await?
+    }
+}
+
+/// 测试用的组件插件。`cargo test` 不会构建其他 target，第一次使用时构建到单独的目录，
+/// 需要安装 wasm32-wasip2 target
+#[cfg(test)]
+fn built_component() -> String {
+    static PATH: OnceLock<String> = OnceLock::new();
+    PATH.get_or_init(|| {
+        // target/debug/deps/<测试程序>
+        let exe = std::env::current_exe().unwrap();
+        let target_dir = exe.ancestors().nth(3).unwrap().join("wasm-test");
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let status = std::process::Command::new(cargo)
+            .args(["build", "--target", "wasm32-wasip2", "--manifest-path"])
+            .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/../plugin-wasm-dryrun/Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "failed to build the wasm component");
+
+        target_dir
+            .join("wasm32-wasip2/debug/plugin_wasm_dryrun.wasm")
+            .to_string_lossy()
+            .into_owned()
+    })
+    .clone()
+}
+
+#[tokio::test]
+async fn test_wasm_translator() -> Result<()> {
+    use serde_json::json;
+    use std::time::Duration;
+
+    let plugin = Arc::new(WasmPlugin::open(&built_component())?);
+    assert_eq!(plugin.name()?, "wasm_dryrun");
+    assert_eq!(plugin.metadata()?.name, "wasm_dryrun");
+    assert!(plugin.config_schema()?.is_some());
+    assert_eq!(plugin.validate_config(&json!({ "prefix": 1 }))?[0].field, "prefix");
+
+    let rate_limited = json!({ "kind": "rate_limited", "retry_after_ms": 1500 });
+    let translator = WasmTranslator::create(plugin.clone(), &json!({ "errors": [rate_limited] }))?;
+    assert!(translator.capabilities().supports_streaming);
+    assert!(translator.is_supported_pair("en".to_string(), "ja".to_string())?);
+    assert!(!translator.is_supported_pair("en".to_string(), "en".to_string())?);
+
+    let task: TranslateTask = serde_json::from_value(json!({
+        "id": "1", "content": "one two three", "target_language": "zh-CN", "terms": [], "references": [],
+    }))?;
+
+    // 插件返回的错误类别在宿主中还原
+    let err = translator.translate(task.clone()).await.unwrap_err();
+    assert_eq!(
+        XTranslateError::find(&err),
+        Some(&XTranslateError::RateLimited {
+            retry_after: Some(Duration::from_millis(1500))
+        })
+    );
+
+    let result = translator.translate(task.clone()).await?;
+    assert_eq!(result.content.as_deref(), Some("[zh-CN] one two three"));
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(64);
+    translator.translate_stream(task.clone(), tx).await?;
+    let mut chunks = vec![];
+    while let Some(chunk) = rx.recv().await {
+        chunks.push(chunk);
+    }
+    assert!(matches!(chunks.first(), Some(TranslateStreamChunk::Start)));
+    assert!(matches!(chunks.last(), Some(TranslateStreamChunk::End)));
+    let content: String = chunks
+        .iter()
+        .filter_map(|chunk| match chunk {
+            TranslateStreamChunk::Delta(delta) => delta.content.clone(),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(content, "[zh-CN] one two three");
+
+    // 接收方被丢弃后插件停止推送，不会一直等待
+    let (tx, rx) = tokio::sync::mpsc::channel(1);
+    drop(rx);
+    translator.translate_stream(task, tx).await?;
+
+    let err = WasmTranslator::load(plugin.path().to_string(), json!({ "prefix": 1 }))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(
+        XTranslateError::find(&err),
+        Some(XTranslateError::InvalidConfig(_))
+    ));
+
+    Ok(())
+}
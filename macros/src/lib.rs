@@ -1,112 +1,469 @@
-use proc_macro::TokenStream;
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, TokenStream, TokenTree};
 use quote::quote;
-use syn::{parse_macro_input, LitStr, Token, Type};
+use syn::{bracketed, parenthesized, parse_macro_input, LitStr, Token, Type};
 use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 
 struct BuildFfiInput {
     pub name: String,
-    pub translator: Type,
+    /// 插件中的翻译器名称与类型，第一个为默认翻译器
+    pub translators: Vec<(String, Type)>,
+    /// 导出函数名的前缀，多个插件静态链接到同一个二进制时避免重名
+    pub prefix: Option<String>,
 }
 
+struct TranslatorEntry {
+    name: LitStr,
+    translator: Type,
+}
+
+impl Parse for TranslatorEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        parenthesized!(content in input);
+
+        let name = content.parse::<LitStr>()?;
+        content.parse::<Token![,]>()?;
+        let translator = content.parse::<Type>()?;
+
+        Ok(TranslatorEntry { name, translator })
+    }
+}
+
+/// `build_ffi!("name", Type)` 或 `build_ffi!("name", [("a", TypeA), ("b", TypeB)])`，
+/// 之后可以加上 `prefix = "xtr_name_"`
 impl Parse for BuildFfiInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let name = input.parse::<LitStr>()?;
 
         input.parse::<Token![,]>()?;
 
-        let typ = input.parse::<Type>()?;
+        let translators = if input.peek(syn::token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            let entries = Punctuated::<TranslatorEntry, Token![,]>::parse_terminated(&content)?;
+            if entries.is_empty() {
+                return Err(syn::Error::new(name.span(), "at least one translator is required"));
+            }
+            entries.into_iter().map(|e| (e.name.value(), e.translator)).collect()
+        } else {
+            vec![(name.value(), input.parse::<Type>()?)]
+        };
+
+        let mut prefix = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key = input.parse::<syn::Ident>()?;
+            if key != "prefix" {
+                return Err(syn::Error::new(key.span(), "expected `prefix`"));
+            }
+            input.parse::<Token![=]>()?;
+            let value = input.parse::<LitStr>()?;
+            if !value.value().chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(syn::Error::new(value.span(), "prefix must be a valid C identifier"));
+            }
+            prefix = Some(value.value());
+            input.parse::<Option<Token![,]>>()?;
+        }
 
         Ok(BuildFfiInput {
             name: name.value(),
-            translator: typ,
+            translators,
+            prefix,
         })
     }
 }
 
+/// 把顶层的 `#[no_mangle] ... fn name` 改为 `#[export_name = "<prefix>name"]`，
+/// Rust 中的函数名不变
+fn prefix_exports(tokens: TokenStream, prefix: &str) -> TokenStream {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    let mut output = Vec::with_capacity(tokens.len());
+
+    for (i, token) in tokens.iter().enumerate() {
+        let is_no_mangle = i > 0
+            && matches!(&tokens[i - 1], TokenTree::Punct(p) if p.as_char() == '#')
+            && matches!(token, TokenTree::Group(group)
+                if group.delimiter() == Delimiter::Bracket && group.stream().to_string() == "no_mangle");
+
+        // 属性之后第一个 `fn` 的名称
+        let export_name = if is_no_mangle {
+            tokens[i + 1..].windows(2).find_map(|w| match w {
+                [TokenTree::Ident(f), TokenTree::Ident(name)] if f.to_string() == "fn" => Some(name.to_string()),
+                _ => None,
+            })
+        } else {
+            None
+        };
+
+        match (token, export_name) {
+            (TokenTree::Group(group), Some(name)) => {
+                let attr: TokenStream = [
+                    TokenTree::Ident(Ident::new("export_name", group.span())),
+                    TokenTree::Punct(Punct::new('=', Spacing::Alone)),
+                    TokenTree::Literal(Literal::string(&format!("{}{}", prefix, name))),
+                ]
+                .into_iter()
+                .collect();
+                output.push(TokenTree::Group(Group::new(Delimiter::Bracket, attr)));
+            }
+            _ => output.push(token.clone()),
+        }
+    }
+
+    output.into_iter().collect()
+}
+
 #[proc_macro]
 pub fn build_ffi(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as BuildFfiInput);
 
     let name = input.name;
-    let translator = input.translator;
+    let names: Vec<&String> = input.translators.iter().map(|(name, _)| name).collect();
+    let types: Vec<&Type> = input.translators.iter().map(|(_, translator)| translator).collect();
+    let default_name = names[0];
 
-    TokenStream::from(quote!{
-use lib::ffi::{FfiResult, FfiResultExt, StreamCallback, TranslateResultFFI, TranslatorHandle, convert_string_vec_to_c_array};
-use lib::{TranslateStreamChunk, TranslateTask, Translator};
+    let tokens = TokenStream::from(quote!{
+use lib::ffi::{CompletionCallback, FfiResult, FfiResultExt, ResultArena, StreamCallback, StreamHandle, TranslateResultFFI, TranslatorHandle, convert_string_vec_to_c_array};
+use lib::{BoxedTranslator, DynTranslator, TranslateStreamChunk, TranslateTask};
+use std::borrow::Cow;
 use std::ffi::{c_char, c_void, CStr, CString};
-use tokio::runtime::Handle;
 use tokio::sync::mpsc::channel;
 
-#[allow(dead_code)]
-impl #translator {
-    pub fn into_ffi(self) -> *mut TranslatorHandle {
-        Box::into_raw(Box::new(self)) as *mut TranslatorHandle
+// 宿主可能在多个线程中同时使用同一个句柄
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    #( assert_send_sync::<#types>(); )*
+};
+
+/// 插件中的翻译器名称，第一个为 `create_translator` 创建的默认翻译器
+const FFI_TRANSLATORS: &[&str] = &[#(#names),*];
+
+async fn new_ffi_translator(name: &str, config: serde_json::Value) -> anyhow::Result<BoxedTranslator> {
+    match name {
+        #(
+            #names => {
+                let translator: BoxedTranslator = Box::new(<#types as lib::Translator>::new(config).await?);
+                Ok(translator)
+            }
+        )*
+        _ => Err(anyhow::anyhow!("translator not found: {}", name)),
     }
+}
 
-    pub fn from_ptr(ptr: *mut TranslatorHandle) -> Option<Box<#translator>> {
-        if ptr.is_null() {
-            return None;
-        }
+/// 句柄指向装箱的 `BoxedTranslator`，不同类型的翻译器共用同一组导出函数
+fn ffi_translator<'a>(ptr: *mut TranslatorHandle) -> Option<&'a dyn DynTranslator> {
+    if ptr.is_null() {
+        return None;
+    }
 
-        Some(unsafe { Box::from_raw(ptr as *mut #translator) })
+    Some(unsafe { &**(ptr as *const BoxedTranslator) })
+}
+
+fn ffi_config_schema(name: &str) -> anyhow::Result<Option<serde_json::Value>> {
+    match name {
+        #( #names => Ok(<#types as lib::Translator>::config_schema()), )*
+        _ => Err(anyhow::anyhow!("translator not found: {}", name)),
+    }
+}
+
+fn ffi_static_info(name: &str) -> anyhow::Result<lib::StaticInfo> {
+    match name {
+        #( #names => Ok(<#types as lib::Translator>::static_info()), )*
+        _ => Err(anyhow::anyhow!("translator not found: {}", name)),
+    }
+}
+
+fn ffi_validate_config(name: &str, config: &serde_json::Value) -> anyhow::Result<Vec<lib::validate::ConfigIssue>> {
+    match name {
+        #( #names => <#types as lib::Translator>::validate_config(config), )*
+        _ => Err(anyhow::anyhow!("translator not found: {}", name)),
+    }
+}
+
+fn ffi_str<'a>(s: *const c_char) -> anyhow::Result<&'a str> {
+    if s.is_null() {
+        return Err(anyhow::anyhow!("Null pointer received"));
     }
+
+    unsafe { CStr::from_ptr(s) }
+        .to_str()
+        .map_err(|e| anyhow::anyhow!("Invalid UTF-8: {}", e))
+}
+
+/// 返回插件中的翻译器名称列表，由 `free_supported_languages` 释放
+#[no_mangle]
+pub extern "C" fn get_plugin_translators(
+    array: *mut *mut *const c_char,
+    len: *mut usize,
+) -> *mut FfiResult<i8> {
+    lib::ffi::catch_ffi(|| {
+        convert_string_vec_to_c_array(FFI_TRANSLATORS.iter().map(|s| s.to_string()).collect(), array, len)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn get_abi_version() -> u32 {
+    lib::ffi::catch_panic(0, || {
+        lib::ffi::ABI_VERSION
+    })
+}
+
+/// 插件编译时 `#[repr(C)]` 结构体的布局，由 `free_string` 释放
+#[no_mangle]
+pub extern "C" fn get_abi_layout() -> *mut c_char {
+    lib::ffi::catch_panic(std::ptr::null_mut(), || {
+        CString::new(serde_json::to_string(&lib::ffi::abi_layout()).unwrap()).unwrap().into_raw()
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn get_plugin_name() -> *mut c_char {
-    CString::new(#name).unwrap().into_raw()
+    lib::ffi::catch_panic(std::ptr::null_mut(), || {
+        CString::new(#name).unwrap().into_raw()
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn create_translator(
-    json_str: *const c_char
-) -> *mut FfiResult<#translator> {
-    let input = unsafe {
-        if json_str.is_null() {
-            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+pub extern "C" fn get_plugin_metadata() -> *mut c_char {
+    lib::ffi::catch_panic(std::ptr::null_mut(), || {
+        let mut features = vec![];
+        if cfg!(feature = "tracing") {
+            features.push("tracing".to_string());
         }
-        match CStr::from_ptr(json_str).to_str() {
-            Ok(s) => s,
-            Err(e) => {
-                return Err(anyhow::anyhow!("Invalid UTF-8: {}", e)).to_ptr();
-            }
+
+        let metadata = lib::ffi::PluginMetadata {
+            name: #name.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            lib_version: lib::ffi::LIB_VERSION.to_string(),
+            abi_version: lib::ffi::ABI_VERSION,
+            min_host_version: lib::ffi::MIN_HOST_VERSION.to_string(),
+            features,
+            authors: env!("CARGO_PKG_AUTHORS")
+                .split(':')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect(),
+            homepage: lib::ffi::non_empty(env!("CARGO_PKG_HOMEPAGE")),
+            description: lib::ffi::non_empty(env!("CARGO_PKG_DESCRIPTION")),
+        };
+
+        CString::new(serde_json::to_string(&metadata).unwrap()).unwrap().into_raw()
+    })
+}
+
+/// 返回 JSON 格式的配置 schema，插件没有提供时返回空指针
+#[no_mangle]
+pub extern "C" fn get_config_schema() -> *mut c_char {
+    lib::ffi::catch_panic(std::ptr::null_mut(), || {
+        match ffi_config_schema(#default_name) {
+            Ok(Some(schema)) => CString::new(schema.to_string()).unwrap().into_raw(),
+            _ => std::ptr::null_mut(),
         }
-    };
+    })
+}
+
+/// 返回指定翻译器的配置 schema，没有提供或没有该翻译器时返回空指针
+#[no_mangle]
+pub extern "C" fn get_named_config_schema(name: *const c_char) -> *mut c_char {
+    lib::ffi::catch_panic(std::ptr::null_mut(), || {
+        let Ok(name) = ffi_str(name) else {
+            return std::ptr::null_mut();
+        };
 
-    let value: serde_json::Value = match serde_json::from_str(input) {
-        Ok(v) => v,
-        Err(e) => {
-            return Err(anyhow::anyhow!("JSON parse error: {}", e)).to_ptr();
+        match ffi_config_schema(name) {
+            Ok(Some(schema)) => CString::new(schema.to_string()).unwrap().into_raw(),
+            _ => std::ptr::null_mut(),
         }
-    };
+    })
+}
 
-    if let Ok(handle) = Handle::try_current() {
-        handle.block_on(async {
-            match #translator::new(value).await {
-                Ok(translator) => {
-                    return Ok(translator).to_ptr();
-                }
+/// 返回指定翻译器不依赖配置的语言与能力，没有该翻译器时返回空指针，由 `free_string` 释放
+#[no_mangle]
+pub extern "C" fn get_named_static_info(name: *const c_char) -> *mut c_char {
+    lib::ffi::catch_panic(std::ptr::null_mut(), || {
+        let Ok(name) = ffi_str(name) else {
+            return std::ptr::null_mut();
+        };
+
+        match ffi_static_info(name) {
+            Ok(info) => CString::new(serde_json::to_string(&info).unwrap()).unwrap().into_raw(),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// 检查配置，返回 JSON 格式的 `Vec<ConfigIssue>`
+#[no_mangle]
+pub extern "C" fn validate_config(json_str: *const c_char) -> *mut FfiResult<c_char> {
+    lib::ffi::catch_ffi(|| {
+        let input = unsafe {
+            if json_str.is_null() {
+                return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+            }
+            match CStr::from_ptr(json_str).to_str() {
+                Ok(s) => s,
                 Err(e) => {
-                    return Err(anyhow::anyhow!("Creation error: {}", e)).to_ptr();
+                    return Err(anyhow::anyhow!("Invalid UTF-8: {}", e)).to_ptr();
                 }
             }
-        })
-    } else {
-        let handle = tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .unwrap();
-        handle.block_on(async {
-            match #translator::new(value).await {
+        };
+
+        let value: serde_json::Value = match serde_json::from_str(input) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!("JSON parse error: {}", e)).to_ptr();
+            }
+        };
+
+        let issues = match ffi_validate_config(#default_name, &value) {
+            Ok(issues) => issues,
+            Err(e) => {
+                return Err(e).to_ptr();
+            }
+        };
+
+        lib::ffi::string_result(serde_json::to_string(&issues).unwrap())
+    })
+}
+
+/// 按指定翻译器检查配置
+#[no_mangle]
+pub extern "C" fn validate_named_config(name: *const c_char, json_str: *const c_char) -> *mut FfiResult<c_char> {
+    lib::ffi::catch_ffi(|| {
+        let (name, input) = match (ffi_str(name), ffi_str(json_str)) {
+            (Ok(name), Ok(input)) => (name, input),
+            (Err(e), _) | (_, Err(e)) => {
+                return Err(e).to_ptr();
+            }
+        };
+
+        let value: serde_json::Value = match serde_json::from_str(input) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!("JSON parse error: {}", e)).to_ptr();
+            }
+        };
+
+        match ffi_validate_config(name, &value) {
+            Ok(issues) => lib::ffi::string_result(serde_json::to_string(&issues).unwrap()),
+            Err(e) => Err(e).to_ptr(),
+        }
+    })
+}
+
+/// 创建默认翻译器
+#[no_mangle]
+pub extern "C" fn create_translator(
+    json_str: *const c_char
+) -> *mut FfiResult<BoxedTranslator> {
+    create_ffi_translator(#default_name, ffi_str(json_str).map(Cow::from))
+}
+
+/// 与 `create_translator` 相同，配置为 `(ptr, len)` 形式的 UTF-8
+#[no_mangle]
+pub extern "C" fn create_translator_utf8(
+    json: *const u8,
+    len: usize
+) -> *mut FfiResult<BoxedTranslator> {
+    create_ffi_translator(#default_name, unsafe { lib::ffi::utf8_arg(json, len) })
+}
+
+/// 与 `create_translator` 相同，配置为 UTF-16，`len` 为 u16 个数
+#[no_mangle]
+pub extern "C" fn create_translator_utf16(
+    json: *const u16,
+    len: usize
+) -> *mut FfiResult<BoxedTranslator> {
+    create_ffi_translator(#default_name, unsafe { lib::ffi::utf16_arg(json, len) })
+}
+
+/// 按名称创建插件中的翻译器，名称见 `get_plugin_translators`
+#[no_mangle]
+pub extern "C" fn create_named_translator(
+    name: *const c_char,
+    json_str: *const c_char
+) -> *mut FfiResult<BoxedTranslator> {
+    match ffi_str(name) {
+        Ok(name) => create_ffi_translator(name, ffi_str(json_str).map(Cow::from)),
+        Err(e) => Err(e).to_ptr(),
+    }
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(input), fields(plugin = #name)))]
+fn create_ffi_translator(
+    name: &str,
+    input: anyhow::Result<Cow<str>>
+) -> *mut FfiResult<BoxedTranslator> {
+    lib::ffi::catch_ffi(|| {
+        let input = match input {
+            Ok(s) => s,
+            Err(e) => return Err(e).to_ptr(),
+        };
+
+        let mut value: serde_json::Value = match serde_json::from_str(&input) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!("JSON parse error: {}", e)).to_ptr();
+            }
+        };
+
+        if let Err(e) = lib::secrets::resolve_secrets(&mut value) {
+            return Err(anyhow::anyhow!("Secret error: {}", e)).to_ptr();
+        }
+
+        lib::ffi::block_on(async {
+            match new_ffi_translator(name, value).await {
                 Ok(translator) => {
                     return Ok(translator).to_ptr();
                 }
                 Err(e) => {
-                    return Err(anyhow::anyhow!("Creation error: {}", e)).to_ptr();
+                    return Err(e.context("Creation error")).to_ptr();
                 }
             }
         })
-    }
+    })
+}
+
+/// 释放 `create_translator` 创建的翻译器的一个引用，最后一个引用释放后不能再使用该句柄。
+/// 销毁前与 `shutdown_translator` 相同地等待进行中的调用结束，不能在回调中释放最后一个引用
+#[no_mangle]
+pub extern "C" fn destroy_translator(translator_ptr: *mut TranslatorHandle) {
+    lib::ffi::catch_panic((), || {
+        if !translator_ptr.is_null() && lib::ffi::release_handle(translator_ptr) {
+            lib::ffi::shutdown_calls(translator_ptr, lib::ffi::DESTROY_TIMEOUT);
+            drop(unsafe { Box::from_raw(translator_ptr as *mut BoxedTranslator) });
+            lib::ffi::forget_calls(translator_ptr);
+        }
+    })
+}
+
+/// 增加一个引用并返回同一个句柄，每个引用都要调用一次 `destroy_translator`。
+/// 各引用共享同一个翻译器，`shutdown_translator` 对所有引用生效
+#[no_mangle]
+pub extern "C" fn clone_translator(translator_ptr: *mut TranslatorHandle) -> *mut TranslatorHandle {
+    lib::ffi::catch_panic(std::ptr::null_mut(), || {
+        if !translator_ptr.is_null() {
+            lib::ffi::retain_handle(translator_ptr);
+        }
+        translator_ptr
+    })
+}
+
+/// 拒绝新的调用并等待进行中的调用结束，超过 `timeout_ms` 后取消它们。
+/// 返回后回调不会再被调用，可以安全地调用 `destroy_translator`
+#[no_mangle]
+pub extern "C" fn shutdown_translator(
+    translator_ptr: *mut TranslatorHandle,
+    timeout_ms: u64
+) -> *mut FfiResult<i8> {
+    lib::ffi::catch_ffi(|| {
+        if translator_ptr.is_null() {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        }
+
+        let cancelled = lib::ffi::shutdown_calls(translator_ptr, std::time::Duration::from_millis(timeout_ms));
+        Ok(cancelled as i8).to_ptr()
+    })
 }
 
 #[no_mangle]
@@ -115,17 +472,17 @@ pub extern "C" fn get_supported_input_languages(
     array: *mut *mut *const c_char,
     len: *mut usize,
 ) -> *mut FfiResult<i8> {
-    if translator_ptr.is_null() {
-        return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
-    }
-
-    let translator = unsafe { &*(translator_ptr as *mut #translator) };
+    lib::ffi::catch_ffi(|| {
+        let Some(translator) = ffi_translator(translator_ptr) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
 
-    let list = translator.get_supported_input_languages();
-    if let Err(e) = list {
-        return Err(anyhow::anyhow!("{}", e)).to_ptr();
-    }
-    convert_string_vec_to_c_array(list.unwrap(), array, len)
+        let list = translator.get_supported_input_languages();
+        if let Err(e) = list {
+            return Err(e).to_ptr();
+        }
+        convert_string_vec_to_c_array(list.unwrap(), array, len)
+    })
 }
 
 #[no_mangle]
@@ -134,17 +491,17 @@ pub extern "C" fn get_supported_output_languages(
     array: *mut *mut *const c_char,
     len: *mut usize,
 ) -> *mut FfiResult<i8> {
-    if translator_ptr.is_null() {
-        return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
-    }
-
-    let translator = unsafe { &*(translator_ptr as *mut #translator) };
+    lib::ffi::catch_ffi(|| {
+        let Some(translator) = ffi_translator(translator_ptr) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
 
-    let list = translator.get_supported_output_languages();
-    if let Err(e) = list {
-        return Err(anyhow::anyhow!("{}", e)).to_ptr();
-    }
-    convert_string_vec_to_c_array(list.unwrap(), array, len)
+        let list = translator.get_supported_output_languages();
+        if let Err(e) = list {
+            return Err(e).to_ptr();
+        }
+        convert_string_vec_to_c_array(list.unwrap(), array, len)
+    })
 }
 
 #[no_mangle]
@@ -152,73 +509,225 @@ pub extern "C" fn is_supported_input_language(
     translator_ptr: *mut TranslatorHandle,
     lang: *const c_char
 ) -> *mut FfiResult<i8> {
-    let lang = unsafe {
-        if lang.is_null() {
+    lib::ffi::catch_ffi(|| {
+        let lang = unsafe {
+            if lang.is_null() {
+                return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+            }
+            match CStr::from_ptr(lang).to_str() {
+                Ok(s) => s,
+                Err(e) => {
+                    return Err(anyhow::anyhow!("Invalid UTF-8: {}", e)).to_ptr();
+                }
+            }
+        };
+
+        let Some(translator) = ffi_translator(translator_ptr) else {
             return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
-        }
-        match CStr::from_ptr(lang).to_str() {
-            Ok(s) => s,
+        };
+
+        let res = translator.is_supported_input_language(lang.to_string());
+        match res {
+            Ok(true) => {
+                Ok(0).to_ptr()
+            }
+            Ok(false) => {
+                Ok(1).to_ptr()
+            }
             Err(e) => {
-                return Err(anyhow::anyhow!("Invalid UTF-8: {}", e)).to_ptr();
+                Err(e).to_ptr()
             }
         }
-    };
+    })
+}
 
-    if translator_ptr.is_null() {
-        return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
-    }
+#[no_mangle]
+pub extern "C" fn is_supported_output_language(
+    translator_ptr: *mut TranslatorHandle,
+    lang: *const c_char
+) -> *mut FfiResult<i8> {
+    lib::ffi::catch_ffi(|| {
+        let lang = unsafe {
+            if lang.is_null() {
+                return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+            }
+            match CStr::from_ptr(lang).to_str() {
+                Ok(s) => s,
+                Err(e) => {
+                    return Err(anyhow::anyhow!("Invalid UTF-8: {}", e)).to_ptr();
+                }
+            }
+        };
 
-    let translator = unsafe { &*(translator_ptr as *mut #translator) };
+        let Some(translator) = ffi_translator(translator_ptr) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
 
-    let res = translator.is_supported_input_language(lang.to_string());
-    match res {
-        Ok(true) => {
-            Ok(0).to_ptr()
-        }
-        Ok(false) => {
-            Ok(1).to_ptr()
-        }
-        Err(e) => {
-            Err(anyhow::anyhow!("{}", e)).to_ptr()
+        let res = translator.is_supported_output_language(lang.to_string());
+        match res {
+            Ok(true) => {
+                Ok(0).to_ptr()
+            }
+            Ok(false) => {
+                Ok(1).to_ptr()
+            }
+            Err(e) => {
+                Err(e).to_ptr()
+            }
         }
-    }
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn is_supported_output_language(
+pub extern "C" fn is_supported_pair(
     translator_ptr: *mut TranslatorHandle,
-    lang: *const c_char
+    source: *const c_char,
+    target: *const c_char
 ) -> *mut FfiResult<i8> {
-    let lang = unsafe {
-        if lang.is_null() {
+    lib::ffi::catch_ffi(|| {
+        let (source, target) = unsafe {
+            if source.is_null() || target.is_null() {
+                return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+            }
+            match (CStr::from_ptr(source).to_str(), CStr::from_ptr(target).to_str()) {
+                (Ok(source), Ok(target)) => (source, target),
+                (Err(e), _) | (_, Err(e)) => {
+                    return Err(anyhow::anyhow!("Invalid UTF-8: {}", e)).to_ptr();
+                }
+            }
+        };
+
+        let Some(translator) = ffi_translator(translator_ptr) else {
             return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
-        }
-        match CStr::from_ptr(lang).to_str() {
-            Ok(s) => s,
+        };
+
+        let res = translator.is_supported_pair(source.to_string(), target.to_string());
+        match res {
+            Ok(true) => {
+                Ok(0).to_ptr()
+            }
+            Ok(false) => {
+                Ok(1).to_ptr()
+            }
             Err(e) => {
-                return Err(anyhow::anyhow!("Invalid UTF-8: {}", e)).to_ptr();
+                Err(e).to_ptr()
             }
         }
-    };
+    })
+}
 
-    if translator_ptr.is_null() {
-        return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+/// 支持时为 0，不支持时为 1
+fn ffi_supported(res: anyhow::Result<bool>) -> *mut FfiResult<i8> {
+    match res {
+        Ok(true) => Ok(0).to_ptr(),
+        Ok(false) => Ok(1).to_ptr(),
+        Err(e) => Err(e).to_ptr(),
     }
+}
+
+/// 与 `is_supported_input_language` 相同，语言为 `(ptr, len)` 形式的 UTF-8
+#[no_mangle]
+pub extern "C" fn is_supported_input_language_utf8(
+    translator_ptr: *mut TranslatorHandle,
+    lang: *const u8,
+    len: usize
+) -> *mut FfiResult<i8> {
+    lib::ffi::catch_ffi(|| {
+        let lang = match unsafe { lib::ffi::utf8_arg(lang, len) } {
+            Ok(s) => s,
+            Err(e) => return Err(e).to_ptr(),
+        };
 
-    let translator = unsafe { &*(translator_ptr as *mut #translator) };
+        let Some(translator) = ffi_translator(translator_ptr) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
 
-    let res = translator.is_supported_output_language(lang.to_string());
-    match res {
-        Ok(true) => {
-            Ok(0).to_ptr()
-        }
-        Ok(false) => {
-            Ok(1).to_ptr()
-        }
-        Err(e) => {
-            Err(anyhow::anyhow!("{}", e)).to_ptr()
+        ffi_supported(translator.is_supported_input_language(lang.into_owned()))
+    })
+}
+
+/// 与 `is_supported_output_language` 相同，语言为 `(ptr, len)` 形式的 UTF-8
+#[no_mangle]
+pub extern "C" fn is_supported_output_language_utf8(
+    translator_ptr: *mut TranslatorHandle,
+    lang: *const u8,
+    len: usize
+) -> *mut FfiResult<i8> {
+    lib::ffi::catch_ffi(|| {
+        let lang = match unsafe { lib::ffi::utf8_arg(lang, len) } {
+            Ok(s) => s,
+            Err(e) => return Err(e).to_ptr(),
+        };
+
+        let Some(translator) = ffi_translator(translator_ptr) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
+
+        ffi_supported(translator.is_supported_output_language(lang.into_owned()))
+    })
+}
+
+/// 与 `is_supported_pair` 相同，语言为 `(ptr, len)` 形式的 UTF-8
+#[no_mangle]
+pub extern "C" fn is_supported_pair_utf8(
+    translator_ptr: *mut TranslatorHandle,
+    source: *const u8,
+    source_len: usize,
+    target: *const u8,
+    target_len: usize
+) -> *mut FfiResult<i8> {
+    lib::ffi::catch_ffi(|| {
+        let (source, target) = match unsafe { (lib::ffi::utf8_arg(source, source_len), lib::ffi::utf8_arg(target, target_len)) } {
+            (Ok(source), Ok(target)) => (source, target),
+            (Err(e), _) | (_, Err(e)) => return Err(e).to_ptr(),
+        };
+
+        let Some(translator) = ffi_translator(translator_ptr) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
+
+        ffi_supported(translator.is_supported_pair(source.into_owned(), target.into_owned()))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn get_supported_pairs(
+    translator_ptr: *mut TranslatorHandle,
+    array: *mut *mut *const c_char,
+    len: *mut usize,
+) -> *mut FfiResult<i8> {
+    lib::ffi::catch_ffi(|| {
+        let Some(translator) = ffi_translator(translator_ptr) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
+
+        let pairs = match translator.get_supported_pairs() {
+            Ok(pairs) => pairs,
+            Err(e) => {
+                return Err(e).to_ptr();
+            }
+        };
+        let list = pairs
+            .into_iter()
+            .flat_map(|(source, target)| [source, target])
+            .collect();
+        convert_string_vec_to_c_array(list, array, len)
+    })
+}
+
+/// 返回 JSON 格式的 `Capabilities`，宿主据此决定是否使用流式翻译、是否传入术语与参考译文
+#[no_mangle]
+pub extern "C" fn get_capabilities(translator_ptr: *mut TranslatorHandle) -> *mut FfiResult<c_char> {
+    lib::ffi::catch_ffi(|| {
+        let Some(translator) = ffi_translator(translator_ptr) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
+
+        match serde_json::to_string(&translator.capabilities()) {
+            Ok(json) => lib::ffi::string_result(json),
+            Err(e) => Err(anyhow::anyhow!("{}", e)).to_ptr(),
         }
-    }
+    })
 }
 
 #[no_mangle]
@@ -226,58 +735,215 @@ pub extern "C" fn call_translate(
     translator_ptr: *mut TranslatorHandle,
     json_str: *const c_char
 ) -> *mut FfiResult<TranslateResultFFI> {
-    let input = unsafe {
-        if json_str.is_null() {
-            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
-        }
-        match CStr::from_ptr(json_str).to_str() {
+    ffi_call_translate(translator_ptr, ffi_str(json_str).map(Cow::from), None)
+}
+
+/// 与 `call_translate` 相同，任务为 `(ptr, len)` 形式的 UTF-8
+#[no_mangle]
+pub extern "C" fn call_translate_utf8(
+    translator_ptr: *mut TranslatorHandle,
+    json: *const u8,
+    len: usize
+) -> *mut FfiResult<TranslateResultFFI> {
+    ffi_call_translate(translator_ptr, unsafe { lib::ffi::utf8_arg(json, len) }, None)
+}
+
+/// 与 `call_translate` 相同，任务为 UTF-16，`len` 为 u16 个数
+#[no_mangle]
+pub extern "C" fn call_translate_utf16(
+    translator_ptr: *mut TranslatorHandle,
+    json: *const u16,
+    len: usize
+) -> *mut FfiResult<TranslateResultFFI> {
+    ffi_call_translate(translator_ptr, unsafe { lib::ffi::utf16_arg(json, len) }, None)
+}
+
+/// 与 `call_translate` 相同，翻译期间通过 `progress` 报告进度，返回后不再调用 `progress`
+#[no_mangle]
+pub extern "C" fn call_translate_with_progress(
+    translator_ptr: *mut TranslatorHandle,
+    json_str: *const c_char,
+    progress: Option<lib::ffi::ProgressCallback>,
+    user_data: *mut c_void
+) -> *mut FfiResult<TranslateResultFFI> {
+    ffi_call_translate(translator_ptr, ffi_str(json_str).map(Cow::from), lib::progress::callback_sink(progress, user_data))
+}
+
+/// 一次翻译多个任务，`json_str` 为任务数组。结果为对应的 `BatchResult` 数组，由 `free_string` 释放
+#[no_mangle]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(plugin = #name)))]
+pub extern "C" fn call_translate_batch(
+    translator_ptr: *mut TranslatorHandle,
+    json_str: *const c_char
+) -> *mut FfiResult<c_char> {
+    lib::ffi::catch_ffi(|| {
+        let input = match ffi_str(json_str) {
             Ok(s) => s,
+            Err(e) => return Err(e).to_ptr(),
+        };
+
+        let tasks: Vec<TranslateTask> = match serde_json::from_str(input) {
+            Ok(v) => v,
             Err(e) => {
-                return Err(anyhow::anyhow!("Invalid UTF-8: {}", e)).to_ptr();
+                return Err(anyhow::anyhow!("JSON parse error: {}", e)).to_ptr();
             }
-        }
+        };
+
+        let Some(translator) = ffi_translator(translator_ptr) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
+
+        let guard = match lib::ffi::CallGuard::begin(translator_ptr) {
+            Ok(guard) => guard,
+            Err(e) => return Err(e).to_ptr(),
+        };
+
+        lib::ffi::block_on(async {
+            let results = tokio::select! {
+                results = lib::ffi::translate_batch(translator, tasks) => results,
+                _ = guard.cancelled() => return Err(anyhow::anyhow!("translator shut down")).to_ptr(),
+            };
+
+            match serde_json::to_string(&results) {
+                Ok(json) => lib::ffi::string_result(json),
+                Err(e) => Err(anyhow::anyhow!("{}", e)).to_ptr(),
+            }
+        })
+    })
+}
+
+fn ffi_call_translate(
+    translator_ptr: *mut TranslatorHandle,
+    input: anyhow::Result<Cow<str>>,
+    progress: Option<std::sync::Arc<dyn lib::progress::ProgressSink>>
+) -> *mut FfiResult<TranslateResultFFI> {
+    lib::ffi::catch_ffi(|| {
+        ffi_run_translate(translator_ptr, input, progress)
+            .map(|result| result.into_ffi_unbox())
+            .to_ptr()
+    })
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(plugin = #name)))]
+fn ffi_run_translate(
+    translator_ptr: *mut TranslatorHandle,
+    input: anyhow::Result<Cow<str>>,
+    progress: Option<std::sync::Arc<dyn lib::progress::ProgressSink>>
+) -> anyhow::Result<lib::TranslateResult> {
+    let task: TranslateTask = serde_json::from_str(&input?)
+        .map_err(|e| anyhow::anyhow!("JSON parse error: {}", e))?;
+
+    let Some(translator) = ffi_translator(translator_ptr) else {
+        return Err(anyhow::anyhow!("Null pointer received"));
     };
 
-    let task: TranslateTask = match serde_json::from_str(input) {
-        Ok(v) => v,
-        Err(e) => {
-            return Err(anyhow::anyhow!("JSON parse error: {}", e)).to_ptr();
+    let guard = lib::ffi::CallGuard::begin(translator_ptr)?;
+
+    lib::ffi::block_on(async {
+        tokio::select! {
+            result = lib::progress::track(progress, translator.translate(task)) => result,
+            _ = guard.cancelled() => Err(anyhow::anyhow!("translator shut down")),
         }
-    };
+    })
+}
 
-    if translator_ptr.is_null() {
-        return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
-    }
+/// 与 `call_translate_utf8` 相同，结果以 JSON 写入 `arena`，`out`、`out_len` 为其位置，
+/// 在下一次写入同一个 `arena` 或 `free_result_arena` 之前有效
+#[no_mangle]
+pub extern "C" fn call_translate_into(
+    translator_ptr: *mut TranslatorHandle,
+    json: *const u8,
+    len: usize,
+    arena: *mut ResultArena,
+    out: *mut *const u8,
+    out_len: *mut usize
+) -> *mut FfiResult<i8> {
+    lib::ffi::catch_ffi(|| {
+        let (Some(buffer), false, false) = (unsafe { lib::ffi::ResultBuffer::from_ptr(arena) }, out.is_null(), out_len.is_null()) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
 
-    let translator = unsafe { &*(translator_ptr as *mut #translator) };
+        let written = ffi_run_translate(translator_ptr, unsafe { lib::ffi::utf8_arg(json, len) }, None)
+            .and_then(|result| buffer.write_json(&result).map(|data| (data.as_ptr(), data.len())));
+        match written {
+            Ok((ptr, written_len)) => {
+                unsafe {
+                    *out = ptr;
+                    *out_len = written_len;
+                }
+                Ok(0).to_ptr()
+            }
+            Err(e) => Err(e).to_ptr(),
+        }
+    })
+}
 
-    if let Ok(handle) = Handle::try_current() {
-        handle.block_on(async {
-            let result = match translator.translate(task).await {
-                Ok(v) => v,
+/// 立即返回，结果通过 `completion` 送达，宿主不需要为每次翻译阻塞一个线程
+#[no_mangle]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(plugin = #name)))]
+pub extern "C" fn call_translate_async(
+    translator_ptr: *mut TranslatorHandle,
+    json_str: *const c_char,
+    completion: CompletionCallback,
+    user_data: *mut c_void
+) -> *mut FfiResult<i8> {
+    lib::ffi::catch_ffi(|| {
+        let input = unsafe {
+            if json_str.is_null() {
+                return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+            }
+            match CStr::from_ptr(json_str).to_str() {
+                Ok(s) => s,
                 Err(e) => {
-                    return Err(anyhow::anyhow!("JSON parse error: {}", e)).to_ptr();
+                    return Err(anyhow::anyhow!("Invalid UTF-8: {}", e)).to_ptr();
                 }
-            };
+            }
+        };
 
-            Ok(result.into_ffi_unbox()).to_ptr()
-        })
-    } else {
-        let handle = tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .unwrap();
-        handle.block_on(async {
-            let result = match translator.translate(task).await {
-                Ok(v) => v,
-                Err(e) => {
-                    return Err(anyhow::anyhow!("{}", e)).to_ptr();
+        let task: TranslateTask = match serde_json::from_str(input) {
+            Ok(v) => v,
+            Err(e) => {
+                return Err(anyhow::anyhow!("JSON parse error: {}", e)).to_ptr();
+            }
+        };
+
+        if translator_ptr.is_null() {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        }
+
+        let guard = match lib::ffi::CallGuard::begin(translator_ptr) {
+            Ok(guard) => guard,
+            Err(e) => return Err(e).to_ptr(),
+        };
+
+        let translator = translator_ptr as usize;
+        let user_data = user_data as usize;
+
+        lib::ffi::runtime().spawn(async move {
+            let translator = unsafe { &**(translator as *const BoxedTranslator) };
+
+            // 翻译中 panic 时同样调用回调，宿主不会一直等待
+            let mut join = tokio::spawn(translator.translate(task));
+            let result = tokio::select! {
+                joined = &mut join => match joined {
+                    Ok(Ok(v)) => Ok(v.into_ffi_unbox()),
+                    Ok(Err(e)) => Err(e),
+                    Err(e) => Err(anyhow::anyhow!("plugin panicked: {}", e)),
+                },
+                _ = guard.cancelled() => {
+                    // 等待翻译真正停止，之后宿主可能释放翻译器
+                    join.abort();
+                    let _ = join.await;
+                    Err(anyhow::anyhow!("translator shut down"))
                 }
             };
 
-            Ok(result.into_ffi_unbox()).to_ptr()
-        })
-    }
+            completion(result.to_ptr(), user_data as *mut c_void);
+            drop(guard);
+        });
+
+        Ok(0i8).to_ptr()
+    })
 }
 
 #[no_mangle]
@@ -287,91 +953,216 @@ pub extern "C" fn call_translate_stream(
     callback_wrapper: StreamCallback,
     callback: *mut c_void
 ) -> *mut FfiResult<i8> {
-    let input = unsafe {
-        if json_str.is_null() {
-            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
-        }
-        match CStr::from_ptr(json_str).to_str() {
+    ffi_call_translate_stream(translator_ptr, ffi_str(json_str).map(Cow::from), callback_wrapper, callback, std::ptr::null_mut())
+}
+
+#[no_mangle]
+pub extern "C" fn call_translate_stream_cancellable(
+    translator_ptr: *mut TranslatorHandle,
+    json_str: *const c_char,
+    callback_wrapper: StreamCallback,
+    callback: *mut c_void,
+    stream_handle: *mut StreamHandle
+) -> *mut FfiResult<i8> {
+    ffi_call_translate_stream(translator_ptr, ffi_str(json_str).map(Cow::from), callback_wrapper, callback, stream_handle)
+}
+
+/// 与 `call_translate_stream_cancellable` 相同，任务为 `(ptr, len)` 形式的 UTF-8
+#[no_mangle]
+pub extern "C" fn call_translate_stream_utf8(
+    translator_ptr: *mut TranslatorHandle,
+    json: *const u8,
+    len: usize,
+    callback_wrapper: StreamCallback,
+    callback: *mut c_void,
+    stream_handle: *mut StreamHandle
+) -> *mut FfiResult<i8> {
+    ffi_call_translate_stream(translator_ptr, unsafe { lib::ffi::utf8_arg(json, len) }, callback_wrapper, callback, stream_handle)
+}
+
+/// 与 `call_translate_stream_cancellable` 相同，任务为 UTF-16，`len` 为 u16 个数
+#[no_mangle]
+pub extern "C" fn call_translate_stream_utf16(
+    translator_ptr: *mut TranslatorHandle,
+    json: *const u16,
+    len: usize,
+    callback_wrapper: StreamCallback,
+    callback: *mut c_void,
+    stream_handle: *mut StreamHandle
+) -> *mut FfiResult<i8> {
+    ffi_call_translate_stream(translator_ptr, unsafe { lib::ffi::utf16_arg(json, len) }, callback_wrapper, callback, stream_handle)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(plugin = #name)))]
+fn ffi_call_translate_stream(
+    translator_ptr: *mut TranslatorHandle,
+    input: anyhow::Result<Cow<str>>,
+    callback_wrapper: StreamCallback,
+    callback: *mut c_void,
+    stream_handle: *mut StreamHandle
+) -> *mut FfiResult<i8> {
+    lib::ffi::catch_ffi(|| {
+        let input = match input {
             Ok(s) => s,
+            Err(e) => return Err(e).to_ptr(),
+        };
+
+        let task: TranslateTask = match serde_json::from_str(&input) {
+            Ok(v) => v,
             Err(e) => {
-                return Err(anyhow::anyhow!("Invalid UTF-8: {}", e)).to_ptr();
+                return Err(anyhow::anyhow!("JSON parse error: {}", e)).to_ptr();
             }
-        }
-    };
+        };
 
-    let task: TranslateTask = match serde_json::from_str(input) {
-        Ok(v) => v,
-        Err(e) => {
-            return Err(anyhow::anyhow!("JSON parse error: {}", e)).to_ptr();
-        }
-    };
+        let Some(translator) = ffi_translator(translator_ptr) else {
+            return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
+        };
 
-    if translator_ptr.is_null() {
-        return Err(anyhow::anyhow!("Null pointer received")).to_ptr();
-    }
+        // 没有传入 `StreamHandle` 时只能由 `shutdown_translator` 取消
+        let local = lib::ffi::StreamControl::default();
+        let control = unsafe { lib::ffi::StreamControl::from_ptr(stream_handle) }.unwrap_or(&local);
 
-    let translator = unsafe { &*(translator_ptr as *mut #translator) };
+        let guard = match lib::ffi::CallGuard::begin(translator_ptr) {
+            Ok(guard) => guard,
+            Err(e) => return Err(e).to_ptr(),
+        };
 
-    let (tx, mut rx) = channel::<TranslateStreamChunk>(256);
+        let (tx, mut rx) = channel::<TranslateStreamChunk>(256);
 
-    let cb = callback as usize;
+        let cb = callback as usize;
 
-    if let Ok(h) = tokio::runtime::Handle::try_current() {
-        h.block_on(async {
+        lib::ffi::block_on(async {
             let handle = tokio::spawn(async move {
                 while let Some(chunk) = rx.recv().await {
                     callback_wrapper(chunk.into_ffi(), cb as *mut c_void);
                 }
             });
 
-            match translator.translate_stream(task, tx).await {
-                Err(e) => {
-                    return Err(anyhow::anyhow!("{}", e)).to_ptr();
-                }
-                _ => {}
+            // 取消时丢弃翻译 future，请求随之中止
+            let end_tx = tx.clone();
+            let outcome = tokio::select! {
+                result = translator.translate_stream(task, tx) => result.map(|_| false),
+                _ = control.cancelled() => Ok(true),
+                _ = guard.cancelled() => Ok(true),
             };
 
+            // 同样由转发任务调用回调，保证在翻译的最后一个增量之后
+            if let Ok(true) = outcome {
+                let _ = end_tx.send(TranslateStreamChunk::End).await;
+            }
+            drop(end_tx);
+
+            // 出错时同样等待回调完成，返回后不会再有回调
             let _ = handle.await;
 
-            Ok(0i8).to_ptr()
+            match outcome {
+                Ok(cancelled) => Ok(cancelled as i8).to_ptr(),
+                Err(e) => Err(e).to_ptr(),
+            }
         })
-    } else {
-        let handle = tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .unwrap();
-        let r = handle.block_on(async {
-            let handle = tokio::spawn(async move {
-                while let Some(chunk) = rx.recv().await {
-                    callback_wrapper(chunk.into_ffi(), cb as *mut c_void);
-                }
-            });
+    })
+}
 
-            match translator.translate_stream(task, tx).await {
-                Err(e) => {
-                    return Err(anyhow::anyhow!("{}", e)).into();
-                }
-                _ => {}
-            };
+#[no_mangle]
+pub extern "C" fn create_stream_handle() -> *mut StreamHandle {
+    lib::ffi::catch_panic(std::ptr::null_mut(), || {
+        lib::ffi::StreamControl::default().into_ffi()
+    })
+}
 
-            let _ = handle.await;
+/// 中止正在进行的流式翻译，回调会收到最后一个 `End`
+#[no_mangle]
+pub extern "C" fn cancel_stream(stream_handle: *mut StreamHandle) {
+    lib::ffi::catch_panic((), || {
+        if let Some(control) = unsafe { lib::ffi::StreamControl::from_ptr(stream_handle) } {
+            control.cancel();
+        }
+    })
+}
 
-            Ok(0i8)
-        });
+#[no_mangle]
+pub extern "C" fn free_stream_handle(stream_handle: *mut StreamHandle) {
+    lib::ffi::catch_panic((), || {
+        unsafe { lib::ffi::StreamControl::free(stream_handle) }
+    })
+}
 
-        r.to_ptr()
-    }
+/// 创建 `call_translate_into` 使用的缓冲区，由 `free_result_arena` 释放
+#[no_mangle]
+pub extern "C" fn create_result_arena() -> *mut ResultArena {
+    lib::ffi::catch_panic(std::ptr::null_mut(), || {
+        lib::ffi::ResultBuffer::default().into_ffi()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn free_result_arena(arena: *mut ResultArena) {
+    lib::ffi::catch_panic((), || {
+        unsafe { lib::ffi::ResultBuffer::free(arena) }
+    })
+}
+
+/// 插件中的 `tracing` 日志交给宿主回调，`max_level` 为 0 时关闭
+#[no_mangle]
+pub extern "C" fn set_log_callback(
+    callback: Option<lib::ffi::LogCallback>,
+    user_data: *mut c_void,
+    max_level: u32
+) {
+    lib::ffi::catch_panic((), || {
+        lib::plugin_log::install(callback, user_data, max_level)
+    })
+}
+
+/// 释放插件返回的字符串，包括错误信息
+#[no_mangle]
+pub extern "C" fn free_string(s: *mut c_char) {
+    lib::ffi::catch_panic((), || {
+        unsafe { lib::ffi::free_string(s) }
+    })
+}
+
+/// 释放结果及其错误信息，`ptr` 指向的内容已交给调用方
+#[no_mangle]
+pub extern "C" fn free_ffi_result(result: *mut FfiResult<c_void>) {
+    lib::ffi::catch_panic((), || {
+        unsafe { lib::ffi::free_ffi_result(result) }
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn free_ffi_status(result: *mut FfiResult<i8>) {
+    lib::ffi::catch_panic((), || {
+        unsafe { lib::ffi::free_ffi_status(result) }
+    })
 }
 
 #[no_mangle]
 pub extern "C" fn free_translate_result(result: *mut TranslateResultFFI) {
-    lib::ffi::free_translate_result(result)
+    lib::ffi::catch_panic((), || {
+        unsafe { lib::ffi::free_translate_result(result) }
+    })
 }
 
+/// 释放流式回调收到的增量，包括其中的译文
 #[no_mangle]
-pub extern "C" fn free_supported_languages(array: *mut *const c_char, len: usize) {
-    lib::ffi::free_supported_languages(array, len)
+pub extern "C" fn free_translate_stream_chunk(chunk: *mut lib::ffi::TranslateStreamChunkFFI) {
+    lib::ffi::catch_panic((), || {
+        unsafe { lib::ffi::free_translate_stream_chunk(chunk) }
+    })
 }
 
+#[no_mangle]
+pub extern "C" fn free_supported_languages(array: *mut *const c_char, len: usize) {
+    lib::ffi::catch_panic((), || {
+        unsafe { lib::ffi::free_supported_languages(array, len) }
     })
-}
\ No newline at end of file
+}
+
+    });
+
+    match &input.prefix {
+        Some(prefix) => prefix_exports(tokens, prefix),
+        None => tokens,
+    }
+}
@@ -0,0 +1,18 @@
+// 构建原生模块并复制为 xtranslator.node：node build.js [--release]
+const { execFileSync } = require('child_process')
+const fs = require('fs')
+const path = require('path')
+
+const release = process.argv.includes('--release')
+const args = ['build', '-p', 'xtranslator-node']
+if (release) {
+  args.push('--release')
+}
+execFileSync('cargo', args, { stdio: 'inherit', cwd: __dirname })
+
+const file = {
+  win32: 'xtranslator_node.dll',
+  darwin: 'libxtranslator_node.dylib',
+}[process.platform] || 'libxtranslator_node.so'
+const target = process.env.CARGO_TARGET_DIR || path.join(__dirname, '..', 'target')
+fs.copyFileSync(path.join(target, release ? 'release' : 'debug', file), path.join(__dirname, 'xtranslator.node'))
@@ -0,0 +1,89 @@
+// xtranslator-node 的接口，与 `Translator` trait 对应。
+// 任务与结果的字段与 lib 中 JSON 格式的 `TranslateTask`、`TranslateResult` 相同
+import { EventEmitter } from 'events'
+
+export interface TranslatedItem {
+  source: string
+  target: string
+}
+
+export interface TranslateTask {
+  id: string
+  content: string
+  source_language?: string | null
+  target_language?: string | null
+  user_prompt?: string | null
+  system_prompt?: string | null
+  field?: string | null
+  terms: TranslatedItem[]
+  references: TranslatedItem[]
+  /** 插件专属参数放在以插件名为键的对象中 */
+  extra?: Record<string, unknown> | null
+  request_id?: string | null
+  deadline_ms?: number | null
+}
+
+export interface Usage {
+  prompt_tokens: number
+  completion_tokens: number
+  characters: number
+  latency_ms?: number | null
+  request_id?: string | null
+}
+
+export interface TranslateResult {
+  reasoning?: string | null
+  content?: string | null
+  provider?: string | null
+  request_id?: string | null
+  partial: boolean
+  confidence?: number | null
+  usage?: Usage | null
+}
+
+export interface Capabilities {
+  supports_streaming: boolean
+  supports_terms: boolean
+  max_terms?: number | null
+  supports_references: boolean
+  max_references?: number | null
+  supports_field: boolean
+  supports_prompt: boolean
+  supports_auto_detect: boolean
+  needs_target_language: boolean
+  max_input_chars?: number | null
+}
+
+export type TranslateStreamChunk =
+  | { type: 'start' }
+  | { type: 'delta'; result: TranslateResult }
+  | { type: 'end' }
+
+/** 流式翻译，发出 `chunk`、`end` 与 `error` 事件，也可用 `for await` 逐段读取增量 */
+export declare class TranslateStream extends EventEmitter implements AsyncIterable<TranslateResult> {
+  on(event: 'chunk', listener: (result: TranslateResult) => void): this
+  on(event: 'end', listener: () => void): this
+  on(event: 'error', listener: (error: Error) => void): this
+  [Symbol.asyncIterator](): AsyncIterator<TranslateResult>
+  /** 停止接收，插件会尽快结束翻译 */
+  cancel(): void
+}
+
+export declare class Translator {
+  /** 从动态库创建翻译器 */
+  static load(path: string, config: Record<string, unknown>): Promise<Translator>
+  /** 按名称从插件目录创建翻译器 */
+  static fromRegistry(root: string, name: string, config: Record<string, unknown>): Promise<Translator>
+
+  getSupportedInputLanguages(): string[]
+  getSupportedOutputLanguages(): string[]
+  getSupportedPairs(): Array<[string, string]>
+  isSupportedPair(source: string, target: string): boolean
+  capabilities(): Capabilities
+
+  translate(task: TranslateTask): Promise<TranslateResult>
+  translateStream(task: TranslateTask): TranslateStream
+}
+
+/** 列出目录中的插件名称 */
+export declare function listPlugins(root: string): string[]
@@ -0,0 +1,130 @@
+// xtranslator-node 的 JS 接口，类型见 index.d.ts。
+// 原生模块由 `npm run build` 构建为同目录的 xtranslator.node
+const { EventEmitter } = require('events')
+const native = require('./xtranslator.node')
+
+class TranslateStream extends EventEmitter {
+  constructor(translator, task) {
+    super()
+    this._results = []
+    this._waiters = []
+    this._done = false
+    this._error = null
+    // 原生回调总在之后的事件循环中触发，调用方可以先注册监听
+    this._native = translator.translateStream(task, (err, chunk) => {
+      if (err) {
+        this._finish(err)
+      } else if (chunk.type === 'delta') {
+        this._push(chunk.result)
+      } else if (chunk.type === 'end') {
+        this._finish(null)
+      }
+    })
+  }
+
+  _push(result) {
+    if (this._done) {
+      return
+    }
+    this.emit('chunk', result)
+    const waiter = this._waiters.shift()
+    if (waiter) {
+      waiter.resolve({ value: result, done: false })
+    } else {
+      this._results.push(result)
+    }
+  }
+
+  _finish(err) {
+    if (this._done) {
+      return
+    }
+    this._done = true
+    this._error = err
+    for (const waiter of this._waiters.splice(0)) {
+      if (err) {
+        waiter.reject(err)
+      } else {
+        waiter.resolve({ value: undefined, done: true })
+      }
+    }
+    if (!err) {
+      this.emit('end')
+    } else if (this.listenerCount('error') > 0) {
+      // 没有监听时不抛出未处理的 `error` 事件，错误由 `for await` 读取
+      this.emit('error', err)
+    }
+  }
+
+  [Symbol.asyncIterator]() {
+    return {
+      next: () => {
+        if (this._results.length > 0) {
+          return Promise.resolve({ value: this._results.shift(), done: false })
+        }
+        if (this._done) {
+          return this._error ? Promise.reject(this._error) : Promise.resolve({ value: undefined, done: true })
+        }
+        return new Promise((resolve, reject) => this._waiters.push({ resolve, reject }))
+      },
+      // `for await` 中途退出时停止接收
+      return: () => {
+        this.cancel()
+        return Promise.resolve({ value: undefined, done: true })
+      },
+    }
+  }
+
+  cancel() {
+    this._native.cancel()
+    this._finish(null)
+  }
+}
+
+class Translator {
+  constructor(inner) {
+    this._native = inner
+  }
+
+  static async load(path, config) {
+    return new Translator(await native.loadTranslator(path, config))
+  }
+
+  static async fromRegistry(root, name, config) {
+    return new Translator(await native.createFromRegistry(root, name, config))
+  }
+
+  getSupportedInputLanguages() {
+    return this._native.getSupportedInputLanguages()
+  }
+
+  getSupportedOutputLanguages() {
+    return this._native.getSupportedOutputLanguages()
+  }
+
+  getSupportedPairs() {
+    return this._native.getSupportedPairs()
+  }
+
+  isSupportedPair(source, target) {
+    return this._native.isSupportedPair(source, target)
+  }
+
+  capabilities() {
+    return this._native.capabilities()
+  }
+
+  translate(task) {
+    return this._native.translate(task)
+  }
+
+  translateStream(task) {
+    return new TranslateStream(this._native, task)
+  }
+}
+
+function listPlugins(root) {
+  return native.listPlugins(root)
+}
+
+module.exports = { Translator, TranslateStream, listPlugins }
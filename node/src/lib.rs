@@ -0,0 +1,158 @@
+use lib::ffi_proxy::{PluginRegistry, ProxyTranslator};
+use lib::{DynTranslator, TranslateStreamChunk, TranslateTask, TranslatorFactory};
+use napi::bindgen_prelude::spawn;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Notify};
+
+fn to_napi(err: anyhow::Error) -> napi::Error {
+    napi::Error::from_reason(format!("{:#}", err))
+}
+
+fn parse_task(task: Value) -> napi::Result<TranslateTask> {
+    serde_json::from_value(task).map_err(|e| napi::Error::from_reason(format!("invalid task: {}", e)))
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> napi::Result<Value> {
+    serde_json::to_value(value).map_err(|e| napi::Error::from_reason(e.to_string()))
+}
+
+/// N-API 层的接口，由 index.js 包装为 index.d.ts 中的 `Translator` 与 `TranslateStream`。
+/// 任务、结果与配置以 JS 对象传递，字段与 JSON 格式的 `TranslateTask`、`TranslateResult` 相同
+#[napi]
+pub struct NativeTranslator {
+    inner: Arc<dyn DynTranslator>,
+}
+
+/// 从动态库创建翻译器
+#[napi]
+pub async fn load_translator(path: String, config: Value) -> napi::Result<NativeTranslator> {
+    let translator = ProxyTranslator::load(path, config).await.map_err(to_napi)?;
+    Ok(NativeTranslator {
+        inner: Arc::new(translator),
+    })
+}
+
+/// 按名称从插件目录创建翻译器
+#[napi]
+pub async fn create_from_registry(root: String, name: String, config: Value) -> napi::Result<NativeTranslator> {
+    let registry = PluginRegistry::scan(root).map_err(to_napi)?;
+    let translator = registry.create(&name, config).await.map_err(to_napi)?;
+    Ok(NativeTranslator {
+        inner: Arc::from(translator),
+    })
+}
+
+/// 列出目录中的插件名称
+#[napi]
+pub fn list_plugins(root: String) -> napi::Result<Vec<String>> {
+    Ok(PluginRegistry::scan(root).map_err(to_napi)?.names())
+}
+
+#[napi]
+impl NativeTranslator {
+    #[napi]
+    pub fn get_supported_input_languages(&self) -> napi::Result<Vec<String>> {
+        self.inner.get_supported_input_languages().map_err(to_napi)
+    }
+
+    #[napi]
+    pub fn get_supported_output_languages(&self) -> napi::Result<Vec<String>> {
+        self.inner.get_supported_output_languages().map_err(to_napi)
+    }
+
+    /// 每个语言对为 `[source, target]`
+    #[napi]
+    pub fn get_supported_pairs(&self) -> napi::Result<Vec<Vec<String>>> {
+        Ok(self
+            .inner
+            .get_supported_pairs()
+            .map_err(to_napi)?
+            .into_iter()
+            .map(|(source, target)| vec![source, target])
+            .collect())
+    }
+
+    #[napi]
+    pub fn is_supported_pair(&self, source: String, target: String) -> napi::Result<bool> {
+        self.inner.is_supported_pair(source, target).map_err(to_napi)
+    }
+
+    #[napi]
+    pub fn capabilities(&self) -> napi::Result<Value> {
+        to_value(self.inner.capabilities())
+    }
+
+    #[napi]
+    pub async fn translate(&self, task: Value) -> napi::Result<Value> {
+        let task = parse_task(task)?;
+        to_value(self.inner.translate(task).await.map_err(to_napi)?)
+    }
+
+    /// 以 `callback(err, chunk)` 依次传回 `{ type: 'start' }`、`{ type: 'delta', result }` 与 `{ type: 'end' }`，
+    /// 出错时只传回错误。调用 `NativeStream::cancel` 后不再回调
+    #[napi(ts_args_type = "task: object, callback: (err: Error | null, chunk: object) => void")]
+    pub fn translate_stream(
+        &self,
+        task: Value,
+        callback: ThreadsafeFunction<Value, ErrorStrategy::CalleeHandled>,
+    ) -> napi::Result<NativeStream> {
+        let task = parse_task(task)?;
+        let translator = self.inner.clone();
+        let cancel = Arc::new(Notify::new());
+        let cancelled = cancel.clone();
+
+        spawn(async move {
+            let (sender, mut receiver) = mpsc::channel(32);
+            let stream = tokio::spawn(async move { translator.translate_stream(task, sender).await });
+
+            let call = |chunk: napi::Result<Value>| {
+                callback.call(chunk, ThreadsafeFunctionCallMode::NonBlocking);
+            };
+            let stopped = loop {
+                tokio::select! {
+                    chunk = receiver.recv() => match chunk {
+                        Some(TranslateStreamChunk::Start) => call(Ok(json!({ "type": "start" }))),
+                        Some(TranslateStreamChunk::Delta(result)) => {
+                            call(to_value(result).map(|result| json!({ "type": "delta", "result": result })))
+                        }
+                        // 结束标记在翻译返回后发送
+                        Some(TranslateStreamChunk::End) => {}
+                        None => break false,
+                    },
+                    _ = cancelled.notified() => break true,
+                }
+            };
+
+            // 关闭接收端，插件发送失败后尽快结束
+            drop(receiver);
+            let result = stream.await;
+            if stopped {
+                return;
+            }
+            match result {
+                Ok(Ok(())) => call(Ok(json!({ "type": "end" }))),
+                Ok(Err(e)) => call(Err(to_napi(e))),
+                Err(e) => call(Err(napi::Error::from_reason(e.to_string()))),
+            }
+        });
+
+        Ok(NativeStream { cancel })
+    }
+}
+
+#[napi]
+pub struct NativeStream {
+    cancel: Arc<Notify>,
+}
+
+#[napi]
+impl NativeStream {
+    /// 停止回调，插件会尽快结束翻译
+    #[napi]
+    pub fn cancel(&self) {
+        self.cancel.notify_one();
+    }
+}
@@ -0,0 +1,54 @@
+// 使用 plugin-dryrun 测试绑定，`npm test` 会先构建插件与原生模块
+const assert = require('assert')
+const path = require('path')
+const test = require('node:test')
+const { Translator, listPlugins } = require('./index.js')
+
+const target = path.join(process.env.CARGO_TARGET_DIR || path.join(__dirname, '..', 'target'), 'debug')
+const dylib = {
+  win32: 'plugin_dryrun.dll',
+  darwin: 'libplugin_dryrun.dylib',
+}[process.platform] || 'libplugin_dryrun.so'
+
+const task = (content) => ({
+  id: '1',
+  content,
+  target_language: 'zh-CN',
+  terms: [],
+  references: [],
+  request_id: 'req-1',
+})
+
+test('translate', async () => {
+  const translator = await Translator.load(path.join(target, dylib), {})
+  assert.deepStrictEqual(translator.getSupportedInputLanguages(), ['*'])
+  assert.strictEqual(translator.capabilities().supports_streaming, true)
+
+  const result = await translator.translate(task('Hello'))
+  assert.strictEqual(result.content, '[zh-CN] Hello')
+
+  await assert.rejects(Translator.load(path.join(target, 'missing.so'), {}))
+  await assert.rejects(translator.translate({ id: '1' }))
+})
+
+test('translateStream', async () => {
+  const translator = await Translator.fromRegistry(target, 'dryrun', {})
+  assert.ok(listPlugins(target).includes('dryrun'))
+
+  const events = []
+  const stream = translator.translateStream(task('Hello world'))
+  stream.on('chunk', (result) => events.push(result.content))
+  const contents = []
+  for await (const result of stream) {
+    contents.push(result.content)
+  }
+  assert.deepStrictEqual(contents.filter(Boolean), ['[zh-CN] ', 'Hello ', 'world'])
+  assert.deepStrictEqual(events, contents)
+
+  // 中途退出后不再接收
+  const stopped = translator.translateStream(task('a b c d'))
+  for await (const result of stopped) {
+    assert.strictEqual(result.content, '[zh-CN] ')
+    break
+  }
+})
@@ -1,19 +1,32 @@
 use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use language_tags::LanguageTag;
-use lib::utils::normal2stream;
+use lib::detect::LanguageDetector;
+use lib::error::XTranslateError;
+use lib::http::{execute, HttpConfig};
+use lib::langmap::LangMap;
+use lib::limit::with_limit;
+use lib::request::{ensure_request_id, with_request_id};
+#[cfg(feature = "tracing")]
+use lib::trace::language_pair;
+use lib::trace::record_usage;
+use lib::utils::{normal2stream, schema_of, with_deadline};
 #[cfg(test)]
 use lib::utils::{test_translate, test_translate_stream};
-use lib::{TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use lib::validate::{ConfigIssue, ConfigValidator};
+use lib::{Capabilities, StaticInfo, TranslateResult, TranslateStreamChunk, TranslateTask, Translator, Usage};
 use md5::Md5;
-use reqwest::{Client, Method};
+use reqwest::Method;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sha2::Digest;
 use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+use std::time::Instant;
 use tokio::sync::mpsc::Sender;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BaiduFanyiLanguages {
     /// 简体中文
     Chinese,
@@ -109,73 +122,142 @@ impl Display for BaiduFanyiLanguages {
     }
 }
 
+impl BaiduFanyiLanguages {
+    /// 由百度语言代码解析
+    pub fn from_code(code: &str) -> Option<Self> {
+        let lang = match code {
+            "zh" => Self::Chinese,
+            "en" => Self::English,
+            "yue" => Self::Yue,
+            "wyw" => Self::Wyw,
+            "jp" => Self::Japanese,
+            "kor" => Self::Korean,
+            "fra" => Self::French,
+            "spa" => Self::Spanish,
+            "th" => Self::Thai,
+            "ara" => Self::Arabic,
+            "ru" => Self::Russian,
+            "pt" => Self::Portuguese,
+            "de" => Self::German,
+            "it" => Self::Italian,
+            "el" => Self::Greek,
+            "nl" => Self::Dutch,
+            "pl" => Self::Polish,
+            "bul" => Self::Bulgarian,
+            "est" => Self::Estonian,
+            "dan" => Self::Danish,
+            "fin" => Self::Finnish,
+            "cs" => Self::Czech,
+            "rom" => Self::Romanian,
+            "slo" => Self::Slovenian,
+            "swe" => Self::Swedish,
+            "hu" => Self::Hungarian,
+            "cht" => Self::TraditionalChinese,
+            "vie" => Self::Vietnamese,
+            _ => return None,
+        };
+        Some(lang)
+    }
+
+    pub fn to_language_tag(&self) -> Result<LanguageTag> {
+        let tag = match self {
+            BaiduFanyiLanguages::Chinese => "zh-CN",
+            BaiduFanyiLanguages::English => "en",
+            BaiduFanyiLanguages::Yue => "yue",
+            BaiduFanyiLanguages::Wyw => "lzh",
+            BaiduFanyiLanguages::Japanese => "ja",
+            BaiduFanyiLanguages::Korean => "ko",
+            BaiduFanyiLanguages::French => "fr",
+            BaiduFanyiLanguages::Spanish => "es",
+            BaiduFanyiLanguages::Thai => "th",
+            BaiduFanyiLanguages::Arabic => "ar",
+            BaiduFanyiLanguages::Russian => "ru",
+            BaiduFanyiLanguages::Portuguese => "pt",
+            BaiduFanyiLanguages::German => "de",
+            BaiduFanyiLanguages::Italian => "it",
+            BaiduFanyiLanguages::Greek => "el",
+            BaiduFanyiLanguages::Dutch => "nl",
+            BaiduFanyiLanguages::Polish => "pl",
+            BaiduFanyiLanguages::Bulgarian => "bg",
+            BaiduFanyiLanguages::Estonian => "et",
+            BaiduFanyiLanguages::Danish => "da",
+            BaiduFanyiLanguages::Finnish => "fi",
+            BaiduFanyiLanguages::Czech => "cs",
+            BaiduFanyiLanguages::Romanian => "ro",
+            BaiduFanyiLanguages::Slovenian => "sl",
+            BaiduFanyiLanguages::Swedish => "sv",
+            BaiduFanyiLanguages::Hungarian => "hu",
+            BaiduFanyiLanguages::TraditionalChinese => "zh-TW",
+            BaiduFanyiLanguages::Vietnamese => "vi",
+        };
+        LanguageTag::parse(tag).map_err(|e| anyhow!(e))
+    }
+}
+
+static LANGUAGES: LazyLock<LangMap<BaiduFanyiLanguages>> = LazyLock::new(|| {
+    LangMap::new()
+        .lang("zh", BaiduFanyiLanguages::Chinese)
+        .script("zh", "Hant", BaiduFanyiLanguages::TraditionalChinese)
+        .lang("en", BaiduFanyiLanguages::English)
+        .lang("yue", BaiduFanyiLanguages::Yue)
+        .lang("lzh", BaiduFanyiLanguages::Wyw)
+        .lang("ja", BaiduFanyiLanguages::Japanese)
+        .lang("ko", BaiduFanyiLanguages::Korean)
+        .lang("fr", BaiduFanyiLanguages::French)
+        .lang("es", BaiduFanyiLanguages::Spanish)
+        .lang("th", BaiduFanyiLanguages::Thai)
+        .lang("ar", BaiduFanyiLanguages::Arabic)
+        .lang("ru", BaiduFanyiLanguages::Russian)
+        .lang("pt", BaiduFanyiLanguages::Portuguese)
+        .lang("de", BaiduFanyiLanguages::German)
+        .lang("it", BaiduFanyiLanguages::Italian)
+        .lang("el", BaiduFanyiLanguages::Greek)
+        .lang("nl", BaiduFanyiLanguages::Dutch)
+        .lang("pl", BaiduFanyiLanguages::Polish)
+        .lang("bg", BaiduFanyiLanguages::Bulgarian)
+        .lang("et", BaiduFanyiLanguages::Estonian)
+        .lang("da", BaiduFanyiLanguages::Danish)
+        .lang("fi", BaiduFanyiLanguages::Finnish)
+        .lang("cs", BaiduFanyiLanguages::Czech)
+        .lang("ro", BaiduFanyiLanguages::Romanian)
+        .lang("sl", BaiduFanyiLanguages::Slovenian)
+        .lang("sv", BaiduFanyiLanguages::Swedish)
+        .lang("hu", BaiduFanyiLanguages::Hungarian)
+        .lang("vi", BaiduFanyiLanguages::Vietnamese)
+});
+
 impl TryFrom<LanguageTag> for BaiduFanyiLanguages {
     type Error = anyhow::Error;
 
     fn try_from(tag: LanguageTag) -> Result<Self, Self::Error> {
-        let primary = tag.primary_language();
-
-        // 特殊处理中文变体
-        if primary == "zh" {
-            return if tag.script() == Some("Hant")
-                || tag
-                    .region()
-                    .map_or(false, |r| ["TW", "HK", "MO"].contains(&r))
-            {
-                Ok(Self::TraditionalChinese)
-            } else {
-                Ok(Self::Chinese)
-            };
-        }
-
-        // 处理其他语言映射
-        match primary {
-            "en" => Ok(Self::English),
-            "yue" => Ok(Self::Yue),
-            "lzh" => Ok(Self::Wyw),
-            "ja" => Ok(Self::Japanese),
-            "ko" => Ok(Self::Korean),
-            "fr" => Ok(Self::French),
-            "es" => Ok(Self::Spanish),
-            "th" => Ok(Self::Thai),
-            "ar" => Ok(Self::Arabic),
-            "ru" => Ok(Self::Russian),
-            "pt" => Ok(Self::Portuguese),
-            "de" => Ok(Self::German),
-            "it" => Ok(Self::Italian),
-            "el" => Ok(Self::Greek),
-            "nl" => Ok(Self::Dutch),
-            "pl" => Ok(Self::Polish),
-            "bg" => Ok(Self::Bulgarian),
-            "et" => Ok(Self::Estonian),
-            "da" => Ok(Self::Danish),
-            "fi" => Ok(Self::Finnish),
-            "cs" => Ok(Self::Czech),
-            "ro" => Ok(Self::Romanian),
-            "sl" => Ok(Self::Slovenian),
-            "sv" => Ok(Self::Swedish),
-            "hu" => Ok(Self::Hungarian),
-            "vi" => Ok(Self::Vietnamese),
-            _ => bail!("Unsupported BCP47 language"),
-        }
+        LANGUAGES.map(&tag)
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct BaiduFanyiTranslator {
+    /// 百度翻译开放平台 APP ID
     pub app_id: String,
+    /// 密钥
     pub secret: String,
+    #[serde(flatten, default)]
+    pub http: HttpConfig,
 }
 
 impl BaiduFanyiTranslator {
-    fn build_request(&self, task: &TranslateTask) -> Result<Value> {
-        let uuid = uuid::Uuid::new_v4().to_string();
-
-        let s = format!("{}{}{}{}", self.app_id, task.content, uuid, self.secret);
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+    fn sign(&self, q: &str, salt: &str) -> String {
+        let s = format!("{}{}{}{}", self.app_id, q, salt, self.secret);
 
         let mut md5 = Md5::new();
         md5.update(s);
-        let hash = hex::encode(md5.finalize());
+        hex::encode(md5.finalize())
+    }
+
+    fn build_request(&self, task: &TranslateTask) -> Result<Value> {
+        let uuid = uuid::Uuid::new_v4().to_string();
+
+        let hash = self.sign(&task.content, &uuid);
 
         let source_language = task
             .source_language
@@ -244,7 +326,37 @@ impl Translator for BaiduFanyiTranslator {
     type This = Self;
 
     async fn new(config: Value) -> Result<Self> {
-        serde_json::from_value(config).map_err(|e| anyhow!(e))
+        serde_json::from_value(config)
+            .map_err(|e| anyhow!(XTranslateError::InvalidConfig(e.to_string())))
+    }
+
+    fn config_schema() -> Option<Value> {
+        Some(schema_of::<Self>())
+    }
+
+    fn validate_config(config: &Value) -> Result<Vec<ConfigIssue>> {
+        let mut validator = ConfigValidator::new(config);
+        if let Some(app_id) = validator.require_str("app_id") {
+            if !app_id.chars().all(|c| c.is_ascii_digit()) {
+                validator.issue("app_id", "APP ID should only contain digits");
+            }
+        }
+        validator.require_str("secret");
+        validator.http();
+        Ok(validator.finish())
+    }
+
+    fn static_info() -> StaticInfo {
+        StaticInfo {
+            input_languages: BaiduFanyiTranslator::lang_list().ok(),
+            output_languages: BaiduFanyiTranslator::lang_list().ok(),
+            capabilities: Some(Capabilities {
+                supports_auto_detect: true,
+                needs_target_language: true,
+                max_input_chars: Some(2000),
+                ..Default::default()
+            }),
+        }
     }
 
     fn get_supported_input_languages(&self) -> Result<Vec<String>> {
@@ -263,46 +375,160 @@ impl Translator for BaiduFanyiTranslator {
         Ok(BaiduFanyiLanguages::try_from(LanguageTag::parse(lang.as_str())?).is_ok())
     }
 
-    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+    /// 单次请求不超过 6000 字节，按每个汉字 3 字节估算
+    fn capabilities(&self) -> Capabilities {
+        Self::static_info().capabilities.unwrap_or_default()
+    }
+
+    async fn translate(&self, mut task: TranslateTask) -> Result<TranslateResult> {
+        let request_id = ensure_request_id(&mut task);
+        with_request_id(
+            &request_id,
+            with_deadline(
+                self.http.deadline(),
+                with_limit(
+                    "baidu_fanyi",
+                    self.http.max_concurrency,
+                    self.do_translate(task),
+                ),
+            ),
+        )
+        .await
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        normal2stream(self, task, sender).await
+    }
+}
+
+impl BaiduFanyiTranslator {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "translate",
+            skip_all,
+            err,
+            fields(provider = "baidu_fanyi", languages = %language_pair(&task))
+        )
+    )]
+    async fn do_translate(&self, task: TranslateTask) -> Result<TranslateResult> {
         let body = self.build_request(&task)?;
 
-        let client = Client::new();
-        let resp = client
+        let client = self.http.build_client()?;
+        let start = Instant::now();
+        let request = client
             .request(
                 Method::POST,
                 "https://fanyi-api.baidu.com/api/trans/vip/translate",
             )
             .form(&body)
-            .send()
-            .await?;
-        let json = resp.json::<Value>().await?;
+            .build()
+            .map_err(XTranslateError::from)?;
+        let resp = execute(&client, request).await?;
+        let json = resp.json::<Value>().await.map_err(XTranslateError::from)?;
 
-        if json["error_code"]
-            .as_str()
-            .map(|n| n != "52000")
-            .unwrap_or(false)
-        {
-            bail!(
-                "Request API error: {}, {:?}",
-                json["error_code"].as_str().unwrap(),
-                json["error_msg"].as_str()
-            )
+        if let Some(code) = json["error_code"].as_str().filter(|&n| n != "52000") {
+            bail!(map_error(code, json["error_msg"].as_str().unwrap_or("")))
         }
 
+        // 按原文字符数计费
+        let usage = Usage {
+            characters: task.content.chars().count() as u64,
+            latency_ms: Some(start.elapsed().as_millis() as u64),
+            ..Default::default()
+        };
+        record_usage(&usage);
+
         Ok(TranslateResult {
             reasoning: None,
             content: json["trans_result"][0]["dst"]
                 .as_str()
                 .map(|s| s.to_string()),
+            usage: Some(usage),
+            request_id: task.request_id.clone(),
+            ..Default::default()
         })
     }
+}
 
-    async fn translate_stream(
-        &self,
-        task: TranslateTask,
-        sender: Sender<TranslateStreamChunk>,
-    ) -> Result<()> {
-        normal2stream(self, task, sender).await
+/// 百度语种识别接口
+#[async_trait]
+impl LanguageDetector for BaiduFanyiTranslator {
+    async fn detect(&self, text: &str) -> Result<Vec<(LanguageTag, f32)>> {
+        let uuid = uuid::Uuid::new_v4().to_string();
+
+        let body = json!({
+            "q": text,
+            "appid": self.app_id,
+            "salt": uuid,
+            "sign": self.sign(text, &uuid),
+        });
+
+        let client = self.http.build_client()?;
+        let request = client
+            .request(
+                Method::POST,
+                "https://fanyi-api.baidu.com/api/trans/vip/language",
+            )
+            .form(&body)
+            .build()
+            .map_err(XTranslateError::from)?;
+        let resp = execute(&client, request).await?;
+        let json = resp.json::<Value>().await.map_err(XTranslateError::from)?;
+
+        let error_code = match &json["error_code"] {
+            Value::Number(n) => n.as_i64().unwrap_or(0),
+            Value::String(s) => s.parse().unwrap_or(0),
+            _ => 0,
+        };
+
+        if error_code != 0 {
+            bail!(map_error(
+                &error_code.to_string(),
+                json["error_msg"].as_str().unwrap_or("")
+            ))
+        }
+
+        let src = json["data"]["src"]
+            .as_str()
+            .ok_or(anyhow!("数据解析失败"))?;
+
+        match BaiduFanyiLanguages::from_code(src) {
+            Some(lang) => Ok(vec![(lang.to_language_tag()?, 1.0)]),
+            None => Ok(vec![]),
+        }
+    }
+}
+
+#[test]
+fn test_baidu_fanyi_language_codes() -> Result<()> {
+    for code in ["zh", "cht", "jp", "kor", "wyw", "vie"] {
+        let lang = BaiduFanyiLanguages::from_code(code).unwrap();
+        let tag = lang.to_language_tag()?;
+        assert_eq!(BaiduFanyiLanguages::try_from(tag)?.to_string(), code);
+    }
+
+    assert!(BaiduFanyiLanguages::from_code("xx").is_none());
+
+    Ok(())
+}
+
+/// 按百度翻译的错误码分类
+fn map_error(code: &str, message: &str) -> XTranslateError {
+    let message = format!("{} ({})", message, code);
+    match code {
+        "52001" => XTranslateError::Timeout(message),
+        "52003" | "54001" | "58000" | "90107" => XTranslateError::AuthFailed(message),
+        "54003" | "54005" => XTranslateError::RateLimited { retry_after: None },
+        "58001" => XTranslateError::UnsupportedLanguage(message),
+        _ => XTranslateError::ProviderError {
+            code: code.to_string(),
+            message,
+        },
     }
 }
 
@@ -311,6 +537,7 @@ async fn test_baidu_fanyi() -> Result<()> {
     let translator = BaiduFanyiTranslator {
         app_id: env!("BAIDU_FANYI_APP_ID").to_string(),
         secret: env!("BAIDU_FANYI_SECRET").to_string(),
+        http: Default::default(),
     };
 
     test_translate(translator).await
@@ -321,6 +548,7 @@ async fn test_baidu_fanyi_stream() -> Result<()> {
     let translator = BaiduFanyiTranslator {
         app_id: env!("BAIDU_FANYI_APP_ID").to_string(),
         secret: env!("BAIDU_FANYI_SECRET").to_string(),
+        http: Default::default(),
     };
 
     test_translate_stream(translator).await
@@ -0,0 +1,705 @@
+pub mod translator;
+
+#[cfg(test)]
+use ::lib::ffi_proxy::{PluginRegistry, ProxyTranslator, ProxyTranslatorFactory};
+#[cfg(test)]
+use ::lib::{TranslateStreamChunk, TranslateTask, Translator, TranslatorFactory};
+#[cfg(test)]
+use std::sync::Arc;
+
+#[cfg(feature = "dylib")]
+pub mod lib {
+    use crate::translator::{DryRunTranslator, PseudoTranslator, StrictTranslator};
+    use macros::build_ffi;
+
+    build_ffi!(
+        "dryrun",
+        [
+            ("dryrun", DryRunTranslator),
+            ("dryrun_pseudo", PseudoTranslator),
+            ("dryrun_strict", StrictTranslator)
+        ]
+    );
+}
+
+/// 插件动态库。`cargo test` 不会构建 cdylib，第一次使用时构建到单独的目录，
+/// 避免与正在运行的 `cargo test` 争用 target 目录的锁
+#[cfg(test)]
+fn built_plugin() -> String {
+    static PATH: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+    PATH.get_or_init(|| {
+        // target/debug/deps/<测试程序>
+        let exe = std::env::current_exe().unwrap();
+        let target_dir = exe.ancestors().nth(3).unwrap().join("plugin-test");
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let status = std::process::Command::new(cargo)
+            .args(["build", "--lib", "--manifest-path", concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")])
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "failed to build the plugin library");
+
+        target_dir
+            .join("debug")
+            .join(format!(
+                "{}plugin_dryrun{}",
+                std::env::consts::DLL_PREFIX,
+                std::env::consts::DLL_SUFFIX
+            ))
+            .to_string_lossy()
+            .into_owned()
+    })
+    .clone()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_proxy_concurrency() -> anyhow::Result<()> {
+    let path = built_plugin();
+
+    let translator = Arc::new(ProxyTranslator::load(path, serde_json::json!({ "delay_ms": 1 })).await?);
+
+    let mut tasks = vec![];
+    for i in 0..32 {
+        let translator = translator.clone();
+        tasks.push(tokio::spawn(async move {
+            let task: TranslateTask = serde_json::from_value(serde_json::json!({
+                "id": i.to_string(),
+                "content": format!("task {}", i),
+                "target_language": "zh-CN",
+                "terms": [],
+                "references": [],
+            }))?;
+
+            let content = if i % 2 == 0 {
+                translator.translate(task).await?.content.unwrap_or_default()
+            } else {
+                let (tx, mut rx) = tokio::sync::mpsc::channel(64);
+                let collect = tokio::spawn(async move {
+                    let mut content = String::new();
+                    while let Some(chunk) = rx.recv().await {
+                        if let TranslateStreamChunk::Delta(delta) = chunk {
+                            content.push_str(delta.content.as_deref().unwrap_or(""));
+                        }
+                    }
+                    content
+                });
+                translator.translate_stream(task, tx).await?;
+                collect.await?
+            };
+
+            assert_eq!(content, format!("[zh-CN] task {}", i));
+            Ok::<_, anyhow::Error>(())
+        }));
+    }
+
+    for task in tasks {
+        task.await??;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "dylib")]
+#[test]
+fn test_utf8_utf16_exports() -> anyhow::Result<()> {
+    use ::lib::ffi::{unwrap_handle_result, TranslatorHandle};
+    use ::lib::TranslateResult;
+
+    let config: Vec<u16> = "{}".encode_utf16().collect();
+    let translator = unwrap_handle_result(lib::create_translator_utf16(config.as_ptr(), config.len()))?
+        as *mut TranslatorHandle;
+
+    let task = r#"{"id":"1","content":"你好","target_language":"en","terms":[],"references":[]}"#;
+    let wide: Vec<u16> = task.encode_utf16().collect();
+    let result = unwrap_handle_result(lib::call_translate_utf16(translator, wide.as_ptr(), wide.len()))?;
+    assert_eq!(TranslateResult::from_ffi(result)?.content.as_deref(), Some("[en] 你好"));
+
+    // 不以 NUL 结尾，只读取前 `len` 个字节
+    let buffer = format!("{}trailing", task);
+    let result = unwrap_handle_result(lib::call_translate_utf8(translator, buffer.as_ptr(), task.len()))?;
+    assert_eq!(TranslateResult::from_ffi(result)?.content.as_deref(), Some("[en] 你好"));
+
+    lib::destroy_translator(translator);
+
+    Ok(())
+}
+
+#[cfg(feature = "dylib")]
+#[test]
+fn test_translate_into() -> anyhow::Result<()> {
+    use ::lib::ffi::{unwrap_handle_result, TranslatorHandle};
+    use ::lib::TranslateResult;
+
+    let config = "{}";
+    let translator = unwrap_handle_result(lib::create_translator_utf8(config.as_ptr(), config.len()))?
+        as *mut TranslatorHandle;
+    let status = lib::is_supported_pair_utf8(translator, "zh".as_ptr(), 2, "en".as_ptr(), 2);
+    assert_eq!(*unsafe { Box::from_raw(unwrap_handle_result(status)?) }, 0);
+
+    let arena = lib::create_result_arena();
+    let mut out: *const u8 = std::ptr::null();
+    let mut out_len = 0;
+    for content in ["你好", "再见"] {
+        let task = format!(r#"{{"id":"1","content":"{}","target_language":"en","terms":[],"references":[]}}"#, content);
+        let status = lib::call_translate_into(translator, task.as_ptr(), task.len(), arena, &mut out, &mut out_len);
+        drop(unsafe { Box::from_raw(unwrap_handle_result(status)?) });
+        let result: TranslateResult = serde_json::from_slice(unsafe { std::slice::from_raw_parts(out, out_len) })?;
+        assert_eq!(result.content, Some(format!("[en] {}", content)));
+    }
+
+    let invalid = "{";
+    assert!(unwrap_handle_result(lib::call_translate_into(translator, invalid.as_ptr(), 1, arena, &mut out, &mut out_len)).is_err());
+
+    lib::free_result_arena(arena);
+    lib::destroy_translator(translator);
+
+    Ok(())
+}
+
+#[cfg(feature = "dylib")]
+#[test]
+fn test_clone_translator() -> anyhow::Result<()> {
+    use ::lib::ffi::{unwrap_handle_result, TranslatorHandle};
+    use ::lib::TranslateResult;
+    use std::ffi::CString;
+
+    let config = CString::new("{}")?;
+    let translator = unwrap_handle_result(lib::create_translator(config.as_ptr()))? as *mut TranslatorHandle;
+    assert!(lib::clone_translator(std::ptr::null_mut()).is_null());
+
+    let workers: Vec<_> = (0..4)
+        .map(|i| {
+            let handle = lib::clone_translator(translator) as usize;
+            std::thread::spawn(move || {
+                let handle = handle as *mut TranslatorHandle;
+                let task = CString::new(format!(
+                    r#"{{"id":"{i}","content":"line {i}","target_language":"fr","terms":[],"references":[]}}"#
+                ))
+                .unwrap();
+                let result = unwrap_handle_result(lib::call_translate(handle, task.as_ptr()))
+                    .and_then(TranslateResult::from_ffi)
+                    .map(|r| r.content);
+                lib::destroy_translator(handle);
+                result
+            })
+        })
+        .collect();
+
+    // 创建时的引用先释放，其余线程仍可使用
+    lib::destroy_translator(translator);
+    for (i, worker) in workers.into_iter().enumerate() {
+        assert_eq!(worker.join().unwrap()?, Some(format!("[fr] line {}", i)));
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_drop_translate_in_flight() -> anyhow::Result<()> {
+    use ::lib::ffi_proxy::PluginLibrary;
+    use std::time::Duration;
+
+    let library = Arc::new(PluginLibrary::open(&built_plugin())?);
+    let translator = ProxyTranslator::create(library.clone(), &serde_json::json!({ "delay_ms": 100 }))?;
+    let task: TranslateTask = serde_json::from_value(serde_json::json!({
+        "id": "1", "content": "one two three four five", "target_language": "en", "terms": [], "references": [],
+    }))?;
+
+    // 超时丢弃 future 后插件中的翻译仍在进行，释放翻译器不会销毁插件中的句柄
+    assert!(tokio::time::timeout(Duration::from_millis(50), translator.translate(task)).await.is_err());
+    drop(translator);
+    assert_eq!(Arc::strong_count(&library), 2);
+
+    // 翻译结束后释放
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while Arc::strong_count(&library) > 1 {
+        assert!(tokio::time::Instant::now() < deadline, "plugin still held after the translation finished");
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "dylib")]
+#[test]
+fn test_destroy_drains_calls() -> anyhow::Result<()> {
+    use ::lib::ffi::{unwrap_handle_result, FfiResult, TranslateResultFFI, TranslatorHandle};
+    use ::lib::TranslateResult;
+    use std::ffi::{c_void, CString};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    static COMPLETED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn completion(result: *mut FfiResult<TranslateResultFFI>, _user_data: *mut c_void) {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let content = unwrap_handle_result(result).and_then(TranslateResult::from_ffi).map(|r| r.content);
+        COMPLETED.store(content.is_ok_and(|c| c.as_deref() == Some("[en] one two three")), Ordering::SeqCst);
+    }
+
+    let config = CString::new(r#"{"delay_ms":20}"#)?;
+    let translator = unwrap_handle_result(lib::create_translator(config.as_ptr()))? as *mut TranslatorHandle;
+    let task = CString::new(r#"{"id":"1","content":"one two three","target_language":"en","terms":[],"references":[]}"#)?;
+    let status = lib::call_translate_async(translator, task.as_ptr(), completion, std::ptr::null_mut());
+    drop(unsafe { Box::from_raw(unwrap_handle_result(status)?) });
+
+    // 销毁最后一个引用时等待进行中的翻译及其回调结束
+    lib::destroy_translator(translator);
+    assert!(COMPLETED.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_proxy_capabilities() -> anyhow::Result<()> {
+    let path = built_plugin();
+
+    let translator = ProxyTranslator::load(path, serde_json::json!({})).await?;
+    let capabilities = translator.capabilities();
+    assert!(capabilities.supports_streaming);
+    assert!(capabilities.supports_auto_detect);
+    assert!(!capabilities.supports_terms);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_translate_batch() -> anyhow::Result<()> {
+    let path = built_plugin();
+
+    let translator = ProxyTranslator::load(path, serde_json::json!({})).await?;
+    let tasks: Vec<TranslateTask> = serde_json::from_value(serde_json::json!([
+        { "id": "1", "content": "first", "target_language": "en", "terms": [], "references": [] },
+        { "id": "2", "content": "second", "target_language": "ja", "terms": [], "references": [] },
+    ]))?;
+
+    let results = translator.translate_batch(tasks).await?;
+    let contents: Vec<String> = results
+        .into_iter()
+        .map(|r| r.map(|r| r.content.unwrap_or_default()))
+        .collect::<anyhow::Result<_>>()?;
+    assert_eq!(contents, vec!["[en] first", "[ja] second"]);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_ffi_error_kind() -> anyhow::Result<()> {
+    use ::lib::error::XTranslateError;
+    use std::time::Duration;
+
+    let path = built_plugin();
+
+    let rate_limited = serde_json::json!({ "kind": "rate_limited", "retry_after_ms": 1500 });
+    let unavailable = serde_json::json!({ "kind": "provider_error", "code": "503", "message": "unavailable" });
+    let config = serde_json::json!({ "errors": [rate_limited, rate_limited, unavailable] });
+    let translator = ProxyTranslator::load(path, config).await?;
+    let task: TranslateTask = serde_json::from_value(serde_json::json!({
+        "id": "1", "content": "hello", "target_language": "en", "terms": [], "references": [],
+    }))?;
+
+    // 插件返回的错误类别在宿主中还原
+    let expected = XTranslateError::RateLimited {
+        retry_after: Some(Duration::from_millis(1500)),
+    };
+    let err = translator.translate(task.clone()).await.unwrap_err();
+    assert_eq!(XTranslateError::find(&err), Some(&expected));
+    assert!(err.to_string().contains("rate limited"));
+
+    let (tx, _rx) = tokio::sync::mpsc::channel(64);
+    let err = translator.translate_stream(task.clone(), tx).await.unwrap_err();
+    assert_eq!(XTranslateError::find(&err), Some(&expected));
+
+    let results = translator.translate_batch(vec![task.clone()]).await?;
+    let err = results.into_iter().next().unwrap().unwrap_err();
+    assert!(XTranslateError::find(&err).is_some_and(|e| e.is_server_error()));
+    assert!(err.to_string().contains("unavailable"));
+
+    assert_eq!(translator.translate(task).await?.content.as_deref(), Some("[en] hello"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_retry_over_ffi() -> anyhow::Result<()> {
+    use ::lib::retry::{ErrorClass, RetryConfig, RetryPolicy, RetryTranslator};
+
+    let path = built_plugin();
+
+    let fast = RetryPolicy {
+        max_retries: 2,
+        backoff_ms: 1,
+        max_backoff_ms: 5,
+    };
+    let config = RetryConfig {
+        rate_limited: fast.clone(),
+        server: fast.clone(),
+        network: fast.clone(),
+        timeout: RetryPolicy::never(),
+        respect_retry_after: true,
+    };
+    let task: TranslateTask = serde_json::from_value(serde_json::json!({
+        "id": "1", "content": "hello", "target_language": "en", "terms": [], "references": [],
+    }))?;
+
+    // 限流、5xx 与网络错误都会重试
+    let errors = serde_json::json!([
+        { "kind": "rate_limited", "retry_after_ms": 60000 },
+        { "kind": "provider_error", "code": "503", "message": "unavailable" },
+        { "kind": "network", "message": "reset" },
+    ]);
+    let translator = RetryTranslator::wrap(
+        ProxyTranslator::load(path.clone(), serde_json::json!({ "errors": errors })).await?,
+        config.clone(),
+    );
+    assert_eq!(translator.translate(task.clone()).await?.content.as_deref(), Some("[en] hello"));
+
+    // 流式翻译在输出增量之前重试
+    let errors = serde_json::json!([{ "kind": "timeout", "message": "slow" }, { "kind": "network", "message": "reset" }]);
+    let translator = RetryTranslator::wrap(
+        ProxyTranslator::load(path.clone(), serde_json::json!({ "errors": errors })).await?,
+        config.clone(),
+    );
+    let (tx, _rx) = tokio::sync::mpsc::channel(64);
+    let err = translator.translate_stream(task.clone(), tx).await.unwrap_err();
+    assert_eq!(ErrorClass::of(&err), ErrorClass::Timeout);
+    let (tx, mut rx) = tokio::sync::mpsc::channel(64);
+    translator.translate_stream(task.clone(), tx).await?;
+    let mut content = String::new();
+    while let Some(chunk) = rx.recv().await {
+        if let TranslateStreamChunk::Delta(delta) = chunk {
+            content.push_str(delta.content.as_deref().unwrap_or(""));
+        }
+    }
+    assert_eq!(content, "[en] hello");
+
+    // 鉴权失败不重试，只用掉一个错误
+    let errors = serde_json::json!([{ "kind": "auth_failed", "message": "bad key" }, { "kind": "auth_failed", "message": "bad key" }]);
+    let translator = RetryTranslator::wrap(
+        ProxyTranslator::load(path, serde_json::json!({ "errors": errors })).await?,
+        config,
+    );
+    let err = translator.translate(task.clone()).await.unwrap_err();
+    assert_eq!(ErrorClass::of(&err), ErrorClass::Permanent);
+    assert!(translator.inner().translate(task.clone()).await.is_err());
+    assert!(translator.translate(task).await.is_ok());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_circuit_breaker_over_ffi() -> anyhow::Result<()> {
+    use ::lib::circuit::{CircuitBreaker, CircuitConfig, CircuitOpen, CircuitState};
+    use std::time::Duration;
+
+    let path = built_plugin();
+
+    let unavailable = serde_json::json!({ "kind": "provider_error", "code": "503", "message": "unavailable" });
+    let auth = serde_json::json!({ "kind": "auth_failed", "message": "bad key" });
+    let config = serde_json::json!({ "errors": [auth, unavailable, unavailable, unavailable] });
+    let translator = CircuitBreaker::wrap(
+        ProxyTranslator::load(path, config).await?,
+        CircuitConfig {
+            failure_threshold: 2,
+            cooldown_ms: 50,
+            fallback: None,
+        },
+    );
+    let task: TranslateTask = serde_json::from_value(serde_json::json!({
+        "id": "1", "content": "hello", "target_language": "en", "terms": [], "references": [],
+    }))?;
+
+    // 鉴权失败不计入，插件返回的 5xx 连续出现后断开
+    assert!(translator.translate(task.clone()).await.is_err());
+    assert_eq!(translator.state(), CircuitState::Closed);
+    assert!(translator.translate(task.clone()).await.is_err());
+    assert_eq!(translator.state(), CircuitState::Closed);
+    assert!(translator.translate(task.clone()).await.is_err());
+    assert_eq!(translator.state(), CircuitState::Open);
+
+    let err = translator.translate(task.clone()).await.unwrap_err();
+    assert!(CircuitOpen::find(&err).is_some());
+
+    // 试探失败后重新断开，之后的试探成功则恢复
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    let (tx, _rx) = tokio::sync::mpsc::channel(64);
+    assert!(translator.translate_stream(task.clone(), tx).await.is_err());
+    assert_eq!(translator.state(), CircuitState::Open);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(translator.translate(task).await?.content.as_deref(), Some("[en] hello"));
+    assert_eq!(translator.state(), CircuitState::Closed);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_translate_with_progress() -> anyhow::Result<()> {
+    use ::lib::progress::{Progress, ProgressPhase};
+
+    let path = built_plugin();
+
+    let translator = ProxyTranslator::load(path, serde_json::json!({})).await?;
+    let task: TranslateTask = serde_json::from_value(serde_json::json!({
+        "id": "1", "content": "hello", "target_language": "de", "terms": [], "references": [],
+    }))?;
+
+    let phases = Arc::new(std::sync::Mutex::new(vec![]));
+    let collected = phases.clone();
+    let sink = Arc::new(move |p: &Progress| collected.lock().unwrap().push(p.phase));
+    let result = translator.translate_with_progress(task, sink).await?;
+    assert_eq!(result.content.as_deref(), Some("[de] hello"));
+    assert_eq!(*phases.lock().unwrap(), vec![ProgressPhase::Started, ProgressPhase::Finished]);
+
+    Ok(())
+}
+
+/// 与 `lib` 中的导出同时存在，导出名带前缀才不会重名
+#[cfg(all(test, feature = "dylib"))]
+mod prefixed {
+    use crate::translator::DryRunTranslator;
+    use macros::build_ffi;
+
+    build_ffi!("dryrun", DryRunTranslator, prefix = "xtr_dryrun_");
+}
+
+#[cfg(feature = "dylib")]
+#[test]
+fn test_export_prefix() -> anyhow::Result<()> {
+    use std::ffi::c_char;
+
+    extern "C" {
+        #[link_name = "xtr_dryrun_get_plugin_name"]
+        fn prefixed_plugin_name() -> *mut c_char;
+    }
+
+    let name = unsafe { prefixed_plugin_name() };
+    assert_eq!(unsafe { std::ffi::CStr::from_ptr(name) }.to_str()?, "dryrun");
+    prefixed::free_string(name);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_factory_unload() -> anyhow::Result<()> {
+    let path = built_plugin();
+
+    let factory = ProxyTranslatorFactory::new([("dryrun".to_string(), path.clone())].into());
+    let translator = factory.create("dryrun", serde_json::json!({})).await?;
+    assert!(translator.is_supported_input_language("en".to_string())?);
+
+    // 卸载后已创建的翻译器不再可用
+    factory.unload("dryrun").await?;
+    let err = translator.is_supported_input_language("en".to_string()).unwrap_err();
+    assert_eq!(err.to_string(), "plugin unloaded");
+    assert!(factory.create("dryrun", serde_json::json!({})).await.is_err());
+    assert!(factory.unload("dryrun").await.is_err());
+
+    let factory = ProxyTranslatorFactory::new([("dryrun".to_string(), path)].into());
+    let translator = factory.create("dryrun", serde_json::json!({})).await?;
+    factory.reload("dryrun").await?;
+    assert!(translator.get_supported_pairs().is_err());
+    let translator = factory.create("dryrun", serde_json::json!({})).await?;
+    assert!(translator.is_supported_output_language("zh-CN".to_string())?);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_unload_timeout() -> anyhow::Result<()> {
+    use std::time::Duration;
+
+    let path = built_plugin();
+
+    let task: TranslateTask = serde_json::from_value(serde_json::json!({
+        "id": "1",
+        "content": "one two three four five",
+        "terms": [],
+        "references": [],
+    }))?;
+
+    // 超时后取消进行中的流式翻译
+    let registry = PluginRegistry::new([("dryrun".to_string(), path.clone())].into());
+    let translator = registry.create("dryrun", serde_json::json!({ "delay_ms": 200 })).await?;
+    let (tx, _rx) = tokio::sync::mpsc::channel(64);
+    let stream_task = task.clone();
+    let stream = tokio::spawn(async move { translator.translate_stream(stream_task, tx).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    registry.unload_timeout("dryrun", Duration::from_millis(50)).await?;
+    stream.await??;
+
+    // 宿主不再读取增量时调用无法结束，卸载返回错误而不是一直等待
+    let registry = PluginRegistry::new([("dryrun".to_string(), path.clone())].into());
+    let translator = registry.create("dryrun", serde_json::json!({ "delay_ms": 1 })).await?;
+    let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+    let stream_task = task.clone();
+    let stream = tokio::spawn(async move { translator.translate_stream(stream_task, tx).await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let err = registry.unload_timeout("dryrun", Duration::from_millis(50)).await.unwrap_err();
+    assert!(err.to_string().contains("still in use"));
+
+    while rx.recv().await.is_some() {}
+    stream.await??;
+
+    // 宿主的 future 与翻译器都已释放时，卸载仍等待插件中的翻译结束
+    let registry = PluginRegistry::new([("dryrun".to_string(), path)].into());
+    let library = registry.library("dryrun")?;
+    let translator = registry.create("dryrun", serde_json::json!({ "delay_ms": 100 })).await?;
+    let translate_task = TranslateTask {
+        target_language: Some("en".parse()?),
+        ..task
+    };
+    assert!(tokio::time::timeout(Duration::from_millis(50), translator.translate(translate_task)).await.is_err());
+    drop(translator);
+    registry.unload_timeout("dryrun", Duration::from_secs(5)).await?;
+    assert_eq!(Arc::strong_count(&library), 1);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_shutdown_translator() -> anyhow::Result<()> {
+    let path = built_plugin();
+
+    let task: TranslateTask = serde_json::from_value(serde_json::json!({
+        "id": "1",
+        "content": "one two three four five",
+        "terms": [],
+        "references": [],
+    }))?;
+
+    // 没有进行中的调用时立即返回
+    let idle = ProxyTranslator::load(path.clone(), serde_json::json!({})).await?;
+    assert!(!idle.shutdown(std::time::Duration::from_secs(1)).await?);
+    assert!(idle.translate(task.clone()).await.is_err());
+
+    let translator = Arc::new(ProxyTranslator::load(path, serde_json::json!({ "delay_ms": 200 })).await?);
+    let (tx, mut rx) = tokio::sync::mpsc::channel(64);
+    let streaming = translator.clone();
+    let stream_task = task.clone();
+    let stream = tokio::spawn(async move { streaming.translate_stream(stream_task, tx).await });
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+    assert!(translator.shutdown(std::time::Duration::from_millis(50)).await?);
+    stream.await??;
+
+    let mut chunks = vec![];
+    while let Some(chunk) = rx.recv().await {
+        chunks.push(chunk);
+    }
+    assert!(matches!(chunks.first(), Some(TranslateStreamChunk::Start)));
+    assert!(matches!(chunks.last(), Some(TranslateStreamChunk::End)));
+    assert!(chunks.len() < 7);
+
+    // 关闭后拒绝新的翻译，语言查询不受影响
+    let err = translator.translate(task).await.unwrap_err();
+    assert!(err.to_string().contains("shutting down"));
+    assert!(translator.get_supported_pairs().is_ok());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_plugin_registry() -> anyhow::Result<()> {
+    let path = built_plugin();
+
+    let dir = std::path::Path::new(&path).parent().unwrap().to_string_lossy().into_owned();
+    let registry = PluginRegistry::scan(dir)?;
+    let plugins = registry.list();
+    let info = plugins.iter().find(|info| info.name == "dryrun").unwrap();
+    assert_eq!(info.version.as_deref(), Some(env!("CARGO_PKG_VERSION")));
+    assert_eq!(info.input_languages, Some(vec!["*".to_string()]));
+    assert!(info.capabilities.is_some());
+    assert_eq!(info.translators, vec!["dryrun", "dryrun_pseudo", "dryrun_strict"]);
+
+    // 必填配置的翻译器无法以空配置创建，语言与能力来自插件导出的 `StaticInfo`
+    let (_, strict) = info.translator_info.iter().find(|(name, _)| name == "dryrun_strict").unwrap();
+    assert_eq!(strict.output_languages, Some(vec!["*".to_string()]));
+    assert!(strict.capabilities.as_ref().is_some_and(|c| c.supports_streaming));
+    assert!(registry.create("dryrun_strict", serde_json::json!({})).await.is_err());
+    let marked = registry.create("dryrun_strict", serde_json::json!({ "marker": "> " })).await?;
+    assert!(marked.capabilities().supports_streaming);
+    drop(marked);
+
+    // 同一动态库中的其他翻译器按名称创建
+    let pseudo = registry.create("dryrun_pseudo", serde_json::json!({ "expansion": 0.0 })).await?;
+    let task: TranslateTask = serde_json::from_value(serde_json::json!({
+        "id": "1",
+        "content": "Hello",
+        "target_language": "zh-CN",
+        "terms": [],
+        "references": [],
+    }))?;
+    assert_eq!(pseudo.translate(task).await?.content.as_deref(), Some("Hélló"));
+    assert_eq!(registry.list().iter().filter(|i| i.path == info.path).count(), 1);
+    drop(pseudo);
+
+    // 同一插件的翻译器共享发现时打开的动态库
+    let library = registry.library("dryrun")?;
+    assert!(Arc::ptr_eq(&library, &info.library));
+    let opened = Arc::strong_count(&library);
+    let first = registry.create("dryrun", serde_json::json!({})).await?;
+    let second = registry.create("dryrun", serde_json::json!({ "prefix": "" })).await?;
+    assert_eq!(Arc::strong_count(&library), opened + 2);
+    assert!(registry.config_schema("dryrun")?.is_some());
+
+    drop((first, second));
+    assert_eq!(Arc::strong_count(&library), opened);
+
+    // 重新加载只替换该名称的登记，同一目录中的其他翻译器仍使用发现时打开的动态库
+    registry.reload("dryrun").await?;
+    assert!(Arc::ptr_eq(&registry.library("dryrun_pseudo")?, &info.library));
+    let shadow = registry.library("dryrun")?.path().to_string();
+    assert_ne!(shadow, info.path);
+    drop(registry);
+    assert!(!std::path::Path::new(&shadow).exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_manifest_discovery() -> anyhow::Result<()> {
+    let path = built_plugin();
+
+    let dir = std::env::temp_dir().join(format!("xtranslator-manifest-scan-{}", std::process::id()));
+    let (compatible, incompatible) = (dir.join("compatible"), dir.join("incompatible"));
+    let file = std::path::Path::new(&path).file_name().unwrap();
+    for (sub, min_abi_version) in [(&compatible, 1), (&incompatible, 99)] {
+        std::fs::create_dir_all(sub)?;
+        std::fs::copy(&path, sub.join(file))?;
+        std::fs::write(
+            sub.join("plugin.toml"),
+            format!(
+                "name = \"dryrun\"\ntranslators = [\"dryrun\", \"dryrun_pseudo\"]\nconfig_schema = \"schema.json\"\nmin_abi_version = {}\n",
+                min_abi_version
+            ),
+        )?;
+        std::fs::write(sub.join("schema.json"), r#"{"type":"object"}"#)?;
+    }
+
+    // 不兼容的插件不会被登记
+    assert!(PluginRegistry::scan(incompatible.to_string_lossy().into_owned())?.names().is_empty());
+    assert!(::lib::ffi_proxy::load_translators(incompatible.to_string_lossy().into_owned())?.is_empty());
+
+    let registry = PluginRegistry::scan(compatible.to_string_lossy().into_owned())?;
+    assert_eq!(registry.names(), vec!["dryrun", "dryrun_pseudo"]);
+    assert_eq!(registry.manifest("dryrun_pseudo").map(|m| m.name), Some("dryrun".to_string()));
+    assert_eq!(registry.config_schema("dryrun")?, Some(serde_json::json!({ "type": "object" })));
+
+    // 动态库在创建翻译器时打开
+    let translator = registry.create("dryrun", serde_json::json!({})).await?;
+    let task: TranslateTask = serde_json::from_value(serde_json::json!({
+        "id": "1",
+        "content": "你好",
+        "target_language": "en",
+        "terms": [],
+        "references": [],
+    }))?;
+    assert_eq!(translator.translate(task).await?.content.as_deref(), Some("[en] 你好"));
+    drop(translator);
+
+    registry.unload("dryrun").await?;
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
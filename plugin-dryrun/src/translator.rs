@@ -0,0 +1,417 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use lib::error::XTranslateError;
+use lib::request::ensure_request_id;
+use lib::utils::{schema_of, stream2normal};
+#[cfg(test)]
+use lib::utils::{test_translate, test_translate_stream};
+use lib::validate::{ConfigIssue, ConfigValidator};
+use lib::{Capabilities, StaticInfo, TranslateResult, TranslateStreamChunk, TranslateTask, Translator, Usage};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DryRunMode {
+    /// 原文前后加上标记
+    #[default]
+    Wrap,
+    /// 伪本地化：字母替换为带重音的字符并按比例加长，用于检查界面布局
+    Pseudo,
+}
+
+/// 不发送任何请求的翻译器，用于宿主程序的集成测试
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct DryRunTranslator {
+    /// 输出模式
+    pub mode: DryRunMode,
+    /// 加在译文前的标记，`{lang}` 替换为目标语言
+    pub prefix: String,
+    /// 加在译文后的标记
+    pub suffix: String,
+    /// 伪本地化时加长的比例，如 `0.3` 表示加长 30%
+    pub expansion: f32,
+    /// 流式输出时每个词之前等待的时间（毫秒），模拟网络延迟
+    pub delay_ms: Option<u64>,
+    /// 依次作为前几次翻译的错误返回，之后正常翻译，用于测试宿主的重试与熔断。
+    /// 格式为 `XTranslateError` 的 JSON 形式，如 `{"kind": "rate_limited", "retry_after_ms": 100}`
+    #[schemars(with = "Vec<Value>")]
+    pub errors: Vec<XTranslateError>,
+    #[serde(skip)]
+    calls: Arc<AtomicUsize>,
+}
+
+impl Default for DryRunTranslator {
+    fn default() -> Self {
+        DryRunTranslator {
+            mode: DryRunMode::Wrap,
+            prefix: "[{lang}] ".to_string(),
+            suffix: String::new(),
+            expansion: 0.3,
+            delay_ms: None,
+            errors: vec![],
+            calls: Arc::default(),
+        }
+    }
+}
+
+/// 带重音的替换字符，保持可读
+fn accent(c: char) -> char {
+    match c {
+        'a' => 'á',
+        'c' => 'ç',
+        'e' => 'é',
+        'i' => 'í',
+        'n' => 'ñ',
+        'o' => 'ó',
+        'u' => 'ú',
+        'y' => 'ý',
+        'A' => 'Å',
+        'C' => 'Ç',
+        'E' => 'É',
+        'I' => 'Î',
+        'N' => 'Ñ',
+        'O' => 'Ö',
+        'U' => 'Ü',
+        'Y' => 'Ý',
+        c => c,
+    }
+}
+
+/// 替换为带重音的字符并加长，`{name}` 等占位符与 `<b>` 等标签保持不变
+pub fn pseudo_localize(text: &str, expansion: f32) -> String {
+    let mut result = String::with_capacity(text.len() * 2);
+    let mut closing = None;
+
+    for c in text.chars() {
+        match closing {
+            Some(end) => {
+                if c == end {
+                    closing = None;
+                }
+                result.push(c);
+            }
+            None => {
+                closing = match c {
+                    '{' => Some('}'),
+                    '<' => Some('>'),
+                    _ => None,
+                };
+                result.push(accent(c));
+            }
+        }
+    }
+
+    let padding = (text.chars().count() as f32 * expansion.max(0.0)).ceil() as usize;
+    if padding > 0 {
+        result.push(' ');
+        result.extend(std::iter::repeat_n('~', padding));
+    }
+
+    result
+}
+
+impl DryRunTranslator {
+    fn render(&self, task: &TranslateTask) -> String {
+        let lang = task
+            .target_language
+            .as_ref()
+            .map(|tag| tag.to_string())
+            .unwrap_or_default();
+
+        let body = match self.mode {
+            DryRunMode::Wrap => task.content.clone(),
+            DryRunMode::Pseudo => pseudo_localize(&task.content, self.expansion),
+        };
+
+        format!(
+            "{}{}{}",
+            self.prefix.replace("{lang}", &lang),
+            body,
+            self.suffix.replace("{lang}", &lang)
+        )
+    }
+}
+
+#[async_trait]
+impl Translator for DryRunTranslator {
+    type This = Self;
+
+    async fn new(config: Value) -> Result<Self> {
+        serde_json::from_value(config)
+            .map_err(|e| anyhow!(XTranslateError::InvalidConfig(e.to_string())))
+    }
+
+    fn config_schema() -> Option<Value> {
+        Some(schema_of::<Self>())
+    }
+
+    fn validate_config(config: &Value) -> Result<Vec<ConfigIssue>> {
+        let mut validator = ConfigValidator::new(config);
+        if config.get("mode").is_some() {
+            validator.one_of("mode", &["wrap", "pseudo"]);
+        }
+        validator.optional_str("prefix");
+        validator.optional_str("suffix");
+        if config["expansion"].as_f64().is_some_and(|e| e < 0.0) {
+            validator.issue("expansion", "must not be negative");
+        }
+        Ok(validator.finish())
+    }
+
+    fn static_info() -> StaticInfo {
+        StaticInfo {
+            input_languages: Some(vec!["*".to_string()]),
+            output_languages: Some(vec!["*".to_string()]),
+            capabilities: Some(Capabilities {
+                supports_streaming: true,
+                supports_auto_detect: true,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        Ok(vec!["*".to_string()])
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        Ok(vec!["*".to_string()])
+    }
+
+    fn is_supported_input_language(&self, _lang: String) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn is_supported_output_language(&self, _lang: String) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Self::static_info().capabilities.unwrap_or_default()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        stream2normal(self, task).await
+    }
+
+    async fn translate_stream(
+        &self,
+        mut task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        if let Some(err) = self.errors.get(self.calls.fetch_add(1, Ordering::SeqCst)) {
+            return Err(err.clone().into());
+        }
+
+        ensure_request_id(&mut task);
+        let content = self.render(&task);
+
+        sender.send(TranslateStreamChunk::Start).await?;
+        for word in content.split_inclusive(' ') {
+            if let Some(delay) = self.delay_ms {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+            }
+            sender
+                .send(TranslateStreamChunk::Delta(TranslateResult {
+                    content: Some(word.to_string()),
+                    request_id: task.request_id.clone(),
+                    ..Default::default()
+                }))
+                .await?;
+        }
+
+        sender
+            .send(TranslateStreamChunk::Delta(TranslateResult {
+                usage: Some(Usage {
+                    characters: task.content.chars().count() as u64,
+                    ..Default::default()
+                }),
+                request_id: task.request_id.clone(),
+                ..Default::default()
+            }))
+            .await?;
+        sender.send(TranslateStreamChunk::End).await?;
+
+        Ok(())
+    }
+}
+
+/// 默认使用伪本地化且不加标记的 `DryRunTranslator`
+pub struct PseudoTranslator(DryRunTranslator);
+
+#[async_trait]
+impl Translator for PseudoTranslator {
+    type This = Self;
+
+    async fn new(mut config: Value) -> Result<Self> {
+        if let Some(config) = config.as_object_mut() {
+            config.entry("mode").or_insert("pseudo".into());
+            config.entry("prefix").or_insert("".into());
+        }
+
+        Ok(PseudoTranslator(DryRunTranslator::new(config).await?))
+    }
+
+    fn config_schema() -> Option<Value> {
+        DryRunTranslator::config_schema()
+    }
+
+    fn validate_config(config: &Value) -> Result<Vec<ConfigIssue>> {
+        DryRunTranslator::validate_config(config)
+    }
+
+    fn static_info() -> StaticInfo {
+        DryRunTranslator::static_info()
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.0.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.0.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.0.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.0.is_supported_output_language(lang)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.0.capabilities()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        self.0.translate(task).await
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        self.0.translate_stream(task, sender).await
+    }
+}
+
+/// `marker` 为必填配置的 `DryRunTranslator`，无法以空配置创建，用于测试发现插件
+pub struct StrictTranslator(DryRunTranslator);
+
+#[derive(Deserialize)]
+struct StrictConfig {
+    /// 加在译文前的标记
+    marker: String,
+}
+
+#[async_trait]
+impl Translator for StrictTranslator {
+    type This = Self;
+
+    async fn new(config: Value) -> Result<Self> {
+        let strict: StrictConfig = serde_json::from_value(config)
+            .map_err(|e| anyhow!(XTranslateError::InvalidConfig(e.to_string())))?;
+
+        Ok(StrictTranslator(DryRunTranslator {
+            prefix: strict.marker,
+            ..Default::default()
+        }))
+    }
+
+    fn validate_config(config: &Value) -> Result<Vec<ConfigIssue>> {
+        let mut validator = ConfigValidator::new(config);
+        validator.require_str("marker");
+        Ok(validator.finish())
+    }
+
+    fn static_info() -> StaticInfo {
+        DryRunTranslator::static_info()
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>> {
+        self.0.get_supported_input_languages()
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>> {
+        self.0.get_supported_output_languages()
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool> {
+        self.0.is_supported_input_language(lang)
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool> {
+        self.0.is_supported_output_language(lang)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.0.capabilities()
+    }
+
+    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        self.0.translate(task).await
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        self.0.translate_stream(task, sender).await
+    }
+}
+
+#[test]
+fn test_pseudo_localize() {
+    assert_eq!(pseudo_localize("Hello", 0.0), "Hélló");
+    assert_eq!(
+        pseudo_localize("Hi {name}, <b>done</b>", 0.0),
+        "Hí {name}, <b>dóñé</b>"
+    );
+    assert_eq!(pseudo_localize("Save", 0.5), "Sávé ~~");
+}
+
+#[tokio::test]
+async fn test_dryrun() -> Result<()> {
+    let translator = DryRunTranslator::new(serde_json::json!({})).await?;
+    let task = TranslateTask {
+        id: "123456".to_string(),
+        content: "Hello world".to_string(),
+        source_language: None,
+        target_language: Some("zh-CN".parse()?),
+        user_prompt: None,
+        system_prompt: None,
+        field: None,
+        terms: vec![],
+        references: vec![],
+        extra: None,
+        request_id: Some("req-1".to_string()),
+        deadline_ms: None,
+    };
+
+    let result = translator.translate(task.clone()).await?;
+    assert_eq!(result.content.as_deref(), Some("[zh-CN] Hello world"));
+    assert_eq!(result.request_id.as_deref(), Some("req-1"));
+    assert_eq!(result.usage.map(|u| u.characters), Some(11));
+
+    let translator = DryRunTranslator {
+        mode: DryRunMode::Pseudo,
+        prefix: "[".to_string(),
+        suffix: "]".to_string(),
+        ..Default::default()
+    };
+    let result = translator.translate(task).await?;
+    assert_eq!(result.content.as_deref(), Some("[Hélló wórld ~~~~]"));
+
+    test_translate(DryRunTranslator::default()).await?;
+    test_translate_stream(DryRunTranslator::default()).await
+}
@@ -2,17 +2,31 @@ use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
 use hmac::{Hmac, Mac};
 use language_tags::LanguageTag;
-use lib::utils::normal2stream;
+use lib::credential::{CommandCredentials, CredentialCache, CredentialProvider};
+use lib::error::XTranslateError;
+use lib::http::{execute, HttpConfig};
+use lib::langmap::LangMap;
+use lib::limit::with_limit;
+use lib::request::{ensure_request_id, with_request_id};
+use lib::terms::select_terms;
+#[cfg(feature = "tracing")]
+use lib::trace::language_pair;
+use lib::trace::record_usage;
+use lib::utils::{normal2stream, schema_of, with_deadline};
 #[cfg(test)]
 use lib::utils::{test_translate, test_translate_stream};
-use lib::{TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use lib::validate::{ConfigIssue, ConfigValidator};
+use lib::{Capabilities, StaticInfo, TranslateResult, TranslateStreamChunk, TranslateTask, Translator, Usage};
 use reqwest::Request;
-use reqwest::{Client, IntoUrl, RequestBuilder};
+use reqwest::{IntoUrl, RequestBuilder};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
 use std::cmp::{min, Ordering};
 use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+use std::time::Instant;
 use tokio::sync::mpsc::Sender;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -103,6 +117,10 @@ impl TencentCloudRequest {
         Ok(request)
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", skip_all, err, fields(action = %self.action))
+    )]
     fn sign(&self, request: &mut Request) -> Result<()> {
         let now = chrono::Utc::now();
         let timestamp = now.timestamp();
@@ -262,11 +280,28 @@ impl TencentCloudRequest {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct TencentCloudResponseInnerError {
     #[serde(rename = "Code")]
-    pub code: i64,
+    pub code: String,
     #[serde(rename = "Message")]
     pub message: String,
 }
 
+impl TencentCloudResponseInnerError {
+    /// 按腾讯云公共错误码分类
+    fn into_error(self) -> XTranslateError {
+        let code = self.code.as_str();
+        if code.starts_with("AuthFailure") {
+            XTranslateError::AuthFailed(self.message)
+        } else if code.starts_with("RequestLimitExceeded") || code.starts_with("LimitExceeded") {
+            XTranslateError::RateLimited { retry_after: None }
+        } else {
+            XTranslateError::ProviderError {
+                code: self.code,
+                message: self.message,
+            }
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct TencentCloudResponseInner {
     #[serde(rename = "RequestId")]
@@ -301,7 +336,7 @@ impl RequestMethod {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum HunyuanTranslationModel {
     #[serde(rename = "hunyuan-translation")]
     HunyuanTranslation,
@@ -316,7 +351,10 @@ impl TryFrom<String> for HunyuanTranslationModel {
         match value.as_str() {
             "hunyuan-translation" => Ok(HunyuanTranslationModel::HunyuanTranslation),
             "hunyuan-translation-lite" => Ok(HunyuanTranslationModel::HunyuanTranslationLite),
-            _ => Err(anyhow!("Invalid model: {}", value)),
+            _ => Err(anyhow!(XTranslateError::InvalidConfig(format!(
+                "invalid model: {}",
+                value
+            )))),
         }
     }
 }
@@ -332,7 +370,7 @@ impl Display for HunyuanTranslationModel {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum HunyuanTransLanguages {
     ///简体中文
     Zh,
@@ -376,41 +414,83 @@ impl Display for HunyuanTransLanguages {
     }
 }
 
+static LANGUAGES: LazyLock<LangMap<HunyuanTransLanguages>> = LazyLock::new(|| {
+    LangMap::new()
+        .lang("zh", HunyuanTransLanguages::Zh)
+        .lang("yue", HunyuanTransLanguages::Yue)
+        .lang("en", HunyuanTransLanguages::En)
+        .lang("fr", HunyuanTransLanguages::Fr)
+        .lang("pt", HunyuanTransLanguages::Pt)
+        .lang("es", HunyuanTransLanguages::Es)
+        .lang("ja", HunyuanTransLanguages::Ja)
+        .lang("tr", HunyuanTransLanguages::Tr)
+        .lang("ru", HunyuanTransLanguages::Ru)
+        .lang("ar", HunyuanTransLanguages::Ar)
+        .lang("ko", HunyuanTransLanguages::Ko)
+        .lang("th", HunyuanTransLanguages::Th)
+        .lang("it", HunyuanTransLanguages::It)
+        .lang("de", HunyuanTransLanguages::De)
+        .lang("vi", HunyuanTransLanguages::Vi)
+        .lang("ms", HunyuanTransLanguages::Ms)
+        .lang("id", HunyuanTransLanguages::Id)
+});
+
 impl TryFrom<LanguageTag> for HunyuanTransLanguages {
     type Error = anyhow::Error;
 
     fn try_from(tag: LanguageTag) -> Result<Self, Self::Error> {
-        let primary = tag.primary_language().to_ascii_lowercase();
-        let primary = primary.as_str();
-        match primary {
-            "zh" => Ok(Self::Zh),
-            "yue" => Ok(Self::Yue),
-            "en" => Ok(Self::En),
-            "fr" => Ok(Self::Fr),
-            "pt" => Ok(Self::Pt),
-            "es" => Ok(Self::Es),
-            "ja" => Ok(Self::Ja),
-            "tr" => Ok(Self::Tr),
-            "ru" => Ok(Self::Ru),
-            "ar" => Ok(Self::Ar),
-            "ko" => Ok(Self::Ko),
-            "th" => Ok(Self::Th),
-            "it" => Ok(Self::It),
-            "de" => Ok(Self::De),
-            "vi" => Ok(Self::Vi),
-            "ms" => Ok(Self::Ms),
-            "id" => Ok(Self::Id),
-            _ => bail!("Unsupported language tag: {}", tag),
-        }
+        LANGUAGES.map(&tag)
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct HunyuanTranslator {
+    /// 模型名称
     pub model: HunyuanTranslationModel,
+    /// 腾讯云 SecretId，设置了 `credential_process` 时可省略
+    #[serde(default)]
     pub secret_id: String,
+    /// 腾讯云 SecretKey
+    #[serde(default)]
     pub secret_key: String,
+    /// 临时密钥的 Token
+    #[serde(default)]
+    pub token: Option<String>,
+    /// 获取临时密钥的命令，输出 `credential::Credential` 格式的 JSON，过期前或鉴权失败时重新执行
+    #[serde(default)]
+    pub credential_process: Option<Vec<String>>,
+    /// 地域，如 `ap-guangzhou`
     pub region: Option<String>,
+    #[serde(skip)]
+    credentials: Option<CredentialCache>,
+    #[serde(flatten, default)]
+    pub http: HttpConfig,
+}
+
+impl HunyuanTranslator {
+    /// 从 `provider` 获取临时密钥，替代配置中的密钥
+    pub fn with_credentials(mut self, provider: impl CredentialProvider + 'static) -> Self {
+        self.credentials = Some(CredentialCache::new(provider));
+        self
+    }
+
+    async fn credential(&self) -> Result<TencentCredential> {
+        match &self.credentials {
+            Some(cache) => {
+                let credential = cache.get().await?;
+                Ok(TencentCredential {
+                    secret_id: credential.id,
+                    secret_key: credential.secret,
+                    token: credential.token,
+                })
+            }
+            None => Ok(TencentCredential {
+                secret_id: self.secret_id.clone(),
+                secret_key: self.secret_key.clone(),
+                token: self.token.clone(),
+            }),
+        }
+    }
 }
 
 impl HunyuanTranslator {
@@ -446,9 +526,9 @@ impl HunyuanTranslator {
 
         let mut references = vec![];
 
+        // 最多 10 条，优先使用原文中出现的术语
         if task.terms.len() > 0 {
-            let mut list = task.terms[0..min(task.terms.len(), 10)]
-                .to_vec()
+            let mut list = select_terms(&task.content, &task.terms, Some(10))
                 .iter()
                 .map(|i| {
                     json!({
@@ -508,7 +588,56 @@ impl Translator for HunyuanTranslator {
     type This = Self;
 
     async fn new(config: Value) -> Result<Self> {
-        serde_json::from_value(config).map_err(|e| anyhow!(e))
+        let translator: HunyuanTranslator = serde_json::from_value(config)
+            .map_err(|e| anyhow!(XTranslateError::InvalidConfig(e.to_string())))?;
+
+        match translator.credential_process.clone() {
+            Some(command) => Ok(translator.with_credentials(CommandCredentials { command })),
+            None => Ok(translator),
+        }
+    }
+
+    fn config_schema() -> Option<Value> {
+        Some(schema_of::<Self>())
+    }
+
+    fn validate_config(config: &Value) -> Result<Vec<ConfigIssue>> {
+        let mut validator = ConfigValidator::new(config);
+        validator.one_of(
+            "model",
+            &["hunyuan-translation", "hunyuan-translation-lite"],
+        );
+        // 临时密钥由 `credential_process` 提供
+        if config.get("credential_process").is_none() {
+            if let Some(secret_id) = validator.require_str("secret_id") {
+                if !secret_id.starts_with("AKID") {
+                    validator.issue("secret_id", "SecretId should start with `AKID`");
+                }
+            }
+            validator.require_str("secret_key");
+        }
+        validator.optional_str("token");
+        validator.optional_str("region");
+        validator.http();
+        Ok(validator.finish())
+    }
+
+    fn static_info() -> StaticInfo {
+        StaticInfo {
+            input_languages: HunyuanTranslator::lang_list().ok(),
+            output_languages: HunyuanTranslator::lang_list().ok(),
+            capabilities: Some(Capabilities {
+                supports_terms: true,
+                max_terms: Some(10),
+                supports_references: true,
+                max_references: Some(10),
+                supports_field: true,
+                supports_auto_detect: true,
+                needs_target_language: true,
+                max_input_chars: Some(5000),
+                ..Default::default()
+            }),
+        }
     }
 
     fn get_supported_input_languages(&self) -> Result<Vec<String>> {
@@ -527,8 +656,63 @@ impl Translator for HunyuanTranslator {
         Ok(HunyuanTransLanguages::try_from(LanguageTag::parse(lang.as_str())?).is_ok())
     }
 
-    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
-        let client = Client::new();
+    fn capabilities(&self) -> Capabilities {
+        Self::static_info().capabilities.unwrap_or_default()
+    }
+
+    async fn translate(&self, mut task: TranslateTask) -> Result<TranslateResult> {
+        let request_id = ensure_request_id(&mut task);
+        with_request_id(
+            &request_id,
+            with_deadline(
+                self.http.deadline(),
+                with_limit(
+                    "hunyuan",
+                    self.http.max_concurrency,
+                    self.do_translate(task),
+                ),
+            ),
+        )
+        .await
+    }
+
+    async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        normal2stream(self, task, sender).await
+    }
+}
+
+impl HunyuanTranslator {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "translate",
+            skip_all,
+            err,
+            fields(provider = "hunyuan", model = %self.model, languages = %language_pair(&task))
+        )
+    )]
+    async fn do_translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        match (self.send(&task).await, &self.credentials) {
+            // 临时密钥提前失效时刷新后重试一次
+            (Err(e), Some(cache))
+                if matches!(
+                    XTranslateError::find(&e),
+                    Some(XTranslateError::AuthFailed(_))
+                ) =>
+            {
+                cache.invalidate().await;
+                self.send(&task).await
+            }
+            (result, _) => result,
+        }
+    }
+
+    async fn send(&self, task: &TranslateTask) -> Result<TranslateResult> {
+        let client = self.http.build_client()?;
 
         let tencent_request = TencentCloudRequest {
             host: "hunyuan.tencentcloudapi.com".to_string(),
@@ -537,44 +721,46 @@ impl Translator for HunyuanTranslator {
             region: self.region.clone(),
             version: "2023-09-01".to_string(),
             language: None,
-            credential: TencentCredential {
-                secret_id: self.secret_id.clone(),
-                secret_key: self.secret_key.clone(),
-                token: None,
-            },
+            credential: self.credential().await?,
             query: None,
-            body: Some(self.build_request(&task, false)?),
+            body: Some(self.build_request(task, false)?),
         };
 
         let req = tencent_request.build_request(&client).unwrap();
-        let resp = client.execute(req).await.map_err(|e| anyhow!(e))?;
-        let json = resp.text().await.map_err(|e| anyhow!(e))?;
+        let start = Instant::now();
+        let resp = execute(&client, req).await?;
+        let json = resp.text().await.map_err(XTranslateError::from)?;
 
         let obj = serde_json::from_str::<TencentCloudResponse>(json.as_str())?;
 
         if !obj.is_success() {
-            bail!("请求失败: {:?}", obj.response.error);
+            bail!(obj.response.error.unwrap().into_error());
         }
 
+        let request_id = obj.response.request_id.clone();
         let data = obj.response.data.ok_or(anyhow!("数据解析失败"))?;
 
         let content = data["Choices"][0]["Message"]["Content"]
             .as_str()
             .map(|s| s.to_string());
 
+        let usage = Usage {
+            prompt_tokens: data["Usage"]["PromptTokens"].as_u64().unwrap_or(0),
+            completion_tokens: data["Usage"]["CompletionTokens"].as_u64().unwrap_or(0),
+            latency_ms: Some(start.elapsed().as_millis() as u64),
+            request_id: Some(request_id),
+            ..Default::default()
+        };
+        record_usage(&usage);
+
         Ok(TranslateResult {
             reasoning: None,
             content,
+            usage: Some(usage),
+            request_id: task.request_id.clone(),
+            ..Default::default()
         })
     }
-
-    async fn translate_stream(
-        &self,
-        task: TranslateTask,
-        sender: Sender<TranslateStreamChunk>,
-    ) -> Result<()> {
-        normal2stream(self, task, sender).await
-    }
 }
 
 #[tokio::test]
@@ -583,7 +769,11 @@ async fn test_hunyuan() -> Result<()> {
         model: HunyuanTranslationModel::HunyuanTranslation,
         secret_id: env!("HUNYUAN_SECRET_ID").to_string(),
         secret_key: env!("HUNYUAN_SECRET_KEY").to_string(),
+        token: None,
+        credential_process: None,
         region: None,
+        credentials: None,
+        http: Default::default(),
     };
 
     test_translate(translator).await
@@ -595,7 +785,11 @@ async fn test_hunyuan_stream() -> Result<()> {
         model: HunyuanTranslationModel::HunyuanTranslation,
         secret_id: env!("HUNYUAN_SECRET_ID").to_string(),
         secret_key: env!("HUNYUAN_SECRET_KEY").to_string(),
+        token: None,
+        credential_process: None,
         region: None,
+        credentials: None,
+        http: Default::default(),
     };
 
     test_translate_stream(translator).await
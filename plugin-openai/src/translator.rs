@@ -1,30 +1,107 @@
 use anyhow::{anyhow, bail, Result};
 use async_openai::config::OpenAIConfig;
+use async_openai::error::OpenAIError;
 use async_openai::types::{
     ChatCompletionRequestMessage, CreateChatCompletionRequest, CreateChatCompletionRequestArgs,
 };
 use async_openai::Client;
 use async_trait::async_trait;
 use futures_util::StreamExt;
-use lib::utils::format_messages;
+use lib::cleanup::{clean_output, CleanupConfig};
+use lib::error::XTranslateError;
+use lib::fewshot::{select_examples, FewShotConfig};
+use lib::http::HttpConfig;
+use lib::limit::with_limit;
+use lib::options::ProviderOptions;
+use lib::preset::{default_preset, preset, preset_names, Preset};
+use lib::request::{ensure_request_id, with_request_id};
+#[cfg(feature = "tracing")]
+use lib::trace::language_pair;
+use lib::trace::record_usage;
+use lib::utils::{
+    format_messages, openai_confidence, openai_usage, schema_of, split_think, with_deadline,
+    ThinkParser,
+};
 #[cfg(test)]
 use lib::utils::{test_translate, test_translate_stream};
-use lib::{TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use lib::validate::{ConfigIssue, ConfigValidator};
+use lib::{Capabilities, StaticInfo, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::time::Instant;
 use tokio::sync::mpsc::Sender;
 
 #[repr(C)]
-#[derive(Debug, Serialize, Deserialize)]
+/// 任务参数，读取自 `task.extra["openai"]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OpenAIOptions {
+    /// 采样温度
+    pub temperature: Option<f32>,
+    /// 核采样概率
+    pub top_p: Option<f32>,
+}
+
+impl ProviderOptions for OpenAIOptions {
+    const NAMESPACE: &'static str = "openai";
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct OpenAITranslator {
+    /// 模型名称
     pub model: String,
+    /// 系统提示词模板
     pub system_prompt: Option<String>,
+    /// 用户提示词模板
     pub user_prompt: Option<String>,
+    /// 内置提示词预设名称，如 `subtitle`、`technical-doc`，见 `lib::preset`
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// 将 `task.references` 作为多轮对话示例发送，未设置时不发送
+    #[serde(default)]
+    pub few_shot: Option<FewShotConfig>,
+    /// 接口地址，如 `https://api.openai.com/v1`
     pub api_base: String,
+    /// API Key
     pub api_key: String,
+    /// 译文后处理，去掉模型附加的标签、引号等，未设置时不处理。流式翻译不处理
+    #[serde(default)]
+    pub cleanup: Option<CleanupConfig>,
+    /// 请求 `logprobs` 并据此计算 `TranslateResult::confidence`，部分兼容接口不支持。流式翻译不计算
+    #[serde(default)]
+    pub confidence: bool,
+    #[serde(flatten, default)]
+    pub http: HttpConfig,
 }
 
 impl OpenAITranslator {
+    /// 请求 ID 以 `X-Client-Request-Id` 头发送，可在 OpenAI 的请求日志中查到
+    fn client(&self, task: &TranslateTask) -> Result<Client<OpenAIConfig>> {
+        let http = match &task.request_id {
+            Some(request_id) => self
+                .http
+                .build_client_with(&[("X-Client-Request-Id", request_id)])?,
+            None => self.http.build_client()?,
+        };
+
+        Ok(Client::with_config(
+            OpenAIConfig::new()
+                .with_api_base(self.api_base.clone())
+                .with_api_key(self.api_key.clone()),
+        )
+        .with_http_client(http))
+    }
+
+    /// 未设置提示词模板时使用的预设：`task.field` 与预设同名时优先，其次为配置的 `preset`
+    fn preset(&self, task: &TranslateTask) -> &'static Preset {
+        task.field
+            .as_deref()
+            .and_then(preset)
+            .or_else(|| self.preset.as_deref().and_then(preset))
+            .unwrap_or_else(default_preset)
+    }
+
     fn build_request(
         &self,
         task: &TranslateTask,
@@ -32,43 +109,63 @@ impl OpenAITranslator {
     ) -> Result<CreateChatCompletionRequest> {
         let mut request_args = CreateChatCompletionRequestArgs::default();
 
+        let preset = self.preset(task);
+
         let system_prompt = if let Some(system_prompt) = &task.system_prompt {
             format_messages(system_prompt, &task)?
         } else if let Some(system_prompt) = &self.system_prompt {
             format_messages(system_prompt, &task)?
         } else {
-            format_messages(&r##"请将以下{{ source_language }}内容精准翻译为{{ target_language }}，确保符合以下要求：
-1. 保持专业语气与原文风格
-2. 要做到信达雅
-3. 保留专业术语及关键数据
-4. 只输出译文，不要输出其它内容"##.to_string(), &task)?
+            format_messages(&preset.system_prompt.to_string(), &task)?
         };
 
-        let user_prompt = if let Some(user_prompt) = &task.user_prompt {
-            format_messages(user_prompt, &task)?
-        } else if let Some(user_prompt) = &self.user_prompt {
-            format_messages(user_prompt, &task)?
-        } else {
-            format_messages(&r##"{{ content }}"##.to_string(), &task)?
+        let user_prompt = |task: &TranslateTask| -> Result<String> {
+            if let Some(user_prompt) = &task.user_prompt {
+                format_messages(user_prompt, task)
+            } else if let Some(user_prompt) = &self.user_prompt {
+                format_messages(user_prompt, task)
+            } else {
+                format_messages(&preset.user_prompt.to_string(), task)
+            }
         };
 
-        request_args.model(self.model.clone()).messages(vec![
-            ChatCompletionRequestMessage::System(system_prompt.into()),
-            ChatCompletionRequestMessage::User(user_prompt.into()),
-        ]);
-
-        if let Some(extra) = task.extra.clone() {
-            if let Value::Number(temperature) = &extra["temperature"] {
-                if let Some(temperature) = temperature.as_i64() {
-                    request_args.temperature(temperature as f32);
-                }
+        let mut messages = vec![ChatCompletionRequestMessage::System(system_prompt.into())];
+
+        // 参考译文作为多轮对话示例，示例原文同样套用用户提示词模板
+        if let Some(few_shot) = &self.few_shot {
+            for example in select_examples(task, few_shot) {
+                let example_task = TranslateTask {
+                    content: example.source.clone(),
+                    references: vec![],
+                    ..task.clone()
+                };
+                messages.push(ChatCompletionRequestMessage::User(
+                    user_prompt(&example_task)?.into(),
+                ));
+                messages.push(ChatCompletionRequestMessage::Assistant(
+                    example.target.clone().into(),
+                ));
             }
+        }
 
-            if let Value::Number(top_p) = &extra["top_p"] {
-                if let Some(top_p) = top_p.as_i64() {
-                    request_args.top_p(top_p as f32);
-                }
-            }
+        messages.push(ChatCompletionRequestMessage::User(
+            user_prompt(task)?.into(),
+        ));
+
+        request_args.model(self.model.clone()).messages(messages);
+
+        let options = OpenAIOptions::from_task(task)?;
+
+        if let Some(temperature) = options.temperature {
+            request_args.temperature(temperature);
+        }
+
+        if let Some(top_p) = options.top_p {
+            request_args.top_p(top_p);
+        }
+
+        if self.confidence && !stream {
+            request_args.logprobs(true);
         }
 
         request_args.stream(stream);
@@ -82,7 +179,37 @@ impl Translator for OpenAITranslator {
     type This = Self;
 
     async fn new(config: Value) -> Result<Self> {
-        serde_json::from_value(config).map_err(|e| anyhow!(e))
+        serde_json::from_value(config)
+            .map_err(|e| anyhow!(XTranslateError::InvalidConfig(e.to_string())))
+    }
+
+    fn config_schema() -> Option<Value> {
+        Some(schema_of::<Self>())
+    }
+
+    fn validate_config(config: &Value) -> Result<Vec<ConfigIssue>> {
+        let mut validator = ConfigValidator::new(config);
+        validator.require_str("model");
+        validator.url("api_base");
+        validator.require_str("api_key");
+        validator.template("system_prompt");
+        validator.template("user_prompt");
+        if let Some(name) = validator.optional_str("preset") {
+            if preset(name).is_none() {
+                validator.issue("preset", format!("must be one of {:?}", preset_names()));
+            }
+        }
+        validator.http();
+        Ok(validator.finish())
+    }
+
+    /// 能力与 `few_shot` 配置有关，只提供语言
+    fn static_info() -> StaticInfo {
+        StaticInfo {
+            input_languages: Some(vec!["*".to_string()]),
+            output_languages: Some(vec!["*".to_string()]),
+            capabilities: None,
+        }
     }
 
     fn get_supported_input_languages(&self) -> Result<Vec<String>> {
@@ -101,88 +228,232 @@ impl Translator for OpenAITranslator {
         Ok(true)
     }
 
-    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
-        let client = Client::with_config(
-            OpenAIConfig::new()
-                .with_api_base(self.api_base.clone())
-                .with_api_key(self.api_key.clone()),
-        );
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            supports_streaming: true,
+            supports_prompt: true,
+            supports_references: self.few_shot.is_some(),
+            max_references: self.few_shot.as_ref().map(|f| f.max_examples),
+            supports_auto_detect: true,
+            needs_target_language: true,
+            ..Default::default()
+        }
+    }
+
+    /// 兼容接口众多，按 `api_base` 区分并发上限
+    async fn translate(&self, mut task: TranslateTask) -> Result<TranslateResult> {
+        let request_id = ensure_request_id(&mut task);
+        with_request_id(
+            &request_id,
+            with_deadline(
+                self.http.deadline(),
+                with_limit(
+                    &self.api_base,
+                    self.http.max_concurrency,
+                    self.do_translate(task),
+                ),
+            ),
+        )
+        .await
+    }
+
+    async fn translate_stream(
+        &self,
+        mut task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let request_id = ensure_request_id(&mut task);
+        with_request_id(
+            &request_id,
+            with_deadline(
+                self.http.deadline(),
+                with_limit(
+                    &self.api_base,
+                    self.http.max_concurrency,
+                    self.do_translate_stream(task, sender),
+                ),
+            ),
+        )
+        .await
+    }
+}
+
+impl OpenAITranslator {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "translate",
+            skip_all,
+            err,
+            fields(provider = "openai", model = %self.model, languages = %language_pair(&task))
+        )
+    )]
+    async fn do_translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let client = self.client(&task)?;
 
         let request = self.build_request(&task, false)?;
 
+        let start = Instant::now();
         let value: Value = client
             .chat()
             .create_byot(request)
             .await
-            .map_err(|e| anyhow!(e))?;
+            .map_err(map_error)?;
 
-        let reasoning = value["choices"][0]["message"]["reasoning_content"]
+        let mut reasoning = value["choices"][0]["message"]["reasoning_content"]
             .as_str()
             .map(|s| s.to_string());
 
+        // 部分兼容接口把推理以 `<think>` 标签放在正文中
         let content = value["choices"][0]["message"]["content"]
             .as_str()
-            .map(|s| s.to_string());
-
-        Ok(TranslateResult { reasoning, content })
+            .map(|s| {
+                let (think, content) = split_think(s);
+                reasoning = reasoning.take().or(think);
+                content
+            })
+            .map(|s| match &self.cleanup {
+                Some(cleanup) => clean_output(&s, &task.content, cleanup),
+                None => s,
+            });
+
+        let confidence = openai_confidence(&value);
+
+        let mut usage = openai_usage(&value).unwrap_or_default();
+        usage.latency_ms = Some(start.elapsed().as_millis() as u64);
+        record_usage(&usage);
+
+        Ok(TranslateResult {
+            reasoning,
+            content,
+            usage: Some(usage),
+            request_id: task.request_id.clone(),
+            confidence,
+            ..Default::default()
+        })
     }
 
-    async fn translate_stream(
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "translate_stream",
+            skip_all,
+            err,
+            fields(provider = "openai", model = %self.model, languages = %language_pair(&task))
+        )
+    )]
+    async fn do_translate_stream(
         &self,
         task: TranslateTask,
         sender: Sender<TranslateStreamChunk>,
     ) -> Result<()> {
-        let client = Client::with_config(
-            OpenAIConfig::new()
-                .with_api_base(self.api_base.clone())
-                .with_api_key(self.api_key.clone()),
-        );
+        let client = self.client(&task)?;
 
         let request = self.build_request(&task, true)?;
 
+        let start = Instant::now();
         let mut stream = client
             .chat()
             .create_stream_byot::<_, Value>(request)
             .await
-            .map_err(|e| anyhow!(e))?;
+            .map_err(map_error)?;
 
         sender.send(TranslateStreamChunk::Start).await?;
 
+        let mut think = ThinkParser::new();
+
         while let Some(result) = stream.next().await {
             if let Ok(chunk) = result {
+                // 服务端开启用量统计时，最后一个数据块携带 `usage`
+                let usage = openai_usage(&chunk).map(|mut usage| {
+                    usage.latency_ms = Some(start.elapsed().as_millis() as u64);
+                    record_usage(&usage);
+                    usage
+                });
+
                 let reasoning = chunk["choices"][0]["delta"]["reasoning_content"]
                     .as_str()
                     .map(|s| s.to_string());
 
-                let content = chunk["choices"][0]["delta"]["content"]
-                    .as_str()
-                    .map(|s| s.to_string());
+                let (think, content) = match chunk["choices"][0]["delta"]["content"].as_str() {
+                    Some(s) => think.push(s),
+                    None => (None, None),
+                };
+                let reasoning = reasoning.or(think);
 
                 sender
                     .send(TranslateStreamChunk::Delta(TranslateResult {
                         content,
                         reasoning,
+                        usage,
+                        request_id: task.request_id.clone(),
+                        ..Default::default()
                     }))
                     .await?;
             } else {
-                bail!(result.unwrap_err())
+                bail!(map_error(result.unwrap_err()))
             }
         }
 
+        let (reasoning, content) = think.finish();
+        if reasoning.is_some() || content.is_some() {
+            sender
+                .send(TranslateStreamChunk::Delta(TranslateResult {
+                    content,
+                    reasoning,
+                    request_id: task.request_id.clone(),
+                    ..Default::default()
+                }))
+                .await?;
+        }
+
         sender.send(TranslateStreamChunk::End).await?;
 
         Ok(())
     }
 }
 
+/// 将 async-openai 的错误映射为 `XTranslateError`
+fn map_error(err: OpenAIError) -> XTranslateError {
+    match err {
+        OpenAIError::Reqwest(e) => e.into(),
+        OpenAIError::ApiError(e) => match e
+            .code
+            .as_deref()
+            .or(e.r#type.as_deref())
+            .unwrap_or_default()
+        {
+            "invalid_api_key" | "authentication_error" => XTranslateError::AuthFailed(e.message),
+            "rate_limit_exceeded" | "rate_limit_error" => {
+                XTranslateError::RateLimited { retry_after: None }
+            }
+            code => XTranslateError::ProviderError {
+                code: code.to_string(),
+                message: e.message,
+            },
+        },
+        OpenAIError::StreamError(message) => XTranslateError::Network(message),
+        OpenAIError::InvalidArgument(message) => XTranslateError::InvalidConfig(message),
+        err => XTranslateError::ProviderError {
+            code: String::new(),
+            message: err.to_string(),
+        },
+    }
+}
+
 #[tokio::test]
 async fn test_openai() -> Result<()> {
     let translator = OpenAITranslator {
         model: "deepseek-reasoner".to_string(),
         system_prompt: None,
         user_prompt: None,
+        preset: None,
+        few_shot: None,
         api_base: env!("OPENAI_API_BASE").to_string(),
         api_key: env!("OPENAI_API_KEY").to_string(),
+        cleanup: None,
+        confidence: false,
+        http: Default::default(),
     };
 
     test_translate(translator).await
@@ -194,9 +465,167 @@ async fn test_openai_stream() -> Result<()> {
         model: "deepseek-reasoner".to_string(),
         system_prompt: None,
         user_prompt: None,
+        preset: None,
+        few_shot: None,
         api_base: env!("OPENAI_API_BASE").to_string(),
         api_key: env!("OPENAI_API_KEY").to_string(),
+        cleanup: None,
+        confidence: false,
+        http: Default::default(),
     };
 
     test_translate_stream(translator).await
 }
+
+#[test]
+fn test_openai_config_schema() {
+    let schema = OpenAITranslator::config_schema().unwrap();
+    let properties = &schema["properties"];
+    assert!(properties["api_key"].is_object());
+    assert!(properties["timeout_ms"].is_object());
+    assert_eq!(properties["model"]["description"], "模型名称");
+}
+
+#[test]
+fn test_openai_preset() -> Result<()> {
+    let translator = OpenAITranslator {
+        model: "gpt-4o-mini".to_string(),
+        system_prompt: None,
+        user_prompt: None,
+        preset: Some("literary".to_string()),
+        few_shot: None,
+        api_base: "https://api.openai.com/v1".to_string(),
+        api_key: "key".to_string(),
+        cleanup: None,
+        confidence: false,
+        http: Default::default(),
+    };
+
+    let mut task = TranslateTask {
+        id: "1".to_string(),
+        content: "Hello".to_string(),
+        source_language: Some("en".parse()?),
+        target_language: Some("zh".parse()?),
+        user_prompt: None,
+        system_prompt: None,
+        field: None,
+        terms: vec![],
+        references: vec![],
+        extra: None,
+        request_id: None,
+        deadline_ms: None,
+    };
+    assert_eq!(translator.preset(&task).name, "literary");
+
+    task.field = Some("subtitle".to_string());
+    assert_eq!(translator.preset(&task).name, "subtitle");
+
+    // 普通的领域描述不影响预设
+    task.field = Some("医学".to_string());
+    assert_eq!(translator.preset(&task).name, "literary");
+
+    let issues = OpenAITranslator::validate_config(&serde_json::json!({
+        "model": "m",
+        "api_base": "https://example.com/v1",
+        "api_key": "k",
+        "preset": "poetry",
+    }))?;
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].field, "preset");
+
+    Ok(())
+}
+
+#[test]
+fn test_openai_few_shot() -> Result<()> {
+    let translator = OpenAITranslator {
+        model: "gpt-4o-mini".to_string(),
+        system_prompt: None,
+        user_prompt: Some("原文：{{ content }}".to_string()),
+        preset: None,
+        few_shot: Some(FewShotConfig::default()),
+        api_base: "https://api.openai.com/v1".to_string(),
+        api_key: "key".to_string(),
+        cleanup: None,
+        confidence: false,
+        http: Default::default(),
+    };
+
+    let task = TranslateTask {
+        id: "1".to_string(),
+        content: "Hello".to_string(),
+        source_language: Some("en".parse()?),
+        target_language: Some("zh".parse()?),
+        user_prompt: None,
+        system_prompt: None,
+        field: None,
+        terms: vec![],
+        references: vec![lib::TranslatedItem {
+            source: "Hi".to_string(),
+            target: "嗨".to_string(),
+        }],
+        extra: None,
+        request_id: None,
+        deadline_ms: None,
+    };
+
+    let request = serde_json::to_value(translator.build_request(&task, false)?)?;
+    let messages: Vec<(&str, &str)> = request["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .skip(1)
+        .map(|m| (m["role"].as_str().unwrap(), m["content"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            ("user", "原文：Hi"),
+            ("assistant", "嗨"),
+            ("user", "原文：Hello")
+        ]
+    );
+    assert_eq!(translator.capabilities().max_references, Some(5));
+
+    Ok(())
+}
+
+#[test]
+fn test_openai_options() -> Result<()> {
+    let translator = OpenAITranslator {
+        model: "gpt-4o-mini".to_string(),
+        system_prompt: None,
+        user_prompt: None,
+        preset: None,
+        few_shot: None,
+        api_base: "https://api.openai.com/v1".to_string(),
+        api_key: "key".to_string(),
+        cleanup: None,
+        confidence: false,
+        http: Default::default(),
+    };
+
+    let mut task = TranslateTask {
+        id: "1".to_string(),
+        content: "Hello".to_string(),
+        source_language: Some("en".parse()?),
+        target_language: Some("zh".parse()?),
+        user_prompt: None,
+        system_prompt: None,
+        field: None,
+        terms: vec![],
+        references: vec![],
+        extra: Some(serde_json::json!({ "openai": { "temperature": 0.5 } })),
+        request_id: None,
+        deadline_ms: None,
+    };
+
+    let request = serde_json::to_value(translator.build_request(&task, false)?)?;
+    assert_eq!(request["temperature"], 0.5);
+
+    task.extra = Some(serde_json::json!({ "openai": { "temprature": 0.5 } }));
+    let err = translator.build_request(&task, false).unwrap_err();
+    assert!(err.to_string().contains("temprature"));
+
+    Ok(())
+}
@@ -1,19 +1,34 @@
 use anyhow::{anyhow, bail, Result};
 use async_openai::config::OpenAIConfig;
+use async_openai::error::OpenAIError;
 use async_openai::types::{ChatCompletionRequestMessage, CreateChatCompletionRequestArgs};
 use async_openai::Client;
 use async_trait::async_trait;
 use futures_util::StreamExt;
 use language_tags::LanguageTag;
+use lib::error::XTranslateError;
+use lib::http::HttpConfig;
+use lib::langmap::LangMap;
+use lib::limit::with_limit;
+use lib::request::{ensure_request_id, with_request_id};
+use lib::terms::select_terms;
+#[cfg(feature = "tracing")]
+use lib::trace::language_pair;
+use lib::trace::record_usage;
+use lib::utils::{openai_usage, schema_of, with_deadline};
 #[cfg(test)]
 use lib::utils::{test_translate, test_translate_stream};
-use lib::{TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use lib::validate::{ConfigIssue, ConfigValidator};
+use lib::{Capabilities, StaticInfo, TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
 use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+use std::time::Instant;
 use tokio::sync::mpsc::Sender;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub enum QwenMtModel {
     #[serde(rename = "qwen-mt-plus")]
     QwenMtPlus,
@@ -28,7 +43,10 @@ impl TryFrom<String> for QwenMtModel {
         match value.as_str() {
             "qwen-mt-plus" => Ok(QwenMtModel::QwenMtPlus),
             "qwen-mt-turbo" => Ok(QwenMtModel::QwenMtTurbo),
-            _ => Err(anyhow!("Invalid model: {}", value)),
+            _ => Err(anyhow!(XTranslateError::InvalidConfig(format!(
+                "invalid model: {}",
+                value
+            )))),
         }
     }
 }
@@ -42,7 +60,7 @@ impl Display for QwenMtModel {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum QwenMtLanguages {
     ///中文
     Chinese,
@@ -104,61 +122,64 @@ impl Display for QwenMtLanguages {
     }
 }
 
+static LANGUAGES: LazyLock<LangMap<QwenMtLanguages>> = LazyLock::new(|| {
+    LangMap::new()
+        .lang("zh", QwenMtLanguages::Chinese)
+        .lang("en", QwenMtLanguages::English)
+        .lang("ja", QwenMtLanguages::Japanese)
+        .lang("ko", QwenMtLanguages::Korean)
+        .lang("th", QwenMtLanguages::Thai)
+        .lang("fr", QwenMtLanguages::French)
+        .lang("de", QwenMtLanguages::German)
+        .lang("es", QwenMtLanguages::Spanish)
+        .lang("ar", QwenMtLanguages::Arabic)
+        .lang("id", QwenMtLanguages::Indonesian)
+        .lang("vi", QwenMtLanguages::Vietnamese)
+        .region("pt", &["BR"], QwenMtLanguages::Portuguese)
+        .lang("it", QwenMtLanguages::Italian)
+        .lang("nl", QwenMtLanguages::Dutch)
+        .lang("ru", QwenMtLanguages::Russian)
+        .lang("km", QwenMtLanguages::Khmer)
+        .lang("ceb", QwenMtLanguages::Cebuano)
+        .lang("fil", QwenMtLanguages::Filipino)
+        .lang("cs", QwenMtLanguages::Czech)
+        .lang("pl", QwenMtLanguages::Polish)
+        .lang("fa", QwenMtLanguages::Persian)
+        .lang("he", QwenMtLanguages::Hebrew)
+        .lang("tr", QwenMtLanguages::Turkish)
+        .lang("hi", QwenMtLanguages::Hindi)
+        .lang("bn", QwenMtLanguages::Bengali)
+        .lang("ur", QwenMtLanguages::Urdu)
+});
+
 impl TryFrom<LanguageTag> for QwenMtLanguages {
     type Error = anyhow::Error;
 
     fn try_from(tag: LanguageTag) -> Result<Self, Self::Error> {
-        let primary = tag.primary_language().to_ascii_lowercase();
-        let primary = primary.as_str();
-        match primary {
-            "zh" => Ok(Self::Chinese),
-            "en" => Ok(Self::English),
-            "ja" => Ok(Self::Japanese),
-            "ko" => Ok(Self::Korean),
-            "th" => Ok(Self::Thai),
-            "fr" => Ok(Self::French),
-            "de" => Ok(Self::German),
-            "es" => Ok(Self::Spanish),
-            "ar" => Ok(Self::Arabic),
-            "id" => Ok(Self::Indonesian),
-            "vi" => Ok(Self::Vietnamese),
-            "pt" => {
-                if let Some(region) = tag.region() {
-                    if region.to_ascii_uppercase() == "BR" {
-                        Ok(Self::Portuguese)
-                    } else {
-                        bail!("Unsupported language tag: {}", tag)
-                    }
-                } else {
-                    bail!("Unsupported language tag: {}", tag)
-                }
-            }
-            "it" => Ok(Self::Italian),
-            "nl" => Ok(Self::Dutch),
-            "ru" => Ok(Self::Russian),
-            "km" => Ok(Self::Khmer),
-            "ceb" => Ok(Self::Cebuano),
-            "fil" => Ok(Self::Filipino),
-            "cs" => Ok(Self::Czech),
-            "pl" => Ok(Self::Polish),
-            "fa" => Ok(Self::Persian),
-            "he" => Ok(Self::Hebrew),
-            "tr" => Ok(Self::Turkish),
-            "hi" => Ok(Self::Hindi),
-            "bn" => Ok(Self::Bengali),
-            "ur" => Ok(Self::Urdu),
-            _ => bail!("Unsupported language tag: {}", tag),
-        }
+        LANGUAGES.map(&tag)
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct QwenMtTranslator {
+    /// 模型名称
     pub model: QwenMtModel,
+    /// 百炼 API Key
     pub api_key: String,
+    #[serde(flatten, default)]
+    pub http: HttpConfig,
 }
 
 impl QwenMtTranslator {
+    fn client(&self) -> Result<Client<OpenAIConfig>> {
+        Ok(Client::with_config(
+            OpenAIConfig::new()
+                .with_api_base("https://dashscope.aliyuncs.com/compatible-mode/v1".to_string())
+                .with_api_key(self.api_key.clone()),
+        )
+        .with_http_client(self.http.build_client()?))
+    }
+
     fn build_request(&self, task: &TranslateTask, stream: bool) -> Result<Value> {
         let mut request_args = CreateChatCompletionRequestArgs::default();
 
@@ -195,8 +216,7 @@ impl QwenMtTranslator {
         }
 
         if task.terms.len() > 0 {
-            let list = task
-                .terms
+            let list = select_terms(&task.content, &task.terms, None)
                 .iter()
                 .map(|i| {
                     json!({
@@ -263,7 +283,42 @@ impl Translator for QwenMtTranslator {
     type This = Self;
 
     async fn new(config: Value) -> Result<Self> {
-        serde_json::from_value(config).map_err(|e| anyhow!(e))
+        serde_json::from_value(config)
+            .map_err(|e| anyhow!(XTranslateError::InvalidConfig(e.to_string())))
+    }
+
+    fn config_schema() -> Option<Value> {
+        Some(schema_of::<Self>())
+    }
+
+    fn validate_config(config: &Value) -> Result<Vec<ConfigIssue>> {
+        let mut validator = ConfigValidator::new(config);
+        validator.one_of("model", &["qwen-mt-plus", "qwen-mt-turbo"]);
+        if let Some(api_key) = validator.require_str("api_key") {
+            if !api_key.starts_with("sk-") {
+                validator.issue("api_key", "DashScope API key should start with `sk-`");
+            }
+        }
+        validator.http();
+        Ok(validator.finish())
+    }
+
+    fn static_info() -> StaticInfo {
+        StaticInfo {
+            input_languages: QwenMtTranslator::lang_list().ok(),
+            output_languages: QwenMtTranslator::lang_list().ok(),
+            capabilities: Some(Capabilities {
+                supports_streaming: true,
+                supports_terms: true,
+                supports_references: true,
+                supports_field: true,
+                supports_auto_detect: true,
+                needs_target_language: true,
+                // 模型最多输入 8k token，预留提示词与术语的空间
+                max_input_chars: Some(4000),
+                ..Default::default()
+            }),
+        }
     }
 
     fn get_supported_input_languages(&self) -> Result<Vec<String>> {
@@ -282,49 +337,106 @@ impl Translator for QwenMtTranslator {
         Ok(QwenMtLanguages::try_from(LanguageTag::parse(lang.as_str())?).is_ok())
     }
 
-    async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
-        let client = Client::with_config(
-            OpenAIConfig::new()
-                .with_api_base("https://dashscope.aliyuncs.com/compatible-mode/v1".to_string())
-                .with_api_key(self.api_key.clone()),
-        );
+    fn capabilities(&self) -> Capabilities {
+        Self::static_info().capabilities.unwrap_or_default()
+    }
+
+    async fn translate(&self, mut task: TranslateTask) -> Result<TranslateResult> {
+        let request_id = ensure_request_id(&mut task);
+        with_request_id(
+            &request_id,
+            with_deadline(
+                self.http.deadline(),
+                with_limit("qwen", self.http.max_concurrency, self.do_translate(task)),
+            ),
+        )
+        .await
+    }
+
+    async fn translate_stream(
+        &self,
+        mut task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let request_id = ensure_request_id(&mut task);
+        with_request_id(
+            &request_id,
+            with_deadline(
+                self.http.deadline(),
+                with_limit(
+                    "qwen",
+                    self.http.max_concurrency,
+                    self.do_translate_stream(task, sender),
+                ),
+            ),
+        )
+        .await
+    }
+}
+
+impl QwenMtTranslator {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "translate",
+            skip_all,
+            err,
+            fields(provider = "qwen", model = %self.model, languages = %language_pair(&task))
+        )
+    )]
+    async fn do_translate(&self, task: TranslateTask) -> Result<TranslateResult> {
+        let client = self.client()?;
 
         let request = self.build_request(&task, false)?;
 
+        let start = Instant::now();
         let value: Value = client
             .chat()
             .create_byot(request)
             .await
-            .map_err(|e| anyhow!(e))?;
+            .map_err(map_error)?;
 
         let content = value["choices"][0]["message"]["content"]
             .as_str()
             .map(|s| s.to_string());
 
+        let mut usage = openai_usage(&value).unwrap_or_default();
+        usage.latency_ms = Some(start.elapsed().as_millis() as u64);
+        record_usage(&usage);
+
         Ok(TranslateResult {
             reasoning: None,
             content,
+            usage: Some(usage),
+            request_id: task.request_id.clone(),
+            ..Default::default()
         })
     }
 
-    async fn translate_stream(
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "translate_stream",
+            skip_all,
+            err,
+            fields(provider = "qwen", model = %self.model, languages = %language_pair(&task))
+        )
+    )]
+    async fn do_translate_stream(
         &self,
         task: TranslateTask,
         sender: Sender<TranslateStreamChunk>,
     ) -> Result<()> {
-        let client = Client::with_config(
-            OpenAIConfig::new()
-                .with_api_base("https://dashscope.aliyuncs.com/compatible-mode/v1".to_string())
-                .with_api_key(self.api_key.clone()),
-        );
+        let client = self.client()?;
 
         let request = self.build_request(&task, true)?;
 
+        let start = Instant::now();
         let mut stream = client
             .chat()
             .create_stream_byot::<_, Value>(request)
             .await
-            .map_err(|e| anyhow!(e))?;
+            .map_err(map_error)?;
 
         sender.send(TranslateStreamChunk::Start).await?;
 
@@ -332,6 +444,13 @@ impl Translator for QwenMtTranslator {
 
         while let Some(result) = stream.next().await {
             if let Ok(chunk) = result {
+                // 服务端开启用量统计时，最后一个数据块携带 `usage`
+                let usage = openai_usage(&chunk).map(|mut usage| {
+                    usage.latency_ms = Some(start.elapsed().as_millis() as u64);
+                    record_usage(&usage);
+                    usage
+                });
+
                 let content = chunk["choices"][0]["delta"]["content"]
                     .as_str()
                     .map(|s| s.to_string());
@@ -343,12 +462,15 @@ impl Translator for QwenMtTranslator {
                             .clone()
                             .and_then(|s| s.strip_prefix(cache.as_str()).map(ToString::to_string)),
                         reasoning: None,
+                        usage,
+                        request_id: task.request_id.clone(),
+                        ..Default::default()
                     }))
                     .await?;
 
                 cache = content.unwrap_or("".to_string());
             } else {
-                bail!(result.unwrap_err())
+                bail!(map_error(result.unwrap_err()))
             }
         }
 
@@ -358,11 +480,40 @@ impl Translator for QwenMtTranslator {
     }
 }
 
+/// 将 async-openai 的错误映射为 `XTranslateError`，错误码参考百炼兼容模式文档
+fn map_error(err: OpenAIError) -> XTranslateError {
+    match err {
+        OpenAIError::Reqwest(e) => e.into(),
+        OpenAIError::ApiError(e) => match e
+            .code
+            .as_deref()
+            .or(e.r#type.as_deref())
+            .unwrap_or_default()
+        {
+            "invalid_api_key" | "InvalidApiKey" => XTranslateError::AuthFailed(e.message),
+            "limit_requests" | "Throttling" | "Throttling.RateQuota" => {
+                XTranslateError::RateLimited { retry_after: None }
+            }
+            code => XTranslateError::ProviderError {
+                code: code.to_string(),
+                message: e.message,
+            },
+        },
+        OpenAIError::StreamError(message) => XTranslateError::Network(message),
+        OpenAIError::InvalidArgument(message) => XTranslateError::InvalidConfig(message),
+        err => XTranslateError::ProviderError {
+            code: String::new(),
+            message: err.to_string(),
+        },
+    }
+}
+
 #[tokio::test]
 async fn test_qwen() -> Result<()> {
     let translator = QwenMtTranslator {
         model: QwenMtModel::QwenMtTurbo,
         api_key: env!("QWEN_API_KEY").to_string(),
+        http: Default::default(),
     };
 
     test_translate(translator).await
@@ -373,7 +524,26 @@ async fn test_qwen_stream() -> Result<()> {
     let translator = QwenMtTranslator {
         model: QwenMtModel::QwenMtTurbo,
         api_key: env!("QWEN_API_KEY").to_string(),
+        http: Default::default(),
     };
 
     test_translate_stream(translator).await
 }
+
+#[test]
+fn test_qwen_validate_config() -> Result<()> {
+    let issues = QwenMtTranslator::validate_config(&json!({
+        "model": "qwen-max",
+        "api_key": "key",
+    }))?;
+    let fields: Vec<&str> = issues.iter().map(|i| i.field.as_str()).collect();
+    assert_eq!(fields, vec!["model", "api_key"]);
+
+    let issues = QwenMtTranslator::validate_config(&json!({
+        "model": "qwen-mt-turbo",
+        "api_key": "sk-xxx",
+    }))?;
+    assert!(issues.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+// 不发送任何请求的 WebAssembly 组件插件，与 plugin-dryrun 的 `DryRunTranslator` 对应，用于测试 `WasmTranslator`
+
+use exports::xtranslator::plugin::translator::{Guest, GuestTranslator, PluginError, Translator};
+use serde_json::{json, Value};
+use std::cell::Cell;
+use xtranslator::plugin::host::push_chunk;
+use xtranslator::plugin::types::StreamChunk;
+
+wit_bindgen::generate!({
+    path: "../wit/translator.wit",
+    world: "plugin",
+});
+
+const LANGUAGES: [&str; 3] = ["en", "zh-CN", "ja"];
+
+fn error(message: impl Into<String>) -> PluginError {
+    PluginError { message: message.into() }
+}
+
+/// `FfiError` 的 JSON 形式，宿主据此还原错误类别
+fn typed_error(mut kind: Value, detail: &str) -> PluginError {
+    match kind.as_object_mut() {
+        Some(fields) => {
+            fields.insert("detail".to_string(), Value::String(detail.to_string()));
+            error(kind.to_string())
+        }
+        None => error(detail),
+    }
+}
+
+struct Plugin;
+
+impl Guest for Plugin {
+    type Translator = DryRun;
+
+    fn get_plugin_name() -> String {
+        "wasm_dryrun".to_string()
+    }
+
+    fn get_plugin_metadata() -> String {
+        json!({
+            "name": "wasm_dryrun",
+            "version": env!("CARGO_PKG_VERSION"),
+            "lib_version": "",
+            "abi_version": 0,
+            "min_host_version": "0.1.0",
+        })
+        .to_string()
+    }
+
+    fn get_config_schema() -> Option<String> {
+        Some(json!({ "type": "object", "properties": { "prefix": { "type": "string" } } }).to_string())
+    }
+
+    fn validate_config(config: String) -> Result<String, PluginError> {
+        let config: Value = serde_json::from_str(&config).map_err(|e| error(e.to_string()))?;
+        let issues = match config.get("prefix") {
+            Some(prefix) if !prefix.is_string() => vec![json!({ "field": "prefix", "message": "expected a string" })],
+            _ => vec![],
+        };
+        Ok(Value::Array(issues).to_string())
+    }
+}
+
+/// 译文为加上 `prefix` 的原文，`{lang}` 替换为目标语言。
+/// 前几次翻译依次返回 `errors` 中的错误，格式为 `XTranslateError` 的 JSON 形式
+struct DryRun {
+    prefix: String,
+    errors: Vec<Value>,
+    calls: Cell<usize>,
+}
+
+impl DryRun {
+    /// 解析任务，返回替换后的前缀与原文
+    fn begin(&self, task: &str) -> Result<(String, String), PluginError> {
+        let call = self.calls.get();
+        self.calls.set(call + 1);
+        if let Some(err) = self.errors.get(call) {
+            return Err(typed_error(err.clone(), &format!("dryrun error {}", call)));
+        }
+
+        let task: Value = serde_json::from_str(task).map_err(|e| error(e.to_string()))?;
+        let lang = task["target_language"].as_str().unwrap_or_default().to_string();
+        let content = task["content"].as_str().unwrap_or_default().to_string();
+        Ok((self.prefix.replace("{lang}", &lang), content))
+    }
+}
+
+impl GuestTranslator for DryRun {
+    fn create(config: String) -> Result<Translator, PluginError> {
+        let config: Value = serde_json::from_str(&config).map_err(|e| error(e.to_string()))?;
+        let prefix = match config.get("prefix") {
+            Some(Value::String(prefix)) => prefix.clone(),
+            Some(_) => {
+                let kind = json!({ "kind": "invalid_config", "message": "prefix must be a string" });
+                return Err(typed_error(kind, "invalid config: prefix must be a string"));
+            }
+            None => "[{lang}] ".to_string(),
+        };
+        let errors = config["errors"].as_array().cloned().unwrap_or_default();
+
+        Ok(Translator::new(DryRun {
+            prefix,
+            errors,
+            calls: Cell::new(0),
+        }))
+    }
+
+    fn get_supported_input_languages(&self) -> Result<Vec<String>, PluginError> {
+        Ok(LANGUAGES.map(String::from).to_vec())
+    }
+
+    fn get_supported_output_languages(&self) -> Result<Vec<String>, PluginError> {
+        Ok(LANGUAGES.map(String::from).to_vec())
+    }
+
+    fn is_supported_input_language(&self, lang: String) -> Result<bool, PluginError> {
+        Ok(LANGUAGES.contains(&lang.as_str()))
+    }
+
+    fn is_supported_output_language(&self, lang: String) -> Result<bool, PluginError> {
+        Ok(LANGUAGES.contains(&lang.as_str()))
+    }
+
+    fn is_supported_pair(&self, source: String, target: String) -> Result<bool, PluginError> {
+        Ok(source != target && LANGUAGES.contains(&source.as_str()) && LANGUAGES.contains(&target.as_str()))
+    }
+
+    fn get_supported_pairs(&self) -> Result<Vec<(String, String)>, PluginError> {
+        let pairs = LANGUAGES
+            .iter()
+            .flat_map(|source| LANGUAGES.iter().filter(move |target| *target != source).map(move |target| (source.to_string(), target.to_string())));
+        Ok(pairs.collect())
+    }
+
+    fn capabilities(&self) -> String {
+        json!({ "supports_streaming": true }).to_string()
+    }
+
+    fn translate(&self, task: String) -> Result<String, PluginError> {
+        let (prefix, content) = self.begin(&task)?;
+        Ok(json!({ "content": format!("{}{}", prefix, content) }).to_string())
+    }
+
+    /// 前缀与每个词各为一个增量，宿主不再接收时停止
+    fn translate_stream(&self, stream_id: u64, task: String) -> Result<(), PluginError> {
+        let (prefix, content) = self.begin(&task)?;
+        let deltas = std::iter::once(prefix).chain(content.split_inclusive(' ').map(String::from));
+
+        if !push_chunk(stream_id, &StreamChunk::Start) {
+            return Ok(());
+        }
+        for delta in deltas {
+            if !push_chunk(stream_id, &StreamChunk::Delta(json!({ "content": delta }).to_string())) {
+                return Ok(());
+            }
+        }
+        push_chunk(stream_id, &StreamChunk::End);
+
+        Ok(())
+    }
+}
+
+export!(Plugin);
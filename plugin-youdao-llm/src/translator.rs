@@ -1,21 +1,33 @@
-use lib::utils::{format_messages, stream2normal};
+use lib::error::XTranslateError;
+use lib::http::{execute, HttpConfig};
+use lib::langmap::LangMap;
+use lib::limit::with_limit;
+use lib::options::ProviderOptions;
+use lib::request::{ensure_request_id, with_request_id};
+#[cfg(feature = "tracing")]
+use lib::trace::language_pair;
+use lib::trace::record_usage;
+use lib::utils::{format_messages, schema_of, stream2normal, with_deadline};
 #[cfg(test)]
 use lib::utils::{test_translate, test_translate_stream};
-use lib::{TranslateResult, TranslateStreamChunk, TranslateTask, Translator};
+use lib::validate::{ConfigIssue, ConfigValidator};
+use lib::{Capabilities, StaticInfo, TranslateResult, TranslateStreamChunk, TranslateTask, Translator, Usage};
 use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
+use eventsource_stream::{EventStreamError, Eventsource};
 use futures_util::StreamExt;
 use hex::ToHex;
 use language_tags::LanguageTag;
-use reqwest::Client;
-use reqwest_eventsource::{Event, EventSource};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
 use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+use std::time::Instant;
 use tokio::sync::mpsc::Sender;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum YoudaoLLMLanguages {
     ///简体中文
     Chinese,
@@ -32,28 +44,50 @@ impl Display for YoudaoLLMLanguages {
     }
 }
 
+static LANGUAGES: LazyLock<LangMap<YoudaoLLMLanguages>> = LazyLock::new(|| {
+    LangMap::new()
+        .lang("zh", YoudaoLLMLanguages::Chinese)
+        .lang("en", YoudaoLLMLanguages::English)
+});
+
 impl TryFrom<LanguageTag> for YoudaoLLMLanguages {
     type Error = anyhow::Error;
 
     fn try_from(tag: LanguageTag) -> Result<Self, Self::Error> {
-        let primary = tag.primary_language().to_ascii_lowercase();
-        let primary = primary.as_str();
-        match primary {
-            "zh" => Ok(Self::Chinese),
-            "en" => Ok(Self::English),
-            _ => bail!("Unsupported language tag: {}", tag),
-        }
+        LANGUAGES.map(&tag)
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 任务参数，读取自 `task.extra["youdao_llm"]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct YoudaoLLMOptions {
+    /// 处理模式
+    pub handle_option: Option<String>,
+    /// 润色选项
+    pub polish_option: Option<String>,
+    /// 扩写选项
+    pub expand_option: Option<String>,
+}
+
+impl ProviderOptions for YoudaoLLMOptions {
+    const NAMESPACE: &'static str = "youdao_llm";
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct YoudaoLLMTranslator {
+    /// 提示词模板
     pub prompt: Option<String>,
+    /// 应用 ID
     pub api_key: String,
+    /// 应用密钥
     pub api_secret: String,
+    #[serde(flatten, default)]
+    pub http: HttpConfig,
 }
 
 impl YoudaoLLMTranslator {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, err))]
     fn build_request(&self, task: &TranslateTask) -> Result<Value> {
         let uuid = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().timestamp();
@@ -90,18 +124,18 @@ impl YoudaoLLMTranslator {
             data["prompt"] = Value::String(format_messages(&prompt, &task)?);
         }
 
-        if let Some(extra) = task.extra.clone() {
-            if let Some(handle) = extra["handleOption"].as_str() {
-                data["handleOption"] = Value::String(handle.to_string());
-            }
+        let options = YoudaoLLMOptions::from_task(task)?;
 
-            if let Some(polish) = extra["polishOption"].as_str() {
-                data["polishOption"] = Value::String(polish.to_string());
-            }
+        if let Some(handle) = options.handle_option {
+            data["handleOption"] = Value::String(handle);
+        }
 
-            if let Some(expand) = extra["expandOption"].as_str() {
-                data["expandOption"] = Value::String(expand.to_string());
-            }
+        if let Some(polish) = options.polish_option {
+            data["polishOption"] = Value::String(polish);
+        }
+
+        if let Some(expand) = options.expand_option {
+            data["expandOption"] = Value::String(expand);
         }
 
         let sign = {
@@ -140,7 +174,36 @@ impl Translator for YoudaoLLMTranslator {
     type This = Self;
 
     async fn new(config: Value) -> Result<Self> {
-        serde_json::from_value(config).map_err(|e| anyhow!(e))
+        serde_json::from_value(config)
+            .map_err(|e| anyhow!(XTranslateError::InvalidConfig(e.to_string())))
+    }
+
+    fn config_schema() -> Option<Value> {
+        Some(schema_of::<Self>())
+    }
+
+    fn validate_config(config: &Value) -> Result<Vec<ConfigIssue>> {
+        let mut validator = ConfigValidator::new(config);
+        validator.require_str("api_key");
+        validator.require_str("api_secret");
+        validator.template("prompt");
+        validator.http();
+        Ok(validator.finish())
+    }
+
+    fn static_info() -> StaticInfo {
+        StaticInfo {
+            input_languages: Some(vec!["zh".to_string(), "en".to_string()]),
+            output_languages: Some(vec!["zh".to_string(), "en".to_string()]),
+            capabilities: Some(Capabilities {
+                supports_streaming: true,
+                supports_prompt: true,
+                supports_auto_detect: true,
+                needs_target_language: true,
+                max_input_chars: Some(5000),
+                ..Default::default()
+            }),
+        }
     }
 
     fn get_supported_input_languages(&self) -> Result<Vec<String>> {
@@ -175,46 +238,109 @@ impl Translator for YoudaoLLMTranslator {
         Ok(li.contains(&tag.primary_language().to_string()))
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Self::static_info().capabilities.unwrap_or_default()
+    }
+
     async fn translate(&self, task: TranslateTask) -> Result<TranslateResult> {
         stream2normal(self, task).await
     }
 
     async fn translate_stream(
+        &self,
+        mut task: TranslateTask,
+        sender: Sender<TranslateStreamChunk>,
+    ) -> Result<()> {
+        let request_id = ensure_request_id(&mut task);
+        with_request_id(
+            &request_id,
+            with_deadline(
+                self.http.deadline(),
+                with_limit(
+                    "youdao_llm",
+                    self.http.max_concurrency,
+                    self.do_translate_stream(task, sender),
+                ),
+            ),
+        )
+        .await
+    }
+}
+
+impl YoudaoLLMTranslator {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "translate_stream",
+            skip_all,
+            err,
+            fields(provider = "youdao_llm", languages = %language_pair(&task))
+        )
+    )]
+    async fn do_translate_stream(
         &self,
         task: TranslateTask,
         sender: Sender<TranslateStreamChunk>,
     ) -> Result<()> {
-        let client = Client::new();
+        let client = self.http.build_client()?;
 
         let body = self.build_request(&task)?;
 
-        let builder = client
+        let request = client
             .post("https://openapi.youdao.com/llm_trans")
-            .form(&body);
+            .form(&body)
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .build()
+            .map_err(XTranslateError::from)?;
+
+        let start = Instant::now();
+        let resp = execute(&client, request).await?;
+        let status = resp.status();
+        if !status.is_success() {
+            bail!(XTranslateError::from_response(
+                status.as_u16(),
+                resp.headers(),
+                status.to_string()
+            ))
+        }
 
-        let mut es = EventSource::new(builder)?;
+        sender.send(TranslateStreamChunk::Start).await?;
 
-        while let Some(event) = es.next().await {
+        let mut events = resp.bytes_stream().eventsource();
+        while let Some(event) = events.next().await {
             match event {
-                Ok(Event::Open) => sender.send(TranslateStreamChunk::Start).await?,
-                Ok(Event::Message(message)) => {
+                Ok(message) => {
                     let data: Value = serde_json::from_str(message.data.as_str())?;
                     sender
                         .send(TranslateStreamChunk::Delta(TranslateResult {
                             reasoning: None,
                             content: data["transIncre"].as_str().map(|s| s.to_string()),
+                            request_id: task.request_id.clone(),
+                            ..Default::default()
                         }))
                         .await?
                 }
-                Err(err) => {
-                    es.close();
-                    if !matches!(err, reqwest_eventsource::Error::StreamEnded) {
-                        bail!(err);
-                    }
-                }
+                Err(EventStreamError::Transport(e)) => bail!(XTranslateError::from(e)),
+                Err(err) => bail!(XTranslateError::Network(err.to_string())),
             }
         }
 
+        // 按原文字符数计费，用量在结束前单独发送
+        let usage = Usage {
+            characters: task.content.chars().count() as u64,
+            latency_ms: Some(start.elapsed().as_millis() as u64),
+            ..Default::default()
+        };
+        record_usage(&usage);
+
+        sender
+            .send(TranslateStreamChunk::Delta(TranslateResult {
+                usage: Some(usage),
+                request_id: task.request_id.clone(),
+                ..Default::default()
+            }))
+            .await?;
+
         sender.send(TranslateStreamChunk::End).await?;
 
         Ok(())
@@ -227,6 +353,7 @@ async fn test_youdao_llm() -> Result<()> {
         prompt: None,
         api_key: env!("YOUDAO_API_KEY").to_string(),
         api_secret: env!("YOUDAO_API_SECRET").to_string(),
+        http: Default::default(),
     };
 
     test_translate(translator).await
@@ -238,6 +365,7 @@ async fn test_youdao_llm_stream() -> Result<()> {
         prompt: None,
         api_key: env!("YOUDAO_API_KEY").to_string(),
         api_secret: env!("YOUDAO_API_SECRET").to_string(),
+        http: Default::default(),
     };
 
     test_translate_stream(translator).await
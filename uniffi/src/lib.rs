@@ -0,0 +1,424 @@
+use lib::error::XTranslateError;
+use lib::ffi_proxy::{PluginRegistry, ProxyTranslator};
+use lib::{DynTranslator, TranslatorFactory};
+use serde_json::Value;
+use std::sync::{Arc, LazyLock};
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc::channel;
+
+uniffi::include_scaffolding!("xtranslator");
+
+/// 绑定的调用方不在 tokio 运行时中，翻译在此运行时中执行，返回的 `JoinHandle` 可由任意执行器等待
+static RUNTIME: LazyLock<Runtime> = LazyLock::new(|| Runtime::new().unwrap());
+
+#[derive(Debug, thiserror::Error)]
+pub enum TranslateError {
+    #[error("{message}")]
+    Config { message: String },
+    #[error("{message}")]
+    Provider { message: String, retryable: bool },
+    #[error("cancelled")]
+    Cancelled,
+    #[error("{message}")]
+    Other { message: String },
+}
+
+impl TranslateError {
+    fn config(err: impl std::fmt::Display) -> Self {
+        TranslateError::Config {
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for TranslateError {
+    fn from(err: anyhow::Error) -> Self {
+        let message = format!("{:#}", err);
+        match XTranslateError::find(&err) {
+            Some(XTranslateError::InvalidConfig(_)) => TranslateError::Config { message },
+            Some(XTranslateError::Cancelled) => TranslateError::Cancelled,
+            Some(e) => TranslateError::Provider {
+                message,
+                retryable: e.is_retryable(),
+            },
+            None => TranslateError::Other { message },
+        }
+    }
+}
+
+pub struct TranslatedItem {
+    pub source: String,
+    pub target: String,
+}
+
+pub struct TranslateTask {
+    pub id: String,
+    pub content: String,
+    pub source_language: Option<String>,
+    pub target_language: Option<String>,
+    pub user_prompt: Option<String>,
+    pub system_prompt: Option<String>,
+    pub field: Option<String>,
+    pub terms: Vec<TranslatedItem>,
+    pub references: Vec<TranslatedItem>,
+    pub extra: Option<String>,
+    pub request_id: Option<String>,
+    pub deadline_ms: Option<u64>,
+}
+
+fn items(items: Vec<TranslatedItem>) -> Vec<lib::TranslatedItem> {
+    items
+        .into_iter()
+        .map(|item| lib::TranslatedItem {
+            source: item.source,
+            target: item.target,
+        })
+        .collect()
+}
+
+impl TryFrom<TranslateTask> for lib::TranslateTask {
+    type Error = TranslateError;
+
+    fn try_from(task: TranslateTask) -> Result<Self, TranslateError> {
+        let language = |tag: Option<String>| {
+            tag.map(|tag| tag.parse())
+                .transpose()
+                .map_err(|e| TranslateError::Other {
+                    message: format!("invalid language tag: {}", e),
+                })
+        };
+        let extra = task
+            .extra
+            .map(|extra| serde_json::from_str(&extra))
+            .transpose()
+            .map_err(|e| TranslateError::Other {
+                message: format!("invalid extra: {}", e),
+            })?;
+
+        Ok(lib::TranslateTask {
+            id: task.id,
+            content: task.content,
+            source_language: language(task.source_language)?,
+            target_language: language(task.target_language)?,
+            user_prompt: task.user_prompt,
+            system_prompt: task.system_prompt,
+            field: task.field,
+            terms: items(task.terms),
+            references: items(task.references),
+            extra,
+            request_id: task.request_id,
+            deadline_ms: task.deadline_ms,
+        })
+    }
+}
+
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub characters: u64,
+    pub latency_ms: Option<u64>,
+    pub request_id: Option<String>,
+}
+
+pub struct TranslateResult {
+    pub reasoning: Option<String>,
+    pub content: Option<String>,
+    pub provider: Option<String>,
+    pub request_id: Option<String>,
+    pub partial: bool,
+    pub confidence: Option<f32>,
+    pub usage: Option<Usage>,
+    pub details: Option<String>,
+}
+
+impl From<lib::TranslateResult> for TranslateResult {
+    fn from(result: lib::TranslateResult) -> Self {
+        let details = (result.glossary.is_some() || result.quality.is_some() || !result.alternatives.is_empty())
+            .then(|| {
+                serde_json::json!({
+                    "glossary": result.glossary,
+                    "quality": result.quality,
+                    "alternatives": result.alternatives,
+                })
+                .to_string()
+            });
+
+        TranslateResult {
+            reasoning: result.reasoning,
+            content: result.content,
+            provider: result.provider,
+            request_id: result.request_id,
+            partial: result.partial,
+            confidence: result.confidence,
+            usage: result.usage.map(|usage| Usage {
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                characters: usage.characters,
+                latency_ms: usage.latency_ms,
+                request_id: usage.request_id,
+            }),
+            details,
+        }
+    }
+}
+
+pub struct LanguagePair {
+    pub source: String,
+    pub target: String,
+}
+
+pub struct Capabilities {
+    pub supports_streaming: bool,
+    pub supports_terms: bool,
+    pub max_terms: Option<u64>,
+    pub supports_references: bool,
+    pub max_references: Option<u64>,
+    pub supports_field: bool,
+    pub supports_prompt: bool,
+    pub supports_auto_detect: bool,
+    pub needs_target_language: bool,
+    pub max_input_chars: Option<u64>,
+}
+
+impl From<lib::Capabilities> for Capabilities {
+    fn from(capabilities: lib::Capabilities) -> Self {
+        Capabilities {
+            supports_streaming: capabilities.supports_streaming,
+            supports_terms: capabilities.supports_terms,
+            max_terms: capabilities.max_terms.map(|n| n as u64),
+            supports_references: capabilities.supports_references,
+            max_references: capabilities.max_references.map(|n| n as u64),
+            supports_field: capabilities.supports_field,
+            supports_prompt: capabilities.supports_prompt,
+            supports_auto_detect: capabilities.supports_auto_detect,
+            needs_target_language: capabilities.needs_target_language,
+            max_input_chars: capabilities.max_input_chars.map(|n| n as u64),
+        }
+    }
+}
+
+pub enum TranslateStreamChunk {
+    Start,
+    Delta { result: TranslateResult },
+    End,
+}
+
+impl From<lib::TranslateStreamChunk> for TranslateStreamChunk {
+    fn from(chunk: lib::TranslateStreamChunk) -> Self {
+        match chunk {
+            lib::TranslateStreamChunk::Start => TranslateStreamChunk::Start,
+            lib::TranslateStreamChunk::Delta(result) => TranslateStreamChunk::Delta {
+                result: result.into(),
+            },
+            lib::TranslateStreamChunk::End => TranslateStreamChunk::End,
+        }
+    }
+}
+
+pub trait StreamListener: Send + Sync {
+    fn on_chunk(&self, chunk: TranslateStreamChunk) -> bool;
+}
+
+fn parse_config(config: &str) -> Result<Value, TranslateError> {
+    serde_json::from_str(config).map_err(TranslateError::config)
+}
+
+/// 列出目录中的插件名称
+pub fn list_plugins(root: String) -> Result<Vec<String>, TranslateError> {
+    Ok(PluginRegistry::scan(root).map_err(TranslateError::config)?.names())
+}
+
+pub struct Translator {
+    inner: Arc<dyn DynTranslator>,
+}
+
+impl Translator {
+    pub fn load(path: String, config: String) -> Result<Self, TranslateError> {
+        let config = parse_config(&config)?;
+        let translator = RUNTIME
+            .block_on(ProxyTranslator::load(path, config))
+            .map_err(TranslateError::config)?;
+
+        Ok(Translator::wrap(Box::new(translator)))
+    }
+
+    pub fn from_registry(root: String, name: String, config: String) -> Result<Self, TranslateError> {
+        let config = parse_config(&config)?;
+        let registry = PluginRegistry::scan(root).map_err(TranslateError::config)?;
+        let translator = RUNTIME
+            .block_on(registry.create(&name, config))
+            .map_err(TranslateError::config)?;
+
+        Ok(Translator::wrap(translator))
+    }
+
+    fn wrap(translator: lib::BoxedTranslator) -> Self {
+        Translator {
+            inner: Arc::from(translator),
+        }
+    }
+
+    pub fn get_supported_input_languages(&self) -> Result<Vec<String>, TranslateError> {
+        Ok(self.inner.get_supported_input_languages()?)
+    }
+
+    pub fn get_supported_output_languages(&self) -> Result<Vec<String>, TranslateError> {
+        Ok(self.inner.get_supported_output_languages()?)
+    }
+
+    pub fn get_supported_pairs(&self) -> Result<Vec<LanguagePair>, TranslateError> {
+        Ok(self
+            .inner
+            .get_supported_pairs()?
+            .into_iter()
+            .map(|(source, target)| LanguagePair { source, target })
+            .collect())
+    }
+
+    pub fn is_supported_pair(&self, source: String, target: String) -> Result<bool, TranslateError> {
+        Ok(self.inner.is_supported_pair(source, target)?)
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities().into()
+    }
+
+    pub async fn translate(&self, task: TranslateTask) -> Result<TranslateResult, TranslateError> {
+        let task = task.try_into()?;
+        let translator = self.inner.clone();
+        let result = RUNTIME
+            .spawn(async move { translator.translate(task).await })
+            .await
+            .map_err(|e| TranslateError::Other { message: e.to_string() })??;
+
+        Ok(result.into())
+    }
+
+    /// `listener` 返回 false 后不再转发增量，等待插件结束后正常返回
+    pub async fn translate_stream(
+        &self,
+        task: TranslateTask,
+        listener: Box<dyn StreamListener>,
+    ) -> Result<(), TranslateError> {
+        let task = task.try_into()?;
+        let translator = self.inner.clone();
+        let (sender, mut receiver) = channel(32);
+        let stream = RUNTIME.spawn(async move { translator.translate_stream(task, sender).await });
+
+        while let Some(chunk) = receiver.recv().await {
+            if !listener.on_chunk(chunk.into()) {
+                drop(receiver);
+                let _ = stream.await;
+                return Ok(());
+            }
+        }
+
+        stream
+            .await
+            .map_err(|e| TranslateError::Other { message: e.to_string() })??;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn dryrun() -> Translator {
+    Translator::wrap(Box::new(plugin_dryrun::translator::DryRunTranslator::default()))
+}
+
+#[cfg(test)]
+fn task(content: &str) -> TranslateTask {
+    TranslateTask {
+        id: "1".to_string(),
+        content: content.to_string(),
+        source_language: None,
+        target_language: Some("zh-CN".to_string()),
+        user_prompt: None,
+        system_prompt: None,
+        field: None,
+        terms: vec![],
+        references: vec![],
+        extra: None,
+        request_id: Some("req-1".to_string()),
+        deadline_ms: None,
+    }
+}
+
+#[test]
+fn test_translate() -> anyhow::Result<()> {
+    let translator = dryrun();
+    assert!(translator.capabilities().supports_streaming);
+    assert_eq!(translator.get_supported_input_languages()?, vec!["*"]);
+
+    // 在运行时之外等待，与绑定的调用方相同
+    let result = futures_executor::block_on(translator.translate(task("Hello")))?;
+    assert_eq!(result.content.as_deref(), Some("[zh-CN] Hello"));
+    assert_eq!(result.request_id.as_deref(), Some("req-1"));
+    assert_eq!(result.usage.map(|u| u.characters), Some(5));
+
+    let mut invalid = task("Hello");
+    invalid.extra = Some("{".to_string());
+    assert!(matches!(
+        futures_executor::block_on(translator.translate(invalid)),
+        Err(TranslateError::Other { .. })
+    ));
+
+    Ok(())
+}
+
+/// 记录收到的增量，收到 `limit` 个后停止
+#[cfg(test)]
+struct Collect {
+    chunks: Arc<std::sync::Mutex<Vec<String>>>,
+    limit: usize,
+}
+
+#[cfg(test)]
+impl StreamListener for Collect {
+    fn on_chunk(&self, chunk: TranslateStreamChunk) -> bool {
+        let mut chunks = self.chunks.lock().unwrap();
+        chunks.push(match chunk {
+            TranslateStreamChunk::Start => "start".to_string(),
+            TranslateStreamChunk::Delta { result } => result.content.unwrap_or_default(),
+            TranslateStreamChunk::End => "end".to_string(),
+        });
+        chunks.len() < self.limit
+    }
+}
+
+#[test]
+fn test_translate_stream() -> anyhow::Result<()> {
+    let translator = dryrun();
+    for (limit, expected) in [
+        (usize::MAX, vec!["start", "[zh-CN] ", "Hello ", "world", "", "end"]),
+        // 停止接收后正常返回
+        (2, vec!["start", "[zh-CN] "]),
+    ] {
+        let chunks = Arc::new(std::sync::Mutex::new(vec![]));
+        let listener = Collect {
+            chunks: chunks.clone(),
+            limit,
+        };
+        futures_executor::block_on(translator.translate_stream(task("Hello world"), Box::new(listener)))?;
+        assert_eq!(*chunks.lock().unwrap(), expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    let err: TranslateError = anyhow::anyhow!(XTranslateError::Network("refused".to_string())).into();
+    assert!(matches!(err, TranslateError::Provider { retryable: true, .. }));
+    let err: TranslateError = anyhow::anyhow!(XTranslateError::InvalidConfig("model".to_string())).into();
+    assert!(matches!(err, TranslateError::Config { .. }));
+
+    assert!(matches!(
+        Translator::load("/nonexistent/plugin.so".to_string(), "{}".to_string()),
+        Err(TranslateError::Config { .. })
+    ));
+    assert!(matches!(
+        Translator::load(String::new(), "{".to_string()),
+        Err(TranslateError::Config { .. })
+    ));
+}
@@ -0,0 +1,318 @@
+use anyhow::{anyhow, bail, Result};
+use proc_macro2::{Delimiter, Group, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::path::{Path, PathBuf};
+use syn::visit::Visit;
+use syn::{Expr, FnArg, ItemFn, Lit, Macro, Meta, Pat, ReturnType, Signature, Type};
+
+/// 仓库中的构建任务，以 `cargo xtask <任务>` 运行：
+///   header          重新生成 include/xtranslator_plugin.h
+///   header --check  检查头文件是否与 lib/src/ffi.rs、`build_ffi!` 一致
+const HEADER: &str = "include/xtranslator_plugin.h";
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["header"] => Ok(std::fs::write(root().join(HEADER), generate_header()?)?),
+        ["header", "--check"] => check_header(),
+        _ => bail!("usage: cargo xtask header [--check]"),
+    }
+}
+
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap().to_path_buf()
+}
+
+fn check_header() -> Result<()> {
+    let current = std::fs::read_to_string(root().join(HEADER))?;
+    if current != generate_header()? {
+        bail!("{} is out of date, run `cargo xtask header`", HEADER);
+    }
+    Ok(())
+}
+
+/// 结构体与函数指针类型由 cbindgen 根据 lib 生成，插件导出的函数由 `build_ffi!` 生成，
+/// cbindgen 无法展开宏，从宏的源码中读取后写入 trailer
+fn generate_header() -> Result<String> {
+    let root = root();
+    let exports = read_exports(&std::fs::read_to_string(root.join("macros/src/lib.rs"))?)?;
+
+    let mut config = cbindgen::Config::from_file(root.join("cbindgen.toml")).map_err(|e| anyhow!(e))?;
+    config.trailer = Some(trailer(&exports)?);
+    // cbindgen 把 trailer 写在 include guard 之外，由这里包上
+    let comment = config.header.take().unwrap_or_default();
+    let guard = config.include_guard.take().ok_or_else(|| anyhow!("include_guard is required"))?;
+
+    let mut body = Vec::new();
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_crate(root.join("lib"))
+        .generate()?
+        .write(&mut body);
+    let body = String::from_utf8(body)?;
+    Ok(format!(
+        "{}\n\n#ifndef {guard}\n#define {guard}\n\n{}\n\n#endif  /* {guard} */\n",
+        comment,
+        body.trim_end()
+    ))
+}
+
+/// `build_ffi!` 导出的函数
+struct Export {
+    docs: Vec<String>,
+    signature: Signature,
+}
+
+/// `build_ffi` 中的 `quote!`
+#[derive(Default)]
+struct QuoteFinder {
+    in_build_ffi: bool,
+    tokens: Option<TokenStream>,
+}
+
+impl Visit<'_> for QuoteFinder {
+    fn visit_item_fn(&mut self, item: &ItemFn) {
+        self.in_build_ffi = item.sig.ident == "build_ffi";
+        syn::visit::visit_item_fn(self, item);
+        self.in_build_ffi = false;
+    }
+
+    fn visit_macro(&mut self, mac: &Macro) {
+        if self.in_build_ffi && mac.path.is_ident("quote") {
+            self.tokens = Some(mac.tokens.clone());
+        }
+    }
+}
+
+/// 与 `prefix_exports` 相同，取顶层带 `#[no_mangle]` 的函数及其文档注释
+fn read_exports(source: &str) -> Result<Vec<Export>> {
+    let mut finder = QuoteFinder::default();
+    finder.visit_file(&syn::parse_file(source)?);
+    let tokens: Vec<TokenTree> = finder
+        .tokens
+        .ok_or_else(|| anyhow!("quote! not found in build_ffi"))?
+        .into_iter()
+        .collect();
+
+    let mut exports = Vec::new();
+    let mut docs = Vec::new();
+    let mut no_mangle = false;
+    // 当前项在属性之后的起始位置
+    let mut start = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        match (&tokens[i], tokens.get(i + 1)) {
+            (TokenTree::Punct(p), Some(TokenTree::Group(attr)))
+                if p.as_char() == '#' && attr.delimiter() == Delimiter::Bracket =>
+            {
+                match syn::parse2::<Meta>(attr.stream())? {
+                    Meta::Path(path) if path.is_ident("no_mangle") => no_mangle = true,
+                    Meta::NameValue(doc) if doc.path.is_ident("doc") => {
+                        if let Expr::Lit(lit) = &doc.value {
+                            if let Lit::Str(s) = &lit.lit {
+                                let line = s.value();
+                                docs.push(line.strip_prefix(' ').unwrap_or(&line).to_string());
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                i += 2;
+                start = i;
+                continue;
+            }
+            (TokenTree::Group(body), _) if body.delimiter() == Delimiter::Brace => {
+                if no_mangle {
+                    // 函数体中可能有插值，只解析签名
+                    let mut item: Vec<TokenTree> = tokens[start..i].to_vec();
+                    item.push(TokenTree::Group(Group::new(Delimiter::Brace, TokenStream::new())));
+                    let item: ItemFn = syn::parse2(item.into_iter().collect())?;
+                    exports.push(Export {
+                        docs: std::mem::take(&mut docs),
+                        signature: item.sig,
+                    });
+                }
+                docs.clear();
+                no_mangle = false;
+                start = i + 1;
+            }
+            (TokenTree::Punct(p), _) if p.as_char() == ';' => {
+                docs.clear();
+                no_mangle = false;
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if exports.is_empty() {
+        bail!("no exported function found in build_ffi");
+    }
+    Ok(exports)
+}
+
+/// Rust 类型的名称，用于 cbindgen 单态化后的结构体名，如 `FfiResult_c_char`
+fn rust_name(ty: &Type) -> Result<String> {
+    let Type::Path(path) = ty else {
+        bail!("unsupported type: {}", quote_type(ty));
+    };
+    let name = path.path.segments.last().unwrap().ident.to_string();
+    // 句柄指向装箱的 `BoxedTranslator`，C 中为不透明的 `TranslatorHandle`
+    Ok(if name == "BoxedTranslator" { "TranslatorHandle".to_string() } else { name })
+}
+
+/// Rust 类型对应的 C 类型，分为基础类型与指针层数，如 `*mut *const c_char` 为 `("const char", 2)`
+fn c_type(ty: &Type) -> Result<(String, usize)> {
+    match ty {
+        Type::Ptr(ptr) => {
+            let (base, depth) = c_type(&ptr.elem)?;
+            if depth == 0 && ptr.const_token.is_some() {
+                Ok((format!("const {}", base), 1))
+            } else if depth > 0 && ptr.const_token.is_some() {
+                bail!("unsupported type: {}", quote_type(ty));
+            } else {
+                Ok((base, depth + 1))
+            }
+        }
+        Type::Tuple(tuple) if tuple.elems.is_empty() => Ok(("void".to_string(), 0)),
+        Type::Path(path) => {
+            let segment = path.path.segments.last().unwrap();
+            let arg = match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                    Some(syn::GenericArgument::Type(arg)) => Some(arg),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let name = segment.ident.to_string();
+            let base = match (name.as_str(), arg) {
+                // 可为空的函数指针
+                ("Option", Some(arg)) => return c_type(arg),
+                ("FfiResult", Some(arg)) => format!("FfiResult_{}", rust_name(arg)?),
+                ("c_char", None) => "char".to_string(),
+                ("c_void", None) => "void".to_string(),
+                ("usize", None) => "size_t".to_string(),
+                ("bool", None) => "bool".to_string(),
+                ("u8" | "u16" | "u32" | "u64", None) => format!("uint{}_t", &name[1..]),
+                ("i8" | "i16" | "i32" | "i64", None) => format!("int{}_t", &name[1..]),
+                (_, None) => rust_name(ty)?,
+                _ => bail!("unsupported type: {}", quote_type(ty)),
+            };
+            Ok((base, 0))
+        }
+        _ => bail!("unsupported type: {}", quote_type(ty)),
+    }
+}
+
+fn quote_type(ty: &Type) -> String {
+    ty.to_token_stream().to_string()
+}
+
+fn c_declarator((base, depth): (String, usize), name: &str) -> String {
+    format!("{} {}{}", base, "*".repeat(depth), name)
+}
+
+/// 函数名写为 `XTRANSLATOR_FN(name)`，以便按插件的前缀声明
+fn declaration(export: &Export) -> Result<String> {
+    let signature = &export.signature;
+    let output = match &signature.output {
+        ReturnType::Default => ("void".to_string(), 0),
+        ReturnType::Type(_, ty) => c_type(ty)?,
+    };
+
+    let params = signature
+        .inputs
+        .iter()
+        .map(|arg| {
+            let FnArg::Typed(arg) = arg else {
+                bail!("unexpected receiver in {}", signature.ident);
+            };
+            let Pat::Ident(name) = &*arg.pat else {
+                bail!("unsupported parameter pattern in {}", signature.ident);
+            };
+            Ok(c_declarator(c_type(&arg.ty)?, &name.ident.to_string()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let params = if params.is_empty() { "void".to_string() } else { params.join(", ") };
+
+    let mut declaration = String::new();
+    if !export.docs.is_empty() {
+        declaration.push_str("/**\n");
+        for line in &export.docs {
+            declaration.push_str(format!(" * {}", line).trim_end());
+            declaration.push('\n');
+        }
+        declaration.push_str(" */\n");
+    }
+    let name = format!("XTRANSLATOR_FN({})", signature.ident);
+    declaration.push_str(&format!("{}({});\n", c_declarator(output, &name), params));
+    Ok(declaration)
+}
+
+fn trailer(exports: &[Export]) -> Result<String> {
+    let mut trailer = String::from(
+        r#"/* `build_ffi!` 指定了 `prefix` 时，包含本文件前把 XTRANSLATOR_PREFIX 定义为同一前缀 */
+#ifndef XTRANSLATOR_PREFIX
+#define XTRANSLATOR_PREFIX
+#endif
+#define XTRANSLATOR_CONCAT_(a, b) a##b
+#define XTRANSLATOR_CONCAT(a, b) XTRANSLATOR_CONCAT_(a, b)
+#define XTRANSLATOR_FN(name) XTRANSLATOR_CONCAT(XTRANSLATOR_PREFIX, name)
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+"#,
+    );
+    for export in exports {
+        trailer.push('\n');
+        trailer.push_str(&declaration(export)?);
+    }
+    trailer.push_str(
+        r#"
+#ifdef __cplusplus
+}
+#endif
+"#,
+    );
+    Ok(trailer)
+}
+
+#[test]
+fn test_declaration() -> Result<()> {
+    let source = r#"
+        fn build_ffi(input: TokenStream) -> TokenStream {
+            quote! {
+                fn helper() {}
+
+                /// 返回名称
+                #[no_mangle]
+                pub extern "C" fn get_name(array: *mut *mut *const c_char, len: *mut usize) -> *mut FfiResult<BoxedTranslator> {
+                    #name
+                }
+
+                #[no_mangle]
+                pub extern "C" fn set_callback(callback: Option<lib::ffi::LogCallback>, user_data: *mut c_void) {}
+            }
+        }
+    "#;
+    let exports = read_exports(source)?;
+    assert_eq!(exports.len(), 2);
+    assert_eq!(
+        declaration(&exports[0])?,
+        "/**\n * 返回名称\n */\nFfiResult_TranslatorHandle *XTRANSLATOR_FN(get_name)(const char ***array, size_t *len);\n"
+    );
+    assert_eq!(
+        declaration(&exports[1])?,
+        "void XTRANSLATOR_FN(set_callback)(LogCallback callback, void *user_data);\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_header_is_current() -> Result<()> {
+    check_header()
+}